@@ -0,0 +1,136 @@
+import { ReactNode, StrictMode, useEffect, useState } from "react"
+import ReactDOM from "react-dom/client"
+import { ThemeProvider } from "@/Theme"
+import { SettingsProvider } from "@/contexts"
+import { Box, Code, Heading, Stack, Table, Tbody, Td, Text, Th, Thead, Tr } from "@chakra-ui/react"
+import { client } from "@/client"
+import { Activity, DebugSnapshot } from "@/gen"
+
+ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(<Root />)
+
+function Root() {
+  return (
+    <StrictMode>
+      <SettingsProvider>
+        <ThemeProvider>
+          <DebugConsole />
+        </ThemeProvider>
+      </SettingsProvider>
+    </StrictMode>
+  )
+}
+
+function DebugConsole() {
+  const [snapshot, setSnapshot] = useState<DebugSnapshot | null>(null)
+
+  useEffect(() => {
+    client.getDebugSnapshot().then((result) => {
+      if (result.ok) {
+        setSnapshot(result.val)
+      }
+    })
+
+    const unsubscribe = client.subscribe("debug_console_snapshot", setSnapshot)
+
+    return () => {
+      unsubscribe.then((u) => u())
+    }
+  }, [])
+
+  if (snapshot === null) {
+    return <Text padding="4">Waiting for backend...</Text>
+  }
+
+  const now = new Date(snapshot.timestamp).getTime()
+
+  return (
+    <Stack padding="4" spacing="6" width="100vw" height="100vh" overflowY="auto">
+      <Section title="Background Tasks">
+        <ActivityTable activities={snapshot.tasks} now={now} />
+      </Section>
+      <Section title="Subprocesses">
+        <ActivityTable activities={snapshot.processes} now={now} />
+      </Section>
+      <Section title="Queues">
+        <Table size="sm">
+          <Thead>
+            <Tr>
+              <Th>Name</Th>
+              <Th isNumeric>Length</Th>
+              <Th isNumeric>Capacity</Th>
+            </Tr>
+          </Thead>
+          <Tbody>
+            {snapshot.queues.map((queue) => (
+              <Tr key={queue.name}>
+                <Td>{queue.name}</Td>
+                <Td isNumeric>{queue.len}</Td>
+                <Td isNumeric>{queue.capacity ?? "-"}</Td>
+              </Tr>
+            ))}
+          </Tbody>
+        </Table>
+      </Section>
+      <Section title="Active Actions">
+        {snapshot.activeActions.length === 0 ? (
+          <Text fontSize="sm">None</Text>
+        ) : (
+          snapshot.activeActions.map((action) => (
+            <Code key={action} width="fit-content">
+              {action}
+            </Code>
+          ))
+        )}
+      </Section>
+      <Section title="Recent UI Messages">
+        {[...snapshot.uiMessages].reverse().map(({ timestamp, message }, i) => (
+          <Code key={i} whiteSpace="pre-wrap" fontSize="xs">
+            {new Date(timestamp).toLocaleTimeString()} {JSON.stringify(message)}
+          </Code>
+        ))}
+      </Section>
+    </Stack>
+  )
+}
+
+function Section({ title, children }: Readonly<{ title: string; children: ReactNode }>) {
+  return (
+    <Box>
+      <Heading size="sm" marginBottom="2">
+        {title}
+      </Heading>
+      <Stack spacing="1">{children}</Stack>
+    </Box>
+  )
+}
+
+function ActivityTable({ activities, now }: Readonly<{ activities: Activity[]; now: number }>) {
+  if (activities.length === 0) {
+    return <Text fontSize="sm">None</Text>
+  }
+
+  return (
+    <Table size="sm">
+      <Thead>
+        <Tr>
+          <Th>Name</Th>
+          <Th>Detail</Th>
+          <Th isNumeric>Running for</Th>
+        </Tr>
+      </Thead>
+      <Tbody>
+        {activities.map((activity, i) => (
+          <Tr key={i}>
+            <Td>{activity.name}</Td>
+            <Td>
+              <Code fontSize="xs">{activity.detail ?? ""}</Code>
+            </Td>
+            <Td isNumeric>
+              {Math.max(0, Math.round((now - new Date(activity.startedAt).getTime()) / 1000))}s
+            </Td>
+          </Tr>
+        ))}
+      </Tbody>
+    </Table>
+  )
+}
@@ -0,0 +1,130 @@
+import { StrictMode, useCallback, useEffect, useState } from "react"
+import ReactDOM from "react-dom/client"
+import { ThemeProvider } from "@/Theme"
+import { SettingsProvider } from "@/contexts"
+import { Code, HStack, Input, Select, Stack, Text } from "@chakra-ui/react"
+import { client } from "@/client"
+import { LogLevel, LogLine, LogStreamFilter, LogWindow } from "@/gen"
+
+ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(<Root />)
+
+// Older lines are dropped so that long running builds don't slow the window down
+const MAX_LINES = 5000
+const LEVELS: readonly LogLevel[] = ["error", "warn", "info", "debug", "trace"]
+
+function Root() {
+  return (
+    <StrictMode>
+      <SettingsProvider>
+        <ThemeProvider>
+          <Logs />
+        </ThemeProvider>
+      </SettingsProvider>
+    </StrictMode>
+  )
+}
+
+function Logs() {
+  const [logWindow, setLogWindow] = useState<LogWindow | null>(null)
+  const [error, setError] = useState<string | null>(null)
+  const [lines, setLines] = useState<readonly LogLine[]>([])
+
+  useEffect(() => {
+    client.getLogWindow().then((result) => {
+      if (result.ok) {
+        setLogWindow(result.val)
+      } else {
+        setError(result.val.message)
+      }
+    })
+  }, [])
+
+  const label = logWindow?.label
+  useEffect(() => {
+    if (label === undefined) {
+      return
+    }
+
+    const unsubscribe = client.subscribe("log_stream", (event) => {
+      if (event.streamId === label) {
+        setLines((current) => [...current, ...event.lines].slice(-MAX_LINES))
+      }
+    })
+
+    return () => {
+      unsubscribe.then((u) => u())
+    }
+  }, [label])
+
+  // Restarting the stream replaces the previous one and resends recent lines with the new filter
+  const filterKey = JSON.stringify(logWindow?.filter)
+  useEffect(() => {
+    if (logWindow === null) {
+      return
+    }
+
+    setLines([])
+    client.streamLogs(logWindow.label, logWindow.filter, logWindow.workspaceId)
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [logWindow?.label, filterKey])
+
+  const handleFilterChanged = useCallback(
+    (changes: Partial<LogStreamFilter>) => {
+      if (logWindow === null) {
+        return
+      }
+
+      const filter = { ...logWindow.filter, ...changes }
+      client.setLogWindowFilter(filter)
+      setLogWindow({ ...logWindow, filter })
+    },
+    [logWindow]
+  )
+
+  if (error !== null) {
+    return <Text padding="4">{error}</Text>
+  }
+  if (logWindow === null) {
+    return <Text padding="4">Waiting for backend...</Text>
+  }
+
+  return (
+    <Stack padding="4" spacing="4" width="100vw" height="100vh">
+      <HStack>
+        <Select
+          size="sm"
+          width="40"
+          value={logWindow.filter.level ?? ""}
+          onChange={(e) =>
+            handleFilterChanged({
+              level: e.target.value === "" ? null : (e.target.value as LogLevel),
+            })
+          }>
+          <option value="">All levels</option>
+          {LEVELS.map((level) => (
+            <option key={level} value={level}>
+              {level}
+            </option>
+          ))}
+        </Select>
+        <Input
+          size="sm"
+          placeholder="Filter"
+          value={logWindow.filter.text ?? ""}
+          onChange={(e) =>
+            handleFilterChanged({ text: e.target.value === "" ? null : e.target.value })
+          }
+        />
+      </HStack>
+      <Stack spacing="0" overflowY="auto" flexGrow={1}>
+        {lines.map((line, i) => (
+          <Code key={i} fontSize="xs" whiteSpace="pre-wrap" backgroundColor="transparent">
+            {line.timestamp !== null && `${line.timestamp} `}
+            {line.level !== null && `${line.level.toUpperCase()} `}
+            {line.message}
+          </Code>
+        ))}
+      </Stack>
+    </Stack>
+  )
+}
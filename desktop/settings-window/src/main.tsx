@@ -0,0 +1,109 @@
+import { StrictMode, useEffect, useRef } from "react"
+import ReactDOM from "react-dom/client"
+import { QueryClient, QueryClientProvider } from "@tanstack/react-query"
+import { Navigate, Outlet, RouterProvider, createMemoryRouter, useNavigate } from "react-router-dom"
+import { Box, Grid, GridItem, useColorModeValue } from "@chakra-ui/react"
+import { ThemeProvider } from "@/Theme"
+import { DevPodProvider, SettingsProvider, ToolbarProvider } from "@/contexts"
+import { Sidebar, SidebarMenuItem, Toolbar } from "@/components"
+import { SIDEBAR_WIDTH } from "@/constants"
+import { Cog, Stack3D } from "@/icons"
+import { client } from "@/client"
+import { Routes } from "@/routes"
+import { ListProviders, Provider, Providers, Settings } from "@/views"
+
+const queryClient = new QueryClient()
+
+const router = createMemoryRouter(
+  [
+    {
+      path: Routes.ROOT,
+      element: <SettingsWindow />,
+      children: [
+        { index: true, element: <Navigate to={Routes.SETTINGS} replace /> },
+        { path: Routes.SETTINGS, element: <Settings /> },
+        {
+          path: Routes.PROVIDERS,
+          element: <Providers />,
+          children: [
+            { index: true, element: <ListProviders /> },
+            { path: Routes.PROVIDER, element: <Provider /> },
+          ],
+        },
+        // Main window routes, e.g. the one after setting up a provider, stay on the providers
+        { path: "*", element: <Navigate to={Routes.PROVIDERS} replace /> },
+      ],
+    },
+  ],
+  { initialEntries: [Routes.SETTINGS] }
+)
+
+ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(<Root />)
+
+function Root() {
+  return (
+    <StrictMode>
+      <SettingsProvider>
+        <ThemeProvider>
+          <QueryClientProvider client={queryClient}>
+            <DevPodProvider>
+              <RouterProvider router={router} />
+            </DevPodProvider>
+          </QueryClientProvider>
+        </ThemeProvider>
+      </SettingsProvider>
+    </StrictMode>
+  )
+}
+
+function SettingsWindow() {
+  const navigate = useNavigate()
+  const isReadyLockRef = useRef(false)
+  const contentBackgroundColor = useColorModeValue("white", "background.darkest")
+
+  // Messages for this window are held back by the backend until it's listening
+  useEffect(() => {
+    const unsubscribe = client.subscribe("event", (event) => {
+      if (event.type === "ShowSettings") {
+        navigate(event.provider !== null ? Routes.toProvider(event.provider) : Routes.SETTINGS)
+      }
+    })
+    if (!isReadyLockRef.current) {
+      isReadyLockRef.current = true
+      unsubscribe.then(() => client.ready())
+    }
+
+    return () => {
+      unsubscribe.then((u) => u())
+    }
+  }, [navigate])
+
+  return (
+    <Grid
+      height="100vh"
+      width="100vw"
+      templateAreas={`"sidebar main"`}
+      gridTemplateColumns={`${SIDEBAR_WIDTH} 1fr`}>
+      <GridItem area="sidebar">
+        <Sidebar>
+          <SidebarMenuItem to={Routes.PROVIDERS} icon={<Stack3D />}>
+            Providers
+          </SidebarMenuItem>
+          <SidebarMenuItem to={Routes.SETTINGS} icon={<Cog />}>
+            Settings
+          </SidebarMenuItem>
+        </Sidebar>
+      </GridItem>
+      <GridItem area="main" height="100vh" overflowX="auto">
+        <ToolbarProvider>
+          <Box backgroundColor={contentBackgroundColor} width="full" height="full">
+            <Toolbar backgroundColor={contentBackgroundColor} height="20" width="full" />
+            <Box as="main" padding="8" width="full" height="calc(100vh - 5rem)" overflowY="auto">
+              <Outlet />
+            </Box>
+          </Box>
+        </ToolbarProvider>
+      </GridItem>
+    </Grid>
+  )
+}
@@ -0,0 +1,72 @@
+import { StrictMode, useEffect, useState } from "react"
+import ReactDOM from "react-dom/client"
+import { ThemeProvider } from "@/Theme"
+import { SettingsProvider } from "@/contexts"
+import { Button, ButtonGroup, Code, Heading, Stack, Text } from "@chakra-ui/react"
+import { client } from "@/client"
+import { ShutdownPhase, ShutdownProgress } from "@/gen"
+
+ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(<Root />)
+
+const PHASE_LABELS: Record<ShutdownPhase, string> = {
+  draining: "Waiting for running work to finish",
+  terminating: "Stopping background processes",
+  flushing: "Saving pending data",
+}
+
+function handleQuitNowClicked() {
+  client.quitNow()
+}
+
+function Root() {
+  return (
+    <StrictMode>
+      <SettingsProvider>
+        <ThemeProvider>
+          <Shutdown />
+        </ThemeProvider>
+      </SettingsProvider>
+    </StrictMode>
+  )
+}
+
+function Shutdown() {
+  const [progress, setProgress] = useState<ShutdownProgress | null>(null)
+
+  useEffect(() => {
+    const unsubscribe = client.subscribe("shutdown_progress", setProgress)
+
+    return () => {
+      unsubscribe.then((u) => u())
+    }
+  }, [])
+
+  return (
+    <Stack padding="4" spacing="4" width="100vw" height="100vh" overflowY="auto">
+      <Heading size="md">Quitting DevPod</Heading>
+      <Text fontSize="md">{PHASE_LABELS[progress?.phase ?? "draining"]}</Text>
+      {progress?.phase === "draining" && (
+        <>
+          <Stack spacing="1">
+            {progress.activeActions.map((action) => (
+              <Code key={action} width="fit-content">
+                {action}
+              </Code>
+            ))}
+            {progress.processes.map((process) => (
+              <Code key={process.id} width="fit-content" fontSize="xs">
+                {process.program} {process.args}
+              </Code>
+            ))}
+          </Stack>
+          <Text fontSize="sm">Cancelling in {Math.ceil(progress.remainingMs / 1000)}s</Text>
+          <ButtonGroup justifyContent="end">
+            <Button variant="primary" onClick={handleQuitNowClicked}>
+              Quit now
+            </Button>
+          </ButtonGroup>
+        </>
+      )}
+    </Stack>
+  )
+}
@@ -1,16 +1,28 @@
-use crate::AppHandle;
+use crate::{
+    power::{self, SleepGuard},
+    redaction, AppHandle, AppState,
+};
 use anyhow::Context;
+use lazy_static::lazy_static;
 use log::info;
 use std::{
-    fs::{self, OpenOptions},
-    io::Write,
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
     path::PathBuf,
+    sync::Mutex,
     time::{Duration, SystemTime},
 };
 use thiserror::Error;
 
 const ACTION_LOGS_DIR: &str = "action_logs";
 const ONE_DAY: Duration = Duration::new(60 * 60 * 24, 0);
+// Enough for the last few lines of a log without reading all of it
+const TAIL_BYTES: u64 = 8 * 1024;
+
+lazy_static! {
+    static ref ACTIONS_SLEEP_GUARD: Mutex<Option<SleepGuard>> = Mutex::new(None);
+}
 
 #[derive(Error, Debug)]
 pub enum ActionLogError {
@@ -38,8 +50,13 @@ pub fn write_action_log(
     action_id: String,
     data: String,
 ) -> Result<(), ActionLogError> {
-    let mut path = get_actions_dir(&app_handle).map_err(|_| ActionLogError::NoDir)?;
-    path.push(format!("{}.log", &action_id));
+    append(&app_handle, &action_id, &data)
+}
+
+/// Adds a serialized stream event to the log of the action.
+pub fn append(app_handle: &AppHandle, action_id: &str, data: &str) -> Result<(), ActionLogError> {
+    let mut path = get_actions_dir(app_handle).map_err(|_| ActionLogError::NoDir)?;
+    path.push(format!("{}.log", action_id));
 
     let mut file = OpenOptions::new()
         .create(true)
@@ -47,12 +64,31 @@ pub fn write_action_log(
         .open(path)
         .map_err(ActionLogError::FileOpen)?;
 
-    file.write_all(format!("{}\n", data).as_bytes())
+    file.write_all(format!("{}\n", redaction::redact(data)).as_bytes())
         .map_err(ActionLogError::Write)?;
 
     Ok(())
 }
 
+/// The latest complete line of the action's log, `None` if it hasn't logged anything yet.
+pub fn last_line(app_handle: &AppHandle, action_id: &str) -> Option<String> {
+    let mut path = get_actions_dir(app_handle).ok()?;
+    path.push(format!("{}.log", action_id));
+
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES)))
+        .ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+
+    String::from_utf8_lossy(&tail)
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .map(String::from)
+}
+
 #[tauri::command]
 pub fn get_action_logs(
     app_handle: AppHandle,
@@ -104,6 +140,32 @@ pub fn sync_action_logs(app_handle: AppHandle, actions: Vec<String>) -> Result<(
     Ok(())
 }
 
+/// Keeps track of the actions the UI is currently running, i.e. workspace builds.
+#[tauri::command]
+pub fn sync_active_actions(
+    state: tauri::State<'_, AppState>,
+    actions: Vec<String>,
+) -> Result<(), ActionLogError> {
+    let actions = actions.into_iter().collect::<HashSet<_>>();
+    let mut active_actions = state.active_actions.lock().unwrap();
+    for started in actions.difference(&active_actions) {
+        info!(target: module_path!(), operation_id = started.as_str(); "Action started");
+    }
+    for finished in active_actions.difference(&actions) {
+        info!(target: module_path!(), operation_id = finished.as_str(); "Action finished");
+    }
+    // Keep the machine awake while workspaces are being built
+    let mut sleep_guard = ACTIONS_SLEEP_GUARD.lock().unwrap();
+    if actions.is_empty() {
+        *sleep_guard = None;
+    } else if sleep_guard.is_none() {
+        *sleep_guard = Some(power::prevent_sleep("workspace actions are running"));
+    }
+    *active_actions = actions;
+
+    Ok(())
+}
+
 pub fn setup(app_handle: &AppHandle) -> anyhow::Result<()> {
     let dir_path = get_actions_dir(app_handle)?;
     let _ = fs::create_dir_all(dir_path); // Make sure we have the action logs dir
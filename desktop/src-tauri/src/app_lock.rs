@@ -0,0 +1,269 @@
+use crate::{
+    audit_log::{self, AuditCategory, AuditInitiator},
+    keychain::{self, KeychainError},
+};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+// Kept in the keychain instead of the settings, the UI is able to write the settings file itself
+const LOCK_SERVICE: &str = "sh.loft.devpod.app-lock";
+const LOCK_ACCOUNT: &str = "enabled";
+// Deleting a couple of workspaces in a row only asks once
+const UNLOCK_DURATION: Duration = Duration::from_secs(60);
+
+/// Commands that destroy data or credentials, with what the user is told they're about to do.
+const PROTECTED_COMMANDS: &[(&str, &str)] = &[
+    ("delete_workspace", "delete a workspace"),
+    ("delete_provider", "remove a provider"),
+    ("delete_pro_instance", "remove a Pro instance"),
+    // Rolling back deletes what the operation has created so far
+    ("roll_back_operation", "roll back an operation"),
+    ("remove_ssh_key", "remove an SSH key"),
+    ("delete_secret", "delete a stored secret"),
+    ("delete_git_credential", "remove a git credential"),
+    ("export_ssh_public_key", "export an SSH key"),
+    ("prune_docker", "remove unused Docker data"),
+    ("uninstall_cli", "uninstall the CLI"),
+    // Otherwise the lock could simply be turned off
+    ("set_app_lock", "change the app lock"),
+];
+
+lazy_static! {
+    static ref ENABLED: Mutex<Option<bool>> = Mutex::new(None);
+    // Held while the user authenticates, so that concurrent commands don't ask twice
+    static ref UNLOCKED_UNTIL: Mutex<Option<Instant>> = Mutex::new(None);
+}
+
+#[derive(Error, Debug)]
+pub enum AppLockError {
+    #[error("no system authentication is available on this device")]
+    NotSupported,
+    #[error("authentication was cancelled or failed")]
+    Denied,
+    #[error("unable to authenticate: {0}")]
+    Failed(String),
+    #[error("unable to access the app lock state")]
+    Keychain(#[from] KeychainError),
+}
+impl serde::Serialize for AppLockError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// What `command` is about to do if it needs the user to authenticate first.
+pub fn protected_reason(command: &str) -> Option<&'static str> {
+    PROTECTED_COMMANDS
+        .iter()
+        .find(|(protected, _)| *protected == command)
+        .map(|(_, reason)| *reason)
+}
+
+pub fn is_enabled() -> bool {
+    let mut enabled = ENABLED.lock().unwrap();
+    if let Some(enabled) = *enabled {
+        return enabled;
+    }
+
+    match keychain::get_password(LOCK_SERVICE, LOCK_ACCOUNT) {
+        Ok(value) => *enabled.insert(value == "true"),
+        Err(KeychainError::NotFound(..)) => *enabled.insert(false),
+        // Not remembered, the keychain might just be locked right now
+        Err(err) => {
+            warn!(
+                "Failed to read app lock state, treating it as enabled: {}",
+                err
+            );
+            true
+        }
+    }
+}
+
+/// Asks the user to authenticate with the OS unless the lock is off or they did so a moment ago. Blocks until they're
+/// done, never call it on the main thread.
+pub fn ensure_unlocked(reason: &str) -> Result<(), AppLockError> {
+    if !is_enabled() {
+        return Ok(());
+    }
+
+    let mut unlocked_until = UNLOCKED_UNTIL.lock().unwrap();
+    if unlocked_until.is_some_and(|until| Instant::now() < until) {
+        return Ok(());
+    }
+
+    let result = platform::authenticate(reason);
+    info!(
+        target: module_path!(),
+        reason = reason,
+        unlocked = result.is_ok();
+        "App lock authentication"
+    );
+    result?;
+    *unlocked_until = Some(Instant::now() + UNLOCK_DURATION);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_app_lock() -> bool {
+    is_enabled()
+}
+
+/// Turning the lock on makes sure authentication works on this device, turning it off needs the lock to be opened
+/// first.
+#[tauri::command]
+pub fn set_app_lock(enabled: bool) -> Result<bool, AppLockError> {
+    if enabled && !is_enabled() {
+        platform::authenticate("turn on the app lock")?;
+    }
+
+    keychain::set_password(LOCK_SERVICE, LOCK_ACCOUNT, &enabled.to_string())?;
+    *ENABLED.lock().unwrap() = Some(enabled);
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Settings,
+        if enabled {
+            "enable app lock"
+        } else {
+            "disable app lock"
+        },
+        None,
+    );
+
+    Ok(enabled)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::AppLockError;
+    use block::ConcreteBlock;
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::runtime::{BOOL, YES};
+    use std::sync::mpsc;
+
+    #[link(name = "LocalAuthentication", kind = "framework")]
+    extern "C" {}
+
+    // LAPolicyDeviceOwnerAuthentication, Touch ID with the login password as fallback
+    const POLICY: isize = 2;
+
+    pub fn authenticate(reason: &str) -> Result<(), AppLockError> {
+        let (tx, rx) = mpsc::channel();
+
+        unsafe {
+            let context: id = msg_send![class!(LAContext), new];
+            let can_evaluate: BOOL = msg_send![context, canEvaluatePolicy: POLICY error: nil];
+            if can_evaluate != YES {
+                let _: () = msg_send![context, release];
+                return Err(AppLockError::NotSupported);
+            }
+
+            // Shown as `"DevPod" is trying to <reason>.`
+            let reason = NSString::alloc(nil).init_str(reason);
+            let reply = ConcreteBlock::new(move |success: BOOL, _error: id| {
+                let _ = tx.send(success == YES);
+            })
+            .copy();
+            let _: () =
+                msg_send![context, evaluatePolicy: POLICY localizedReason: reason reply: &*reply];
+            let _: () = msg_send![reason, release];
+
+            let verified = rx.recv().unwrap_or(false);
+            let _: () = msg_send![context, release];
+
+            verified.then_some(()).ok_or(AppLockError::Denied)
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::AppLockError;
+    use windows::{
+        core::HSTRING,
+        Security::Credentials::UI::{
+            UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+        },
+    };
+
+    /// Asks for Windows Hello, i.e. the fingerprint, face or PIN of the user.
+    pub fn authenticate(reason: &str) -> Result<(), AppLockError> {
+        let failed = |err: windows::core::Error| AppLockError::Failed(err.to_string());
+
+        let availability = UserConsentVerifier::CheckAvailabilityAsync()
+            .and_then(|operation| operation.get())
+            .map_err(failed)?;
+        if availability != UserConsentVerifierAvailability::Available {
+            return Err(AppLockError::NotSupported);
+        }
+
+        let message = HSTRING::from(format!("DevPod is trying to {}.", reason));
+        let result = UserConsentVerifier::RequestVerificationAsync(&message)
+            .and_then(|operation| operation.get())
+            .map_err(failed)?;
+        match result {
+            UserConsentVerificationResult::Verified => Ok(()),
+            _ => Err(AppLockError::Denied),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::AppLockError;
+    use std::{io, process::Command};
+
+    // Available on every system with polkit, usually requires the password of an administrator
+    const POLKIT_ACTION: &str = "org.freedesktop.policykit.exec";
+
+    /// Asks the polkit agent of the desktop, there's no way to tell it why.
+    pub fn authenticate(_reason: &str) -> Result<(), AppLockError> {
+        let status = Command::new("pkcheck")
+            .args(["--action-id", POLKIT_ACTION, "--process"])
+            .arg(std::process::id().to_string())
+            .arg("--allow-user-interaction")
+            .status();
+
+        match status {
+            Ok(status) if status.success() => Ok(()),
+            Ok(_) => Err(AppLockError::Denied),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Err(AppLockError::NotSupported),
+            Err(err) => Err(AppLockError::Failed(err.to_string())),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod platform {
+    use super::AppLockError;
+
+    pub fn authenticate(_reason: &str) -> Result<(), AppLockError> {
+        Err(AppLockError::NotSupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_protect_destructive_commands() {
+        assert_eq!(
+            protected_reason("delete_workspace"),
+            Some("delete a workspace")
+        );
+        assert!(protected_reason("set_app_lock").is_some());
+        assert!(protected_reason("export_ssh_public_key").is_some());
+        assert!(protected_reason("get_app_lock").is_none());
+        assert!(protected_reason("list_ssh_keys").is_none());
+    }
+}
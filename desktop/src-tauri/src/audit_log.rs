@@ -0,0 +1,208 @@
+use crate::AppHandle;
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+const AUDIT_LOG_FILE_NAME: &str = "audit.jsonl";
+const DEFAULT_QUERY_LIMIT: usize = 500;
+
+lazy_static! {
+    // Set once the app data dir is known, actions before that aren't recorded
+    static ref AUDIT_LOG_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+#[derive(Error, Debug)]
+pub enum AuditLogError {
+    #[error("audit log is not available")]
+    NotAvailable,
+    #[error("unable to read audit log")]
+    Read(#[source] std::io::Error),
+}
+impl serde::Serialize for AuditLogError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// Who started a privileged action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum AuditInitiator {
+    Ui,
+    DeepLink,
+    Scheduler,
+    /// The backend on its own, e.g. cleaning up after a failed operation
+    Backend,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum AuditCategory {
+    Delete,
+    Credentials,
+    Install,
+    /// Settings that weaken or strengthen security, e.g. trusted link domains
+    Settings,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct AuditEntry {
+    at: DateTime<Utc>,
+    initiator: AuditInitiator,
+    category: AuditCategory,
+    /// e.g. `delete workspace`
+    action: String,
+    /// What the action was applied to, e.g. the workspace id. Never holds secret values.
+    target: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct AuditLogFilter {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    category: Option<AuditCategory>,
+    initiator: Option<AuditInitiator>,
+    limit: Option<usize>,
+}
+
+impl AuditLogFilter {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.from.is_none_or(|from| entry.at >= from)
+            && self.to.is_none_or(|to| entry.at <= to)
+            && self
+                .category
+                .is_none_or(|category| entry.category == category)
+            && self
+                .initiator
+                .is_none_or(|initiator| entry.initiator == initiator)
+    }
+}
+
+fn append(path: &Path, entry: &AuditEntry) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(format!("{}\n", serde_json::to_string(entry)?).as_bytes())?;
+
+    Ok(())
+}
+
+fn read_entries(path: &Path) -> Result<Vec<AuditEntry>, std::io::Error> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+
+    // Skip lines we can't parse, i.e. one that got cut off when the app was killed
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Appends a privileged action to the audit log. Unlike the operation history it's never pruned, failures are only
+/// logged to never get in the way of the action itself.
+pub fn record(
+    initiator: AuditInitiator,
+    category: AuditCategory,
+    action: &str,
+    target: Option<&str>,
+) {
+    let entry = AuditEntry {
+        at: Utc::now(),
+        initiator,
+        category,
+        action: action.to_string(),
+        target: target.map(String::from),
+    };
+    let path = AUDIT_LOG_PATH.lock().unwrap();
+    let Some(path) = path.as_ref() else {
+        return;
+    };
+
+    if let Err(err) = append(path, &entry) {
+        error!("Failed to record {} in audit log: {}", action, err);
+    }
+}
+
+pub fn setup(app_handle: &AppHandle) {
+    if let Some(dir) = app_handle.path_resolver().app_data_dir() {
+        *AUDIT_LOG_PATH.lock().unwrap() = Some(dir.join(AUDIT_LOG_FILE_NAME));
+    }
+}
+
+/// Returns the entries matching `filter`, newest first.
+#[tauri::command]
+pub fn query_audit_log(filter: Option<AuditLogFilter>) -> Result<Vec<AuditEntry>, AuditLogError> {
+    let filter = filter.unwrap_or_default();
+    let path = AUDIT_LOG_PATH.lock().unwrap();
+    let path = path.as_ref().ok_or(AuditLogError::NotAvailable)?;
+
+    let mut entries: Vec<_> = read_entries(path)
+        .map_err(AuditLogError::Read)?
+        .into_iter()
+        .filter(|entry| filter.matches(entry))
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.at));
+    entries.truncate(filter.limit.unwrap_or(DEFAULT_QUERY_LIMIT));
+
+    Ok(entries)
+}
+
+/// Records a privileged action the UI ran itself, e.g. removing a provider with the CLI.
+#[tauri::command]
+pub fn record_audit_event(category: AuditCategory, action: String, target: Option<String>) {
+    record(AuditInitiator::Ui, category, &action, target.as_deref());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn should_filter_entries() {
+        let now = Utc::now();
+        let entry = |initiator, category, minutes_ago| AuditEntry {
+            at: now - Duration::minutes(minutes_ago),
+            initiator,
+            category,
+            action: "action".to_string(),
+            target: None,
+        };
+        let deleted = entry(AuditInitiator::Ui, AuditCategory::Delete, 10);
+        let installed = entry(AuditInitiator::Scheduler, AuditCategory::Install, 0);
+
+        let filter = AuditLogFilter {
+            category: Some(AuditCategory::Delete),
+            ..Default::default()
+        };
+        assert!(filter.matches(&deleted));
+        assert!(!filter.matches(&installed));
+
+        let filter = AuditLogFilter {
+            from: Some(now - Duration::minutes(5)),
+            initiator: Some(AuditInitiator::Scheduler),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&deleted));
+        assert!(filter.matches(&installed));
+    }
+}
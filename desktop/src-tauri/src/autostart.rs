@@ -0,0 +1,254 @@
+use crate::util::app_path;
+use log::info;
+use serde::Serialize;
+use thiserror::Error;
+use ts_rs::TS;
+
+// Passed by the login entry to keep the main window hidden, leaving only the system tray
+pub const MINIMIZED_FLAG: &str = "--minimized";
+#[cfg(not(target_os = "macos"))]
+const AUTOSTART_NAME: &str = "DevPod";
+
+#[derive(Error, Debug)]
+pub enum AutostartError {
+    #[error("unable to find app executable")]
+    NoExePath(#[source] std::io::Error),
+    #[error("unable to find autostart dir")]
+    NoDir,
+    #[error("unable to update autostart entry")]
+    Write(#[source] anyhow::Error),
+}
+impl serde::Serialize for AutostartError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct AutostartStatus {
+    enabled: bool,
+    /// Starts hidden in the system tray
+    minimized: bool,
+}
+
+/// Whether this instance has been started at login with the main window hidden.
+pub fn launched_minimized() -> bool {
+    std::env::args().any(|arg| arg == MINIMIZED_FLAG)
+}
+
+#[tauri::command]
+pub fn get_autostart() -> Result<AutostartStatus, AutostartError> {
+    read_entry().map(|args| match args {
+        Some(args) => AutostartStatus {
+            enabled: true,
+            minimized: args.contains(MINIMIZED_FLAG),
+        },
+        None => AutostartStatus::default(),
+    })
+}
+
+/// Adds or removes the entry starting DevPod at login.
+#[tauri::command]
+pub fn set_autostart(enabled: bool, minimized: bool) -> Result<AutostartStatus, AutostartError> {
+    if enabled {
+        write_entry(&app_path().map_err(AutostartError::NoExePath)?, minimized)?;
+    } else {
+        remove_entry()?;
+    }
+    info!(
+        "Autostart {}",
+        match (enabled, minimized) {
+            (false, _) => "disabled",
+            (true, false) => "enabled",
+            (true, true) => "enabled, minimized",
+        }
+    );
+
+    get_autostart()
+}
+
+#[cfg(target_os = "linux")]
+fn entry_path() -> Result<std::path::PathBuf, AutostartError> {
+    // https://specifications.freedesktop.org/autostart-spec/autostart-spec-latest.html
+    dirs::config_dir()
+        .map(|dir| dir.join("autostart").join("devpod.desktop"))
+        .ok_or(AutostartError::NoDir)
+}
+
+#[cfg(target_os = "linux")]
+fn desktop_entry(app_path: &std::path::Path, minimized: bool) -> String {
+    let mut exec = format!("\"{}\"", app_path.to_string_lossy());
+    if minimized {
+        exec.push(' ');
+        exec.push_str(MINIMIZED_FLAG);
+    }
+
+    format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec={}\nX-GNOME-Autostart-enabled=true\nTerminal=false\n",
+        AUTOSTART_NAME, exec
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn read_entry() -> Result<Option<String>, AutostartError> {
+    let entry = match std::fs::read_to_string(entry_path()?) {
+        Ok(entry) => entry,
+        Err(_) => return Ok(None),
+    };
+
+    Ok(entry
+        .lines()
+        .find_map(|line| line.strip_prefix("Exec="))
+        .map(String::from))
+}
+
+#[cfg(target_os = "linux")]
+fn write_entry(app_path: &std::path::Path, minimized: bool) -> Result<(), AutostartError> {
+    use anyhow::Context;
+
+    let path = entry_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .context("create autostart dir")
+            .map_err(AutostartError::Write)?;
+    }
+
+    std::fs::write(&path, desktop_entry(app_path, minimized))
+        .with_context(|| format!("write {}", path.to_string_lossy()))
+        .map_err(AutostartError::Write)
+}
+
+#[cfg(target_os = "macos")]
+fn entry_path() -> Result<std::path::PathBuf, AutostartError> {
+    // Launch agents show up in the login items of the system settings
+    dirs::home_dir()
+        .map(|dir| dir.join("Library/LaunchAgents/sh.loft.devpod.autostart.plist"))
+        .ok_or(AutostartError::NoDir)
+}
+
+#[cfg(target_os = "macos")]
+fn launch_agent(app_path: &std::path::Path, minimized: bool) -> String {
+    let mut arguments = format!("<string>{}</string>", app_path.to_string_lossy());
+    if minimized {
+        arguments.push_str(&format!("<string>{}</string>", MINIMIZED_FLAG));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>Label</key>
+  <string>sh.loft.devpod.autostart</string>
+  <key>ProgramArguments</key>
+  <array>{}</array>
+  <key>RunAtLoad</key>
+  <true/>
+</dict>
+</plist>
+"#,
+        arguments
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn read_entry() -> Result<Option<String>, AutostartError> {
+    Ok(std::fs::read_to_string(entry_path()?).ok())
+}
+
+#[cfg(target_os = "macos")]
+fn write_entry(app_path: &std::path::Path, minimized: bool) -> Result<(), AutostartError> {
+    use anyhow::Context;
+
+    let path = entry_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .context("create launch agents dir")
+            .map_err(AutostartError::Write)?;
+    }
+
+    std::fs::write(&path, launch_agent(app_path, minimized))
+        .with_context(|| format!("write {}", path.to_string_lossy()))
+        .map_err(AutostartError::Write)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn remove_entry() -> Result<(), AutostartError> {
+    match std::fs::remove_file(entry_path()?) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            Err(AutostartError::Write(err.into()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(target_os = "windows")]
+const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+
+#[cfg(target_os = "windows")]
+fn read_entry() -> Result<Option<String>, AutostartError> {
+    use winreg::{enums::HKEY_CURRENT_USER, RegKey};
+
+    Ok(RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey(RUN_KEY)
+        .and_then(|key| key.get_value::<String, _>(AUTOSTART_NAME))
+        .ok())
+}
+
+#[cfg(target_os = "windows")]
+fn write_entry(app_path: &std::path::Path, minimized: bool) -> Result<(), AutostartError> {
+    use winreg::{
+        enums::{HKEY_CURRENT_USER, KEY_SET_VALUE},
+        RegKey,
+    };
+
+    let mut command = format!("\"{}\"", app_path.to_string_lossy());
+    if minimized {
+        command.push(' ');
+        command.push_str(MINIMIZED_FLAG);
+    }
+
+    RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey_with_flags(RUN_KEY, KEY_SET_VALUE)
+        .and_then(|key| key.set_value(AUTOSTART_NAME, &command))
+        .map_err(|err| AutostartError::Write(err.into()))
+}
+
+#[cfg(target_os = "windows")]
+fn remove_entry() -> Result<(), AutostartError> {
+    use winreg::{
+        enums::{HKEY_CURRENT_USER, KEY_SET_VALUE},
+        RegKey,
+    };
+
+    match RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey_with_flags(RUN_KEY, KEY_SET_VALUE)
+        .and_then(|key| key.delete_value(AUTOSTART_NAME))
+    {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            Err(AutostartError::Write(err.into()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn should_write_desktop_entry() {
+        use super::*;
+
+        let entry = desktop_entry(std::path::Path::new("/opt/DevPod/devpod"), true);
+        assert!(entry.contains("Exec=\"/opt/DevPod/devpod\" --minimized\n"));
+        assert!(
+            desktop_entry(std::path::Path::new("/opt/DevPod/devpod"), false)
+                .contains("Exec=\"/opt/DevPod/devpod\"\n")
+        );
+    }
+}
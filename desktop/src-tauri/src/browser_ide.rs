@@ -0,0 +1,505 @@
+use crate::{
+    commands::{workspace_ssh::WorkspaceSshCommand, DevpodCommandConfig},
+    connections::{self, Connection, ConnectionKind},
+    debug_console,
+    port_detection::{established_ports, listening_ports, LIST_SOCKETS_COMMAND},
+    ports::{self, PortForward, PortsError},
+    scheduler::{self, Schedule},
+    settings::Settings,
+    util::random_id,
+    AppHandle,
+};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    net::TcpListener,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::Manager;
+use thiserror::Error;
+use ts_rs::TS;
+
+// WARN: needs to match the channel in `client.ts`
+const BROWSER_IDE_SESSIONS_EVENT: &str = "browser_ide_sessions";
+const STARTUP_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+// Includes starting the server for the first time and connecting the tunnel
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(90);
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    static ref SESSIONS: Mutex<Vec<Session>> = Mutex::new(Vec::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum BrowserIde {
+    Openvscode,
+    CodeServer,
+}
+
+impl BrowserIde {
+    fn binary(&self) -> &'static str {
+        match self {
+            BrowserIde::Openvscode => "openvscode-server",
+            BrowserIde::CodeServer => "code-server",
+        }
+    }
+
+    /// Port in the workspace, they differ so that both can run side by side.
+    fn remote_port(&self) -> u16 {
+        match self {
+            BrowserIde::Openvscode => 10800,
+            BrowserIde::CodeServer => 10801,
+        }
+    }
+
+    fn pid_file(&self) -> String {
+        format!("/tmp/devpod-{}.pid", self.binary())
+    }
+
+    /// Starts the server in the background, replacing the one of a previous session whose token is lost. Prints the
+    /// server's PID.
+    fn start_command(&self, token: &str) -> String {
+        let server = match self {
+            BrowserIde::Openvscode => format!(
+                "{} --host 127.0.0.1 --port {} --connection-token {}",
+                self.binary(),
+                self.remote_port(),
+                token
+            ),
+            BrowserIde::CodeServer => format!(
+                "PASSWORD={} {} --bind-addr 127.0.0.1:{} --auth password --disable-telemetry",
+                token,
+                self.binary(),
+                self.remote_port()
+            ),
+        };
+
+        format!(
+            "command -v {binary} >/dev/null || exit 127; {stop}; nohup {server} >/tmp/devpod-{binary}.log 2>&1 </dev/null & echo $! | tee {pid_file}",
+            binary = self.binary(),
+            stop = self.stop_command(),
+            server = server,
+            pid_file = self.pid_file(),
+        )
+    }
+
+    // `pkill -f` would match the shell that runs the command as well
+    fn stop_command(&self) -> String {
+        format!(
+            "kill $(cat {pid_file} 2>/dev/null) 2>/dev/null; rm -f {pid_file}",
+            pid_file = self.pid_file()
+        )
+    }
+
+    fn url(&self, local_port: u16, token: &str) -> String {
+        match self {
+            BrowserIde::Openvscode => format!("http://localhost:{}/?tkn={}", local_port, token),
+            BrowserIde::CodeServer => format!("http://localhost:{}/", local_port),
+        }
+    }
+}
+
+impl std::fmt::Display for BrowserIde {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.binary())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum BrowserIdeSessionState {
+    /// Waiting for the server and the tunnel to it
+    Starting,
+    Ready,
+    Failed,
+}
+
+/// A browser IDE running in a workspace, reachable through a forwarded local port.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct BrowserIdeSession {
+    workspace_id: String,
+    ide: BrowserIde,
+    state: BrowserIdeSessionState,
+    url: String,
+    /// code-server asks for it on its login page, openvscode gets the token with the URL
+    password: Option<String>,
+    local_port: u16,
+    /// Of the server in the workspace, once it has been started
+    pid: Option<u32>,
+    error: Option<String>,
+}
+
+impl BrowserIdeSession {
+    fn connection_id(&self) -> String {
+        format!("browser-ide:{}:{}", self.workspace_id, self.ide)
+    }
+}
+
+struct Session {
+    status: BrowserIdeSession,
+    forward: Option<PortForward>,
+    last_active: Instant,
+}
+
+impl Session {
+    fn matches(&self, workspace_id: &str, ide: BrowserIde) -> bool {
+        self.status.workspace_id == workspace_id && self.status.ide == ide
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum BrowserIdeError {
+    #[error("unable to find a free local port")]
+    NoFreePort(#[source] io::Error),
+    #[error("unable to start {0} in the workspace, is it installed?")]
+    Start(BrowserIde),
+    #[error("{0} didn't start in time")]
+    Timeout(BrowserIde),
+    #[error(transparent)]
+    Forward(#[from] PortsError),
+    #[error("browser IDE session not found")]
+    NotFound,
+}
+impl serde::Serialize for BrowserIdeError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn statuses(sessions: &[Session]) -> Vec<BrowserIdeSession> {
+    sessions
+        .iter()
+        .map(|session| session.status.clone())
+        .collect()
+}
+
+fn emit(app_handle: &AppHandle, sessions: &[Session]) {
+    if let Err(err) = app_handle.emit_all(BROWSER_IDE_SESSIONS_EVENT, statuses(sessions)) {
+        warn!("Failed to send browser IDE sessions: {}", err);
+    }
+}
+
+fn free_port() -> io::Result<u16> {
+    TcpListener::bind(("127.0.0.1", 0))?
+        .local_addr()
+        .map(|address| address.port())
+}
+
+fn open_browser(app_handle: &AppHandle, url: &str) {
+    if let Err(err) = tauri::api::shell::open(&app_handle.shell_scope(), url, None) {
+        warn!("Failed to open browser: {}", err);
+    }
+}
+
+fn run_in_workspace(app_handle: &AppHandle, workspace_id: &str, command: String) -> Option<String> {
+    WorkspaceSshCommand::new(workspace_id.to_string(), command)
+        .exec(app_handle)
+        .ok()
+}
+
+fn wait_for(ide: BrowserIde, mut ready: impl FnMut() -> bool) -> Result<(), BrowserIdeError> {
+    let started_at = Instant::now();
+    while !ready() {
+        if started_at.elapsed() > STARTUP_TIMEOUT {
+            return Err(BrowserIdeError::Timeout(ide));
+        }
+        thread::sleep(STARTUP_CHECK_INTERVAL);
+    }
+
+    Ok(())
+}
+
+/// Starts the server and forwards it, the forward is stored in the session right away so that it can be cleaned up.
+fn launch(
+    app_handle: &AppHandle,
+    workspace_id: &str,
+    ide: BrowserIde,
+    token: &str,
+    local_port: u16,
+) -> Result<(), BrowserIdeError> {
+    let _task = debug_console::track_task("browser IDE", Some(workspace_id.to_string()));
+    let pid = run_in_workspace(app_handle, workspace_id, ide.start_command(token))
+        .ok_or(BrowserIdeError::Start(ide))?
+        .trim()
+        .parse()
+        .ok();
+    wait_for(ide, || {
+        run_in_workspace(app_handle, workspace_id, LIST_SOCKETS_COMMAND.to_string())
+            .is_some_and(|output| listening_ports(&output).contains(&ide.remote_port()))
+    })?;
+
+    let forward = ports::forward_transient(
+        app_handle,
+        workspace_id.to_string(),
+        local_port,
+        ide.remote_port(),
+    )?;
+    match SESSIONS
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .find(|session| session.matches(workspace_id, ide))
+    {
+        Some(session) => {
+            session.forward = Some(forward.clone());
+            session.status.pid = pid;
+        }
+        // Closed while it was starting
+        None => {
+            ports::unforward_port(app_handle.clone(), forward)?;
+            return Err(BrowserIdeError::NotFound);
+        }
+    }
+
+    wait_for(ide, || ports::is_connected(&forward))
+}
+
+fn remove(app_handle: &AppHandle, session: Session) -> Result<(), BrowserIdeError> {
+    connections::disconnect(app_handle, &session.status.connection_id(), None);
+    if let Some(forward) = session.forward {
+        ports::unforward_port(app_handle.clone(), forward)?;
+    }
+
+    Ok(())
+}
+
+/// Removes the session, its forward and stops the server in the workspace.
+fn close(
+    app_handle: &AppHandle,
+    workspace_id: &str,
+    ide: BrowserIde,
+) -> Result<(), BrowserIdeError> {
+    let session = {
+        let mut sessions = SESSIONS.lock().unwrap();
+        let index = sessions
+            .iter()
+            .position(|session| session.matches(workspace_id, ide))
+            .ok_or(BrowserIdeError::NotFound)?;
+        let session = sessions.remove(index);
+        emit(app_handle, &sessions);
+
+        session
+    };
+
+    remove(app_handle, session)?;
+    if run_in_workspace(app_handle, workspace_id, ide.stop_command()).is_none() {
+        warn!("Failed to stop {} in {}", ide, workspace_id);
+    }
+    info!("Closed {} session of {}", ide, workspace_id);
+
+    Ok(())
+}
+
+/// Forgets the sessions of a workspace that has been stopped, its servers are gone with it.
+pub fn close_workspace_sessions(app_handle: &AppHandle, workspace_id: &str) {
+    let closed: Vec<Session> = {
+        let mut sessions = SESSIONS.lock().unwrap();
+        let (closed, kept): (Vec<Session>, Vec<Session>) = std::mem::take(&mut *sessions)
+            .into_iter()
+            .partition(|session| session.status.workspace_id == workspace_id);
+        *sessions = kept;
+        if !closed.is_empty() {
+            emit(app_handle, &sessions);
+        }
+
+        closed
+    };
+
+    for session in closed {
+        let ide = session.status.ide;
+        if let Err(err) = remove(app_handle, session) {
+            warn!(
+                "Failed to close {} session of {}: {}",
+                ide, workspace_id, err
+            );
+        }
+    }
+}
+
+/// Stops servers nobody has been connected to for longer than the idle timeout of the settings, closed browser tabs
+/// don't disconnect anything we could notice otherwise.
+fn close_idle_sessions(app_handle: &AppHandle) {
+    let idle_timeout = match Settings::browser_ide_idle_timeout_minutes(app_handle) {
+        0 => return,
+        minutes => Duration::from_secs(u64::from(minutes) * 60),
+    };
+    let ready: Vec<(String, BrowserIde)> = SESSIONS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|session| session.status.state == BrowserIdeSessionState::Ready)
+        .map(|session| (session.status.workspace_id.clone(), session.status.ide))
+        .collect();
+
+    for (workspace_id, ide) in ready {
+        let in_use = run_in_workspace(app_handle, &workspace_id, LIST_SOCKETS_COMMAND.to_string())
+            .map(|output| established_ports(&output).contains(&ide.remote_port()));
+        let idle = {
+            let mut sessions = SESSIONS.lock().unwrap();
+            let session = match sessions
+                .iter_mut()
+                .find(|session| session.matches(&workspace_id, ide))
+            {
+                Some(session) => session,
+                None => continue,
+            };
+            // Stopped workspaces can't be checked, they don't run the server anymore either
+            if in_use == Some(true) {
+                session.last_active = Instant::now();
+            }
+
+            session.last_active.elapsed() > idle_timeout
+        };
+
+        if idle {
+            info!("{} session of {} is idle", ide, workspace_id);
+            if let Err(err) = close(app_handle, &workspace_id, ide) {
+                warn!("Failed to close idle {} session: {}", ide, err);
+            }
+        }
+    }
+}
+
+/// Closes idle sessions periodically. Their servers are left running when the app quits, the next session replaces them.
+pub fn setup() {
+    scheduler::Task::new(
+        "close idle browser IDEs",
+        Schedule::every(IDLE_CHECK_INTERVAL),
+        close_idle_sessions,
+    )
+    .register();
+}
+
+#[tauri::command]
+pub fn list_browser_ide_sessions() -> Vec<BrowserIdeSession> {
+    statuses(&SESSIONS.lock().unwrap())
+}
+
+/// Opens the workspace in a browser IDE, reuses a running session of the same IDE instead of starting another one.
+#[tauri::command]
+pub fn open_browser_ide(
+    app_handle: AppHandle,
+    workspace_id: String,
+    ide: BrowserIde,
+) -> Result<BrowserIdeSession, BrowserIdeError> {
+    let mut sessions = SESSIONS.lock().unwrap();
+    if let Some(session) = sessions
+        .iter_mut()
+        .find(|session| session.matches(&workspace_id, ide))
+    {
+        match session.status.state {
+            BrowserIdeSessionState::Starting => return Ok(session.status.clone()),
+            BrowserIdeSessionState::Ready => {
+                session.last_active = Instant::now();
+                open_browser(&app_handle, &session.status.url);
+                return Ok(session.status.clone());
+            }
+            BrowserIdeSessionState::Failed => {
+                sessions.retain(|session| !session.matches(&workspace_id, ide));
+            }
+        }
+    }
+
+    let local_port = free_port().map_err(BrowserIdeError::NoFreePort)?;
+    let token = format!("{:016x}{:016x}", random_id(), random_id());
+    let status = BrowserIdeSession {
+        workspace_id: workspace_id.clone(),
+        ide,
+        state: BrowserIdeSessionState::Starting,
+        url: ide.url(local_port, &token),
+        password: (ide == BrowserIde::CodeServer).then(|| token.clone()),
+        local_port,
+        pid: None,
+        error: None,
+    };
+    sessions.push(Session {
+        status: status.clone(),
+        forward: None,
+        last_active: Instant::now(),
+    });
+    emit(&app_handle, &sessions);
+    drop(sessions);
+
+    info!("Starting {} in {}", ide, workspace_id);
+    thread::spawn(move || {
+        let result = launch(&app_handle, &workspace_id, ide, &token, local_port);
+        let mut sessions = SESSIONS.lock().unwrap();
+        let session = match sessions
+            .iter_mut()
+            .find(|session| session.matches(&workspace_id, ide))
+        {
+            Some(session) => session,
+            // Closed while it was starting
+            None => return,
+        };
+
+        match result {
+            Ok(()) => {
+                session.status.state = BrowserIdeSessionState::Ready;
+                session.last_active = Instant::now();
+                connections::connect(
+                    &app_handle,
+                    Connection::new(
+                        session.status.connection_id(),
+                        workspace_id.clone(),
+                        ConnectionKind::BrowserIde,
+                        format!("{} on localhost:{}", ide, local_port),
+                    ),
+                );
+                open_browser(&app_handle, &session.status.url);
+            }
+            Err(err) => {
+                warn!("Failed to start {} in {}: {}", ide, workspace_id, err);
+                session.status.state = BrowserIdeSessionState::Failed;
+                session.status.error = Some(err.to_string());
+                if let Some(forward) = session.forward.take() {
+                    let _ = ports::unforward_port(app_handle.clone(), forward);
+                }
+            }
+        }
+        emit(&app_handle, &sessions);
+    });
+
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn close_browser_ide(
+    app_handle: AppHandle,
+    workspace_id: String,
+    ide: BrowserIde,
+) -> Result<(), BrowserIdeError> {
+    close(&app_handle, &workspace_id, ide)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_start_server_on_workspace_loopback() {
+        let command = BrowserIde::Openvscode.start_command("abc");
+
+        assert!(command.starts_with("command -v openvscode-server >/dev/null || exit 127;"));
+        assert!(command.contains(
+            "nohup openvscode-server --host 127.0.0.1 --port 10800 --connection-token abc >"
+        ));
+        assert!(command.ends_with("echo $! | tee /tmp/devpod-openvscode-server.pid"));
+        assert_eq!(
+            BrowserIde::Openvscode.url(4000, "abc"),
+            "http://localhost:4000/?tkn=abc"
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use lazy_static::lazy_static;
+use log::{error, info};
+use reqwest::{
+    header::{AUTHORIZATION, COOKIE, LOCATION, PROXY_AUTHORIZATION},
+    redirect::Policy,
+    tls::TlsInfo,
+    Client, RequestBuilder, Response,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{fs, io, path::PathBuf, sync::RwLock};
+use thiserror::Error;
+
+// Managed by administrators, in a directory only they are allowed to write to
+const PINS_FILE_NAME: &str = "certificate-pins.json";
+// Same limit as the default redirect policy of reqwest
+const MAX_REDIRECTS: usize = 10;
+
+lazy_static! {
+    // A broken pin file fails every pinned connection instead of silently turning pinning off
+    static ref PINS: RwLock<Result<Vec<HostPins>, String>> = RwLock::new(Ok(vec![]));
+}
+
+#[derive(Error, Debug)]
+pub enum PinError {
+    #[error("the certificate of {0} doesn't match its pinned certificates, the connection may be intercepted")]
+    Mismatch(String),
+    #[error("the certificate of {0} doesn't match its pinned certificates, the connection may be intercepted. Only this check before connecting is pinned, the updater and the CLI make their own connections")]
+    PreflightMismatch(String),
+    #[error("{0} didn't present a certificate")]
+    NoCertificate(String),
+    #[error("the certificate of {0} can't be read")]
+    InvalidCertificate(String),
+    #[error("certificate pins are invalid: {0}")]
+    Config(String),
+    #[error("unable to reach {0}")]
+    Request(String, #[source] reqwest::Error),
+}
+impl serde::Serialize for PinError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PinConfig {
+    hosts: Vec<HostPins>,
+}
+
+/// The certificates a host may present, either the SHA-256 of the whole certificate or of its public key. Pinning the
+/// public key survives certificate renewals that keep the key.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HostPins {
+    host: String,
+    #[serde(default)]
+    certificate_sha256: Vec<String>,
+    #[serde(default)]
+    public_key_sha256: Vec<String>,
+}
+
+impl HostPins {
+    fn matches(&self, certificate: &[u8]) -> Result<bool, PinError> {
+        let certificate_hash = hex_sha256(certificate);
+        if self
+            .certificate_sha256
+            .iter()
+            .any(|pin| normalize(pin) == certificate_hash)
+        {
+            return Ok(true);
+        }
+        if self.public_key_sha256.is_empty() {
+            return Ok(false);
+        }
+
+        let (_, parsed) = x509_parser::parse_x509_certificate(certificate)
+            .map_err(|_| PinError::InvalidCertificate(self.host.clone()))?;
+        let public_key_hash = hex_sha256(parsed.public_key().raw);
+
+        Ok(self
+            .public_key_sha256
+            .iter()
+            .any(|pin| normalize(pin) == public_key_hash))
+    }
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+// `openssl x509 -fingerprint -sha256` separates the bytes with colons
+fn normalize(pin: &str) -> String {
+    pin.trim().replace(':', "").to_lowercase()
+}
+
+#[cfg(target_os = "macos")]
+fn pins_dir() -> Option<PathBuf> {
+    Some(PathBuf::from("/Library/Application Support/DevPod"))
+}
+
+#[cfg(target_os = "windows")]
+fn pins_dir() -> Option<PathBuf> {
+    std::env::var_os("ProgramData").map(|dir| PathBuf::from(dir).join("DevPod"))
+}
+
+#[cfg(target_os = "linux")]
+fn pins_dir() -> Option<PathBuf> {
+    Some(PathBuf::from("/etc/devpod"))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn pins_dir() -> Option<PathBuf> {
+    None
+}
+
+fn load() -> Result<Vec<HostPins>, String> {
+    let Some(path) = pins_dir().map(|dir| dir.join(PINS_FILE_NAME)) else {
+        return Ok(vec![]);
+    };
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(format!("unable to read {}: {}", path.display(), err)),
+    };
+
+    let config: PinConfig = serde_json::from_str(&content)
+        .map_err(|err| format!("unable to parse {}: {}", path.display(), err))?;
+    info!(
+        "Loaded certificate pins for {} host(s) from {}",
+        config.hosts.len(),
+        path.display()
+    );
+
+    Ok(config.hosts)
+}
+
+fn pins_for(host: &str) -> Result<Option<HostPins>, PinError> {
+    let pins = PINS.read().unwrap();
+    let pins = pins.as_ref().map_err(|err| PinError::Config(err.clone()))?;
+
+    Ok(pins
+        .iter()
+        .find(|pins| pins.host.eq_ignore_ascii_case(host))
+        .cloned())
+}
+
+/// A client for connections that are checked against the pins, see `send`. It doesn't follow redirects on its own,
+/// `send` does that after checking each hop.
+pub fn client() -> reqwest::Result<Client> {
+    Client::builder()
+        .user_agent("loft-sh/devpod")
+        .tls_info(true)
+        .redirect(Policy::none())
+        .build()
+}
+
+/// Checks the certificate the host of `response` presented, hosts without pins pass.
+pub fn verify(response: &Response) -> Result<(), PinError> {
+    let host = response.url().host_str().unwrap_or_default().to_string();
+    let Some(pins) = pins_for(&host)? else {
+        return Ok(());
+    };
+
+    let certificate = response
+        .extensions()
+        .get::<TlsInfo>()
+        .and_then(|info| info.peer_certificate())
+        .ok_or_else(|| PinError::NoCertificate(host.clone()))?;
+    if !pins.matches(certificate)? {
+        error!("Certificate pin mismatch for {}", host);
+        return Err(PinError::Mismatch(host));
+    }
+
+    Ok(())
+}
+
+/// Sends a request built with `client` and follows its redirects, rejecting the response as soon as a host on the
+/// way doesn't match its pins. Only the headers have been received at that point, the body hasn't been read yet.
+pub async fn send(request: RequestBuilder) -> anyhow::Result<Response> {
+    let (client, request) = request.build_split();
+    let mut request = request?;
+
+    for _ in 0..=MAX_REDIRECTS {
+        // Only requests without a streamed body can be repeated for the next hop, i.e. all GET requests
+        let next = request.try_clone();
+        let response = client.execute(request).await?;
+        verify(&response)?;
+
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .filter(|_| response.status().is_redirection())
+            .map(|location| location.to_str().map(String::from));
+        let (Some(location), Some(mut next)) = (location, next) else {
+            return Ok(response);
+        };
+
+        let url = response.url().join(&location?)?;
+        if url.host_str() != next.url().host_str() {
+            // Like reqwest, credentials stay with the host they were meant for
+            for header in [AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION] {
+                next.headers_mut().remove(header);
+            }
+        }
+        *next.url_mut() = url;
+        request = next;
+    }
+
+    anyhow::bail!("too many redirects")
+}
+
+/// Connects to `url` only to check its certificate, for connections that are made by someone else, e.g. the updater
+/// or the CLI. Redirects aren't followed, it's the host of `url` that's checked. This is a preflight: the connection
+/// that follows isn't pinned, an interception that only starts after the check goes unnoticed.
+pub async fn check(url: &str) -> Result<(), PinError> {
+    let request_error = |err| PinError::Request(url.to_string(), err);
+    let response = client()
+        .map_err(request_error)?
+        .head(url)
+        .send()
+        .await
+        .map_err(request_error)?;
+
+    verify(&response).map_err(|err| match err {
+        PinError::Mismatch(host) => PinError::PreflightMismatch(host),
+        err => err,
+    })
+}
+
+pub fn setup() {
+    let pins = load();
+    if let Err(err) = &pins {
+        error!(
+            "Invalid certificate pins, pinned connections will fail: {}",
+            err
+        );
+    }
+    *PINS.write().unwrap() = pins;
+}
+
+/// Checks a Pro host before the CLI logs into it.
+#[tauri::command]
+pub async fn check_certificate_pins(url: String) -> Result<(), PinError> {
+    check(&url).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_certificate_pins() {
+        let certificate = b"not a real certificate";
+        let fingerprint = hex_sha256(certificate)
+            .to_uppercase()
+            .as_bytes()
+            .chunks(2)
+            .map(|byte| String::from_utf8_lossy(byte).to_string())
+            .collect::<Vec<_>>()
+            .join(":");
+        let pins = HostPins {
+            host: "github.com".to_string(),
+            certificate_sha256: vec![fingerprint],
+            public_key_sha256: vec![],
+        };
+
+        assert!(pins.matches(certificate).unwrap());
+        assert!(!pins.matches(b"another certificate").unwrap());
+
+        let key_pins = HostPins {
+            public_key_sha256: vec![hex_sha256(b"key")],
+            ..pins
+        };
+        assert!(matches!(
+            key_pins.matches(b"another certificate"),
+            Err(PinError::InvalidCertificate(_))
+        ));
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{
+    commands::{
+        provider_options::ProviderOptionsCommand, version::VersionCommand,
+        workspace_status::WorkspaceStatusCommand, DevpodCommandConfig, DevpodCommandError,
+    },
+    AppHandle,
+};
+use lazy_static::lazy_static;
+use log::debug;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+// Short enough to catch changes made outside of the app, e.g. with the CLI in a terminal
+const WORKSPACE_STATUS_TTL: Duration = Duration::from_secs(15);
+const PROVIDER_OPTIONS_TTL: Duration = Duration::from_secs(5 * 60);
+// The CLI only changes with an update, which clears the cache
+const VERSION_TTL: Duration = Duration::from_secs(60 * 60);
+
+lazy_static! {
+    static ref CACHE: Mutex<HashMap<CacheKey, Entry>> = Mutex::new(HashMap::new());
+}
+/// Goes up with every invalidation, results of commands that ran across one aren't stored.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum CacheKey {
+    Version,
+    WorkspaceStatus(String),
+    ProviderOptions(String),
+}
+
+impl CacheKey {
+    fn ttl(&self) -> Duration {
+        match self {
+            CacheKey::Version => VERSION_TTL,
+            CacheKey::WorkspaceStatus(_) => WORKSPACE_STATUS_TTL,
+            CacheKey::ProviderOptions(_) => PROVIDER_OPTIONS_TTL,
+        }
+    }
+
+    fn is_in(&self, scope: &CliCacheScope) -> bool {
+        match (scope, self) {
+            (CliCacheScope::Cli, _) => true,
+            (CliCacheScope::Workspace(id), CacheKey::WorkspaceStatus(key)) => id == key,
+            (CliCacheScope::Provider(name), CacheKey::ProviderOptions(key)) => name == key,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: serde_json::Value,
+    stored_at: Instant,
+}
+
+/// What an operation may have changed, the cached results that depend on it are dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[serde(tag = "kind", content = "id", rename_all = "camelCase")]
+#[ts(export)]
+pub enum CliCacheScope {
+    /// Started, stopped, rebuilt or deleted
+    Workspace(String),
+    /// Options set, updated or deleted
+    Provider(String),
+    /// The CLI itself has been replaced or its context switched
+    Cli,
+}
+
+#[derive(Error, Debug)]
+pub enum CliCacheError {
+    #[error(transparent)]
+    Command(#[from] DevpodCommandError),
+    #[error("unable to run command")]
+    Join(#[source] tauri::Error),
+}
+impl serde::Serialize for CliCacheError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn lookup<T: DeserializeOwned>(key: &CacheKey, now: Instant) -> Option<T> {
+    let cache = CACHE.lock().unwrap();
+    let entry = cache.get(key)?;
+    if now.duration_since(entry.stored_at) >= key.ttl() {
+        return None;
+    }
+
+    serde_json::from_value(entry.value.clone()).ok()
+}
+
+fn store<T: Serialize>(key: CacheKey, value: &T, now: Instant) {
+    if let Ok(value) = serde_json::to_value(value) {
+        CACHE.lock().unwrap().insert(
+            key,
+            Entry {
+                value,
+                stored_at: now,
+            },
+        );
+    }
+}
+
+/// Returns the cached result while it's fresh, runs `load` otherwise. Failures aren't cached.
+fn cached<T, F>(key: CacheKey, load: F) -> Result<T, DevpodCommandError>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Result<T, DevpodCommandError>,
+{
+    if let Some(value) = lookup(&key, Instant::now()) {
+        return Ok(value);
+    }
+
+    debug!("CLI cache miss for {:?}", key);
+    let generation = GENERATION.load(Ordering::SeqCst);
+    let value = load()?;
+    if GENERATION.load(Ordering::SeqCst) == generation {
+        store(key, &value, Instant::now());
+    }
+
+    Ok(value)
+}
+
+/// Drops everything that depends on `scope`, the next query runs the CLI again.
+pub fn invalidate(scope: &CliCacheScope) {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    CACHE.lock().unwrap().retain(|key, _| !key.is_in(scope));
+}
+
+pub fn version(app_handle: &AppHandle) -> Result<String, DevpodCommandError> {
+    cached(CacheKey::Version, || VersionCommand::new().exec(app_handle))
+}
+
+pub fn workspace_status(
+    app_handle: &AppHandle,
+    workspace_id: &str,
+) -> Result<String, DevpodCommandError> {
+    cached(CacheKey::WorkspaceStatus(workspace_id.to_string()), || {
+        WorkspaceStatusCommand::new(workspace_id.to_string()).exec(app_handle)
+    })
+}
+
+pub fn provider_options(
+    app_handle: &AppHandle,
+    provider: &str,
+) -> Result<serde_json::Value, DevpodCommandError> {
+    cached(CacheKey::ProviderOptions(provider.to_string()), || {
+        ProviderOptionsCommand::new(provider.to_string()).exec(app_handle)
+    })
+}
+
+/// The value of the option `name` of `provider`, `None` if the provider has no such option and empty if it isn't set.
+pub fn provider_option(
+    app_handle: &AppHandle,
+    provider: &str,
+    name: &str,
+) -> Result<Option<String>, DevpodCommandError> {
+    let options = provider_options(app_handle, provider)?;
+
+    Ok(options.get(name).map(|option| {
+        option
+            .get("value")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string()
+    }))
+}
+
+/// The state of the workspace as reported by its provider, e.g. `Running`.
+#[tauri::command]
+pub async fn get_workspace_status(
+    app_handle: AppHandle,
+    workspace_id: String,
+) -> Result<String, CliCacheError> {
+    tauri::async_runtime::spawn_blocking(move || Ok(workspace_status(&app_handle, &workspace_id)?))
+        .await
+        .map_err(CliCacheError::Join)?
+}
+
+#[tauri::command]
+pub async fn get_provider_options(
+    app_handle: AppHandle,
+    provider: String,
+) -> Result<serde_json::Value, CliCacheError> {
+    tauri::async_runtime::spawn_blocking(move || Ok(provider_options(&app_handle, &provider)?))
+        .await
+        .map_err(CliCacheError::Join)?
+}
+
+/// For operations the frontend runs itself, the backend invalidates after its own.
+#[tauri::command]
+pub fn invalidate_cli_cache(scope: CliCacheScope) {
+    invalidate(&scope);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_drop_only_entries_in_scope() {
+        let now = Instant::now();
+        store(CacheKey::WorkspaceStatus("a".to_string()), &"Running", now);
+        store(CacheKey::WorkspaceStatus("b".to_string()), &"Stopped", now);
+        store(CacheKey::ProviderOptions("a".to_string()), &42, now);
+
+        invalidate(&CliCacheScope::Workspace("a".to_string()));
+        assert_eq!(
+            lookup::<String>(&CacheKey::WorkspaceStatus("a".to_string()), now),
+            None
+        );
+        assert_eq!(
+            lookup::<String>(&CacheKey::WorkspaceStatus("b".to_string()), now).as_deref(),
+            Some("Stopped")
+        );
+        assert_eq!(
+            lookup::<u32>(&CacheKey::ProviderOptions("a".to_string()), now),
+            Some(42)
+        );
+
+        // Expired entries are treated like missing ones
+        let later = now + WORKSPACE_STATUS_TTL;
+        assert_eq!(
+            lookup::<String>(&CacheKey::WorkspaceStatus("b".to_string()), later),
+            None
+        );
+
+        invalidate(&CliCacheScope::Cli);
+        assert_eq!(
+            lookup::<u32>(&CacheKey::ProviderOptions("a".to_string()), now),
+            None
+        );
+    }
+}
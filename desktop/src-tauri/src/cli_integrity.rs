@@ -0,0 +1,364 @@
+use crate::{
+    commands::DEVPOD_BINARY_NAME,
+    keychain::{self, KeychainError},
+    ui_messages::{CliTamperedMsg, UiMessage},
+    AppHandle, AppState,
+};
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+use sha2::{Digest, Sha256};
+use std::{
+    env,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+    time::SystemTime,
+};
+use tauri::Manager;
+use thiserror::Error;
+
+// Kept in the keychain, anything running as the user is able to write to the directory of the binary
+const INTEGRITY_SERVICE: &str = "sh.loft.devpod.cli-integrity";
+const CURRENT_ACCOUNT: &str = "current";
+// The binary an update replaced, kept next to the cli until a rollback swaps it back in
+const PREVIOUS_ACCOUNT: &str = "previous";
+// Updates install the cli into the app data dir, writing to the signed app bundle would invalidate its signature
+const INSTALL_DIR_NAME: &str = "bin";
+
+lazy_static! {
+    // Held while hashing, so that concurrent commands wait for the result instead of hashing again
+    static ref CHECKED: Mutex<Option<Checked>> = Mutex::new(None);
+}
+
+#[derive(Error, Debug)]
+pub enum CliIntegrityError {
+    #[error("unable to determine cli path")]
+    NoCliPath(#[source] io::Error),
+    #[error("unable to read cli")]
+    Read(#[source] io::Error),
+    #[error(
+        "the cli has been modified since it was installed, download it again to keep using it"
+    )]
+    Tampered,
+}
+impl serde::Serialize for CliIntegrityError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// Changes whenever the binary is written to, checking it only stats the file unless it changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fingerprint {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Checked {
+    fingerprint: Fingerprint,
+    intact: bool,
+}
+
+/// The checksum the installed cli is expected to have and the app version it was recorded with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Expected {
+    app_version: String,
+    sha256: String,
+}
+
+impl Expected {
+    fn parse(value: &str) -> Option<Self> {
+        let (app_version, sha256) = value.split_once('/')?;
+
+        Some(Self {
+            app_version: app_version.to_string(),
+            sha256: sha256.to_string(),
+        })
+    }
+
+    fn to_value(&self) -> String {
+        format!("{}/{}", self.app_version, self.sha256)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Verdict {
+    Intact,
+    /// Nothing to compare against, the binary becomes the expected one
+    Trust,
+    Tampered,
+}
+
+fn verdict(expected: Option<&Expected>, app_version: &str, sha256: &str) -> Verdict {
+    match expected {
+        Some(expected) if expected.sha256 == sha256 => Verdict::Intact,
+        // App updates are signed and ship their own cli
+        Some(expected) if expected.app_version != app_version => Verdict::Trust,
+        Some(_) => Verdict::Tampered,
+        // First run or the keychain has been reset
+        None => Verdict::Trust,
+    }
+}
+
+/// The path to the cli the app runs, the one installed by an update if there is one.
+pub fn cli_path(app_handle: &AppHandle) -> Result<PathBuf, io::Error> {
+    match installed_cli_path(app_handle) {
+        Ok(path) if path.exists() => Ok(path),
+        _ => bundled_cli_path(),
+    }
+}
+
+/// The path to the `devpod-cli` binary shipped next to the desktop app executable.
+pub fn bundled_cli_path() -> Result<PathBuf, io::Error> {
+    let mut exe_path = env::current_exe()?;
+    exe_path.pop();
+    exe_path.push(format!("{}{}", DEVPOD_BINARY_NAME, env::consts::EXE_SUFFIX));
+
+    Ok(exe_path)
+}
+
+/// The path cli updates are installed to, whether or not there is a binary yet.
+pub fn installed_cli_path(app_handle: &AppHandle) -> Result<PathBuf, io::Error> {
+    let mut path = app_handle
+        .path_resolver()
+        .app_data_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no app data dir"))?;
+    path.push(INSTALL_DIR_NAME);
+    path.push(format!("{}{}", DEVPOD_BINARY_NAME, env::consts::EXE_SUFFIX));
+
+    Ok(path)
+}
+
+/// App updates ship their own cli, so one installed by an update for an older version of the app is removed.
+fn remove_outdated_install(app_handle: &AppHandle) {
+    let app_version = app_version(app_handle);
+    let Ok(path) = installed_cli_path(app_handle) else {
+        return;
+    };
+    if !path.exists() {
+        return;
+    }
+    match read(CURRENT_ACCOUNT) {
+        Ok(Some(expected)) if expected.app_version != app_version => {}
+        // Installed for this version, or nothing recorded to tell
+        Ok(_) => return,
+        Err(err) => {
+            warn!(
+                "Unable to read cli checksum, keeping installed cli: {}",
+                err
+            );
+            return;
+        }
+    }
+
+    info!(
+        "Switching to the cli shipped with app {}, removing {}",
+        app_version,
+        path.display()
+    );
+    if let Some(install_dir) = path.parent() {
+        if let Err(err) = fs::remove_dir_all(install_dir) {
+            error!("Failed to remove outdated cli: {}", err);
+        }
+    }
+}
+
+fn app_version(app_handle: &AppHandle) -> String {
+    app_handle.package_info().version.to_string()
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+fn hash_file(path: &Path) -> Result<String, io::Error> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn read(account: &str) -> Result<Option<Expected>, KeychainError> {
+    match keychain::get_password(INTEGRITY_SERVICE, account) {
+        Ok(value) => Ok(Expected::parse(&value)),
+        Err(KeychainError::NotFound(..)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn write(account: &str, expected: Option<&Expected>) {
+    let result = match expected {
+        Some(expected) => keychain::set_password(INTEGRITY_SERVICE, account, &expected.to_value()),
+        None => match keychain::delete_password(INTEGRITY_SERVICE, account) {
+            Err(KeychainError::NotFound(..)) => Ok(()),
+            result => result,
+        },
+    };
+    if let Err(err) = result {
+        warn!("Failed to record cli checksum: {}", err);
+    }
+}
+
+/// Compares the checksum with the recorded one, trusting the binary if there's nothing to compare against.
+fn check(sha256: &str, app_version: &str) -> bool {
+    let expected = match read(CURRENT_ACCOUNT) {
+        Ok(expected) => expected,
+        // Without the keychain there's nothing to compare against, that's no reason to stop working
+        Err(err) => {
+            warn!(
+                "Unable to read expected cli checksum, skipping check: {}",
+                err
+            );
+            return true;
+        }
+    };
+
+    match verdict(expected.as_ref(), app_version, sha256) {
+        Verdict::Intact => true,
+        Verdict::Trust => {
+            info!("Recording checksum of cli for {}", app_version);
+            write(
+                CURRENT_ACCOUNT,
+                Some(&Expected {
+                    app_version: app_version.to_string(),
+                    sha256: sha256.to_string(),
+                }),
+            );
+            true
+        }
+        Verdict::Tampered => false,
+    }
+}
+
+/// Makes sure the installed cli is the one that was installed by the app. A binary that has been modified is reported
+/// to the UI once and refused until the app replaces it.
+pub fn verify(app_handle: &AppHandle) -> Result<(), CliIntegrityError> {
+    let path = cli_path(app_handle).map_err(CliIntegrityError::NoCliPath)?;
+    let fingerprint = match fs::metadata(&path) {
+        Ok(metadata) => Fingerprint {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        },
+        // Spawning it fails on its own
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(CliIntegrityError::Read(err)),
+    };
+
+    let mut checked = CHECKED.lock().unwrap();
+    let intact = match *checked {
+        Some(checked) if checked.fingerprint == fingerprint => checked.intact,
+        _ => {
+            let sha256 = hash_file(&path).map_err(CliIntegrityError::Read)?;
+            let intact = check(&sha256, &app_version(app_handle));
+            if !intact {
+                report_tampered(app_handle, &path);
+            }
+            *checked = Some(Checked {
+                fingerprint,
+                intact,
+            });
+
+            intact
+        }
+    };
+
+    intact.then_some(()).ok_or(CliIntegrityError::Tampered)
+}
+
+fn report_tampered(app_handle: &AppHandle, path: &Path) {
+    error!(
+        "CLI at {} doesn't match the installed binary, refusing to run it",
+        path.display()
+    );
+
+    let app_handle = app_handle.clone();
+    let msg = CliTamperedMsg::new(
+        path.to_string_lossy().to_string(),
+        cfg!(feature = "enable-updater"),
+    );
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        if let Err(err) = state.ui_messages.send(UiMessage::CliTampered(msg)).await {
+            error!("Failed to broadcast cli tampered message: {:?}", err);
+        }
+    });
+}
+
+/// Trusts `binary`, which the app just installed as the cli. The checksum of the binary it replaced is kept for a
+/// rollback.
+pub fn record_install(app_handle: &AppHandle, binary: &[u8]) {
+    match read(CURRENT_ACCOUNT) {
+        Ok(current) => write(PREVIOUS_ACCOUNT, current.as_ref()),
+        Err(err) => warn!("Failed to read cli checksum: {}", err),
+    }
+    write(
+        CURRENT_ACCOUNT,
+        Some(&Expected {
+            app_version: app_version(app_handle),
+            sha256: hex_sha256(binary),
+        }),
+    );
+    *CHECKED.lock().unwrap() = None;
+}
+
+/// The installed cli and the previous one traded places, i.e. after a rollback or a failed update.
+pub fn record_swap(app_handle: &AppHandle) {
+    let (current, previous) = match (read(CURRENT_ACCOUNT), read(PREVIOUS_ACCOUNT)) {
+        (Ok(current), Ok(previous)) => (current, previous),
+        (Err(err), _) | (_, Err(err)) => {
+            warn!("Failed to read cli checksums: {}", err);
+            return;
+        }
+    };
+
+    // Recorded with this version so that an older binary doesn't count as shipped by an app update
+    let previous = previous.map(|previous| Expected {
+        app_version: app_version(app_handle),
+        ..previous
+    });
+    write(CURRENT_ACCOUNT, previous.as_ref());
+    write(PREVIOUS_ACCOUNT, current.as_ref());
+    *CHECKED.lock().unwrap() = None;
+}
+
+pub fn setup(app_handle: &AppHandle) {
+    // Before anything runs the cli, otherwise the outdated one would be trusted for this version
+    remove_outdated_install(app_handle);
+
+    // Hashing takes a moment, done up front so that the first command doesn't wait for it. `verify` reports a
+    // modified binary itself.
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let _ = verify(&app_handle);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_changed_binary() {
+        let expected = Expected {
+            app_version: "0.5.0".to_string(),
+            sha256: hex_sha256(b"cli"),
+        };
+        assert_eq!(
+            Expected::parse(&expected.to_value()).as_ref(),
+            Some(&expected)
+        );
+
+        let verdict_for =
+            |binary: &[u8], app_version| verdict(Some(&expected), app_version, &hex_sha256(binary));
+        assert_eq!(verdict_for(b"cli", "0.5.0"), Verdict::Intact);
+        assert_eq!(verdict_for(b"modified cli", "0.5.0"), Verdict::Tampered);
+        assert_eq!(verdict_for(b"bundled cli", "0.6.0"), Verdict::Trust);
+        assert_eq!(verdict(None, "0.5.0", &hex_sha256(b"cli")), Verdict::Trust);
+    }
+}
@@ -0,0 +1,626 @@
+use crate::{
+    audit_log::{self, AuditCategory, AuditInitiator},
+    cert_pinning,
+    cli_cache::{self, CliCacheScope},
+    cli_integrity,
+    commands::{version::VersionCommand, DevpodCommandConfig},
+    network,
+    notifications::{self, NotificationEvent},
+    power,
+    scheduler::{self, Schedule},
+    settings::{ReleaseChannel, Settings},
+    update_safety::{self, UpdateSafetyError, UpdateTarget},
+    updates::{Release, UpdateHelper},
+    verification::{
+        self, VerificationError, VerifiedManifest, CHECKSUM_MANIFEST_NAME,
+        CHECKSUM_MANIFEST_SIGNATURE_NAME,
+    },
+    AppHandle,
+};
+use anyhow::Context;
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use tokio::fs::File;
+use ts_rs::TS;
+
+const CLI_UPDATE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+const CLI_UPDATE_CHECK_JITTER: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+#[derive(Error, Debug)]
+pub enum CliUpdateError {
+    #[error("unable to determine cli path")]
+    NoCliPath(#[source] std::io::Error),
+    #[error("no cli release found for this platform")]
+    NoAsset,
+    #[error("cli is already up to date")]
+    UpToDate,
+    #[error(
+        "installed cli {installed} is newer than {latest}, the latest version of this channel"
+    )]
+    Downgrade { installed: String, latest: String },
+    #[error("failed to download cli {0}")]
+    Download(#[source] anyhow::Error),
+    #[error("failed to verify cli {0}")]
+    Verification(#[from] VerificationError),
+    #[error("failed to install cli {0}")]
+    Install(#[source] anyhow::Error),
+    #[error("failed to fetch releases {0}")]
+    FetchRelease(#[source] anyhow::Error),
+    #[error("no previous cli version to roll back to")]
+    NoPreviousVersion,
+    #[error(transparent)]
+    Safety(#[from] UpdateSafetyError),
+    #[error("unable to run the cli")]
+    Join(#[source] tauri::Error),
+}
+impl serde::Serialize for CliUpdateError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CliStatus {
+    path: String,
+    channel: ReleaseChannel,
+    installed_version: Option<String>,
+    latest_version: Option<String>,
+    update_available: bool,
+    downgrade_available: bool,
+}
+
+#[tauri::command]
+pub async fn get_cli_status(app_handle: AppHandle) -> Result<CliStatus, CliUpdateError> {
+    CliManager::new(&app_handle).status().await
+}
+
+#[tauri::command]
+pub async fn update_cli(
+    app_handle: AppHandle,
+    allow_downgrade: Option<bool>,
+    force: Option<bool>,
+) -> Result<CliStatus, CliUpdateError> {
+    let force = force.unwrap_or(false);
+    if !force {
+        update_safety::ensure_idle(&app_handle, UpdateTarget::Cli).await?;
+    }
+
+    let cli_manager = CliManager::new(&app_handle);
+    if let Err(err) = cli_manager
+        .update(allow_downgrade.unwrap_or(false), force)
+        .await
+    {
+        error!("Failed to update cli: {}", err);
+        return Err(err);
+    }
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Install,
+        "update cli",
+        None,
+    );
+
+    cli_manager.status().await
+}
+
+/// Replaces a cli that failed its integrity check with the latest release of the configured channel, verified like
+/// any other update. The modified binary doesn't report a version, so there's nothing to compare with.
+#[tauri::command]
+pub async fn repair_cli(app_handle: AppHandle) -> Result<CliStatus, CliUpdateError> {
+    let cli_manager = CliManager::new(&app_handle);
+    if let Err(err) = cli_manager.update(true, true).await {
+        error!("Failed to repair cli: {}", err);
+        return Err(err);
+    }
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Install,
+        "repair cli",
+        None,
+    );
+
+    cli_manager.status().await
+}
+
+#[derive(Clone, Debug)]
+pub struct CliManager<'a> {
+    app_handle: &'a AppHandle,
+}
+
+impl<'a> CliManager<'a> {
+    pub fn new(app_handle: &'a AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    /// The path to the `devpod-cli` binary the app runs, the bundled one until an update installs another one.
+    pub fn cli_path(&self) -> Result<PathBuf, std::io::Error> {
+        cli_integrity::cli_path(self.app_handle)
+    }
+
+    /// Where updates are installed to. The bundled binary stays as it is, changing it would break the signature of
+    /// the app. It is copied over on the first update, so that the update can be rolled back like any other.
+    fn install_path(&self) -> Result<PathBuf, CliUpdateError> {
+        let install_path = cli_integrity::installed_cli_path(self.app_handle)
+            .map_err(CliUpdateError::NoCliPath)?;
+        if !install_path.exists() {
+            let bundled_path =
+                cli_integrity::bundled_cli_path().map_err(CliUpdateError::NoCliPath)?;
+            fs::read(bundled_path)
+                .map_err(anyhow::Error::from)
+                .and_then(|binary| write_executable(&install_path, &binary))
+                .context("copy bundled cli")
+                .map_err(CliUpdateError::Install)?;
+        }
+
+        Ok(install_path)
+    }
+
+    /// Asks the cli for its version. Blocks until the cli exits, see `current_version` for async code.
+    pub fn installed_version(&self) -> Option<String> {
+        match VersionCommand::new().exec(self.app_handle) {
+            Ok(version) => Some(normalize_version(&version)),
+            Err(err) => {
+                warn!("Failed to get cli version: {}", err);
+                None
+            }
+        }
+    }
+
+    /// `installed_version` without blocking the async runtime.
+    pub async fn current_version(&self) -> Option<String> {
+        let app_handle = self.app_handle.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            CliManager::new(&app_handle).installed_version()
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    /// Runs `binary` from a temporary location to find out its version before it gets installed.
+    pub fn bundled_version(&self, binary: &[u8]) -> Option<String> {
+        let check_path = cli_integrity::installed_cli_path(self.app_handle)
+            .map(|cli_path| sibling_path(&cli_path, ".check"))
+            .ok()?;
+        let version = write_executable(&check_path, binary).and_then(|_| {
+            let output = std::process::Command::new(&check_path)
+                .arg("version")
+                .output()
+                .context("run cli")?;
+            anyhow::ensure!(output.status.success(), "cli exited with {}", output.status);
+
+            Ok(normalize_version(&String::from_utf8_lossy(&output.stdout)))
+        });
+        let _ = fs::remove_file(&check_path);
+
+        match version {
+            Ok(version) => Some(version),
+            Err(err) => {
+                warn!("Failed to get version of new cli: {}", err);
+                None
+            }
+        }
+    }
+
+    pub async fn status(&self) -> Result<CliStatus, CliUpdateError> {
+        let path = self.cli_path().map_err(CliUpdateError::NoCliPath)?;
+        let installed_version = self.current_version().await;
+        let latest_release = self.latest_release().await.ok();
+        let latest_version = latest_release
+            .as_ref()
+            .map(|release| normalize_version(&release.tag_name));
+
+        Ok(CliStatus {
+            path: path.to_string_lossy().to_string(),
+            channel: Settings::update_channel(self.app_handle),
+            update_available: is_newer(latest_version.as_deref(), installed_version.as_deref()),
+            downgrade_available: latest_version.is_some()
+                && is_newer(installed_version.as_deref(), latest_version.as_deref()),
+            installed_version,
+            latest_version,
+        })
+    }
+
+    /// Update checks wait while offline.
+    pub async fn poll(&self) {
+        network::wait_until_online().await;
+        if let Err(err) = self.check_update().await {
+            debug!("Failed to check for cli update: {}", err);
+        }
+    }
+
+    async fn check_update(&self) -> anyhow::Result<()> {
+        let release = self.latest_release().await?;
+        let latest_version = normalize_version(&release.tag_name);
+        let installed_version = self.current_version().await;
+        if !is_newer(Some(&latest_version), installed_version.as_deref()) {
+            return Ok(());
+        }
+
+        info!("CLI update available: {}", latest_version);
+        self.notify_update_available(&release).await
+    }
+
+    /// Installs the latest cli of the configured channel. Moving to an older version, i.e. after switching from
+    /// nightly back to stable, needs to be explicitly allowed. Versions incompatible with the agents of existing
+    /// workspaces are only installed if `force` is set.
+    pub async fn update(&self, allow_downgrade: bool, force: bool) -> Result<(), CliUpdateError> {
+        let release = self.latest_release().await?;
+        let latest_version = normalize_version(&release.tag_name);
+        let installed_version = self.current_version().await;
+        if installed_version.as_deref() == Some(latest_version.as_str()) {
+            return Err(CliUpdateError::UpToDate);
+        }
+        if !is_newer(Some(&latest_version), installed_version.as_deref()) {
+            let installed = installed_version.clone().unwrap_or_default();
+            if !allow_downgrade {
+                return Err(CliUpdateError::Downgrade {
+                    installed,
+                    latest: latest_version,
+                });
+            }
+            warn!("Downgrading cli from {} to {}", installed, latest_version);
+        }
+        if !force {
+            update_safety::check_protocol_compatibility(
+                self.app_handle,
+                installed_version.as_deref(),
+                &latest_version,
+            )?;
+        }
+
+        let asset_name = cli_asset_name();
+        let asset = release
+            .assets
+            .iter()
+            .find(|asset| asset.name == asset_name)
+            .ok_or(CliUpdateError::NoAsset)?;
+        let _sleep_guard = power::prevent_sleep("updating cli");
+        let manifest = self.download_manifest(&release).await?;
+
+        let delta_binary = match installed_version.as_deref() {
+            Some(installed_version) => {
+                self.download_delta(&release, &asset_name, installed_version, &manifest)
+                    .await
+            }
+            None => None,
+        };
+        let binary = match delta_binary {
+            Some(binary) => binary,
+            None => {
+                info!(
+                    "Downloading cli {} from {}",
+                    latest_version, asset.browser_download_url
+                );
+                let binary = download(&asset.browser_download_url)
+                    .await
+                    .map_err(CliUpdateError::Download)?;
+                if let Err(err) = manifest.verify(&asset_name, &binary) {
+                    verification::report_failure(self.app_handle, &asset_name, &err).await;
+                    return Err(err.into());
+                }
+
+                binary
+            }
+        };
+
+        let app_handle = self.app_handle.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            CliManager::new(&app_handle).install(&binary, Some(&latest_version))
+        })
+        .await
+        .map_err(CliUpdateError::Join)?
+    }
+
+    /// Swaps in `binary` as the new cli. If `expected_version` is set, the new binary has to report it or the
+    /// previous binary gets restored.
+    pub fn install(
+        &self,
+        binary: &[u8],
+        expected_version: Option<&str>,
+    ) -> Result<(), CliUpdateError> {
+        let cli_path = self.install_path()?;
+        swap_binary(&cli_path, binary).map_err(CliUpdateError::Install)?;
+        cli_integrity::record_install(self.app_handle, binary);
+        cli_cache::invalidate(&CliCacheScope::Cli);
+
+        // Make sure the new binary actually runs before we report success
+        match self.installed_version() {
+            Some(version) if expected_version.is_none_or(|expected| expected == version) => {
+                info!("Successfully installed cli {}", version);
+                Ok(())
+            }
+            version => {
+                error!(
+                    "Installed cli reports unexpected version {:?}, restoring previous binary",
+                    version
+                );
+                restore_binary(&cli_path).map_err(CliUpdateError::Install)?;
+                cli_integrity::record_swap(self.app_handle);
+
+                Err(CliUpdateError::Install(anyhow::anyhow!(
+                    "new cli binary reported version {:?}",
+                    version
+                )))
+            }
+        }
+    }
+
+    /// Tries to build the new binary from a binary diff against the installed version.
+    /// Returns `None` if there is no patch for the installed version or the patched binary doesn't match the
+    /// release checksum, callers should fall back to downloading the full binary in that case.
+    async fn download_delta(
+        &self,
+        release: &Release,
+        asset_name: &str,
+        installed_version: &str,
+        manifest: &VerifiedManifest,
+    ) -> Option<Vec<u8>> {
+        let patch_asset_name = delta_asset_name(asset_name, installed_version);
+        let patch_asset = release
+            .assets
+            .iter()
+            .find(|asset| asset.name == patch_asset_name)?;
+
+        info!(
+            "Downloading cli patch {} from {}",
+            patch_asset_name, patch_asset.browser_download_url
+        );
+        let patch = match download(&patch_asset.browser_download_url).await {
+            Ok(patch) => patch,
+            Err(err) => {
+                warn!(
+                    "Failed to download cli patch, falling back to full download: {}",
+                    err
+                );
+                return None;
+            }
+        };
+
+        let binary = self
+            .cli_path()
+            .map_err(anyhow::Error::from)
+            .and_then(|cli_path| apply_patch(&cli_path, &patch));
+        let binary = match binary {
+            Ok(binary) => binary,
+            Err(err) => {
+                warn!(
+                    "Failed to apply cli patch, falling back to full download: {}",
+                    err
+                );
+                return None;
+            }
+        };
+
+        if let Err(err) = manifest.verify(asset_name, &binary) {
+            warn!(
+                "Patched cli failed verification, falling back to full download: {}",
+                err
+            );
+            return None;
+        }
+
+        Some(binary)
+    }
+
+    async fn download_manifest(
+        &self,
+        release: &Release,
+    ) -> Result<VerifiedManifest, CliUpdateError> {
+        let find_asset = |name: &str| {
+            release
+                .assets
+                .iter()
+                .find(|asset| asset.name == name)
+                .ok_or(CliUpdateError::NoAsset)
+        };
+        let manifest_asset = find_asset(CHECKSUM_MANIFEST_NAME)?;
+        let signature_asset = find_asset(CHECKSUM_MANIFEST_SIGNATURE_NAME)?;
+
+        let manifest = download(&manifest_asset.browser_download_url)
+            .await
+            .map_err(CliUpdateError::Download)?;
+        let signature = download(&signature_asset.browser_download_url)
+            .await
+            .map_err(CliUpdateError::Download)?;
+
+        let manifest = VerifiedManifest::new(
+            &String::from_utf8_lossy(&manifest),
+            &String::from_utf8_lossy(&signature),
+        );
+        if let Err(err) = &manifest {
+            verification::report_failure(self.app_handle, CHECKSUM_MANIFEST_NAME, err).await;
+        }
+
+        manifest.map_err(CliUpdateError::from)
+    }
+
+    /// Swaps the installed cli with the version that was replaced by the last update.
+    /// Rolling back twice restores the updated version.
+    pub fn rollback(&self) -> Result<(), CliUpdateError> {
+        let cli_path = self.cli_path().map_err(CliUpdateError::NoCliPath)?;
+        if !sibling_path(&cli_path, ".old").exists() {
+            return Err(CliUpdateError::NoPreviousVersion);
+        }
+        swap_with_previous(&cli_path).map_err(CliUpdateError::Install)?;
+        cli_integrity::record_swap(self.app_handle);
+        cli_cache::invalidate(&CliCacheScope::Cli);
+        info!("Rolled back cli to {:?}", self.installed_version());
+
+        Ok(())
+    }
+
+    async fn latest_release(&self) -> Result<Release, CliUpdateError> {
+        let channel = Settings::update_channel(self.app_handle);
+
+        UpdateHelper::new(self.app_handle)
+            .latest_release(channel)
+            .await
+            .map_err(CliUpdateError::FetchRelease)
+    }
+
+    async fn notify_update_available(&self, release: &Release) -> anyhow::Result<()> {
+        // only notify once per release
+        if let Some(mut target) = self.app_handle.path_resolver().app_cache_dir() {
+            target.push(format!("cli_update_{}", release.tag_name));
+            if target.exists() {
+                return Ok(());
+            }
+            let _ = File::create(target).await?;
+        }
+
+        notifications::notify(
+            self.app_handle,
+            NotificationEvent::CliUpdateAvailable,
+            "CLI update available",
+            &format!("DevPod CLI {} is available", release.tag_name),
+            vec![],
+        )
+    }
+}
+
+fn normalize_version(version: &str) -> String {
+    version.trim().trim_start_matches('v').to_string()
+}
+
+fn is_newer(latest: Option<&str>, installed: Option<&str>) -> bool {
+    match (latest, installed) {
+        (Some(latest), Some(installed)) => {
+            match (
+                semver::Version::parse(latest),
+                semver::Version::parse(installed),
+            ) {
+                (Ok(latest), Ok(installed)) => latest > installed,
+                _ => false,
+            }
+        }
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+// Matches the release asset names produced by the CLI build, i.e. `devpod-linux-amd64`
+pub fn cli_asset_name() -> String {
+    let os = match env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    let arch = match env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        arch => arch,
+    };
+
+    format!("devpod-{}-{}{}", os, arch, env::consts::EXE_SUFFIX)
+}
+
+// Binary diffs are published next to the full binaries, i.e. `devpod-linux-amd64.from-v0.3.6.patch`
+fn delta_asset_name(asset_name: &str, from_version: &str) -> String {
+    format!("{}.from-v{}.patch", asset_name, from_version)
+}
+
+/// Applies a bsdiff patch to the binary at `source_path` and returns the patched binary.
+fn apply_patch(source_path: &Path, patch: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let source = fs::read(source_path).context("read installed binary")?;
+    let patcher = qbsdiff::Bspatch::new(patch).context("parse patch")?;
+
+    let mut target = Vec::with_capacity(patcher.hint_target_size() as usize);
+    patcher
+        .apply(&source, std::io::Cursor::new(&mut target))
+        .context("apply patch")?;
+
+    Ok(target)
+}
+
+async fn download(url: &str) -> anyhow::Result<Vec<u8>> {
+    let bytes = cert_pinning::send(cert_pinning::client()?.get(url))
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await
+        .with_context(|| format!("Download {}", url))?;
+
+    Ok(bytes.to_vec())
+}
+
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut sibling = path.as_os_str().to_owned();
+    sibling.push(suffix);
+
+    PathBuf::from(sibling)
+}
+
+/// Writes `contents` to `path` and makes it executable. Anything at `path` is overwritten, see `swap_binary` for
+/// replacing the installed cli.
+fn write_executable(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("create binary dir")?;
+    }
+    fs::write(path, contents).context("write binary")?;
+    #[cfg(not(target_os = "windows"))]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+            .context("make binary executable")?;
+    }
+
+    Ok(())
+}
+
+/// Replaces the binary at `path` with `contents`, keeping the previous version next to it.
+fn swap_binary(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    let staged_path = sibling_path(path, ".new");
+    let previous_path = sibling_path(path, ".old");
+
+    write_executable(&staged_path, contents).context("stage binary")?;
+
+    if path.exists() {
+        fs::rename(path, &previous_path).context("move previous binary")?;
+    }
+    if let Err(err) = fs::rename(&staged_path, path) {
+        let _ = fs::rename(&previous_path, path);
+        return Err(err).context("move staged binary");
+    }
+
+    Ok(())
+}
+
+fn restore_binary(path: &Path) -> anyhow::Result<()> {
+    let previous_path = sibling_path(path, ".old");
+
+    fs::rename(previous_path, path).context("restore previous binary")
+}
+
+fn swap_with_previous(path: &Path) -> anyhow::Result<()> {
+    let staged_path = sibling_path(path, ".new");
+    let previous_path = sibling_path(path, ".old");
+
+    fs::rename(path, &staged_path).context("move current binary")?;
+    if let Err(err) = fs::rename(&previous_path, path) {
+        let _ = fs::rename(&staged_path, path);
+        return Err(err).context("restore previous binary");
+    }
+
+    fs::rename(&staged_path, &previous_path).context("keep current binary")
+}
+
+pub fn setup() {
+    scheduler::Task::new(
+        "cli update check",
+        Schedule::every(CLI_UPDATE_POLL_INTERVAL),
+        |app_handle| tauri::async_runtime::block_on(CliManager::new(app_handle).poll()),
+    )
+    .jitter(CLI_UPDATE_CHECK_JITTER)
+    .defer_on_battery()
+    .register();
+}
@@ -0,0 +1,173 @@
+use crate::{
+    commands::DEVPOD_BINARY_NAME,
+    process::{ProcessError, Stream, SupervisedCommand},
+    AppHandle,
+};
+use log::warn;
+use serde::Serialize;
+use tauri::Window;
+use thiserror::Error;
+use ts_rs::TS;
+
+// WARN: needs to match the event name in `command.ts`
+const CLI_OUTPUT_EVENT: &str = "cli_output";
+// Commands with subcommands of their own, e.g. `devpod provider delete`
+const COMMAND_GROUPS: &[&str] = &["context", "ide", "machine", "pro", "provider"];
+// Global flags of the CLI that take a value, i.e. `--context default`
+const FLAGS_WITH_VALUE: &[&str] = &[
+    "--context",
+    "--provider",
+    "--log-output",
+    "--devpod-home",
+    "--agent-dir",
+];
+
+#[derive(Error, Debug)]
+pub enum CliRunnerError {
+    #[error(transparent)]
+    Process(#[from] ProcessError),
+    #[error("`{0}` deletes data, it runs through its own command")]
+    Destructive(String),
+    #[error("unable to run the CLI")]
+    Join(#[source] tauri::Error),
+}
+impl serde::Serialize for CliRunnerError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CliOutput {
+    /// `None` if the CLI was terminated by a signal
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+}
+
+/// A line the CLI printed while it's running, for the window that started it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CliOutputLine {
+    stream_id: String,
+    stderr: bool,
+    line: String,
+}
+
+/// Whether `args` delete anything. These go through dedicated commands, which the app lock guards.
+pub(crate) fn is_destructive(args: &[String]) -> bool {
+    let mut words = positional_args(args).into_iter();
+    match words.next() {
+        Some("delete") => true,
+        Some(group) if COMMAND_GROUPS.contains(&group) => words.next() == Some("delete"),
+        _ => false,
+    }
+}
+
+/// `args` without flags and their values, i.e. the command and its arguments.
+fn positional_args(args: &[String]) -> Vec<&str> {
+    let mut positional = vec![];
+    let mut args = args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            positional.extend(args);
+            break;
+        }
+        if !arg.starts_with('-') {
+            positional.push(arg);
+        } else if FLAGS_WITH_VALUE.contains(&arg) {
+            // `--flag=value` carries its value itself
+            args.next();
+        }
+    }
+
+    positional
+}
+
+/// Runs the CLI for the UI through the supervisor, so that its calls are queued, limited, cancellable and get the same
+/// environment as the ones of the backend. With a `stream_id` every line is sent to the window as it comes in.
+#[tauri::command]
+pub async fn run_cli(
+    app_handle: AppHandle,
+    window: Window,
+    args: Vec<String>,
+    workspace_id: Option<String>,
+    operation_id: Option<String>,
+    stream_id: Option<String>,
+) -> Result<CliOutput, CliRunnerError> {
+    if is_destructive(&args) {
+        return Err(CliRunnerError::Destructive(args.join(" ")));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let output = SupervisedCommand::sidecar(&app_handle, DEVPOD_BINARY_NAME)
+            .args(&args)
+            .workspace(workspace_id.as_deref())
+            .operation(operation_id.as_deref())
+            .run(|stream, line| {
+                let Some(stream_id) = &stream_id else {
+                    return;
+                };
+                let line = CliOutputLine {
+                    stream_id: stream_id.clone(),
+                    stderr: stream == Stream::Stderr,
+                    line: line.to_string(),
+                };
+                if let Err(err) = window.emit(CLI_OUTPUT_EVENT, line) {
+                    warn!("Failed to emit CLI output: {}", err);
+                }
+            })?;
+
+        Ok(CliOutput {
+            code: output.status.code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    })
+    .await
+    .map_err(CliRunnerError::Join)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn should_detect_destructive_args() {
+        assert!(is_destructive(&args(&["delete", "my-ws", "--force"])));
+        assert!(is_destructive(&args(&["--debug", "delete", "my-ws"])));
+        assert!(is_destructive(&args(&["pro", "delete", "my-pro"])));
+        assert!(is_destructive(&args(&["provider", "delete", "docker"])));
+        assert!(!is_destructive(&args(&["up", "delete"])));
+        assert!(!is_destructive(&args(&["provider", "list", "--json"])));
+        assert!(!is_destructive(&args(&[])));
+    }
+
+    #[test]
+    fn should_skip_flag_values() {
+        let destructive: &[&[&str]] = &[
+            &["--context", "default", "delete", "ws"],
+            &["--provider", "x", "delete", "ws"],
+            &["--log-output", "json", "delete"],
+            &["--devpod-home", "/tmp", "pro", "delete"],
+            &["provider", "--agent-dir", "/a", "delete"],
+            &["--context=default", "delete", "ws"],
+        ];
+        for case in destructive {
+            assert!(is_destructive(&args(case)), "{:?}", case);
+        }
+
+        assert!(!is_destructive(&args(&["--context", "delete", "up", "ws"])));
+        assert!(!is_destructive(&args(&["up", "--provider", "x", "delete"])));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    drag_drop::is_git_url,
+    notifications::{self, NotificationAction, NotificationEvent},
+    settings::Settings,
+    ui_messages::{ClipboardUrlMsg, UiMessage},
+    AppHandle, AppState,
+};
+use log::{error, info, warn};
+use std::{collections::HashSet, thread, time::Duration};
+use tauri::{ClipboardManager, Manager};
+use url::Url;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+// How often the setting is checked while the watcher is turned off, the clipboard isn't read at all then
+const DISABLED_POLL_INTERVAL: Duration = Duration::from_secs(5);
+// Longer texts are never just a URL
+const MAX_URL_LENGTH: usize = 2048;
+
+/// `devpod://open` link that creates a workspace from `source`.
+fn open_url(source: &str) -> String {
+    Url::parse_with_params("devpod://open", &[("source", source)])
+        .map(|url| url.to_string())
+        .unwrap_or_else(|_| "devpod://open".to_string())
+}
+
+/// The git URL to offer for clipboard content, `None` if it's anything else.
+fn git_url(text: &str) -> Option<&str> {
+    let text = text.trim();
+    if text.len() > MAX_URL_LENGTH || text.contains(char::is_whitespace) || !is_git_url(text) {
+        return None;
+    }
+
+    Some(text)
+}
+
+fn offer(app_handle: &AppHandle, source: &str) {
+    info!("Offering to create a workspace from copied git URL");
+    let visible = app_handle
+        .get_window("main")
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
+
+    // Only show a toast if the window is open anyway, sending it would bring the window up otherwise
+    if visible {
+        let msg =
+            UiMessage::ClipboardUrl(ClipboardUrlMsg::new(source.to_string(), open_url(source)));
+        let app_state = app_handle.state::<AppState>();
+        tauri::async_runtime::block_on(async move {
+            if let Err(err) = app_state.ui_messages.send(msg).await {
+                error!("Failed to broadcast clipboard message: {}", err);
+            }
+        });
+        return;
+    }
+
+    if let Err(err) = notifications::notify(
+        app_handle,
+        NotificationEvent::ClipboardGitUrl,
+        "Create a workspace?",
+        &format!("You copied {}, open it in DevPod", source),
+        vec![NotificationAction::OpenDeepLink(open_url(source))],
+    ) {
+        warn!("Failed to show clipboard notification: {}", err);
+    }
+}
+
+/// Watches the clipboard for git URLs, but only while the user opted in.
+/// Every URL is only offered once, and nothing that was in the clipboard before the watcher was turned on.
+pub fn setup(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let clipboard = app_handle.clipboard_manager();
+        let mut last: Option<Option<String>> = None;
+        let mut offered = HashSet::new();
+
+        loop {
+            if !Settings::watch_clipboard(&app_handle) {
+                last = None;
+                thread::sleep(DISABLED_POLL_INTERVAL);
+                continue;
+            }
+
+            let text = clipboard.read_text().unwrap_or(None);
+            let changed = last.as_ref().is_some_and(|last| *last != text);
+            if changed {
+                if let Some(source) = text.as_deref().and_then(git_url) {
+                    if offered.insert(source.to_string()) {
+                        offer(&app_handle, source);
+                    }
+                }
+            }
+            last = Some(text);
+
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_offer_git_urls() {
+        assert_eq!(
+            git_url("  https://github.com/loft-sh/devpod\n"),
+            Some("https://github.com/loft-sh/devpod")
+        );
+        assert_eq!(
+            git_url("git@github.com:loft-sh/devpod.git"),
+            Some("git@github.com:loft-sh/devpod.git")
+        );
+        assert_eq!(git_url("https://github.com"), None);
+        assert_eq!(git_url("see https://github.com/loft-sh/devpod"), None);
+        assert_eq!(git_url("/home/user/project"), None);
+    }
+
+    #[test]
+    fn should_build_open_url() {
+        assert_eq!(
+            open_url("https://github.com/loft-sh/devpod"),
+            "devpod://open?source=https%3A%2F%2Fgithub.com%2Floft-sh%2Fdevpod"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+use crate::{
+    devcontainer::{self, CONFIG_PATHS},
+    environment, AppHandle,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{collections::HashSet, fs, path::Path, thread};
+use ts_rs::TS;
+
+// The provider that runs workspaces on this machine, its machine specs are the ones of the host
+const LOCAL_DOCKER_PROVIDER: &str = "docker";
+
+/// How DevPod deals with a setting that's specific to GitHub Codespaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum CodespacesSupport {
+    /// Works the same way or has a DevPod equivalent that's already set up
+    Supported,
+    /// Has a DevPod equivalent the user needs to set up themselves
+    Manual,
+    /// Ignored
+    Unsupported,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CodespacesField {
+    /// JSON pointer to the setting, e.g. `/hostRequirements/cpus`
+    path: String,
+    support: CodespacesSupport,
+    message: String,
+}
+
+/// The Codespaces specific settings of a devcontainer config and their DevPod equivalents.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CodespacesCompatibility {
+    fields: Vec<CodespacesField>,
+}
+
+impl CodespacesCompatibility {
+    fn push(&mut self, path: &str, support: CodespacesSupport, message: String) {
+        self.fields.push(CodespacesField {
+            path: path.to_string(),
+            support,
+            message,
+        });
+    }
+}
+
+/// What `check` needs to know about the workspace that's about to be created.
+#[derive(Debug, Default)]
+struct Target<'a> {
+    provider: Option<&'a str>,
+    /// Environment variables configured for the workspace
+    variables: HashSet<String>,
+    host_cpus: Option<usize>,
+}
+
+fn check_host_requirements(
+    requirements: &Map<String, Value>,
+    target: &Target,
+    compatibility: &mut CodespacesCompatibility,
+) {
+    let cpus = requirements.get("cpus").and_then(Value::as_u64);
+    let memory = requirements.get("memory").and_then(Value::as_str);
+    if cpus.is_some() || memory.is_some() {
+        let spec = [
+            cpus.map(|cpus| format!("{} CPUs", cpus)),
+            memory.map(|memory| format!("{} of memory", memory)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" and ");
+        let path = if cpus.is_some() {
+            "/hostRequirements/cpus"
+        } else {
+            "/hostRequirements/memory"
+        };
+
+        match (target.provider, cpus, target.host_cpus) {
+            (Some(LOCAL_DOCKER_PROVIDER), Some(cpus), Some(host_cpus))
+                if host_cpus < cpus as usize =>
+            {
+                compatibility.push(
+                    path,
+                    CodespacesSupport::Unsupported,
+                    format!(
+                        "Requires {}, this machine only has {} CPUs",
+                        spec, host_cpus
+                    ),
+                );
+            }
+            (Some(LOCAL_DOCKER_PROVIDER), ..) => compatibility.push(
+                path,
+                CodespacesSupport::Supported,
+                format!("Runs on this machine, make sure Docker may use {}", spec),
+            ),
+            _ => compatibility.push(
+                path,
+                CodespacesSupport::Manual,
+                format!(
+                    "Pick a machine type with at least {} in the options of the provider",
+                    spec
+                ),
+            ),
+        }
+    }
+
+    if let Some(storage) = requirements.get("storage").and_then(Value::as_str) {
+        let support = if target.provider == Some(LOCAL_DOCKER_PROVIDER) {
+            CodespacesSupport::Supported
+        } else {
+            CodespacesSupport::Manual
+        };
+        compatibility.push(
+            "/hostRequirements/storage",
+            support,
+            format!(
+                "Needs {} of disk space, cloud providers take it as the DISK_SIZE option",
+                storage
+            ),
+        );
+    }
+
+    if let Some(gpu) = requirements
+        .get("gpu")
+        .filter(|gpu| gpu != &&Value::Bool(false))
+    {
+        // Optional GPUs are only used if the machine happens to have one
+        let support = if gpu.as_str() == Some("optional") {
+            CodespacesSupport::Supported
+        } else {
+            CodespacesSupport::Manual
+        };
+        compatibility.push(
+            "/hostRequirements/gpu",
+            support,
+            "DevPod doesn't pick GPU machines on its own, choose one in the options of the provider".to_string(),
+        );
+    }
+}
+
+fn check_secrets(
+    secrets: &Map<String, Value>,
+    target: &Target,
+    compatibility: &mut CodespacesCompatibility,
+) {
+    for (name, secret) in secrets {
+        let path = devcontainer::pointer("/secrets", name);
+        if target.variables.contains(name) {
+            compatibility.push(
+                &path,
+                CodespacesSupport::Supported,
+                "Set as an environment variable in the settings".to_string(),
+            );
+            continue;
+        }
+
+        let description = secret
+            .get("description")
+            .and_then(Value::as_str)
+            .map(|description| format!(" ({})", description))
+            .unwrap_or_default();
+        compatibility.push(
+            &path,
+            CodespacesSupport::Manual,
+            format!(
+                "Add {}{} as an environment variable in the settings",
+                name, description
+            ),
+        );
+    }
+}
+
+fn check_customizations(
+    codespaces: &Map<String, Value>,
+    compatibility: &mut CodespacesCompatibility,
+) {
+    for key in codespaces.keys() {
+        let path = devcontainer::pointer("/customizations/codespaces", key);
+        let (support, message) = match key.as_str() {
+            "repositories" => (
+                CodespacesSupport::Supported,
+                "Git credentials of this machine are forwarded, the workspace can access every repository you can",
+            ),
+            "openFiles" => (
+                CodespacesSupport::Unsupported,
+                "The IDE opens the workspace folder only",
+            ),
+            "disableAutomaticConfiguration" => (
+                CodespacesSupport::Unsupported,
+                "DevPod doesn't configure workspaces without a devcontainer config",
+            ),
+            _ => (CodespacesSupport::Unsupported, "Unknown Codespaces setting"),
+        };
+        compatibility.push(&path, support, message.to_string());
+    }
+}
+
+/// Codespaces runs `updateContentCommand` and everything before it in prebuilds.
+fn check_prebuild_hints(config: &Map<String, Value>, compatibility: &mut CodespacesCompatibility) {
+    if config.contains_key("updateContentCommand") {
+        compatibility.push(
+            "/updateContentCommand",
+            CodespacesSupport::Manual,
+            "Runs when the workspace is created, set a prebuild repository to run it ahead of time"
+                .to_string(),
+        );
+    }
+    if config.contains_key("waitFor") {
+        compatibility.push(
+            "/waitFor",
+            CodespacesSupport::Unsupported,
+            "DevPod waits for every lifecycle command before opening the IDE".to_string(),
+        );
+    }
+}
+
+/// Maps the Codespaces specific settings of `config`, i.e. machine specs, secrets, prebuild hints and
+/// `customizations.codespaces`, to what DevPod does instead. Empty for configs that don't use any.
+fn check(config: &Value, target: &Target) -> CodespacesCompatibility {
+    let mut compatibility = CodespacesCompatibility::default();
+    let Some(config) = config.as_object() else {
+        return compatibility;
+    };
+
+    let codespaces = config
+        .get("customizations")
+        .and_then(|customizations| customizations.get("codespaces"))
+        .and_then(Value::as_object);
+    let requirements = config.get("hostRequirements").and_then(Value::as_object);
+    let secrets = config.get("secrets").and_then(Value::as_object);
+    // Prebuild hints on their own are part of any devcontainer config
+    if codespaces.is_none() && requirements.is_none() && secrets.is_none() {
+        return compatibility;
+    }
+
+    if let Some(requirements) = requirements {
+        check_host_requirements(requirements, target, &mut compatibility);
+    }
+    if let Some(secrets) = secrets {
+        check_secrets(secrets, target, &mut compatibility);
+    }
+    check_prebuild_hints(config, &mut compatibility);
+    if let Some(codespaces) = codespaces {
+        check_customizations(codespaces, &mut compatibility);
+    }
+
+    compatibility
+}
+
+/// Checks the devcontainer config of a local folder before a workspace is created from it. `None` for other
+/// sources, the config is only known once the CLI cloned them.
+#[tauri::command]
+pub fn check_codespaces_compatibility(
+    app_handle: AppHandle,
+    source: String,
+    devcontainer_path: Option<String>,
+    provider: Option<String>,
+) -> Option<CodespacesCompatibility> {
+    let folder = Path::new(&source);
+    if !folder.is_dir() {
+        return None;
+    }
+    let content = match devcontainer_path.filter(|path| !path.is_empty()) {
+        Some(path) => fs::read_to_string(folder.join(path)).ok()?,
+        None => CONFIG_PATHS
+            .iter()
+            .find_map(|path| fs::read_to_string(folder.join(path)).ok())?,
+    };
+    let config = devcontainer::parse(&content)?;
+
+    let target = Target {
+        provider: provider.as_deref(),
+        variables: environment::names(&app_handle, provider.as_deref(), &[]),
+        host_cpus: thread::available_parallelism().ok().map(usize::from),
+    };
+    let compatibility = check(&config, &target);
+
+    (!compatibility.fields.is_empty()).then_some(compatibility)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_map_codespaces_settings() {
+        let config = json!({
+            "image": "mcr.microsoft.com/devcontainers/base",
+            "hostRequirements": { "cpus": 8, "memory": "16gb", "storage": "64gb" },
+            "secrets": {
+                "GITHUB_TOKEN": {},
+                "API_KEY": { "description": "Key for the staging API" }
+            },
+            "updateContentCommand": "npm ci",
+            "customizations": { "codespaces": { "openFiles": ["README.md"] } }
+        });
+        let target = Target {
+            provider: Some(LOCAL_DOCKER_PROVIDER),
+            variables: HashSet::from(["GITHUB_TOKEN".to_string()]),
+            host_cpus: Some(4),
+        };
+
+        let support = check(&config, &target)
+            .fields
+            .into_iter()
+            .map(|field| (field.path, field.support))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            support,
+            vec![
+                (
+                    "/hostRequirements/cpus".to_string(),
+                    CodespacesSupport::Unsupported
+                ),
+                (
+                    "/hostRequirements/storage".to_string(),
+                    CodespacesSupport::Supported
+                ),
+                ("/secrets/API_KEY".to_string(), CodespacesSupport::Manual),
+                (
+                    "/secrets/GITHUB_TOKEN".to_string(),
+                    CodespacesSupport::Supported
+                ),
+                (
+                    "/updateContentCommand".to_string(),
+                    CodespacesSupport::Manual
+                ),
+                (
+                    "/customizations/codespaces/openFiles".to_string(),
+                    CodespacesSupport::Unsupported
+                ),
+            ]
+        );
+
+        let config = json!({ "image": "alpine", "updateContentCommand": "make" });
+        assert!(check(&config, &Target::default()).fields.is_empty());
+    }
+}
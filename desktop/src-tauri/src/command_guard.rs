@@ -0,0 +1,110 @@
+use crate::{
+    app_lock, debug_console::DEBUG_CONSOLE_WINDOW_LABEL, log_windows,
+    shutdown::SHUTDOWN_WINDOW_LABEL, status_widget::STATUS_WIDGET_WINDOW_LABEL,
+    ui_messages::SETTINGS_WINDOW_LABEL,
+};
+use log::warn;
+use std::{sync::Arc, thread};
+use tauri::{Invoke, Wry};
+
+// Every window negotiates how it receives events
+const COMMON_COMMANDS: &[&str] = &["negotiate_payload_encoding"];
+const DEBUG_CONSOLE_COMMANDS: &[&str] = &["get_debug_snapshot"];
+const LOG_WINDOW_COMMANDS: &[&str] = &[
+    "get_log_window",
+    "set_log_window_filter",
+    "stream_logs",
+    "set_log_stream_filter",
+    "pause_log_stream",
+    "resume_log_stream",
+    "stop_log_stream",
+];
+const SHUTDOWN_COMMANDS: &[&str] = &["quit_now"];
+const STATUS_WIDGET_COMMANDS: &[&str] = &[
+    "get_status_widget_snapshot",
+    "open_widget_workspace",
+    "stop_widget_workspace",
+];
+
+/// The commands a window may call.
+#[derive(Debug, PartialEq, Eq)]
+enum CommandScope {
+    All,
+    Only(&'static [&'static str]),
+}
+
+fn scope(label: &str) -> CommandScope {
+    match label {
+        // The settings window shows the same views as the main window
+        "main" | SETTINGS_WINDOW_LABEL => CommandScope::All,
+        DEBUG_CONSOLE_WINDOW_LABEL => CommandScope::Only(DEBUG_CONSOLE_COMMANDS),
+        SHUTDOWN_WINDOW_LABEL => CommandScope::Only(SHUTDOWN_COMMANDS),
+        STATUS_WIDGET_WINDOW_LABEL => CommandScope::Only(STATUS_WIDGET_COMMANDS),
+        label if log_windows::is_log_window(label) => CommandScope::Only(LOG_WINDOW_COMMANDS),
+        // e.g. the update window, which only uses the Tauri API
+        _ => CommandScope::Only(&[]),
+    }
+}
+
+fn is_allowed(label: &str, command: &str) -> bool {
+    if COMMON_COMMANDS.contains(&command) {
+        return true;
+    }
+
+    match scope(label) {
+        CommandScope::All => true,
+        CommandScope::Only(commands) => commands.contains(&command),
+    }
+}
+
+/// Wraps the invoke handler so that windows can only call the commands they need, e.g. a log window can't delete a
+/// workspace. Limits what a compromised or buggy window can do. Destructive commands additionally wait for the app
+/// lock to be opened.
+pub fn guard<F>(handler: F) -> impl Fn(Invoke<Wry>) + Send + Sync + 'static
+where
+    F: Fn(Invoke<Wry>) + Send + Sync + 'static,
+{
+    let handler = Arc::new(handler);
+    move |invoke| {
+        let label = invoke.message.window().label().to_string();
+        let command = invoke.message.command().to_string();
+        if !is_allowed(&label, &command) {
+            warn!("Window {} is not allowed to call {}", label, command);
+            invoke
+                .resolver
+                .reject(format!("{} is not allowed in this window", command));
+            return;
+        }
+
+        match app_lock::protected_reason(&command) {
+            // Authenticating blocks until the user is done
+            Some(reason) => {
+                let handler = handler.clone();
+                thread::spawn(move || match app_lock::ensure_unlocked(reason) {
+                    Ok(()) => handler(invoke),
+                    Err(err) => invoke.resolver.reject(err.to_string()),
+                });
+            }
+            None => handler(invoke),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_scope_commands_by_window() {
+        assert!(is_allowed("main", "prune_docker"));
+        assert!(is_allowed(SETTINGS_WINDOW_LABEL, "install_cli"));
+        assert!(is_allowed("logs-my-project", "stream_logs"));
+        assert!(!is_allowed("logs-my-project", "prune_docker"));
+        assert!(!is_allowed("logs-my-project", "stop_widget_workspace"));
+        assert!(!is_allowed("logs-my-project", "run_cli"));
+        assert!(!is_allowed(STATUS_WIDGET_WINDOW_LABEL, "quit_now"));
+        assert!(is_allowed("update_ready", "negotiate_payload_encoding"));
+        assert!(!is_allowed("update_ready", "get_debug_snapshot"));
+        assert_eq!(scope("unknown"), CommandScope::Only(&[]));
+    }
+}
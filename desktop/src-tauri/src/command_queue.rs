@@ -0,0 +1,259 @@
+use crate::{process::ProcessError, redaction, settings::Settings, workspaces, AppHandle};
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Condvar, Mutex,
+    },
+    time::Duration,
+};
+use tauri::Manager;
+use ts_rs::TS;
+
+lazy_static! {
+    static ref QUEUE: (Mutex<QueueState>, Condvar) =
+        (Mutex::new(QueueState::default()), Condvar::new());
+}
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+// WARN: needs to match the event name in `client.ts`
+const QUEUE_CHANGED_EVENT: &str = "queue_changed";
+// Waiting commands check this often whether they have been cancelled in the meantime
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Commands the user is waiting for start before background refreshes.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS,
+)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum QueuePriority {
+    Background,
+    #[default]
+    User,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct QueuedCommand {
+    #[ts(type = "number")]
+    id: u64,
+    priority: QueuePriority,
+    provider: Option<String>,
+    program: String,
+    /// Redacted
+    args: String,
+    workspace_id: Option<String>,
+    queued_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct QueueStatus {
+    running: Vec<QueuedCommand>,
+    /// In the order they would start if there were no limits
+    waiting: Vec<QueuedCommand>,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    running: Vec<QueuedCommand>,
+    waiting: Vec<QueuedCommand>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Limits {
+    /// `0` doesn't limit the number of commands
+    max_concurrent: usize,
+    providers: HashMap<String, usize>,
+}
+
+impl Limits {
+    fn load(app_handle: &AppHandle) -> Self {
+        Self {
+            max_concurrent: Settings::max_concurrent_commands(app_handle) as usize,
+            providers: Settings::provider_concurrency_limits(app_handle)
+                .into_iter()
+                .map(|(provider, limit)| (provider, limit as usize))
+                .collect(),
+        }
+    }
+}
+
+impl QueueState {
+    fn fits(&self, command: &QueuedCommand, limits: &Limits) -> bool {
+        if limits.max_concurrent > 0 && self.running.len() >= limits.max_concurrent {
+            return false;
+        }
+        let Some(provider) = &command.provider else {
+            return true;
+        };
+
+        limits.providers.get(provider).map_or(true, |limit| {
+            self.running
+                .iter()
+                .filter(|running| running.provider.as_ref() == Some(provider))
+                .count()
+                < *limit
+        })
+    }
+
+    /// The waiting command that starts next, by priority and in the order they were queued. A command held back by
+    /// the limit of its provider doesn't hold up the others.
+    fn next(&self, limits: &Limits) -> Option<u64> {
+        self.waiting
+            .iter()
+            .filter(|command| self.fits(command, limits))
+            .min_by_key(|command| (std::cmp::Reverse(command.priority), command.id))
+            .map(|command| command.id)
+    }
+
+    fn status(&self) -> QueueStatus {
+        let mut waiting = self.waiting.clone();
+        waiting.sort_by_key(|command| (std::cmp::Reverse(command.priority), command.id));
+
+        QueueStatus {
+            running: self.running.clone(),
+            waiting,
+        }
+    }
+}
+
+fn changed(app_handle: &AppHandle, state: &QueueState) {
+    if let Err(err) = app_handle.emit_all(QUEUE_CHANGED_EVENT, state.status()) {
+        warn!("Failed to send queue status: {}", err);
+    }
+}
+
+/// Holds a place among the running commands until it's dropped.
+#[must_use]
+#[derive(Debug)]
+pub struct Slot {
+    id: u64,
+    app_handle: AppHandle,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let (lock, condvar) = &*QUEUE;
+        let mut state = lock.lock().unwrap();
+        state.running.retain(|command| command.id != self.id);
+        changed(&self.app_handle, &state);
+        condvar.notify_all();
+    }
+}
+
+/// Waits until the command may start, `check` ends waiting early with its error, e.g. once the command is cancelled.
+pub fn acquire(
+    app_handle: &AppHandle,
+    priority: QueuePriority,
+    provider: Option<&str>,
+    program: &str,
+    args: &[String],
+    workspace_id: Option<&str>,
+    check: impl Fn() -> Result<(), ProcessError>,
+) -> Result<Slot, ProcessError> {
+    let provider = provider.map(String::from).or_else(|| {
+        workspace_id.and_then(|workspace_id| workspaces::provider(app_handle, workspace_id))
+    });
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let limits = Limits::load(app_handle);
+
+    let (lock, condvar) = &*QUEUE;
+    let mut state = lock.lock().unwrap();
+    state.waiting.push(QueuedCommand {
+        id,
+        priority,
+        provider,
+        program: program.to_string(),
+        args: redaction::redact(&args.join(" ")).into_owned(),
+        workspace_id: workspace_id.map(String::from),
+        queued_at: Utc::now(),
+    });
+    loop {
+        if let Err(err) = check() {
+            state.waiting.retain(|command| command.id != id);
+            changed(app_handle, &state);
+            // Its place might have been what held up another command
+            condvar.notify_all();
+            return Err(err);
+        }
+        if state.next(&limits) == Some(id) {
+            let position = state
+                .waiting
+                .iter()
+                .position(|command| command.id == id)
+                .unwrap();
+            let command = state.waiting.remove(position);
+            state.running.push(command);
+            changed(app_handle, &state);
+            return Ok(Slot {
+                id,
+                app_handle: app_handle.clone(),
+            });
+        }
+
+        state = condvar
+            .wait_timeout(state, CANCEL_CHECK_INTERVAL)
+            .unwrap()
+            .0;
+    }
+}
+
+/// Commands waiting for a place, for the debug console.
+pub fn waiting() -> usize {
+    QUEUE.0.lock().unwrap().waiting.len()
+}
+
+#[tauri::command]
+pub fn get_command_queue() -> QueueStatus {
+    QUEUE.0.lock().unwrap().status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(id: u64, priority: QueuePriority, provider: Option<&str>) -> QueuedCommand {
+        QueuedCommand {
+            id,
+            priority,
+            provider: provider.map(String::from),
+            program: "devpod-cli".to_string(),
+            args: "up".to_string(),
+            workspace_id: None,
+            queued_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn should_start_by_priority_within_provider_limits() {
+        let limits = Limits {
+            max_concurrent: 2,
+            providers: HashMap::from([("aws".to_string(), 1)]),
+        };
+        let mut state = QueueState {
+            running: vec![command(0, QueuePriority::User, Some("aws"))],
+            waiting: vec![
+                command(1, QueuePriority::Background, None),
+                command(2, QueuePriority::User, Some("aws")),
+                command(3, QueuePriority::User, Some("docker")),
+            ],
+        };
+        assert_eq!(state.next(&limits), Some(3));
+
+        state
+            .running
+            .push(command(3, QueuePriority::User, Some("docker")));
+        state.waiting.retain(|command| command.id != 3);
+        assert_eq!(state.next(&limits), None);
+
+        state.running.remove(0);
+        assert_eq!(state.next(&limits), Some(2));
+    }
+}
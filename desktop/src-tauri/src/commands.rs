@@ -3,7 +3,18 @@ pub mod constants;
 pub use config::{DevpodCommandConfig, DevpodCommandError};
 pub use constants::DEVPOD_BINARY_NAME;
 
+pub mod add_provider;
+pub mod completion;
 pub mod delete_provider;
+pub mod delete_workspace;
 pub mod delete_pro_instance;
 pub mod list_workspaces;
 pub mod list_pro_instances;
+pub mod list_providers;
+pub mod provider_options;
+pub mod set_provider_options;
+pub mod stop_workspace;
+pub mod version;
+pub mod workspace_logs;
+pub mod workspace_ssh;
+pub mod workspace_status;
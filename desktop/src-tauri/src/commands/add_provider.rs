@@ -0,0 +1,29 @@
+use super::{
+    config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
+    constants::{DEVPOD_BINARY_NAME, DEVPOD_COMMAND_ADD, DEVPOD_COMMAND_PROVIDER},
+};
+use crate::AppHandle;
+
+pub struct AddProviderCommand {
+    provider: String,
+}
+impl AddProviderCommand {
+    pub fn new(provider: String) -> Self {
+        AddProviderCommand { provider }
+    }
+}
+impl DevpodCommandConfig<()> for AddProviderCommand {
+    fn config(&self) -> CommandConfig {
+        CommandConfig {
+            binary_name: DEVPOD_BINARY_NAME,
+            args: vec![DEVPOD_COMMAND_PROVIDER, DEVPOD_COMMAND_ADD, &self.provider],
+        }
+    }
+
+    fn exec(self, app_handle: &AppHandle) -> Result<(), DevpodCommandError> {
+        self.status(app_handle)?
+            .success()
+            .then_some(())
+            .ok_or_else(|| DevpodCommandError::Exit)
+    }
+}
@@ -0,0 +1,31 @@
+use super::{
+    config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
+    constants::{DEVPOD_BINARY_NAME, DEVPOD_COMMAND_COMPLETION},
+};
+use crate::AppHandle;
+
+pub struct CompletionCommand {
+    shell: &'static str,
+}
+impl CompletionCommand {
+    pub fn new(shell: &'static str) -> Self {
+        CompletionCommand { shell }
+    }
+}
+impl DevpodCommandConfig<String> for CompletionCommand {
+    fn config(&self) -> CommandConfig {
+        CommandConfig {
+            binary_name: DEVPOD_BINARY_NAME,
+            args: vec![DEVPOD_COMMAND_COMPLETION, self.shell],
+        }
+    }
+
+    fn exec(self, app_handle: &AppHandle) -> Result<String, DevpodCommandError> {
+        let output = self.output(app_handle)?;
+        if !output.status.success() {
+            return Err(DevpodCommandError::Exit);
+        }
+
+        Ok(output.stdout)
+    }
+}
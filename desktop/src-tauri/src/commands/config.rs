@@ -1,11 +1,13 @@
-use std::collections::HashMap;
-
-use tauri::api::process::Command;
+use chrono::Utc;
 use thiserror::Error;
 
-use crate::commands::constants::DEVPOD_BINARY_NAME;
-
-use super::constants::DEVPOD_UI_ENV_VAR;
+use crate::{
+    command_queue::QueuePriority,
+    commands::constants::DEVPOD_BINARY_NAME,
+    operation_history::{self, OperationRecord},
+    process::{ExitStatus, Output, ProcessError, SupervisedCommand},
+    AppHandle,
+};
 
 pub struct CommandConfig<'a> {
     pub(crate) binary_name: &'static str,
@@ -34,6 +36,25 @@ pub enum DevpodCommandError {
     Failed(#[from] tauri::api::Error),
     #[error("command exited with non-zero code")]
     Exit,
+    #[error("command has been cancelled")]
+    Cancelled,
+    #[error("command timed out")]
+    TimedOut,
+    #[error("the cli failed its integrity check")]
+    Integrity,
+}
+impl From<ProcessError> for DevpodCommandError {
+    fn from(err: ProcessError) -> Self {
+        match err {
+            ProcessError::Sidecar => DevpodCommandError::Sidecar,
+            ProcessError::Spawn(_, err) => DevpodCommandError::Failed(err),
+            ProcessError::Cancelled(_) | ProcessError::ShuttingDown => {
+                DevpodCommandError::Cancelled
+            }
+            ProcessError::TimedOut(..) => DevpodCommandError::TimedOut,
+            ProcessError::Integrity(_) => DevpodCommandError::Integrity,
+        }
+    }
 }
 impl serde::Serialize for DevpodCommandError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -50,18 +71,58 @@ pub trait DevpodCommandConfig<T> {
             args: vec![],
         }
     }
-    fn exec(self) -> Result<T, DevpodCommandError>;
+    fn exec(self, app_handle: &AppHandle) -> Result<T, DevpodCommandError>;
+
+    /// The workspace this command operates on, used to correlate it in the operation history.
+    fn workspace_id(&self) -> Option<&str> {
+        None
+    }
+
+    /// The operation this command is a step of, cancelling the operation kills it.
+    fn operation_id(&self) -> Option<&str> {
+        None
+    }
 
-    fn new_command(&self) -> Result<Command, DevpodCommandError> {
+    /// Background refreshes wait for commands the user started.
+    fn priority(&self) -> QueuePriority {
+        QueuePriority::User
+    }
+
+    fn new_command(&self, app_handle: &AppHandle) -> SupervisedCommand {
         let config = self.config();
-        let env_vars: HashMap<String, String> =
-            HashMap::from([(DEVPOD_UI_ENV_VAR.into(), "true".into())]);
 
-        let cmd = Command::new_sidecar(config.binary_name())
-            .map_err(|_| DevpodCommandError::Sidecar)?
-            .envs(env_vars)
-            .args(config.args());
+        SupervisedCommand::sidecar(app_handle, config.binary_name())
+            .args(config.args())
+            .workspace(self.workspace_id())
+            .operation(self.operation_id())
+            .priority(self.priority())
+    }
 
-        Ok(cmd)
+    /// Runs the command to completion, collecting its output and recording it in the operation history.
+    fn output(&self, app_handle: &AppHandle) -> Result<Output, DevpodCommandError> {
+        let started_at = Utc::now();
+        let output = self.new_command(app_handle).run(|_, _| {});
+        self.record(
+            started_at,
+            output.as_ref().ok().and_then(|o| o.status.code()),
+        );
+
+        Ok(output?)
+    }
+
+    /// Runs the command to completion and records it in the operation history.
+    fn status(&self, app_handle: &AppHandle) -> Result<ExitStatus, DevpodCommandError> {
+        self.output(app_handle).map(|output| output.status)
+    }
+
+    fn record(&self, started_at: chrono::DateTime<Utc>, exit_code: Option<i32>) {
+        let config = self.config();
+        operation_history::record(OperationRecord::new(
+            config.binary_name(),
+            config.args(),
+            self.workspace_id(),
+            started_at,
+            exit_code,
+        ));
     }
 }
@@ -6,10 +6,23 @@ pub(super) const DEVPOD_COMMAND_LIST: &str = "list";
 pub(super) const DEVPOD_COMMAND_PROVIDER: &str = "provider";
 pub(super) const DEVPOD_COMMAND_PRO: &str = "pro";
 pub(super) const DEVPOD_COMMAND_DELETE: &str = "delete";
+pub(super) const DEVPOD_COMMAND_ADD: &str = "add";
+pub(super) const DEVPOD_COMMAND_VERSION: &str = "version";
+pub(super) const DEVPOD_COMMAND_LOGS: &str = "logs";
+pub(super) const DEVPOD_COMMAND_STOP: &str = "stop";
+pub(super) const DEVPOD_COMMAND_STATUS: &str = "status";
+pub(super) const DEVPOD_COMMAND_SSH: &str = "ssh";
+pub(super) const DEVPOD_COMMAND_COMPLETION: &str = "completion";
+pub(super) const DEVPOD_COMMAND_OPTIONS: &str = "options";
+pub(super) const DEVPOD_COMMAND_SET_OPTIONS: &str = "set-options";
 
 // Flags
 pub(super) const FLAG_OUTPUT_JSON: &str = "--output=json";
 pub(super) const FLAG_IGNORE_NOT_FOUND: &str = "--ignore-not-found";
+pub(super) const FLAG_COMMAND: &str = "--command";
+pub(super) const FLAG_LOG_OUTPUT_JSON: &str = "--log-output=json";
+pub(super) const FLAG_FORCE: &str = "--force";
+pub(super) const FLAG_OPTION: &str = "--option";
 
 // Env vars
-pub(super) const DEVPOD_UI_ENV_VAR: &str = "DEVPOD_UI";
+pub(crate) const DEVPOD_UI_ENV_VAR: &str = "DEVPOD_UI";
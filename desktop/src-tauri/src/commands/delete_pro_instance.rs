@@ -4,6 +4,7 @@ use super::{
         DEVPOD_BINARY_NAME, DEVPOD_COMMAND_DELETE, DEVPOD_COMMAND_PRO, FLAG_IGNORE_NOT_FOUND,
     },
 };
+use crate::AppHandle;
 
 pub struct DeleteProInstanceCommand {
     pro_id: String,
@@ -26,11 +27,8 @@ impl DevpodCommandConfig<()> for DeleteProInstanceCommand {
         }
     }
 
-    fn exec(self) -> Result<(), DevpodCommandError> {
-        let cmd = self.new_command()?;
-
-        cmd.status()
-            .map_err(DevpodCommandError::Failed)?
+    fn exec(self, app_handle: &AppHandle) -> Result<(), DevpodCommandError> {
+        self.status(app_handle)?
             .success()
             .then_some(())
             .ok_or_else(|| DevpodCommandError::Exit)
@@ -1,3 +1,8 @@
+use crate::{
+    cli_cache::{self, CliCacheScope},
+    AppHandle,
+};
+
 use super::{
     config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
     constants::{DEVPOD_BINARY_NAME, DEVPOD_COMMAND_DELETE, DEVPOD_COMMAND_PROVIDER},
@@ -23,11 +28,11 @@ impl DevpodCommandConfig<()> for DeleteProviderCommand {
         }
     }
 
-    fn exec(self) -> Result<(), DevpodCommandError> {
-        let cmd = self.new_command()?;
+    fn exec(self, app_handle: &AppHandle) -> Result<(), DevpodCommandError> {
+        let status = self.status(app_handle);
+        cli_cache::invalidate(&CliCacheScope::Provider(self.provider_id.clone()));
 
-        cmd.status()
-            .map_err(DevpodCommandError::Failed)?
+        status?
             .success()
             .then_some(())
             .ok_or_else(|| DevpodCommandError::Exit)
@@ -0,0 +1,52 @@
+use crate::{
+    cli_cache::{self, CliCacheScope},
+    process::Output,
+    AppHandle,
+};
+
+use super::{
+    config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
+    constants::{DEVPOD_BINARY_NAME, DEVPOD_COMMAND_DELETE, FLAG_FORCE, FLAG_LOG_OUTPUT_JSON},
+};
+
+pub struct DeleteWorkspaceCommand {
+    workspace_id: String,
+    force: bool,
+}
+impl DeleteWorkspaceCommand {
+    pub fn new(workspace_id: String, force: bool) -> Self {
+        DeleteWorkspaceCommand {
+            workspace_id,
+            force,
+        }
+    }
+}
+impl DevpodCommandConfig<Output> for DeleteWorkspaceCommand {
+    fn config(&self) -> CommandConfig {
+        let mut args = vec![
+            DEVPOD_COMMAND_DELETE,
+            &self.workspace_id,
+            FLAG_LOG_OUTPUT_JSON,
+        ];
+        if self.force {
+            args.push(FLAG_FORCE);
+        }
+
+        CommandConfig {
+            binary_name: DEVPOD_BINARY_NAME,
+            args,
+        }
+    }
+
+    fn workspace_id(&self) -> Option<&str> {
+        Some(&self.workspace_id)
+    }
+
+    /// The output of the CLI whether it succeeded or not, its logs explain what went wrong.
+    fn exec(self, app_handle: &AppHandle) -> Result<Output, DevpodCommandError> {
+        let output = self.output(app_handle);
+        cli_cache::invalidate(&CliCacheScope::Workspace(self.workspace_id.clone()));
+
+        output
+    }
+}
@@ -4,6 +4,7 @@ use super::{
     config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
     constants::{DEVPOD_BINARY_NAME, DEVPOD_COMMAND_LIST, DEVPOD_COMMAND_PRO, FLAG_OUTPUT_JSON},
 };
+use crate::AppHandle;
 
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 #[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
@@ -27,6 +28,13 @@ impl ListProInstancesCommand {
     fn deserialize(&self, str: &str) -> Result<Vec<ProInstance>, DevpodCommandError> {
         serde_json::from_str(str).map_err(DevpodCommandError::Parse)
     }
+
+    /// The instances as the CLI reports them, `ProInstance` only keeps what the backend needs.
+    pub fn exec_raw(self, app_handle: &AppHandle) -> Result<serde_json::Value, DevpodCommandError> {
+        let output = self.output(app_handle)?;
+
+        serde_json::from_str(&output.stdout).map_err(DevpodCommandError::Parse)
+    }
 }
 impl DevpodCommandConfig<Vec<ProInstance>> for ListProInstancesCommand {
     fn config(&self) -> CommandConfig {
@@ -36,11 +44,8 @@ impl DevpodCommandConfig<Vec<ProInstance>> for ListProInstancesCommand {
         }
     }
 
-    fn exec(self) -> Result<Vec<ProInstance>, DevpodCommandError> {
-        let output = self
-            .new_command()?
-            .output()
-            .map_err(|_| DevpodCommandError::Output)?;
+    fn exec(self, app_handle: &AppHandle) -> Result<Vec<ProInstance>, DevpodCommandError> {
+        let output = self.output(app_handle)?;
 
         self.deserialize(&output.stdout)
     }
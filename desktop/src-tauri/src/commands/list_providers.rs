@@ -0,0 +1,43 @@
+use super::{
+    config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
+    constants::{
+        DEVPOD_BINARY_NAME, DEVPOD_COMMAND_LIST, DEVPOD_COMMAND_PROVIDER, FLAG_OUTPUT_JSON,
+    },
+};
+use crate::{command_queue::QueuePriority, AppHandle};
+
+pub struct ListProvidersCommand {}
+impl ListProvidersCommand {
+    pub fn new() -> Self {
+        ListProvidersCommand {}
+    }
+
+    fn deserialize(&self, str: &str) -> Result<serde_json::Value, DevpodCommandError> {
+        serde_json::from_str(str).map_err(DevpodCommandError::Parse)
+    }
+}
+impl DevpodCommandConfig<serde_json::Value> for ListProvidersCommand {
+    fn config(&self) -> CommandConfig {
+        CommandConfig {
+            binary_name: DEVPOD_BINARY_NAME,
+            args: vec![
+                DEVPOD_COMMAND_PROVIDER,
+                DEVPOD_COMMAND_LIST,
+                FLAG_OUTPUT_JSON,
+            ],
+        }
+    }
+
+    fn priority(&self) -> QueuePriority {
+        QueuePriority::Background
+    }
+
+    fn exec(self, app_handle: &AppHandle) -> Result<serde_json::Value, DevpodCommandError> {
+        let output = self.output(app_handle)?;
+        if !output.status.success() {
+            return Err(DevpodCommandError::Exit);
+        }
+
+        self.deserialize(&output.stdout)
+    }
+}
@@ -2,7 +2,7 @@ use super::{
     config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
     constants::{DEVPOD_BINARY_NAME, DEVPOD_COMMAND_LIST, FLAG_OUTPUT_JSON},
 };
-use crate::workspaces::WorkspacesState;
+use crate::{command_queue::QueuePriority, workspaces::WorkspacesState, AppHandle};
 
 pub struct ListWorkspacesCommand {}
 impl ListWorkspacesCommand {
@@ -13,6 +13,18 @@ impl ListWorkspacesCommand {
     fn deserialize(&self, str: &str) -> Result<WorkspacesState, DevpodCommandError> {
         serde_json::from_str(str).map_err(DevpodCommandError::Parse)
     }
+
+    /// The workspaces along with everything the CLI reports about them, `WorkspacesState` only keeps what the
+    /// backend needs.
+    pub fn exec_with_raw(
+        self,
+        app_handle: &AppHandle,
+    ) -> Result<(WorkspacesState, Vec<serde_json::Value>), DevpodCommandError> {
+        let output = self.output(app_handle)?;
+        let raw = serde_json::from_str(&output.stdout).map_err(DevpodCommandError::Parse)?;
+
+        Ok((self.deserialize(&output.stdout)?, raw))
+    }
 }
 impl DevpodCommandConfig<WorkspacesState> for ListWorkspacesCommand {
     fn config(&self) -> CommandConfig {
@@ -22,11 +34,12 @@ impl DevpodCommandConfig<WorkspacesState> for ListWorkspacesCommand {
         }
     }
 
-    fn exec(self) -> Result<WorkspacesState, DevpodCommandError> {
-        let output = self
-            .new_command()?
-            .output()
-            .map_err(|_| DevpodCommandError::Output)?;
+    fn priority(&self) -> QueuePriority {
+        QueuePriority::Background
+    }
+
+    fn exec(self, app_handle: &AppHandle) -> Result<WorkspacesState, DevpodCommandError> {
+        let output = self.output(app_handle)?;
 
         self.deserialize(&output.stdout)
     }
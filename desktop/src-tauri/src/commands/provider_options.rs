@@ -0,0 +1,42 @@
+use super::{
+    config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
+    constants::{
+        DEVPOD_BINARY_NAME, DEVPOD_COMMAND_OPTIONS, DEVPOD_COMMAND_PROVIDER, FLAG_OUTPUT_JSON,
+    },
+};
+use crate::AppHandle;
+
+pub struct ProviderOptionsCommand {
+    provider: String,
+}
+impl ProviderOptionsCommand {
+    pub fn new(provider: String) -> Self {
+        ProviderOptionsCommand { provider }
+    }
+
+    fn deserialize(&self, str: &str) -> Result<serde_json::Value, DevpodCommandError> {
+        serde_json::from_str(str).map_err(DevpodCommandError::Parse)
+    }
+}
+impl DevpodCommandConfig<serde_json::Value> for ProviderOptionsCommand {
+    fn config(&self) -> CommandConfig {
+        CommandConfig {
+            binary_name: DEVPOD_BINARY_NAME,
+            args: vec![
+                DEVPOD_COMMAND_PROVIDER,
+                DEVPOD_COMMAND_OPTIONS,
+                &self.provider,
+                FLAG_OUTPUT_JSON,
+            ],
+        }
+    }
+
+    fn exec(self, app_handle: &AppHandle) -> Result<serde_json::Value, DevpodCommandError> {
+        let output = self.output(app_handle)?;
+        if !output.status.success() {
+            return Err(DevpodCommandError::Exit);
+        }
+
+        self.deserialize(&output.stdout)
+    }
+}
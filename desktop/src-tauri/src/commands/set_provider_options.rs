@@ -0,0 +1,55 @@
+use crate::{
+    cli_cache::{self, CliCacheScope},
+    AppHandle,
+};
+
+use super::{
+    config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
+    constants::{
+        DEVPOD_BINARY_NAME, DEVPOD_COMMAND_PROVIDER, DEVPOD_COMMAND_SET_OPTIONS, FLAG_OPTION,
+    },
+};
+
+pub struct SetProviderOptionsCommand {
+    provider: String,
+    /// `NAME=value` pairs, an empty value resets the option to its default
+    options: Vec<String>,
+}
+impl SetProviderOptionsCommand {
+    pub fn new(provider: String, options: &[(&str, &str)]) -> Self {
+        SetProviderOptionsCommand {
+            provider,
+            options: options
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect(),
+        }
+    }
+}
+impl DevpodCommandConfig<()> for SetProviderOptionsCommand {
+    fn config(&self) -> CommandConfig {
+        let mut args = vec![
+            DEVPOD_COMMAND_PROVIDER,
+            DEVPOD_COMMAND_SET_OPTIONS,
+            &self.provider,
+        ];
+        for option in &self.options {
+            args.extend([FLAG_OPTION, option.as_str()]);
+        }
+
+        CommandConfig {
+            binary_name: DEVPOD_BINARY_NAME,
+            args,
+        }
+    }
+
+    fn exec(self, app_handle: &AppHandle) -> Result<(), DevpodCommandError> {
+        let status = self.status(app_handle);
+        cli_cache::invalidate(&CliCacheScope::Provider(self.provider.clone()));
+
+        status?
+            .success()
+            .then_some(())
+            .ok_or_else(|| DevpodCommandError::Exit)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::{
+    cli_cache::{self, CliCacheScope},
+    AppHandle,
+};
+
+use super::{
+    config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
+    constants::{DEVPOD_BINARY_NAME, DEVPOD_COMMAND_STOP},
+};
+
+pub struct StopWorkspaceCommand {
+    workspace_id: String,
+}
+impl StopWorkspaceCommand {
+    pub fn new(workspace_id: String) -> Self {
+        StopWorkspaceCommand { workspace_id }
+    }
+}
+impl DevpodCommandConfig<()> for StopWorkspaceCommand {
+    fn config(&self) -> CommandConfig {
+        CommandConfig {
+            binary_name: DEVPOD_BINARY_NAME,
+            args: vec![DEVPOD_COMMAND_STOP, &self.workspace_id],
+        }
+    }
+
+    fn workspace_id(&self) -> Option<&str> {
+        Some(&self.workspace_id)
+    }
+
+    fn exec(self, app_handle: &AppHandle) -> Result<(), DevpodCommandError> {
+        let status = self.status(app_handle);
+        // Even a failed stop may have gotten the workspace halfway down
+        cli_cache::invalidate(&CliCacheScope::Workspace(self.workspace_id.clone()));
+
+        status?
+            .success()
+            .then_some(())
+            .ok_or_else(|| DevpodCommandError::Exit)
+    }
+}
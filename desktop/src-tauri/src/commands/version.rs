@@ -0,0 +1,29 @@
+use super::{
+    config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
+    constants::{DEVPOD_BINARY_NAME, DEVPOD_COMMAND_VERSION},
+};
+use crate::AppHandle;
+
+pub struct VersionCommand {}
+impl VersionCommand {
+    pub fn new() -> Self {
+        VersionCommand {}
+    }
+}
+impl DevpodCommandConfig<String> for VersionCommand {
+    fn config(&self) -> CommandConfig {
+        CommandConfig {
+            binary_name: DEVPOD_BINARY_NAME,
+            args: vec![DEVPOD_COMMAND_VERSION],
+        }
+    }
+
+    fn exec(self, app_handle: &AppHandle) -> Result<String, DevpodCommandError> {
+        let output = self.output(app_handle)?;
+        if !output.status.success() {
+            return Err(DevpodCommandError::Exit);
+        }
+
+        Ok(output.stdout.trim().to_string())
+    }
+}
@@ -0,0 +1,35 @@
+use super::{
+    config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
+    constants::{DEVPOD_BINARY_NAME, DEVPOD_COMMAND_LOGS},
+};
+use crate::AppHandle;
+
+pub struct WorkspaceLogsCommand {
+    workspace_id: String,
+}
+impl WorkspaceLogsCommand {
+    pub fn new(workspace_id: String) -> Self {
+        WorkspaceLogsCommand { workspace_id }
+    }
+}
+impl DevpodCommandConfig<String> for WorkspaceLogsCommand {
+    fn config(&self) -> CommandConfig {
+        CommandConfig {
+            binary_name: DEVPOD_BINARY_NAME,
+            args: vec![DEVPOD_COMMAND_LOGS, &self.workspace_id],
+        }
+    }
+
+    fn workspace_id(&self) -> Option<&str> {
+        Some(&self.workspace_id)
+    }
+
+    fn exec(self, app_handle: &AppHandle) -> Result<String, DevpodCommandError> {
+        let output = self.output(app_handle)?;
+        if !output.status.success() {
+            return Err(DevpodCommandError::Exit);
+        }
+
+        Ok(output.stdout)
+    }
+}
@@ -0,0 +1,45 @@
+use super::{
+    config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
+    constants::{DEVPOD_BINARY_NAME, DEVPOD_COMMAND_SSH, FLAG_COMMAND},
+};
+use crate::AppHandle;
+
+/// Runs a shell command in a running workspace.
+pub struct WorkspaceSshCommand {
+    workspace_id: String,
+    command: String,
+}
+impl WorkspaceSshCommand {
+    pub fn new(workspace_id: String, command: String) -> Self {
+        WorkspaceSshCommand {
+            workspace_id,
+            command,
+        }
+    }
+}
+impl DevpodCommandConfig<String> for WorkspaceSshCommand {
+    fn config(&self) -> CommandConfig {
+        CommandConfig {
+            binary_name: DEVPOD_BINARY_NAME,
+            args: vec![
+                DEVPOD_COMMAND_SSH,
+                &self.workspace_id,
+                FLAG_COMMAND,
+                &self.command,
+            ],
+        }
+    }
+
+    fn workspace_id(&self) -> Option<&str> {
+        Some(&self.workspace_id)
+    }
+
+    fn exec(self, app_handle: &AppHandle) -> Result<String, DevpodCommandError> {
+        let output = self.output(app_handle)?;
+        if !output.status.success() {
+            return Err(DevpodCommandError::Exit);
+        }
+
+        Ok(output.stdout)
+    }
+}
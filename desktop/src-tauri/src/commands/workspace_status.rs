@@ -0,0 +1,53 @@
+use serde::Deserialize;
+
+use crate::{command_queue::QueuePriority, AppHandle};
+
+use super::{
+    config::{CommandConfig, DevpodCommandConfig, DevpodCommandError},
+    constants::{DEVPOD_BINARY_NAME, DEVPOD_COMMAND_STATUS, FLAG_OUTPUT_JSON},
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceStatus {
+    state: String,
+}
+
+pub struct WorkspaceStatusCommand {
+    workspace_id: String,
+}
+impl WorkspaceStatusCommand {
+    pub fn new(workspace_id: String) -> Self {
+        WorkspaceStatusCommand { workspace_id }
+    }
+
+    fn deserialize(&self, str: &str) -> Result<WorkspaceStatus, DevpodCommandError> {
+        serde_json::from_str(str).map_err(DevpodCommandError::Parse)
+    }
+}
+impl DevpodCommandConfig<String> for WorkspaceStatusCommand {
+    fn config(&self) -> CommandConfig {
+        CommandConfig {
+            binary_name: DEVPOD_BINARY_NAME,
+            args: vec![DEVPOD_COMMAND_STATUS, &self.workspace_id, FLAG_OUTPUT_JSON],
+        }
+    }
+
+    fn workspace_id(&self) -> Option<&str> {
+        Some(&self.workspace_id)
+    }
+
+    fn priority(&self) -> QueuePriority {
+        QueuePriority::Background
+    }
+
+    /// The state as reported by the provider, e.g. `Running` or `Stopped`.
+    fn exec(self, app_handle: &AppHandle) -> Result<String, DevpodCommandError> {
+        let output = self.output(app_handle)?;
+        if !output.status.success() {
+            return Err(DevpodCommandError::Exit);
+        }
+
+        Ok(self.deserialize(&output.stdout)?.state)
+    }
+}
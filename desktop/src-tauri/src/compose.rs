@@ -0,0 +1,634 @@
+use crate::{
+    devcontainer::{self, CONFIG_PATHS},
+    docker_contexts::{self, DockerContextError},
+    workspaces, AppHandle,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+const COMPOSE_SIDECARS_FILE_NAME: &str = "compose_sidecars.json";
+const PROJECT_LABEL: &str = "com.docker.compose.project";
+const SERVICE_LABEL: &str = "com.docker.compose.service";
+
+#[derive(Error, Debug)]
+pub enum ComposeError {
+    #[error("unable to read compose file {0}")]
+    Read(String, #[source] io::Error),
+    #[error("unable to parse compose file {0}: {1}")]
+    Parse(String, #[source] serde_yaml::Error),
+    #[error("workspace {0} doesn't exist")]
+    UnknownWorkspace(String),
+    #[error("{0} isn't a service of the compose project")]
+    UnknownService(String),
+    #[error("{0} is the service the workspace runs in")]
+    PrimaryService(String),
+    #[error("unable to run docker compose: {0}")]
+    Docker(String),
+    #[error(transparent)]
+    DockerContext(#[from] DockerContextError),
+    #[error("unable to save compose sidecars")]
+    Persist(#[source] anyhow::Error),
+    #[error("unable to manage compose sidecars")]
+    Join(#[source] tauri::Error),
+}
+impl serde::Serialize for ComposeError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ComposeFile {
+    #[serde(default)]
+    services: BTreeMap<String, ComposeFileService>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ComposeFileService {
+    image: Option<String>,
+    #[serde(default)]
+    depends_on: DependsOn,
+}
+
+/// `depends_on` is either a list of services or a map of services to their conditions.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum DependsOn {
+    List(Vec<String>),
+    Map(BTreeMap<String, serde_yaml::Value>),
+}
+
+impl Default for DependsOn {
+    fn default() -> Self {
+        DependsOn::List(vec![])
+    }
+}
+
+impl DependsOn {
+    fn into_services(self) -> Vec<String> {
+        match self {
+            DependsOn::List(services) => services,
+            DependsOn::Map(services) => services.into_keys().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ComposeService {
+    name: String,
+    image: Option<String>,
+    /// The service the workspace runs in
+    primary: bool,
+    /// Started by the CLI along with the workspace, i.e. listed in `runServices` or every service without it
+    run_with_workspace: bool,
+    depends_on: Vec<String>,
+}
+
+/// The compose project a devcontainer config points to with `dockerComposeFile`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ComposeProject {
+    /// In the order they are merged, later files override earlier ones
+    files: Vec<String>,
+    service: String,
+    services: Vec<ComposeService>,
+}
+
+impl ComposeProject {
+    fn service(&self, name: &str) -> Option<&ComposeService> {
+        self.services.iter().find(|service| service.name == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum ComposeServiceState {
+    Running,
+    Stopped,
+    /// No container has been created for the service yet
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ComposeServiceStatus {
+    service: String,
+    state: ComposeServiceState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum ComposeSidecarsState {
+    Running,
+    /// Some of them are running
+    Degraded,
+    Stopped,
+}
+
+/// The state of the sidecars of a workspace as a whole and of each of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ComposeSidecarsStatus {
+    state: ComposeSidecarsState,
+    services: Vec<ComposeServiceStatus>,
+}
+
+fn sidecars_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(COMPOSE_SIDECARS_FILE_NAME))
+}
+
+fn load(app_handle: &AppHandle) -> BTreeMap<String, Vec<String>> {
+    sidecars_path(app_handle)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(
+    app_handle: &AppHandle,
+    sidecars: &BTreeMap<String, Vec<String>>,
+) -> Result<(), ComposeError> {
+    let path = sidecars_path(app_handle)
+        .ok_or_else(|| ComposeError::Persist(anyhow::anyhow!("unable to find app data dir")))?;
+    let content =
+        serde_json::to_vec_pretty(sidecars).map_err(|err| ComposeError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| ComposeError::Persist(err.into()))
+}
+
+/// The devcontainer config of `folder` and the folder it's in, which paths in it are relative to.
+fn read_config(folder: &Path, devcontainer_path: Option<&str>) -> Option<(PathBuf, Value)> {
+    let paths = match devcontainer_path {
+        Some(path) => vec![path],
+        None => CONFIG_PATHS.to_vec(),
+    };
+
+    paths.into_iter().find_map(|path| {
+        let path = folder.join(path);
+        let config = devcontainer::parse(&fs::read_to_string(&path).ok()?)?;
+
+        Some((path.parent()?.to_path_buf(), config))
+    })
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(value)) => vec![value.clone()],
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Merges the compose files the way `docker compose -f ... -f ...` does as far as the services are concerned.
+fn project(
+    config: &Value,
+    read: impl Fn(&str) -> Result<String, ComposeError>,
+) -> Result<Option<ComposeProject>, ComposeError> {
+    let files = string_list(config.get("dockerComposeFile"));
+    let Some(service) = config.get("service").and_then(Value::as_str) else {
+        return Ok(None);
+    };
+    if files.is_empty() {
+        return Ok(None);
+    }
+    let run_services = config
+        .get("runServices")
+        .map(|run_services| string_list(Some(run_services)));
+
+    let mut merged = BTreeMap::<String, (Option<String>, BTreeSet<String>)>::new();
+    for file in &files {
+        let content = read(file)?;
+        let compose: ComposeFile =
+            serde_yaml::from_str(&content).map_err(|err| ComposeError::Parse(file.clone(), err))?;
+        for (name, compose_service) in compose.services {
+            let (image, depends_on) = merged.entry(name).or_default();
+            if compose_service.image.is_some() {
+                *image = compose_service.image;
+            }
+            depends_on.extend(compose_service.depends_on.into_services());
+        }
+    }
+
+    let services = merged
+        .into_iter()
+        .map(|(name, (image, depends_on))| ComposeService {
+            primary: name == service,
+            run_with_workspace: name == service
+                || run_services
+                    .as_ref()
+                    .is_none_or(|run_services| run_services.contains(&name)),
+            name,
+            image,
+            depends_on: depends_on.into_iter().collect(),
+        })
+        .collect();
+
+    Ok(Some(ComposeProject {
+        files,
+        service: service.to_string(),
+        services,
+    }))
+}
+
+/// The compose project of the devcontainer config in `folder`, with the paths of its files resolved.
+fn local_project(
+    folder: &Path,
+    devcontainer_path: Option<&str>,
+) -> Result<Option<ComposeProject>, ComposeError> {
+    let Some((config_folder, config)) = read_config(folder, devcontainer_path) else {
+        return Ok(None);
+    };
+    let mut project = project(&config, |file| {
+        fs::read_to_string(config_folder.join(file))
+            .map_err(|err| ComposeError::Read(file.to_string(), err))
+    })?;
+    if let Some(project) = project.as_mut() {
+        project.files = project
+            .files
+            .iter()
+            .map(|file| config_folder.join(file).to_string_lossy().to_string())
+            .collect();
+    }
+
+    Ok(project)
+}
+
+/// The name the CLI gives the compose project of a workspace, normalized the way `docker compose` expects it.
+fn project_name(workspace_id: &str) -> String {
+    workspace_id
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+        .collect()
+}
+
+/// `services` along with everything they depend on except the primary service, which the CLI manages.
+fn with_dependencies(project: &ComposeProject, services: &[String]) -> Vec<String> {
+    let mut resolved = BTreeSet::new();
+    let mut pending = services.to_vec();
+    while let Some(name) = pending.pop() {
+        if name == project.service || !resolved.insert(name.clone()) {
+            continue;
+        }
+        if let Some(service) = project.service(&name) {
+            pending.extend(service.depends_on.iter().cloned());
+        }
+    }
+
+    resolved.into_iter().collect()
+}
+
+fn aggregate(services: Vec<ComposeServiceStatus>) -> ComposeSidecarsStatus {
+    let state = if services
+        .iter()
+        .all(|service| service.state == ComposeServiceState::Running)
+    {
+        ComposeSidecarsState::Running
+    } else if services
+        .iter()
+        .all(|service| service.state != ComposeServiceState::Running)
+    {
+        ComposeSidecarsState::Stopped
+    } else {
+        ComposeSidecarsState::Degraded
+    };
+
+    ComposeSidecarsStatus { state, services }
+}
+
+/// What the sidecars of a workspace are managed with.
+struct Sidecars {
+    project_name: String,
+    /// Compose files on this machine, without them containers can only be started once the CLI created them
+    files: Vec<String>,
+    services: Vec<String>,
+    docker_host: Option<String>,
+}
+
+impl Sidecars {
+    fn resolve(app_handle: &AppHandle, workspace_id: &str) -> Result<Option<Self>, ComposeError> {
+        let selected = load(app_handle).remove(workspace_id).unwrap_or_default();
+        if selected.is_empty() {
+            return Ok(None);
+        }
+        let provider = workspaces::provider(app_handle, workspace_id)
+            .ok_or_else(|| ComposeError::UnknownWorkspace(workspace_id.to_string()))?;
+        // Compose projects only exist for workspaces on a docker daemon
+        let Some(docker_host) = docker_contexts::docker_host(app_handle, &provider)? else {
+            return Ok(None);
+        };
+
+        let project = workspaces::local_source(app_handle, workspace_id)
+            .map(|(folder, devcontainer_path)| {
+                local_project(Path::new(&folder), devcontainer_path.as_deref())
+            })
+            .transpose()?
+            .flatten();
+        let (files, services) = match project {
+            Some(project) => {
+                let services = with_dependencies(&project, &selected);
+                (project.files, services)
+            }
+            None => (vec![], selected),
+        };
+
+        Ok(Some(Sidecars {
+            project_name: project_name(workspace_id),
+            files,
+            services,
+            docker_host: Some(docker_host).filter(|host| !host.is_empty()),
+        }))
+    }
+
+    fn docker(&self, args: &[&str]) -> Result<String, ComposeError> {
+        docker_contexts::docker(self.docker_host.as_deref(), args).map_err(ComposeError::Docker)
+    }
+
+    fn compose(&self, files: &[String], args: &[&str]) -> Result<String, ComposeError> {
+        let mut compose_args = vec!["compose", "-p", self.project_name.as_str()];
+        for file in files {
+            compose_args.extend(["-f", file.as_str()]);
+        }
+        compose_args.extend(args);
+        compose_args.extend(self.services.iter().map(String::as_str));
+
+        self.docker(&compose_args)
+    }
+
+    /// Leaves the service of the workspace alone, the CLI has added its own settings to it.
+    fn start(&self) -> Result<(), ComposeError> {
+        if self.files.is_empty() {
+            self.compose(&[], &["start"])?;
+        } else {
+            self.compose(&self.files, &["up", "--detach", "--no-deps"])?;
+        }
+
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), ComposeError> {
+        self.compose(&[], &["stop"])?;
+
+        Ok(())
+    }
+
+    fn status(&self) -> Result<ComposeSidecarsStatus, ComposeError> {
+        let project_filter = format!("label={}={}", PROJECT_LABEL, self.project_name);
+        let format = format!("{{{{.Label \"{}\"}}}}\t{{{{.State}}}}", SERVICE_LABEL);
+        let output = self.docker(&[
+            "ps",
+            "--all",
+            "--filter",
+            &project_filter,
+            "--format",
+            &format,
+        ])?;
+        let states = output
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .collect::<Vec<_>>();
+
+        let services = self
+            .services
+            .iter()
+            .map(|service| {
+                let containers = states
+                    .iter()
+                    .filter(|(name, _)| *name == service.as_str())
+                    .collect::<Vec<_>>();
+                let state = if containers.is_empty() {
+                    ComposeServiceState::Missing
+                } else if containers.iter().any(|(_, state)| *state == "running") {
+                    ComposeServiceState::Running
+                } else {
+                    ComposeServiceState::Stopped
+                };
+
+                ComposeServiceStatus {
+                    service: service.clone(),
+                    state,
+                }
+            })
+            .collect();
+
+        Ok(aggregate(services))
+    }
+}
+
+/// The compose project of a local folder before a workspace is created from it. `None` for other sources and
+/// configs without `dockerComposeFile`.
+#[tauri::command]
+pub fn detect_compose_project(
+    source: String,
+    devcontainer_path: Option<String>,
+) -> Result<Option<ComposeProject>, ComposeError> {
+    let folder = Path::new(&source);
+    if !folder.is_dir() {
+        return Ok(None);
+    }
+
+    local_project(
+        folder,
+        devcontainer_path.as_deref().filter(|path| !path.is_empty()),
+    )
+}
+
+/// The compose project of a workspace created from a local folder.
+#[tauri::command]
+pub fn get_workspace_compose_project(
+    app_handle: AppHandle,
+    workspace_id: String,
+) -> Result<Option<ComposeProject>, ComposeError> {
+    match workspaces::local_source(&app_handle, &workspace_id) {
+        Some((folder, devcontainer_path)) => {
+            local_project(Path::new(&folder), devcontainer_path.as_deref())
+        }
+        None => Ok(None),
+    }
+}
+
+#[tauri::command]
+pub fn get_compose_sidecars(app_handle: AppHandle, workspace_id: String) -> Vec<String> {
+    load(&app_handle).remove(&workspace_id).unwrap_or_default()
+}
+
+/// Selects the services that are started and stopped along with the workspace, on top of the ones the CLI starts.
+#[tauri::command]
+pub fn set_compose_sidecars(
+    app_handle: AppHandle,
+    workspace_id: String,
+    services: Vec<String>,
+) -> Result<(), ComposeError> {
+    let project = get_workspace_compose_project(app_handle.clone(), workspace_id.clone())?;
+    if let Some(project) = project {
+        for service in &services {
+            match project.service(service) {
+                Some(service) if service.primary => {
+                    return Err(ComposeError::PrimaryService(service.name.clone()))
+                }
+                Some(_) => {}
+                None => return Err(ComposeError::UnknownService(service.clone())),
+            }
+        }
+    }
+
+    let mut sidecars = load(&app_handle);
+    if services.is_empty() {
+        sidecars.remove(&workspace_id);
+    } else {
+        sidecars.insert(workspace_id, services);
+    }
+
+    save(&app_handle, &sidecars)
+}
+
+/// Starts the sidecars of a workspace once it's running, does nothing without any.
+#[tauri::command]
+pub async fn start_compose_sidecars(
+    app_handle: AppHandle,
+    workspace_id: String,
+) -> Result<(), ComposeError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(sidecars) = Sidecars::resolve(&app_handle, &workspace_id)? {
+            sidecars.start()?;
+            info!(
+                "Started {} along with {}",
+                sidecars.services.join(", "),
+                workspace_id
+            );
+        }
+
+        Ok(())
+    })
+    .await
+    .map_err(ComposeError::Join)?
+}
+
+#[tauri::command]
+pub async fn stop_compose_sidecars(
+    app_handle: AppHandle,
+    workspace_id: String,
+) -> Result<(), ComposeError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(sidecars) = Sidecars::resolve(&app_handle, &workspace_id)? {
+            sidecars.stop()?;
+            info!(
+                "Stopped {} along with {}",
+                sidecars.services.join(", "),
+                workspace_id
+            );
+        }
+
+        Ok(())
+    })
+    .await
+    .map_err(ComposeError::Join)?
+}
+
+/// `None` for workspaces without sidecars.
+#[tauri::command]
+pub async fn get_compose_sidecars_status(
+    app_handle: AppHandle,
+    workspace_id: String,
+) -> Result<Option<ComposeSidecarsStatus>, ComposeError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        Sidecars::resolve(&app_handle, &workspace_id)?
+            .map(|sidecars| sidecars.status())
+            .transpose()
+    })
+    .await
+    .map_err(ComposeError::Join)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_merge_compose_files() {
+        let config = json!({
+            "dockerComposeFile": ["../docker-compose.yml", "docker-compose.extend.yml"],
+            "service": "app",
+            "runServices": ["app", "db"]
+        });
+        let project = project(&config, |file| {
+            Ok(match file {
+                "../docker-compose.yml" => r#"
+services:
+  app:
+    build: .
+    depends_on: [db]
+  db:
+    image: postgres:15
+  worker:
+    image: example/worker
+    depends_on:
+      queue:
+        condition: service_healthy
+      app:
+        condition: service_started
+  queue:
+    image: redis:7
+"#
+                .to_string(),
+                _ => "services:\n  db:\n    image: postgres:16\n".to_string(),
+            })
+        })
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(project.service, "app");
+        let db = project.service("db").unwrap();
+        assert_eq!(db.image.as_deref(), Some("postgres:16"));
+        assert!(db.run_with_workspace);
+        assert!(project.service("app").unwrap().primary);
+        assert!(!project.service("worker").unwrap().run_with_workspace);
+        assert_eq!(
+            with_dependencies(&project, &["worker".to_string()]),
+            vec!["queue".to_string(), "worker".to_string()]
+        );
+
+        assert_eq!(project_name("My.Project-1"), "myproject-1");
+        let status = aggregate(vec![
+            ComposeServiceStatus {
+                service: "queue".to_string(),
+                state: ComposeServiceState::Running,
+            },
+            ComposeServiceStatus {
+                service: "worker".to_string(),
+                state: ComposeServiceState::Missing,
+            },
+        ]);
+        assert_eq!(status.state, ComposeSidecarsState::Degraded);
+        assert_eq!(
+            project(&json!({ "image": "alpine" }), |_| unreachable!()).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,339 @@
+use crate::{
+    jump_hosts::{env_condition, env_defined_condition, is_config_token},
+    ssh_config::{self, SshConfigError},
+    util::shell_command,
+    AppHandle,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{BufRead, BufReader, Read},
+    path::PathBuf,
+    process::Stdio,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+const PROXIES_FILE_NAME: &str = "connection_proxies.json";
+// SSH servers greet with their version before the client says anything
+const SSH_BANNER_PREFIX: &str = "SSH-";
+const TEST_TIMEOUT: Duration = Duration::from_secs(15);
+const DEFAULT_SSH_PORT: u16 = 22;
+// Waiting for the error once the proxy is gone
+const STDERR_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Command that SSH connections to workspace machines are tunneled through, with the usual `ProxyCommand` tokens
+/// like `%h` and `%p`. `none` connects directly, e.g. to exempt a provider from the default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConnectionProxy {
+    command: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConnectionProxies {
+    default: Option<ConnectionProxy>,
+    /// Takes precedence over the default
+    providers: BTreeMap<String, ConnectionProxy>,
+}
+
+impl ConnectionProxies {
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.providers.is_empty()
+    }
+}
+
+/// A starting point for common VPN and tunnel CLIs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConnectionProxyPreset {
+    name: &'static str,
+    command: &'static str,
+}
+
+const PRESETS: &[ConnectionProxyPreset] = &[
+    ConnectionProxyPreset {
+        name: "Cloudflare Tunnel",
+        command: "cloudflared access ssh --hostname %h",
+    },
+    ConnectionProxyPreset {
+        name: "Tailscale",
+        command: "tailscale nc %h %p",
+    },
+    ConnectionProxyPreset {
+        name: "SOCKS proxy",
+        command: "nc -X 5 -x localhost:1080 %h %p",
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConnectionProxyTest {
+    reachable: bool,
+    /// Version the SSH server announced
+    banner: Option<String>,
+    error: Option<String>,
+    #[ts(type = "number")]
+    duration_ms: u64,
+}
+
+#[derive(Error, Debug)]
+pub enum ConnectionProxyError {
+    #[error("invalid {0}")]
+    Invalid(&'static str),
+    #[error("unable to save connection proxies")]
+    Persist(#[source] anyhow::Error),
+    #[error(transparent)]
+    SshConfig(#[from] SshConfigError),
+}
+impl serde::Serialize for ConnectionProxyError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn proxies_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(PROXIES_FILE_NAME))
+}
+
+pub fn load(app_handle: &AppHandle) -> ConnectionProxies {
+    proxies_path(app_handle)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(app_handle: &AppHandle, proxies: &ConnectionProxies) -> Result<(), ConnectionProxyError> {
+    let path = proxies_path(app_handle).ok_or_else(|| {
+        ConnectionProxyError::Persist(anyhow::anyhow!("unable to find app data dir"))
+    })?;
+    let content = serde_json::to_vec_pretty(proxies)
+        .map_err(|err| ConnectionProxyError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| ConnectionProxyError::Persist(err.into()))
+}
+
+fn validate(proxy: &ConnectionProxy) -> Result<(), ConnectionProxyError> {
+    // The rest of the line is the command, anything after a line break would be config
+    if proxy.command.trim().is_empty() || proxy.command.contains(['\n', '\r']) {
+        return Err(ConnectionProxyError::Invalid("command"));
+    }
+
+    Ok(())
+}
+
+fn render_match(condition: &str, proxy: &ConnectionProxy) -> String {
+    format!(
+        "\nMatch originalhost * exec \"{}\"\n  ProxyCommand {}\n",
+        condition,
+        proxy.command.trim()
+    )
+}
+
+/// `Match` blocks for the SSH connections the CLI makes to workspace machines. They come after the jump hosts, so
+/// that a jump host wins for the connection to the machine and the proxy is used to reach the jump host.
+pub fn render(proxies: &ConnectionProxies) -> String {
+    let mut config: String = proxies
+        .providers
+        .iter()
+        .map(|(provider, proxy)| {
+            render_match(&env_condition("WORKSPACE_PROVIDER", provider), proxy)
+        })
+        .collect();
+    if let Some(proxy) = &proxies.default {
+        config.push_str(&render_match(&env_defined_condition("WORKSPACE_ID"), proxy));
+    }
+
+    config
+}
+
+/// `%h`, `%p` and `%%` like `ssh` would expand them.
+fn expand_tokens(command: &str, host: &str, port: u16) -> String {
+    let mut expanded = String::new();
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('%', Some('h')) => expanded.push_str(host),
+            ('%', Some('p')) => expanded.push_str(&port.to_string()),
+            ('%', Some('%')) => expanded.push('%'),
+            _ => {
+                expanded.push(c);
+                continue;
+            }
+        }
+        chars.next();
+    }
+
+    expanded
+}
+
+/// Runs the proxy like `ssh` would and waits for the SSH server's greeting.
+fn read_banner(command: &str) -> Result<String, String> {
+    let mut child = shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("unable to run proxy: {}", err))?;
+    let (stdout, stderr) = match (child.stdout.take(), child.stderr.take()) {
+        (Some(stdout), Some(stderr)) => (stdout, stderr),
+        _ => return Err("unable to read from proxy".to_string()),
+    };
+
+    let (banner_tx, banner_rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut line = String::new();
+        let _ = BufReader::new(stdout).read_line(&mut line);
+        let _ = banner_tx.send(line);
+    });
+    // Processes the proxy started may hold on to stderr, so it's never waited for
+    let (error_tx, error_rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut error = String::new();
+        let _ = BufReader::new(stderr).read_to_string(&mut error);
+        let _ = error_tx.send(error);
+    });
+    let banner = banner_rx.recv_timeout(TEST_TIMEOUT);
+    let _ = child.kill();
+    let _ = child.wait();
+
+    match banner {
+        Ok(line) if line.starts_with(SSH_BANNER_PREFIX) => Ok(line.trim().to_string()),
+        Ok(line) if !line.trim().is_empty() => {
+            Err(format!("expected an SSH server, got \"{}\"", line.trim()))
+        }
+        Ok(_) => Err(error_rx
+            .recv_timeout(STDERR_TIMEOUT)
+            .ok()
+            .map(|error| error.trim().to_string())
+            .filter(|error| !error.is_empty())
+            .unwrap_or_else(|| "proxy closed the connection".to_string())),
+        Err(_) => Err(format!("no response within {}s", TEST_TIMEOUT.as_secs())),
+    }
+}
+
+fn try_connect(proxy: &ConnectionProxy, host: &str, port: u16) -> ConnectionProxyTest {
+    let started_at = Instant::now();
+    let result = read_banner(&expand_tokens(proxy.command.trim(), host, port));
+    let duration_ms = started_at.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(banner) => ConnectionProxyTest {
+            reachable: true,
+            banner: Some(banner),
+            error: None,
+            duration_ms,
+        },
+        Err(error) => ConnectionProxyTest {
+            reachable: false,
+            banner: None,
+            error: Some(error),
+            duration_ms,
+        },
+    }
+}
+
+#[tauri::command]
+pub fn get_connection_proxies(app_handle: AppHandle) -> ConnectionProxies {
+    load(&app_handle)
+}
+
+#[tauri::command]
+pub fn list_connection_proxy_presets() -> Vec<ConnectionProxyPreset> {
+    PRESETS.to_vec()
+}
+
+/// Sets or removes (`None`) the default proxy, or the one of `provider`, and updates the SSH config.
+#[tauri::command]
+pub fn set_connection_proxy(
+    app_handle: AppHandle,
+    provider: Option<String>,
+    proxy: Option<ConnectionProxy>,
+) -> Result<ConnectionProxies, ConnectionProxyError> {
+    if let Some(proxy) = &proxy {
+        validate(proxy)?;
+    }
+    if provider
+        .as_deref()
+        .is_some_and(|provider| !is_config_token(provider))
+    {
+        return Err(ConnectionProxyError::Invalid("provider"));
+    }
+
+    let mut proxies = load(&app_handle);
+    let scope = provider.as_deref().unwrap_or("all providers");
+    match (provider.clone(), proxy) {
+        (None, proxy) => proxies.default = proxy,
+        (Some(provider), Some(proxy)) => {
+            proxies.providers.insert(provider, proxy);
+        }
+        (Some(provider), None) => {
+            proxies.providers.remove(&provider);
+        }
+    }
+    info!("Updated connection proxy of {}", scope);
+    save(&app_handle, &proxies)?;
+    ssh_config::apply(&app_handle)?;
+
+    Ok(proxies)
+}
+
+/// Connects to `host` through the proxy, without saving it.
+#[tauri::command]
+pub async fn test_connection_proxy(
+    proxy: ConnectionProxy,
+    host: String,
+    port: Option<u16>,
+) -> Result<ConnectionProxyTest, ConnectionProxyError> {
+    validate(&proxy)?;
+    if !is_config_token(&host) {
+        return Err(ConnectionProxyError::Invalid("host"));
+    }
+
+    Ok(try_connect(&proxy, &host, port.unwrap_or(DEFAULT_SSH_PORT)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render_provider_overrides_first() {
+        let proxy = |command: &str| ConnectionProxy {
+            command: command.to_string(),
+        };
+        let proxies = ConnectionProxies {
+            default: Some(proxy("tailscale nc %h %p")),
+            providers: BTreeMap::from([("aws".to_string(), proxy("none"))]),
+        };
+        let config = render(&proxies);
+
+        let provider_match = config.find("ProxyCommand none\n").unwrap();
+        let default_match = config.find("ProxyCommand tailscale nc %h %p\n").unwrap();
+        assert!(provider_match < default_match);
+    }
+
+    #[test]
+    fn should_expand_tokens() {
+        assert_eq!(
+            expand_tokens("nc -x proxy:1080 %h %p # 100%%", "10.0.0.1", 22),
+            "nc -x proxy:1080 10.0.0.1 22 # 100%"
+        );
+    }
+}
@@ -0,0 +1,348 @@
+use crate::{payloads, AppHandle, AppState};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+use tauri::Manager;
+use ts_rs::TS;
+
+// WARN: needs to match the channel in `client.ts`
+const CONNECTIONS_EVENT: &str = "connections";
+// WARN: needs to match the channel in `client.ts`
+const CONNECTION_METRICS_EVENT: &str = "connection_metrics";
+// Round trips to the workspace, editing over SSH starts to lag above this
+const DEGRADED_LATENCY: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum ConnectionKind {
+    PortForward,
+    ReverseForward,
+    BrowserIde,
+}
+
+/// A tunnel or SSH session to a workspace that the app keeps open.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct Connection {
+    id: String,
+    workspace_id: String,
+    kind: ConnectionKind,
+    /// What the connection is for, e.g. `localhost:3000 → 3000`
+    description: String,
+    connected_since: DateTime<Utc>,
+    /// Latest round trip time
+    #[ts(type = "number | null")]
+    latency_ms: Option<u64>,
+    degraded: bool,
+    /// Bytes sent to the workspace since connecting
+    #[ts(type = "number")]
+    bytes_sent: u64,
+    #[ts(type = "number")]
+    bytes_received: u64,
+    /// Bytes per second since the previous sample
+    #[ts(type = "number")]
+    send_rate: u64,
+    #[ts(type = "number")]
+    receive_rate: u64,
+}
+
+impl Connection {
+    pub fn new(
+        id: String,
+        workspace_id: String,
+        kind: ConnectionKind,
+        description: String,
+    ) -> Self {
+        Self {
+            id,
+            workspace_id,
+            kind,
+            description,
+            connected_since: Utc::now(),
+            latency_ms: None,
+            degraded: false,
+            bytes_sent: 0,
+            bytes_received: 0,
+            send_rate: 0,
+            receive_rate: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum ConnectionEventKind {
+    Connected,
+    Disconnected,
+    /// Latency went above the threshold
+    Degraded,
+    Recovered,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConnectionEvent {
+    kind: ConnectionEventKind,
+    connection: Connection,
+    reason: Option<String>,
+}
+
+/// Traffic and latency of all connections to a workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct WorkspaceMetrics {
+    workspace_id: String,
+    connections: usize,
+    /// The worst latency of the connections
+    #[ts(type = "number | null")]
+    latency_ms: Option<u64>,
+    #[ts(type = "number")]
+    bytes_sent: u64,
+    #[ts(type = "number")]
+    bytes_received: u64,
+    #[ts(type = "number")]
+    send_rate: u64,
+    #[ts(type = "number")]
+    receive_rate: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct Connections {
+    connections: HashMap<String, Connection>,
+    /// When the totals of a connection were last sampled
+    samples: HashMap<String, Instant>,
+}
+
+impl Connections {
+    /// Busiest workspaces first.
+    fn metrics(&self) -> Vec<WorkspaceMetrics> {
+        let mut metrics: BTreeMap<&str, WorkspaceMetrics> = BTreeMap::new();
+        for connection in self.connections.values() {
+            let workspace =
+                metrics
+                    .entry(&connection.workspace_id)
+                    .or_insert_with(|| WorkspaceMetrics {
+                        workspace_id: connection.workspace_id.clone(),
+                        connections: 0,
+                        latency_ms: None,
+                        bytes_sent: 0,
+                        bytes_received: 0,
+                        send_rate: 0,
+                        receive_rate: 0,
+                    });
+            workspace.connections += 1;
+            workspace.latency_ms = workspace.latency_ms.max(connection.latency_ms);
+            workspace.bytes_sent += connection.bytes_sent;
+            workspace.bytes_received += connection.bytes_received;
+            workspace.send_rate += connection.send_rate;
+            workspace.receive_rate += connection.receive_rate;
+        }
+
+        let mut metrics: Vec<WorkspaceMetrics> = metrics.into_values().collect();
+        // Stable, ties stay sorted by workspace
+        metrics.sort_by_key(|workspace| {
+            std::cmp::Reverse(workspace.send_rate + workspace.receive_rate)
+        });
+
+        metrics
+    }
+}
+
+fn emit(
+    app_handle: &AppHandle,
+    kind: ConnectionEventKind,
+    connection: Connection,
+    reason: Option<String>,
+) {
+    let event = ConnectionEvent {
+        kind,
+        connection,
+        reason,
+    };
+    if let Err(err) = app_handle.emit_all(CONNECTIONS_EVENT, event) {
+        warn!("Failed to send connection event: {}", err);
+    }
+}
+
+/// Starts tracking `connection`, replacing a previous one with the same id.
+pub fn connect(app_handle: &AppHandle, connection: Connection) {
+    info!(
+        "Connected to {}: {}",
+        connection.workspace_id, connection.description
+    );
+    {
+        let app_state = app_handle.state::<AppState>();
+        let mut connections = app_state.connections.lock().unwrap();
+        connections
+            .connections
+            .insert(connection.id.clone(), connection.clone());
+    }
+
+    emit(app_handle, ConnectionEventKind::Connected, connection, None);
+}
+
+pub fn disconnect(app_handle: &AppHandle, id: &str, reason: Option<String>) {
+    let connection = {
+        let app_state = app_handle.state::<AppState>();
+        let mut connections = app_state.connections.lock().unwrap();
+        connections.samples.remove(id);
+        connections.connections.remove(id)
+    };
+
+    if let Some(connection) = connection {
+        info!(
+            "Disconnected from {}: {}",
+            connection.workspace_id, connection.description
+        );
+        emit(
+            app_handle,
+            ConnectionEventKind::Disconnected,
+            connection,
+            reason,
+        );
+    }
+}
+
+/// Updates the latency of a connection, lets the UI know when it crosses the threshold.
+pub fn record_latency(app_handle: &AppHandle, id: &str, latency: Duration) {
+    let changed = {
+        let app_state = app_handle.state::<AppState>();
+        let mut connections = app_state.connections.lock().unwrap();
+        let connection = match connections.connections.get_mut(id) {
+            Some(connection) => connection,
+            None => return,
+        };
+
+        connection.latency_ms = Some(latency.as_millis() as u64);
+        let degraded = latency > DEGRADED_LATENCY;
+        if connection.degraded == degraded {
+            None
+        } else {
+            connection.degraded = degraded;
+            Some(connection.clone())
+        }
+    };
+
+    if let Some(connection) = changed {
+        let kind = if connection.degraded {
+            warn!(
+                "Connection to {} degraded, latency {}ms",
+                connection.workspace_id,
+                latency.as_millis()
+            );
+            ConnectionEventKind::Degraded
+        } else {
+            ConnectionEventKind::Recovered
+        };
+        emit(app_handle, kind, connection, None);
+    }
+}
+
+/// Updates the traffic of a connection from the totals since it connected.
+pub fn record_throughput(app_handle: &AppHandle, id: &str, bytes_sent: u64, bytes_received: u64) {
+    let app_state = app_handle.state::<AppState>();
+    let mut connections = app_state.connections.lock().unwrap();
+    let now = Instant::now();
+    let previous_sample = connections.samples.insert(id.to_string(), now);
+    let connection = match connections.connections.get_mut(id) {
+        Some(connection) => connection,
+        None => return,
+    };
+
+    if let Some(previous_sample) = previous_sample {
+        let elapsed = (now - previous_sample).as_secs_f64();
+        if elapsed > 0.0 {
+            let rate = |total: u64, previous: u64| {
+                (total.saturating_sub(previous) as f64 / elapsed) as u64
+            };
+            connection.send_rate = rate(bytes_sent, connection.bytes_sent);
+            connection.receive_rate = rate(bytes_received, connection.bytes_received);
+        }
+    }
+    connection.bytes_sent = bytes_sent;
+    connection.bytes_received = bytes_received;
+}
+
+/// Sends the metrics of all workspaces to the UI, called after each round of samples.
+pub fn emit_metrics(app_handle: &AppHandle) {
+    let metrics = {
+        let app_state = app_handle.state::<AppState>();
+        let connections = app_state.connections.lock().unwrap();
+        connections.metrics()
+    };
+
+    if let Err(err) = payloads::emit_all(app_handle, CONNECTION_METRICS_EVENT, metrics) {
+        warn!("Failed to send connection metrics: {}", err);
+    }
+}
+
+/// Everything that is currently connected, grouped by workspace.
+#[tauri::command]
+pub fn get_connections(state: tauri::State<'_, AppState>) -> Vec<Connection> {
+    let mut connections: Vec<Connection> = state
+        .connections
+        .lock()
+        .unwrap()
+        .connections
+        .values()
+        .cloned()
+        .collect();
+    connections.sort_by(|a, b| {
+        (&a.workspace_id, a.connected_since).cmp(&(&b.workspace_id, b.connected_since))
+    });
+
+    connections
+}
+
+/// Traffic and latency per workspace, to tell which workspace uses the bandwidth.
+#[tauri::command]
+pub fn get_connection_metrics(state: tauri::State<'_, AppState>) -> Vec<WorkspaceMetrics> {
+    state.connections.lock().unwrap().metrics()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_sum_metrics_per_workspace() {
+        let connection = |id: &str, workspace_id: &str, rate: u64, latency_ms: u64| Connection {
+            send_rate: rate,
+            receive_rate: rate,
+            bytes_sent: rate,
+            bytes_received: rate,
+            latency_ms: Some(latency_ms),
+            ..Connection::new(
+                id.to_string(),
+                workspace_id.to_string(),
+                ConnectionKind::PortForward,
+                String::new(),
+            )
+        };
+        let connections = Connections {
+            connections: HashMap::from([
+                ("a".to_string(), connection("a", "idle", 10, 40)),
+                ("b".to_string(), connection("b", "busy", 500, 30)),
+                ("c".to_string(), connection("c", "busy", 700, 120)),
+            ]),
+            samples: HashMap::new(),
+        };
+        let metrics = connections.metrics();
+
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].workspace_id, "busy");
+        assert_eq!(metrics[0].connections, 2);
+        assert_eq!(metrics[0].send_rate, 1200);
+        assert_eq!(metrics[0].latency_ms, Some(120));
+        assert_eq!(metrics[1].workspace_id, "idle");
+    }
+}
@@ -0,0 +1,294 @@
+use crate::{drag_drop, util::app_path, AppHandle};
+use log::info;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContextMenuError {
+    #[error("unable to find app executable")]
+    NoExePath(#[source] std::io::Error),
+    #[error("unable to find file manager config dir")]
+    NoDir,
+    #[error("unable to update context menu entry")]
+    Write(#[source] anyhow::Error),
+    #[error("the Finder service can only be turned off in the keyboard settings")]
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    Unsupported,
+}
+impl serde::Serialize for ContextMenuError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// Whether folders offer "Open in DevPod" in the file manager.
+#[tauri::command]
+pub fn get_context_menu() -> Result<bool, ContextMenuError> {
+    is_installed()
+}
+
+/// Adds or removes the "Open in DevPod" entry. The entry launches DevPod with the selected folders,
+/// which `single_instance` forwards to the running instance.
+#[tauri::command]
+pub fn set_context_menu(enabled: bool) -> Result<bool, ContextMenuError> {
+    if enabled {
+        install(&app_path().map_err(ContextMenuError::NoExePath)?)?;
+    } else {
+        uninstall()?;
+    }
+    info!(
+        "Context menu entry {}",
+        if enabled { "installed" } else { "removed" }
+    );
+
+    is_installed()
+}
+
+/// Opens folders the app has been launched with from the context menu.
+pub fn setup(app_handle: &AppHandle) {
+    // Folders selected while the app is already running are forwarded by `single_instance`
+    let folders: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| Path::new(arg).is_dir())
+        .collect();
+    if !folders.is_empty() {
+        drag_drop::handle_drop(app_handle, folders);
+    }
+
+    #[cfg(target_os = "macos")]
+    macos::listen(app_handle);
+}
+
+#[cfg(target_os = "linux")]
+fn entry_paths() -> Result<(std::path::PathBuf, std::path::PathBuf), ContextMenuError> {
+    let data_dir = dirs::data_dir().ok_or(ContextMenuError::NoDir)?;
+
+    Ok((
+        data_dir
+            .join("nautilus")
+            .join("scripts")
+            .join("Open in DevPod"),
+        data_dir
+            .join("kio")
+            .join("servicemenus")
+            .join("devpod.desktop"),
+    ))
+}
+
+#[cfg(target_os = "linux")]
+fn is_installed() -> Result<bool, ContextMenuError> {
+    let (nautilus_script, _) = entry_paths()?;
+
+    Ok(nautilus_script.exists())
+}
+
+#[cfg(target_os = "linux")]
+fn nautilus_script(exe: &Path) -> String {
+    // Nautilus passes the selection relative to the current folder, the env var has absolute paths
+    format!(
+        "#!/bin/sh\nIFS='\n'\nif [ -n \"$NAUTILUS_SCRIPT_SELECTED_FILE_PATHS\" ]; then\n  set -- $NAUTILUS_SCRIPT_SELECTED_FILE_PATHS\nfi\nexec \"{}\" \"$@\"\n",
+        exe.to_string_lossy()
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn dolphin_service_menu(exe: &Path) -> String {
+    format!(
+        "[Desktop Entry]\nType=Service\nMimeType=inode/directory;\nActions=openInDevPod;\nX-KDE-ServiceTypes=KonqPopupMenu/Plugin\n\n[Desktop Action openInDevPod]\nName=Open in DevPod\nIcon=devpod\nExec=\"{}\" %F\n",
+        exe.to_string_lossy()
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn install(exe: &Path) -> Result<(), ContextMenuError> {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    let (nautilus_path, dolphin_path) = entry_paths()?;
+    for (path, content) in [
+        (nautilus_path, nautilus_script(exe)),
+        (dolphin_path, dolphin_service_menu(exe)),
+    ] {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| ContextMenuError::Write(err.into()))?;
+        }
+        fs::write(&path, content).map_err(|err| ContextMenuError::Write(err.into()))?;
+        // Both file managers only run entries that are executable
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .map_err(|err| ContextMenuError::Write(err.into()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn uninstall() -> Result<(), ContextMenuError> {
+    let (nautilus_path, dolphin_path) = entry_paths()?;
+    for path in [nautilus_path, dolphin_path] {
+        match std::fs::remove_file(path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                return Err(ContextMenuError::Write(err.into()))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// The Finder service is declared in `Info.plist` and always available
+#[cfg(target_os = "macos")]
+fn is_installed() -> Result<bool, ContextMenuError> {
+    Ok(true)
+}
+
+#[cfg(target_os = "macos")]
+fn install(_exe: &Path) -> Result<(), ContextMenuError> {
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn uninstall() -> Result<(), ContextMenuError> {
+    Err(ContextMenuError::Unsupported)
+}
+
+// Folders and the background of an open folder
+#[cfg(target_os = "windows")]
+const SHELL_KEYS: [&str; 2] = [
+    "Software\\Classes\\Directory\\shell\\DevPod",
+    "Software\\Classes\\Directory\\Background\\shell\\DevPod",
+];
+
+#[cfg(target_os = "windows")]
+fn is_installed() -> Result<bool, ContextMenuError> {
+    use winreg::{enums::HKEY_CURRENT_USER, RegKey};
+
+    Ok(RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey(SHELL_KEYS[0])
+        .is_ok())
+}
+
+#[cfg(target_os = "windows")]
+fn install(exe: &Path) -> Result<(), ContextMenuError> {
+    use winreg::{enums::HKEY_CURRENT_USER, RegKey};
+
+    let exe = exe.to_string_lossy();
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    for key in SHELL_KEYS {
+        let write = || -> std::io::Result<()> {
+            let (shell, _) = hkcu.create_subkey(key)?;
+            shell.set_value("", &"Open in DevPod")?;
+            shell.set_value("Icon", &format!("\"{}\",0", exe))?;
+            let (command, _) = shell.create_subkey("command")?;
+            command.set_value("", &format!("\"{}\" \"%V\"", exe))
+        };
+        write().map_err(|err| ContextMenuError::Write(err.into()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn uninstall() -> Result<(), ContextMenuError> {
+    use winreg::{enums::HKEY_CURRENT_USER, RegKey};
+
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    for key in SHELL_KEYS {
+        match hkcu.delete_subkey_all(key) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                return Err(ContextMenuError::Write(err.into()))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use crate::{drag_drop, AppHandle};
+    use cocoa::base::{id, nil};
+    use objc::{
+        declare::ClassDecl,
+        runtime::{Object, Sel},
+    };
+    use std::{ffi::CStr, os::raw::c_char};
+
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {
+        fn NSUpdateDynamicServices();
+    }
+
+    unsafe fn pasteboard_paths(pasteboard: id) -> Vec<String> {
+        let classes: id = msg_send![class!(NSArray), arrayWithObject: class!(NSURL)];
+        let urls: id = msg_send![pasteboard, readObjectsForClasses: classes options: nil];
+        if urls == nil {
+            return vec![];
+        }
+
+        let count: usize = msg_send![urls, count];
+        (0..count)
+            .filter_map(|i| {
+                let url: id = msg_send![urls, objectAtIndex: i];
+                let path: id = msg_send![url, path];
+                if path == nil {
+                    return None;
+                }
+                let path: *const c_char = msg_send![path, UTF8String];
+
+                Some(CStr::from_ptr(path).to_string_lossy().to_string())
+            })
+            .collect()
+    }
+
+    // `NSMessage` of the service in `Info.plist`
+    extern "C" fn open_in_devpod(
+        this: &Object,
+        _cmd: Sel,
+        pasteboard: id,
+        _user_data: id,
+        _error: *mut id,
+    ) {
+        let paths = unsafe { pasteboard_paths(pasteboard) };
+        // The provider lives as long as the app, so its handle is never freed
+        let app_handle = unsafe { &*(*this.get_ivar::<usize>("appHandle") as *const AppHandle) };
+        drag_drop::handle_drop(app_handle, paths);
+    }
+
+    /// Handles the "Open in DevPod" Finder service.
+    pub fn listen(app_handle: &AppHandle) {
+        let handle = app_handle.clone();
+        let _ = app_handle.run_on_main_thread(move || unsafe {
+            let mut decl = match ClassDecl::new("DevPodServiceProvider", class!(NSObject)) {
+                Some(decl) => decl,
+                None => return,
+            };
+            decl.add_method(
+                sel!(openInDevPod:userData:error:),
+                open_in_devpod as extern "C" fn(&Object, Sel, id, id, *mut id),
+            );
+            decl.add_ivar::<usize>("appHandle");
+            let provider: id = msg_send![decl.register(), new];
+            (*provider).set_ivar("appHandle", Box::into_raw(Box::new(handle)) as usize);
+
+            let app: id = msg_send![class!(NSApplication), sharedApplication];
+            let _: () = msg_send![app, setServicesProvider: provider];
+            NSUpdateDynamicServices();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn should_pass_selection_to_app() {
+        let exe = std::path::Path::new("/opt/DevPod/dev-pod");
+
+        assert!(super::nautilus_script(exe).ends_with("exec \"/opt/DevPod/dev-pod\" \"$@\"\n"));
+        assert!(super::dolphin_service_menu(exe).contains("Exec=\"/opt/DevPod/dev-pod\" %F\n"));
+    }
+}
@@ -0,0 +1,393 @@
+use crate::AppHandle;
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    backtrace::Backtrace,
+    fs,
+    panic::{self, PanicHookInfo},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    thread,
+};
+use tauri::Config;
+use thiserror::Error;
+use ts_rs::TS;
+
+const CRASH_REPORTS_DIR: &str = "crash_reports";
+// Exists while the app is running, a leftover marker on startup means the last session didn't exit cleanly
+const SESSION_MARKER_FILE_NAME: &str = "session.json";
+const REPORT_ID_PREFIX: &str = "crash-";
+const CRASH_REPORT_URL: &str = "https://admin.loft.sh/analytics/v1/devpod/v1/crashes";
+
+static SESSION_MARKER: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Error, Debug)]
+pub enum CrashReportError {
+    #[error("unable to get crash report dir")]
+    NoDir,
+    #[error("unable to read crash report {0}")]
+    Read(#[source] anyhow::Error),
+    #[error("invalid crash report id {0}")]
+    InvalidId(String),
+    #[error("unable to send crash report {0}")]
+    Send(#[source] anyhow::Error),
+}
+impl serde::Serialize for CrashReportError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum CrashKind {
+    Panic,
+    /// The process went away without running its exit handlers, i.e. because of a signal or an abort.
+    UnexpectedExit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CrashReport {
+    id: String,
+    kind: CrashKind,
+    timestamp: DateTime<Utc>,
+    session_started_at: DateTime<Utc>,
+    app_version: String,
+    os: String,
+    arch: String,
+    thread: Option<String>,
+    message: Option<String>,
+    location: Option<String>,
+    backtrace: Option<String>,
+}
+
+/// A locally stored crash report together with the exact payload that would be uploaded.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CrashReportPreview {
+    report: CrashReport,
+    payload: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Session {
+    started_at: DateTime<Utc>,
+    app_version: String,
+}
+
+/// Installs the panic hook and records crashes of the previous session.
+/// Needs to run as early as possible, before the app is built.
+pub fn install(config: &Config, app_version: &str) {
+    let dir = match tauri::api::path::app_data_dir(config) {
+        Some(dir) => dir.join(CRASH_REPORTS_DIR),
+        None => return,
+    };
+    if let Err(err) = fs::create_dir_all(&dir) {
+        eprintln!("Failed to create crash report dir: {}", err);
+        return;
+    }
+
+    check_previous_session(&dir, app_version);
+
+    let session = Session {
+        started_at: Utc::now(),
+        app_version: app_version.to_string(),
+    };
+    let marker = dir.join(SESSION_MARKER_FILE_NAME);
+    match write_json(&marker, &session) {
+        Ok(()) => {
+            let _ = SESSION_MARKER.set(marker);
+            // SAFETY: the callback doesn't unwind and only touches the marker path, which is set at this point
+            unsafe {
+                atexit(remove_session_marker);
+            }
+        }
+        Err(err) => eprintln!("Failed to write session marker: {}", err),
+    }
+
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let report = panic_report(info, &session);
+        // Logging might be what panicked, write to stderr as well
+        eprintln!("Panic captured, writing crash report {}", report.id);
+        if let Err(err) = write_report(&dir, &report) {
+            eprintln!("Failed to write crash report: {}", err);
+        }
+
+        default_hook(info);
+    }));
+}
+
+extern "C" {
+    fn atexit(callback: extern "C" fn()) -> std::os::raw::c_int;
+}
+
+// Most exit paths, i.e. quitting from the tray or the UI, call `std::process::exit` and skip tauri's exit event.
+// Exit handlers still run for those, but not if the process gets killed by a signal or aborts.
+extern "C" fn remove_session_marker() {
+    if let Some(marker) = SESSION_MARKER.get() {
+        let _ = fs::remove_file(marker);
+    }
+}
+
+fn check_previous_session(dir: &Path, app_version: &str) {
+    let marker = dir.join(SESSION_MARKER_FILE_NAME);
+    let session: Session = match fs::read(&marker)
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+    {
+        Some(session) => session,
+        None => return,
+    };
+    let _ = fs::remove_file(&marker);
+
+    // Installing an update restarts the app without going through the regular exit
+    if session.app_version != app_version {
+        return;
+    }
+    // A panic already left a more helpful report behind
+    let has_panic_report = read_reports(dir)
+        .iter()
+        .any(|report| report.session_started_at == session.started_at);
+    if has_panic_report {
+        return;
+    }
+
+    let report = CrashReport {
+        id: report_id(),
+        kind: CrashKind::UnexpectedExit,
+        timestamp: Utc::now(),
+        session_started_at: session.started_at,
+        app_version: session.app_version,
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        thread: None,
+        message: None,
+        location: None,
+        backtrace: None,
+    };
+    if let Err(err) = write_report(dir, &report) {
+        eprintln!("Failed to write crash report: {}", err);
+    }
+}
+
+fn panic_report(info: &PanicHookInfo, session: &Session) -> CrashReport {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned());
+
+    CrashReport {
+        id: report_id(),
+        kind: CrashKind::Panic,
+        timestamp: Utc::now(),
+        session_started_at: session.started_at,
+        app_version: session.app_version.clone(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        thread: thread::current().name().map(String::from),
+        message,
+        location: info.location().map(|location| location.to_string()),
+        backtrace: Some(Backtrace::force_capture().to_string()),
+    }
+}
+
+fn report_id() -> String {
+    format!("{}{}", REPORT_ID_PREFIX, Utc::now().timestamp_millis())
+}
+
+// Ids are passed in from the UI and end up in file paths
+fn validate_id(id: &str) -> Result<(), CrashReportError> {
+    match id.strip_prefix(REPORT_ID_PREFIX) {
+        Some(millis) if !millis.is_empty() && millis.chars().all(|c| c.is_ascii_digit()) => Ok(()),
+        _ => Err(CrashReportError::InvalidId(id.to_string())),
+    }
+}
+
+fn crash_reports_dir(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(CRASH_REPORTS_DIR))
+}
+
+fn report_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.json", id))
+}
+
+fn write_report(dir: &Path, report: &CrashReport) -> anyhow::Result<()> {
+    write_json(&report_path(dir, &report.id), report)
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> anyhow::Result<()> {
+    fs::write(path, serde_json::to_vec_pretty(value)?)?;
+
+    Ok(())
+}
+
+fn read_reports(dir: &Path) -> Vec<CrashReport> {
+    let mut reports: Vec<CrashReport> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.file_name().and_then(|name| name.to_str())
+                        != Some(SESSION_MARKER_FILE_NAME)
+                })
+                .filter_map(|path| fs::read(path).ok())
+                .filter_map(|data| serde_json::from_slice(&data).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    reports.sort_by_key(|report| std::cmp::Reverse(report.timestamp));
+
+    reports
+}
+
+fn payload(report: &CrashReport) -> Result<String, CrashReportError> {
+    serde_json::to_string_pretty(report).map_err(|err| CrashReportError::Read(err.into()))
+}
+
+/// Returns the crash reports that haven't been sent or dismissed yet, newest first.
+#[tauri::command]
+pub fn get_crash_reports(
+    app_handle: AppHandle,
+) -> Result<Vec<CrashReportPreview>, CrashReportError> {
+    let dir = crash_reports_dir(&app_handle).ok_or(CrashReportError::NoDir)?;
+
+    read_reports(&dir)
+        .into_iter()
+        .map(|report| {
+            Ok(CrashReportPreview {
+                payload: payload(&report)?,
+                report,
+            })
+        })
+        .collect()
+}
+
+/// Uploads the given crash reports exactly as previewed and removes them locally afterwards.
+/// Only ever called after the user opted in.
+#[tauri::command]
+pub async fn send_crash_reports(
+    app_handle: AppHandle,
+    ids: Vec<String>,
+) -> Result<(), CrashReportError> {
+    let dir = crash_reports_dir(&app_handle).ok_or(CrashReportError::NoDir)?;
+    let client = reqwest::Client::new();
+
+    for id in ids {
+        validate_id(&id)?;
+        let path = report_path(&dir, &id);
+        let report: CrashReport = fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| serde_json::from_slice(&data).map_err(anyhow::Error::from))
+            .map_err(CrashReportError::Read)?;
+
+        info!("Sending crash report {}", id);
+        client
+            .post(CRASH_REPORT_URL)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(payload(&report)?)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| {
+                error!("Failed to send crash report {}: {}", id, err);
+                CrashReportError::Send(err.into())
+            })?;
+
+        if let Err(err) = fs::remove_file(&path) {
+            warn!("Failed to remove sent crash report {}: {}", id, err);
+        }
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn dismiss_crash_reports(
+    app_handle: AppHandle,
+    ids: Vec<String>,
+) -> Result<(), CrashReportError> {
+    let dir = crash_reports_dir(&app_handle).ok_or(CrashReportError::NoDir)?;
+    for id in ids {
+        validate_id(&id)?;
+        let path = report_path(&dir, &id);
+        if path.exists() {
+            fs::remove_file(path).map_err(|err| CrashReportError::Read(err.into()))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "devpod-crash-reports-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    fn write_session(dir: &Path, app_version: &str) -> Session {
+        let session = Session {
+            started_at: Utc::now(),
+            app_version: app_version.to_string(),
+        };
+        write_json(&dir.join(SESSION_MARKER_FILE_NAME), &session).unwrap();
+
+        session
+    }
+
+    #[test]
+    fn should_report_unexpected_exit() {
+        let dir = temp_dir("unexpected-exit");
+        write_session(&dir, "0.3.0");
+
+        check_previous_session(&dir, "0.3.0");
+
+        let reports = read_reports(&dir);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].kind, CrashKind::UnexpectedExit);
+        assert!(!dir.join(SESSION_MARKER_FILE_NAME).exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn should_ignore_restart_after_update() {
+        let dir = temp_dir("update");
+        write_session(&dir, "0.3.0");
+
+        check_previous_session(&dir, "0.4.0");
+
+        assert!(read_reports(&dir).is_empty());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn should_validate_ids() {
+        assert!(validate_id("crash-1697452800000").is_ok());
+        assert!(validate_id("crash-").is_err());
+        assert!(validate_id("../settings").is_err());
+        assert!(validate_id("crash-1/../../x").is_err());
+    }
+}
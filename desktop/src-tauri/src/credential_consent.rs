@@ -0,0 +1,275 @@
+use crate::{
+    audit_log::{self, AuditCategory, AuditInitiator},
+    environment,
+    keychain::{self, KeychainError},
+    secrets::SecretKind,
+    AppHandle, AppState, UiMessage,
+};
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, sync::Mutex};
+use tauri::Manager;
+use thiserror::Error;
+use ts_rs::TS;
+
+// Kept in the keychain instead of the settings, an imported configuration must not be able to bring its own decisions
+const CONSENT_SERVICE: &str = "sh.loft.devpod.credential-consent";
+const CONSENT_ACCOUNT: &str = "decisions";
+
+lazy_static! {
+    // Read from the keychain on first use
+    static ref DECISIONS: Mutex<Option<Vec<CredentialDecision>>> = Mutex::new(None);
+    // Put in front of the user and not answered yet, asking again would only stack up prompts
+    static ref PENDING: Mutex<HashSet<CredentialRequest>> = Mutex::new(HashSet::new());
+}
+
+#[derive(Error, Debug)]
+pub enum CredentialConsentError {
+    #[error("unable to access credential decisions")]
+    Keychain(#[from] KeychainError),
+    #[error("unable to save credential decisions")]
+    Serialize(#[source] serde_json::Error),
+}
+impl serde::Serialize for CredentialConsentError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// Who wants to use a stored secret.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(tag = "kind", content = "name", rename_all = "camelCase")]
+#[ts(export)]
+pub enum CredentialRequester {
+    /// Workspaces of the provider
+    Provider(String),
+    /// Workspaces with the tag
+    Tag(String),
+    AllWorkspaces,
+}
+
+impl CredentialRequester {
+    fn label(&self) -> String {
+        match self {
+            CredentialRequester::Provider(provider) => format!("provider {}", provider),
+            CredentialRequester::Tag(tag) => format!("workspaces tagged {}", tag),
+            CredentialRequester::AllWorkspaces => "all workspaces".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CredentialRequest {
+    requester: CredentialRequester,
+    kind: SecretKind,
+    /// The keychain account of the secret, never its value
+    account: String,
+}
+
+impl CredentialRequest {
+    pub fn new(requester: CredentialRequester, kind: SecretKind, account: &str) -> Self {
+        Self {
+            requester,
+            kind,
+            account: account.to_string(),
+        }
+    }
+
+    fn label(&self) -> String {
+        format!("{} for {}", self.account, self.requester.label())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CredentialDecision {
+    request: CredentialRequest,
+    allowed: bool,
+    decided_at: DateTime<Utc>,
+}
+
+fn load() -> Result<Vec<CredentialDecision>, CredentialConsentError> {
+    match keychain::get_password(CONSENT_SERVICE, CONSENT_ACCOUNT) {
+        Ok(value) => Ok(serde_json::from_str(&value).unwrap_or_else(|err| {
+            warn!("Ignoring unreadable credential decisions: {}", err);
+            vec![]
+        })),
+        Err(KeychainError::NotFound(..)) => Ok(vec![]),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn save(decisions: &[CredentialDecision]) -> Result<(), CredentialConsentError> {
+    let value = serde_json::to_string(decisions).map_err(CredentialConsentError::Serialize)?;
+    keychain::set_password(CONSENT_SERVICE, CONSENT_ACCOUNT, &value)?;
+
+    Ok(())
+}
+
+fn decisions() -> Result<Vec<CredentialDecision>, CredentialConsentError> {
+    let mut decisions = DECISIONS.lock().unwrap();
+    if decisions.is_none() {
+        *decisions = Some(load()?);
+    }
+
+    Ok(decisions.clone().unwrap_or_default())
+}
+
+fn decision(decisions: &[CredentialDecision], request: &CredentialRequest) -> Option<bool> {
+    decisions
+        .iter()
+        .find(|decision| &decision.request == request)
+        .map(|decision| decision.allowed)
+}
+
+fn audit(request: &CredentialRequest, decision: &str) {
+    let requester = request.requester.label();
+    info!(
+        target: module_path!(),
+        credential_requester = requester.as_str(),
+        credential_account = request.account.as_str();
+        "Credential access {}",
+        decision
+    );
+}
+
+/// Whether the requester may use the secret. The first request is denied and put in front of the user, their decision
+/// applies from then on.
+pub fn is_allowed(app_handle: &AppHandle, request: &CredentialRequest) -> bool {
+    match decisions().map(|decisions| decision(&decisions, request)) {
+        Ok(Some(allowed)) => allowed,
+        Ok(None) => {
+            ask(app_handle, request);
+            false
+        }
+        Err(err) => {
+            warn!(
+                "Unable to read credential decisions, denying access: {}",
+                err
+            );
+            false
+        }
+    }
+}
+
+fn ask(app_handle: &AppHandle, request: &CredentialRequest) {
+    if !PENDING.lock().unwrap().insert(request.clone()) {
+        return;
+    }
+
+    audit(request, "requested");
+    let msg = UiMessage::CredentialRequested(request.clone());
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        if let Err(err) = state.ui_messages.send(msg).await {
+            error!("Failed to broadcast credential request: {:?}", err);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_credential_decisions() -> Result<Vec<CredentialDecision>, CredentialConsentError> {
+    decisions()
+}
+
+/// Remembers what the user decided about a request, secrets they allowed are picked up right away.
+#[tauri::command]
+pub fn decide_credential_request(
+    app_handle: AppHandle,
+    request: CredentialRequest,
+    allowed: bool,
+) -> Result<(), CredentialConsentError> {
+    let mut decisions = decisions()?;
+    decisions.retain(|decision| decision.request != request);
+    decisions.push(CredentialDecision {
+        request: request.clone(),
+        allowed,
+        decided_at: Utc::now(),
+    });
+    save(&decisions)?;
+    *DECISIONS.lock().unwrap() = Some(decisions);
+    PENDING.lock().unwrap().remove(&request);
+
+    audit(&request, if allowed { "allowed" } else { "denied" });
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Credentials,
+        if allowed {
+            "allow credential access"
+        } else {
+            "deny credential access"
+        },
+        Some(&request.label()),
+    );
+    if allowed {
+        environment::reload_global(&app_handle);
+    }
+
+    Ok(())
+}
+
+/// Forgets all decisions, every requester has to ask again.
+#[tauri::command]
+pub fn forget_credential_decisions(app_handle: AppHandle) -> Result<(), CredentialConsentError> {
+    match keychain::delete_password(CONSENT_SERVICE, CONSENT_ACCOUNT) {
+        Ok(()) | Err(KeychainError::NotFound(..)) => {}
+        Err(err) => return Err(err.into()),
+    }
+    *DECISIONS.lock().unwrap() = Some(vec![]);
+    PENDING.lock().unwrap().clear();
+
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Credentials,
+        "forget credential decisions",
+        None,
+    );
+    environment::reload_global(&app_handle);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_remember_decisions_per_requester() {
+        let request = |requester| {
+            CredentialRequest::new(requester, SecretKind::EnvironmentVariable, "GITHUB_TOKEN")
+        };
+        let decisions = vec![CredentialDecision {
+            request: request(CredentialRequester::Provider("docker".to_string())),
+            allowed: true,
+            decided_at: Utc::now(),
+        }];
+
+        assert_eq!(
+            decision(
+                &decisions,
+                &request(CredentialRequester::Provider("docker".to_string()))
+            ),
+            Some(true)
+        );
+        assert_eq!(
+            decision(
+                &decisions,
+                &request(CredentialRequester::Provider("imported".to_string()))
+            ),
+            None
+        );
+        assert_eq!(
+            decision(&decisions, &request(CredentialRequester::AllWorkspaces)),
+            None
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use crate::{
+    installed_ides::{IdeSource, InstalledIde},
+    settings::Settings,
+    ssh_config,
+    util::shell_command,
+    workspaces, AppHandle,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use thiserror::Error;
+use ts_rs::TS;
+
+// Everything the CLI opens by itself, a custom IDE must not shadow them
+const BUILTIN_IDES: &[&str] = &[
+    "none",
+    "vscode",
+    "openvscode",
+    "fleet",
+    "jupyternotebook",
+    "intellij",
+    "goland",
+    "pycharm",
+    "phpstorm",
+    "clion",
+    "rubymine",
+    "rider",
+    "webstorm",
+];
+
+/// An IDE the user registered themselves, opened by running `command` on this machine once the workspace is up.
+/// `{host}`, `{folder}` and `{ssh}` in the command are replaced with the workspace's SSH host, the folder in the
+/// workspace and both combined as `ssh://<host><folder>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CustomIde {
+    /// What it's selected as wherever an `ide` is accepted, e.g. `devpod://open?ide=<id>`
+    id: String,
+    name: String,
+    command: String,
+}
+
+impl CustomIde {
+    fn render(&self, workspace_id: &str) -> String {
+        let host = ssh_config::host_alias(workspace_id);
+        let folder = workspaces::remote_folder(workspace_id);
+
+        self.command
+            .trim()
+            .replace("{ssh}", &format!("ssh://{}{}", host, folder))
+            .replace("{host}", &host)
+            .replace("{folder}", &folder)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum CustomIdeError {
+    #[error("invalid {0}")]
+    Invalid(&'static str),
+    #[error("{0} is a built-in IDE")]
+    Builtin(String),
+    #[error("no custom IDE {0}")]
+    NotFound(String),
+    #[error("unable to save custom IDEs")]
+    Persist(#[source] anyhow::Error),
+    #[error("unable to launch {0}")]
+    Launch(String, #[source] std::io::Error),
+}
+impl serde::Serialize for CustomIdeError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn is_id(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_'))
+}
+
+fn validate(ide: &CustomIde) -> Result<(), CustomIdeError> {
+    if !is_id(&ide.id) {
+        return Err(CustomIdeError::Invalid("id"));
+    }
+    if BUILTIN_IDES.contains(&ide.id.as_str()) {
+        return Err(CustomIdeError::Builtin(ide.id.clone()));
+    }
+    if ide.name.trim().is_empty() {
+        return Err(CustomIdeError::Invalid("name"));
+    }
+    if ide.command.trim().is_empty() || ide.command.contains(['\n', '\r']) {
+        return Err(CustomIdeError::Invalid("command"));
+    }
+
+    Ok(())
+}
+
+/// The registered IDE with `id`, `None` for the ones the CLI opens itself.
+pub fn find(app_handle: &AppHandle, id: &str) -> Option<CustomIde> {
+    Settings::custom_ides(app_handle)
+        .into_iter()
+        .find(|ide| ide.id == id)
+}
+
+/// The registered IDEs the way the IDE picker lists installed ones.
+pub fn as_installed(app_handle: &AppHandle) -> Vec<InstalledIde> {
+    Settings::custom_ides(app_handle)
+        .into_iter()
+        .map(|ide| {
+            InstalledIde::new(
+                ide.id.clone(),
+                ide.name,
+                Some(ide.id),
+                ide.command,
+                IdeSource::Custom,
+            )
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn list_custom_ides(app_handle: AppHandle) -> Vec<CustomIde> {
+    Settings::custom_ides(&app_handle)
+}
+
+/// Adds the IDE, or replaces the one with the same id.
+#[tauri::command]
+pub fn register_custom_ide(
+    app_handle: AppHandle,
+    ide: CustomIde,
+) -> Result<Vec<CustomIde>, CustomIdeError> {
+    validate(&ide)?;
+
+    let mut ides = Settings::custom_ides(&app_handle);
+    info!("Registered custom IDE {}", ide.id);
+    match ides.iter_mut().find(|existing| existing.id == ide.id) {
+        Some(existing) => *existing = ide,
+        None => ides.push(ide),
+    }
+    Settings::set_custom_ides(&app_handle, &ides).map_err(CustomIdeError::Persist)?;
+
+    Ok(ides)
+}
+
+#[tauri::command]
+pub fn remove_custom_ide(
+    app_handle: AppHandle,
+    id: String,
+) -> Result<Vec<CustomIde>, CustomIdeError> {
+    let mut ides = Settings::custom_ides(&app_handle);
+    let count = ides.len();
+    ides.retain(|ide| ide.id != id);
+    if ides.len() == count {
+        return Err(CustomIdeError::NotFound(id));
+    }
+    info!("Removed custom IDE {}", id);
+    Settings::set_custom_ides(&app_handle, &ides).map_err(CustomIdeError::Persist)?;
+
+    Ok(ides)
+}
+
+/// Runs the IDE's command for a workspace that has been started with `--ide none`.
+#[tauri::command]
+pub fn open_custom_ide(
+    app_handle: AppHandle,
+    workspace_id: String,
+    id: String,
+) -> Result<(), CustomIdeError> {
+    let ide = find(&app_handle, &id).ok_or_else(|| CustomIdeError::NotFound(id.clone()))?;
+
+    info!("Opening {} in {}", workspace_id, ide.id);
+    shell_command(&ide.render(&workspace_id))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| CustomIdeError::Launch(ide.name, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ide(id: &str, command: &str) -> CustomIde {
+        CustomIde {
+            id: id.to_string(),
+            name: "Editor".to_string(),
+            command: command.to_string(),
+        }
+    }
+
+    #[test]
+    fn should_render_placeholders() {
+        assert_eq!(
+            ide("editor", "editor --remote {host} {folder}").render("my-project"),
+            "editor --remote my-project.devpod /workspaces/my-project"
+        );
+        assert_eq!(
+            ide("editor", " editor {ssh} ").render("my-project"),
+            "editor ssh://my-project.devpod/workspaces/my-project"
+        );
+    }
+
+    #[test]
+    fn should_reject_builtin_and_invalid_ids() {
+        assert!(validate(&ide("sublime", "subl {folder}")).is_ok());
+        assert!(matches!(
+            validate(&ide("vscode", "code {folder}")),
+            Err(CustomIdeError::Builtin(_))
+        ));
+        assert!(validate(&ide("My Editor", "editor")).is_err());
+        assert!(validate(&ide("editor", "editor\nrm -rf /")).is_err());
+    }
+}
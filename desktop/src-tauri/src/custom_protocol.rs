@@ -1,11 +1,16 @@
-use log::{error, info};
+use lazy_static::lazy_static;
+use log::{error, info, warn};
 use serde::{de, Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path, sync::Mutex};
 use tauri::{AppHandle, Manager, State};
 use thiserror::Error;
+use ts_rs::TS;
 use url::Url;
 
 use crate::{
+    custom_ides, devcontainer,
+    link_consent::{self, ActionOrigin},
+    telemetry::Span,
     ui_messages::{ShowToastMsg, ToastStatus},
     AppState, UiMessage,
 };
@@ -14,8 +19,50 @@ use crate::{
 const APP_IDENTIFIER: &str = "sh.loft.devpod";
 const APP_URL_SCHEME: &str = "devpod";
 
+lazy_static! {
+    static ref REGISTRATION: Mutex<Option<DeepLinkRegistration>> = Mutex::new(None);
+}
+
 pub struct CustomProtocol;
 
+/// Outcome of registering the app as handler for `devpod://` links.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DeepLinkRegistration {
+    /// How the app has been packaged, e.g. `flatpak` or `appimage`
+    packaging: String,
+    mechanisms: Vec<DeepLinkMechanism>,
+}
+
+impl DeepLinkRegistration {
+    fn failed_mechanisms(&self) -> Vec<&str> {
+        self.mechanisms
+            .iter()
+            .filter(|mechanism| mechanism.error.is_some())
+            .map(|mechanism| mechanism.name.as_str())
+            .collect()
+    }
+}
+
+/// A single step of the registration, e.g. writing the desktop entry or setting the default handler.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DeepLinkMechanism {
+    name: String,
+    error: Option<String>,
+}
+
+impl DeepLinkMechanism {
+    fn new<E: std::fmt::Display>(name: &str, result: Result<(), E>) -> Self {
+        Self {
+            name: name.to_string(),
+            error: result.err().map(|err| err.to_string()),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct OpenWorkspaceMsg {
     #[serde(rename(deserialize = "workspace"))]
@@ -24,6 +71,29 @@ pub struct OpenWorkspaceMsg {
     provider_id: Option<String>,
     ide: Option<String>,
     source: Option<String>,
+    #[serde(rename(deserialize = "devcontainer"))]
+    devcontainer_path: Option<String>,
+    /// VS Code profile to open the workspace with, the one from the settings if not set
+    #[serde(rename(deserialize = "vscode-profile"))]
+    vscode_profile: Option<String>,
+    /// Whether `ide` has been picked based on the devcontainer customizations rather than by the user
+    #[serde(skip_deserializing)]
+    ide_suggested: bool,
+    /// Whether VS Code opens the workspace in the last active window instead of a new one
+    #[serde(rename(deserialize = "reuse-window"))]
+    reuse_window: Option<bool>,
+    /// Whether `ide` is one the user registered, the workspace is then started without an IDE and the custom one is
+    /// launched afterwards
+    #[serde(skip_deserializing)]
+    custom_ide: bool,
+    #[serde(skip_deserializing)]
+    origin: ActionOrigin,
+    /// What the user is asked to trust, the host of the source
+    #[serde(skip_deserializing)]
+    consent_domain: Option<String>,
+    /// Whether the workspace may be opened without asking the user
+    #[serde(skip_deserializing)]
+    trusted: bool,
 }
 
 #[derive(Debug, PartialEq, Serialize, Clone)]
@@ -32,6 +102,11 @@ pub struct ImportWorkspaceMsg {
     workspace_uid: String,
     devpod_pro_host: String,
     options: HashMap<String, String>,
+    origin: ActionOrigin,
+    /// What the user is asked to trust, the host of the Pro instance
+    consent_domain: Option<String>,
+    /// Whether the workspace may be imported without asking the user
+    trusted: bool,
 }
 
 impl<'de> Deserialize<'de> for ImportWorkspaceMsg {
@@ -58,6 +133,9 @@ impl<'de> Deserialize<'de> for ImportWorkspaceMsg {
             workspace_uid,
             devpod_pro_host,
             options,
+            origin: ActionOrigin::default(),
+            consent_domain: None,
+            trusted: false,
         })
     }
 }
@@ -77,6 +155,14 @@ impl OpenWorkspaceMsg {
             provider_id: None,
             ide: None,
             source: None,
+            devcontainer_path: None,
+            vscode_profile: None,
+            reuse_window: None,
+            ide_suggested: false,
+            custom_ide: false,
+            origin: ActionOrigin::App,
+            consent_domain: None,
+            trusted: true,
         }
     }
     pub fn with_id(id: String) -> OpenWorkspaceMsg {
@@ -85,8 +171,104 @@ impl OpenWorkspaceMsg {
             provider_id: None,
             ide: None,
             source: None,
+            devcontainer_path: None,
+            vscode_profile: None,
+            reuse_window: None,
+            ide_suggested: false,
+            custom_ide: false,
+            origin: ActionOrigin::App,
+            consent_domain: None,
+            trusted: true,
         }
     }
+    pub fn new(
+        workspace_id: Option<String>,
+        provider_id: Option<String>,
+        ide: Option<String>,
+        source: Option<String>,
+        devcontainer_path: Option<String>,
+    ) -> OpenWorkspaceMsg {
+        OpenWorkspaceMsg {
+            workspace_id,
+            provider_id,
+            ide,
+            source,
+            devcontainer_path,
+            vscode_profile: None,
+            reuse_window: None,
+            ide_suggested: false,
+            custom_ide: false,
+            origin: ActionOrigin::App,
+            consent_domain: None,
+            trusted: true,
+        }
+    }
+
+    /// Picks the IDE the devcontainer of a local source has been customized for, unless one has been chosen.
+    pub fn with_suggested_ide(mut self) -> Self {
+        if self.ide.is_some() {
+            return self;
+        }
+        let Some(source) = self
+            .source
+            .as_deref()
+            .map(Path::new)
+            .filter(|source| source.is_dir())
+        else {
+            return self;
+        };
+
+        self.ide = devcontainer::read_local(source, self.devcontainer_path.as_deref())
+            .and_then(|config| devcontainer::preferred_ide(&config));
+        self.ide_suggested = self.ide.is_some();
+
+        self
+    }
+
+    fn resolve_custom_ide(mut self, app_handle: &AppHandle) -> Self {
+        self.custom_ide = self
+            .ide
+            .as_deref()
+            .is_some_and(|ide| custom_ides::find(app_handle, ide).is_some());
+
+        self
+    }
+
+    fn with_origin(mut self, app_handle: &AppHandle, origin: ActionOrigin) -> Self {
+        self.origin = origin;
+        self.consent_domain = self.source.as_deref().and_then(link_consent::source_domain);
+        self.trusted = origin == ActionOrigin::App
+            || link_consent::is_trusted(app_handle, self.consent_domain.as_deref());
+        audit(origin, "open", self.consent_domain.as_deref(), self.trusted);
+
+        self
+    }
+}
+
+impl ImportWorkspaceMsg {
+    fn with_origin(mut self, app_handle: &AppHandle, origin: ActionOrigin) -> Self {
+        self.origin = origin;
+        self.consent_domain = link_consent::source_domain(&self.devpod_pro_host);
+        self.trusted = origin == ActionOrigin::App
+            || link_consent::is_trusted(app_handle, self.consent_domain.as_deref());
+        audit(
+            origin,
+            "import",
+            self.consent_domain.as_deref(),
+            self.trusted,
+        );
+
+        self
+    }
+}
+
+fn audit(origin: ActionOrigin, action: &str, domain: Option<&str>, trusted: bool) {
+    let decision = if trusted {
+        "allowed"
+    } else {
+        "awaiting consent"
+    };
+    link_consent::audit(origin, action, domain, decision);
 }
 
 pub struct Request {
@@ -208,72 +390,133 @@ impl CustomProtocol {
 
     pub fn setup(&self, app: AppHandle) {
         let app_handle = app.clone();
+        let handler =
+            move |url_scheme: String| CustomProtocol::handle_url(&app_handle, &url_scheme);
 
-        let result = tauri_plugin_deep_link::register(APP_URL_SCHEME, move |url_scheme| {
-            tauri::async_runtime::block_on(async {
-                info!("App opened with URL: {:?}", url_scheme.to_string());
-
-                let request = UrlParser::parse(&url_scheme.to_string());
-                let app_state = app_handle.state::<AppState>();
-                if let Err(err) = request {
-                    #[cfg(not(target_os = "windows"))]
-                    send_ui_message(
-                        app_state,
-                        UiMessage::CommandFailed(err),
-                        "Failed to broadcast custom protocol message",
-                    )
-                    .await;
-                    return;
+        // The plugin only knows about `xdg-mime` and `update-desktop-database`, which don't work in
+        // sandboxed packages, so on Linux it's only used to listen for URLs
+        #[cfg(target_os = "linux")]
+        let registration = match tauri_plugin_deep_link::listen(handler) {
+            Ok(..) => linux::register(APP_URL_SCHEME),
+            Err(err) => DeepLinkRegistration {
+                packaging: linux::Packaging::detect().to_string(),
+                mechanisms: vec![DeepLinkMechanism::new("listener", Err(err))],
+            },
+        };
+        #[cfg(not(target_os = "linux"))]
+        let registration = DeepLinkRegistration {
+            packaging: "native".to_string(),
+            mechanisms: vec![DeepLinkMechanism::new(
+                "url scheme",
+                tauri_plugin_deep_link::register(APP_URL_SCHEME, handler),
+            )],
+        };
+
+        let failed = registration.failed_mechanisms();
+        if failed.is_empty() {
+            info!(
+                "Registered {} URL handler for {} package",
+                APP_URL_SCHEME, registration.packaging
+            );
+        } else {
+            for mechanism in &registration.mechanisms {
+                if let Some(err) = &mechanism.error {
+                    warn!("Custom protocol setup failed; {}: {}", mechanism.name, err);
                 }
-                let request = request.unwrap();
+            }
 
-                match request.host.as_str() {
-                    "open" => {
-                        let msg = CustomProtocol::parse(&request);
-                        OpenHandler::handle(msg, app_state).await
-                    }
+            #[cfg(target_os = "linux")]
+            tauri::async_runtime::block_on(async {
+                let app_state = app.state::<AppState>();
+                let show_toast_msg = ShowToastMsg::new(
+                    "Custom protocol handling needs to be configured".to_string(),
+                    format!(
+                        "Unable to register DevPod for {} links ({} package), failed: {}",
+                        APP_URL_SCHEME,
+                        registration.packaging,
+                        failed.join(", ")
+                    ),
+                    ToastStatus::Warning,
+                );
+                if let Err(err) = app_state
+                    .ui_messages
+                    .send(UiMessage::ShowToast(show_toast_msg))
+                    .await
+                {
+                    log::error!(
+                        "Failed to broadcast show toast message: {:?}, {}",
+                        err.0,
+                        err
+                    );
+                };
+            })
+        }
+
+        *REGISTRATION.lock().unwrap() = Some(registration);
+    }
+
+    /// Whether `arg` is a URL this app is registered for, i.e. one passed by the OS when opening a link.
+    pub fn is_url(arg: &str) -> bool {
+        arg.starts_with(&format!("{}://", APP_URL_SCHEME))
+    }
+
+    /// Dispatches a `devpod://` URL, either received from the OS, forwarded by a second instance or dropped onto the
+    /// window. It may come from any web page, so the user is asked before it runs.
+    pub fn handle_url(app_handle: &AppHandle, url_scheme: &str) {
+        Self::dispatch(app_handle, url_scheme, ActionOrigin::DeepLink)
+    }
+
+    /// Dispatches a `devpod://` URL the app built itself, e.g. for a notification the user clicked.
+    pub fn handle_app_url(app_handle: &AppHandle, url_scheme: &str) {
+        Self::dispatch(app_handle, url_scheme, ActionOrigin::App)
+    }
 
-                    "import" => {
-                        let msg = CustomProtocol::parse(&request);
-                        ImportHandler::handle(msg, app_state).await
+    fn dispatch(app_handle: &AppHandle, url_scheme: &str, origin: ActionOrigin) {
+        tauri::async_runtime::block_on(async {
+            info!("App opened with URL: {:?}", url_scheme);
+            let mut span = Span::start("deep link");
+
+            let request = UrlParser::parse(url_scheme);
+            let app_state = app_handle.state::<AppState>();
+            if let Err(err) = request {
+                span.set_error();
+                link_consent::audit(origin, "unknown", None, "rejected");
+                #[cfg(not(target_os = "windows"))]
+                send_ui_message(
+                    app_state,
+                    UiMessage::CommandFailed(err),
+                    "Failed to broadcast custom protocol message",
+                )
+                .await;
+                return;
+            }
+            let request = request.unwrap();
+            span.set_attribute("deep_link.host", &request.host);
+
+            match request.host.as_str() {
+                "open" => {
+                    let msg = CustomProtocol::parse::<OpenWorkspaceMsg>(&request).map(|msg| {
+                        msg.with_suggested_ide()
+                            .resolve_custom_ide(app_handle)
+                            .with_origin(app_handle, origin)
+                    });
+                    if msg.is_err() {
+                        link_consent::audit(origin, "open", None, "rejected");
                     }
-                    _ => {}
+                    OpenHandler::handle(msg, app_state).await
                 }
-            })
-        });
 
-        #[cfg(target_os = "linux")]
-        {
-            match result {
-                Ok(..) => {}
-                Err(error) => {
-                    let msg = "Either update-desktop-database or xdg-mime are missing. Please make sure they are available on your system";
-                    log::warn!("Custom protocol setup failed; {}: {}", msg, error);
-
-                    tauri::async_runtime::block_on(async {
-                        let app_state = app.state::<AppState>();
-                        let show_toast_msg = ShowToastMsg::new(
-                            "Custom protocol handling needs to be configured".to_string(),
-                            msg.to_string(),
-                            ToastStatus::Warning,
-                        );
-                        if let Err(err) = app_state
-                            .ui_messages
-                            .send(UiMessage::ShowToast(show_toast_msg))
-                            .await
-                        {
-                            log::error!(
-                                "Failed to broadcast show toast message: {:?}, {}",
-                                err.0,
-                                err
-                            );
-                        };
-                    })
+                "import" => {
+                    let msg = CustomProtocol::parse::<ImportWorkspaceMsg>(&request)
+                        .map(|msg| msg.with_origin(app_handle, origin));
+                    if msg.is_err() {
+                        link_consent::audit(origin, "import", None, "rejected");
+                    }
+                    ImportHandler::handle(msg, app_state).await
                 }
-            };
-        }
-
-        let _ = result;
+                _ => {}
+            }
+        })
     }
 
     fn parse<'a, Msg>(request: &'a Request) -> Result<Msg, ParseError>
@@ -285,6 +528,313 @@ impl CustomProtocol {
     }
 }
 
+/// Registers the app for `devpod://` links again, e.g. once the tools it needs have been installed. The listener
+/// that's already running keeps receiving the links. Returns the mechanisms that failed.
+#[cfg(target_os = "linux")]
+pub fn register_again() -> Vec<String> {
+    let registration = linux::register(APP_URL_SCHEME);
+    let failed = registration
+        .failed_mechanisms()
+        .into_iter()
+        .map(String::from)
+        .collect();
+    *REGISTRATION.lock().unwrap() = Some(registration);
+
+    failed
+}
+
+/// How the app registered itself for `devpod://` links, `None` until the app has been set up.
+#[tauri::command]
+pub fn get_deep_link_registration() -> Option<DeepLinkRegistration> {
+    REGISTRATION.lock().unwrap().clone()
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{DeepLinkMechanism, DeepLinkRegistration, APP_IDENTIFIER};
+    use anyhow::{anyhow, Context};
+    use std::{
+        env, fmt, fs,
+        path::{Path, PathBuf},
+        process::Command,
+    };
+
+    const DEFAULT_APPLICATIONS: &str = "[Default Applications]";
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Packaging {
+        Flatpak,
+        Snap,
+        AppImage,
+        Native,
+    }
+
+    impl Packaging {
+        pub fn detect() -> Self {
+            if env::var_os("FLATPAK_ID").is_some() || Path::new("/.flatpak-info").exists() {
+                Self::Flatpak
+            } else if env::var_os("SNAP").is_some() {
+                Self::Snap
+            } else if env::var_os("APPIMAGE").is_some() {
+                Self::AppImage
+            } else {
+                Self::Native
+            }
+        }
+    }
+
+    impl fmt::Display for Packaging {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let name = match self {
+                Self::Flatpak => "flatpak",
+                Self::Snap => "snap",
+                Self::AppImage => "appimage",
+                Self::Native => "native",
+            };
+            write!(f, "{}", name)
+        }
+    }
+
+    pub fn register(scheme: &str) -> DeepLinkRegistration {
+        let packaging = Packaging::detect();
+        let mime_type = format!("x-scheme-handler/{}", scheme);
+        let mechanisms = match packaging {
+            Packaging::Flatpak => register_flatpak(&mime_type),
+            Packaging::Snap => register_snap(&mime_type),
+            Packaging::AppImage | Packaging::Native => register_desktop_entry(&mime_type),
+        };
+
+        DeepLinkRegistration {
+            packaging: packaging.to_string(),
+            mechanisms,
+        }
+    }
+
+    /// Flatpaks can't write to the host's applications, the entry exported by the manifest has to declare the scheme.
+    fn register_flatpak(mime_type: &str) -> Vec<DeepLinkMechanism> {
+        let app_id = env::var("FLATPAK_ID").unwrap_or_else(|_| APP_IDENTIFIER.to_string());
+        let desktop_file = format!("{}.desktop", app_id);
+        let exported = Path::new("/app/share/applications").join(&desktop_file);
+
+        vec![
+            DeepLinkMechanism::new("exported desktop entry", declares(&exported, mime_type)),
+            // Needs `--talk-name=org.freedesktop.Flatpak`
+            DeepLinkMechanism::new(
+                "flatpak-spawn xdg-mime",
+                run(
+                    "flatpak-spawn",
+                    &["--host", "xdg-mime", "default", &desktop_file, mime_type],
+                ),
+            ),
+        ]
+    }
+
+    /// snapd exports the entries in `meta/gui`, prefixed with the snap's name.
+    fn register_snap(mime_type: &str) -> Vec<DeepLinkMechanism> {
+        let snap = env::var("SNAP").unwrap_or_default();
+        let name = env::var("SNAP_INSTANCE_NAME")
+            .or_else(|_| env::var("SNAP_NAME"))
+            .unwrap_or_else(|_| "devpod".to_string());
+        let entry = Path::new(&snap)
+            .join("meta/gui")
+            .join(format!("{}.desktop", name));
+        let desktop_file = format!("{}_{}.desktop", name, name);
+
+        vec![
+            DeepLinkMechanism::new("snap desktop entry", declares(&entry, mime_type)),
+            default_handler(&desktop_file, mime_type),
+        ]
+    }
+
+    fn register_desktop_entry(mime_type: &str) -> Vec<DeepLinkMechanism> {
+        let (applications_dir, desktop_file) = match entry_location() {
+            Ok(location) => location,
+            Err(err) => return vec![DeepLinkMechanism::new("desktop entry", Err(err))],
+        };
+        // AppImages are mounted at a different path on every start, so link to the image itself
+        let exec = env::var("APPIMAGE")
+            .map(PathBuf::from)
+            .or_else(|_| tauri::utils::platform::current_exe());
+        let written = exec
+            .context("unable to find app executable")
+            .and_then(|exec| {
+                fs::create_dir_all(&applications_dir)?;
+                fs::write(
+                    applications_dir.join(&desktop_file),
+                    desktop_entry(&exec, mime_type),
+                )?;
+                Ok(())
+            });
+        if written.is_err() {
+            return vec![DeepLinkMechanism::new("desktop entry", written)];
+        }
+
+        let applications_dir = applications_dir.to_string_lossy();
+        vec![
+            DeepLinkMechanism::new("desktop entry", written),
+            DeepLinkMechanism::new(
+                "update-desktop-database",
+                run("update-desktop-database", &[&applications_dir]),
+            ),
+            default_handler(&desktop_file, mime_type),
+        ]
+    }
+
+    /// Same file name as `tauri-plugin-deep-link` so that existing entries are replaced.
+    fn entry_location() -> anyhow::Result<(PathBuf, String)> {
+        let applications_dir = dirs::data_dir()
+            .ok_or_else(|| anyhow!("data directory not found"))?
+            .join("applications");
+        let exe = tauri::utils::platform::current_exe()?;
+        let exe_name = exe
+            .file_name()
+            .ok_or_else(|| anyhow!("unable to get file name of the app executable"))?;
+
+        Ok((
+            applications_dir,
+            format!("{}-handler.desktop", exe_name.to_string_lossy()),
+        ))
+    }
+
+    fn desktop_entry(exec: &Path, mime_type: &str) -> String {
+        format!(
+            "[Desktop Entry]\nType=Application\nName=DevPod\nExec=\"{}\" %u\nTerminal=false\nMimeType={};\nNoDisplay=true\n",
+            exec.to_string_lossy(),
+            mime_type
+        )
+    }
+
+    fn declares(desktop_entry: &Path, mime_type: &str) -> anyhow::Result<()> {
+        let content = fs::read_to_string(desktop_entry)
+            .with_context(|| format!("unable to read {}", desktop_entry.display()))?;
+        let declared = content
+            .lines()
+            .filter_map(|line| line.strip_prefix("MimeType="))
+            .any(|mime_types| mime_types.split(';').any(|t| t.trim() == mime_type));
+        if !declared {
+            return Err(anyhow!(
+                "{} doesn't declare {}",
+                desktop_entry.display(),
+                mime_type
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Prefers `xdg-mime`, but edits `mimeapps.list` directly if it isn't installed.
+    fn default_handler(desktop_file: &str, mime_type: &str) -> DeepLinkMechanism {
+        if run("xdg-mime", &["default", desktop_file, mime_type]).is_ok() {
+            return DeepLinkMechanism::new("xdg-mime", Ok::<(), anyhow::Error>(()));
+        }
+
+        let patched = dirs::config_dir()
+            .ok_or_else(|| anyhow!("config directory not found"))
+            .and_then(|config_dir| {
+                let path = config_dir.join("mimeapps.list");
+                let content = fs::read_to_string(&path).unwrap_or_default();
+                fs::create_dir_all(&config_dir)?;
+                fs::write(&path, set_default(&content, mime_type, desktop_file))?;
+                Ok(())
+            });
+
+        DeepLinkMechanism::new("mimeapps.list", patched)
+    }
+
+    fn run(program: &str, args: &[&str]) -> anyhow::Result<()> {
+        let status = Command::new(program)
+            .args(args)
+            .status()
+            .with_context(|| format!("unable to run {}", program))?;
+        if !status.success() {
+            return Err(anyhow!("{} exited with {}", program, status));
+        }
+
+        Ok(())
+    }
+
+    /// Sets `desktop_file` as default for `mime_type` in the content of a `mimeapps.list`.
+    fn set_default(content: &str, mime_type: &str, desktop_file: &str) -> String {
+        let entry = format!("{}={};", mime_type, desktop_file);
+        let mut lines: Vec<String> = content.lines().map(String::from).collect();
+        let section = match lines
+            .iter()
+            .position(|line| line.trim() == DEFAULT_APPLICATIONS)
+        {
+            Some(section) => section,
+            None => {
+                if lines.last().is_some_and(|line| !line.is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.push(DEFAULT_APPLICATIONS.to_string());
+                lines.len() - 1
+            }
+        };
+        let section_end = lines[section + 1..]
+            .iter()
+            .position(|line| line.starts_with('['))
+            .map_or(lines.len(), |end| section + 1 + end);
+        let existing = lines[section + 1..section_end]
+            .iter()
+            .position(|line| line.starts_with(&format!("{}=", mime_type)));
+
+        match existing {
+            Some(existing) => lines[section + 1 + existing] = entry,
+            None => lines.insert(section + 1, entry),
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn should_set_default_handler() {
+            let handler = "x-scheme-handler/devpod=dev-pod-handler.desktop;";
+
+            assert_eq!(
+                set_default("", "x-scheme-handler/devpod", "dev-pod-handler.desktop"),
+                format!("[Default Applications]\n{}\n", handler)
+            );
+            assert_eq!(
+                set_default(
+                    "[Default Applications]\nx-scheme-handler/devpod=old.desktop;\n[Added Associations]\nx-scheme-handler/devpod=other.desktop;\n",
+                    "x-scheme-handler/devpod",
+                    "dev-pod-handler.desktop"
+                ),
+                format!(
+                    "[Default Applications]\n{}\n[Added Associations]\nx-scheme-handler/devpod=other.desktop;\n",
+                    handler
+                )
+            );
+            assert_eq!(
+                set_default(
+                    "[Added Associations]\n",
+                    "x-scheme-handler/devpod",
+                    "dev-pod-handler.desktop"
+                ),
+                format!(
+                    "[Added Associations]\n\n[Default Applications]\n{}\n",
+                    handler
+                )
+            );
+        }
+
+        #[test]
+        fn should_quote_exec() {
+            let entry = desktop_entry(
+                Path::new("/home/user/Apps/DevPod 0.4.AppImage"),
+                "x-scheme-handler/devpod",
+            );
+
+            assert!(entry.contains("Exec=\"/home/user/Apps/DevPod 0.4.AppImage\" %u\n"));
+            assert!(entry.contains("MimeType=x-scheme-handler/devpod;\n"));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     mod url_parser {
@@ -341,6 +891,17 @@ mod tests {
             assert_eq!(got.ide, Some("vscode".into()));
         }
 
+        #[test]
+        fn should_parse_vscode_options() {
+            let url_str =
+                "devpod://open?workspace=workspace&ide=vscode&vscode-profile=Go&reuse-window=true";
+            let request = UrlParser::parse(&url_str).unwrap();
+            let got: OpenWorkspaceMsg = CustomProtocol::parse(&request).unwrap();
+
+            assert_eq!(got.vscode_profile, Some("Go".to_string()));
+            assert_eq!(got.reuse_window, Some(true));
+        }
+
         #[test]
         fn should_parse_workspace() {
             let url_str = "devpod://open?workspace=some-workspace";
@@ -0,0 +1,193 @@
+use crate::{
+    command_queue, error_reporting, redaction, telemetry,
+    ui_messages::{self, UiMessageRecord},
+    window::WindowHelper,
+    AppHandle, AppState,
+};
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use log::{error, info};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+use tauri::Manager;
+use ts_rs::TS;
+
+pub const DEBUG_CONSOLE_WINDOW_LABEL: &str = "debug_console";
+// WARN: needs to match the event name in `debug-console/src/main.tsx`
+const SNAPSHOT_EVENT: &str = "debug_console_snapshot";
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+const WINDOW_OPEN_TIMEOUT_TICKS: u32 = 10;
+
+lazy_static! {
+    static ref TASKS: Mutex<BTreeMap<u64, Activity>> = Mutex::new(BTreeMap::new());
+    static ref PROCESSES: Mutex<BTreeMap<u64, Activity>> = Mutex::new(BTreeMap::new());
+}
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Something the backend is busy with, tracked from start until the guard returned by `track_*` is dropped.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct Activity {
+    name: String,
+    detail: Option<String>,
+    started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct QueueDepth {
+    name: String,
+    len: usize,
+    capacity: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DebugSnapshot {
+    timestamp: DateTime<Utc>,
+    #[ts(type = "Array<{ timestamp: string, message: { type: string } }>")]
+    ui_messages: Vec<UiMessageRecord>,
+    active_actions: Vec<String>,
+    tasks: Vec<Activity>,
+    processes: Vec<Activity>,
+    queues: Vec<QueueDepth>,
+}
+
+#[must_use]
+pub struct Tracked {
+    id: u64,
+    registry: &'static Mutex<BTreeMap<u64, Activity>>,
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.registry.lock().unwrap().remove(&self.id);
+    }
+}
+
+fn track(
+    registry: &'static Mutex<BTreeMap<u64, Activity>>,
+    name: &str,
+    detail: Option<String>,
+) -> Tracked {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    registry.lock().unwrap().insert(
+        id,
+        Activity {
+            name: name.to_string(),
+            detail,
+            started_at: Utc::now(),
+        },
+    );
+
+    Tracked { id, registry }
+}
+
+/// Shows `name` as a running background task until the returned guard is dropped.
+pub fn track_task(name: &str, detail: Option<String>) -> Tracked {
+    track(&TASKS, name, detail)
+}
+
+/// Shows a spawned subprocess until the returned guard is dropped, `args` are redacted.
+pub fn track_process(program: &str, args: &[&str]) -> Tracked {
+    let args = redaction::redact(&args.join(" ")).into_owned();
+
+    track(&PROCESSES, program, Some(args))
+}
+
+fn activities(registry: &Mutex<BTreeMap<u64, Activity>>) -> Vec<Activity> {
+    registry.lock().unwrap().values().cloned().collect()
+}
+
+fn snapshot(app_handle: &AppHandle) -> DebugSnapshot {
+    let state = app_handle.state::<AppState>();
+    let ui_messages_tx = &state.ui_messages;
+    let mut active_actions: Vec<_> = state
+        .active_actions
+        .lock()
+        .unwrap()
+        .iter()
+        .cloned()
+        .collect();
+    active_actions.sort();
+
+    DebugSnapshot {
+        timestamp: Utc::now(),
+        ui_messages: ui_messages::recent_messages(),
+        active_actions,
+        tasks: activities(&TASKS),
+        processes: activities(&PROCESSES),
+        queues: vec![
+            QueueDepth {
+                name: "ui_messages".to_string(),
+                len: ui_messages_tx.max_capacity() - ui_messages_tx.capacity(),
+                capacity: Some(ui_messages_tx.max_capacity()),
+            },
+            QueueDepth {
+                name: "commands".to_string(),
+                len: command_queue::waiting(),
+                capacity: None,
+            },
+            QueueDepth {
+                name: "telemetry_spans".to_string(),
+                len: telemetry::pending_spans(),
+                capacity: None,
+            },
+            QueueDepth {
+                name: "error_reports".to_string(),
+                len: error_reporting::pending_events(),
+                capacity: None,
+            },
+        ],
+    }
+}
+
+#[tauri::command]
+pub fn get_debug_snapshot(app_handle: AppHandle) -> DebugSnapshot {
+    snapshot(&app_handle)
+}
+
+/// Opens the debug console, or closes it if it's already open.
+#[tauri::command]
+pub fn toggle_debug_console(app_handle: AppHandle) {
+    if let Some(window) = app_handle.get_window(DEBUG_CONSOLE_WINDOW_LABEL) {
+        if let Err(err) = window.close() {
+            error!("Failed to close debug console: {}", err);
+        }
+        return;
+    }
+
+    info!("Opening debug console");
+    if let Err(err) = WindowHelper::new(app_handle.clone()).new_debug_console_window() {
+        error!("{}", err);
+        return;
+    }
+
+    // Pushes snapshots for as long as the window is open, it's created asynchronously on the main thread
+    thread::spawn(move || {
+        let mut opened = false;
+        for tick in 0.. {
+            thread::sleep(SNAPSHOT_INTERVAL);
+            let window = match app_handle.get_window(DEBUG_CONSOLE_WINDOW_LABEL) {
+                Some(window) => window,
+                None if !opened && tick < WINDOW_OPEN_TIMEOUT_TICKS => continue,
+                None => break,
+            };
+            opened = true;
+            if let Err(err) = window.emit(SNAPSHOT_EVENT, snapshot(&app_handle)) {
+                error!("Failed to send debug snapshot: {}", err);
+            }
+        }
+    });
+}
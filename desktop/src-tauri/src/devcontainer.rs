@@ -0,0 +1,816 @@
+use crate::{
+    commands::{workspace_ssh::WorkspaceSshCommand, DevpodCommandConfig},
+    jetbrains_gateway, workspaces, AppHandle,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{fs, iter::Peekable, path::Path, str::Chars};
+use thiserror::Error;
+use ts_rs::TS;
+
+// The places the CLI looks at unless told otherwise, relative to the workspace's folder
+pub(crate) const CONFIG_PATHS: &[&str] = &[".devcontainer/devcontainer.json", ".devcontainer.json"];
+
+/// Calls `f` for every character outside of string literals, which are copied as they are.
+fn map_outside_strings(
+    content: &str,
+    mut f: impl FnMut(char, &mut Peekable<Chars>, &mut String),
+) -> String {
+    let mut mapped = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            mapped.push(c);
+            match c {
+                '\\' => mapped.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+            mapped.push(c);
+        } else {
+            f(c, &mut chars, &mut mapped);
+        }
+    }
+
+    mapped
+}
+
+/// Replaces `c` with as many spaces as it takes up, so that serde_json still reports the original positions.
+fn blank(c: char, mapped: &mut String) {
+    if c == '\n' {
+        mapped.push(c);
+    } else {
+        mapped.extend(std::iter::repeat(' ').take(c.len_utf8()));
+    }
+}
+
+/// `devcontainer.json` allows comments and trailing commas, neither of which `serde_json` accepts.
+fn strip_jsonc(content: &str) -> String {
+    let without_comments =
+        map_outside_strings(content, |c, chars, mapped| match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                blank(c, mapped);
+                while let Some(c) = chars.next_if(|c| *c != '\n') {
+                    blank(c, mapped);
+                }
+            }
+            ('/', Some('*')) => {
+                blank(c, mapped);
+                blank(chars.next().unwrap_or('*'), mapped);
+                while let Some(c) = chars.next() {
+                    blank(c, mapped);
+                    if c == '*' && chars.next_if_eq(&'/').is_some() {
+                        blank('/', mapped);
+                        break;
+                    }
+                }
+            }
+            _ => mapped.push(c),
+        });
+
+    map_outside_strings(&without_comments, |c, chars, mapped| {
+        let closes = || {
+            chars
+                .clone()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|c| matches!(c, '}' | ']'))
+        };
+        if c != ',' || !closes() {
+            mapped.push(c);
+        } else {
+            blank(c, mapped);
+        }
+    })
+}
+
+#[derive(Error, Debug)]
+pub enum DevcontainerError {
+    #[error("invalid devcontainer.json at line {line}, column {column}: {message}")]
+    Syntax {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+}
+impl serde::Serialize for DevcontainerError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn parse_checked(content: &str) -> Result<Value, DevcontainerError> {
+    serde_json::from_str(&strip_jsonc(content)).map_err(|err| DevcontainerError::Syntax {
+        // Without the position, it's reported separately
+        message: err
+            .to_string()
+            .split(" at line ")
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        line: err.line(),
+        column: err.column(),
+    })
+}
+
+pub fn parse(content: &str) -> Option<Value> {
+    parse_checked(content).ok()
+}
+
+/// Suggests the IDE of a running workspace.
+#[tauri::command]
+pub async fn suggest_workspace_ide(app_handle: AppHandle, workspace_id: String) -> Option<String> {
+    read(&app_handle, &workspace_id).and_then(|config| preferred_ide(&config))
+}
+
+/// The `devcontainer.json` of a running workspace, `None` if it has none or it can't be read.
+pub fn read(app_handle: &AppHandle, workspace_id: &str) -> Option<Value> {
+    let folder = workspaces::remote_folder(workspace_id);
+    let command = CONFIG_PATHS
+        .iter()
+        .map(|path| format!("cat {}/{} 2>/dev/null", folder, path))
+        .collect::<Vec<_>>()
+        .join(" || ");
+    let content = WorkspaceSshCommand::new(workspace_id.to_string(), command)
+        .exec(app_handle)
+        .ok()?;
+
+    parse(&content)
+}
+
+/// The `devcontainer.json` of a local folder, at `devcontainer_path` relative to it if set.
+pub fn read_local(folder: &Path, devcontainer_path: Option<&str>) -> Option<Value> {
+    let content = match devcontainer_path {
+        Some(path) => fs::read_to_string(folder.join(path)).ok()?,
+        None => CONFIG_PATHS
+            .iter()
+            .find_map(|path| fs::read_to_string(folder.join(path)).ok())?,
+    };
+
+    parse(&content)
+}
+
+/// The IDE the project has been set up for, JetBrains customizations name a backend while VS Code ones only list
+/// settings and extensions.
+pub fn preferred_ide(config: &Value) -> Option<String> {
+    let backend = config
+        .pointer("/customizations/jetbrains/backend")
+        .and_then(Value::as_str)
+        .map(str::to_lowercase);
+    if let Some(ide) = backend.filter(|backend| jetbrains_gateway::product_code(backend).is_some())
+    {
+        return Some(ide);
+    }
+    if config.pointer("/customizations/vscode").is_some() {
+        return Some("vscode".to_string());
+    }
+
+    None
+}
+
+/// The strings at `customizations.<tool>.<key>`, e.g. `customizations.vscode.extensions`.
+pub fn customization_list(config: &Value, tool: &str, key: &str) -> Vec<String> {
+    config
+        .pointer(&format!("/customizations/{}/{}", tool, key))
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum DevcontainerSeverity {
+    /// The CLI will fail or ignore the configuration
+    Error,
+    /// Probably a mistake, e.g. a misspelled property
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum DevcontainerDiagnosticKind {
+    Syntax,
+    UnknownProperty,
+    MissingProperty,
+    InvalidType,
+    InvalidValue,
+    InvalidFeature,
+    InvalidPort,
+    Deprecated,
+}
+
+/// A problem with a `devcontainer.json`, found before the CLI gets to build it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DevcontainerDiagnostic {
+    severity: DevcontainerSeverity,
+    kind: DevcontainerDiagnosticKind,
+    /// JSON pointer to the value, e.g. `/forwardPorts/1`, empty for the whole file
+    path: String,
+    message: String,
+    /// Only known for syntax errors
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+impl DevcontainerDiagnostic {
+    fn error(kind: DevcontainerDiagnosticKind, path: &str, message: String) -> Self {
+        Self {
+            severity: DevcontainerSeverity::Error,
+            kind,
+            path: path.to_string(),
+            message,
+            line: None,
+            column: None,
+        }
+    }
+
+    fn warning(kind: DevcontainerDiagnosticKind, path: &str, message: String) -> Self {
+        Self {
+            severity: DevcontainerSeverity::Warning,
+            ..Self::error(kind, path, message)
+        }
+    }
+}
+
+/// What a property has to hold.
+#[derive(Debug, Clone, Copy)]
+enum Expect {
+    String,
+    Bool,
+    Number,
+    Object,
+    Array,
+    StringArray,
+    StringOrArray,
+    OneOf(&'static [&'static str]),
+    /// A lifecycle command, i.e. a shell command, the command and its arguments or several of them run in parallel
+    Command,
+    /// Checked separately
+    Any,
+}
+
+const LIFECYCLE_COMMANDS: &[&str] = &[
+    "initializeCommand",
+    "onCreateCommand",
+    "updateContentCommand",
+    "postCreateCommand",
+    "postStartCommand",
+    "postAttachCommand",
+];
+
+// As of the devcontainer.json reference at https://containers.dev/implementors/json_reference
+const PROPERTIES: &[(&str, Expect)] = &[
+    ("$schema", Expect::String),
+    ("name", Expect::String),
+    ("image", Expect::String),
+    ("build", Expect::Object),
+    ("dockerFile", Expect::String),
+    ("context", Expect::String),
+    ("dockerComposeFile", Expect::StringOrArray),
+    ("service", Expect::String),
+    ("runServices", Expect::StringArray),
+    ("workspaceFolder", Expect::String),
+    ("workspaceMount", Expect::String),
+    (
+        "shutdownAction",
+        Expect::OneOf(&["none", "stopContainer", "stopCompose"]),
+    ),
+    ("overrideCommand", Expect::Bool),
+    ("initializeCommand", Expect::Command),
+    ("onCreateCommand", Expect::Command),
+    ("updateContentCommand", Expect::Command),
+    ("postCreateCommand", Expect::Command),
+    ("postStartCommand", Expect::Command),
+    ("postAttachCommand", Expect::Command),
+    ("waitFor", Expect::OneOf(LIFECYCLE_COMMANDS)),
+    (
+        "userEnvProbe",
+        Expect::OneOf(&[
+            "none",
+            "loginShell",
+            "loginInteractiveShell",
+            "interactiveShell",
+        ]),
+    ),
+    ("features", Expect::Object),
+    ("overrideFeatureInstallOrder", Expect::StringArray),
+    ("forwardPorts", Expect::Array),
+    ("portsAttributes", Expect::Object),
+    ("otherPortsAttributes", Expect::Object),
+    ("appPort", Expect::Any),
+    ("containerEnv", Expect::Object),
+    ("remoteEnv", Expect::Object),
+    ("containerUser", Expect::String),
+    ("remoteUser", Expect::String),
+    ("updateRemoteUserUID", Expect::Bool),
+    ("mounts", Expect::Array),
+    ("runArgs", Expect::StringArray),
+    ("securityOpt", Expect::StringArray),
+    ("privileged", Expect::Bool),
+    ("capAdd", Expect::StringArray),
+    ("init", Expect::Bool),
+    ("customizations", Expect::Object),
+    ("hostRequirements", Expect::Object),
+    ("secrets", Expect::Object),
+    // Moved to `customizations.vscode`, still picked up
+    ("extensions", Expect::StringArray),
+    ("settings", Expect::Object),
+];
+
+const BUILD_PROPERTIES: &[(&str, Expect)] = &[
+    ("dockerfile", Expect::String),
+    ("context", Expect::String),
+    ("args", Expect::Object),
+    ("target", Expect::String),
+    ("cacheFrom", Expect::StringOrArray),
+    ("options", Expect::StringArray),
+];
+
+const HOST_REQUIREMENT_PROPERTIES: &[(&str, Expect)] = &[
+    ("cpus", Expect::Number),
+    ("memory", Expect::String),
+    ("storage", Expect::String),
+    ("gpu", Expect::Any),
+];
+
+const PORT_ATTRIBUTE_PROPERTIES: &[(&str, Expect)] = &[
+    ("label", Expect::String),
+    (
+        "onAutoForward",
+        Expect::OneOf(&[
+            "notify",
+            "openBrowser",
+            "openBrowserOnce",
+            "openPreview",
+            "silent",
+            "ignore",
+        ]),
+    ),
+    ("elevateIfNeeded", Expect::Bool),
+    ("requireLocalPort", Expect::Bool),
+    ("protocol", Expect::OneOf(&["http", "https"])),
+];
+
+/// Escapes `key` as a JSON pointer segment, feature ids contain slashes.
+pub(crate) fn pointer(parent: &str, key: &str) -> String {
+    format!("{}/{}", parent, key.replace('~', "~0").replace('/', "~1"))
+}
+
+fn type_name(expect: Expect) -> String {
+    match expect {
+        Expect::String => "a string".to_string(),
+        Expect::Bool => "a boolean".to_string(),
+        Expect::Number => "a number".to_string(),
+        Expect::Object => "an object".to_string(),
+        Expect::Array => "an array".to_string(),
+        Expect::StringArray => "an array of strings".to_string(),
+        Expect::StringOrArray => "a string or an array of strings".to_string(),
+        Expect::OneOf(values) => format!("one of {}", values.join(", ")),
+        Expect::Command => "a string, an array of strings or an object".to_string(),
+        Expect::Any => "anything".to_string(),
+    }
+}
+
+fn is_string_array(value: &Value) -> bool {
+    value
+        .as_array()
+        .is_some_and(|values| values.iter().all(Value::is_string))
+}
+
+fn has_type(value: &Value, expect: Expect) -> bool {
+    match expect {
+        Expect::String => value.is_string(),
+        Expect::Bool => value.is_boolean(),
+        Expect::Number => value.is_number(),
+        Expect::Object => value.is_object(),
+        Expect::Array => value.is_array(),
+        Expect::StringArray => is_string_array(value),
+        Expect::StringOrArray => value.is_string() || is_string_array(value),
+        Expect::OneOf(values) => value.as_str().is_some_and(|value| values.contains(&value)),
+        Expect::Command => {
+            value.is_string()
+                || is_string_array(value)
+                || value.as_object().is_some_and(|commands| {
+                    commands
+                        .values()
+                        .all(|command| command.is_string() || is_string_array(command))
+                })
+        }
+        Expect::Any => true,
+    }
+}
+
+/// Checks the known properties of `object` against `properties` and warns about the others.
+fn check_properties(
+    object: &Map<String, Value>,
+    properties: &[(&str, Expect)],
+    parent: &str,
+    diagnostics: &mut Vec<DevcontainerDiagnostic>,
+) {
+    for (key, value) in object {
+        let path = pointer(parent, key);
+        let Some((_, expect)) = properties.iter().find(|(name, _)| name == key) else {
+            diagnostics.push(DevcontainerDiagnostic::warning(
+                DevcontainerDiagnosticKind::UnknownProperty,
+                &path,
+                format!("unknown property {}", key),
+            ));
+            continue;
+        };
+        if has_type(value, *expect) {
+            continue;
+        }
+
+        let kind = match expect {
+            Expect::OneOf(_) if value.is_string() => DevcontainerDiagnosticKind::InvalidValue,
+            _ => DevcontainerDiagnosticKind::InvalidType,
+        };
+        diagnostics.push(DevcontainerDiagnostic::error(
+            kind,
+            &path,
+            format!("{} must be {}", key, type_name(*expect)),
+        ));
+    }
+}
+
+/// Why `id` can't be resolved to a feature, `None` if it looks fine. Features are referenced by OCI reference, e.g.
+/// `ghcr.io/devcontainers/features/node:1`, by a `https` URL to a tarball or by a path relative to the
+/// `.devcontainer` folder.
+fn feature_reference_error(id: &str) -> Option<String> {
+    if id.trim().is_empty() {
+        return Some("feature id is empty".to_string());
+    }
+    if id.chars().any(char::is_whitespace) {
+        return Some(format!("feature id {} contains whitespace", id));
+    }
+    if id.starts_with("./") || id.starts_with("../") {
+        return None;
+    }
+    if id.starts_with("http://") {
+        return Some(format!("feature {} has to be downloaded over https", id));
+    }
+    if id.starts_with("https://") {
+        return None;
+    }
+
+    let (repository, version) = match id.split_once('@') {
+        Some((repository, digest)) => (repository, Some(("@", digest))),
+        None => match id.rsplit_once(':').filter(|(_, tag)| !tag.contains('/')) {
+            Some((repository, tag)) => (repository, Some((":", tag))),
+            None => (id, None),
+        },
+    };
+    if repository.split('/').any(str::is_empty) {
+        return Some(format!("feature {} is not a valid OCI reference", id));
+    }
+    // The registry may use upper case letters and a port, the path may not
+    if repository
+        .split('/')
+        .skip(1)
+        .any(|segment| segment.chars().any(|c| c.is_ascii_uppercase()))
+    {
+        return Some(format!("feature {} has to be lower case", id));
+    }
+
+    match version {
+        Some(("@", digest)) => {
+            let valid = digest.strip_prefix("sha256:").is_some_and(|hash| {
+                hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())
+            });
+            (!valid).then(|| format!("feature {} has an invalid digest", id))
+        }
+        Some((_, tag)) => {
+            let valid = !tag.is_empty()
+                && tag.len() <= 128
+                && !tag.starts_with(['.', '-'])
+                && tag
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+            (!valid).then(|| format!("feature {} has an invalid version", id))
+        }
+        None => None,
+    }
+}
+
+fn check_features(features: &Map<String, Value>, diagnostics: &mut Vec<DevcontainerDiagnostic>) {
+    for (id, options) in features {
+        let path = pointer("/features", id);
+        if let Some(message) = feature_reference_error(id) {
+            diagnostics.push(DevcontainerDiagnostic::error(
+                DevcontainerDiagnosticKind::InvalidFeature,
+                &path,
+                message,
+            ));
+        } else if !id.contains('/') {
+            diagnostics.push(DevcontainerDiagnostic::warning(
+                DevcontainerDiagnosticKind::Deprecated,
+                &path,
+                format!(
+                    "{} is a deprecated short id, reference the feature with its OCI reference instead",
+                    id
+                ),
+            ));
+        }
+
+        if !(options.is_object() || options.is_string() || options.is_boolean()) {
+            diagnostics.push(DevcontainerDiagnostic::error(
+                DevcontainerDiagnosticKind::InvalidType,
+                &path,
+                format!("options of feature {} must be an object", id),
+            ));
+        }
+    }
+}
+
+fn is_valid_port(port: &str) -> bool {
+    port.parse::<u16>().is_ok_and(|port| port > 0)
+}
+
+/// Ports are forwarded by number or as `host:port`.
+fn check_forward_port(port: &Value, path: &str, diagnostics: &mut Vec<DevcontainerDiagnostic>) {
+    let valid = match port {
+        Value::Number(number) => number
+            .as_u64()
+            .is_some_and(|port| (1..=u16::MAX as u64).contains(&port)),
+        Value::String(port) => match port.rsplit_once(':') {
+            Some((host, port)) => !host.is_empty() && is_valid_port(port),
+            None => is_valid_port(port),
+        },
+        _ => false,
+    };
+    if !valid {
+        diagnostics.push(DevcontainerDiagnostic::error(
+            DevcontainerDiagnosticKind::InvalidPort,
+            path,
+            format!(
+                "{} is not a port, use a number between 1 and 65535 or host:port",
+                port
+            ),
+        ));
+    }
+}
+
+fn check_ports(config: &Map<String, Value>, diagnostics: &mut Vec<DevcontainerDiagnostic>) {
+    if let Some(ports) = config.get("forwardPorts").and_then(Value::as_array) {
+        for (i, port) in ports.iter().enumerate() {
+            check_forward_port(port, &format!("/forwardPorts/{}", i), diagnostics);
+        }
+    }
+
+    // Published like `docker run -p`, which accepts more than a port
+    if let Some(app_port) = config.get("appPort") {
+        let ports = match app_port {
+            Value::Array(ports) => ports.iter().enumerate().collect(),
+            port => vec![(0, port)],
+        };
+        for (i, port) in ports {
+            let path = match app_port {
+                Value::Array(_) => format!("/appPort/{}", i),
+                _ => "/appPort".to_string(),
+            };
+            match port {
+                Value::Number(_) => check_forward_port(port, &path, diagnostics),
+                Value::String(port) if !port.is_empty() => {}
+                _ => diagnostics.push(DevcontainerDiagnostic::error(
+                    DevcontainerDiagnosticKind::InvalidPort,
+                    &path,
+                    format!("{} is not a port", port),
+                )),
+            }
+        }
+    }
+
+    if let Some(attributes) = config.get("portsAttributes").and_then(Value::as_object) {
+        for (port, attributes) in attributes {
+            let path = pointer("/portsAttributes", port);
+            match attributes.as_object() {
+                Some(attributes) => {
+                    check_properties(attributes, PORT_ATTRIBUTE_PROPERTIES, &path, diagnostics)
+                }
+                None => diagnostics.push(DevcontainerDiagnostic::error(
+                    DevcontainerDiagnosticKind::InvalidType,
+                    &path,
+                    format!("attributes of port {} must be an object", port),
+                )),
+            }
+        }
+    }
+    if let Some(attributes) = config
+        .get("otherPortsAttributes")
+        .and_then(Value::as_object)
+    {
+        check_properties(
+            attributes,
+            PORT_ATTRIBUTE_PROPERTIES,
+            "/otherPortsAttributes",
+            diagnostics,
+        );
+    }
+}
+
+/// The CLI needs to know where the container comes from.
+fn check_source(config: &Map<String, Value>, diagnostics: &mut Vec<DevcontainerDiagnostic>) {
+    let has_dockerfile = config.contains_key("dockerFile")
+        || config
+            .get("build")
+            .is_some_and(|build| build.get("dockerfile").is_some());
+    if config.contains_key("dockerComposeFile") {
+        if !config.contains_key("service") {
+            diagnostics.push(DevcontainerDiagnostic::error(
+                DevcontainerDiagnosticKind::MissingProperty,
+                "/service",
+                "service is required with dockerComposeFile".to_string(),
+            ));
+        }
+    } else if !config.contains_key("image") && !has_dockerfile {
+        diagnostics.push(DevcontainerDiagnostic::error(
+            DevcontainerDiagnosticKind::MissingProperty,
+            "",
+            "one of image, build.dockerfile or dockerComposeFile is required".to_string(),
+        ));
+    }
+}
+
+/// Everything that's wrong with `content`, an empty list if the CLI should be able to build it.
+pub fn validate(content: &str) -> Vec<DevcontainerDiagnostic> {
+    let config = match parse_checked(content) {
+        Ok(config) => config,
+        Err(DevcontainerError::Syntax {
+            message,
+            line,
+            column,
+        }) => {
+            return vec![DevcontainerDiagnostic {
+                line: Some(line),
+                column: Some(column),
+                ..DevcontainerDiagnostic::error(DevcontainerDiagnosticKind::Syntax, "", message)
+            }]
+        }
+    };
+    let Some(config) = config.as_object() else {
+        return vec![DevcontainerDiagnostic::error(
+            DevcontainerDiagnosticKind::InvalidType,
+            "",
+            "devcontainer.json must contain an object".to_string(),
+        )];
+    };
+
+    let mut diagnostics = vec![];
+    check_properties(config, PROPERTIES, "", &mut diagnostics);
+    if let Some(build) = config.get("build").and_then(Value::as_object) {
+        check_properties(build, BUILD_PROPERTIES, "/build", &mut diagnostics);
+    }
+    if let Some(requirements) = config.get("hostRequirements").and_then(Value::as_object) {
+        check_properties(
+            requirements,
+            HOST_REQUIREMENT_PROPERTIES,
+            "/hostRequirements",
+            &mut diagnostics,
+        );
+    }
+    if let Some(features) = config.get("features").and_then(Value::as_object) {
+        check_features(features, &mut diagnostics);
+    }
+    check_ports(config, &mut diagnostics);
+    for (key, allow_null) in [("containerEnv", false), ("remoteEnv", true)] {
+        let Some(variables) = config.get(key).and_then(Value::as_object) else {
+            continue;
+        };
+        for (name, value) in variables {
+            if !(value.is_string() || (allow_null && value.is_null())) {
+                diagnostics.push(DevcontainerDiagnostic::error(
+                    DevcontainerDiagnosticKind::InvalidType,
+                    &pointer(&format!("/{}", key), name),
+                    format!("{} must be a string", name),
+                ));
+            }
+        }
+    }
+    check_source(config, &mut diagnostics);
+
+    diagnostics
+}
+
+/// Parses a `devcontainer.json`, e.g. one the user is editing, comments and trailing commas included.
+#[tauri::command]
+pub fn parse_devcontainer(content: String) -> Result<Value, DevcontainerError> {
+    parse_checked(&content)
+}
+
+#[tauri::command]
+pub fn validate_devcontainer(content: String) -> Vec<DevcontainerDiagnostic> {
+    validate(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_jsonc() {
+        let config = parse(
+            r#"{
+                // Comments are allowed
+                "name": "http://example.com/*not a comment*/",
+                /* and so are
+                   block comments */
+                "customizations": {
+                    "vscode": {
+                        "extensions": ["golang.go", "ms-azuretools.vscode-docker",],
+                    },
+                },
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config["name"], "http://example.com/*not a comment*/");
+        assert_eq!(
+            customization_list(&config, "vscode", "extensions"),
+            vec!["golang.go", "ms-azuretools.vscode-docker"]
+        );
+        assert!(customization_list(&config, "jetbrains", "plugins").is_empty());
+        assert_eq!(preferred_ide(&config), Some("vscode".to_string()));
+    }
+
+    #[test]
+    fn should_prefer_jetbrains_backend() {
+        let config = parse(
+            r#"{ "customizations": { "vscode": {}, "jetbrains": { "backend": "GoLand" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(preferred_ide(&config), Some("goland".to_string()));
+
+        let config =
+            parse(r#"{ "customizations": { "jetbrains": { "backend": "Fleet" } } }"#).unwrap();
+        assert_eq!(preferred_ide(&config), None);
+    }
+
+    #[test]
+    fn should_report_diagnostics() {
+        let diagnostics = validate(
+            r#"{
+                /* the base image,
+                   kept small */
+                "image": "mcr.microsoft.com/devcontainers/base:ubuntu",
+                "features": {
+                    "ghcr.io/devcontainers/features/node:1": {},
+                    "ghcr.io/devcontainers/features/Go:latest": {},
+                    "ghcr.io/devcontainers/features/rust:": {},
+                    "./local-feature": true,
+                },
+                "forwardPorts": [3000, "db:5432", 70000, "web"],
+                "remoteEnv": { "PATH": null },
+                "postCreateCommand": { "install": ["npm", "ci"] },
+                "forwardedPorts": [],
+            }"#,
+        );
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.kind, diagnostic.path.as_str()))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (
+                    DevcontainerDiagnosticKind::UnknownProperty,
+                    "/forwardedPorts"
+                ),
+                (
+                    DevcontainerDiagnosticKind::InvalidFeature,
+                    "/features/ghcr.io~1devcontainers~1features~1Go:latest"
+                ),
+                (
+                    DevcontainerDiagnosticKind::InvalidFeature,
+                    "/features/ghcr.io~1devcontainers~1features~1rust:"
+                ),
+                (DevcontainerDiagnosticKind::InvalidPort, "/forwardPorts/2"),
+                (DevcontainerDiagnosticKind::InvalidPort, "/forwardPorts/3"),
+            ]
+        );
+
+        let diagnostics =
+            validate("{\n  /* a comment */ \"image\": \"ubuntu\"\n  \"name\": \"test\"\n}");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DevcontainerDiagnosticKind::Syntax);
+        assert_eq!(
+            (diagnostics[0].line, diagnostics[0].column),
+            (Some(3), Some(3))
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use crate::{
+    commands::{
+        list_providers::ListProvidersCommand, version::VersionCommand, DevpodCommandConfig,
+    },
+    debug_console,
+    redaction::{self, REDACTED},
+    settings::Settings,
+    ui_messages, AppHandle,
+};
+use chrono::Local;
+use log::{error, info};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use zip::{write::FileOptions, ZipWriter};
+
+// Keys whose values might contain credentials, matched case insensitively ignoring `_` and `-`
+const SENSITIVE_KEYS: [&str; 9] = [
+    "token",
+    "secret",
+    "password",
+    "passwd",
+    "apikey",
+    "accesskey",
+    "privatekey",
+    "credential",
+    "authorization",
+];
+
+#[derive(Error, Debug)]
+pub enum DiagnosticsError {
+    #[error("unable to determine where to write the diagnostics bundle")]
+    NoDir,
+    #[error("unable to write diagnostics bundle {0}")]
+    Write(#[source] anyhow::Error),
+}
+impl serde::Serialize for DiagnosticsError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexEntry {
+    name: String,
+    description: String,
+    error: Option<String>,
+}
+
+/// Collects everything we usually ask for in bug reports into a zip archive at `target`, or in the app cache dir
+/// if no target is given. Returns the path of the archive.
+#[tauri::command]
+pub async fn collect_diagnostics(
+    app_handle: AppHandle,
+    target: Option<PathBuf>,
+) -> Result<String, DiagnosticsError> {
+    let target = match target {
+        Some(target) => target,
+        None => {
+            let mut target = app_handle
+                .path_resolver()
+                .app_cache_dir()
+                .ok_or(DiagnosticsError::NoDir)?;
+            target.push(format!(
+                "devpod-diagnostics-{}.zip",
+                Local::now().format("%Y-%m-%d_%H-%M-%S")
+            ));
+
+            target
+        }
+    };
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let _task = debug_console::track_task("collect diagnostics", None);
+        write_bundle(&app_handle, &target).map(|_| target)
+    })
+    .await
+    .map_err(|err| DiagnosticsError::Write(err.into()))
+    .and_then(|result| result.map_err(DiagnosticsError::Write));
+
+    match result {
+        Ok(target) => {
+            info!("Wrote diagnostics bundle to {:?}", target);
+            Ok(target.to_string_lossy().to_string())
+        }
+        Err(err) => {
+            error!("Failed to collect diagnostics: {}", err);
+            Err(err)
+        }
+    }
+}
+
+struct Bundle {
+    writer: ZipWriter<fs::File>,
+    index: Vec<IndexEntry>,
+}
+
+impl Bundle {
+    fn create(path: &Path) -> anyhow::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        Ok(Self {
+            writer: ZipWriter::new(fs::File::create(path)?),
+            index: vec![],
+        })
+    }
+
+    /// Adds a file to the bundle, or records why it is missing in the index.
+    fn add(&mut self, name: &str, description: &str, contents: anyhow::Result<Vec<u8>>) {
+        let error = match contents.and_then(|contents| self.write(name, &contents)) {
+            Ok(()) => None,
+            Err(err) => Some(err.to_string()),
+        };
+
+        self.index.push(IndexEntry {
+            name: name.to_string(),
+            description: description.to_string(),
+            error,
+        });
+    }
+
+    fn write(&mut self, name: &str, contents: &[u8]) -> anyhow::Result<()> {
+        self.writer.start_file(name, FileOptions::default())?;
+        self.writer.write_all(contents)?;
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> anyhow::Result<()> {
+        let index = serde_json::to_vec_pretty(&self.index)?;
+        self.write("index.json", &index)?;
+        self.writer.finish()?;
+
+        Ok(())
+    }
+}
+
+fn write_bundle(app_handle: &AppHandle, target: &Path) -> anyhow::Result<()> {
+    let mut bundle = Bundle::create(target)?;
+
+    bundle.add(
+        "system.json",
+        "Operating system and app versions",
+        to_json(&system_info(app_handle)),
+    );
+    bundle.add(
+        "cli_version.txt",
+        "Output of `devpod version`",
+        VersionCommand::new()
+            .exec(app_handle)
+            .map(String::into_bytes)
+            .map_err(anyhow::Error::from),
+    );
+    bundle.add(
+        "providers.json",
+        "Configured providers, option values are redacted",
+        ListProvidersCommand::new()
+            .exec(app_handle)
+            .map_err(anyhow::Error::from)
+            .and_then(|providers| to_json(&redact(providers))),
+    );
+    bundle.add(
+        "settings.json",
+        "Desktop app settings, sensitive values are redacted",
+        to_json(&redact(Value::Object(Settings::all(app_handle)))),
+    );
+    bundle.add(
+        "ui_messages.json",
+        "Recent messages from the backend to the UI",
+        serde_json::to_value(ui_messages::recent_messages())
+            .map_err(anyhow::Error::from)
+            .and_then(|messages| to_json(&redact(messages))),
+    );
+
+    match app_handle.path_resolver().app_log_dir() {
+        Some(log_dir) => {
+            let entries = fs::read_dir(&log_dir)
+                .map(|entries| entries.filter_map(|entry| entry.ok()).collect::<Vec<_>>())
+                .unwrap_or_default();
+            for entry in entries {
+                let name = format!("logs/{}", entry.file_name().to_string_lossy());
+                bundle.add(&name, "Desktop app log", read_log(&entry.path()));
+            }
+        }
+        None => bundle.add(
+            "logs",
+            "Desktop app logs",
+            Err(anyhow::anyhow!("no log dir")),
+        ),
+    }
+
+    bundle.finish()
+}
+
+/// Reads a log file, masking secrets in logs written before redaction was in place.
+/// Compressed archives are added as is.
+fn read_log(path: &Path) -> anyhow::Result<Vec<u8>> {
+    if path.extension().is_some_and(|extension| extension == "log") {
+        let contents = fs::read_to_string(path)?;
+        return Ok(redaction::redact(&contents).into_owned().into_bytes());
+    }
+
+    fs::read(path).map_err(anyhow::Error::from)
+}
+
+fn system_info(app_handle: &AppHandle) -> Value {
+    let package_info = app_handle.package_info();
+
+    json!({
+        "os": std::env::consts::OS,
+        "osFamily": std::env::consts::FAMILY,
+        "arch": std::env::consts::ARCH,
+        "osVersion": os_version(),
+        "appVersion": package_info.version.to_string(),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn os_version() -> Option<String> {
+    command_output("sw_vers", &["-productVersion"])
+}
+
+#[cfg(target_os = "linux")]
+fn os_version() -> Option<String> {
+    command_output("uname", &["-sr"])
+}
+
+#[cfg(target_os = "windows")]
+fn os_version() -> Option<String> {
+    command_output("cmd", &["/C", "ver"])
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()?;
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn to_json(value: &Value) -> anyhow::Result<Vec<u8>> {
+    serde_json::to_vec_pretty(value).map_err(anyhow::Error::from)
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let key = key.to_lowercase().replace(['_', '-'], "");
+
+    SENSITIVE_KEYS
+        .iter()
+        .any(|sensitive| key.contains(sensitive))
+}
+
+/// Replaces all values stored under keys that look like they could hold credentials, as well as the `value` of
+/// `{ name, value }` pairs with such a name. Everything else is masked like log output.
+fn redact(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let sensitive_pair = map
+                .get("name")
+                .and_then(Value::as_str)
+                .is_some_and(is_sensitive);
+
+            Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| {
+                        let sensitive = is_sensitive(&key) || (sensitive_pair && key == "value");
+                        if sensitive && !matches!(value, Value::Null) {
+                            (key, Value::String(REDACTED.to_string()))
+                        } else {
+                            (key, redact(value))
+                        }
+                    })
+                    .collect(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(redact).collect()),
+        Value::String(text) => Value::String(redaction::redact(&text).into_owned()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_redact_sensitive_values() {
+        let value = json!({
+            "zoom": "md",
+            "environmentVariables": [{ "name": "GITHUB_TOKEN", "value": "ghp_123" }],
+            "provider": { "options": { "AWS_SECRET_ACCESS_KEY": { "value": "abc" } } },
+            "apiKey": null,
+        });
+
+        assert_eq!(
+            redact(value),
+            json!({
+                "zoom": "md",
+                "environmentVariables": [{ "name": "GITHUB_TOKEN", "value": REDACTED }],
+                "provider": { "options": { "AWS_SECRET_ACCESS_KEY": REDACTED } },
+                "apiKey": null,
+            })
+        );
+    }
+    #[test]
+    fn should_keep_values_that_only_look_sensitive() {
+        let value = json!({
+            "sshKeyPath": "~/.ssh/id_ed25519",
+            "environmentVariables": [{ "name": "EDITOR", "value": "vim" }],
+            "author": "jane",
+            "lastError": "clone failed: token ghp_aaaaaaaaaaaaaaaaaaaaaaaa rejected",
+        });
+
+        assert_eq!(
+            redact(value),
+            json!({
+                "sshKeyPath": "~/.ssh/id_ed25519",
+                "environmentVariables": [{ "name": "EDITOR", "value": "vim" }],
+                "author": "jane",
+                "lastError": format!("clone failed: token {} rejected", REDACTED),
+            })
+        );
+    }
+}
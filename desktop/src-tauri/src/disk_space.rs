@@ -0,0 +1,292 @@
+use crate::{
+    audit_log::{self, AuditCategory, AuditInitiator},
+    debug_console,
+    doctor::{free_disk_bytes, run},
+    notifications::{self, NotificationAction, NotificationEvent},
+    settings::Settings,
+    util::devpod_home,
+    AppHandle,
+};
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const GIB: u64 = 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum DiskSpaceLevel {
+    Ok,
+    /// Below the warning threshold
+    Low,
+    /// Below the hard floor, no new builds are started
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct Volume {
+    name: String,
+    path: String,
+    #[ts(type = "number")]
+    available_bytes: u64,
+    level: DiskSpaceLevel,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DiskSpaceStatus {
+    /// The worst level of all volumes
+    level: DiskSpaceLevel,
+    volumes: Vec<Volume>,
+}
+
+#[derive(Error, Debug)]
+pub enum DiskSpaceError {
+    #[error("Only {available} left on the volume of the {name}, free up disk space before building workspaces")]
+    BelowFloor { name: String, available: String },
+    #[error("Unable to prune Docker data: {0}")]
+    Prune(String),
+    #[error("Disk space check failed")]
+    Join(#[source] tauri::Error),
+}
+impl serde::Serialize for DiskSpaceError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Thresholds {
+    warning_bytes: u64,
+    floor_bytes: u64,
+}
+
+impl Thresholds {
+    fn from_settings(app_handle: &AppHandle) -> Self {
+        Self {
+            warning_bytes: Settings::low_disk_space_warning_gb(app_handle) as u64 * GIB,
+            floor_bytes: Settings::low_disk_space_floor_gb(app_handle) as u64 * GIB,
+        }
+    }
+
+    fn level(&self, available_bytes: u64) -> DiskSpaceLevel {
+        if available_bytes < self.floor_bytes {
+            DiskSpaceLevel::Critical
+        } else if available_bytes < self.warning_bytes {
+            DiskSpaceLevel::Low
+        } else {
+            DiskSpaceLevel::Ok
+        }
+    }
+}
+
+fn format_gib(bytes: u64) -> String {
+    format!("{:.1} GiB", bytes as f64 / GIB as f64)
+}
+
+/// Docker's data root, or the disk image of Docker Desktop's VM if the data root only exists inside of it.
+fn docker_data_dir() -> Option<PathBuf> {
+    let (root_dir, _) = run("docker", &["info", "--format", "{{.DockerRootDir}}"]).ok()?;
+    let root_dir = PathBuf::from(root_dir);
+    if root_dir.is_absolute() && root_dir.exists() {
+        return Some(root_dir);
+    }
+
+    #[cfg(target_os = "macos")]
+    let vm_dir = dirs::home_dir().map(|home| home.join("Library/Containers/com.docker.docker"));
+    #[cfg(target_os = "windows")]
+    let vm_dir = dirs::data_local_dir().map(|dir| dir.join("Docker"));
+    #[cfg(target_os = "linux")]
+    let vm_dir = dirs::home_dir().map(|home| home.join(".docker/desktop"));
+
+    vm_dir.filter(|dir| dir.exists())
+}
+
+/// The closest existing directory, the DevPod home might not have been created yet.
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|dir| dir.exists())
+}
+
+fn volume(name: &str, path: &Path, thresholds: &Thresholds) -> Option<Volume> {
+    let available_bytes = free_disk_bytes(existing_ancestor(path)?)?;
+
+    Some(Volume {
+        name: name.to_string(),
+        path: path.to_string_lossy().to_string(),
+        available_bytes,
+        level: thresholds.level(available_bytes),
+    })
+}
+
+fn status(thresholds: &Thresholds) -> DiskSpaceStatus {
+    let volumes: Vec<Volume> = [
+        ("DevPod home", devpod_home()),
+        ("Docker data", docker_data_dir()),
+    ]
+    .into_iter()
+    .filter_map(|(name, path)| volume(name, &path?, thresholds))
+    .collect();
+    let level = volumes
+        .iter()
+        .map(|volume| volume.level)
+        .max()
+        .unwrap_or(DiskSpaceLevel::Ok);
+
+    DiskSpaceStatus { level, volumes }
+}
+
+fn warn_low_disk_space(app_handle: &AppHandle, status: &DiskSpaceStatus) {
+    let volume = match status.volumes.iter().max_by_key(|volume| volume.level) {
+        Some(volume) => volume,
+        None => return,
+    };
+    let (title, body) = match status.level {
+        DiskSpaceLevel::Ok => return,
+        DiskSpaceLevel::Low => (
+            "Low disk space",
+            format!(
+                "Only {} left for the {}, prune unused images and containers to free up space",
+                format_gib(volume.available_bytes),
+                volume.name
+            ),
+        ),
+        DiskSpaceLevel::Critical => (
+            "Disk almost full",
+            format!(
+                "Only {} left for the {}, no workspaces can be built until you free up space",
+                format_gib(volume.available_bytes),
+                volume.name
+            ),
+        ),
+    };
+    warn!("{}: {}", title, body);
+
+    if let Err(err) = notifications::notify(
+        app_handle,
+        NotificationEvent::LowDiskSpace,
+        title,
+        &body,
+        vec![NotificationAction::CleanUpDiskSpace],
+    ) {
+        warn!("Failed to show low disk space notification: {}", err);
+    }
+}
+
+/// Periodically checks the free space, warns once whenever it gets worse.
+pub fn setup(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let mut warned = DiskSpaceLevel::Ok;
+        loop {
+            let task = debug_console::track_task("disk space check", None);
+            let disk_space = status(&Thresholds::from_settings(&app_handle));
+            drop(task);
+
+            if disk_space.level > warned {
+                warn_low_disk_space(&app_handle, &disk_space);
+            }
+            warned = disk_space.level;
+
+            thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_disk_space(app_handle: AppHandle) -> Result<DiskSpaceStatus, DiskSpaceError> {
+    tauri::async_runtime::spawn_blocking(move || status(&Thresholds::from_settings(&app_handle)))
+        .await
+        .map_err(DiskSpaceError::Join)
+}
+
+/// Fails if a volume is below the hard floor, checked by the UI before starting a build.
+#[tauri::command]
+pub async fn check_build_disk_space(app_handle: AppHandle) -> Result<(), DiskSpaceError> {
+    let thresholds = Thresholds::from_settings(&app_handle);
+    let disk_space = tauri::async_runtime::spawn_blocking(move || status(&thresholds))
+        .await
+        .map_err(DiskSpaceError::Join)?;
+
+    match disk_space
+        .volumes
+        .into_iter()
+        .find(|volume| volume.level == DiskSpaceLevel::Critical)
+    {
+        Some(volume) => Err(DiskSpaceError::BelowFloor {
+            name: volume.name,
+            available: format_gib(volume.available_bytes),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Removes stopped containers, dangling images, unused networks and the build cache. Volumes are kept,
+/// they might hold data the user still needs. Returns the reclaimed space as reported by Docker.
+#[tauri::command]
+pub async fn prune_docker() -> Result<String, DiskSpaceError> {
+    let output = tauri::async_runtime::spawn_blocking(|| {
+        let _task = debug_console::track_task("docker prune", None);
+        run("docker", &["system", "prune", "--force"])
+    })
+    .await
+    .map_err(DiskSpaceError::Join)?
+    .map_err(DiskSpaceError::Prune)?;
+
+    let reclaimed = parse_reclaimed(&output.0).unwrap_or_else(|| "0B".to_string());
+    info!("Pruned Docker data, reclaimed {}", reclaimed);
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Delete,
+        "prune docker",
+        None,
+    );
+
+    Ok(reclaimed)
+}
+
+fn parse_reclaimed(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("Total reclaimed space:"))
+        .map(|space| space.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_compare_against_thresholds() {
+        let thresholds = Thresholds {
+            warning_bytes: 10 * GIB,
+            floor_bytes: 2 * GIB,
+        };
+
+        assert_eq!(thresholds.level(20 * GIB), DiskSpaceLevel::Ok);
+        assert_eq!(thresholds.level(10 * GIB), DiskSpaceLevel::Ok);
+        assert_eq!(thresholds.level(5 * GIB), DiskSpaceLevel::Low);
+        assert_eq!(thresholds.level(GIB), DiskSpaceLevel::Critical);
+    }
+
+    #[test]
+    fn should_parse_reclaimed_space() {
+        let output = "Deleted build cache objects:\nabc123\n\nTotal reclaimed space: 1.234GB";
+
+        assert_eq!(parse_reclaimed(output), Some("1.234GB".to_string()));
+        assert_eq!(parse_reclaimed("Total"), None);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::AppHandle;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::sync::Mutex;
+use ts_rs::TS;
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const OVERLAY_SIZE: usize = 16;
+// Glyphs are 3x5 pixels and drawn at twice their size, centered on the overlay
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const GLYPH_SCALE: usize = 2;
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const RUNNING_COLOR: [u8; 3] = [0x8b, 0x5c, 0xf6];
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const FAILED_COLOR: [u8; 3] = [0xe5, 0x3e, 0x3e];
+
+lazy_static! {
+    static ref CURRENT: Mutex<DockBadge> = Mutex::new(DockBadge::default());
+}
+
+/// Workspace activity as seen by the main window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DockBadge {
+    running: u32,
+    /// Workspaces whose latest action failed, they take precedence over the running ones
+    failed: u32,
+}
+
+impl DockBadge {
+    /// What the badge shows, nothing while no workspace is running.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    fn label(&self) -> Option<String> {
+        if self.failed > 0 {
+            return Some("!".to_string());
+        }
+
+        (self.running > 0).then(|| self.running.to_string())
+    }
+
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    fn description(&self) -> String {
+        if self.failed > 0 {
+            return format!("Failed workspaces: {}", self.failed);
+        }
+
+        format!("Running workspaces: {}", self.running)
+    }
+
+    /// The single character the taskbar overlay has room for, and its background.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    fn overlay(&self) -> Option<(char, [u8; 3])> {
+        if self.failed > 0 {
+            return Some(('!', FAILED_COLOR));
+        }
+
+        match self.running {
+            0 => None,
+            running @ 1..=9 => char::from_digit(running, 10).map(|c| (c, RUNNING_COLOR)),
+            _ => Some(('+', RUNNING_COLOR)),
+        }
+    }
+}
+
+/// Rows of a 3x5 glyph, the leftmost pixel in the highest of the three bits.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        _ => [0; 5],
+    }
+}
+
+/// A white character on a colored circle, as premultiplied BGRA rows from the top.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn overlay_pixels(c: char, color: [u8; 3]) -> Vec<u8> {
+    let glyph = glyph(c);
+    let glyph_x = (OVERLAY_SIZE - 3 * GLYPH_SCALE) / 2;
+    let glyph_y = (OVERLAY_SIZE - 5 * GLYPH_SCALE) / 2;
+    let radius = OVERLAY_SIZE as f32 / 2.0;
+    let mut pixels = Vec::with_capacity(OVERLAY_SIZE * OVERLAY_SIZE * 4);
+
+    for y in 0..OVERLAY_SIZE {
+        for x in 0..OVERLAY_SIZE {
+            let dx = x as f32 + 0.5 - radius;
+            let dy = y as f32 + 0.5 - radius;
+            if dx * dx + dy * dy > radius * radius {
+                pixels.extend_from_slice(&[0, 0, 0, 0]);
+                continue;
+            }
+
+            let in_glyph = (glyph_x..glyph_x + 3 * GLYPH_SCALE).contains(&x)
+                && (glyph_y..glyph_y + 5 * GLYPH_SCALE).contains(&y)
+                && glyph[(y - glyph_y) / GLYPH_SCALE] & (0b100 >> ((x - glyph_x) / GLYPH_SCALE))
+                    != 0;
+            let [r, g, b] = if in_glyph { [0xff; 3] } else { color };
+            pixels.extend_from_slice(&[b, g, r, 0xff]);
+        }
+    }
+
+    pixels
+}
+
+/// Shows the number of running workspaces on the dock icon on macOS, and as an overlay of the taskbar button on
+/// Windows. Failures show up as `!` until the workspace's next action succeeds.
+#[tauri::command]
+pub fn set_dock_badge(app_handle: AppHandle, badge: DockBadge) {
+    {
+        let mut current = CURRENT.lock().unwrap();
+        if *current == badge {
+            return;
+        }
+        *current = badge;
+    }
+
+    #[cfg(target_os = "macos")]
+    macos::set_badge_label(&app_handle, badge.label());
+    // The overlay belongs to the taskbar button, so it's only visible while the main window has one
+    #[cfg(target_os = "windows")]
+    if let Some(window) = tauri::Manager::get_window(&app_handle, "main") {
+        let _ = app_handle.run_on_main_thread(move || {
+            if let Err(err) = taskbar::set_overlay(&window, &badge) {
+                log::error!("Failed to update taskbar overlay: {}", err);
+            }
+        });
+    }
+    #[cfg(target_os = "linux")]
+    let _ = app_handle;
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use crate::AppHandle;
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+
+    pub fn set_badge_label(app_handle: &AppHandle, label: Option<String>) {
+        let _ = app_handle.run_on_main_thread(move || unsafe {
+            let app: id = msg_send![class!(NSApplication), sharedApplication];
+            let dock_tile: id = msg_send![app, dockTile];
+            let label = match label {
+                Some(label) => NSString::alloc(nil).init_str(&label),
+                None => nil,
+            };
+            let _: () = msg_send![dock_tile, setBadgeLabel: label];
+            if label != nil {
+                let _: () = msg_send![label, release];
+            }
+        });
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod taskbar {
+    use super::{overlay_pixels, DockBadge, OVERLAY_SIZE};
+    use windows::{
+        core::{HSTRING, PCWSTR},
+        Win32::{
+            Foundation::HWND,
+            Graphics::Gdi::{CreateBitmap, DeleteObject},
+            System::Com::{
+                CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED,
+            },
+            UI::{
+                Shell::{ITaskbarList3, TaskbarList},
+                WindowsAndMessaging::{CreateIconIndirect, DestroyIcon, HICON, ICONINFO},
+            },
+        },
+    };
+
+    pub fn set_overlay(window: &tauri::Window, badge: &DockBadge) -> anyhow::Result<()> {
+        let hwnd = HWND(window.hwnd()?.0);
+
+        unsafe {
+            // Fails if COM has already been initialized on this thread, which is fine
+            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+            let taskbar: ITaskbarList3 =
+                CoCreateInstance(&TaskbarList, None, CLSCTX_INPROC_SERVER)?;
+            taskbar.HrInit()?;
+            let Some((c, color)) = badge.overlay() else {
+                taskbar.SetOverlayIcon(hwnd, HICON::default(), PCWSTR::null())?;
+                return Ok(());
+            };
+
+            let pixels = overlay_pixels(c, color);
+            // The alpha channel of the color bitmap is used, the mask only has to exist
+            let mask_bits = vec![0u8; OVERLAY_SIZE * OVERLAY_SIZE / 8];
+            let size = OVERLAY_SIZE as i32;
+            let color_bitmap = CreateBitmap(size, size, 1, 32, Some(pixels.as_ptr() as *const _));
+            let mask_bitmap = CreateBitmap(size, size, 1, 1, Some(mask_bits.as_ptr() as *const _));
+            let icon = CreateIconIndirect(&ICONINFO {
+                fIcon: true.into(),
+                xHotspot: 0,
+                yHotspot: 0,
+                hbmMask: mask_bitmap,
+                hbmColor: color_bitmap,
+            });
+            DeleteObject(color_bitmap);
+            DeleteObject(mask_bitmap);
+            let icon = icon?;
+
+            // The taskbar keeps its own copy of the icon
+            let result = taskbar.SetOverlayIcon(hwnd, icon, &HSTRING::from(badge.description()));
+            DestroyIcon(icon);
+            result?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_show_failures_over_running_workspaces() {
+        let badge = |running, failed| DockBadge { running, failed };
+
+        assert_eq!(badge(0, 0).label(), None);
+        assert_eq!(badge(3, 0).label().as_deref(), Some("3"));
+        assert_eq!(badge(3, 1).label().as_deref(), Some("!"));
+        assert_eq!(badge(12, 0).overlay(), Some(('+', RUNNING_COLOR)));
+        assert_eq!(badge(0, 2).overlay(), Some(('!', FAILED_COLOR)));
+
+        // Top left of the `1` glyph is blank, the pixel to its right is part of it
+        let pixels = overlay_pixels('1', RUNNING_COLOR);
+        let pixel = |x: usize, y: usize| &pixels[(y * OVERLAY_SIZE + x) * 4..][..4];
+        assert_eq!(pixel(5, 3), &[0xf6, 0x5c, 0x8b, 0xff]);
+        assert_eq!(pixel(7, 3), &[0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(pixel(0, 0), &[0, 0, 0, 0]);
+    }
+}
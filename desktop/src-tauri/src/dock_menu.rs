@@ -0,0 +1,294 @@
+use crate::{
+    browser_ide,
+    commands::{stop_workspace::StopWorkspaceCommand, DevpodCommandConfig},
+    custom_protocol::OpenWorkspaceMsg,
+    ui_messages::{ShowToastMsg, ToastStatus},
+    workspaces::WorkspacesState,
+    AppHandle, AppState, UiMessage,
+};
+use log::{error, info};
+use std::thread;
+use tauri::Manager;
+
+const RECENT_WORKSPACES_LIMIT: usize = 5;
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DockAction {
+    OpenWorkspace(String),
+    CreateWorkspace,
+    StopAll(Vec<String>),
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DockItem {
+    Action(String, DockAction),
+    Separator,
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn items(workspaces: &WorkspacesState) -> Vec<DockItem> {
+    let ids = workspaces.recent_ids();
+    let mut items: Vec<DockItem> = ids
+        .iter()
+        .take(RECENT_WORKSPACES_LIMIT)
+        .map(|id| DockItem::Action(id.to_string(), DockAction::OpenWorkspace(id.to_string())))
+        .collect();
+    if !items.is_empty() {
+        items.push(DockItem::Separator);
+    }
+
+    items.push(DockItem::Action(
+        "New Workspace…".to_string(),
+        DockAction::CreateWorkspace,
+    ));
+    if !ids.is_empty() {
+        let ids = ids.iter().map(|id| id.to_string()).collect();
+        items.push(DockItem::Action(
+            "Stop All Workspaces".to_string(),
+            DockAction::StopAll(ids),
+        ));
+    }
+
+    items
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn send(app_handle: &AppHandle, msg: UiMessage) {
+    let app_state = app_handle.state::<AppState>();
+    tauri::async_runtime::block_on(async move {
+        if let Err(err) = app_state.ui_messages.send(msg).await {
+            error!("Failed to broadcast dock menu message: {}", err);
+        }
+    });
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn handle_action(app_handle: &AppHandle, action: DockAction) {
+    match action {
+        DockAction::OpenWorkspace(id) => send(
+            app_handle,
+            UiMessage::OpenWorkspace(OpenWorkspaceMsg::with_id(id)),
+        ),
+        DockAction::CreateWorkspace => send(
+            app_handle,
+            UiMessage::OpenWorkspace(OpenWorkspaceMsg::empty()),
+        ),
+        DockAction::StopAll(ids) => {
+            let app_handle = app_handle.clone();
+            thread::spawn(move || {
+                info!("Stopping {} workspaces from dock menu", ids.len());
+                let failed: Vec<String> = ids
+                    .into_iter()
+                    .filter(|id| {
+                        let stopped = StopWorkspaceCommand::new(id.clone())
+                            .exec(&app_handle)
+                            .is_ok();
+                        if stopped {
+                            browser_ide::close_workspace_sessions(&app_handle, id);
+                        }
+
+                        !stopped
+                    })
+                    .collect();
+
+                let msg = if failed.is_empty() {
+                    ShowToastMsg::new(
+                        "Workspaces stopped".to_string(),
+                        "All workspaces have been stopped".to_string(),
+                        ToastStatus::Success,
+                    )
+                } else {
+                    ShowToastMsg::new(
+                        "Unable to stop workspaces".to_string(),
+                        format!("Failed to stop {}", failed.join(", ")),
+                        ToastStatus::Error,
+                    )
+                };
+                send(&app_handle, UiMessage::ShowToast(msg));
+            });
+        }
+    }
+}
+
+/// Installs the dock menu, its items are filled in by `update` once the workspaces are loaded.
+pub fn setup(app_handle: &AppHandle) {
+    #[cfg(target_os = "macos")]
+    macos::setup(app_handle);
+    #[cfg(not(target_os = "macos"))]
+    let _ = app_handle;
+}
+
+/// Rebuilds the dock menu, called whenever the workspaces changed, just like the tray menu.
+pub fn update(app_handle: &AppHandle, workspaces: &WorkspacesState) {
+    #[cfg(target_os = "macos")]
+    macos::set_items(app_handle, items(workspaces));
+    #[cfg(not(target_os = "macos"))]
+    let _ = (app_handle, workspaces);
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::{handle_action, DockAction, DockItem};
+    use crate::AppHandle;
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use lazy_static::lazy_static;
+    use log::warn;
+    use objc::{
+        declare::ClassDecl,
+        runtime::{class_addMethod, object_getClass, Class, Object, Sel},
+    };
+    use std::sync::Mutex;
+
+    struct DockMenu {
+        // NSMenu and its target are only ever touched on the main thread
+        menu: usize,
+        target: usize,
+        actions: Vec<DockAction>,
+    }
+
+    lazy_static! {
+        static ref DOCK_MENU: Mutex<DockMenu> = Mutex::new(DockMenu {
+            menu: 0,
+            target: 0,
+            actions: vec![],
+        });
+    }
+
+    extern "C" fn application_dock_menu(_this: &Object, _cmd: Sel, _app: id) -> id {
+        DOCK_MENU.lock().unwrap().menu as id
+    }
+
+    extern "C" fn handle_item(this: &Object, _cmd: Sel, sender: id) {
+        let tag: isize = unsafe { msg_send![sender, tag] };
+        let action = DOCK_MENU.lock().unwrap().actions.get(tag as usize).cloned();
+        // The target lives as long as the app, so its handle is never freed
+        let app_handle = unsafe { &*(*this.get_ivar::<usize>("appHandle") as *const AppHandle) };
+        if let Some(action) = action {
+            handle_action(app_handle, action);
+        }
+    }
+
+    /// Tauri doesn't expose the dock menu, so we teach the application delegate `applicationDockMenu:`.
+    pub fn setup(app_handle: &AppHandle) {
+        let handle = app_handle.clone();
+        let _ = app_handle.run_on_main_thread(move || unsafe {
+            let app: id = msg_send![class!(NSApplication), sharedApplication];
+            let delegate: id = msg_send![app, delegate];
+            if delegate == nil {
+                warn!("No application delegate, unable to set up dock menu");
+                return;
+            }
+            let added = class_addMethod(
+                object_getClass(delegate) as *mut Class,
+                sel!(applicationDockMenu:),
+                std::mem::transmute(application_dock_menu as extern "C" fn(&Object, Sel, id) -> id),
+                b"@@:@\0".as_ptr() as *const _,
+            );
+            if added == objc::runtime::NO {
+                warn!("Application delegate already provides a dock menu");
+            }
+
+            let mut decl = match ClassDecl::new("DevPodDockMenuTarget", class!(NSObject)) {
+                Some(decl) => decl,
+                None => return,
+            };
+            decl.add_method(
+                sel!(handleItem:),
+                handle_item as extern "C" fn(&Object, Sel, id),
+            );
+            decl.add_ivar::<usize>("appHandle");
+            let target: id = msg_send![decl.register(), new];
+            (*target).set_ivar("appHandle", Box::into_raw(Box::new(handle)) as usize);
+            DOCK_MENU.lock().unwrap().target = target as usize;
+        });
+    }
+
+    pub fn set_items(app_handle: &AppHandle, items: Vec<DockItem>) {
+        let _ = app_handle.run_on_main_thread(move || unsafe {
+            let mut dock_menu = DOCK_MENU.lock().unwrap();
+            let target = dock_menu.target as id;
+            let menu: id = msg_send![class!(NSMenu), new];
+            let mut actions = vec![];
+
+            for item in items {
+                let menu_item: id = match item {
+                    DockItem::Separator => msg_send![class!(NSMenuItem), separatorItem],
+                    DockItem::Action(title, action) => {
+                        let title = NSString::alloc(nil).init_str(&title);
+                        let key = NSString::alloc(nil).init_str("");
+                        let menu_item: id = msg_send![class!(NSMenuItem), alloc];
+                        let menu_item: id = msg_send![
+                            menu_item,
+                            initWithTitle: title
+                            action: sel!(handleItem:)
+                            keyEquivalent: key
+                        ];
+                        let _: () = msg_send![menu_item, setTarget: target];
+                        let _: () = msg_send![menu_item, setTag: actions.len() as isize];
+                        let _: () = msg_send![menu_item, autorelease];
+                        let _: () = msg_send![title, release];
+                        let _: () = msg_send![key, release];
+                        actions.push(action);
+
+                        menu_item
+                    }
+                };
+                let _: () = msg_send![menu, addItem: menu_item];
+            }
+
+            let previous = dock_menu.menu as id;
+            if previous != nil {
+                let _: () = msg_send![previous, release];
+            }
+            dock_menu.menu = menu as usize;
+            dock_menu.actions = actions;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_list_recent_workspaces() {
+        let workspaces: WorkspacesState = serde_json::from_str(
+            r#"[
+                {"id": "old", "lastUsed": "2023-01-01T00:00:00Z"},
+                {"id": "new", "lastUsed": "2023-06-01T00:00:00Z"},
+                {"id": null}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            items(&workspaces),
+            vec![
+                DockItem::Action(
+                    "new".to_string(),
+                    DockAction::OpenWorkspace("new".to_string())
+                ),
+                DockItem::Action(
+                    "old".to_string(),
+                    DockAction::OpenWorkspace("old".to_string())
+                ),
+                DockItem::Separator,
+                DockItem::Action("New Workspace…".to_string(), DockAction::CreateWorkspace),
+                DockItem::Action(
+                    "Stop All Workspaces".to_string(),
+                    DockAction::StopAll(vec!["new".to_string(), "old".to_string()])
+                ),
+            ]
+        );
+        assert_eq!(
+            items(&WorkspacesState::default()),
+            vec![DockItem::Action(
+                "New Workspace…".to_string(),
+                DockAction::CreateWorkspace
+            )]
+        );
+    }
+}
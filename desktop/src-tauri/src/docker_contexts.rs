@@ -0,0 +1,317 @@
+use crate::{
+    cli_cache,
+    commands::{
+        set_provider_options::SetProviderOptionsCommand, DevpodCommandConfig, DevpodCommandError,
+    },
+    doctor::run,
+    AppHandle,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+// The option of the docker provider that points it at a daemon, unset it talks to the default one
+const DOCKER_HOST_OPTION: &str = "DOCKER_HOST";
+// Unreachable SSH hosts keep the client waiting for minutes
+const CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Error, Debug)]
+pub enum DockerContextError {
+    #[error("unable to list docker contexts: {0}")]
+    List(String),
+    #[error("docker context {0} doesn't exist")]
+    UnknownContext(String),
+    #[error("provider {0} doesn't connect to a docker daemon")]
+    NotDockerProvider(String),
+    #[error("unable to access provider options")]
+    Provider(#[from] DevpodCommandError),
+    #[error("unable to check docker context")]
+    Join(#[source] tauri::Error),
+}
+impl serde::Serialize for DockerContextError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// A line of `docker context ls --format '{{json .}}'`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListedContext {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    docker_endpoint: String,
+    #[serde(default)]
+    current: bool,
+    #[serde(default)]
+    error: String,
+}
+
+/// A docker daemon the docker CLI knows about, e.g. Docker Desktop, colima, a podman socket or a remote host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DockerContext {
+    name: String,
+    description: Option<String>,
+    /// e.g. `unix:///Users/me/.colima/default/docker.sock` or `ssh://me@build-host`
+    endpoint: String,
+    /// The context `docker` uses unless told otherwise
+    current: bool,
+    /// Why the context can't be loaded, e.g. a broken config
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DockerContextCheck {
+    reachable: bool,
+    server_version: Option<String>,
+    error: Option<String>,
+}
+
+fn non_empty(value: String) -> Option<String> {
+    Some(value).filter(|value| !value.trim().is_empty())
+}
+
+fn parse_contexts(output: &str) -> Result<Vec<DockerContext>, DockerContextError> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let context: ListedContext = serde_json::from_str(line)
+                .map_err(|err| DockerContextError::List(err.to_string()))?;
+
+            Ok(DockerContext {
+                name: context.name,
+                description: non_empty(context.description),
+                endpoint: context.docker_endpoint,
+                current: context.current,
+                error: non_empty(context.error),
+            })
+        })
+        .collect()
+}
+
+fn contexts() -> Result<Vec<DockerContext>, DockerContextError> {
+    let (output, _) = run("docker", &["context", "ls", "--format", "{{json .}}"])
+        .map_err(DockerContextError::List)?;
+
+    parse_contexts(&output)
+}
+
+/// The `DOCKER_HOST` option of a provider, `None` for providers without one.
+pub(crate) fn docker_host(
+    app_handle: &AppHandle,
+    provider: &str,
+) -> Result<Option<String>, DockerContextError> {
+    Ok(cli_cache::provider_option(
+        app_handle,
+        provider,
+        DOCKER_HOST_OPTION,
+    )?)
+}
+
+/// Runs `docker` against `docker_host` the way a provider with that `DOCKER_HOST` option would, without one against
+/// the daemon `docker` uses by default.
+pub(crate) fn docker(docker_host: Option<&str>, args: &[&str]) -> Result<String, String> {
+    let mut command = Command::new("docker");
+    command.args(args).stdin(Stdio::null());
+    if let Some(docker_host) = docker_host.filter(|host| !host.is_empty()) {
+        command
+            .env("DOCKER_HOST", docker_host)
+            .env_remove("DOCKER_CONTEXT");
+    }
+    let output = command.output().map_err(|err| err.to_string())?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if !output.status.success() {
+        return Err(if stderr.is_empty() { stdout } else { stderr });
+    }
+
+    Ok(stdout)
+}
+
+/// The context whose endpoint the provider uses, `None` if it uses the default daemon or an endpoint no context
+/// points to.
+fn selected(contexts: &[DockerContext], docker_host: &str) -> Option<String> {
+    if docker_host.is_empty() {
+        return None;
+    }
+
+    contexts
+        .iter()
+        .find(|context| context.endpoint == docker_host)
+        .map(|context| context.name.clone())
+}
+
+/// Runs `docker version` against `endpoint` the way the provider would, i.e. with `DOCKER_HOST` only. Contexts that
+/// need TLS certificates fail here as they would in the provider.
+fn check(endpoint: &str) -> DockerContextCheck {
+    let unreachable = |error: String| DockerContextCheck {
+        reachable: false,
+        server_version: None,
+        error: Some(error),
+    };
+
+    let child = Command::new("docker")
+        .args(["version", "--format", "{{.Server.Version}}"])
+        .env("DOCKER_HOST", endpoint)
+        .env_remove("DOCKER_CONTEXT")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(err) => return unreachable(err.to_string()),
+    };
+
+    let started_at = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started_at.elapsed() < CHECK_TIMEOUT => {
+                thread::sleep(Duration::from_millis(100))
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return unreachable(format!(
+                    "no response within {} seconds",
+                    CHECK_TIMEOUT.as_secs()
+                ));
+            }
+            Err(err) => return unreachable(err.to_string()),
+        }
+    }
+
+    let output = match child.wait_with_output() {
+        Ok(output) => output,
+        Err(err) => return unreachable(err.to_string()),
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if !output.status.success() {
+        return unreachable(if stderr.is_empty() { stdout } else { stderr });
+    }
+
+    DockerContextCheck {
+        reachable: true,
+        server_version: non_empty(stdout),
+        error: None,
+    }
+}
+
+#[tauri::command]
+pub async fn list_docker_contexts() -> Result<Vec<DockerContext>, DockerContextError> {
+    tauri::async_runtime::spawn_blocking(contexts)
+        .await
+        .map_err(DockerContextError::Join)?
+}
+
+/// The context the docker provider `provider` talks to, `None` if it uses the default one.
+#[tauri::command]
+pub async fn get_docker_context(
+    app_handle: AppHandle,
+    provider: String,
+) -> Result<Option<String>, DockerContextError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let docker_host = docker_host(&app_handle, &provider)?
+            .ok_or_else(|| DockerContextError::NotDockerProvider(provider.clone()))?;
+
+        Ok(selected(&contexts()?, &docker_host))
+    })
+    .await
+    .map_err(DockerContextError::Join)?
+}
+
+/// Points the docker provider `provider` at the endpoint of `context`, without a context it goes back to the default
+/// one. Other instances of the provider keep theirs.
+#[tauri::command]
+pub async fn set_docker_context(
+    app_handle: AppHandle,
+    provider: String,
+    context: Option<String>,
+) -> Result<(), DockerContextError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if docker_host(&app_handle, &provider)?.is_none() {
+            return Err(DockerContextError::NotDockerProvider(provider));
+        }
+        let endpoint = match &context {
+            Some(name) => contexts()?
+                .into_iter()
+                .find(|context| &context.name == name)
+                .map(|context| context.endpoint)
+                .ok_or_else(|| DockerContextError::UnknownContext(name.clone()))?,
+            None => String::new(),
+        };
+
+        SetProviderOptionsCommand::new(provider.clone(), &[(DOCKER_HOST_OPTION, &endpoint)])
+            .exec(&app_handle)?;
+        info!(
+            "Provider {} uses docker context {}",
+            provider,
+            context.as_deref().unwrap_or("default")
+        );
+
+        Ok(())
+    })
+    .await
+    .map_err(DockerContextError::Join)?
+}
+
+/// Whether the daemon of `context` can be reached the way a provider would connect to it.
+#[tauri::command]
+pub async fn check_docker_context(
+    context: String,
+) -> Result<DockerContextCheck, DockerContextError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let endpoint = contexts()?
+            .into_iter()
+            .find(|listed| listed.name == context)
+            .map(|listed| listed.endpoint)
+            .ok_or(DockerContextError::UnknownContext(context))?;
+
+        Ok(check(&endpoint))
+    })
+    .await
+    .map_err(DockerContextError::Join)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_docker_contexts() {
+        let contexts = parse_contexts(
+            r#"{"Current":true,"Description":"Create a context with the colima socket","DockerEndpoint":"unix:///Users/me/.colima/default/docker.sock","Error":"","Name":"colima"}
+{"Current":false,"Description":"","DockerEndpoint":"ssh://me@build-host","Error":"","Name":"build-host"}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(contexts.len(), 2);
+        assert!(contexts[0].current);
+        assert_eq!(contexts[1].description, None);
+        assert_eq!(
+            selected(&contexts, "ssh://me@build-host"),
+            Some("build-host".to_string())
+        );
+        assert_eq!(selected(&contexts, ""), None);
+        assert_eq!(selected(&contexts, "tcp://127.0.0.1:2375"), None);
+    }
+}
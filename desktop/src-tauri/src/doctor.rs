@@ -0,0 +1,390 @@
+use crate::{
+    cli_cache, debug_console,
+    keychain::{self, KeychainError},
+    AppHandle,
+};
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use ts_rs::TS;
+
+// Unlikely to ever exist, only used to find out whether the keychain can be accessed at all
+const KEYCHAIN_PROBE_SERVICE: &str = "sh.loft.devpod.doctor";
+const KEYCHAIN_PROBE_ACCOUNT: &str = "probe";
+const MIN_FREE_DISK_BYTES: u64 = 1024 * 1024 * 1024;
+const LOW_FREE_DISK_BYTES: u64 = 5 * 1024 * 1024 * 1024;
+const CONTAINER_RUNTIME_HINT: &str =
+    "Install Docker or Podman to run workspaces locally. Cloud and Kubernetes providers don't need it.";
+const SSH_HINT: &str =
+    "Install an OpenSSH client and make sure `ssh` is on your PATH, IDEs connect to workspaces through it.";
+// The name `install_cli` links the bundled cli to
+#[cfg(not(target_os = "windows"))]
+const CLI_NAMES: [&str; 1] = ["devpod"];
+#[cfg(target_os = "windows")]
+const CLI_NAMES: [&str; 2] = ["devpod.cmd", "devpod.exe"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DoctorCheck {
+    id: String,
+    name: String,
+    status: CheckStatus,
+    message: String,
+    /// What the user can do about a failed check
+    remediation: Option<String>,
+}
+
+impl DoctorCheck {
+    fn new(id: &str, name: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            status,
+            message: message.into(),
+            remediation: None,
+        }
+    }
+
+    fn remediation(mut self, remediation: &str) -> Self {
+        self.remediation = Some(remediation.to_string());
+        self
+    }
+
+    pub fn status(&self) -> CheckStatus {
+        self.status
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn remediation_hint(&self) -> Option<&str> {
+        self.remediation.as_deref()
+    }
+}
+
+#[derive(Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DoctorReport {
+    /// The worst status of all checks
+    status: CheckStatus,
+    checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    fn new(checks: Vec<DoctorCheck>) -> Self {
+        let status = checks
+            .iter()
+            .map(|check| check.status)
+            .max()
+            .unwrap_or(CheckStatus::Pass);
+
+        Self { status, checks }
+    }
+}
+
+/// Checks everything DevPod depends on in the local environment.
+#[tauri::command]
+pub async fn run_doctor(app_handle: AppHandle) -> Result<DoctorReport, ()> {
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        let _task = debug_console::track_task("doctor", None);
+        DoctorReport::new(vec![
+            check_container_runtime(),
+            check_ssh(),
+            check_cli_version(&app_handle),
+            check_cli_path(),
+            check_protocol_handler(),
+            check_keychain(),
+            check_disk_space(&app_handle),
+        ])
+    })
+    .await
+    .map_err(|err| warn!("Failed to run doctor: {}", err))?;
+
+    info!("Doctor finished with status {:?}", report.status);
+    for check in report.checks.iter() {
+        if check.status != CheckStatus::Pass {
+            info!(
+                "Doctor check {}: {:?}, {}",
+                check.id, check.status, check.message
+            );
+        }
+    }
+
+    Ok(report)
+}
+
+/// Runs `program`, returning stdout and stderr if it exited successfully.
+pub(crate) fn run(program: &str, args: &[&str]) -> Result<(String, String), String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| err.to_string())?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if !output.status.success() {
+        return Err(if stderr.is_empty() { stdout } else { stderr });
+    }
+
+    Ok((stdout, stderr))
+}
+
+pub(crate) fn check_container_runtime() -> DoctorCheck {
+    let (id, name) = ("container_runtime", "Container runtime");
+    if let Ok((version, _)) = run("docker", &["version", "--format", "{{.Server.Version}}"]) {
+        return DoctorCheck::new(id, name, CheckStatus::Pass, format!("Docker {}", version));
+    }
+    if let Ok((version, _)) = run("podman", &["version", "--format", "{{.Version}}"]) {
+        return DoctorCheck::new(id, name, CheckStatus::Pass, format!("Podman {}", version));
+    }
+
+    // The client might be installed without a running daemon
+    if run("docker", &["--version"]).is_ok() {
+        return DoctorCheck::new(
+            id,
+            name,
+            CheckStatus::Warn,
+            "Docker is installed but the daemon is not reachable",
+        )
+        .remediation("Start Docker Desktop or the docker service, then run the doctor again.");
+    }
+
+    // Only the docker provider needs a local runtime
+    DoctorCheck::new(
+        id,
+        name,
+        CheckStatus::Warn,
+        "Neither Docker nor Podman found",
+    )
+    .remediation(CONTAINER_RUNTIME_HINT)
+}
+
+fn check_ssh() -> DoctorCheck {
+    let (id, name) = ("ssh", "SSH client");
+    match run("ssh", &["-V"]) {
+        // `ssh -V` prints to stderr
+        Ok((stdout, stderr)) => DoctorCheck::new(
+            id,
+            name,
+            CheckStatus::Pass,
+            if stderr.is_empty() { stdout } else { stderr },
+        ),
+        Err(err) => DoctorCheck::new(
+            id,
+            name,
+            CheckStatus::Fail,
+            format!("ssh not found: {}", err),
+        )
+        .remediation(SSH_HINT),
+    }
+}
+
+fn check_cli_version(app_handle: &AppHandle) -> DoctorCheck {
+    let (id, name) = ("cli_version", "DevPod CLI");
+    let app_version = app_handle.package_info().version.to_string();
+    match cli_cache::version(app_handle) {
+        Ok(version) if version.trim_start_matches('v') == app_version => {
+            DoctorCheck::new(id, name, CheckStatus::Pass, version)
+        }
+        Ok(version) => DoctorCheck::new(
+            id,
+            name,
+            CheckStatus::Warn,
+            format!("CLI {} doesn't match app {}", version, app_version),
+        )
+        .remediation("Reinstall DevPod to get a matching CLI."),
+        Err(err) => DoctorCheck::new(
+            id,
+            name,
+            CheckStatus::Fail,
+            format!("Unable to run bundled CLI: {}", err),
+        )
+        .remediation("Reinstall DevPod, the bundled CLI is missing or broken."),
+    }
+}
+
+fn find_on_path(names: &[&str]) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+
+    env::split_paths(&path)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+fn check_cli_path() -> DoctorCheck {
+    let (id, name) = ("cli_path", "CLI on PATH");
+    match find_on_path(&CLI_NAMES) {
+        Some(path) => DoctorCheck::new(id, name, CheckStatus::Pass, path.to_string_lossy()),
+        None => DoctorCheck::new(id, name, CheckStatus::Warn, "`devpod` is not on your PATH")
+            .remediation("Add the CLI to your PATH in Settings to use DevPod from a terminal."),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn check_protocol_handler() -> DoctorCheck {
+    let (id, name) = ("protocol_handler", "devpod:// links");
+    match run("xdg-mime", &["query", "default", "x-scheme-handler/devpod"]) {
+        Ok((handler, _)) if !handler.is_empty() => DoctorCheck::new(
+            id,
+            name,
+            CheckStatus::Pass,
+            format!("Handled by {}", handler),
+        ),
+        _ => DoctorCheck::new(
+            id,
+            name,
+            CheckStatus::Warn,
+            "No handler registered for devpod:// links",
+        )
+        .remediation("Install update-desktop-database and xdg-mime, then restart DevPod."),
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn check_protocol_handler() -> DoctorCheck {
+    use winreg::{enums::HKEY_CURRENT_USER, RegKey};
+
+    let (id, name) = ("protocol_handler", "devpod:// links");
+    match RegKey::predef(HKEY_CURRENT_USER).open_subkey("Software\\Classes\\devpod") {
+        Ok(_) => DoctorCheck::new(id, name, CheckStatus::Pass, "Registered"),
+        Err(_) => DoctorCheck::new(
+            id,
+            name,
+            CheckStatus::Warn,
+            "No handler registered for devpod:// links",
+        )
+        .remediation("Restart DevPod to register the link handler."),
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn check_protocol_handler() -> DoctorCheck {
+    // Registered through the app bundle's Info.plist, nothing that can go missing at runtime
+    DoctorCheck::new(
+        "protocol_handler",
+        "devpod:// links",
+        CheckStatus::Pass,
+        "Registered by the app bundle",
+    )
+}
+
+fn check_keychain() -> DoctorCheck {
+    let (id, name) = ("keychain", "Keychain");
+    match keychain::get_password(KEYCHAIN_PROBE_SERVICE, KEYCHAIN_PROBE_ACCOUNT) {
+        Ok(_) | Err(KeychainError::NotFound(..)) => {
+            DoctorCheck::new(id, name, CheckStatus::Pass, "Accessible")
+        }
+        Err(err) => DoctorCheck::new(
+            id,
+            name,
+            CheckStatus::Warn,
+            format!("{:#}", anyhow::Error::from(err)),
+        )
+        .remediation(if cfg!(target_os = "linux") {
+            "Install libsecret-tools and make sure a secret service like GNOME Keyring is running."
+        } else {
+            "Unlock your keychain and allow DevPod to access it."
+        }),
+    }
+}
+
+fn check_disk_space(app_handle: &AppHandle) -> DoctorCheck {
+    let (id, name) = ("disk_space", "Disk space");
+    let dir = match app_handle.path_resolver().app_data_dir() {
+        Some(dir) if dir.exists() => dir,
+        _ => env::temp_dir(),
+    };
+    let free = match free_disk_bytes(&dir) {
+        Some(free) => free,
+        None => {
+            return DoctorCheck::new(
+                id,
+                name,
+                CheckStatus::Warn,
+                "Unable to determine free disk space",
+            )
+        }
+    };
+
+    let message = format!("{:.1} GiB free", free as f64 / 1024.0 / 1024.0 / 1024.0);
+    if free < MIN_FREE_DISK_BYTES {
+        DoctorCheck::new(id, name, CheckStatus::Fail, message)
+            .remediation("Free up disk space, workspaces and images need room to be created.")
+    } else if free < LOW_FREE_DISK_BYTES {
+        DoctorCheck::new(id, name, CheckStatus::Warn, message)
+            .remediation("Free up disk space or prune unused workspaces and images.")
+    } else {
+        DoctorCheck::new(id, name, CheckStatus::Pass, message)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn free_disk_bytes(dir: &Path) -> Option<u64> {
+    let (output, _) = run("df", &["-Pk", &dir.to_string_lossy()]).ok()?;
+
+    parse_df(&output)
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn free_disk_bytes(dir: &Path) -> Option<u64> {
+    let script = format!(
+        "(Get-Item -LiteralPath '{}').PSDrive.Free",
+        dir.to_string_lossy()
+    );
+    let (output, _) = run("powershell", &["-NoProfile", "-Command", &script]).ok()?;
+
+    output.trim().parse().ok()
+}
+
+/// Reads the available space from POSIX `df -Pk` output.
+#[allow(dead_code)]
+fn parse_df(output: &str) -> Option<u64> {
+    let available_kb: u64 = output
+        .lines()
+        .nth(1)?
+        .split_whitespace()
+        .nth(3)?
+        .parse()
+        .ok()?;
+
+    Some(available_kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_df() {
+        let output = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+                      /dev/nvme0n1p2   490617784 300000000 165621784      65% /";
+
+        assert_eq!(parse_df(output), Some(165621784 * 1024));
+        assert_eq!(parse_df("Filesystem"), None);
+    }
+
+    #[test]
+    fn should_report_worst_status() {
+        let report = DoctorReport::new(vec![
+            DoctorCheck::new("a", "A", CheckStatus::Pass, ""),
+            DoctorCheck::new("b", "B", CheckStatus::Warn, ""),
+        ]);
+        assert_eq!(report.status, CheckStatus::Warn);
+        assert_eq!(DoctorReport::new(vec![]).status, CheckStatus::Pass);
+    }
+}
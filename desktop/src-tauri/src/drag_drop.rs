@@ -0,0 +1,212 @@
+use crate::{
+    custom_protocol::{CustomProtocol, OpenWorkspaceMsg},
+    file_association,
+    ui_messages::{ShowToastMsg, ToastStatus, UiMessage},
+    AppHandle, AppState,
+};
+use lazy_static::lazy_static;
+use log::{error, info};
+use regex::Regex;
+use std::{collections::VecDeque, path::Path, sync::Mutex};
+use tauri::Manager;
+use url::Url;
+
+const GIT_URL_SCHEMES: [&str; 5] = ["http", "https", "ssh", "git", "git+ssh"];
+
+lazy_static! {
+    // scp-like git remotes, i.e. `git@github.com:loft-sh/devpod.git`
+    static ref SCP_GIT_URL: Regex = Regex::new(r"^[\w.\-]+@[\w.\-]+:[\w.\-~/]+$").unwrap();
+    static ref DROP_QUEUE: Mutex<DropQueue> = Mutex::new(DropQueue::default());
+}
+
+/// Workspaces from a multi-item drop, the UI can only open one at a time.
+/// The next one is opened once the UI finished a workspace action.
+#[derive(Debug, Default)]
+struct DropQueue {
+    pending: VecDeque<OpenWorkspaceMsg>,
+    in_flight: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum DroppedItem {
+    DeepLink(String),
+    Workspace(OpenWorkspaceMsg),
+}
+
+pub(crate) fn is_git_url(item: &str) -> bool {
+    if SCP_GIT_URL.is_match(item) {
+        return true;
+    }
+
+    match Url::parse(item) {
+        Ok(url) => {
+            GIT_URL_SCHEMES.contains(&url.scheme())
+                && url.host_str().is_some()
+                && url.path().trim_matches('/').contains('/')
+        }
+        Err(_) => false,
+    }
+}
+
+/// Turns a dropped path or URL into what opening it would do, `None` if it can't be used as a workspace source.
+fn parse_item(item: &str) -> Option<DroppedItem> {
+    let item = item.trim();
+    if CustomProtocol::is_url(item) {
+        return Some(DroppedItem::DeepLink(item.to_string()));
+    }
+
+    let path = match Url::parse(item) {
+        Ok(url) if url.scheme() == "file" => url.to_file_path().ok()?,
+        _ => Path::new(item).to_path_buf(),
+    };
+    if path.is_dir() {
+        let source = path.to_string_lossy().to_string();
+        return Some(DroppedItem::Workspace(OpenWorkspaceMsg::new(
+            None,
+            None,
+            None,
+            Some(source),
+            None,
+        )));
+    }
+    if file_association::is_workspace_file(&path.to_string_lossy()) {
+        return file_association::open_workspace_msg(&path)
+            .ok()
+            .map(DroppedItem::Workspace);
+    }
+
+    if is_git_url(item) {
+        return Some(DroppedItem::Workspace(OpenWorkspaceMsg::new(
+            None,
+            None,
+            None,
+            Some(item.to_string()),
+            None,
+        )));
+    }
+
+    None
+}
+
+fn send(app_handle: &AppHandle, msg: UiMessage) {
+    let app_state = app_handle.state::<AppState>();
+    tauri::async_runtime::block_on(async move {
+        if let Err(err) = app_state.ui_messages.send(msg).await {
+            error!("Failed to broadcast drop message: {}", err);
+        }
+    });
+}
+
+/// Opens the next queued workspace, called whenever the UI finished a workspace action.
+pub fn advance(app_handle: &AppHandle) {
+    let (next, remaining) = {
+        let mut queue = DROP_QUEUE.lock().unwrap();
+        let next = queue.pending.pop_front();
+        queue.in_flight = next.is_some();
+
+        (next, queue.pending.len())
+    };
+
+    if let Some(msg) = next {
+        if remaining > 0 {
+            send(
+                app_handle,
+                UiMessage::ShowToast(ShowToastMsg::new(
+                    "Dropped workspaces".to_string(),
+                    format!("{} more will be opened afterwards", remaining),
+                    ToastStatus::Info,
+                )),
+            );
+        }
+        send(
+            app_handle,
+            UiMessage::OpenWorkspace(msg.with_suggested_ide()),
+        );
+    }
+}
+
+/// Handles folders, devcontainer files and git URLs dropped onto a window or the app icon.
+pub fn handle_drop(app_handle: &AppHandle, items: Vec<String>) {
+    info!("Dropped {} items", items.len());
+
+    let mut rejected = vec![];
+    let mut workspaces = vec![];
+    for item in items {
+        match parse_item(&item) {
+            Some(DroppedItem::DeepLink(url)) => CustomProtocol::handle_url(app_handle, &url),
+            Some(DroppedItem::Workspace(msg)) => workspaces.push(msg),
+            None => rejected.push(item),
+        }
+    }
+
+    if !rejected.is_empty() {
+        send(
+            app_handle,
+            UiMessage::ShowToast(ShowToastMsg::new(
+                "Unable to open dropped items".to_string(),
+                format!("Not a folder or git repository: {}", rejected.join(", ")),
+                ToastStatus::Warning,
+            )),
+        );
+    }
+    if workspaces.is_empty() {
+        return;
+    }
+
+    let in_flight = {
+        let mut queue = DROP_QUEUE.lock().unwrap();
+        queue.pending.extend(workspaces);
+        queue.in_flight
+    };
+    if !in_flight {
+        advance(app_handle);
+    }
+}
+
+/// Handles URLs and text dropped onto the webview, files are received by the window directly.
+#[tauri::command]
+pub fn drop_items(app_handle: AppHandle, items: Vec<String>) {
+    handle_drop(&app_handle, items);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_git_urls() {
+        for url in [
+            "https://github.com/loft-sh/devpod",
+            "https://gitlab.com/group/subgroup/project.git",
+            "git@github.com:loft-sh/devpod.git",
+            "ssh://git@github.com/loft-sh/devpod.git",
+        ] {
+            assert!(is_git_url(url), "{}", url);
+        }
+        for url in ["https://github.com", "mailto:someone@example.com", "hello"] {
+            assert!(!is_git_url(url), "{}", url);
+        }
+    }
+
+    #[test]
+    fn should_parse_dropped_items() {
+        let dir = std::env::temp_dir();
+        assert_eq!(
+            parse_item(&dir.to_string_lossy()),
+            Some(DroppedItem::Workspace(OpenWorkspaceMsg::new(
+                None,
+                None,
+                None,
+                Some(dir.to_string_lossy().to_string()),
+                None
+            )))
+        );
+        assert_eq!(
+            parse_item("devpod://open?workspace=test"),
+            Some(DroppedItem::DeepLink(
+                "devpod://open?workspace=test".to_string()
+            ))
+        );
+        assert_eq!(parse_item("not a workspace"), None);
+    }
+}
@@ -0,0 +1,354 @@
+use crate::{
+    credential_consent::{self, CredentialRequest, CredentialRequester},
+    secrets::{self, SecretKind},
+    settings::Settings,
+    workspaces, AppHandle,
+};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+};
+use ts_rs::TS;
+
+lazy_static! {
+    // Names of variables that most likely hold credentials, same as the ones `redaction` masks
+    static ref SECRET_NAME_REGEX: Regex = Regex::new(
+        r"(?i)(password|passwd|secret|token|api_?key|access_?key|private_?key|credential)"
+    )
+    .unwrap();
+    // Snapshot of the variables scoped to all workspaces, injected into every CLI call the backend makes
+    static ref GLOBAL_ENVIRONMENT: RwLock<GlobalEnvironment> = RwLock::new(GlobalEnvironment::default());
+}
+
+#[derive(Debug, Default)]
+struct GlobalEnvironment {
+    variables: Vec<EnvironmentVariable>,
+    resolved: HashMap<String, String>,
+    /// Derived from other settings, like the proxy
+    injected: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct EnvironmentVariable {
+    name: String,
+    /// Literal value for `value` sources, keychain account for `keychain` sources
+    value: String,
+    source: EnvironmentVariableSource,
+    scope: EnvironmentVariableScope,
+    /// Provider name or tag, depending on `scope`
+    scope_value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum EnvironmentVariableSource {
+    Value,
+    Keychain,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum EnvironmentVariableScope {
+    All,
+    Provider,
+    Tag,
+}
+
+impl EnvironmentVariable {
+    fn applies_to(&self, provider: Option<&str>, tags: &[String]) -> bool {
+        match self.scope {
+            EnvironmentVariableScope::All => true,
+            EnvironmentVariableScope::Provider => {
+                provider.is_some() && self.scope_value.as_deref() == provider
+            }
+            EnvironmentVariableScope::Tag => self
+                .scope_value
+                .as_ref()
+                .map_or(false, |tag| tags.contains(tag)),
+        }
+    }
+
+    /// Who uses the secret of a `keychain` variable, a configuration imported from someone else may point it anywhere.
+    fn requester(&self) -> CredentialRequester {
+        let scope_value = self.scope_value.clone().unwrap_or_default();
+        match self.scope {
+            EnvironmentVariableScope::All => CredentialRequester::AllWorkspaces,
+            EnvironmentVariableScope::Provider => CredentialRequester::Provider(scope_value),
+            EnvironmentVariableScope::Tag => CredentialRequester::Tag(scope_value),
+        }
+    }
+
+    fn resolve_value(&self, app_handle: &AppHandle) -> Option<String> {
+        match self.source {
+            EnvironmentVariableSource::Value => Some(self.value.clone()),
+            EnvironmentVariableSource::Keychain => {
+                let request = CredentialRequest::new(
+                    self.requester(),
+                    SecretKind::EnvironmentVariable,
+                    &self.value,
+                );
+                if !credential_consent::is_allowed(app_handle, &request) {
+                    warn!(
+                        "Failed to resolve environment variable {}: access hasn't been allowed",
+                        self.name
+                    );
+                    return None;
+                }
+
+                match secrets::get(SecretKind::EnvironmentVariable, &self.value) {
+                    Ok(Some(value)) => Some(value),
+                    Ok(None) => {
+                        warn!(
+                            "Failed to resolve environment variable {}: not found in keychain",
+                            self.name
+                        );
+                        None
+                    }
+                    Err(err) => {
+                        warn!(
+                            "Failed to resolve environment variable {}: {}",
+                            self.name, err
+                        );
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    fn looks_secret(&self) -> bool {
+        SECRET_NAME_REGEX.is_match(&self.name)
+    }
+
+    /// Keychain account for a migrated variable, unique across scopes.
+    fn keychain_account(&self) -> String {
+        match &self.scope_value {
+            Some(scope_value) => format!("{}@{}", self.name, scope_value),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Moves the values of variables that look like credentials into the keychain, returns whether anything changed.
+/// Variables that fail to be stored are left untouched.
+fn migrate_variables(
+    variables: &mut [EnvironmentVariable],
+    mut store: impl FnMut(&str, &str) -> anyhow::Result<()>,
+) -> bool {
+    let mut migrated = false;
+    for env_var in variables.iter_mut() {
+        if env_var.source != EnvironmentVariableSource::Value || !env_var.looks_secret() {
+            continue;
+        }
+
+        let account = env_var.keychain_account();
+        match store(&account, &env_var.value) {
+            Ok(()) => {
+                info!(
+                    "Moved environment variable {} into the keychain",
+                    env_var.name
+                );
+                env_var.source = EnvironmentVariableSource::Keychain;
+                env_var.value = account;
+                migrated = true;
+            }
+            Err(err) => warn!(
+                "Failed to move environment variable {} into the keychain: {}",
+                env_var.name, err
+            ),
+        }
+    }
+
+    migrated
+}
+
+/// Secrets used to be stored as plain values in the settings.
+fn migrate_plaintext_secrets(app_handle: &AppHandle) {
+    let mut variables = Settings::environment_variables(app_handle);
+    let migrated = migrate_variables(&mut variables, |account, value| {
+        secrets::set(SecretKind::EnvironmentVariable, account, value).map_err(anyhow::Error::from)
+    });
+    if !migrated {
+        return;
+    }
+
+    if let Err(err) = Settings::set_environment_variables(app_handle, &variables) {
+        warn!("Failed to save migrated environment variables: {}", err);
+    }
+}
+
+/// Resolves all configured environment variables that apply to a workspace of `provider` with `tags`.
+pub fn resolve(
+    app_handle: &AppHandle,
+    provider: Option<&str>,
+    tags: &[String],
+) -> HashMap<String, String> {
+    let variables: Vec<_> = Settings::environment_variables(app_handle)
+        .into_iter()
+        .filter(|env_var| env_var.applies_to(provider, tags))
+        .collect();
+
+    resolve_values(app_handle, &variables)
+}
+
+/// The variables for CLI calls about the workspace, including the ones scoped to its provider and tags. `provider`
+/// defaults to the one the workspace has been created with.
+pub fn for_workspace(
+    app_handle: &AppHandle,
+    workspace_id: &str,
+    provider: Option<&str>,
+) -> HashMap<String, String> {
+    let provider = provider
+        .map(String::from)
+        .or_else(|| workspaces::provider(app_handle, workspace_id));
+    let tags = Settings::workspace_tags(app_handle)
+        .remove(workspace_id)
+        .unwrap_or_default();
+
+    resolve(app_handle, provider.as_deref(), &tags)
+}
+
+/// Names of the configured variables that apply to a workspace of `provider` with `tags`, values aren't resolved.
+pub fn names(app_handle: &AppHandle, provider: Option<&str>, tags: &[String]) -> HashSet<String> {
+    Settings::environment_variables(app_handle)
+        .into_iter()
+        .filter(|env_var| env_var.applies_to(provider, tags))
+        .map(|env_var| env_var.name)
+        .collect()
+}
+
+fn resolve_values(
+    app_handle: &AppHandle,
+    variables: &[EnvironmentVariable],
+) -> HashMap<String, String> {
+    variables
+        .iter()
+        .filter_map(|env_var| Some((env_var.name.clone(), env_var.resolve_value(app_handle)?)))
+        .collect()
+}
+
+/// Variables the CLI picks the proxy and trace exporter of the settings up from.
+fn injected_from_settings(app_handle: &AppHandle) -> HashMap<String, String> {
+    let mut injected = HashMap::new();
+    if let Some(proxy) = Settings::http_proxy(app_handle) {
+        for name in ["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"] {
+            injected.insert(name.to_string(), proxy.clone());
+        }
+    }
+    if let Some(hosts) = Settings::no_proxy(app_handle) {
+        injected.insert("NO_PROXY".to_string(), hosts.clone());
+        injected.insert("no_proxy".to_string(), hosts);
+    }
+    if let Some(endpoint) = Settings::otlp_endpoint(app_handle) {
+        injected.insert("OTEL_EXPORTER_OTLP_ENDPOINT".to_string(), endpoint);
+    }
+
+    injected
+}
+
+/// Reloads the variables scoped to all workspaces from the settings.
+/// Values are only resolved again if the configured variables changed, so this is cheap to call frequently.
+pub fn refresh_global(app_handle: &AppHandle) {
+    let variables: Vec<_> = Settings::environment_variables(app_handle)
+        .into_iter()
+        .filter(|env_var| env_var.scope == EnvironmentVariableScope::All)
+        .collect();
+
+    let mut global_environment = GLOBAL_ENVIRONMENT.write().unwrap();
+    global_environment.injected = injected_from_settings(app_handle);
+    if global_environment.variables == variables {
+        return;
+    }
+    global_environment.resolved = resolve_values(app_handle, &variables);
+    global_environment.variables = variables;
+}
+
+/// Resolves the variables scoped to all workspaces again, e.g. once the user allowed access to their secrets.
+pub fn reload_global(app_handle: &AppHandle) {
+    GLOBAL_ENVIRONMENT.write().unwrap().variables.clear();
+    refresh_global(app_handle);
+}
+
+/// The variables scoped to all workspaces as of the last `refresh_global`.
+pub fn global() -> HashMap<String, String> {
+    GLOBAL_ENVIRONMENT.read().unwrap().resolved.clone()
+}
+
+/// The variables derived from other settings as of the last `refresh_global`, configured variables take precedence.
+pub fn injected() -> HashMap<String, String> {
+    GLOBAL_ENVIRONMENT.read().unwrap().injected.clone()
+}
+
+pub fn setup(app_handle: &AppHandle) {
+    migrate_plaintext_secrets(app_handle);
+    refresh_global(app_handle);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(name: &str, value: &str, scope_value: Option<&str>) -> EnvironmentVariable {
+        EnvironmentVariable {
+            name: name.to_string(),
+            value: value.to_string(),
+            source: EnvironmentVariableSource::Value,
+            scope: EnvironmentVariableScope::All,
+            scope_value: scope_value.map(String::from),
+        }
+    }
+
+    #[test]
+    fn should_migrate_secret_looking_variables() {
+        let mut variables = vec![
+            variable("GITHUB_TOKEN", "ghp_123", None),
+            variable("AWS_SECRET_ACCESS_KEY", "abc", Some("aws")),
+            variable("EDITOR", "vim", None),
+        ];
+        let mut stored = vec![];
+
+        assert!(migrate_variables(&mut variables, |account, value| {
+            stored.push((account.to_string(), value.to_string()));
+            Ok(())
+        }));
+        assert_eq!(
+            stored,
+            vec![
+                ("GITHUB_TOKEN".to_string(), "ghp_123".to_string()),
+                ("AWS_SECRET_ACCESS_KEY@aws".to_string(), "abc".to_string()),
+            ]
+        );
+        assert_eq!(variables[0].source, EnvironmentVariableSource::Keychain);
+        assert_eq!(variables[0].value, "GITHUB_TOKEN");
+        assert_eq!(variables[2], variable("EDITOR", "vim", None));
+
+        // Already migrated variables and failures are left alone
+        let mut failing = vec![variable("NPM_TOKEN", "123", None)];
+        assert!(!migrate_variables(&mut variables, |_, _| Ok(())));
+        assert!(!migrate_variables(&mut failing, |_, _| anyhow::bail!(
+            "locked"
+        )));
+        assert_eq!(failing[0], variable("NPM_TOKEN", "123", None));
+    }
+    #[test]
+    fn should_keep_ordinary_variables() {
+        let mut variables = vec![
+            variable("KEYBOARD_LAYOUT", "de", None),
+            variable("SSH_AUTH_SOCK", "/tmp/agent.sock", None),
+            variable("AUTHOR", "jane", None),
+            variable("KEYMAP", "us", None),
+        ];
+        let original = variables.clone();
+
+        assert!(!migrate_variables(&mut variables, |_, _| Ok(())));
+        assert_eq!(variables, original);
+    }
+}
@@ -0,0 +1,392 @@
+use crate::{
+    custom_protocol::OpenWorkspaceMsg,
+    telemetry::Span,
+    ui_messages::{ShowToastMsg, ToastStatus, UiMessage},
+    AppHandle, AppState,
+};
+use anyhow::Context;
+use log::{error, info};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+    thread,
+};
+use tauri::Manager;
+
+const DEVPOD_FILE_EXTENSION: &str = "devpod";
+const DEVCONTAINER_FILE_NAMES: [&str; 2] = ["devcontainer.json", ".devcontainer.json"];
+const DEVCONTAINER_DIR_NAME: &str = ".devcontainer";
+
+/// Contents of a `.devpod` file, takes the same options as `devpod://open`.
+/// Relative local sources are resolved against the folder containing the file, which is also the default source.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+struct WorkspaceDefinition {
+    workspace: Option<String>,
+    provider: Option<String>,
+    ide: Option<String>,
+    source: Option<String>,
+    devcontainer: Option<String>,
+}
+
+/// Whether the app has been asked to open `arg` as a file it's registered for.
+pub fn is_workspace_file(arg: &str) -> bool {
+    let path = Path::new(arg);
+    if !path.is_file() {
+        return false;
+    }
+
+    let is_devpod_file = path
+        .extension()
+        .is_some_and(|ext| ext == DEVPOD_FILE_EXTENSION);
+    let is_devcontainer_file = path
+        .file_name()
+        .is_some_and(|name| DEVCONTAINER_FILE_NAMES.iter().any(|n| name == *n));
+
+    is_devpod_file || is_devcontainer_file
+}
+
+/// The project root of a devcontainer config and its path relative to that root.
+/// Configs live in the root itself, in `.devcontainer/` or in a subfolder of `.devcontainer/`.
+fn devcontainer_source(path: &Path) -> (PathBuf, String) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let root = dir
+        .ancestors()
+        .take(2)
+        .find(|ancestor| {
+            ancestor
+                .file_name()
+                .is_some_and(|n| n == DEVCONTAINER_DIR_NAME)
+        })
+        .and_then(Path::parent)
+        .unwrap_or(dir);
+
+    let relative = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    (root.to_path_buf(), relative)
+}
+
+fn parse_definition(path: &Path, content: &str) -> anyhow::Result<OpenWorkspaceMsg> {
+    let definition: WorkspaceDefinition = if content.trim().is_empty() {
+        WorkspaceDefinition::default()
+    } else {
+        serde_yaml::from_str(content).context("parse workspace definition")?
+    };
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    let source = match definition.source {
+        Some(source) if source.starts_with("./") || source.starts_with("../") || source == "." => {
+            dir.join(source).to_string_lossy().to_string()
+        }
+        Some(source) => source,
+        None => dir.to_string_lossy().to_string(),
+    };
+
+    Ok(OpenWorkspaceMsg::new(
+        definition.workspace,
+        definition.provider,
+        definition.ide,
+        Some(source),
+        definition.devcontainer,
+    ))
+}
+
+/// Builds the same message as `devpod://open?source=<folder>` would, with the devcontainer path prefilled.
+pub fn open_workspace_msg(path: &Path) -> anyhow::Result<OpenWorkspaceMsg> {
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("resolve {}", path.to_string_lossy()))?;
+
+    if path
+        .extension()
+        .is_some_and(|ext| ext == DEVPOD_FILE_EXTENSION)
+    {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("read {}", path.to_string_lossy()))?;
+
+        return parse_definition(&path, &content);
+    }
+
+    let (root, devcontainer_path) = devcontainer_source(&path);
+    Ok(OpenWorkspaceMsg::new(
+        None,
+        None,
+        None,
+        Some(root.to_string_lossy().to_string()),
+        Some(devcontainer_path),
+    ))
+}
+
+/// Opens a `.devpod` or `devcontainer.json` file in the create workspace view, or starts the matching workspace.
+pub fn open_file(app_handle: &AppHandle, path: &str) {
+    info!("App opened with file: {:?}", path);
+    let mut span = Span::start("file association");
+
+    let msg = match open_workspace_msg(Path::new(path)) {
+        Ok(msg) => UiMessage::OpenWorkspace(msg.with_suggested_ide()),
+        Err(err) => {
+            span.set_error();
+            UiMessage::ShowToast(ShowToastMsg::new(
+                "Failed to open file".to_string(),
+                format!("{:#}", err),
+                ToastStatus::Error,
+            ))
+        }
+    };
+
+    let app_state = app_handle.state::<AppState>();
+    tauri::async_runtime::block_on(async move {
+        if let Err(err) = app_state.ui_messages.send(msg).await {
+            error!("Failed to broadcast open file message: {}", err);
+        }
+    });
+}
+
+/// Registers the file types with the OS and opens files the app has been launched with.
+pub fn setup(app_handle: &AppHandle) {
+    // Files opened while the app is already running are forwarded by `single_instance`
+    for arg in std::env::args().skip(1) {
+        if is_workspace_file(&arg) {
+            open_file(app_handle, &arg);
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    macos::listen(app_handle);
+
+    thread::spawn(|| {
+        if let Err(err) = register() {
+            error!("Failed to register file associations: {:#}", err);
+        }
+    });
+}
+
+#[cfg(target_os = "macos")]
+fn register() -> anyhow::Result<()> {
+    // Declared in `Info.plist`
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn register() -> anyhow::Result<()> {
+    use std::process::Command;
+
+    const MIME_TYPE: &str = "application/x-devpod-workspace";
+    const DESKTOP_FILE_NAME: &str = "devpod-files.desktop";
+
+    let data_dir = dirs::data_dir().context("find data dir")?;
+    let mime_dir = data_dir.join("mime");
+    let applications_dir = data_dir.join("applications");
+    fs::create_dir_all(mime_dir.join("packages"))?;
+    fs::create_dir_all(&applications_dir)?;
+
+    fs::write(
+        mime_dir.join("packages").join("devpod.xml"),
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<mime-info xmlns="http://www.freedesktop.org/standards/shared-mime-info">
+  <mime-type type="{}">
+    <comment>DevPod Workspace</comment>
+    <glob pattern="*.{}"/>
+  </mime-type>
+</mime-info>
+"#,
+            MIME_TYPE, DEVPOD_FILE_EXTENSION
+        ),
+    )?;
+    // devcontainer.json is plain JSON, we only offer to open it instead of taking it over
+    fs::write(
+        applications_dir.join(DESKTOP_FILE_NAME),
+        format!(
+            "[Desktop Entry]\nType=Application\nName=DevPod\nExec=\"{}\" %f\nTerminal=false\nMimeType={};application/json;\nNoDisplay=true\n",
+            crate::util::app_path()?.to_string_lossy(),
+            MIME_TYPE
+        ),
+    )?;
+
+    Command::new("update-mime-database")
+        .arg(&mime_dir)
+        .status()
+        .context("update-mime-database")?;
+    Command::new("update-desktop-database")
+        .arg(&applications_dir)
+        .status()
+        .context("update-desktop-database")?;
+    Command::new("xdg-mime")
+        .args(["default", DESKTOP_FILE_NAME, MIME_TYPE])
+        .status()
+        .context("xdg-mime")?;
+
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn register() -> anyhow::Result<()> {
+    use winreg::{enums::HKEY_CURRENT_USER, RegKey};
+
+    const PROG_ID: &str = "DevPod.Workspace";
+
+    let exe = crate::util::app_path()?.to_string_lossy().to_string();
+    let classes = RegKey::predef(HKEY_CURRENT_USER).open_subkey("Software\\Classes")?;
+
+    let (extension, _) = classes.create_subkey(format!(".{}", DEVPOD_FILE_EXTENSION))?;
+    extension.set_value("", &PROG_ID)?;
+    let (prog_id, _) = classes.create_subkey(PROG_ID)?;
+    prog_id.set_value("", &"DevPod Workspace")?;
+    let (icon, _) = prog_id.create_subkey("DefaultIcon")?;
+    icon.set_value("", &format!("\"{}\",0", exe))?;
+    let (command, _) = prog_id.create_subkey("shell\\open\\command")?;
+    command.set_value("", &format!("\"{}\" \"%1\"", exe))?;
+
+    // Windows can't associate a single file name, offer to open any JSON file instead
+    let (open_with, _) = classes.create_subkey(".json\\OpenWithProgids")?;
+    open_with.set_value(PROG_ID, &"")?;
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use crate::{drag_drop, AppHandle};
+    use cocoa::base::{id, nil};
+    use objc::{
+        declare::ClassDecl,
+        runtime::{Object, Sel},
+    };
+    use std::{ffi::CStr, os::raw::c_char};
+
+    // Four character codes of the Apple events, https://developer.apple.com/documentation/coreservices/apple_events
+    const CORE_EVENT_CLASS: u32 = u32::from_be_bytes(*b"aevt");
+    const OPEN_DOCUMENTS_EVENT: u32 = u32::from_be_bytes(*b"odoc");
+    const DIRECT_OBJECT_KEYWORD: u32 = u32::from_be_bytes(*b"----");
+    const FILE_URL_TYPE: u32 = u32::from_be_bytes(*b"furl");
+
+    unsafe fn event_paths(event: id) -> Vec<String> {
+        let list: id = msg_send![event, paramDescriptorForKeyword: DIRECT_OBJECT_KEYWORD];
+        if list == nil {
+            return vec![];
+        }
+
+        let count: isize = msg_send![list, numberOfItems];
+        // Descriptor lists are 1-based
+        (1..=count)
+            .filter_map(|i| {
+                let item: id = msg_send![list, descriptorAtIndex: i];
+                let file_url: id = msg_send![item, coerceToDescriptorType: FILE_URL_TYPE];
+                if file_url == nil {
+                    return None;
+                }
+                let data: id = msg_send![file_url, data];
+                let url: id =
+                    msg_send![class!(NSURL), URLWithDataRepresentation: data relativeToURL: nil];
+                let path: id = msg_send![url, path];
+                if path == nil {
+                    return None;
+                }
+                let path: *const c_char = msg_send![path, UTF8String];
+
+                Some(CStr::from_ptr(path).to_string_lossy().to_string())
+            })
+            .collect()
+    }
+
+    extern "C" fn handle_open_documents(this: &Object, _cmd: Sel, event: id, _reply: id) {
+        let paths = unsafe { event_paths(event) };
+        // The handler lives as long as the app, so its handle is never freed
+        let app_handle = unsafe { &*(*this.get_ivar::<usize>("appHandle") as *const AppHandle) };
+        // Also sent for folders dropped onto the dock icon
+        drag_drop::handle_drop(app_handle, paths);
+    }
+
+    /// Files aren't passed as arguments on macOS but sent to the running app as an Apple event.
+    pub fn listen(app_handle: &AppHandle) {
+        let handle = app_handle.clone();
+        let _ = app_handle.run_on_main_thread(move || unsafe {
+            let mut decl = match ClassDecl::new("DevPodOpenDocumentsHandler", class!(NSObject)) {
+                Some(decl) => decl,
+                None => return,
+            };
+            decl.add_method(
+                sel!(handleEvent:withReplyEvent:),
+                handle_open_documents as extern "C" fn(&Object, Sel, id, id),
+            );
+            decl.add_ivar::<usize>("appHandle");
+            let handler: id = msg_send![decl.register(), new];
+            (*handler).set_ivar("appHandle", Box::into_raw(Box::new(handle)) as usize);
+
+            let manager: id = msg_send![class!(NSAppleEventManager), sharedAppleEventManager];
+            let _: () = msg_send![
+                manager,
+                setEventHandler: handler
+                andSelector: sel!(handleEvent:withReplyEvent:)
+                forEventClass: CORE_EVENT_CLASS
+                andEventID: OPEN_DOCUMENTS_EVENT
+            ];
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_devcontainer_root() {
+        let root = Path::new("/home/user/project");
+        for (path, relative) in [
+            ("devcontainer.json", "devcontainer.json"),
+            (".devcontainer.json", ".devcontainer.json"),
+            (
+                ".devcontainer/devcontainer.json",
+                ".devcontainer/devcontainer.json",
+            ),
+            (
+                ".devcontainer/go/devcontainer.json",
+                ".devcontainer/go/devcontainer.json",
+            ),
+        ] {
+            assert_eq!(
+                devcontainer_source(&root.join(path)),
+                (root.to_path_buf(), relative.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn should_parse_workspace_definition() {
+        let path = Path::new("/home/user/project/dev.devpod");
+        assert_eq!(
+            parse_definition(path, "").unwrap(),
+            OpenWorkspaceMsg::new(
+                None,
+                None,
+                None,
+                Some("/home/user/project".to_string()),
+                None
+            )
+        );
+        assert_eq!(
+            parse_definition(
+                path,
+                "provider: docker\nsource: github.com/loft-sh/devpod\ndevcontainer: .devcontainer/go/devcontainer.json\n"
+            )
+            .unwrap(),
+            OpenWorkspaceMsg::new(
+                None,
+                Some("docker".to_string()),
+                None,
+                Some("github.com/loft-sh/devpod".to_string()),
+                Some(".devcontainer/go/devcontainer.json".to_string())
+            )
+        );
+    }
+}
@@ -0,0 +1,633 @@
+use crate::{
+    audit_log::{self, AuditCategory, AuditInitiator},
+    drag_drop::is_git_url,
+    process,
+    secrets::{self, SecretError, SecretKind},
+    ssh_config, AppHandle,
+};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::RwLock,
+};
+use thiserror::Error;
+use ts_rs::TS;
+use url::Url;
+
+const GIT_CREDENTIALS_FILE_NAME: &str = "git_credentials.json";
+// Hosts accept any username along with a token, this is the one GitHub documents for tokens
+const DEFAULT_USERNAME: &str = "x-access-token";
+// Bare sources, e.g. `ghcr.io/org/image`, are just as likely to be images on other hosts
+const KNOWN_GIT_HOSTS: [&str; 4] = ["github.com", "gitlab.com", "bitbucket.org", "dev.azure.com"];
+// Git must fail instead of asking for anything, there's no terminal to ask in
+const NON_INTERACTIVE_ENV: [(&str, &str); 3] = [
+    ("GIT_TERMINAL_PROMPT", "0"),
+    ("GCM_INTERACTIVE", "never"),
+    (
+        "GIT_SSH_COMMAND",
+        "ssh -o BatchMode=yes -o ConnectTimeout=10",
+    ),
+];
+
+lazy_static! {
+    // Reading the vault is audited, the variables are only derived again once credentials change
+    static ref ENVIRONMENT: RwLock<Option<HashMap<String, String>>> = RwLock::new(None);
+}
+
+#[derive(Error, Debug)]
+pub enum GitCredentialError {
+    #[error("invalid {0}")]
+    Invalid(&'static str),
+    #[error("unable to save git credentials")]
+    Persist(#[source] anyhow::Error),
+    #[error(transparent)]
+    Secret(#[from] SecretError),
+    #[error("unable to check access to the repository")]
+    Join(#[source] tauri::Error),
+}
+impl serde::Serialize for GitCredentialError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// What is kept next to the token in the vault, the token itself never leaves it for the UI.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct StoredCredential {
+    username: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct GitCredential {
+    host: String,
+    /// `x-access-token` is used if there is none
+    username: Option<String>,
+    has_token: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum GitCredentialSource {
+    /// A personal access token from the vault
+    Vault,
+    /// A credential helper of the git config, e.g. the git credential manager or the keychain
+    CredentialHelper,
+    /// The keys of the SSH agent or `~/.ssh`
+    Ssh,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum GitAccess {
+    Granted,
+    Unauthorized,
+    /// Hosts answer this for private repositories as well if the credentials can't see them
+    NotFound,
+    Unreachable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct GitAccessCheck {
+    host: String,
+    url: String,
+    access: GitAccess,
+    credential_source: GitCredentialSource,
+    /// What went wrong and how to fix it, unless access was granted
+    message: Option<String>,
+}
+
+/// The remote a workspace source clones from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Repository {
+    url: String,
+    host: String,
+    ssh: bool,
+}
+
+fn git_credentials_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(GIT_CREDENTIALS_FILE_NAME))
+}
+
+fn load(app_handle: &AppHandle) -> BTreeMap<String, StoredCredential> {
+    git_credentials_path(app_handle)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(
+    app_handle: &AppHandle,
+    credentials: &BTreeMap<String, StoredCredential>,
+) -> Result<(), GitCredentialError> {
+    let path = git_credentials_path(app_handle).ok_or_else(|| {
+        GitCredentialError::Persist(anyhow::anyhow!("unable to find app data dir"))
+    })?;
+    let content = serde_json::to_vec_pretty(credentials)
+        .map_err(|err| GitCredentialError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| GitCredentialError::Persist(err.into()))
+}
+
+/// Hosts end up in git config keys, e.g. `credential.https://github.com.helper`.
+fn is_valid_host(host: &str) -> bool {
+    !host.is_empty()
+        && !host.starts_with(['-', '.'])
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-'))
+}
+
+/// Everything the git credential protocol can carry on a single line.
+fn is_valid_username(username: &str) -> bool {
+    !username.is_empty() && !username.contains(|c: char| c.is_whitespace() || c.is_control())
+}
+
+/// The variables that make git answer credential requests for the hosts with the given usernames and tokens.
+///
+/// Entries are added through `GIT_CONFIG_COUNT` after the ones that are already set, `offset` is their count. The
+/// helpers of the git config are reset for these hosts, and the tokens only show up as variables, never in the config.
+fn credential_environment(
+    credentials: &[(String, String, String)],
+    offset: usize,
+) -> HashMap<String, String> {
+    let mut environment = HashMap::new();
+    if credentials.is_empty() {
+        return environment;
+    }
+
+    let mut index = offset;
+    for (i, (host, username, token)) in credentials.iter().enumerate() {
+        let key = format!("credential.https://{}.helper", host);
+        let username_var = format!("DEVPOD_GIT_USERNAME_{}", i);
+        let token_var = format!("DEVPOD_GIT_TOKEN_{}", i);
+        let helper = format!(
+            r#"!f() {{ test "$1" = get && printf 'username=%s\npassword=%s\n' "${}" "${}"; }}; f"#,
+            username_var, token_var
+        );
+        for value in [String::new(), helper] {
+            environment.insert(format!("GIT_CONFIG_KEY_{}", index), key.clone());
+            environment.insert(format!("GIT_CONFIG_VALUE_{}", index), value);
+            index += 1;
+        }
+        environment.insert(username_var, username.clone());
+        environment.insert(token_var, token.clone());
+    }
+    environment.insert("GIT_CONFIG_COUNT".to_string(), index.to_string());
+
+    environment
+}
+
+fn load_environment(app_handle: &AppHandle) -> HashMap<String, String> {
+    let credentials: Vec<_> = load(app_handle)
+        .into_iter()
+        .filter_map(|(host, credential)| {
+            let token = match secrets::get(SecretKind::GitToken, &host) {
+                Ok(token) => token?,
+                Err(err) => {
+                    warn!("Failed to read the token for {}: {}", host, err);
+                    return None;
+                }
+            };
+            let username = credential
+                .username
+                .unwrap_or_else(|| DEFAULT_USERNAME.to_string());
+
+            Some((host, username, token))
+        })
+        .collect();
+    let offset = env::var("GIT_CONFIG_COUNT")
+        .ok()
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(0);
+
+    credential_environment(&credentials, offset)
+}
+
+/// The variables that let git in CLI calls authenticate with the tokens of the vault.
+pub fn environment(app_handle: &AppHandle) -> HashMap<String, String> {
+    if let Some(environment) = ENVIRONMENT.read().unwrap().as_ref() {
+        return environment.clone();
+    }
+
+    let environment = load_environment(app_handle);
+    *ENVIRONMENT.write().unwrap() = Some(environment.clone());
+
+    environment
+}
+
+fn reset_environment() {
+    *ENVIRONMENT.write().unwrap() = None;
+}
+
+/// The remote of a workspace source, `None` for local folders and images.
+/// Sources can name a branch or commit after an `@`, e.g. `github.com/org/repo@main`.
+fn parse_repository(source: &str, known_hosts: &[&str]) -> Option<Repository> {
+    let source = source.trim();
+    let path_start = source.rfind('/')?;
+    let source = match source[path_start..].find('@') {
+        Some(at) => &source[..path_start + at],
+        None => source,
+    };
+
+    if !source.contains("://") {
+        if is_git_url(source) {
+            // scp-like, i.e. `git@github.com:org/repo.git`
+            let (_, rest) = source.split_once('@')?;
+            let (host, _) = rest.split_once(':')?;
+            return Some(Repository {
+                url: source.to_string(),
+                host: host.to_string(),
+                ssh: true,
+            });
+        }
+
+        let (host, _) = source.split_once('/')?;
+        if !known_hosts.contains(&host) && !source.ends_with(".git") {
+            return None;
+        }
+        return parse_repository(&format!("https://{}", source), known_hosts);
+    }
+
+    if !is_git_url(source) {
+        return None;
+    }
+    let url = Url::parse(source).ok()?;
+    let host = url.host_str()?.to_string();
+
+    Some(Repository {
+        url: source.to_string(),
+        host,
+        ssh: matches!(url.scheme(), "ssh" | "git+ssh"),
+    })
+}
+
+fn git(app_handle: &AppHandle) -> Command {
+    let mut command = Command::new("git");
+    command
+        .envs(process::environment(app_handle))
+        .envs(NON_INTERACTIVE_ENV)
+        .stdin(Stdio::null());
+
+    command
+}
+
+/// Whether a credential helper of the git config has credentials for `host`, without asking the user for any.
+fn has_helper_credentials(app_handle: &AppHandle, host: &str) -> bool {
+    let child = git(app_handle)
+        .args(["credential", "fill"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = write!(stdin, "protocol=https\nhost={}\n\n", host);
+    }
+
+    child.wait_with_output().is_ok_and(|output| {
+        output.status.success()
+            && String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .any(|line| line.starts_with("password="))
+    })
+}
+
+fn credential_source(
+    app_handle: &AppHandle,
+    repository: &Repository,
+) -> Result<GitCredentialSource, GitCredentialError> {
+    if repository.ssh {
+        let has_keys = env::var_os("SSH_AUTH_SOCK").is_some()
+            || ssh_config::ssh_dir().is_ok_and(|dir| {
+                ["id_ed25519", "id_ecdsa", "id_rsa"]
+                    .iter()
+                    .any(|key| dir.join(key).exists())
+            });
+        return Ok(if has_keys {
+            GitCredentialSource::Ssh
+        } else {
+            GitCredentialSource::None
+        });
+    }
+
+    if load(app_handle).contains_key(&repository.host)
+        && secrets::get(SecretKind::GitToken, &repository.host)?.is_some()
+    {
+        return Ok(GitCredentialSource::Vault);
+    }
+    if has_helper_credentials(app_handle, &repository.host) {
+        return Ok(GitCredentialSource::CredentialHelper);
+    }
+
+    Ok(GitCredentialSource::None)
+}
+
+/// Sorts the error of `git ls-remote` into what the user can do about it.
+fn classify(stderr: &str) -> GitAccess {
+    let stderr = stderr.to_lowercase();
+    let unauthorized = [
+        "could not read username",
+        "could not read password",
+        "terminal prompts disabled",
+        "authentication failed",
+        "invalid username or password",
+        "permission denied",
+        "access denied",
+        "returned error: 401",
+        "returned error: 403",
+    ];
+    let not_found = [
+        "repository not found",
+        "does not appear to be a git repository",
+        "returned error: 404",
+        "not found",
+    ];
+
+    if unauthorized.iter().any(|pattern| stderr.contains(pattern)) {
+        GitAccess::Unauthorized
+    } else if not_found.iter().any(|pattern| stderr.contains(pattern)) {
+        GitAccess::NotFound
+    } else {
+        GitAccess::Unreachable
+    }
+}
+
+fn message(
+    repository: &Repository,
+    access: GitAccess,
+    source: GitCredentialSource,
+    detail: &str,
+) -> Option<String> {
+    let Repository { url, host, .. } = repository;
+    let credentials = match source {
+        GitCredentialSource::Vault => format!("the personal access token for {}", host),
+        GitCredentialSource::CredentialHelper => {
+            "the credentials of your git credential helper".to_string()
+        }
+        GitCredentialSource::Ssh => "your SSH keys".to_string(),
+        GitCredentialSource::None => "no credentials".to_string(),
+    };
+    let fix = match source {
+        GitCredentialSource::Ssh => format!("add one of your SSH keys to your account on {}", host),
+        GitCredentialSource::Vault => format!(
+            "make sure the token for {} hasn't expired and can read the repository",
+            host
+        ),
+        _ => format!(
+            "add a personal access token for {} in the settings or sign in with a git credential helper",
+            host
+        ),
+    };
+
+    match access {
+        GitAccess::Granted => None,
+        GitAccess::Unauthorized => Some(format!(
+            "Access to {} was denied with {}, {}",
+            url, credentials, fix
+        )),
+        GitAccess::NotFound => Some(format!(
+            "{} doesn't exist or can't be seen with {}, {}",
+            url, credentials, fix
+        )),
+        GitAccess::Unreachable => Some(format!("Unable to reach {}: {}", host, detail)),
+    }
+}
+
+fn check_access(
+    app_handle: &AppHandle,
+    repository: Repository,
+) -> Result<GitAccessCheck, GitCredentialError> {
+    let credential_source = credential_source(app_handle, &repository)?;
+    // Aborts transfers that stall instead of waiting for the OS to give up on the connection
+    let output = git(app_handle)
+        .args([
+            "-c",
+            "http.lowSpeedLimit=1000",
+            "-c",
+            "http.lowSpeedTime=10",
+        ])
+        .args(["ls-remote", "--exit-code", &repository.url, "HEAD"])
+        .stdout(Stdio::null())
+        .output();
+
+    let (access, detail) = match output {
+        // `--exit-code` exits with 2 if there's no HEAD, i.e. for empty repositories
+        Ok(output) if output.status.success() || output.status.code() == Some(2) => {
+            (GitAccess::Granted, String::new())
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            (classify(&stderr), stderr)
+        }
+        Err(err) => (
+            GitAccess::Unreachable,
+            format!("unable to run git: {}", err),
+        ),
+    };
+    info!(
+        "Access to {} with {:?} credentials: {:?}",
+        repository.host, credential_source, access
+    );
+    let message = message(&repository, access, credential_source, &detail);
+
+    Ok(GitAccessCheck {
+        host: repository.host,
+        url: repository.url,
+        access,
+        credential_source,
+        message,
+    })
+}
+
+#[tauri::command]
+pub fn get_git_credentials(
+    app_handle: AppHandle,
+) -> Result<Vec<GitCredential>, GitCredentialError> {
+    load(&app_handle)
+        .into_iter()
+        .map(|(host, credential)| -> Result<_, GitCredentialError> {
+            let has_token = secrets::get(SecretKind::GitToken, &host)?.is_some();
+            Ok(GitCredential {
+                host,
+                username: credential.username,
+                has_token,
+            })
+        })
+        .collect()
+}
+
+/// Stores the personal access token for `host`, CLI calls authenticate with it from then on.
+#[tauri::command]
+pub fn set_git_credential(
+    app_handle: AppHandle,
+    host: String,
+    username: Option<String>,
+    token: String,
+) -> Result<(), GitCredentialError> {
+    let host = host.trim().to_lowercase();
+    if !is_valid_host(&host) {
+        return Err(GitCredentialError::Invalid("host"));
+    }
+    let username = username.filter(|username| !username.is_empty());
+    if username
+        .as_deref()
+        .is_some_and(|username| !is_valid_username(username))
+    {
+        return Err(GitCredentialError::Invalid("username"));
+    }
+    if token.is_empty() || token.contains(['\n', '\r']) {
+        return Err(GitCredentialError::Invalid("token"));
+    }
+
+    secrets::set(SecretKind::GitToken, &host, &token)?;
+    let mut credentials = load(&app_handle);
+    credentials.insert(host.clone(), StoredCredential { username });
+    save(&app_handle, &credentials)?;
+    reset_environment();
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Credentials,
+        "set git credential",
+        Some(&host),
+    );
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_git_credential(
+    app_handle: AppHandle,
+    host: String,
+) -> Result<(), GitCredentialError> {
+    secrets::delete(SecretKind::GitToken, &host)?;
+    let mut credentials = load(&app_handle);
+    if credentials.remove(&host).is_some() {
+        save(&app_handle, &credentials)?;
+    }
+    reset_environment();
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Credentials,
+        "delete git credential",
+        Some(&host),
+    );
+
+    Ok(())
+}
+
+/// Checks whether the repository of a workspace source can be cloned before the workspace is created, `None` if the
+/// source isn't a git repository.
+#[tauri::command]
+pub async fn check_git_access(
+    app_handle: AppHandle,
+    source: String,
+) -> Result<Option<GitAccessCheck>, GitCredentialError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if Path::new(&source).exists() {
+            return Ok(None);
+        }
+        let stored_hosts: Vec<String> = load(&app_handle).into_keys().collect();
+        let known_hosts: Vec<&str> = KNOWN_GIT_HOSTS
+            .into_iter()
+            .chain(stored_hosts.iter().map(String::as_str))
+            .collect();
+        let Some(repository) = parse_repository(&source, &known_hosts) else {
+            return Ok(None);
+        };
+
+        check_access(&app_handle, repository).map(Some)
+    })
+    .await
+    .map_err(GitCredentialError::Join)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_repository_sources() {
+        let repository = |url: &str, host: &str, ssh: bool| {
+            Some(Repository {
+                url: url.to_string(),
+                host: host.to_string(),
+                ssh,
+            })
+        };
+
+        assert_eq!(
+            parse_repository("github.com/loft-sh/devpod@main", &KNOWN_GIT_HOSTS),
+            repository("https://github.com/loft-sh/devpod", "github.com", false)
+        );
+        assert_eq!(
+            parse_repository("git@gitlab.example.com:team/app.git", &KNOWN_GIT_HOSTS),
+            repository(
+                "git@gitlab.example.com:team/app.git",
+                "gitlab.example.com",
+                true
+            )
+        );
+        assert_eq!(
+            parse_repository("https://git.example.com/team/app.git", &KNOWN_GIT_HOSTS),
+            repository(
+                "https://git.example.com/team/app.git",
+                "git.example.com",
+                false
+            )
+        );
+        assert_eq!(
+            parse_repository("ghcr.io/team/image", &KNOWN_GIT_HOSTS),
+            None
+        );
+        assert_eq!(
+            parse_repository("/home/me/projects/app", &KNOWN_GIT_HOSTS),
+            None
+        );
+        assert_eq!(
+            classify("fatal: could not read Username for 'https://github.com': terminal prompts disabled"),
+            GitAccess::Unauthorized
+        );
+        assert_eq!(
+            classify("remote: Repository not found.\nfatal: repository 'https://github.com/a/b/' not found"),
+            GitAccess::NotFound
+        );
+
+        let environment = credential_environment(
+            &[(
+                "github.com".to_string(),
+                DEFAULT_USERNAME.to_string(),
+                "ghp_secret".to_string(),
+            )],
+            1,
+        );
+        assert_eq!(environment["GIT_CONFIG_COUNT"], "3");
+        assert_eq!(
+            environment["GIT_CONFIG_KEY_1"],
+            "credential.https://github.com.helper"
+        );
+        assert_eq!(environment["GIT_CONFIG_VALUE_1"], "");
+        assert!(!environment["GIT_CONFIG_VALUE_2"].contains("ghp_secret"));
+        assert_eq!(environment["DEVPOD_GIT_TOKEN_0"], "ghp_secret");
+    }
+}
@@ -0,0 +1,518 @@
+use crate::devcontainer::CONFIG_PATHS;
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::{collections::BTreeMap, fs, io, path::Path};
+use thiserror::Error;
+use ts_rs::TS;
+
+const GITPOD_FILE_NAME: &str = ".gitpod.yml";
+// Next to the configs of the repository instead of replacing them, relative to the workspace's folder
+const IMPORTED_CONFIG_PATH: &str = ".devcontainer/gitpod/devcontainer.json";
+// Paths in `.gitpod.yml` are relative to the repository, the generated config is two folders down
+const REPOSITORY_ROOT: &str = "../..";
+// What Gitpod starts workspaces without an `image` from
+const DEFAULT_IMAGE: &str = "gitpod/workspace-full";
+
+#[derive(Error, Debug)]
+pub enum GitpodError {
+    #[error("unable to read .gitpod.yml")]
+    Read(#[source] io::Error),
+    #[error("unable to parse .gitpod.yml: {0}")]
+    Parse(#[source] serde_yaml::Error),
+    #[error("unable to write the imported devcontainer config")]
+    Write(#[source] io::Error),
+}
+impl serde::Serialize for GitpodError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitpodConfig {
+    image: Option<GitpodImage>,
+    #[serde(default)]
+    tasks: Vec<GitpodTask>,
+    #[serde(default)]
+    ports: Vec<GitpodPort>,
+    vscode: Option<GitpodVscode>,
+    #[serde(default)]
+    git_config: BTreeMap<String, serde_yaml::Value>,
+    /// Everything without a counterpart, reported as untranslated
+    #[serde(flatten)]
+    other: BTreeMap<String, serde_yaml::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum GitpodImage {
+    Name(String),
+    Dockerfile {
+        file: String,
+        context: Option<String>,
+    },
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitpodTask {
+    name: Option<String>,
+    /// Runs on every start, before `init` and `command`
+    before: Option<String>,
+    /// Runs once when the workspace is created
+    init: Option<String>,
+    /// Runs on every start, usually long running, e.g. a dev server
+    command: Option<String>,
+    #[serde(default)]
+    env: BTreeMap<String, serde_yaml::Value>,
+    #[serde(flatten)]
+    other: BTreeMap<String, serde_yaml::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitpodPort {
+    /// A port or a range like `3000-3999`
+    port: serde_yaml::Value,
+    on_open: Option<String>,
+    name: Option<String>,
+    protocol: Option<String>,
+    #[serde(flatten)]
+    other: BTreeMap<String, serde_yaml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct GitpodVscode {
+    #[serde(default)]
+    extensions: Vec<String>,
+}
+
+/// A setting from `.gitpod.yml` that isn't part of the imported config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct GitpodUntranslated {
+    /// e.g. `tasks[0].openMode`
+    path: String,
+    reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct GitpodImport {
+    /// Where the imported config has been written to, relative to the workspace's folder
+    devcontainer_path: String,
+    untranslated: Vec<GitpodUntranslated>,
+}
+
+#[derive(Debug, Default)]
+struct Conversion {
+    config: Map<String, Value>,
+    untranslated: Vec<GitpodUntranslated>,
+}
+
+impl Conversion {
+    fn report(&mut self, path: impl Into<String>, reason: &str) {
+        self.untranslated.push(GitpodUntranslated {
+            path: path.into(),
+            reason: reason.to_string(),
+        });
+    }
+
+    fn command(&mut self, lifecycle: &str, name: &str, command: String) {
+        let commands = self
+            .config
+            .entry(lifecycle)
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(commands) = commands {
+            commands.insert(name.to_string(), Value::String(command));
+        }
+    }
+}
+
+fn reason(key: &str) -> &'static str {
+    match key {
+        "openMode" | "openIn" => "DevPod doesn't open terminals for tasks",
+        "prebuild" | "github" => "Prebuilds are configured with the prebuild repository instead",
+        "workspaceLocation" | "checkoutLocation" => {
+            "The workspace opens in the folder of the repository"
+        }
+        "additionalRepositories" | "mainConfiguration" => {
+            "Workspaces are created from a single repository"
+        }
+        "visibility" => "Ports are forwarded to localhost only",
+        "jetbrains" => {
+            "JetBrains IDE settings aren't supported, use the plugins of the IDE instead"
+        }
+        _ => "There's no devcontainer equivalent",
+    }
+}
+
+// Scalars of any type are valid values, e.g. `PORT: 3000`
+fn scalar(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(value) => Some(value.clone()),
+        serde_yaml::Value::Number(value) => Some(value.to_string()),
+        serde_yaml::Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn join_scripts<'a>(scripts: impl IntoIterator<Item = &'a Option<String>>) -> Option<String> {
+    let scripts = scripts
+        .into_iter()
+        .flatten()
+        .map(|script| script.trim())
+        .filter(|script| !script.is_empty())
+        .collect::<Vec<_>>();
+
+    (!scripts.is_empty()).then(|| scripts.join("\n"))
+}
+
+fn convert_image(image: Option<&GitpodImage>, conversion: &mut Conversion) {
+    match image {
+        Some(GitpodImage::Name(name)) => {
+            conversion.config.insert("image".to_string(), json!(name));
+        }
+        Some(GitpodImage::Dockerfile { file, context }) => {
+            let context = match context.as_deref().map(|context| context.trim_matches('/')) {
+                Some("") | Some(".") | None => REPOSITORY_ROOT.to_string(),
+                Some(context) => format!("{}/{}", REPOSITORY_ROOT, context),
+            };
+            conversion.config.insert(
+                "build".to_string(),
+                json!({
+                    "dockerfile": format!("{}/{}", REPOSITORY_ROOT, file.trim_start_matches("./")),
+                    "context": context,
+                }),
+            );
+        }
+        None => {
+            conversion
+                .config
+                .insert("image".to_string(), json!(DEFAULT_IMAGE));
+        }
+    }
+}
+
+/// Gitpod runs the tasks side by side in terminals, they become named lifecycle commands which run in parallel as well.
+/// `command` usually doesn't exit, it's started in the background so that it doesn't hold up the workspace.
+fn convert_tasks(tasks: &[GitpodTask], conversion: &mut Conversion) {
+    let mut env = Map::new();
+    for (i, task) in tasks.iter().enumerate() {
+        let path = format!("tasks[{}]", i);
+        let name = match task.name.as_deref().map(str::trim) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => format!("task-{}", i),
+        };
+        // Names don't have to be unique in Gitpod
+        let name = if tasks[..i]
+            .iter()
+            .any(|other| other.name.as_deref().map(str::trim) == Some(name.as_str()))
+        {
+            format!("{}-{}", name, i)
+        } else {
+            name
+        };
+
+        if let Some(script) = join_scripts([&task.before, &task.init]) {
+            conversion.command("onCreateCommand", &name, script);
+        }
+        if let Some(command) = &task.command {
+            let script = join_scripts([&task.before, &Some(command.clone())]).unwrap_or_default();
+            let log_file = format!(
+                "/tmp/gitpod-{}.log",
+                name.replace(|c: char| !c.is_ascii_alphanumeric(), "-")
+            );
+            conversion.command(
+                "postStartCommand",
+                &name,
+                format!("nohup sh -c {} > {} 2>&1 &", shell_quote(&script), log_file),
+            );
+        }
+
+        for (key, value) in &task.env {
+            let Some(value) = scalar(value) else {
+                conversion.report(
+                    format!("{}.env.{}", path, key),
+                    "Only plain values are supported",
+                );
+                continue;
+            };
+            match env.get(key) {
+                Some(existing) if existing != &json!(value) => conversion.report(
+                    format!("{}.env.{}", path, key),
+                    "Another task sets it to a different value, environment variables apply to every task",
+                ),
+                _ => {
+                    env.insert(key.clone(), json!(value));
+                }
+            }
+        }
+        for key in task.other.keys() {
+            conversion.report(format!("{}.{}", path, key), reason(key));
+        }
+    }
+
+    if !env.is_empty() {
+        conversion
+            .config
+            .insert("remoteEnv".to_string(), Value::Object(env));
+    }
+}
+
+fn convert_ports(ports: &[GitpodPort], conversion: &mut Conversion) {
+    let mut forward_ports = vec![];
+    let mut attributes = Map::new();
+    for (i, port) in ports.iter().enumerate() {
+        let path = format!("ports[{}]", i);
+        let Some(key) = scalar(&port.port) else {
+            conversion.report(format!("{}.port", path), "Not a port or a range of ports");
+            continue;
+        };
+        // Ranges only get attributes, there's no forwarding a range
+        if let Ok(number) = key.parse::<u16>() {
+            forward_ports.push(json!(number));
+        }
+
+        let mut port_attributes = Map::new();
+        if let Some(name) = &port.name {
+            port_attributes.insert("label".to_string(), json!(name));
+        }
+        if let Some(protocol) = &port.protocol {
+            port_attributes.insert("protocol".to_string(), json!(protocol));
+        }
+        if let Some(on_open) = &port.on_open {
+            let on_auto_forward = match on_open.as_str() {
+                "open-browser" => Some("openBrowser"),
+                "open-preview" => Some("openPreview"),
+                "notify" => Some("notify"),
+                "ignore" => Some("silent"),
+                "ignore-completely" => Some("ignore"),
+                _ => None,
+            };
+            match on_auto_forward {
+                Some(value) => {
+                    port_attributes.insert("onAutoForward".to_string(), json!(value));
+                }
+                None => conversion.report(format!("{}.onOpen", path), "Unknown value"),
+            }
+        }
+        for key in port.other.keys() {
+            conversion.report(format!("{}.{}", path, key), reason(key));
+        }
+
+        if !port_attributes.is_empty() {
+            attributes.insert(key, Value::Object(port_attributes));
+        }
+    }
+
+    if !forward_ports.is_empty() {
+        conversion
+            .config
+            .insert("forwardPorts".to_string(), Value::Array(forward_ports));
+    }
+    if !attributes.is_empty() {
+        conversion
+            .config
+            .insert("portsAttributes".to_string(), Value::Object(attributes));
+    }
+}
+
+fn convert(config: &GitpodConfig) -> Conversion {
+    let mut conversion = Conversion::default();
+    conversion
+        .config
+        .insert("name".to_string(), json!("Imported from Gitpod"));
+
+    convert_image(config.image.as_ref(), &mut conversion);
+    convert_tasks(&config.tasks, &mut conversion);
+    convert_ports(&config.ports, &mut conversion);
+
+    let mut git_config = vec![];
+    for (key, value) in &config.git_config {
+        match scalar(value) {
+            Some(value) => git_config.push(format!(
+                "git config --global {} {}",
+                shell_quote(key),
+                shell_quote(&value)
+            )),
+            None => conversion.report(
+                format!("gitConfig.{}", key),
+                "Only plain values are supported",
+            ),
+        }
+    }
+    if !git_config.is_empty() {
+        conversion.command("onCreateCommand", "gitConfig", git_config.join("\n"));
+    }
+
+    let extensions = config
+        .vscode
+        .as_ref()
+        .map(|vscode| vscode.extensions.as_slice())
+        .unwrap_or_default();
+    let mut marketplace_extensions = vec![];
+    for (i, extension) in extensions.iter().enumerate() {
+        if extension.contains("://") {
+            conversion.report(
+                format!("vscode.extensions[{}]", i),
+                "Extensions can only be installed from the marketplace",
+            );
+        } else {
+            marketplace_extensions.push(json!(extension));
+        }
+    }
+    if !marketplace_extensions.is_empty() {
+        conversion.config.insert(
+            "customizations".to_string(),
+            json!({ "vscode": { "extensions": marketplace_extensions } }),
+        );
+    }
+
+    for key in config.other.keys() {
+        conversion.report(key.clone(), reason(key));
+    }
+
+    conversion
+}
+
+fn parse(content: &str) -> Result<GitpodConfig, GitpodError> {
+    if content.trim().is_empty() {
+        return Ok(GitpodConfig::default());
+    }
+
+    serde_yaml::from_str(content).map_err(GitpodError::Parse)
+}
+
+/// Converts the `.gitpod.yml` of a local folder into a devcontainer config next to the ones of the repository.
+/// `None` if there's nothing to import, i.e. the folder has no `.gitpod.yml` or a devcontainer config of its own.
+pub fn import(folder: &Path) -> Result<Option<GitpodImport>, GitpodError> {
+    if CONFIG_PATHS.iter().any(|path| folder.join(path).is_file()) {
+        return Ok(None);
+    }
+    let content = match fs::read_to_string(folder.join(GITPOD_FILE_NAME)) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(GitpodError::Read(err)),
+    };
+
+    let conversion = convert(&parse(&content)?);
+    let content = serde_json::to_string_pretty(&conversion.config)
+        .map_err(|err| GitpodError::Write(err.into()))?;
+    let path = folder.join(IMPORTED_CONFIG_PATH);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(GitpodError::Write)?;
+    }
+    fs::write(
+        &path,
+        format!(
+            "// Imported from {} by DevPod\n{}\n",
+            GITPOD_FILE_NAME, content
+        ),
+    )
+    .map_err(GitpodError::Write)?;
+    info!(
+        "Imported {} of {}, {} setting(s) not translated",
+        GITPOD_FILE_NAME,
+        folder.display(),
+        conversion.untranslated.len()
+    );
+
+    Ok(Some(GitpodImport {
+        devcontainer_path: IMPORTED_CONFIG_PATH.to_string(),
+        untranslated: conversion.untranslated,
+    }))
+}
+
+#[tauri::command]
+pub async fn import_gitpod_workspace(folder: String) -> Result<Option<GitpodImport>, GitpodError> {
+    import(Path::new(&folder))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_gitpod_config() {
+        let config = parse(
+            r#"
+image:
+  file: .gitpod.Dockerfile
+tasks:
+  - name: app
+    before: export PATH=$PATH:./bin
+    init: npm install
+    command: npm run dev
+    openMode: split-right
+    env:
+      PORT: 3000
+  - init: make
+ports:
+  - port: 3000
+    onOpen: open-preview
+    visibility: public
+  - port: 5000-5010
+    onOpen: ignore
+vscode:
+  extensions:
+    - dbaeumer.vscode-eslint
+    - https://example.com/extension.vsix
+workspaceLocation: app
+"#,
+        )
+        .unwrap();
+        let conversion = convert(&config);
+        let devcontainer = Value::Object(conversion.config);
+
+        assert_eq!(
+            devcontainer["build"],
+            json!({ "dockerfile": "../../.gitpod.Dockerfile", "context": "../.." })
+        );
+        assert_eq!(
+            devcontainer["onCreateCommand"],
+            json!({ "app": "export PATH=$PATH:./bin\nnpm install", "task-1": "make" })
+        );
+        assert_eq!(
+            devcontainer["postStartCommand"]["app"],
+            "nohup sh -c 'export PATH=$PATH:./bin\nnpm run dev' > /tmp/gitpod-app.log 2>&1 &"
+        );
+        assert_eq!(devcontainer["remoteEnv"], json!({ "PORT": "3000" }));
+        assert_eq!(devcontainer["forwardPorts"], json!([3000]));
+        assert_eq!(
+            devcontainer["portsAttributes"],
+            json!({
+                "3000": { "onAutoForward": "openPreview" },
+                "5000-5010": { "onAutoForward": "silent" },
+            })
+        );
+        assert_eq!(
+            devcontainer["customizations"]["vscode"]["extensions"],
+            json!(["dbaeumer.vscode-eslint"])
+        );
+        assert_eq!(
+            conversion
+                .untranslated
+                .iter()
+                .map(|untranslated| untranslated.path.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "tasks[0].openMode",
+                "ports[0].visibility",
+                "vscode.extensions[1]",
+                "workspaceLocation"
+            ]
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    cli_cache::{self, CliCacheScope},
+    state_sync, util, workspaces, AppHandle,
+};
+use log::{debug, info, warn};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::{
+    collections::BTreeSet,
+    path::{Component, Path},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+// The CLI writes a handful of files per operation, they're handled as one change
+const DEBOUNCE: Duration = Duration::from_millis(500);
+// Files that are written continuously, e.g. logs, would hold the changes back forever otherwise
+const MAX_DELAY: Duration = Duration::from_secs(2);
+const CONFIG_FILE_NAME: &str = "config.yaml";
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Something the CLI changed in the DevPod home.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Change {
+    /// Contexts and their options, which everything else depends on
+    Config,
+    Workspace(String),
+    Provider(String),
+}
+
+/// What a path in the DevPod home belongs to, e.g. `contexts/default/workspaces/my-project/workspace.json`.
+fn classify(home: &Path, path: &Path) -> Option<Change> {
+    let mut components =
+        path.strip_prefix(home)
+            .ok()?
+            .components()
+            .map(|component| match component {
+                Component::Normal(name) => name.to_str(),
+                _ => None,
+            });
+
+    match components.next()?? {
+        CONFIG_FILE_NAME => Some(Change::Config),
+        "contexts" => {
+            let _context = components.next()??;
+            let kind = components.next()??;
+            let name = components.next()??.to_string();
+            match kind {
+                "workspaces" => Some(Change::Workspace(name)),
+                "providers" => Some(Change::Provider(name)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn changes(home: &Path, event: notify::Result<Event>) -> Vec<Change> {
+    match event {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => event
+            .paths
+            .iter()
+            .filter_map(|path| classify(home, path))
+            .collect(),
+        Ok(_) => vec![],
+        Err(err) => {
+            warn!("DevPod home watcher error: {}", err);
+            vec![]
+        }
+    }
+}
+
+/// Drops what the changes made stale and lists what they touched again.
+fn apply(app_handle: &AppHandle, changes: BTreeSet<Change>) {
+    debug!("DevPod home changed: {:?}", changes);
+    let mut refresh_workspaces = false;
+    let mut refresh_providers = false;
+    for change in &changes {
+        match change {
+            Change::Config => {
+                cli_cache::invalidate(&CliCacheScope::Cli);
+                refresh_workspaces = true;
+                refresh_providers = true;
+            }
+            Change::Workspace(id) => {
+                cli_cache::invalidate(&CliCacheScope::Workspace(id.clone()));
+                refresh_workspaces = true;
+            }
+            Change::Provider(name) => {
+                cli_cache::invalidate(&CliCacheScope::Provider(name.clone()));
+                refresh_providers = true;
+            }
+        }
+    }
+
+    if refresh_workspaces {
+        if let Err(err) = workspaces::refresh(app_handle) {
+            warn!("Failed to list workspaces: {}", err);
+        }
+    }
+    if refresh_providers {
+        if let Err(err) = state_sync::refresh_providers(app_handle) {
+            warn!("Failed to list providers: {}", err);
+        }
+    }
+}
+
+/// Whether changes to the DevPod home are picked up as they happen, polling can slow down while they are.
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Watches the DevPod home for changes made with the CLI, e.g. from a terminal. Without a home or a working watcher
+/// the app keeps polling at full speed.
+pub fn setup(app_handle: &AppHandle) {
+    let Some(home) = util::devpod_home() else {
+        warn!("Unable to find the DevPod home, not watching it");
+        return;
+    };
+
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = match notify::recommended_watcher(tx) {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                warn!("Failed to create DevPod home watcher: {}", err);
+                return;
+            }
+        };
+        if let Err(err) = watcher.watch(&home, RecursiveMode::Recursive) {
+            warn!("Failed to watch {:?}: {}", home, err);
+            return;
+        }
+        info!("Watching {:?} for changes", home);
+        ACTIVE.store(true, Ordering::Relaxed);
+
+        while let Ok(event) = rx.recv() {
+            let mut pending: BTreeSet<Change> = changes(&home, event).into_iter().collect();
+            let deadline = Instant::now() + MAX_DELAY;
+            while let Ok(event) =
+                rx.recv_timeout(DEBOUNCE.min(deadline.saturating_duration_since(Instant::now())))
+            {
+                pending.extend(changes(&home, event));
+            }
+            if !pending.is_empty() {
+                apply(&app_handle, pending);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_classify_changes_by_path() {
+        let home = PathBuf::from("/home/user/.devpod");
+        let classify = |path: &str| classify(&home, &home.join(path));
+
+        assert_eq!(classify("config.yaml"), Some(Change::Config));
+        assert_eq!(
+            classify("contexts/default/workspaces/my-project/workspace.json"),
+            Some(Change::Workspace("my-project".to_string()))
+        );
+        assert_eq!(
+            classify("contexts/default/providers/docker/provider.json"),
+            Some(Change::Provider("docker".to_string()))
+        );
+        assert_eq!(classify("contexts/default/workspaces"), None);
+        assert_eq!(classify("agent/devpod"), None);
+        // Joining an absolute path replaces the home
+        assert_eq!(classify("/tmp/config.yaml"), None);
+    }
+}
@@ -0,0 +1,492 @@
+use crate::{
+    ports::{self, PortForward},
+    relay,
+    settings::Settings,
+    AppHandle, AppState,
+};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::Manager;
+use thiserror::Error;
+use ts_rs::TS;
+
+// Browsers and most resolvers map `*.localhost` to the loopback interface (RFC 6761), no hosts file needed
+const HOSTNAME_SUFFIX: &str = ".devpod.localhost";
+const HOSTNAMES_FILE_NAME: &str = "workspace_hostnames.json";
+// Where most dev servers listen, configurable per workspace
+const DEFAULT_TARGET_PORT: u16 = 3000;
+const SETTINGS_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+const HEAD_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_HEAD_SIZE: usize = 64 * 1024;
+// Includes waiting for the workspace to come up
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const CONNECT_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+lazy_static! {
+    static ref ROUTES: Mutex<Vec<Route>> = Mutex::new(Vec::new());
+}
+
+/// Ports in the workspaces that their hostnames lead to, `<port>.<name>.devpod.localhost` picks one explicitly.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct WorkspaceHostnames {
+    ports: BTreeMap<String, u16>,
+}
+
+impl WorkspaceHostnames {
+    fn target_port(&self, workspace_id: &str) -> u16 {
+        self.ports
+            .get(workspace_id)
+            .copied()
+            .unwrap_or(DEFAULT_TARGET_PORT)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum HostnameError {
+    #[error("workspace hostnames are disabled")]
+    Disabled,
+    #[error("workspace {0} has no valid hostname")]
+    InvalidWorkspaceId(String),
+    #[error("unable to save workspace hostnames")]
+    Persist(#[source] anyhow::Error),
+}
+impl serde::Serialize for HostnameError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// A forward that requests for a workspace port go through, shared by all connections to it.
+struct Route {
+    workspace_id: String,
+    remote_port: u16,
+    local_port: u16,
+    forward: PortForward,
+    last_used: Instant,
+    /// Open connections, websockets can stay open for hours
+    active: Arc<AtomicUsize>,
+}
+
+struct Server {
+    port: u16,
+    stopped: Arc<AtomicBool>,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+fn hostnames_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(HOSTNAMES_FILE_NAME))
+}
+
+fn load(app_handle: &AppHandle) -> WorkspaceHostnames {
+    hostnames_path(app_handle)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(app_handle: &AppHandle, hostnames: &WorkspaceHostnames) -> Result<(), HostnameError> {
+    let path = hostnames_path(app_handle)
+        .ok_or_else(|| HostnameError::Persist(anyhow::anyhow!("unable to find app data dir")))?;
+    let content =
+        serde_json::to_vec_pretty(hostnames).map_err(|err| HostnameError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| HostnameError::Persist(err.into()))
+}
+
+fn is_hostname_label(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 63
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// The workspace and the explicit port, if any, of `[<sub>.][<port>.]<name>.devpod.localhost[:<port>]`.
+/// Subdomains are left to host-based routing in the workspace.
+fn parse_host(host: &str) -> Option<(String, Option<u16>)> {
+    let host = host.trim().to_ascii_lowercase();
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host.as_str(),
+    };
+    let name = host.trim_end_matches('.').strip_suffix(HOSTNAME_SUFFIX)?;
+
+    let mut labels = name.rsplit('.');
+    let workspace_id = labels.next().filter(|label| is_hostname_label(label))?;
+    let port = labels.next().and_then(|label| label.parse().ok());
+
+    Some((workspace_id.to_string(), port))
+}
+
+fn host_header(head: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(head)
+        .split("\r\n")
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("host"))
+        .map(|(_, value)| value.trim().to_string())
+}
+
+/// Reads up to the end of the request head, whatever follows is passed on as is.
+fn read_head(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let mut head = Vec::new();
+    let mut buffer = [0; 4096];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_HEAD_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request head too large",
+            ));
+        }
+        match stream.read(&mut buffer)? {
+            0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            read => head.extend_from_slice(&buffer[..read]),
+        }
+    }
+
+    Ok(head)
+}
+
+fn respond(stream: &mut TcpStream, status: &str, message: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        message.len(),
+        message
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+fn is_known_workspace(app_handle: &AppHandle, workspace_id: &str) -> bool {
+    let app_state = app_handle.state::<AppState>();
+    let workspaces = app_state.workspaces.lock().unwrap();
+
+    workspaces
+        .ids_with_context()
+        .into_iter()
+        .any(|(id, _)| id == workspace_id)
+}
+
+fn free_port() -> io::Result<u16> {
+    TcpListener::bind(("127.0.0.1", 0))?
+        .local_addr()
+        .map(|address| address.port())
+}
+
+/// The local port of the forward to `remote_port`, starts one if there is none yet and waits until it's connected.
+fn route(
+    app_handle: &AppHandle,
+    workspace_id: &str,
+    remote_port: u16,
+) -> Result<(u16, Arc<AtomicUsize>), String> {
+    let (local_port, forward, active) = {
+        let mut routes = ROUTES.lock().unwrap();
+        match routes
+            .iter_mut()
+            .find(|route| route.workspace_id == workspace_id && route.remote_port == remote_port)
+        {
+            Some(route) => {
+                route.last_used = Instant::now();
+                (
+                    route.local_port,
+                    route.forward.clone(),
+                    Arc::clone(&route.active),
+                )
+            }
+            None => {
+                if !is_known_workspace(app_handle, workspace_id) {
+                    return Err(format!("Workspace {} doesn't exist", workspace_id));
+                }
+                let local_port = free_port().map_err(|err| err.to_string())?;
+                let forward = ports::forward_transient(
+                    app_handle,
+                    workspace_id.to_string(),
+                    local_port,
+                    remote_port,
+                )
+                .map_err(|err| err.to_string())?;
+                info!(
+                    "Routing {}{} to port {}",
+                    workspace_id, HOSTNAME_SUFFIX, remote_port
+                );
+                let active = Arc::new(AtomicUsize::new(0));
+                routes.push(Route {
+                    workspace_id: workspace_id.to_string(),
+                    remote_port,
+                    local_port,
+                    forward: forward.clone(),
+                    last_used: Instant::now(),
+                    active: Arc::clone(&active),
+                });
+
+                (local_port, forward, active)
+            }
+        }
+    };
+
+    let started_at = Instant::now();
+    while !ports::is_connected(&forward) {
+        if started_at.elapsed() > CONNECT_TIMEOUT {
+            return Err(format!(
+                "Workspace {} isn't reachable, is it running?",
+                workspace_id
+            ));
+        }
+        thread::sleep(CONNECT_CHECK_INTERVAL);
+    }
+
+    Ok((local_port, active))
+}
+
+fn handle(app_handle: &AppHandle, mut client: TcpStream) {
+    // Accepted sockets inherit non-blocking mode on some platforms
+    if client.set_nonblocking(false).is_err()
+        || client.set_read_timeout(Some(HEAD_TIMEOUT)).is_err()
+    {
+        return;
+    }
+    let head = match read_head(&mut client) {
+        Ok(head) => head,
+        Err(_) => return respond(&mut client, "400 Bad Request", "Invalid request"),
+    };
+    let (workspace_id, port) = match host_header(&head).as_deref().and_then(parse_host) {
+        Some(host) => host,
+        None => {
+            return respond(
+                &mut client,
+                "404 Not Found",
+                &format!("Use <workspace>{} to reach a workspace", HOSTNAME_SUFFIX),
+            )
+        }
+    };
+    let remote_port = port.unwrap_or_else(|| load(app_handle).target_port(&workspace_id));
+
+    let (local_port, active) = match route(app_handle, &workspace_id, remote_port) {
+        Ok(route) => route,
+        Err(message) => return respond(&mut client, "502 Bad Gateway", &message),
+    };
+    let mut upstream = match TcpStream::connect(("127.0.0.1", local_port)) {
+        Ok(upstream) => upstream,
+        Err(err) => return respond(&mut client, "502 Bad Gateway", &err.to_string()),
+    };
+    if upstream.write_all(&head).is_err() || client.set_read_timeout(None).is_err() {
+        return;
+    }
+
+    active.fetch_add(1, Ordering::SeqCst);
+    if let Err(err) = relay::splice(client, upstream) {
+        warn!("Failed to relay request for {}: {}", workspace_id, err);
+    }
+    active.fetch_sub(1, Ordering::SeqCst);
+    if let Some(route) = ROUTES
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .find(|route| route.local_port == local_port)
+    {
+        route.last_used = Instant::now();
+    }
+}
+
+/// Listens on IPv4 and, where available, IPv6 loopback, `localhost` names resolve to either.
+fn serve(app_handle: &AppHandle, port: u16) -> io::Result<Server> {
+    let mut listeners = vec![TcpListener::bind(("127.0.0.1", port))?];
+    if let Ok(listener) = TcpListener::bind(("::1", port)) {
+        listeners.push(listener);
+    }
+    let stopped = Arc::new(AtomicBool::new(false));
+
+    for listener in listeners {
+        listener.set_nonblocking(true)?;
+        let app_handle = app_handle.clone();
+        let stopped = Arc::clone(&stopped);
+        thread::spawn(move || {
+            while !stopped.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((client, _)) => {
+                        let app_handle = app_handle.clone();
+                        thread::spawn(move || handle(&app_handle, client));
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_INTERVAL)
+                    }
+                    Err(err) => {
+                        warn!("Failed to accept hostname connection: {}", err);
+                        thread::sleep(ACCEPT_INTERVAL);
+                    }
+                }
+            }
+        });
+    }
+
+    Ok(Server { port, stopped })
+}
+
+/// Removes the forwards of routes that haven't been used for a while, or all of them.
+fn close_routes(app_handle: &AppHandle, all: bool) {
+    let closed: Vec<PortForward> = {
+        let mut routes = ROUTES.lock().unwrap();
+        let (closed, kept) = routes.drain(..).partition(|route: &Route| {
+            all || (route.active.load(Ordering::SeqCst) == 0
+                && route.last_used.elapsed() > IDLE_TIMEOUT)
+        });
+        *routes = kept;
+
+        closed.into_iter().map(|route| route.forward).collect()
+    };
+
+    for forward in closed {
+        if let Err(err) = ports::unforward_port(app_handle.clone(), forward) {
+            warn!("Failed to remove hostname forward: {}", err);
+        }
+    }
+}
+
+/// Starts or stops serving hostnames as the settings change and cleans up unused forwards.
+pub fn setup(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let mut server: Option<Server> = None;
+        // Only warn once about a port that is taken
+        let mut failed_port = None;
+        loop {
+            let port = Settings::workspace_hostnames_port(&app_handle);
+            let enabled = Settings::workspace_hostnames(&app_handle);
+            if !enabled || server.as_ref().is_some_and(|server| server.port != port) {
+                if server.take().is_some() {
+                    info!("Stopped serving workspace hostnames");
+                    close_routes(&app_handle, true);
+                }
+                failed_port = None;
+            }
+            if enabled && server.is_none() && failed_port != Some(port) {
+                match serve(&app_handle, port) {
+                    Ok(started) => {
+                        info!("Serving workspace hostnames on port {}", port);
+                        server = Some(started);
+                    }
+                    Err(err) => {
+                        warn!(
+                            "Failed to serve workspace hostnames on port {}: {}",
+                            port, err
+                        );
+                        failed_port = Some(port);
+                    }
+                }
+            }
+
+            close_routes(&app_handle, false);
+            thread::sleep(SETTINGS_CHECK_INTERVAL);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_workspace_hostnames(app_handle: AppHandle) -> WorkspaceHostnames {
+    load(&app_handle)
+}
+
+/// Sets the port the hostname of the workspace leads to, `None` resets it to the default.
+#[tauri::command]
+pub fn set_workspace_hostname_port(
+    app_handle: AppHandle,
+    workspace_id: String,
+    port: Option<u16>,
+) -> Result<WorkspaceHostnames, HostnameError> {
+    if !is_hostname_label(&workspace_id) {
+        return Err(HostnameError::InvalidWorkspaceId(workspace_id));
+    }
+    let mut hostnames = load(&app_handle);
+    match port {
+        Some(port) => hostnames.ports.insert(workspace_id, port),
+        None => hostnames.ports.remove(&workspace_id),
+    };
+    save(&app_handle, &hostnames)?;
+
+    Ok(hostnames)
+}
+
+/// `http://<name>.devpod.localhost:<port>` for the workspace.
+#[tauri::command]
+pub fn get_workspace_url(
+    app_handle: AppHandle,
+    workspace_id: String,
+) -> Result<String, HostnameError> {
+    if !Settings::workspace_hostnames(&app_handle) {
+        return Err(HostnameError::Disabled);
+    }
+    if !is_hostname_label(&workspace_id) {
+        return Err(HostnameError::InvalidWorkspaceId(workspace_id));
+    }
+
+    Ok(format!(
+        "http://{}{}:{}",
+        workspace_id,
+        HOSTNAME_SUFFIX,
+        Settings::workspace_hostnames_port(&app_handle)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_route_hostnames_to_workspaces() {
+        assert_eq!(
+            parse_host("my-project.devpod.localhost:8470"),
+            Some(("my-project".to_string(), None))
+        );
+        assert_eq!(
+            parse_host("API.My-Project.devpod.localhost"),
+            Some(("my-project".to_string(), None))
+        );
+        assert_eq!(
+            parse_host("api.5173.my-project.devpod.localhost:8470"),
+            Some(("my-project".to_string(), Some(5173)))
+        );
+        assert_eq!(parse_host("devpod.localhost"), None);
+        assert_eq!(parse_host("example.com"), None);
+    }
+
+    #[test]
+    fn should_read_host_header() {
+        let head =
+            b"GET / HTTP/1.1\r\nhost: web.my-project.devpod.localhost:8470\r\nAccept: */*\r\n\r\n";
+
+        assert_eq!(
+            host_header(head),
+            Some("web.my-project.devpod.localhost:8470".to_string())
+        );
+    }
+}
@@ -0,0 +1,434 @@
+use crate::{
+    commands::{workspace_ssh::WorkspaceSshCommand, DevpodCommandConfig},
+    debug_console, devcontainer, jetbrains_gateway, workspaces, AppHandle,
+};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use thiserror::Error;
+use ts_rs::TS;
+
+const EXTENSIONS_FILE_NAME: &str = "ide_extensions.json";
+// Whichever server the IDE has installed into the workspace
+const VSCODE_SERVERS: &str = "~/.vscode-server/bin/*/bin/code-server ~/.vscode-server-insiders/bin/*/bin/code-server-insiders ~/.openvscode-server/bin/openvscode-server";
+const JETBRAINS_SERVERS: &str = "~/.cache/JetBrains/RemoteDev/dist/*/bin/remote-dev-server.sh";
+const SERVER_MISSING: &str = "missing";
+
+/// VS Code extensions and JetBrains plugins by their marketplace ids, e.g. `golang.go` or `org.jetbrains.plugins.go`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ExtensionList {
+    #[serde(default)]
+    vscode: Vec<String>,
+    #[serde(default)]
+    jetbrains: Vec<String>,
+}
+
+impl ExtensionList {
+    fn extend(&mut self, other: &ExtensionList) {
+        for (list, other) in [
+            (&mut self.vscode, &other.vscode),
+            (&mut self.jetbrains, &other.jetbrains),
+        ] {
+            for id in other {
+                if !list.contains(id) {
+                    list.push(id.clone());
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct WorkspaceExtensions {
+    /// Name of a template whose extensions are installed as well
+    template: Option<String>,
+    #[serde(flatten)]
+    extensions: ExtensionList,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct IdeExtensions {
+    /// Lists shared between workspaces, `default` applies to workspaces without a template of their own
+    templates: BTreeMap<String, ExtensionList>,
+    workspaces: BTreeMap<String, WorkspaceExtensions>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ExtensionFailure {
+    id: String,
+    error: String,
+}
+
+/// What happened to the managed extensions when the IDE was opened.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ExtensionInstallReport {
+    installed: Vec<String>,
+    failed: Vec<ExtensionFailure>,
+    /// Listed in the devcontainer customizations too, the CLI installs them already
+    from_devcontainer: Vec<String>,
+}
+
+#[derive(Error, Debug)]
+pub enum IdeExtensionError {
+    #[error("invalid extension id {0}")]
+    InvalidId(String),
+    #[error("template {0} not found")]
+    TemplateNotFound(String),
+    #[error("{0} doesn't support managed extensions")]
+    Unsupported(String),
+    #[error("unable to save IDE extensions")]
+    Persist(#[source] anyhow::Error),
+}
+impl serde::Serialize for IdeExtensionError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IdeKind {
+    Vscode,
+    JetBrains,
+}
+
+impl IdeKind {
+    fn of(ide: &str) -> Option<Self> {
+        match ide {
+            "vscode" | "openvscode" => Some(Self::Vscode),
+            ide if jetbrains_gateway::product_code(ide).is_some() => Some(Self::JetBrains),
+            _ => None,
+        }
+    }
+
+    fn managed<'a>(&self, extensions: &'a ExtensionList) -> &'a [String] {
+        match self {
+            Self::Vscode => &extensions.vscode,
+            Self::JetBrains => &extensions.jetbrains,
+        }
+    }
+
+    fn devcontainer(&self, config: &serde_json::Value) -> Vec<String> {
+        match self {
+            Self::Vscode => devcontainer::customization_list(config, "vscode", "extensions"),
+            Self::JetBrains => devcontainer::customization_list(config, "jetbrains", "plugins"),
+        }
+    }
+
+    /// Prints `ok <id>` or `failed <id>` per extension, or `missing` if the IDE hasn't installed its server yet.
+    fn install_command(&self, workspace_id: &str, ids: &[&str]) -> String {
+        let (servers, install) = match self {
+            Self::Vscode => (VSCODE_SERVERS, "--install-extension".to_string()),
+            Self::JetBrains => (
+                JETBRAINS_SERVERS,
+                format!("installPlugins {}", workspaces::remote_folder(workspace_id)),
+            ),
+        };
+
+        format!(
+            "server=$(ls -d {servers} 2>/dev/null | tail -n 1); [ -n \"$server\" ] || {{ echo {missing}; exit 0; }}; for id in {ids}; do if \"$server\" {install} \"$id\" >/dev/null 2>&1; then echo \"ok $id\"; else echo \"failed $id\"; fi; done",
+            servers = servers,
+            missing = SERVER_MISSING,
+            ids = ids.join(" "),
+            install = install,
+        )
+    }
+}
+
+/// Marketplace ids with an optional `@<version>`, they end up unquoted in a shell command.
+fn is_extension_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '@'))
+}
+
+fn validate(extensions: &ExtensionList) -> Result<(), IdeExtensionError> {
+    match extensions
+        .vscode
+        .iter()
+        .chain(&extensions.jetbrains)
+        .find(|id| !is_extension_id(id))
+    {
+        Some(id) => Err(IdeExtensionError::InvalidId(id.clone())),
+        None => Ok(()),
+    }
+}
+
+fn extensions_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(EXTENSIONS_FILE_NAME))
+}
+
+fn load(app_handle: &AppHandle) -> IdeExtensions {
+    extensions_path(app_handle)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(app_handle: &AppHandle, extensions: &IdeExtensions) -> Result<(), IdeExtensionError> {
+    let path = extensions_path(app_handle).ok_or_else(|| {
+        IdeExtensionError::Persist(anyhow::anyhow!("unable to find app data dir"))
+    })?;
+    let content = serde_json::to_vec_pretty(extensions)
+        .map_err(|err| IdeExtensionError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| IdeExtensionError::Persist(err.into()))
+}
+
+impl IdeExtensions {
+    /// The workspace's own list merged with its template, or the `default` template.
+    fn resolve(&self, workspace_id: &str) -> ExtensionList {
+        let mut resolved = ExtensionList::default();
+        let workspace = self.workspaces.get(workspace_id);
+        let template = workspace
+            .and_then(|workspace| workspace.template.as_deref())
+            .unwrap_or("default");
+        if let Some(template) = self.templates.get(template) {
+            resolved.extend(template);
+        }
+        if let Some(workspace) = workspace {
+            resolved.extend(&workspace.extensions);
+        }
+
+        resolved
+    }
+}
+
+fn parse_install_output(
+    output: &str,
+    ids: &[&str],
+) -> Option<(Vec<String>, Vec<ExtensionFailure>)> {
+    if output.lines().any(|line| line.trim() == SERVER_MISSING) {
+        return None;
+    }
+
+    let mut installed = Vec::new();
+    let mut failed = Vec::new();
+    for id in ids {
+        if output
+            .lines()
+            .any(|line| line.trim() == format!("ok {}", id))
+        {
+            installed.push(id.to_string());
+        } else {
+            let error = if output
+                .lines()
+                .any(|line| line.trim() == format!("failed {}", id))
+            {
+                "installation failed"
+            } else {
+                "not attempted"
+            };
+            failed.push(ExtensionFailure {
+                id: id.to_string(),
+                error: error.to_string(),
+            });
+        }
+    }
+
+    Some((installed, failed))
+}
+
+fn install(
+    app_handle: &AppHandle,
+    workspace_id: &str,
+    kind: IdeKind,
+    managed: &[String],
+) -> ExtensionInstallReport {
+    let devcontainer_ids = devcontainer::read(app_handle, workspace_id)
+        .map(|config| kind.devcontainer(&config))
+        .unwrap_or_default();
+    let (from_devcontainer, pending): (Vec<&String>, Vec<&String>) = managed
+        .iter()
+        .partition(|id| devcontainer_ids.contains(*id));
+    let mut report = ExtensionInstallReport {
+        from_devcontainer: from_devcontainer.into_iter().cloned().collect(),
+        ..ExtensionInstallReport::default()
+    };
+    if pending.is_empty() {
+        return report;
+    }
+
+    let ids: Vec<&str> = pending.iter().map(|id| id.as_str()).collect();
+    let result = WorkspaceSshCommand::new(
+        workspace_id.to_string(),
+        kind.install_command(workspace_id, &ids),
+    )
+    .exec(app_handle)
+    .map_err(|err| err.to_string())
+    .and_then(|output| {
+        parse_install_output(&output, &ids)
+            .ok_or_else(|| "the IDE hasn't been set up in the workspace yet".to_string())
+    });
+    match result {
+        Ok((installed, failed)) => {
+            report.installed = installed;
+            report.failed = failed;
+        }
+        Err(error) => {
+            report.failed = ids
+                .into_iter()
+                .map(|id| ExtensionFailure {
+                    id: id.to_string(),
+                    error: error.clone(),
+                })
+                .collect();
+        }
+    }
+
+    report
+}
+
+#[tauri::command]
+pub fn get_ide_extensions(app_handle: AppHandle) -> IdeExtensions {
+    load(&app_handle)
+}
+
+/// Sets or removes (`None`) a template.
+#[tauri::command]
+pub fn set_ide_extension_template(
+    app_handle: AppHandle,
+    name: String,
+    extensions: Option<ExtensionList>,
+) -> Result<IdeExtensions, IdeExtensionError> {
+    let mut ide_extensions = load(&app_handle);
+    match extensions {
+        Some(extensions) => {
+            validate(&extensions)?;
+            ide_extensions.templates.insert(name, extensions);
+        }
+        None => {
+            ide_extensions.templates.remove(&name);
+        }
+    }
+    save(&app_handle, &ide_extensions)?;
+
+    Ok(ide_extensions)
+}
+
+/// Sets or removes (`None`) the extensions of a workspace.
+#[tauri::command]
+pub fn set_workspace_ide_extensions(
+    app_handle: AppHandle,
+    workspace_id: String,
+    extensions: Option<WorkspaceExtensions>,
+) -> Result<IdeExtensions, IdeExtensionError> {
+    let mut ide_extensions = load(&app_handle);
+    match extensions {
+        Some(extensions) => {
+            validate(&extensions.extensions)?;
+            if let Some(template) = &extensions.template {
+                if !ide_extensions.templates.contains_key(template) {
+                    return Err(IdeExtensionError::TemplateNotFound(template.clone()));
+                }
+            }
+            ide_extensions.workspaces.insert(workspace_id, extensions);
+        }
+        None => {
+            ide_extensions.workspaces.remove(&workspace_id);
+        }
+    }
+    save(&app_handle, &ide_extensions)?;
+
+    Ok(ide_extensions)
+}
+
+/// Installs the managed extensions of the workspace into `ide`, meant to be called once the IDE has been opened.
+#[tauri::command]
+pub async fn install_ide_extensions(
+    app_handle: AppHandle,
+    workspace_id: String,
+    ide: String,
+) -> Result<ExtensionInstallReport, IdeExtensionError> {
+    let kind = IdeKind::of(&ide).ok_or_else(|| IdeExtensionError::Unsupported(ide.clone()))?;
+    let managed = kind
+        .managed(&load(&app_handle).resolve(&workspace_id))
+        .to_vec();
+    if managed.is_empty() {
+        return Ok(ExtensionInstallReport::default());
+    }
+
+    let _task = debug_console::track_task("IDE extensions", Some(workspace_id.clone()));
+    let report = install(&app_handle, &workspace_id, kind, &managed);
+    if report.failed.is_empty() {
+        info!(
+            "Installed {} extensions into {} of {}",
+            report.installed.len(),
+            ide,
+            workspace_id
+        );
+    } else {
+        warn!(
+            "Failed to install {} extensions into {} of {}",
+            report.failed.len(),
+            ide,
+            workspace_id
+        );
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(vscode: &[&str]) -> ExtensionList {
+        ExtensionList {
+            vscode: vscode.iter().map(|id| id.to_string()).collect(),
+            jetbrains: vec![],
+        }
+    }
+
+    #[test]
+    fn should_merge_template_and_workspace() {
+        let extensions = IdeExtensions {
+            templates: BTreeMap::from([
+                ("default".to_string(), list(&["golang.go"])),
+                ("web".to_string(), list(&["esbenp.prettier-vscode"])),
+            ]),
+            workspaces: BTreeMap::from([(
+                "my-project".to_string(),
+                WorkspaceExtensions {
+                    template: Some("web".to_string()),
+                    extensions: list(&["esbenp.prettier-vscode", "eamodio.gitlens"]),
+                },
+            )]),
+        };
+
+        assert_eq!(
+            extensions.resolve("my-project").vscode,
+            vec!["esbenp.prettier-vscode", "eamodio.gitlens"]
+        );
+        assert_eq!(extensions.resolve("other").vscode, vec!["golang.go"]);
+    }
+
+    #[test]
+    fn should_report_failed_installs() {
+        let ids = ["golang.go", "eamodio.gitlens", "ms-python.python"];
+        let (installed, failed) =
+            parse_install_output("ok golang.go\nfailed eamodio.gitlens\n", &ids).unwrap();
+
+        assert_eq!(installed, vec!["golang.go"]);
+        assert_eq!(failed.len(), 2);
+        assert_eq!(failed[0].error, "installation failed");
+        assert_eq!(failed[1].error, "not attempted");
+        assert!(parse_install_output("missing\n", &ids).is_none());
+    }
+}
@@ -0,0 +1,302 @@
+use crate::{jetbrains_gateway, AppHandle};
+use anyhow::Context;
+use log::info;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+const VERSIONS_FILE_NAME: &str = "ide_versions.json";
+const JETBRAINS_RELEASES_URL: &str = "https://data.services.jetbrains.com/products/releases";
+const OPENVSCODE_RELEASES_URL: &str =
+    "https://api.github.com/repos/gitpod-io/openvscode-server/releases/tags";
+// The IDE option the CLI downloads a specific release of the server with
+const VERSION_OPTION: &str = "VERSION";
+
+/// A release of the IDE's backend, by version like `2023.3.2` or for JetBrains IDEs by build like `233.13135.104`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PinnedIdeVersion {
+    ide: String,
+    version: String,
+}
+
+/// The release a pinned version refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ResolvedIdeVersion {
+    version: String,
+    build: Option<String>,
+    /// To pass along to `devpod up`, e.g. `VERSION=2023.3.2`
+    ide_options: Vec<String>,
+}
+
+impl ResolvedIdeVersion {
+    pub fn build(&self) -> Option<&str> {
+        self.build.as_deref()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct JetBrainsRelease {
+    version: String,
+    build: String,
+}
+
+#[derive(Error, Debug)]
+pub enum IdeVersionError {
+    #[error("invalid version {0}")]
+    InvalidVersion(String),
+    #[error("{0} doesn't support pinned versions")]
+    Unsupported(String),
+    #[error("{ide} {version} is not available, pin another version or remove the pin")]
+    Unavailable { ide: String, version: String },
+    #[error("unable to look up {0} releases")]
+    Lookup(String, #[source] anyhow::Error),
+    #[error("unable to save IDE versions")]
+    Persist(#[source] anyhow::Error),
+}
+impl serde::Serialize for IdeVersionError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn versions_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(VERSIONS_FILE_NAME))
+}
+
+fn load(app_handle: &AppHandle) -> BTreeMap<String, PinnedIdeVersion> {
+    versions_path(app_handle)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(
+    app_handle: &AppHandle,
+    versions: &BTreeMap<String, PinnedIdeVersion>,
+) -> Result<(), IdeVersionError> {
+    let path = versions_path(app_handle)
+        .ok_or_else(|| IdeVersionError::Persist(anyhow::anyhow!("unable to find app data dir")))?;
+    let content =
+        serde_json::to_vec_pretty(versions).map_err(|err| IdeVersionError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| IdeVersionError::Persist(err.into()))
+}
+
+fn validate(pin: &PinnedIdeVersion) -> Result<(), IdeVersionError> {
+    if pin.ide != "openvscode" && jetbrains_gateway::product_code(&pin.ide).is_none() {
+        return Err(IdeVersionError::Unsupported(pin.ide.clone()));
+    }
+    // Ends up as an IDE option of the CLI and in release URLs
+    let valid = pin.version.starts_with(|c: char| c.is_ascii_digit())
+        && pin
+            .version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    if !valid {
+        return Err(IdeVersionError::InvalidVersion(pin.version.clone()));
+    }
+
+    Ok(())
+}
+
+/// The release matching the version or the build of a pin.
+fn find_release<'a>(
+    releases: &'a [JetBrainsRelease],
+    version: &str,
+) -> Option<&'a JetBrainsRelease> {
+    releases
+        .iter()
+        .find(|release| release.version == version || release.build == version)
+}
+
+fn unavailable(pin: &PinnedIdeVersion) -> IdeVersionError {
+    IdeVersionError::Unavailable {
+        ide: pin.ide.clone(),
+        version: pin.version.clone(),
+    }
+}
+
+async fn resolve_jetbrains(
+    client: &Client,
+    pin: &PinnedIdeVersion,
+    product_code: &str,
+) -> anyhow::Result<Option<ResolvedIdeVersion>> {
+    let releases = client
+        .get(JETBRAINS_RELEASES_URL)
+        .query(&[("code", product_code), ("type", "release")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<HashMap<String, Vec<JetBrainsRelease>>>()
+        .await
+        .with_context(|| format!("Fetch releases from {}", JETBRAINS_RELEASES_URL))?;
+    let release = releases
+        .get(product_code)
+        .and_then(|releases| find_release(releases, &pin.version));
+
+    Ok(release.map(|release| ResolvedIdeVersion {
+        version: release.version.clone(),
+        build: Some(release.build.clone()),
+        ide_options: vec![format!("{}={}", VERSION_OPTION, release.version)],
+    }))
+}
+
+async fn resolve_openvscode(
+    client: &Client,
+    pin: &PinnedIdeVersion,
+) -> anyhow::Result<Option<ResolvedIdeVersion>> {
+    let url = format!(
+        "{}/openvscode-server-v{}",
+        OPENVSCODE_RELEASES_URL, pin.version
+    );
+    let response = client
+        .get(&url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    response.error_for_status()?;
+
+    Ok(Some(ResolvedIdeVersion {
+        version: pin.version.clone(),
+        build: None,
+        ide_options: vec![format!("{}={}", VERSION_OPTION, pin.version)],
+    }))
+}
+
+/// Looks up the release pinned for the workspace, `None` if there is no pin for `ide`.
+pub async fn resolve(
+    app_handle: &AppHandle,
+    workspace_id: &str,
+    ide: &str,
+) -> Result<Option<ResolvedIdeVersion>, IdeVersionError> {
+    let Some(pin) = load(app_handle)
+        .remove(workspace_id)
+        .filter(|pin| pin.ide == ide)
+    else {
+        return Ok(None);
+    };
+
+    let lookup = |err: anyhow::Error| IdeVersionError::Lookup(pin.ide.clone(), err);
+    let client = Client::builder()
+        .user_agent("loft-sh/devpod")
+        .build()
+        .map_err(|err| lookup(err.into()))?;
+    let resolved = match jetbrains_gateway::product_code(ide) {
+        Some(product_code) => resolve_jetbrains(&client, &pin, product_code).await,
+        None => resolve_openvscode(&client, &pin).await,
+    }
+    .map_err(lookup)?
+    .ok_or_else(|| unavailable(&pin))?;
+    info!(
+        "Using pinned {} {} for {}",
+        ide, resolved.version, workspace_id
+    );
+
+    Ok(Some(resolved))
+}
+
+#[tauri::command]
+pub fn get_pinned_ide_versions(app_handle: AppHandle) -> BTreeMap<String, PinnedIdeVersion> {
+    load(&app_handle)
+}
+
+/// Pins the IDE backend of the workspace to a release, `None` goes back to the latest one.
+#[tauri::command]
+pub fn pin_ide_version(
+    app_handle: AppHandle,
+    workspace_id: String,
+    pin: Option<PinnedIdeVersion>,
+) -> Result<BTreeMap<String, PinnedIdeVersion>, IdeVersionError> {
+    let mut versions = load(&app_handle);
+    match pin {
+        Some(pin) => {
+            validate(&pin)?;
+            versions.insert(workspace_id, pin);
+        }
+        None => {
+            versions.remove(&workspace_id);
+        }
+    }
+    save(&app_handle, &versions)?;
+
+    Ok(versions)
+}
+
+/// Checks that the pinned release is still available before the workspace is opened with `ide`.
+#[tauri::command]
+pub async fn resolve_ide_version(
+    app_handle: AppHandle,
+    workspace_id: String,
+    ide: String,
+) -> Result<Option<ResolvedIdeVersion>, IdeVersionError> {
+    resolve(&app_handle, &workspace_id, &ide).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin(ide: &str, version: &str) -> PinnedIdeVersion {
+        PinnedIdeVersion {
+            ide: ide.to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    #[test]
+    fn should_validate_pins() {
+        assert!(validate(&pin("goland", "2023.3.2")).is_ok());
+        assert!(validate(&pin("openvscode", "1.86.2")).is_ok());
+        assert!(matches!(
+            validate(&pin("vscode", "1.86.2")),
+            Err(IdeVersionError::Unsupported(_))
+        ));
+        assert!(matches!(
+            validate(&pin("goland", "latest; rm -rf ~")),
+            Err(IdeVersionError::InvalidVersion(_))
+        ));
+    }
+
+    #[test]
+    fn should_find_release_by_version_or_build() {
+        let releases = vec![
+            JetBrainsRelease {
+                version: "2023.3.2".to_string(),
+                build: "233.13135.104".to_string(),
+            },
+            JetBrainsRelease {
+                version: "2023.3.1".to_string(),
+                build: "233.11799.300".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            find_release(&releases, "233.11799.300").map(|r| r.version.as_str()),
+            Some("2023.3.1")
+        );
+        assert_eq!(
+            find_release(&releases, "2023.3.2").map(|r| r.build.as_str()),
+            Some("233.13135.104")
+        );
+        assert!(find_release(&releases, "2021.1").is_none());
+    }
+}
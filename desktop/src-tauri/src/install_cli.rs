@@ -1,8 +1,12 @@
-use crate::{commands::DEVPOD_BINARY_NAME, AppHandle};
+use crate::{
+    audit_log::{self, AuditCategory, AuditInitiator},
+    cli_integrity::{self, CliIntegrityError},
+    commands::DEVPOD_BINARY_NAME,
+    AppHandle,
+};
 use log::error;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::Lines;
-use std::{env, path::PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -12,6 +16,10 @@ pub enum InstallCLIError {
     NoExePath(#[source] std::io::Error),
     #[error("Unable to create link to cli {0}")]
     Link(#[source] anyhow::Error),
+    #[error("Unable to remove link to cli {0}")]
+    Unlink(#[source] anyhow::Error),
+    #[error("Administrator privileges were not granted")]
+    Elevation,
     #[error("Unable to convert path to string")]
     PathConversion,
     #[error("Encountered an issue with the windows registry: ")]
@@ -24,6 +32,8 @@ pub enum InstallCLIError {
     WriteFile(#[source] std::io::Error),
     #[error("Failed to inform Windows about the change in environment variables. You will need to reboot you machine for them to take effect.")]
     WindowsBroadcastChange,
+    #[error(transparent)]
+    Integrity(#[from] CliIntegrityError),
 }
 impl serde::Serialize for InstallCLIError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -36,66 +46,91 @@ impl serde::Serialize for InstallCLIError {
 
 #[tauri::command]
 pub fn install_cli(app_handle: AppHandle, force: bool) -> Result<(), InstallCLIError> {
-    if let Err(err) = install(app_handle, force) {
+    // Puts the cli on the PATH, where it's run without any further checks
+    let result = cli_integrity::verify(&app_handle)
+        .map_err(InstallCLIError::from)
+        .and_then(|_| install(app_handle, force));
+    if let Err(err) = result {
         error!("{}", err);
         Err(err)
     } else {
+        audit_log::record(
+            AuditInitiator::Ui,
+            AuditCategory::Install,
+            "install cli",
+            None,
+        );
+        Ok(())
+    }
+}
+
+/// Whether `devpod` on the PATH can be run.
+#[tauri::command]
+pub async fn is_cli_installed() -> bool {
+    tauri::async_runtime::spawn_blocking(|| {
+        let mut command = std::process::Command::new("devpod");
+        command.arg("version");
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            // CREATE_NO_WINDOW
+            command.creation_flags(0x08000000);
+        }
+
+        command
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    })
+    .await
+    .unwrap_or(false)
+}
+
+#[tauri::command]
+pub fn uninstall_cli(app_handle: AppHandle, force: bool) -> Result<(), InstallCLIError> {
+    if let Err(err) = uninstall(app_handle, force) {
+        error!("{}", err);
+        Err(err)
+    } else {
+        audit_log::record(
+            AuditInitiator::Ui,
+            AuditCategory::Install,
+            "uninstall cli",
+            None,
+        );
         Ok(())
     }
 }
 
-// The path to the `devpod-cli` binary/executable. If bundled correctly, will be placed next to the desktop app executable.
-fn get_cli_path() -> Result<PathBuf, std::io::Error> {
-    let mut exe_path = env::current_exe()?;
-    exe_path.pop();
-    exe_path.push(DEVPOD_BINARY_NAME);
+// The path to the `devpod-cli` binary/executable the app runs, without the `.exe` suffix on Windows. Either the one
+// installed by an update or the one bundled next to the desktop app executable.
+pub(crate) fn get_cli_path(app_handle: &AppHandle) -> Result<PathBuf, std::io::Error> {
+    let mut cli_path = cli_integrity::cli_path(app_handle)?;
+    cli_path.set_file_name(DEVPOD_BINARY_NAME);
 
-    Ok(exe_path)
+    Ok(cli_path)
 }
 
 #[cfg(not(target_os = "windows"))]
-fn install(_app_handle: AppHandle, force: bool) -> Result<(), InstallCLIError> {
+fn install(app_handle: AppHandle, force: bool) -> Result<(), InstallCLIError> {
     use anyhow::Context;
-    use dirs::home_dir;
     use log::{info, warn};
     use std::{fs::remove_file, os::unix::fs::symlink};
 
-    let cli_path = get_cli_path().map_err(InstallCLIError::NoExePath)?;
-
-    // The binary we ship with is `devpod-cli`, but we want to link it to `devpod` so that users can just run `devpod` in their terminal
-    let mut target_paths: Vec<PathBuf> = vec![];
-
-    // /usr/local/bin/devpod
-    let raw_system_bin = format!("/usr/local/bin/{}", "devpod");
-    target_paths.push(PathBuf::from(&raw_system_bin));
+    let cli_path = get_cli_path(&app_handle).map_err(InstallCLIError::NoExePath)?;
 
     if force {
         info!("Attempting to force install CLI");
-        let script = format!("osascript -e \"do shell script \\\"mkdir -p /usr/local/bin && ln -sf '{}' '{}'\\\" with administrator privileges\"", cli_path.to_string_lossy(), raw_system_bin);
-        let status = std::process::Command::new("sh")
-            .arg("-c")
-            .arg(script)
-            .status()
-            .map_err(anyhow::Error::msg)
-            .map_err(InstallCLIError::Link)?;
-        info!("Status: {}", status);
-
-        return Ok(());
-    }
-
-    if let Some(home) = home_dir() {
-        // $HOME/bin/devpod
-        let mut user_bin = home.clone();
-        user_bin.push("bin/devpod");
-
-        // $HOME/.local/bin/devpod
-        let mut user_local_bin = home;
-        user_local_bin.push(".local/bin/devpod");
+        let script = format!(
+            "mkdir -p /usr/local/bin && ln -sf '{}' '{}'",
+            cli_path.to_string_lossy(),
+            SYSTEM_BIN_PATH
+        );
 
-        target_paths.push(user_bin);
-        target_paths.push(user_local_bin);
+        return run_elevated(&script);
     }
 
+    let target_paths = get_target_paths();
     let mut latest_error: Option<InstallCLIError> = None;
     let is_on_tmpfs = is_tmpfs(&cli_path.as_path());
 
@@ -150,6 +185,128 @@ fn install(_app_handle: AppHandle, force: bool) -> Result<(), InstallCLIError> {
     Ok(())
 }
 
+// The binary we ship with is `devpod-cli`, but we want to link it to `devpod` so that users can just run `devpod` in their terminal
+#[cfg(not(target_os = "windows"))]
+const SYSTEM_BIN_PATH: &str = "/usr/local/bin/devpod";
+
+#[cfg(not(target_os = "windows"))]
+fn get_target_paths() -> Vec<PathBuf> {
+    use dirs::home_dir;
+
+    // /usr/local/bin/devpod
+    let mut target_paths = vec![PathBuf::from(SYSTEM_BIN_PATH)];
+
+    if let Some(home) = home_dir() {
+        // $HOME/bin/devpod
+        let mut user_bin = home.clone();
+        user_bin.push("bin/devpod");
+
+        // $HOME/.local/bin/devpod
+        let mut user_local_bin = home;
+        user_local_bin.push(".local/bin/devpod");
+
+        target_paths.push(user_bin);
+        target_paths.push(user_local_bin);
+    }
+
+    target_paths
+}
+
+#[cfg(not(target_os = "windows"))]
+fn uninstall(app_handle: AppHandle, force: bool) -> Result<(), InstallCLIError> {
+    use anyhow::Context;
+    use log::info;
+    use std::fs::remove_file;
+
+    let cli_path = get_cli_path(&app_handle).map_err(InstallCLIError::NoExePath)?;
+
+    if force {
+        info!("Attempting to force uninstall CLI");
+        if !is_cli_link(Path::new(SYSTEM_BIN_PATH), &cli_path) {
+            return Ok(());
+        }
+        let script = format!("rm -f '{}'", SYSTEM_BIN_PATH);
+
+        return run_elevated(&script);
+    }
+
+    let mut latest_error: Option<InstallCLIError> = None;
+    for target_path in get_target_paths() {
+        // Leave other installations of the cli alone, i.e. one installed through a package manager
+        if !is_cli_link(&target_path, &cli_path) {
+            continue;
+        }
+
+        info!("Removing cli link {}", target_path.to_string_lossy());
+        if let Err(err) = remove_file(&target_path)
+            .with_context(|| format!("path: {}", target_path.to_string_lossy()))
+            .map_err(InstallCLIError::Unlink)
+        {
+            latest_error = Some(err);
+        }
+    }
+
+    if let Some(err) = latest_error {
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+// Whether `target_path` has been created by `install`, either as a symlink or as a copy on tmpfs
+#[cfg(not(target_os = "windows"))]
+fn is_cli_link(target_path: &Path, cli_path: &Path) -> bool {
+    if let Ok(link) = std::fs::read_link(target_path) {
+        return link == cli_path;
+    }
+
+    match (std::fs::read(target_path), std::fs::read(cli_path)) {
+        (Ok(target), Ok(cli)) => target == cli,
+        _ => false,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn run_elevated(script: &str) -> Result<(), InstallCLIError> {
+    use log::info;
+
+    let status = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(format!(
+            "do shell script \"{}\" with administrator privileges",
+            script
+        ))
+        .status()
+        .map_err(anyhow::Error::msg)
+        .map_err(InstallCLIError::Link)?;
+    info!("Status: {}", status);
+    if !status.success() {
+        return Err(InstallCLIError::Elevation);
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn run_elevated(script: &str) -> Result<(), InstallCLIError> {
+    use log::info;
+
+    // pkexec shows the desktop environment's graphical authentication prompt
+    let status = std::process::Command::new("pkexec")
+        .arg("sh")
+        .arg("-c")
+        .arg(script)
+        .status()
+        .map_err(anyhow::Error::msg)
+        .map_err(InstallCLIError::Link)?;
+    info!("Status: {}", status);
+    if !status.success() {
+        return Err(InstallCLIError::Elevation);
+    }
+
+    Ok(())
+}
+
 fn copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> std::io::Result<()> {
     std::fs::copy(from, to).map(|_| ())
 }
@@ -185,12 +342,7 @@ fn find_fs_type(curr_path: &Path, mount_lines: &Lines) -> Option<String> {
 
 #[cfg(target_os = "windows")]
 fn install(app_handle: AppHandle, force: bool) -> Result<(), InstallCLIError> {
-    use log::error;
     use std::fs;
-    use windows::Win32::{
-        Foundation::{GetLastError, HWND, LPARAM},
-        UI::WindowsAndMessaging::{SendMessageTimeoutW, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE},
-    };
     use winreg::{
         enums::{HKEY_CURRENT_USER, KEY_ALL_ACCESS},
         RegKey,
@@ -201,25 +353,22 @@ fn install(app_handle: AppHandle, force: bool) -> Result<(), InstallCLIError> {
         content: String,
     }
 
-    let cli_path = get_cli_path().map_err(|e| InstallCLIError::NoExePath(e))?;
-    let mut bin_dir = app_handle
-        .path_resolver()
-        .app_data_dir()
-        .ok_or(InstallCLIError::DataDir)?;
-    bin_dir.push("bin");
+    let cli_path = get_cli_path(&app_handle).map_err(|e| InstallCLIError::NoExePath(e))?;
+    let bin_dir = get_bin_dir(&app_handle)?;
 
     // Create binary directory in app dir and write bin_files to disk
     // These will be stored in a /bin folder under our control, usually `%APP_DIR%/sh.loft.devpod/bin`
     let cli_path = cli_path.to_str().ok_or(InstallCLIError::PathConversion)?;
 
+    let [sh_name, cmd_name] = BIN_FILE_NAMES;
     let sh_file = BinFile {
-        name: "devpod".to_string(),
+        name: sh_name.to_string(),
         // WARN: we actually need to debug print here because this escapes the backslash to `\\` and will then be recognised by the shell
         content: format!("#!/usr/bin/env sh\n{:?}.exe \"$@\" \nexit $?", cli_path),
     };
 
     let cmd_file = BinFile {
-        name: format!("{}.cmd", "devpod".to_string()),
+        name: cmd_name.to_string(),
         content: format!("@echo off\n\"{}.exe\" %*", cli_path),
     };
 
@@ -255,6 +404,77 @@ fn install(app_handle: AppHandle, force: bool) -> Result<(), InstallCLIError> {
         .set_value("Path", &current_env_path)
         .map_err(|e| InstallCLIError::Registry(e))?;
 
+    broadcast_environment_change()
+}
+
+#[cfg(target_os = "windows")]
+fn uninstall(app_handle: AppHandle, _force: bool) -> Result<(), InstallCLIError> {
+    use std::fs;
+    use winreg::{
+        enums::{HKEY_CURRENT_USER, KEY_ALL_ACCESS},
+        RegKey,
+    };
+
+    // Only our entry points, cli updates are installed into the same folder
+    let bin_dir = get_bin_dir(&app_handle)?;
+    for name in BIN_FILE_NAMES {
+        let file_path = bin_dir.join(name);
+        if file_path.exists() {
+            fs::remove_file(&file_path)
+                .map_err(anyhow::Error::msg)
+                .map_err(InstallCLIError::Unlink)?;
+        }
+    }
+
+    let current_dir_path = bin_dir.to_str().ok_or(InstallCLIError::PathConversion)?;
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let environment_key = hkcu
+        .open_subkey_with_flags("Environment", KEY_ALL_ACCESS)
+        .map_err(|e| InstallCLIError::Registry(e))?;
+    let current_env_path: String = environment_key
+        .get_value("Path")
+        .map_err(|e| InstallCLIError::Registry(e))?;
+
+    if !current_env_path.contains(current_dir_path) {
+        return Ok(());
+    }
+
+    let new_env_path = current_env_path
+        .split(';')
+        .filter(|path| *path != current_dir_path)
+        .collect::<Vec<_>>()
+        .join(";");
+
+    environment_key
+        .set_value("Path", &new_env_path)
+        .map_err(|e| InstallCLIError::Registry(e))?;
+
+    broadcast_environment_change()
+}
+
+// Entry points for the shell and cmd placed in the bin folder, see `install`
+#[cfg(target_os = "windows")]
+const BIN_FILE_NAMES: [&str; 2] = ["devpod", "devpod.cmd"];
+
+#[cfg(target_os = "windows")]
+fn get_bin_dir(app_handle: &AppHandle) -> Result<PathBuf, InstallCLIError> {
+    let mut bin_dir = app_handle
+        .path_resolver()
+        .app_data_dir()
+        .ok_or(InstallCLIError::DataDir)?;
+    bin_dir.push("bin");
+
+    Ok(bin_dir)
+}
+
+#[cfg(target_os = "windows")]
+fn broadcast_environment_change() -> Result<(), InstallCLIError> {
+    use log::error;
+    use windows::Win32::{
+        Foundation::{GetLastError, HWND, LPARAM},
+        UI::WindowsAndMessaging::{SendMessageTimeoutW, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE},
+    };
+
     // After setting the registry key we need to inform windows about the changes.
     // Otherwise it would require a full system reboot for them to take effect.
     unsafe {
@@ -0,0 +1,465 @@
+use crate::{custom_ides, AppHandle};
+use lazy_static::lazy_static;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use ts_rs::TS;
+
+// Installing an IDE while the app runs is rare, the picker can ask for a refresh
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+// Toolbox nests installs as `<product>/<channel>/<build>/`, or puts them right into `<product>/`
+const TOOLBOX_SCAN_DEPTH: usize = 4;
+const PRODUCT_INFO_FILE_NAME: &str = "product-info.json";
+
+lazy_static! {
+    static ref CACHE: Mutex<Option<(Instant, Vec<InstalledIde>)>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum IdeSource {
+    /// Its command line launcher is on the `PATH`
+    Path,
+    Application,
+    /// Installed and updated by JetBrains Toolbox
+    Toolbox,
+    /// Registered by the user with a launch command
+    Custom,
+}
+
+/// An IDE found on this machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InstalledIde {
+    /// Unique per product, e.g. `vscode-insiders` or `cursor`
+    id: String,
+    name: String,
+    /// The `ide` the CLI opens with it, `None` if the CLI doesn't support it
+    ide: Option<String>,
+    version: Option<String>,
+    /// Launcher or application bundle, the launch command of custom IDEs
+    path: String,
+    source: IdeSource,
+}
+
+impl InstalledIde {
+    pub fn new(
+        id: String,
+        name: String,
+        ide: Option<String>,
+        path: String,
+        source: IdeSource,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            ide,
+            version: None,
+            path,
+            source,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn source(&self) -> IdeSource {
+        self.source
+    }
+}
+
+/// Editors that ship a command line launcher, in the places their installers put them.
+struct Editor {
+    id: &'static str,
+    name: &'static str,
+    ide: Option<&'static str>,
+    binaries: &'static [&'static str],
+    mac_app: &'static str,
+    /// Relative to `%LOCALAPPDATA%\Programs`, where user installs go
+    windows_exe: &'static str,
+}
+
+const EDITORS: &[Editor] = &[
+    Editor {
+        id: "vscode",
+        name: "Visual Studio Code",
+        ide: Some("vscode"),
+        binaries: &["code"],
+        mac_app: "Visual Studio Code.app",
+        windows_exe: "Microsoft VS Code\\Code.exe",
+    },
+    Editor {
+        id: "vscode-insiders",
+        name: "Visual Studio Code - Insiders",
+        ide: None,
+        binaries: &["code-insiders"],
+        mac_app: "Visual Studio Code - Insiders.app",
+        windows_exe: "Microsoft VS Code Insiders\\Code - Insiders.exe",
+    },
+    Editor {
+        id: "vscodium",
+        name: "VSCodium",
+        ide: None,
+        binaries: &["codium"],
+        mac_app: "VSCodium.app",
+        windows_exe: "VSCodium\\VSCodium.exe",
+    },
+    Editor {
+        id: "cursor",
+        name: "Cursor",
+        ide: None,
+        binaries: &["cursor"],
+        mac_app: "Cursor.app",
+        windows_exe: "cursor\\Cursor.exe",
+    },
+    Editor {
+        id: "zed",
+        name: "Zed",
+        ide: None,
+        // Some Linux packages rename it to avoid a clash with the ZFS event daemon
+        binaries: &["zed", "zeditor"],
+        mac_app: "Zed.app",
+        windows_exe: "Zed\\Zed.exe",
+    },
+    Editor {
+        id: "fleet",
+        name: "Fleet",
+        ide: Some("fleet"),
+        binaries: &["fleet"],
+        mac_app: "Fleet.app",
+        windows_exe: "Fleet\\Fleet.exe",
+    },
+];
+
+/// JetBrains product codes and the `ide` the CLI knows them as.
+fn jetbrains_ide(product_code: &str) -> Option<(&'static str, Option<&'static str>)> {
+    let ide = match product_code {
+        "IU" | "IC" => ("intellij", Some("intellij")),
+        "GO" => ("goland", Some("goland")),
+        "PY" | "PC" => ("pycharm", Some("pycharm")),
+        "PS" => ("phpstorm", Some("phpstorm")),
+        "CL" => ("clion", Some("clion")),
+        "RM" => ("rubymine", Some("rubymine")),
+        "RD" => ("rider", Some("rider")),
+        "WS" => ("webstorm", Some("webstorm")),
+        // Not an IDE by itself, but what remote JetBrains IDEs are opened with
+        "GW" => ("gateway", None),
+        _ => return None,
+    };
+
+    Some(ide)
+}
+
+fn find_on_path(names: &[&str]) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    let extensions: &[&str] = if cfg!(windows) {
+        &["exe", "cmd"]
+    } else {
+        &[""]
+    };
+
+    env::split_paths(&path)
+        .flat_map(|dir| {
+            names.iter().flat_map(move |name| {
+                let dir = dir.clone();
+                extensions
+                    .iter()
+                    .map(move |extension| dir.join(name).with_extension(extension))
+            })
+        })
+        .find(|candidate| candidate.is_file())
+}
+
+/// The first thing that looks like a version number, `code` prints it on the first line and `zed` after its name.
+fn parse_version(output: &str) -> Option<String> {
+    output
+        .lines()
+        .next()?
+        .split_whitespace()
+        .find(|token| token.starts_with(|c: char| c.is_ascii_digit()))
+        .map(String::from)
+}
+
+fn launcher_version(launcher: &Path) -> Option<String> {
+    let mut command = Command::new(launcher);
+    command.arg("--version");
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW
+        command.creation_flags(0x08000000);
+    }
+    let output = command.output().ok()?;
+
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Value of a `<key>` in an XML property list, enough for the `Info.plist` of application bundles.
+fn plist_string(plist: &str, key: &str) -> Option<String> {
+    let rest = &plist[plist.find(&format!("<key>{}</key>", key))?..];
+    let start = rest.find("<string>")? + "<string>".len();
+    let end = rest[start..].find("</string>")? + start;
+
+    Some(rest[start..end].trim().to_string())
+}
+
+fn mac_app_dirs() -> Vec<PathBuf> {
+    let mut app_dirs = vec![PathBuf::from("/Applications")];
+    if let Some(home) = dirs::home_dir() {
+        app_dirs.push(home.join("Applications"));
+    }
+
+    app_dirs
+}
+
+fn detect_editor(editor: &Editor) -> Option<InstalledIde> {
+    let installed = |path: &Path, version, source| InstalledIde {
+        id: editor.id.to_string(),
+        name: editor.name.to_string(),
+        ide: editor.ide.map(String::from),
+        version,
+        path: path.to_string_lossy().to_string(),
+        source,
+    };
+
+    if let Some(launcher) = find_on_path(editor.binaries) {
+        return Some(installed(
+            &launcher,
+            launcher_version(&launcher),
+            IdeSource::Path,
+        ));
+    }
+    if cfg!(target_os = "macos") {
+        let app = mac_app_dirs()
+            .into_iter()
+            .map(|dir| dir.join(editor.mac_app))
+            .find(|app| app.is_dir())?;
+        let version = fs::read_to_string(app.join("Contents/Info.plist"))
+            .ok()
+            .and_then(|plist| plist_string(&plist, "CFBundleShortVersionString"));
+        return Some(installed(&app, version, IdeSource::Application));
+    }
+    if cfg!(windows) {
+        let exe = dirs::data_local_dir()?
+            .join("Programs")
+            .join(editor.windows_exe);
+        return exe
+            .is_file()
+            .then(|| installed(&exe, None, IdeSource::Application));
+    }
+
+    None
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProductInfo {
+    name: String,
+    version: Option<String>,
+    product_code: String,
+    #[serde(default)]
+    launch: Vec<ProductLaunch>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProductLaunch {
+    launcher_path: String,
+}
+
+/// Every JetBrains IDE describes itself in a `product-info.json` next to its binaries.
+fn parse_product_info(dir: &Path, content: &str, source: IdeSource) -> Option<InstalledIde> {
+    let info: ProductInfo = serde_json::from_str(content).ok()?;
+    let (id, ide) = jetbrains_ide(&info.product_code)?;
+    let launcher = info.launch.first()?.launcher_path.as_str();
+
+    Some(InstalledIde {
+        id: id.to_string(),
+        name: info.name,
+        ide: ide.map(String::from),
+        version: info.version,
+        path: dir.join(launcher).to_string_lossy().to_string(),
+        source,
+    })
+}
+
+fn find_product_infos(dir: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    let candidate = dir.join(PRODUCT_INFO_FILE_NAME);
+    if candidate.is_file() {
+        found.push(candidate);
+        return;
+    }
+    if depth == 0 {
+        return;
+    }
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                find_product_infos(&path, depth - 1, found);
+            }
+        }
+    }
+}
+
+fn toolbox_apps_dir() -> Option<PathBuf> {
+    let dir = if cfg!(target_os = "linux") {
+        dirs::data_dir()?
+    } else if cfg!(target_os = "macos") {
+        dirs::home_dir()?.join("Library/Application Support")
+    } else {
+        dirs::data_local_dir()?
+    };
+
+    Some(dir.join("JetBrains").join("Toolbox").join("apps"))
+}
+
+fn detect_jetbrains() -> Vec<InstalledIde> {
+    let mut product_infos: Vec<(PathBuf, IdeSource)> = Vec::new();
+    if let Some(dir) = toolbox_apps_dir() {
+        let mut found = Vec::new();
+        find_product_infos(&dir, TOOLBOX_SCAN_DEPTH, &mut found);
+        product_infos.extend(found.into_iter().map(|path| (path, IdeSource::Toolbox)));
+    }
+    // Toolbox 2 and manual installs put application bundles there
+    let app_dirs: Vec<PathBuf> = if cfg!(target_os = "macos") {
+        mac_app_dirs()
+            .into_iter()
+            .flat_map(|dir| fs::read_dir(dir).into_iter().flatten())
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().join("Contents/Resources"))
+            .collect()
+    } else if cfg!(windows) {
+        env::var_os("ProgramFiles")
+            .map(|dir| PathBuf::from(dir).join("JetBrains"))
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    product_infos.extend(
+        app_dirs
+            .into_iter()
+            .map(|dir| dir.join(PRODUCT_INFO_FILE_NAME))
+            .filter(|path| path.is_file())
+            .map(|path| (path, IdeSource::Application)),
+    );
+
+    product_infos
+        .into_iter()
+        .filter_map(|(path, source)| {
+            let content = fs::read_to_string(&path).ok()?;
+            parse_product_info(path.parent()?, &content, source)
+        })
+        .collect()
+}
+
+fn detect() -> Vec<InstalledIde> {
+    let mut ides: Vec<InstalledIde> = EDITORS.iter().filter_map(detect_editor).collect();
+    ides.extend(detect_jetbrains());
+
+    // The same install can be found through Toolbox and as an application
+    let mut seen = HashSet::new();
+    ides.retain(|ide| seen.insert(ide.path.clone()));
+    ides.sort_by(|a, b| (&a.name, &b.version).cmp(&(&b.name, &a.version)));
+
+    ides
+}
+
+/// The installed IDEs, detected at most every `CACHE_TTL` unless `refresh` is set.
+pub fn installed(refresh: bool) -> Vec<InstalledIde> {
+    let mut cache = CACHE.lock().unwrap();
+    if let Some((detected_at, ides)) = cache.as_ref() {
+        if !refresh && detected_at.elapsed() < CACHE_TTL {
+            return ides.clone();
+        }
+    }
+
+    let ides = detect();
+    info!(
+        "Detected IDEs: {}",
+        ides.iter()
+            .map(|ide| ide.id.as_str())
+            .collect::<Vec<&str>>()
+            .join(", ")
+    );
+    *cache = Some((Instant::now(), ides.clone()));
+
+    ides
+}
+
+/// Local IDEs only, browser based ones like `openvscode` need nothing installed. Custom IDEs come last.
+#[tauri::command]
+pub async fn list_installed_ides(
+    app_handle: AppHandle,
+    refresh: Option<bool>,
+) -> Vec<InstalledIde> {
+    let mut ides = installed(refresh.unwrap_or(false));
+    ides.extend(custom_ides::as_installed(&app_handle));
+
+    ides
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_versions() {
+        assert_eq!(
+            parse_version("1.89.1\ndc96b837cf6bb4af9cd736aa3af08cf8279f7685\nx64\n"),
+            Some("1.89.1".to_string())
+        );
+        assert_eq!(parse_version("Zed 0.150.4\n"), Some("0.150.4".to_string()));
+        assert_eq!(parse_version(""), None);
+        assert_eq!(
+            plist_string(
+                "<dict>\n\t<key>CFBundleShortVersionString</key>\n\t<string>1.89.1</string>\n</dict>",
+                "CFBundleShortVersionString"
+            ),
+            Some("1.89.1".to_string())
+        );
+    }
+
+    #[test]
+    fn should_map_jetbrains_products() {
+        let content = r#"{
+            "name": "GoLand",
+            "version": "2024.1.2",
+            "buildNumber": "241.17011.108",
+            "productCode": "GO",
+            "launch": [{ "os": "Linux", "launcherPath": "bin/goland.sh" }]
+        }"#;
+        let ide =
+            parse_product_info(Path::new("/opt/goland"), content, IdeSource::Toolbox).unwrap();
+
+        assert_eq!(ide.id, "goland");
+        assert_eq!(ide.ide.as_deref(), Some("goland"));
+        assert_eq!(ide.version.as_deref(), Some("2024.1.2"));
+        assert_eq!(Path::new(&ide.path), Path::new("/opt/goland/bin/goland.sh"));
+        assert!(parse_product_info(
+            Path::new("/opt/datagrip"),
+            &content.replace("\"GO\"", "\"DB\""),
+            IdeSource::Toolbox
+        )
+        .is_none());
+    }
+}
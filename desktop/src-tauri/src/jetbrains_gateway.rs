@@ -0,0 +1,174 @@
+use crate::{
+    ide_versions::{self, IdeVersionError},
+    installed_ides::{self, IdeSource},
+    ssh_config, workspaces, AppHandle,
+};
+use log::info;
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+use tauri::Manager;
+use thiserror::Error;
+use url::form_urlencoded;
+
+const GATEWAY_ID: &str = "gateway";
+const GATEWAY_DOWNLOAD_URL: &str = "https://www.jetbrains.com/remote-development/gateway/";
+// The SSH host of the workspace goes through the CLI, which picks the container's user regardless
+const DEFAULT_USER: &str = "root";
+const SSH_PORT: &str = "22";
+
+#[derive(Error, Debug)]
+pub enum GatewayError {
+    #[error("{0} is not a JetBrains IDE")]
+    UnknownIde(String),
+    #[error(
+        "JetBrains Gateway is not installed, get it from {}",
+        GATEWAY_DOWNLOAD_URL
+    )]
+    NotInstalled,
+    #[error(transparent)]
+    Version(#[from] IdeVersionError),
+    #[error("unable to launch JetBrains Gateway")]
+    Launch(#[source] anyhow::Error),
+}
+impl serde::Serialize for GatewayError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// The code Gateway identifies the backend it deploys into the workspace with.
+pub(crate) fn product_code(ide: &str) -> Option<&'static str> {
+    let code = match ide {
+        "intellij" => "IU",
+        "goland" => "GO",
+        "pycharm" => "PY",
+        "phpstorm" => "PS",
+        "clion" => "CL",
+        "rubymine" => "RM",
+        "rider" => "RD",
+        "webstorm" => "WS",
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+/// Connects over SSH and lets Gateway deploy the product, the latest release unless `build` is set. The parameters go
+/// into the fragment.
+fn connect_url(workspace_id: &str, product_code: &str, build: Option<&str>, user: &str) -> String {
+    let mut params = form_urlencoded::Serializer::new(String::new());
+    params
+        .append_pair("type", "ssh")
+        .append_pair("deploy", "true")
+        .append_pair("host", &ssh_config::host_alias(workspace_id))
+        .append_pair("port", SSH_PORT)
+        .append_pair("user", user)
+        .append_pair("projectPath", &workspaces::remote_folder(workspace_id))
+        .append_pair("productCode", product_code);
+    if let Some(build) = build {
+        params.append_pair("buildNumber", build);
+    }
+
+    format!("jetbrains-gateway://connect#{}", params.finish())
+}
+
+fn gateway_url(
+    workspace_id: &str,
+    ide: &str,
+    build: Option<&str>,
+    user: Option<&str>,
+) -> Result<String, GatewayError> {
+    let product_code =
+        product_code(ide).ok_or_else(|| GatewayError::UnknownIde(ide.to_string()))?;
+
+    Ok(connect_url(
+        workspace_id,
+        product_code,
+        build,
+        user.unwrap_or(DEFAULT_USER),
+    ))
+}
+
+/// Deploys the build pinned for the workspace if there is one.
+async fn pinned_gateway_url(
+    app_handle: &AppHandle,
+    workspace_id: &str,
+    ide: &str,
+    user: Option<&str>,
+) -> Result<String, GatewayError> {
+    // Fail before looking up releases
+    product_code(ide).ok_or_else(|| GatewayError::UnknownIde(ide.to_string()))?;
+    let pinned = ide_versions::resolve(app_handle, workspace_id, ide).await?;
+    let build = pinned.as_ref().and_then(|pinned| pinned.build());
+
+    gateway_url(workspace_id, ide, build, user)
+}
+
+/// The `jetbrains-gateway://` URL that opens the workspace in `ide`, e.g. `goland`.
+#[tauri::command]
+pub async fn get_gateway_url(
+    app_handle: AppHandle,
+    workspace_id: String,
+    ide: String,
+    user: Option<String>,
+) -> Result<String, GatewayError> {
+    pinned_gateway_url(&app_handle, &workspace_id, &ide, user.as_deref()).await
+}
+
+/// Hands the workspace to Gateway, through its launcher if there is one and the URL handler it registered otherwise.
+#[tauri::command]
+pub async fn open_in_gateway(
+    app_handle: AppHandle,
+    workspace_id: String,
+    ide: String,
+    user: Option<String>,
+) -> Result<(), GatewayError> {
+    let url = pinned_gateway_url(&app_handle, &workspace_id, &ide, user.as_deref()).await?;
+    let gateway = installed_ides::installed(false)
+        .into_iter()
+        .find(|installed| installed.id() == GATEWAY_ID)
+        .ok_or(GatewayError::NotInstalled)?;
+
+    info!("Opening {} in {} through Gateway", workspace_id, ide);
+    let launcher = Path::new(gateway.path());
+    if gateway.source() != IdeSource::Application && launcher.is_file() {
+        Command::new(launcher)
+            .arg(&url)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map(|_| ())
+            .map_err(|err| GatewayError::Launch(err.into()))
+    } else {
+        tauri::api::shell::open(&app_handle.shell_scope(), url, None)
+            .map_err(|err| GatewayError::Launch(err.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_connect_url() {
+        assert_eq!(
+            gateway_url("my-project", "goland", None, None).unwrap(),
+            "jetbrains-gateway://connect#type=ssh&deploy=true&host=my-project.devpod&port=22&user=root&projectPath=%2Fworkspaces%2Fmy-project&productCode=GO"
+        );
+        assert!(
+            gateway_url("my-project", "goland", Some("233.13135.104"), None)
+                .unwrap()
+                .ends_with("&productCode=GO&buildNumber=233.13135.104")
+        );
+        assert!(matches!(
+            gateway_url("my-project", "vscode", None, None),
+            Err(GatewayError::UnknownIde(_))
+        ));
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{
+    ssh_config::{self, SshConfigError},
+    AppHandle,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use thiserror::Error;
+use ts_rs::TS;
+
+const JUMP_HOSTS_FILE_NAME: &str = "jump_hosts.json";
+// Aliases of the jump hosts themselves must never be routed through a jump host
+const ALIAS_PREFIX: &str = "devpod-jump-";
+
+/// A bastion that SSH connections to workspace machines go through.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct JumpHost {
+    host: String,
+    port: Option<u16>,
+    user: Option<String>,
+    /// Private key for the jump host, the keys of the SSH agent are used otherwise
+    identity_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum JumpHostScope {
+    Provider,
+    /// Takes precedence over the jump host of the provider
+    Workspace,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct JumpHosts {
+    providers: BTreeMap<String, JumpHost>,
+    workspaces: BTreeMap<String, JumpHost>,
+}
+
+impl JumpHosts {
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty() && self.workspaces.is_empty()
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum JumpHostError {
+    #[error("invalid {0}")]
+    Invalid(&'static str),
+    #[error("unable to save jump hosts")]
+    Persist(#[source] anyhow::Error),
+    #[error(transparent)]
+    SshConfig(#[from] SshConfigError),
+}
+impl serde::Serialize for JumpHostError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn jump_hosts_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(JUMP_HOSTS_FILE_NAME))
+}
+
+pub fn load(app_handle: &AppHandle) -> JumpHosts {
+    jump_hosts_path(app_handle)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(app_handle: &AppHandle, jump_hosts: &JumpHosts) -> Result<(), JumpHostError> {
+    let path = jump_hosts_path(app_handle)
+        .ok_or_else(|| JumpHostError::Persist(anyhow::anyhow!("unable to find app data dir")))?;
+    let content =
+        serde_json::to_vec_pretty(jump_hosts).map_err(|err| JumpHostError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| JumpHostError::Persist(err.into()))
+}
+
+/// Everything that ends up in the SSH config unquoted.
+pub(crate) fn is_config_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn validate(id: &str, jump_host: &JumpHost) -> Result<(), JumpHostError> {
+    if !is_config_token(id) {
+        return Err(JumpHostError::Invalid("id"));
+    }
+    if !is_config_token(&jump_host.host) {
+        return Err(JumpHostError::Invalid("host"));
+    }
+    if jump_host
+        .user
+        .as_deref()
+        .is_some_and(|user| !is_config_token(user))
+    {
+        return Err(JumpHostError::Invalid("user"));
+    }
+    if jump_host
+        .identity_file
+        .as_deref()
+        .is_some_and(|path| path.is_empty() || path.contains(['"', '\n', '\r']))
+    {
+        return Err(JumpHostError::Invalid("identity file"));
+    }
+
+    Ok(())
+}
+
+/// Condition on the environment the CLI runs provider commands with.
+pub(crate) fn env_condition(variable: &str, value: &str) -> String {
+    // `x` prefix so that unset variables compare fine
+    if cfg!(windows) {
+        format!("if x%{}%==x{} (exit 0) else (exit 1)", variable, value)
+    } else {
+        format!("test x${} = x{}", variable, value)
+    }
+}
+
+/// Condition on `variable` being set at all.
+pub(crate) fn env_defined_condition(variable: &str) -> String {
+    if cfg!(windows) {
+        format!("if defined {} (exit 0) else (exit 1)", variable)
+    } else {
+        format!("test x${} != x", variable)
+    }
+}
+
+fn render_match(variable: &str, id: &str, alias: &str) -> String {
+    format!(
+        "\nMatch originalhost *,!{prefix}* exec \"{condition}\"\n  ProxyJump {alias}\n",
+        prefix = ALIAS_PREFIX,
+        condition = env_condition(variable, id),
+        alias = alias,
+    )
+}
+
+fn render_host(alias: &str, jump_host: &JumpHost) -> String {
+    let mut host = format!("\nHost {}\n  HostName {}\n", alias, jump_host.host);
+    if let Some(port) = jump_host.port {
+        host.push_str(&format!("  Port {}\n", port));
+    }
+    if let Some(user) = &jump_host.user {
+        host.push_str(&format!("  User {}\n", user));
+    }
+    if let Some(identity_file) = &jump_host.identity_file {
+        host.push_str(&format!(
+            "  IdentityFile \"{}\"\n  IdentitiesOnly yes\n",
+            identity_file
+        ));
+    }
+
+    host
+}
+
+/// `Match` blocks for the SSH connections the CLI makes to workspace machines, those run with the workspace
+/// and provider in their environment. The first `ProxyJump` wins, so workspaces come before providers.
+pub fn render(jump_hosts: &JumpHosts) -> String {
+    let scopes = [
+        ("WORKSPACE_ID", "workspace", &jump_hosts.workspaces),
+        ("WORKSPACE_PROVIDER", "provider", &jump_hosts.providers),
+    ];
+    let mut matches = String::new();
+    let mut hosts = String::new();
+    for (variable, scope, jump_hosts) in scopes {
+        for (id, jump_host) in jump_hosts {
+            let alias = format!("{}{}-{}", ALIAS_PREFIX, scope, id);
+            matches.push_str(&render_match(variable, id, &alias));
+            hosts.push_str(&render_host(&alias, jump_host));
+        }
+    }
+
+    format!("{}{}", matches, hosts)
+}
+
+#[tauri::command]
+pub fn get_jump_hosts(app_handle: AppHandle) -> JumpHosts {
+    load(&app_handle)
+}
+
+/// Sets or removes (`None`) the jump host of a provider or workspace and updates the SSH config.
+#[tauri::command]
+pub fn set_jump_host(
+    app_handle: AppHandle,
+    scope: JumpHostScope,
+    id: String,
+    jump_host: Option<JumpHost>,
+) -> Result<JumpHosts, JumpHostError> {
+    let mut jump_hosts = load(&app_handle);
+    let scoped = match scope {
+        JumpHostScope::Provider => &mut jump_hosts.providers,
+        JumpHostScope::Workspace => &mut jump_hosts.workspaces,
+    };
+    match jump_host {
+        Some(jump_host) => {
+            validate(&id, &jump_host)?;
+            info!(
+                "Setting jump host of {:?} {} to {}",
+                scope, id, jump_host.host
+            );
+            scoped.insert(id, jump_host);
+        }
+        None => {
+            info!("Removing jump host of {:?} {}", scope, id);
+            scoped.remove(&id);
+        }
+    }
+    save(&app_handle, &jump_hosts)?;
+    ssh_config::apply(&app_handle)?;
+
+    Ok(jump_hosts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render_workspace_jump_hosts_first() {
+        let jump_host = |host: &str| JumpHost {
+            host: host.to_string(),
+            port: Some(2222),
+            user: Some("ops".to_string()),
+            identity_file: None,
+        };
+        let jump_hosts = JumpHosts {
+            providers: BTreeMap::from([("aws".to_string(), jump_host("bastion.example.com"))]),
+            workspaces: BTreeMap::from([("my-project".to_string(), jump_host("10.0.0.1"))]),
+        };
+        let config = render(&jump_hosts);
+
+        let workspace_match = config
+            .find("ProxyJump devpod-jump-workspace-my-project\n")
+            .unwrap();
+        let provider_match = config.find("ProxyJump devpod-jump-provider-aws\n").unwrap();
+        assert!(workspace_match < provider_match);
+        assert!(config.contains("Match originalhost *,!devpod-jump-* exec "));
+        assert!(config.contains(
+            "\nHost devpod-jump-provider-aws\n  HostName bastion.example.com\n  Port 2222\n  User ops\n"
+        ));
+    }
+
+    #[test]
+    fn should_reject_values_that_break_the_config() {
+        let jump_host = JumpHost {
+            host: "bastion.example.com\n  ProxyCommand evil".to_string(),
+            port: None,
+            user: None,
+            identity_file: None,
+        };
+
+        assert!(validate("aws", &jump_host).is_err());
+    }
+}
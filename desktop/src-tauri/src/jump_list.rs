@@ -0,0 +1,216 @@
+use crate::{settings::Settings, workspaces::WorkspacesState, AppHandle};
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+use url::Url;
+
+const RECENT_WORKSPACES_LIMIT: usize = 5;
+const JUMP_LIST_IDE: &str = "vscode";
+
+lazy_static! {
+    static ref CURRENT: Mutex<Option<JumpList>> = Mutex::new(None);
+}
+
+/// A jump list item, launches DevPod with a deep link that is forwarded to the running instance.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct JumpListEntry {
+    title: String,
+    url: String,
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct JumpList {
+    pinned: Vec<JumpListEntry>,
+    recent: Vec<JumpListEntry>,
+    tasks: Vec<JumpListEntry>,
+}
+
+fn open_url(params: &[(&str, &str)]) -> String {
+    if params.is_empty() {
+        return "devpod://open".to_string();
+    }
+
+    Url::parse_with_params("devpod://open", params)
+        .map(|url| url.to_string())
+        .unwrap_or_else(|_| "devpod://open".to_string())
+}
+
+fn workspace_entry(id: &str) -> JumpListEntry {
+    JumpListEntry {
+        title: format!("Open {} in VS Code", id),
+        url: open_url(&[("workspace", id), ("ide", JUMP_LIST_IDE)]),
+    }
+}
+
+fn jump_list(pinned: &[String], workspaces: &WorkspacesState) -> JumpList {
+    let ids = workspaces.recent_ids();
+    // Pinned workspaces might have been deleted in the meantime
+    let pinned_entries = pinned
+        .iter()
+        .filter(|id| ids.contains(&id.as_str()))
+        .map(|id| workspace_entry(id))
+        .collect();
+    let recent = ids
+        .iter()
+        .filter(|id| !pinned.iter().any(|pinned_id| pinned_id == *id))
+        .take(RECENT_WORKSPACES_LIMIT)
+        .map(|id| workspace_entry(id))
+        .collect();
+
+    JumpList {
+        pinned: pinned_entries,
+        recent,
+        tasks: vec![JumpListEntry {
+            title: "New Workspace".to_string(),
+            url: open_url(&[]),
+        }],
+    }
+}
+
+/// Rebuilds the taskbar jump list from the pinned and most recently used workspaces, if they changed.
+pub fn update(app_handle: &AppHandle, workspaces: &WorkspacesState) {
+    let jump_list = jump_list(&Settings::pinned_workspaces(app_handle), workspaces);
+    {
+        let mut current = CURRENT.lock().unwrap();
+        if current.as_ref() == Some(&jump_list) {
+            return;
+        }
+        *current = Some(jump_list.clone());
+    }
+
+    #[cfg(target_os = "windows")]
+    if let Err(err) = taskbar::set_jump_list(&jump_list) {
+        log::error!("Failed to update jump list: {}", err);
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod taskbar {
+    use super::{JumpList, JumpListEntry};
+    use log::warn;
+    use windows::{
+        core::{ComInterface, Result, HSTRING, PWSTR},
+        w,
+        Win32::{
+            Storage::EnhancedStorage::PKEY_Title,
+            System::Com::{
+                CoCreateInstance, CoInitializeEx, StructuredStorage::PROPVARIANT,
+                CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED, VT_LPWSTR,
+            },
+            UI::Shell::{
+                Common::{IObjectArray, IObjectCollection},
+                DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW,
+                PropertiesSystem::IPropertyStore,
+                ShellLink,
+            },
+        },
+    };
+
+    unsafe fn shell_link(exe: &HSTRING, entry: &JumpListEntry) -> Result<IShellLinkW> {
+        let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+        link.SetPath(exe)?;
+        link.SetArguments(&HSTRING::from(entry.url.as_str()))?;
+        link.SetIconLocation(exe, 0)?;
+
+        // Jump lists display the title property rather than the link description
+        let title = HSTRING::from(entry.title.as_str());
+        let mut value = PROPVARIANT::default();
+        (*value.Anonymous.Anonymous).vt = VT_LPWSTR;
+        (*value.Anonymous.Anonymous).Anonymous.pwszVal = PWSTR(title.as_ptr() as *mut u16);
+        let store: IPropertyStore = link.cast()?;
+        store.SetValue(&PKEY_Title, &value)?;
+        store.Commit()?;
+
+        Ok(link)
+    }
+
+    unsafe fn arguments(link: &IShellLinkW) -> Result<String> {
+        let mut buf = [0u16; 1024];
+        link.GetArguments(&mut buf)?;
+        let len = buf.iter().position(|c| *c == 0).unwrap_or(buf.len());
+
+        Ok(String::from_utf16_lossy(&buf[..len]))
+    }
+
+    unsafe fn collection(
+        exe: &HSTRING,
+        entries: &[JumpListEntry],
+        removed: &[String],
+    ) -> Result<IObjectArray> {
+        let collection: IObjectCollection =
+            CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+        // Windows refuses the whole list if it contains an item the user removed from it
+        for entry in entries.iter().filter(|entry| !removed.contains(&entry.url)) {
+            collection.AddObject(&shell_link(exe, entry)?)?;
+        }
+
+        collection.cast()
+    }
+
+    pub fn set_jump_list(jump_list: &JumpList) -> anyhow::Result<()> {
+        let exe = HSTRING::from(crate::util::app_path()?.as_os_str());
+
+        unsafe {
+            // Fails if COM has already been initialized on this thread, which is fine
+            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+            let list: ICustomDestinationList =
+                CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+            let mut min_slots = 0u32;
+            let removed_items: IObjectArray = list.BeginList(&mut min_slots)?;
+            let mut removed = vec![];
+            for i in 0..removed_items.GetCount()? {
+                if let Ok(link) = removed_items.GetAt::<IShellLinkW>(i) {
+                    removed.push(arguments(&link)?);
+                }
+            }
+
+            if !jump_list.pinned.is_empty() {
+                let pinned = collection(&exe, &jump_list.pinned, &removed)?;
+                if let Err(err) = list.AppendCategory(w!("Pinned"), &pinned) {
+                    warn!("Failed to add pinned workspaces to jump list: {}", err);
+                }
+            }
+            if !jump_list.recent.is_empty() {
+                let recent = collection(&exe, &jump_list.recent, &removed)?;
+                if let Err(err) = list.AppendCategory(w!("Recent"), &recent) {
+                    warn!("Failed to add recent workspaces to jump list: {}", err);
+                }
+            }
+            list.AddUserTasks(&collection(&exe, &jump_list.tasks, &[])?)?;
+            list.CommitList()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_jump_list() {
+        let workspaces: WorkspacesState = serde_json::from_str(
+            r#"[
+                {"id": "old", "lastUsed": "2023-01-01T00:00:00Z"},
+                {"id": "new", "lastUsed": "2023-06-01T00:00:00Z"},
+                {"id": "pinned", "lastUsed": "2022-01-01T00:00:00Z"}
+            ]"#,
+        )
+        .unwrap();
+
+        let jump_list = jump_list(&["pinned".to_string(), "deleted".to_string()], &workspaces);
+        assert_eq!(jump_list.pinned, vec![workspace_entry("pinned")]);
+        assert_eq!(
+            jump_list.recent,
+            vec![workspace_entry("new"), workspace_entry("old")]
+        );
+        assert_eq!(
+            workspace_entry("new").url,
+            "devpod://open?workspace=new&ide=vscode"
+        );
+        assert_eq!(jump_list.tasks[0].url, "devpod://open");
+    }
+}
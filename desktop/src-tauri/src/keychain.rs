@@ -0,0 +1,250 @@
+use anyhow::Context;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum KeychainError {
+    #[error("unable to find keychain item {0}/{1}")]
+    NotFound(String, String),
+    #[error("unable to access keychain")]
+    Access(#[source] anyhow::Error),
+}
+impl serde::Serialize for KeychainError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// Reads the password stored for `service` and `account` from the platform keychain.
+pub fn get_password(service: &str, account: &str) -> Result<String, KeychainError> {
+    match read_password(service, account).map_err(KeychainError::Access)? {
+        Some(password) => Ok(password),
+        None => Err(KeychainError::NotFound(
+            service.to_string(),
+            account.to_string(),
+        )),
+    }
+}
+
+/// Stores `password` for `service` and `account` in the platform keychain, replacing an existing one.
+pub fn set_password(service: &str, account: &str, password: &str) -> Result<(), KeychainError> {
+    write_password(service, account, password).map_err(KeychainError::Access)
+}
+
+/// Removes the password for `service` and `account`, succeeds if there is none.
+pub fn delete_password(service: &str, account: &str) -> Result<(), KeychainError> {
+    remove_password(service, account).map_err(KeychainError::Access)
+}
+
+#[cfg(target_os = "macos")]
+fn read_password(service: &str, account: &str) -> anyhow::Result<Option<String>> {
+    let output = std::process::Command::new("security")
+        .args(["find-generic-password", "-s", service, "-a", account, "-w"])
+        .output()
+        .context("run security")?;
+
+    // `security` exits with 44 if the item could not be found
+    if output.status.code() == Some(44) {
+        return Ok(None);
+    }
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    Ok(Some(trim_newline(&output.stdout)))
+}
+
+#[cfg(target_os = "macos")]
+fn write_password(service: &str, account: &str, password: &str) -> anyhow::Result<()> {
+    let output = std::process::Command::new("security")
+        .args([
+            "add-generic-password",
+            "-U",
+            "-s",
+            service,
+            "-a",
+            account,
+            "-w",
+            password,
+        ])
+        .output()
+        .context("run security")?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn remove_password(service: &str, account: &str) -> anyhow::Result<()> {
+    let output = std::process::Command::new("security")
+        .args(["delete-generic-password", "-s", service, "-a", account])
+        .output()
+        .context("run security")?;
+    if !output.status.success() && output.status.code() != Some(44) {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn read_password(service: &str, account: &str) -> anyhow::Result<Option<String>> {
+    let output = std::process::Command::new("secret-tool")
+        .args(["lookup", "service", service, "account", account])
+        .output()
+        .context("run secret-tool, make sure libsecret-tools is installed")?;
+
+    // `secret-tool` exits with 1 and no output if the item could not be found
+    if !output.status.success() {
+        if output.stderr.is_empty() {
+            return Ok(None);
+        }
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    Ok(Some(trim_newline(&output.stdout)))
+}
+
+#[cfg(target_os = "linux")]
+fn write_password(service: &str, account: &str, password: &str) -> anyhow::Result<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    // The password is read from stdin so that it doesn't show up in the process list
+    let mut child = Command::new("secret-tool")
+        .args(["store", "--label", &format!("DevPod {}", account)])
+        .args(["service", service, "account", account])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("run secret-tool, make sure libsecret-tools is installed")?;
+    child
+        .stdin
+        .take()
+        .context("open secret-tool stdin")?
+        .write_all(password.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn remove_password(service: &str, account: &str) -> anyhow::Result<()> {
+    let output = std::process::Command::new("secret-tool")
+        .args(["clear", "service", service, "account", account])
+        .output()
+        .context("run secret-tool, make sure libsecret-tools is installed")?;
+    if !output.status.success() && !output.stderr.is_empty() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn read_password(service: &str, account: &str) -> anyhow::Result<Option<String>> {
+    use windows::{
+        core::PCWSTR,
+        Win32::{
+            Foundation::ERROR_NOT_FOUND,
+            Security::Credentials::{CredFree, CredReadW, CREDENTIALW, CRED_TYPE_GENERIC},
+        },
+    };
+
+    let target_name = target_name(service, account);
+    let mut credential: *mut CREDENTIALW = std::ptr::null_mut();
+
+    unsafe {
+        if let Err(err) = CredReadW(
+            PCWSTR(target_name.as_ptr()),
+            CRED_TYPE_GENERIC,
+            0,
+            &mut credential,
+        )
+        .ok()
+        {
+            if err.code() == ERROR_NOT_FOUND.to_hresult() {
+                return Ok(None);
+            }
+            return Err(err).context("read credential");
+        }
+
+        let blob = std::slice::from_raw_parts(
+            (*credential).CredentialBlob,
+            (*credential).CredentialBlobSize as usize,
+        );
+        let password = String::from_utf8_lossy(blob).into_owned();
+        CredFree(credential as *const std::ffi::c_void);
+
+        Ok(Some(password))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn target_name(service: &str, account: &str) -> Vec<u16> {
+    format!("{}:{}", service, account)
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn write_password(service: &str, account: &str, password: &str) -> anyhow::Result<()> {
+    use windows::{
+        core::PWSTR,
+        Win32::Security::Credentials::{
+            CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE, CRED_TYPE_GENERIC,
+        },
+    };
+
+    let mut target_name = target_name(service, account);
+    let mut user_name: Vec<u16> = account.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut blob = password.as_bytes().to_vec();
+    let credential = CREDENTIALW {
+        Type: CRED_TYPE_GENERIC,
+        TargetName: PWSTR(target_name.as_mut_ptr()),
+        CredentialBlobSize: blob.len() as u32,
+        CredentialBlob: blob.as_mut_ptr(),
+        Persist: CRED_PERSIST_LOCAL_MACHINE,
+        UserName: PWSTR(user_name.as_mut_ptr()),
+        ..Default::default()
+    };
+
+    unsafe { CredWriteW(&credential, 0) }
+        .ok()
+        .context("write credential")
+}
+
+#[cfg(target_os = "windows")]
+fn remove_password(service: &str, account: &str) -> anyhow::Result<()> {
+    use windows::{
+        core::PCWSTR,
+        Win32::{
+            Foundation::ERROR_NOT_FOUND,
+            Security::Credentials::{CredDeleteW, CRED_TYPE_GENERIC},
+        },
+    };
+
+    let target_name = target_name(service, account);
+    match unsafe { CredDeleteW(PCWSTR(target_name.as_ptr()), CRED_TYPE_GENERIC, 0) }.ok() {
+        Err(err) if err.code() != ERROR_NOT_FOUND.to_hresult() => {
+            Err(err).context("delete credential")
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn trim_newline(raw: &[u8]) -> String {
+    String::from_utf8_lossy(raw)
+        .trim_end_matches(['\r', '\n'])
+        .to_string()
+}
@@ -0,0 +1,461 @@
+use crate::{
+    cli_cache,
+    commands::{
+        set_provider_options::SetProviderOptionsCommand, DevpodCommandConfig, DevpodCommandError,
+    },
+    doctor::run,
+    AppHandle,
+};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+// Options of the kubernetes provider
+const CONTEXT_OPTION: &str = "KUBERNETES_CONTEXT";
+const NAMESPACE_OPTION: &str = "KUBERNETES_NAMESPACE";
+const CONFIG_OPTION: &str = "KUBERNETES_CONFIG";
+// Keeps checks against unreachable clusters short
+const REQUEST_TIMEOUT: &str = "--request-timeout=10s";
+const DEFAULT_STORAGE_CLASS_ANNOTATION: &str = "storageclass.kubernetes.io/is-default-class";
+
+#[derive(Error, Debug)]
+pub enum KubeContextError {
+    #[error("unable to read kubeconfig {0}")]
+    Read(String, #[source] io::Error),
+    #[error("unable to parse kubeconfig {0}: {1}")]
+    Parse(String, #[source] serde_yaml::Error),
+    #[error("kube context {0} doesn't exist")]
+    UnknownContext(String),
+    #[error("unable to list namespaces: {0}")]
+    Namespaces(String),
+    #[error("provider {0} doesn't connect to a kubernetes cluster")]
+    NotKubernetesProvider(String),
+    #[error("unable to access provider options")]
+    Provider(#[from] DevpodCommandError),
+    #[error("unable to check kube context")]
+    Join(#[source] tauri::Error),
+}
+impl serde::Serialize for KubeContextError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Kubeconfig {
+    current_context: Option<String>,
+    #[serde(default)]
+    contexts: Vec<NamedContext>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NamedContext {
+    name: String,
+    #[serde(default)]
+    context: ContextDetails,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ContextDetails {
+    #[serde(default)]
+    cluster: String,
+    namespace: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct KubeContext {
+    name: String,
+    cluster: String,
+    /// The namespace of the context, `default` if it has none
+    namespace: Option<String>,
+    /// The context `kubectl` uses unless told otherwise
+    current: bool,
+}
+
+/// The context and namespace a kubernetes provider instance is set to, `None` for the kubeconfig's defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct KubeProviderContext {
+    context: Option<String>,
+    namespace: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct KubeContextCheck {
+    /// Workspaces run as pods, the provider can't do anything without
+    can_list_pods: bool,
+    pods_error: Option<String>,
+    storage_classes: Vec<String>,
+    /// Used for the volumes of workspaces unless the provider is given one
+    default_storage_class: Option<String>,
+    storage_error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StorageClassList {
+    items: Vec<StorageClass>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StorageClass {
+    metadata: StorageClassMetadata,
+}
+
+#[derive(Debug, Deserialize)]
+struct StorageClassMetadata {
+    name: String,
+    #[serde(default)]
+    annotations: BTreeMap<String, String>,
+}
+
+/// The kubeconfig files in the order `kubectl` merges them, `config` being the one the provider is set to.
+fn kubeconfig_paths(config: Option<&str>) -> Vec<PathBuf> {
+    if let Some(config) = config.filter(|config| !config.is_empty()) {
+        return vec![PathBuf::from(config)];
+    }
+    if let Some(kubeconfig) = env::var_os("KUBECONFIG").filter(|value| !value.is_empty()) {
+        return env::split_paths(&kubeconfig).collect();
+    }
+
+    dirs::home_dir()
+        .map(|home| vec![home.join(".kube").join("config")])
+        .unwrap_or_default()
+}
+
+fn read_kubeconfig(path: &Path) -> Result<Kubeconfig, KubeContextError> {
+    let display = path.to_string_lossy().to_string();
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        // `KUBECONFIG` may list files that don't exist
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Kubeconfig::default()),
+        Err(err) => return Err(KubeContextError::Read(display, err)),
+    };
+    if content.trim().is_empty() {
+        return Ok(Kubeconfig::default());
+    }
+
+    serde_yaml::from_str(&content).map_err(|err| KubeContextError::Parse(display, err))
+}
+
+/// Merges kubeconfigs like `kubectl` does, the first file to set something wins.
+fn merge(kubeconfigs: Vec<Kubeconfig>) -> Vec<KubeContext> {
+    let current_context = kubeconfigs
+        .iter()
+        .find_map(|kubeconfig| kubeconfig.current_context.clone())
+        .filter(|context| !context.is_empty());
+
+    let mut contexts: Vec<KubeContext> = vec![];
+    for context in kubeconfigs
+        .into_iter()
+        .flat_map(|kubeconfig| kubeconfig.contexts)
+    {
+        if contexts.iter().any(|known| known.name == context.name) {
+            continue;
+        }
+        contexts.push(KubeContext {
+            current: current_context.as_deref() == Some(context.name.as_str()),
+            name: context.name,
+            cluster: context.context.cluster,
+            namespace: context.context.namespace,
+        });
+    }
+
+    contexts
+}
+
+fn contexts(config: Option<&str>) -> Result<Vec<KubeContext>, KubeContextError> {
+    let kubeconfigs = kubeconfig_paths(config)
+        .iter()
+        .map(|path| read_kubeconfig(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(merge(kubeconfigs))
+}
+
+/// The kubeconfig the provider is set to, `None` if it's a kubernetes provider using the default one.
+fn provider_config(
+    app_handle: &AppHandle,
+    provider: &str,
+) -> Result<Option<String>, KubeContextError> {
+    if cli_cache::provider_option(app_handle, provider, CONTEXT_OPTION)?.is_none() {
+        return Err(KubeContextError::NotKubernetesProvider(
+            provider.to_string(),
+        ));
+    }
+
+    let config = cli_cache::provider_option(app_handle, provider, CONFIG_OPTION)?;
+
+    Ok(config.filter(|config| !config.is_empty()))
+}
+
+/// `kubectl` with the context and, if the provider has one, its kubeconfig.
+fn kubectl(config: Option<&str>, context: &str, args: &[&str]) -> Result<String, String> {
+    let mut kubectl_args = vec!["--context", context, REQUEST_TIMEOUT];
+    if let Some(config) = config {
+        kubectl_args.extend(["--kubeconfig", config]);
+    }
+    kubectl_args.extend(args);
+
+    run("kubectl", &kubectl_args).map(|(stdout, _)| stdout)
+}
+
+fn parse_storage_classes(output: &str) -> Result<(Vec<String>, Option<String>), String> {
+    let list: StorageClassList = serde_json::from_str(output).map_err(|err| err.to_string())?;
+    let default = list
+        .items
+        .iter()
+        .find(|class| {
+            class
+                .metadata
+                .annotations
+                .get(DEFAULT_STORAGE_CLASS_ANNOTATION)
+                .is_some_and(|value| value == "true")
+        })
+        .map(|class| class.metadata.name.clone());
+
+    Ok((
+        list.items
+            .into_iter()
+            .map(|class| class.metadata.name)
+            .collect(),
+        default,
+    ))
+}
+
+fn check(config: Option<&str>, context: &str, namespace: &str) -> KubeContextCheck {
+    let pods = kubectl(
+        config,
+        context,
+        &["get", "pods", "--namespace", namespace, "--output", "name"],
+    );
+    let storage_classes = kubectl(
+        config,
+        context,
+        &["get", "storageclasses", "--output", "json"],
+    )
+    .and_then(|output| parse_storage_classes(&output));
+    let (storage_classes, default_storage_class, storage_error) = match storage_classes {
+        Ok((classes, _)) if classes.is_empty() => (
+            classes,
+            None,
+            Some("the cluster has no storage class, workspaces can't keep their files".to_string()),
+        ),
+        Ok((classes, default)) => (classes, default, None),
+        Err(err) => (vec![], None, Some(err)),
+    };
+
+    KubeContextCheck {
+        can_list_pods: pods.is_ok(),
+        pods_error: pods.err(),
+        storage_classes,
+        default_storage_class,
+        storage_error,
+    }
+}
+
+/// The contexts of the kubeconfig `provider` uses, the default kubeconfig without a provider.
+#[tauri::command]
+pub async fn list_kube_contexts(
+    app_handle: AppHandle,
+    provider: Option<String>,
+) -> Result<Vec<KubeContext>, KubeContextError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let config = match &provider {
+            Some(provider) => provider_config(&app_handle, provider)?,
+            None => None,
+        };
+
+        contexts(config.as_deref())
+    })
+    .await
+    .map_err(KubeContextError::Join)?
+}
+
+/// The namespaces of the cluster behind `context`, as far as the user is allowed to list them.
+#[tauri::command]
+pub async fn list_kube_namespaces(
+    app_handle: AppHandle,
+    provider: String,
+    context: String,
+) -> Result<Vec<String>, KubeContextError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let config = provider_config(&app_handle, &provider)?;
+        let output = kubectl(
+            config.as_deref(),
+            &context,
+            &[
+                "get",
+                "namespaces",
+                "--output",
+                "jsonpath={.items[*].metadata.name}",
+            ],
+        )
+        .map_err(KubeContextError::Namespaces)?;
+
+        Ok(output.split_whitespace().map(str::to_string).collect())
+    })
+    .await
+    .map_err(KubeContextError::Join)?
+}
+
+#[tauri::command]
+pub async fn get_kube_context(
+    app_handle: AppHandle,
+    provider: String,
+) -> Result<KubeProviderContext, KubeContextError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        provider_config(&app_handle, &provider)?;
+        let option = |name: &str| -> Result<Option<String>, KubeContextError> {
+            Ok(cli_cache::provider_option(&app_handle, &provider, name)?
+                .filter(|value| !value.is_empty()))
+        };
+
+        Ok(KubeProviderContext {
+            context: option(CONTEXT_OPTION)?,
+            namespace: option(NAMESPACE_OPTION)?,
+        })
+    })
+    .await
+    .map_err(KubeContextError::Join)?
+}
+
+/// Sets the context and namespace of the kubernetes provider `provider`, `None` goes back to the kubeconfig's
+/// defaults. Other instances of the provider keep theirs.
+#[tauri::command]
+pub async fn set_kube_context(
+    app_handle: AppHandle,
+    provider: String,
+    context: Option<String>,
+    namespace: Option<String>,
+) -> Result<(), KubeContextError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let config = provider_config(&app_handle, &provider)?;
+        if let Some(context) = &context {
+            if !contexts(config.as_deref())?
+                .iter()
+                .any(|known| &known.name == context)
+            {
+                return Err(KubeContextError::UnknownContext(context.clone()));
+            }
+        }
+
+        SetProviderOptionsCommand::new(
+            provider.clone(),
+            &[
+                (CONTEXT_OPTION, context.as_deref().unwrap_or_default()),
+                (NAMESPACE_OPTION, namespace.as_deref().unwrap_or_default()),
+            ],
+        )
+        .exec(&app_handle)?;
+        info!(
+            "Provider {} uses kube context {} and namespace {}",
+            provider,
+            context.as_deref().unwrap_or("default"),
+            namespace.as_deref().unwrap_or("default")
+        );
+
+        Ok(())
+    })
+    .await
+    .map_err(KubeContextError::Join)?
+}
+
+/// Whether workspaces can run in `namespace` of `context`, i.e. pods can be listed and volumes can be provisioned.
+#[tauri::command]
+pub async fn check_kube_context(
+    app_handle: AppHandle,
+    provider: String,
+    context: String,
+    namespace: String,
+) -> Result<KubeContextCheck, KubeContextError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let config = provider_config(&app_handle, &provider)?;
+        let result = check(config.as_deref(), &context, &namespace);
+        if !result.can_list_pods {
+            warn!(
+                "Unable to list pods in {} of kube context {}: {}",
+                namespace,
+                context,
+                result.pods_error.as_deref().unwrap_or_default()
+            );
+        }
+
+        Ok(result)
+    })
+    .await
+    .map_err(KubeContextError::Join)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_merge_kubeconfigs() {
+        let parse = |content| serde_yaml::from_str::<Kubeconfig>(content).unwrap();
+        let contexts = merge(vec![
+            parse(
+                r#"
+current-context: staging
+contexts:
+  - name: staging
+    context:
+      cluster: staging-cluster
+      namespace: devpod
+"#,
+            ),
+            parse(
+                r#"
+current-context: kind-local
+contexts:
+  - name: kind-local
+    context:
+      cluster: kind-local
+  - name: staging
+    context:
+      cluster: shadowed
+"#,
+            ),
+        ]);
+
+        assert_eq!(
+            contexts,
+            vec![
+                KubeContext {
+                    name: "staging".to_string(),
+                    cluster: "staging-cluster".to_string(),
+                    namespace: Some("devpod".to_string()),
+                    current: true,
+                },
+                KubeContext {
+                    name: "kind-local".to_string(),
+                    cluster: "kind-local".to_string(),
+                    namespace: None,
+                    current: false,
+                },
+            ]
+        );
+
+        let (classes, default) = parse_storage_classes(
+            r#"{"items":[{"metadata":{"name":"standard","annotations":{"storageclass.kubernetes.io/is-default-class":"true"}}},{"metadata":{"name":"fast"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(classes, vec!["standard", "fast"]);
+        assert_eq!(default, Some("standard".to_string()));
+    }
+}
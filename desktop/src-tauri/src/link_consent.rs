@@ -0,0 +1,158 @@
+use crate::{
+    audit_log::{self, AuditCategory, AuditInitiator},
+    settings::Settings,
+    AppHandle,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+use url::Url;
+
+/// Where an action came from, the ones from outside of the app need the user's consent.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum ActionOrigin {
+    /// Started from within the app, e.g. the tray menu or a notification
+    #[default]
+    App,
+    /// A `devpod://` link, e.g. on a web page
+    DeepLink,
+}
+
+impl ActionOrigin {
+    fn label(&self) -> &'static str {
+        match self {
+            ActionOrigin::App => "app",
+            ActionOrigin::DeepLink => "deep link",
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum LinkConsentError {
+    #[error("unable to save trusted domains")]
+    Persist(#[source] anyhow::Error),
+}
+impl serde::Serialize for LinkConsentError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// The host a workspace source or Pro instance points to, e.g. `github.com` for `git@github.com:loft-sh/devpod.git`. Local folders
+/// and anything else without a host have none, links to them are confirmed every time.
+pub fn source_domain(source: &str) -> Option<String> {
+    let source = source.trim();
+    let host = if source.contains("://") {
+        Url::parse(source).ok()?.host_str()?.to_string()
+    } else if let Some((user_host, _)) = source
+        .split_once(':')
+        .filter(|(user_host, _)| user_host.contains('@') && !user_host.contains('/'))
+    {
+        // scp-like git URLs
+        user_host.rsplit('@').next()?.to_string()
+    } else {
+        // e.g. `github.com/loft-sh/devpod`, `ghcr.io/loft-sh/image` or a plain host
+        let host = source.split('/').next()?;
+        if !host.contains('.') || host.starts_with('.') {
+            return None;
+        }
+        host.to_string()
+    };
+
+    Some(host.to_lowercase()).filter(|host| !host.is_empty())
+}
+
+/// Whether links to `domain` may run without asking the user.
+pub fn is_trusted(app_handle: &AppHandle, domain: Option<&str>) -> bool {
+    domain.is_some_and(|domain| {
+        Settings::trusted_link_domains(app_handle)
+            .iter()
+            .any(|trusted| trusted == domain)
+    })
+}
+
+/// Every action that comes in through the protocol ends up here, whether it ran or not.
+pub fn audit(origin: ActionOrigin, action: &str, domain: Option<&str>, decision: &str) {
+    info!(
+        target: module_path!(),
+        origin = origin.label(),
+        link_action = action,
+        link_domain = domain.unwrap_or("none"),
+        decision = decision;
+        "Protocol action"
+    );
+}
+
+/// The user decided about a link that needed consent. With `remember`, links to the domain run right away from now
+/// on.
+#[tauri::command]
+pub fn decide_link_action(
+    app_handle: AppHandle,
+    action: String,
+    domain: Option<String>,
+    allowed: bool,
+    remember: bool,
+) -> Result<(), LinkConsentError> {
+    let decision = match (allowed, remember) {
+        (true, true) => "allowed, domain trusted",
+        (true, false) => "allowed once",
+        (false, _) => "declined",
+    };
+    audit(ActionOrigin::DeepLink, &action, domain.as_deref(), decision);
+
+    let Some(domain) = domain.filter(|_| allowed && remember) else {
+        return Ok(());
+    };
+    let mut trusted = Settings::trusted_link_domains(&app_handle);
+    if !trusted.contains(&domain) {
+        trusted.push(domain.clone());
+        Settings::set_trusted_link_domains(&app_handle, &trusted)
+            .map_err(LinkConsentError::Persist)?;
+        audit_log::record(
+            AuditInitiator::DeepLink,
+            AuditCategory::Settings,
+            "trust link domain",
+            Some(&domain),
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_source_domain() {
+        let domain = |source| source_domain(source);
+
+        assert_eq!(
+            domain("https://GitHub.com/loft-sh/devpod"),
+            Some("github.com".to_string())
+        );
+        assert_eq!(
+            domain("git@gitlab.com:group/project.git"),
+            Some("gitlab.com".to_string())
+        );
+        assert_eq!(
+            domain("github.com/loft-sh/devpod@main"),
+            Some("github.com".to_string())
+        );
+        assert_eq!(
+            domain("ghcr.io/loft-sh/image@sha256:0123"),
+            Some("ghcr.io".to_string())
+        );
+        assert_eq!(domain("devpod.pro"), Some("devpod.pro".to_string()));
+        assert_eq!(domain("/home/user/project"), None);
+        assert_eq!(domain("./project"), None);
+        assert_eq!(domain("C:\\Users\\user\\project"), None);
+        assert_eq!(domain("project"), None);
+    }
+}
@@ -0,0 +1,482 @@
+use crate::{
+    commands::{workspace_logs::WorkspaceLogsCommand, DevpodCommandConfig},
+    debug_console, payloads, redaction, AppHandle,
+};
+use lazy_static::lazy_static;
+use log::{error, warn};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    thread,
+    time::Duration,
+};
+use tauri::{Manager, Window, WindowEvent};
+use thiserror::Error;
+use ts_rs::TS;
+
+// WARN: needs to match the event name in `client.ts`
+const LOG_STREAM_EVENT: &str = "log_stream";
+const LOG_STREAM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+// Workspace logs are fetched through the CLI, which is a lot more expensive than reading a local file
+const WORKSPACE_LOG_POLL_INTERVAL: Duration = Duration::from_secs(5);
+// Lines of history sent when a stream starts
+const INITIAL_LINES: usize = 200;
+
+lazy_static! {
+    static ref LOG_STREAMS: Mutex<HashMap<String, Arc<LogStream>>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Error, Debug)]
+pub enum LogStreamError {
+    #[error("unable to get log dir")]
+    NoDir,
+    #[error("log stream {0} not found")]
+    NotFound(String),
+}
+impl serde::Serialize for LogStreamError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct LogStreamFilter {
+    /// Least severe level to include, i.e. `warn` includes warnings and errors
+    level: Option<LogLevel>,
+    target: Option<String>,
+    text: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum LogSource {
+    Desktop,
+    Workspace,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct LogLine {
+    source: LogSource,
+    timestamp: Option<String>,
+    level: Option<LogLevel>,
+    target: Option<String>,
+    message: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct LogStreamEvent {
+    stream_id: String,
+    lines: Vec<LogLine>,
+}
+
+#[derive(Debug, Default)]
+struct LogStream {
+    filter: RwLock<LogStreamFilter>,
+    paused: AtomicBool,
+    stopped: AtomicBool,
+}
+
+impl LogStreamFilter {
+    fn matches(&self, line: &LogLine) -> bool {
+        // lines we can't parse are continuations of multiline messages or come from the agent, always keep them
+        let level_matches = match (self.level, line.level) {
+            (Some(max_level), Some(level)) => level <= max_level,
+            _ => true,
+        };
+        let target_matches = match (&self.target, &line.target) {
+            (Some(filter), Some(target)) => target.contains(filter.as_str()),
+            _ => true,
+        };
+        let text_matches = self
+            .text
+            .as_ref()
+            .is_none_or(|text| line.message.to_lowercase().contains(&text.to_lowercase()));
+
+        level_matches && target_matches && text_matches
+    }
+}
+
+/// Starts following the desktop log, and the agent log of `workspace_id` if set, and sends new lines to `window` as
+/// `log_stream` events. Streams run until `stop_log_stream` is called or the window is closed.
+#[tauri::command]
+pub fn stream_logs(
+    app_handle: AppHandle,
+    window: Window,
+    stream_id: String,
+    filter: Option<LogStreamFilter>,
+    workspace_id: Option<String>,
+) -> Result<(), LogStreamError> {
+    let log_dir = app_handle
+        .path_resolver()
+        .app_log_dir()
+        .ok_or(LogStreamError::NoDir)?;
+    let log_path = log_dir.join(format!("{}.log", app_handle.package_info().name));
+
+    let stream = Arc::new(LogStream {
+        filter: RwLock::new(filter.unwrap_or_default()),
+        ..LogStream::default()
+    });
+    if let Some(previous) = LOG_STREAMS
+        .lock()
+        .unwrap()
+        .insert(stream_id.clone(), stream.clone())
+    {
+        previous.stopped.store(true, Ordering::Relaxed);
+    }
+
+    let stream_on_close = stream.clone();
+    let stream_id_on_close = stream_id.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            remove_stream(&stream_id_on_close, &stream_on_close);
+        }
+    });
+
+    if let Some(workspace_id) = workspace_id {
+        let window = window.clone();
+        let stream_id = stream_id.clone();
+        let stream = stream.clone();
+        thread::spawn(move || {
+            let _task =
+                debug_console::track_task("workspace log stream", Some(workspace_id.clone()));
+            follow_workspace_log(&window, &stream_id, &stream, workspace_id)
+        });
+    }
+    thread::spawn(move || {
+        let _task = debug_console::track_task("desktop log stream", Some(stream_id.clone()));
+        tail_desktop_log(&window, &stream_id, &stream, &log_path)
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_log_stream_filter(
+    stream_id: String,
+    filter: LogStreamFilter,
+) -> Result<(), LogStreamError> {
+    let stream = get_stream(&stream_id)?;
+    *stream.filter.write().unwrap() = filter;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn pause_log_stream(stream_id: String) -> Result<(), LogStreamError> {
+    get_stream(&stream_id)?
+        .paused
+        .store(true, Ordering::Relaxed);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn resume_log_stream(stream_id: String) -> Result<(), LogStreamError> {
+    get_stream(&stream_id)?
+        .paused
+        .store(false, Ordering::Relaxed);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_log_stream(stream_id: String) -> Result<(), LogStreamError> {
+    let stream = LOG_STREAMS
+        .lock()
+        .unwrap()
+        .remove(&stream_id)
+        .ok_or_else(|| LogStreamError::NotFound(stream_id.clone()))?;
+    stream.stopped.store(true, Ordering::Relaxed);
+
+    Ok(())
+}
+
+/// Stops `stream` and forgets about it, unless `stream_id` has been reused for a newer stream in the meantime.
+fn remove_stream(stream_id: &str, stream: &Arc<LogStream>) {
+    stream.stopped.store(true, Ordering::Relaxed);
+
+    let mut streams = LOG_STREAMS.lock().unwrap();
+    if streams
+        .get(stream_id)
+        .is_some_and(|current| Arc::ptr_eq(current, stream))
+    {
+        streams.remove(stream_id);
+    }
+}
+
+fn get_stream(stream_id: &str) -> Result<Arc<LogStream>, LogStreamError> {
+    LOG_STREAMS
+        .lock()
+        .unwrap()
+        .get(stream_id)
+        .cloned()
+        .ok_or_else(|| LogStreamError::NotFound(stream_id.to_string()))
+}
+
+fn emit(window: &Window, stream_id: &str, stream: &LogStream, lines: Vec<LogLine>) -> bool {
+    let filter = stream.filter.read().unwrap().clone();
+    let lines: Vec<_> = lines
+        .into_iter()
+        .filter(|line| filter.matches(line))
+        .collect();
+    if lines.is_empty() {
+        return true;
+    }
+
+    let event = LogStreamEvent {
+        stream_id: stream_id.to_string(),
+        lines,
+    };
+    if let Err(err) = payloads::emit(window, LOG_STREAM_EVENT, event) {
+        // the window is gone, nobody is listening anymore
+        warn!("Failed to emit log stream event: {}", err);
+        return false;
+    }
+
+    true
+}
+
+fn tail_desktop_log(window: &Window, stream_id: &str, stream: &Arc<LogStream>, log_path: &Path) {
+    let mut tail = LogTail::new(log_path.to_path_buf());
+    let mut lines = tail.last_lines(INITIAL_LINES);
+
+    loop {
+        if stream.stopped.load(Ordering::Relaxed) {
+            return;
+        }
+        // While paused, new lines are buffered and sent after resuming
+        if !stream.paused.load(Ordering::Relaxed) {
+            let batch = lines
+                .drain(..)
+                .map(|line| parse_line(&line, LogSource::Desktop))
+                .collect();
+            if !emit(window, stream_id, stream, batch) {
+                remove_stream(stream_id, stream);
+                return;
+            }
+        }
+
+        thread::sleep(LOG_STREAM_POLL_INTERVAL);
+        match tail.read_new_lines() {
+            Ok(new_lines) => lines.extend(new_lines),
+            Err(err) => error!("Failed to read log file {:?}: {}", log_path, err),
+        }
+    }
+}
+
+/// Follows the agent log of `workspace_id`. It lives on the workspace machine, so it's fetched periodically and
+/// only the lines we haven't seen yet are sent.
+fn follow_workspace_log(
+    window: &Window,
+    stream_id: &str,
+    stream: &Arc<LogStream>,
+    workspace_id: String,
+) {
+    let app_handle = window.app_handle();
+    let mut seen_lines: Option<usize> = None;
+    let mut failing = false;
+
+    loop {
+        if stream.stopped.load(Ordering::Relaxed) {
+            return;
+        }
+        // While paused, the next fetch picks up everything that's been written in the meantime
+        if !stream.paused.load(Ordering::Relaxed) {
+            match WorkspaceLogsCommand::new(workspace_id.clone()).exec(&app_handle) {
+                Ok(logs) => {
+                    failing = false;
+                    let lines: Vec<_> = logs.lines().collect();
+                    let skip = match seen_lines {
+                        Some(seen) if seen <= lines.len() => seen,
+                        // the log has been rotated or the workspace recreated, start over
+                        Some(_) => 0,
+                        None => lines.len().saturating_sub(INITIAL_LINES),
+                    };
+                    seen_lines = Some(lines.len());
+
+                    let batch = lines[skip..]
+                        .iter()
+                        .map(|line| parse_line(&redaction::redact(line), LogSource::Workspace))
+                        .collect();
+                    if !emit(window, stream_id, stream, batch) {
+                        remove_stream(stream_id, stream);
+                        return;
+                    }
+                }
+                // the workspace is most likely stopped, only report this once until it's back
+                Err(err) if !failing => {
+                    failing = true;
+                    error!(
+                        target: module_path!(), workspace_id = workspace_id.as_str();
+                        "Failed to get workspace logs: {}", err
+                    );
+                }
+                Err(_) => {}
+            }
+        }
+
+        thread::sleep(WORKSPACE_LOG_POLL_INTERVAL);
+    }
+}
+
+/// Follows a log file across rotations by remembering the read offset.
+struct LogTail {
+    path: PathBuf,
+    offset: u64,
+    remainder: String,
+}
+
+impl LogTail {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            offset: 0,
+            remainder: String::new(),
+        }
+    }
+
+    fn last_lines(&mut self, count: usize) -> Vec<String> {
+        let lines = self.read_new_lines().unwrap_or_default();
+        let skip = lines.len().saturating_sub(count);
+
+        lines.into_iter().skip(skip).collect()
+    }
+
+    fn read_new_lines(&mut self) -> std::io::Result<Vec<String>> {
+        let mut file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            // the log plugin hasn't created the file yet or it has just been rotated
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            // the file has been rotated, start over
+            self.offset = 0;
+            self.remainder.clear();
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        self.offset += buf.len() as u64;
+
+        self.remainder.push_str(&String::from_utf8_lossy(&buf));
+        // keep incomplete lines around until they are finished
+        let mut lines: Vec<String> = self.remainder.split('\n').map(String::from).collect();
+        self.remainder = lines.pop().unwrap_or_default();
+
+        Ok(lines)
+    }
+}
+
+// Format of the log plugin, i.e. `[2023-05-10][12:00:00][devpod_desktop::workspaces][INFO] message`
+fn parse_line(line: &str, source: LogSource) -> LogLine {
+    lazy_static! {
+        static ref LINE_REGEX: Regex = Regex::new(
+            r"^\[(\d{4}-\d{2}-\d{2})\]\[(\d{2}:\d{2}:\d{2})\]\[([^\]]*)\]\[(\w+)\] ?(.*)$"
+        )
+        .unwrap();
+    }
+
+    let line = line.trim_end_matches('\r');
+    match LINE_REGEX.captures(line) {
+        Some(captures) => LogLine {
+            source,
+            timestamp: Some(format!("{}T{}", &captures[1], &captures[2])),
+            target: Some(captures[3].to_string()),
+            level: parse_level(&captures[4]),
+            message: captures[5].to_string(),
+        },
+        None => LogLine {
+            source,
+            timestamp: None,
+            level: None,
+            target: None,
+            message: line.to_string(),
+        },
+    }
+}
+
+fn parse_level(level: &str) -> Option<LogLevel> {
+    match level.to_uppercase().as_str() {
+        "ERROR" => Some(LogLevel::Error),
+        "WARN" => Some(LogLevel::Warn),
+        "INFO" => Some(LogLevel::Info),
+        "DEBUG" => Some(LogLevel::Debug),
+        "TRACE" => Some(LogLevel::Trace),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_plugin_format() {
+        let line = parse_line(
+            "[2023-05-10][12:00:01][devpod_desktop::workspaces][WARN] something happened",
+            LogSource::Desktop,
+        );
+
+        assert_eq!(line.timestamp.as_deref(), Some("2023-05-10T12:00:01"));
+        assert_eq!(line.target.as_deref(), Some("devpod_desktop::workspaces"));
+        assert_eq!(line.level, Some(LogLevel::Warn));
+        assert_eq!(line.message, "something happened");
+    }
+
+    #[test]
+    fn should_filter_lines() {
+        let filter = LogStreamFilter {
+            level: Some(LogLevel::Warn),
+            target: Some("workspaces".to_string()),
+            text: Some("HAPPENED".to_string()),
+        };
+        let matching = parse_line(
+            "[2023-05-10][12:00:01][devpod_desktop::workspaces][ERROR] something happened",
+            LogSource::Desktop,
+        );
+        let too_verbose = parse_line(
+            "[2023-05-10][12:00:01][devpod_desktop::workspaces][INFO] something happened",
+            LogSource::Desktop,
+        );
+        let unparsed = parse_line(
+            "  continuation of something that happened",
+            LogSource::Desktop,
+        );
+
+        assert!(filter.matches(&matching));
+        assert!(!filter.matches(&too_verbose));
+        assert!(filter.matches(&unparsed));
+    }
+}
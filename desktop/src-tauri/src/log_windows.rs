@@ -0,0 +1,175 @@
+use crate::{
+    log_stream::{self, LogStreamFilter},
+    window::WindowHelper,
+    AppHandle,
+};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use serde::Serialize;
+use std::{collections::BTreeMap, sync::Mutex};
+use tauri::{Manager, Window, WindowEvent};
+use thiserror::Error;
+use ts_rs::TS;
+
+lazy_static! {
+    static ref LOG_WINDOWS: Mutex<BTreeMap<String, LogWindow>> = Mutex::new(BTreeMap::new());
+}
+// WARN: needs to match the event name in `client.ts`
+const LOG_WINDOWS_CHANGED_EVENT: &str = "log_windows_changed";
+const LOG_WINDOW_LABEL_PREFIX: &str = "logs-";
+
+/// A window of its own for the logs of a single workspace. Its state lives here rather than in the window, so that
+/// it's kept across reloads of either window.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct LogWindow {
+    /// Also the ID of the window's log stream
+    label: String,
+    workspace_id: String,
+    filter: LogStreamFilter,
+}
+
+#[derive(Error, Debug)]
+pub enum LogWindowError {
+    #[error("{0} isn't a log window")]
+    NotFound(String),
+    #[error("unable to open log window")]
+    Open(#[source] anyhow::Error),
+}
+impl serde::Serialize for LogWindowError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// Window labels may only contain alphanumerics, `-`, `/`, `:` and `_`.
+fn label(workspace_id: &str) -> String {
+    let workspace_id: String = workspace_id
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect();
+
+    format!("{}{}", LOG_WINDOW_LABEL_PREFIX, workspace_id)
+}
+
+/// Whether the window shows the logs of a workspace.
+pub fn is_log_window(label: &str) -> bool {
+    label.starts_with(LOG_WINDOW_LABEL_PREFIX)
+}
+
+fn changed(app_handle: &AppHandle, windows: &BTreeMap<String, LogWindow>) {
+    let windows: Vec<&LogWindow> = windows.values().collect();
+    if let Err(err) = app_handle.emit_all(LOG_WINDOWS_CHANGED_EVENT, windows) {
+        warn!("Failed to send log windows: {}", err);
+    }
+}
+
+fn forget(app_handle: &AppHandle, label: &str) {
+    let mut windows = LOG_WINDOWS.lock().unwrap();
+    if windows.remove(label).is_some() {
+        changed(app_handle, &windows);
+    }
+}
+
+/// Opens the logs of the workspace in a window of their own, or focuses the one that's already open.
+#[tauri::command]
+pub fn open_log_window(
+    app_handle: AppHandle,
+    workspace_id: String,
+) -> Result<LogWindow, LogWindowError> {
+    let label = label(&workspace_id);
+    let mut windows = LOG_WINDOWS.lock().unwrap();
+    if let Some(window) = app_handle.get_window(&label) {
+        if let Err(err) = window.set_focus() {
+            warn!("Failed to focus log window of {}: {}", workspace_id, err);
+        }
+        if let Some(log_window) = windows.get(&label) {
+            return Ok(log_window.clone());
+        }
+    }
+
+    info!("Opening log window of {}", workspace_id);
+    WindowHelper::new(app_handle.clone())
+        .new_log_window(label.clone(), &workspace_id)
+        .map_err(LogWindowError::Open)?;
+    let log_window = LogWindow {
+        label: label.clone(),
+        workspace_id,
+        filter: LogStreamFilter::default(),
+    };
+    windows.insert(label, log_window.clone());
+    changed(&app_handle, &windows);
+
+    Ok(log_window)
+}
+
+#[tauri::command]
+pub fn close_log_window(app_handle: AppHandle, workspace_id: String) {
+    if let Some(window) = app_handle.get_window(&label(&workspace_id)) {
+        if let Err(err) = window.close() {
+            warn!("Failed to close log window of {}: {}", workspace_id, err);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn list_log_windows() -> Vec<LogWindow> {
+    LOG_WINDOWS.lock().unwrap().values().cloned().collect()
+}
+
+/// The state of the calling log window, which asks for it whenever it's loaded. Its state is dropped once it's
+/// closed.
+#[tauri::command]
+pub fn get_log_window(app_handle: AppHandle, window: Window) -> Result<LogWindow, LogWindowError> {
+    let label = window.label().to_string();
+    let log_window = LOG_WINDOWS
+        .lock()
+        .unwrap()
+        .get(&label)
+        .cloned()
+        .ok_or_else(|| LogWindowError::NotFound(label.clone()))?;
+
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            forget(&app_handle, &label);
+        }
+    });
+
+    Ok(log_window)
+}
+
+/// Keeps the filter of the calling log window and applies it to its stream, if it's already running.
+#[tauri::command]
+pub fn set_log_window_filter(
+    window: Window,
+    filter: LogStreamFilter,
+) -> Result<(), LogWindowError> {
+    let label = window.label().to_string();
+    let mut windows = LOG_WINDOWS.lock().unwrap();
+    let log_window = windows
+        .get_mut(&label)
+        .ok_or_else(|| LogWindowError::NotFound(label.clone()))?;
+    log_window.filter = filter.clone();
+    // Fails if the window hasn't started streaming yet, it starts with the stored filter then
+    let _ = log_stream::set_log_stream_filter(label, filter);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_derive_valid_window_labels() {
+        assert_eq!(label("my-project"), "logs-my-project");
+        assert_eq!(label("org/repo.git"), "logs-org_repo_git");
+    }
+}
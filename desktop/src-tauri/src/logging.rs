@@ -1,6 +1,223 @@
-use log::LevelFilter;
-use tauri::{plugin::TauriPlugin, Wry};
-use tauri_plugin_log::LogTarget;
+use crate::{
+    error_reporting, redaction,
+    scheduler::{self, Schedule},
+    settings::{LogFormat, Settings},
+    AppHandle,
+};
+use anyhow::Context;
+use chrono::{Local, Utc};
+use lazy_static::lazy_static;
+use log::{
+    error, info,
+    kv::{self, Key, Visitor},
+    Level, LevelFilter, Metadata, Record,
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::Arguments,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        RwLock,
+    },
+    time::{Duration, SystemTime},
+};
+use tauri::{plugin::TauriPlugin, Config, Wry};
+use tauri_plugin_log::{fern::FormatCallback, LogTarget, RotationStrategy};
+use thiserror::Error;
+use ts_rs::TS;
+
+// The log plugin starts a new file once the current one exceeds this size
+const MAX_LOG_FILE_SIZE: u128 = 10 * 1024 * 1024;
+const MAX_LOG_FILE_AGE: Duration = Duration::from_secs(60 * 60 * 24);
+const LOG_MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const LOG_MAINTENANCE_JITTER: Duration = Duration::from_secs(5 * 60);
+const COMPRESSED_LOG_EXTENSION: &str = "gz";
+
+#[cfg(debug_assertions)] // only enable during development
+const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::Debug;
+#[cfg(not(debug_assertions))] // only enable in release builds
+const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::Info;
+const CRATE_NAME: &str = env!("CARGO_CRATE_NAME");
+
+lazy_static! {
+    // The plugin lets everything through, this decides what actually gets logged and can be changed at runtime
+    static ref LOG_LEVELS: RwLock<LevelConfig> = RwLock::new(LevelConfig::default());
+    // When the current log file was started. It's truncated rather than replaced while in use, so it keeps the
+    // creation time of the first one
+    static ref LOG_FILE_STARTED: RwLock<Option<SystemTime>> = RwLock::new(None);
+}
+
+// The plugin is built before settings can be read, so the formatter checks this on every record
+static JSON_FORMAT: AtomicBool = AtomicBool::new(false);
+
+#[derive(Error, Debug)]
+pub enum LogError {
+    #[error("unable to get log dir")]
+    NoDir,
+    #[error("unable to read log dir")]
+    Read(#[source] std::io::Error),
+    #[error("invalid log level {0}")]
+    InvalidLevel(String),
+}
+impl serde::Serialize for LogError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Default, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct LogDiskUsage {
+    path: String,
+    #[ts(type = "number")]
+    current_bytes: u64,
+    #[ts(type = "number")]
+    archived_bytes: u64,
+    archived_files: usize,
+}
+
+/// The current log filter, levels are `off`, `error`, `warn`, `info`, `debug` or `trace`.
+#[derive(Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct LogLevels {
+    default: String,
+    modules: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LevelConfig {
+    default: LevelFilter,
+    // Keyed by module path, either `custom_protocol` within the app or a full path like `reqwest::connect`
+    modules: BTreeMap<String, LevelFilter>,
+}
+
+impl Default for LevelConfig {
+    fn default() -> Self {
+        Self {
+            default: DEFAULT_LOG_LEVEL,
+            modules: BTreeMap::new(),
+        }
+    }
+}
+
+impl LevelConfig {
+    /// The level of the most specific module matching `target`.
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let local_target = target
+            .strip_prefix(CRATE_NAME)
+            .and_then(|target| target.strip_prefix("::"));
+
+        self.modules
+            .iter()
+            .filter(|(module, _)| {
+                is_module_of(target, module)
+                    || local_target.is_some_and(|target| is_module_of(target, module))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    fn max(&self) -> LevelFilter {
+        self.modules.values().copied().fold(self.default, Ord::max)
+    }
+
+    fn to_levels(&self) -> LogLevels {
+        LogLevels {
+            default: self.default.as_str().to_lowercase(),
+            modules: self
+                .modules
+                .iter()
+                .map(|(module, level)| (module.clone(), level.as_str().to_lowercase()))
+                .collect(),
+        }
+    }
+}
+
+fn is_module_of(target: &str, module: &str) -> bool {
+    target == module
+        || target
+            .strip_prefix(module)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
+
+fn is_enabled(metadata: &Metadata) -> bool {
+    metadata.level() <= LOG_LEVELS.read().unwrap().level_for(metadata.target())
+}
+
+fn update_levels(update: impl FnOnce(&mut LevelConfig)) -> LogLevels {
+    let mut levels = LOG_LEVELS.write().unwrap();
+    update(&mut levels);
+    // Skips formatting records nobody is interested in
+    log::set_max_level(levels.max());
+
+    levels.to_levels()
+}
+
+/// Changes the log filter without restarting. Sets the level of `module`, or the default level if no module is given.
+/// Passing no level removes the module's override, or resets the whole filter if no module is given either.
+#[tauri::command]
+pub fn set_log_level(level: Option<String>, module: Option<String>) -> Result<LogLevels, LogError> {
+    let level = level
+        .map(|level| LevelFilter::from_str(&level).map_err(|_| LogError::InvalidLevel(level)))
+        .transpose()?;
+
+    let levels = update_levels(|levels| match (module.clone(), level) {
+        (Some(module), Some(level)) => {
+            levels.modules.insert(module, level);
+        }
+        (Some(module), None) => {
+            levels.modules.remove(&module);
+        }
+        (None, Some(level)) => levels.default = level,
+        (None, None) => *levels = LevelConfig::default(),
+    });
+    info!(
+        "Log level of {} set to {:?}",
+        module.as_deref().unwrap_or("all modules"),
+        level
+    );
+
+    Ok(levels)
+}
+
+#[tauri::command]
+pub fn get_log_levels() -> LogLevels {
+    LOG_LEVELS.read().unwrap().to_levels()
+}
+
+pub fn is_debug_logging_enabled() -> bool {
+    LOG_LEVELS.read().unwrap().default >= LevelFilter::Debug
+}
+
+/// Switches between debug logging and the default level, returns whether debug logging is enabled now.
+pub fn toggle_debug_logging() -> bool {
+    let mut enabled = false;
+    update_levels(|levels| {
+        enabled = levels.default < LevelFilter::Debug;
+        levels.default = if enabled {
+            LevelFilter::Debug
+        } else {
+            DEFAULT_LOG_LEVEL
+        };
+    });
+    info!(
+        "Debug logging {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+
+    enabled
+}
 
 #[allow(unused_variables)]
 pub fn build_plugin() -> TauriPlugin<Wry> {
@@ -15,13 +232,282 @@ pub fn build_plugin() -> TauriPlugin<Wry> {
     #[cfg(not(debug_assertions))] // only enable in release builds
     targets.push(LogTarget::LogDir);
 
-    let builder = tauri_plugin_log::Builder::default().targets(targets);
+    tauri_plugin_log::Builder::default()
+        .targets(targets)
+        .format(format_record)
+        .max_file_size(MAX_LOG_FILE_SIZE)
+        .rotation_strategy(RotationStrategy::KeepAll)
+        .level(LevelFilter::Trace)
+        .filter(is_enabled)
+        .build()
+}
 
-    #[cfg(debug_assertions)] // only enable during development
-    let builder = builder.level(LevelFilter::Debug);
+/// Collects the key-values of a record, i.e. `info!(target: module_path!(), workspace_id = id; "...")`.
+#[derive(Default)]
+struct Fields(Vec<(String, String)>);
 
-    #[cfg(not(debug_assertions))] // only enable in release builds
-    let builder = builder.level(LevelFilter::Info);
+impl<'kvs> Visitor<'kvs> for Fields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        self.0.push((
+            key.to_string(),
+            redaction::redact(&value.to_string()).into_owned(),
+        ));
 
-    builder.build()
+        Ok(())
+    }
+}
+
+fn format_record(out: FormatCallback, message: &Arguments, record: &Record) {
+    let mut fields = Fields::default();
+    let _ = record.key_values().visit(&mut fields);
+    let message = message.to_string();
+    let message = redaction::redact(&message);
+    if record.level() == Level::Error {
+        error_reporting::capture(record.target(), &message, &fields.0);
+    }
+
+    if JSON_FORMAT.load(Ordering::Relaxed) {
+        out.finish(format_args!("{}", format_json(record, &message, &fields.0)))
+    } else {
+        out.finish(format_args!(
+            "[{}][{}][{}] {}{}",
+            Local::now().format("%Y-%m-%d][%H:%M:%S"),
+            record.target(),
+            record.level(),
+            message,
+            fields
+                .0
+                .iter()
+                .map(|(key, value)| format!(" {}={}", key, value))
+                .collect::<String>()
+        ))
+    }
+}
+
+/// One JSON object per line. Fields are top level so that they line up with the fields of cli and agent logs.
+fn format_json(record: &Record, message: &str, fields: &[(String, String)]) -> String {
+    let mut line = serde_json::Map::new();
+    for (key, value) in fields {
+        line.insert(key.clone(), serde_json::Value::String(value.clone()));
+    }
+    line.insert("time".to_string(), Utc::now().to_rfc3339().into());
+    line.insert(
+        "level".to_string(),
+        record.level().as_str().to_lowercase().into(),
+    );
+    line.insert("target".to_string(), record.target().into());
+    line.insert("msg".to_string(), message.into());
+
+    serde_json::Value::Object(line).to_string()
+}
+
+/// Rotates the current log file if it has become too old while the app wasn't running.
+/// Needs to run before the log plugin opens the file.
+pub fn rotate_at_startup(config: &Config, app_name: &str) {
+    if let Some(log_dir) = tauri::api::path::app_log_dir(config) {
+        // Logging isn't set up yet, nothing to report to
+        let _ = rotate_by_age(&log_dir, app_name, false);
+    }
+}
+
+/// Moves the current log file aside if it has been started more than a day ago. While the log plugin has the file
+/// open, it is copied aside and truncated instead, lines logged in between are lost.
+fn rotate_by_age(log_dir: &Path, app_name: &str, in_use: bool) -> anyhow::Result<()> {
+    let current_path = current_log_path(log_dir, app_name);
+    let mut log_file_started = LOG_FILE_STARTED.write().unwrap();
+    let metadata = match fs::metadata(&current_path) {
+        Ok(metadata) => metadata,
+        // Nothing logged yet, the next line starts a new file
+        Err(_) => {
+            *log_file_started = Some(SystemTime::now());
+            return Ok(());
+        }
+    };
+    let started = log_file_started
+        .or_else(|| metadata.created().or_else(|_| metadata.modified()).ok())
+        .unwrap_or_else(SystemTime::now);
+    *log_file_started = Some(started);
+
+    let age = SystemTime::now()
+        .duration_since(started)
+        .unwrap_or_default();
+    if age < MAX_LOG_FILE_AGE {
+        return Ok(());
+    }
+
+    // Same naming scheme the log plugin uses for files it rotates by size
+    let rotated_path = log_dir.join(format!(
+        "{}_{}.log",
+        app_name,
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    ));
+    if in_use {
+        fs::copy(&current_path, rotated_path).context("copy log file")?;
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&current_path)
+            .and_then(|file| file.set_len(0))
+            .context("truncate log file")?;
+    } else {
+        fs::rename(&current_path, rotated_path).context("move log file")?;
+    }
+    *log_file_started = Some(SystemTime::now());
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_log_disk_usage(app_handle: AppHandle) -> Result<LogDiskUsage, LogError> {
+    let log_dir = app_handle
+        .path_resolver()
+        .app_log_dir()
+        .ok_or(LogError::NoDir)?;
+    let current_path = current_log_path(&log_dir, &app_handle.package_info().name);
+
+    let mut usage = LogDiskUsage {
+        path: log_dir.to_string_lossy().to_string(),
+        ..LogDiskUsage::default()
+    };
+    for entry in fs::read_dir(&log_dir).map_err(LogError::Read)? {
+        let entry = entry.map_err(LogError::Read)?;
+        let size = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        if entry.path() == current_path {
+            usage.current_bytes = size;
+        } else {
+            usage.archived_bytes += size;
+            usage.archived_files += 1;
+        }
+    }
+
+    Ok(usage)
+}
+
+fn current_log_path(log_dir: &Path, app_name: &str) -> PathBuf {
+    log_dir.join(format!("{}.log", app_name))
+}
+
+/// Starts a new log file once a day, compresses rotated log files and removes them once they are older than the
+/// configured retention.
+fn maintain_logs(app_handle: &AppHandle) -> anyhow::Result<()> {
+    let log_dir = app_handle
+        .path_resolver()
+        .app_log_dir()
+        .context("no log dir")?;
+    let app_name = &app_handle.package_info().name;
+    if let Err(err) = rotate_by_age(&log_dir, app_name, true) {
+        error!("Failed to rotate log file: {}", err);
+    }
+    let current_path = current_log_path(&log_dir, app_name);
+    let retention =
+        Duration::from_secs(60 * 60 * 24 * u64::from(Settings::log_retention_days(app_handle)));
+
+    for entry in fs::read_dir(&log_dir)? {
+        let path = entry?.path();
+        if path == current_path {
+            continue;
+        }
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("log") => {
+                if let Err(err) = compress(&path) {
+                    error!("Failed to compress log file {:?}: {}", path, err);
+                }
+            }
+            Some(COMPRESSED_LOG_EXTENSION) => {
+                let age = fs::metadata(&path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                    .unwrap_or_default();
+                if age > retention {
+                    info!("Removing log file {:?}", path);
+                    fs::remove_file(&path)?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn compress(path: &Path) -> anyhow::Result<()> {
+    let mut compressed_path = path.as_os_str().to_owned();
+    compressed_path.push(format!(".{}", COMPRESSED_LOG_EXTENSION));
+
+    let data = fs::read(path)?;
+    let file = fs::File::create(&compressed_path)?;
+    let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    encoder.write_all(&data)?;
+    encoder.finish()?;
+
+    fs::remove_file(path).context("remove uncompressed log file")
+}
+
+pub fn setup(app_handle: &AppHandle) {
+    // The plugin raised the max level to let everything through
+    log::set_max_level(LOG_LEVELS.read().unwrap().max());
+    JSON_FORMAT.store(
+        Settings::log_format(app_handle) == LogFormat::Json,
+        Ordering::Relaxed,
+    );
+
+    scheduler::Task::new(
+        "log maintenance",
+        Schedule::every(LOG_MAINTENANCE_INTERVAL),
+        |app_handle| {
+            if let Err(err) = maintain_logs(app_handle) {
+                error!("Failed to clean up log files: {}", err);
+            }
+        },
+    )
+    .jitter(LOG_MAINTENANCE_JITTER)
+    .defer_on_battery()
+    .register();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_format_json() {
+        let record = Record::builder()
+            .level(log::Level::Warn)
+            .target("devpod_desktop::workspaces")
+            .build();
+        let fields = vec![
+            ("workspace_id".to_string(), "my-workspace".to_string()),
+            ("msg".to_string(), "overwritten".to_string()),
+        ];
+
+        let line: serde_json::Value =
+            serde_json::from_str(&format_json(&record, "Workspace started", &fields)).unwrap();
+
+        assert_eq!(line["level"], "warn");
+        assert_eq!(line["target"], "devpod_desktop::workspaces");
+        assert_eq!(line["msg"], "Workspace started");
+        assert_eq!(line["workspace_id"], "my-workspace");
+    }
+
+    #[test]
+    fn should_use_most_specific_module_level() {
+        let levels = LevelConfig {
+            default: LevelFilter::Info,
+            modules: BTreeMap::from([
+                ("custom_protocol".to_string(), LevelFilter::Trace),
+                ("reqwest".to_string(), LevelFilter::Warn),
+                ("reqwest::connect".to_string(), LevelFilter::Debug),
+            ]),
+        };
+
+        assert_eq!(
+            levels.level_for(&format!("{}::custom_protocol", CRATE_NAME)),
+            LevelFilter::Trace
+        );
+        assert_eq!(levels.level_for("reqwest::async_impl"), LevelFilter::Warn);
+        assert_eq!(levels.level_for("reqwest::connect"), LevelFilter::Debug);
+        assert_eq!(levels.level_for("reqwest_middleware"), LevelFilter::Info);
+        assert_eq!(levels.max(), LevelFilter::Trace);
+    }
 }
@@ -8,27 +8,124 @@
 extern crate objc;
 
 mod action_logs;
+mod app_lock;
+mod audit_log;
+mod autostart;
+mod browser_ide;
+mod cert_pinning;
+mod cli_cache;
+mod cli_integrity;
+#[cfg(feature = "enable-updater")]
+mod cli_manager;
+mod cli_runner;
+mod clipboard_watcher;
+mod codespaces;
+mod command_guard;
+mod command_queue;
 mod commands;
 mod community_contributions;
+mod compose;
+mod connection_proxies;
+mod connections;
+mod context_menu;
+mod crash_reporter;
+mod credential_consent;
+mod custom_ides;
 mod custom_protocol;
+mod debug_console;
+mod devcontainer;
+mod diagnostics;
+mod disk_space;
+mod dock_badge;
+mod dock_menu;
+mod docker_contexts;
+mod doctor;
+mod drag_drop;
+mod environment;
+mod error_reporting;
+mod file_association;
 mod fix_env;
+mod git_credentials;
+mod gitpod;
+mod home_watcher;
+mod hostnames;
+mod ide_extensions;
+mod ide_versions;
 mod install_cli;
+mod installed_ides;
+mod jetbrains_gateway;
+mod jump_hosts;
+mod jump_list;
+mod keychain;
+mod kube_contexts;
+mod link_consent;
+mod log_stream;
+mod log_windows;
 mod logging;
+mod network;
+mod notifications;
+#[cfg(feature = "enable-updater")]
+mod offline_update;
+mod onboarding;
+mod operation_history;
+mod operation_policies;
+mod orphans;
+mod payloads;
+mod port_detection;
+mod ports;
+mod power;
+mod process;
 mod providers;
+mod redaction;
+mod relay;
+#[cfg(feature = "enable-updater")]
+mod release_notes;
+mod remote_editors;
+mod resource_limits;
+mod resumable_operations;
+#[cfg(feature = "enable-updater")]
+mod rollback;
+mod scheduler;
+mod secrets;
+mod session_end;
 mod settings;
+mod shell_completion;
+mod shutdown;
+mod single_instance;
+mod ssh_config;
+mod ssh_hosts;
+mod ssh_keys;
+mod startup;
+mod startup_profile;
+mod state_sync;
+mod status_widget;
+mod subsystems;
 mod system_tray;
+mod telemetry;
+mod terminal;
 mod ui_messages;
 mod ui_ready;
 #[cfg(feature = "enable-updater")]
+mod update_safety;
+#[cfg(feature = "enable-updater")]
 mod updates;
 mod util;
+mod vault;
+mod verification;
+mod vscode;
 mod window;
+mod window_state;
+mod workspace_export;
+mod workspace_query;
 mod workspaces;
 
 use community_contributions::CommunityContributions;
 use custom_protocol::CustomProtocol;
 use log::{error, info};
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
 use system_tray::SystemTray;
 use tauri::{Manager, Menu, Wry};
 use tokio::sync::mpsc::{self, Sender};
@@ -42,6 +139,8 @@ pub struct AppState {
     workspaces: Arc<Mutex<WorkspacesState>>,
     community_contributions: Arc<Mutex<CommunityContributions>>,
     ui_messages: Sender<UiMessage>,
+    active_actions: Arc<Mutex<HashSet<String>>>,
+    connections: Arc<Mutex<connections::Connections>>,
     #[cfg(feature = "enable-updater")]
     releases: Arc<Mutex<updates::Releases>>,
     #[cfg(feature = "enable-updater")]
@@ -51,11 +150,15 @@ pub struct AppState {
 }
 
 fn main() -> anyhow::Result<()> {
+    startup_profile::init();
     // https://unix.stackexchange.com/questions/82620/gui-apps-dont-inherit-path-from-parent-console-apps
     fix_env::fix_env("PATH")?;
 
     let ctx = tauri::generate_context!();
     let app_name = ctx.package_info().name.to_string();
+    let single_instance = single_instance::init(ctx.config());
+    crash_reporter::install(ctx.config(), &ctx.package_info().version.to_string());
+    logging::rotate_at_startup(ctx.config(), &app_name);
     let menu = if cfg!(target_os = "macos") {
         Menu::os_default(&app_name)
     } else {
@@ -75,6 +178,8 @@ fn main() -> anyhow::Result<()> {
             workspaces: Arc::new(Mutex::new(WorkspacesState::default())),
             community_contributions: Arc::new(Mutex::new(contributions)),
             ui_messages: tx.clone(),
+            active_actions: Arc::new(Mutex::new(HashSet::new())),
+            connections: Arc::new(Mutex::new(connections::Connections::default())),
             #[cfg(feature = "enable-updater")]
             releases: Arc::new(Mutex::new(updates::Releases::default())),
             #[cfg(feature = "enable-updater")]
@@ -86,34 +191,67 @@ fn main() -> anyhow::Result<()> {
         .plugin(tauri_plugin_store::Builder::default().build())
         .system_tray(system_tray.build_tray(vec![Box::new(&WorkspacesState::default())]))
         .menu(menu)
+        .register_uri_scheme_protocol(payloads::PAYLOAD_PROTOCOL, payloads::handle_request)
         .setup(move |app| {
             info!("Setup application");
+            telemetry::setup(&app.handle());
+            error_reporting::setup(&app.handle());
+            let mut setup_span = telemetry::Span::start("app setup");
+            setup_span.set_attribute("app.version", app.package_info().version.to_string());
 
-            providers::check_dangling_provider(&app.handle());
-            let window_helper = window::WindowHelper::new(app.handle());
-
-            let window = app.get_window("main").unwrap();
-            window_helper.setup(&window);
+            let app_handle = app.handle();
+            let fast_start = settings::Settings::fast_start(&app_handle);
 
-            workspaces::setup(&app.handle(), app.state());
-            community_contributions::setup(app.state());
-            action_logs::setup(&app.handle())?;
-            custom_protocol.setup(app.handle());
+            let window_helper = startup_profile::phase(&setup_span, "window", || {
+                let window_helper = window::WindowHelper::new(app.handle());
+                window_helper.setup(&app.get_window("main").unwrap());
 
-            #[cfg(feature = "enable-updater")]
-            let app_handle = app.handle();
-            #[cfg(feature = "enable-updater")]
-            tauri::async_runtime::spawn(async move {
-                let update_helper = updates::UpdateHelper::new(&app_handle);
-                if let Ok(releases) = update_helper.fetch_releases().await {
-                    let state = app_handle.state::<AppState>();
-                    let mut releases_state = state.releases.lock().unwrap();
-                    *releases_state = releases;
+                window_helper
+            });
+            startup_profile::phase(&setup_span, "logging", || {
+                scheduler::setup(&app_handle);
+                logging::setup(&app_handle);
+                operation_history::setup(&app_handle);
+                audit_log::setup(&app_handle);
+                redaction::setup();
+            });
+            startup_profile::phase(&setup_span, "environment", || {
+                environment::setup(&app_handle);
+                vault::setup(&app_handle);
+                cert_pinning::setup();
+                cli_integrity::setup(&app_handle);
+            });
+            startup_profile::phase(&setup_span, "workspaces", || {
+                workspaces::setup(&app_handle, app.state());
+                startup::setup(&app_handle);
+            });
+            startup_profile::phase(&setup_span, "action logs", || {
+                action_logs::setup(&app_handle)
+            })?;
+            startup_profile::phase(&setup_span, "deep links", || {
+                custom_protocol.setup(app.handle());
+                file_association::setup(&app_handle);
+                if let Some(single_instance) = single_instance {
+                    single_instance.setup(&app_handle);
                 }
-
-                update_helper.poll().await;
             });
 
+            // Nothing the main window shows depends on these, with fast start they wait for its first render
+            let non_essential = |name, setup: fn(&AppHandle)| {
+                startup_profile::non_essential(&app_handle, &setup_span, fast_start, name, setup)
+            };
+            non_essential("dangling providers", providers::check_dangling_provider);
+            non_essential("orphans", orphans::setup);
+            non_essential("dock menu", dock_menu::setup);
+            non_essential("context menu", context_menu::setup);
+            non_essential("session end", session_end::setup);
+            non_essential("resumable operations", resumable_operations::setup);
+            non_essential("network", network::setup);
+            non_essential("ports", ports::setup);
+            non_essential("browser ide", |_| browser_ide::setup());
+            non_essential("hostnames", hostnames::setup);
+            non_essential("status widget", status_widget::setup);
+
             let app_handle = app.handle();
             tauri::async_runtime::spawn(async move {
                 ui_messages::UiMessageHelper::new(app_handle, app_name, window_helper)
@@ -121,6 +259,7 @@ fn main() -> anyhow::Result<()> {
                     .await;
             });
 
+            drop(setup_span);
             info!("Setup done");
             Ok(())
         })
@@ -128,28 +267,368 @@ fn main() -> anyhow::Result<()> {
 
     #[cfg(feature = "enable-updater")]
     {
-        app_builder = app_builder.invoke_handler(tauri::generate_handler![
+        app_builder = app_builder.invoke_handler(command_guard::guard(tauri::generate_handler![
             ui_ready::ui_ready,
             action_logs::write_action_log,
             action_logs::get_action_logs,
             action_logs::sync_action_logs,
+            action_logs::sync_active_actions,
+            operation_history::record_operation,
+            operation_history::query_operation_history,
             install_cli::install_cli,
+            install_cli::uninstall_cli,
+            install_cli::is_cli_installed,
             community_contributions::get_contributions,
+            logging::get_log_disk_usage,
+            logging::set_log_level,
+            logging::get_log_levels,
+            diagnostics::collect_diagnostics,
+            doctor::run_doctor,
+            debug_console::get_debug_snapshot,
+            debug_console::toggle_debug_console,
+            autostart::get_autostart,
+            autostart::set_autostart,
+            drag_drop::drop_items,
+            shell_completion::get_shell_completions,
+            shell_completion::install_shell_completions,
+            secrets::set_secret,
+            secrets::delete_secret,
+            secrets::has_secret,
+            context_menu::get_context_menu,
+            context_menu::set_context_menu,
+            disk_space::get_disk_space,
+            disk_space::check_build_disk_space,
+            disk_space::prune_docker,
+            network::get_network_status,
+            custom_protocol::get_deep_link_registration,
+            ssh_config::get_ssh_config_management,
+            ssh_config::set_ssh_config_management,
+            ssh_config::preview_ssh_config,
+            ports::list_port_forwards,
+            ports::forward_port,
+            ports::expose_port,
+            ports::unforward_port,
+            ports::reconnect_port_forwards,
+            port_detection::decide_detected_port,
+            port_detection::get_port_decisions,
+            port_detection::set_port_decision,
+            connections::get_connections,
+            connections::get_connection_metrics,
+            browser_ide::list_browser_ide_sessions,
+            browser_ide::open_browser_ide,
+            browser_ide::close_browser_ide,
+            ssh_keys::generate_ssh_key,
+            ssh_keys::list_ssh_keys,
+            ssh_keys::remove_ssh_key,
+            ssh_keys::export_ssh_public_key,
+            jump_hosts::get_jump_hosts,
+            jump_hosts::set_jump_host,
+            connection_proxies::get_connection_proxies,
+            connection_proxies::list_connection_proxy_presets,
+            connection_proxies::set_connection_proxy,
+            connection_proxies::test_connection_proxy,
+            installed_ides::list_installed_ides,
+            custom_ides::list_custom_ides,
+            custom_ides::register_custom_ide,
+            custom_ides::remove_custom_ide,
+            custom_ides::open_custom_ide,
+            jetbrains_gateway::get_gateway_url,
+            jetbrains_gateway::open_in_gateway,
+            vscode::open_vscode,
+            remote_editors::open_remote_editor,
+            process::list_processes,
+            command_queue::get_command_queue,
+            resumable_operations::begin_resumable_operation,
+            resumable_operations::end_resumable_operation,
+            resumable_operations::resume_operation,
+            resumable_operations::roll_back_operation,
+            status_widget::toggle_status_widget,
+            status_widget::get_status_widget_snapshot,
+            status_widget::open_widget_workspace,
+            status_widget::stop_widget_workspace,
+            onboarding::is_onboarding_completed,
+            onboarding::get_onboarding_steps,
+            onboarding::fix_onboarding_step,
+            onboarding::skip_onboarding_step,
+            onboarding::complete_onboarding,
+            dock_badge::set_dock_badge,
+            state_sync::get_state_snapshot,
+            subsystems::get_ready_subsystems,
+            subsystems::ensure_subsystem,
+            workspace_query::query_workspaces,
+            cli_cache::get_workspace_status,
+            cli_cache::get_provider_options,
+            cli_cache::invalidate_cli_cache,
+            startup::get_loaded_startup_data,
+            startup_profile::get_startup_profile,
+            payloads::negotiate_payload_encoding,
+            link_consent::decide_link_action,
+            audit_log::query_audit_log,
+            audit_log::record_audit_event,
+            app_lock::get_app_lock,
+            app_lock::set_app_lock,
+            workspaces::delete_workspace,
+            providers::delete_provider,
+            providers::delete_pro_instance,
+            cert_pinning::check_certificate_pins,
+            cli_runner::run_cli,
+            credential_consent::get_credential_decisions,
+            credential_consent::decide_credential_request,
+            credential_consent::forget_credential_decisions,
+            gitpod::import_gitpod_workspace,
+            codespaces::check_codespaces_compatibility,
+            docker_contexts::list_docker_contexts,
+            docker_contexts::get_docker_context,
+            docker_contexts::set_docker_context,
+            docker_contexts::check_docker_context,
+            kube_contexts::list_kube_contexts,
+            kube_contexts::list_kube_namespaces,
+            kube_contexts::get_kube_context,
+            kube_contexts::set_kube_context,
+            kube_contexts::check_kube_context,
+            compose::detect_compose_project,
+            compose::get_workspace_compose_project,
+            compose::get_compose_sidecars,
+            compose::set_compose_sidecars,
+            compose::start_compose_sidecars,
+            compose::stop_compose_sidecars,
+            compose::get_compose_sidecars_status,
+            workspace_export::export_workspace,
+            ssh_hosts::list_ssh_hosts,
+            git_credentials::get_git_credentials,
+            git_credentials::set_git_credential,
+            git_credentials::delete_git_credential,
+            git_credentials::check_git_access,
+            ui_messages::show_settings,
+            window_state::get_window_ui_state,
+            window_state::set_window_route,
+            window_state::set_window_ui_state,
+            log_windows::open_log_window,
+            log_windows::close_log_window,
+            log_windows::list_log_windows,
+            log_windows::get_log_window,
+            log_windows::set_log_window_filter,
+            scheduler::list_scheduled_tasks,
+            scheduler::set_task_schedule,
+            process::cancel_operation,
+            shutdown::quit_app,
+            shutdown::quit_now,
+            devcontainer::suggest_workspace_ide,
+            devcontainer::parse_devcontainer,
+            devcontainer::validate_devcontainer,
+            ide_extensions::get_ide_extensions,
+            ide_extensions::set_ide_extension_template,
+            ide_extensions::set_workspace_ide_extensions,
+            ide_extensions::install_ide_extensions,
+            ide_versions::get_pinned_ide_versions,
+            ide_versions::pin_ide_version,
+            ide_versions::resolve_ide_version,
+            terminal::list_terminals,
+            terminal::open_terminal,
+            terminal::write_terminal,
+            terminal::resize_terminal,
+            terminal::get_terminal_scrollback,
+            terminal::close_terminal,
+            hostnames::get_workspace_hostnames,
+            hostnames::set_workspace_hostname_port,
+            hostnames::get_workspace_url,
+            crash_reporter::get_crash_reports,
+            crash_reporter::send_crash_reports,
+            crash_reporter::dismiss_crash_reports,
+            log_stream::stream_logs,
+            log_stream::set_log_stream_filter,
+            log_stream::pause_log_stream,
+            log_stream::resume_log_stream,
+            log_stream::stop_log_stream,
             updates::get_releases,
             updates::get_pending_update,
-            updates::check_updates
-        ]);
+            updates::check_updates,
+            updates::get_update_channel,
+            updates::defer_update,
+            release_notes::get_release_notes,
+            cli_manager::get_cli_status,
+            cli_manager::update_cli,
+            cli_manager::repair_cli,
+            rollback::rollback_update,
+            offline_update::install_offline_update
+        ]));
     }
     #[cfg(not(feature = "enable-updater"))]
     {
-        app_builder = app_builder.invoke_handler(tauri::generate_handler![
+        app_builder = app_builder.invoke_handler(command_guard::guard(tauri::generate_handler![
             ui_ready::ui_ready,
             action_logs::write_action_log,
             action_logs::get_action_logs,
             action_logs::sync_action_logs,
+            action_logs::sync_active_actions,
+            operation_history::record_operation,
+            operation_history::query_operation_history,
             install_cli::install_cli,
+            install_cli::uninstall_cli,
+            install_cli::is_cli_installed,
             community_contributions::get_contributions,
-        ]);
+            logging::get_log_disk_usage,
+            logging::set_log_level,
+            logging::get_log_levels,
+            diagnostics::collect_diagnostics,
+            doctor::run_doctor,
+            debug_console::get_debug_snapshot,
+            debug_console::toggle_debug_console,
+            autostart::get_autostart,
+            autostart::set_autostart,
+            drag_drop::drop_items,
+            shell_completion::get_shell_completions,
+            shell_completion::install_shell_completions,
+            secrets::set_secret,
+            secrets::delete_secret,
+            secrets::has_secret,
+            context_menu::get_context_menu,
+            context_menu::set_context_menu,
+            disk_space::get_disk_space,
+            disk_space::check_build_disk_space,
+            disk_space::prune_docker,
+            network::get_network_status,
+            custom_protocol::get_deep_link_registration,
+            ssh_config::get_ssh_config_management,
+            ssh_config::set_ssh_config_management,
+            ssh_config::preview_ssh_config,
+            ports::list_port_forwards,
+            ports::forward_port,
+            ports::expose_port,
+            ports::unforward_port,
+            ports::reconnect_port_forwards,
+            port_detection::decide_detected_port,
+            port_detection::get_port_decisions,
+            port_detection::set_port_decision,
+            connections::get_connections,
+            connections::get_connection_metrics,
+            browser_ide::list_browser_ide_sessions,
+            browser_ide::open_browser_ide,
+            browser_ide::close_browser_ide,
+            ssh_keys::generate_ssh_key,
+            ssh_keys::list_ssh_keys,
+            ssh_keys::remove_ssh_key,
+            ssh_keys::export_ssh_public_key,
+            jump_hosts::get_jump_hosts,
+            jump_hosts::set_jump_host,
+            connection_proxies::get_connection_proxies,
+            connection_proxies::list_connection_proxy_presets,
+            connection_proxies::set_connection_proxy,
+            connection_proxies::test_connection_proxy,
+            installed_ides::list_installed_ides,
+            custom_ides::list_custom_ides,
+            custom_ides::register_custom_ide,
+            custom_ides::remove_custom_ide,
+            custom_ides::open_custom_ide,
+            jetbrains_gateway::get_gateway_url,
+            jetbrains_gateway::open_in_gateway,
+            vscode::open_vscode,
+            remote_editors::open_remote_editor,
+            process::list_processes,
+            command_queue::get_command_queue,
+            resumable_operations::begin_resumable_operation,
+            resumable_operations::end_resumable_operation,
+            resumable_operations::resume_operation,
+            resumable_operations::roll_back_operation,
+            status_widget::toggle_status_widget,
+            status_widget::get_status_widget_snapshot,
+            status_widget::open_widget_workspace,
+            status_widget::stop_widget_workspace,
+            onboarding::is_onboarding_completed,
+            onboarding::get_onboarding_steps,
+            onboarding::fix_onboarding_step,
+            onboarding::skip_onboarding_step,
+            onboarding::complete_onboarding,
+            dock_badge::set_dock_badge,
+            state_sync::get_state_snapshot,
+            subsystems::get_ready_subsystems,
+            subsystems::ensure_subsystem,
+            workspace_query::query_workspaces,
+            cli_cache::get_workspace_status,
+            cli_cache::get_provider_options,
+            cli_cache::invalidate_cli_cache,
+            startup::get_loaded_startup_data,
+            startup_profile::get_startup_profile,
+            payloads::negotiate_payload_encoding,
+            link_consent::decide_link_action,
+            audit_log::query_audit_log,
+            audit_log::record_audit_event,
+            app_lock::get_app_lock,
+            app_lock::set_app_lock,
+            workspaces::delete_workspace,
+            providers::delete_provider,
+            providers::delete_pro_instance,
+            cert_pinning::check_certificate_pins,
+            cli_runner::run_cli,
+            credential_consent::get_credential_decisions,
+            credential_consent::decide_credential_request,
+            credential_consent::forget_credential_decisions,
+            gitpod::import_gitpod_workspace,
+            codespaces::check_codespaces_compatibility,
+            docker_contexts::list_docker_contexts,
+            docker_contexts::get_docker_context,
+            docker_contexts::set_docker_context,
+            docker_contexts::check_docker_context,
+            kube_contexts::list_kube_contexts,
+            kube_contexts::list_kube_namespaces,
+            kube_contexts::get_kube_context,
+            kube_contexts::set_kube_context,
+            kube_contexts::check_kube_context,
+            compose::detect_compose_project,
+            compose::get_workspace_compose_project,
+            compose::get_compose_sidecars,
+            compose::set_compose_sidecars,
+            compose::start_compose_sidecars,
+            compose::stop_compose_sidecars,
+            compose::get_compose_sidecars_status,
+            workspace_export::export_workspace,
+            ssh_hosts::list_ssh_hosts,
+            git_credentials::get_git_credentials,
+            git_credentials::set_git_credential,
+            git_credentials::delete_git_credential,
+            git_credentials::check_git_access,
+            ui_messages::show_settings,
+            window_state::get_window_ui_state,
+            window_state::set_window_route,
+            window_state::set_window_ui_state,
+            log_windows::open_log_window,
+            log_windows::close_log_window,
+            log_windows::list_log_windows,
+            log_windows::get_log_window,
+            log_windows::set_log_window_filter,
+            scheduler::list_scheduled_tasks,
+            scheduler::set_task_schedule,
+            process::cancel_operation,
+            shutdown::quit_app,
+            shutdown::quit_now,
+            devcontainer::suggest_workspace_ide,
+            devcontainer::parse_devcontainer,
+            devcontainer::validate_devcontainer,
+            ide_extensions::get_ide_extensions,
+            ide_extensions::set_ide_extension_template,
+            ide_extensions::set_workspace_ide_extensions,
+            ide_extensions::install_ide_extensions,
+            ide_versions::get_pinned_ide_versions,
+            ide_versions::pin_ide_version,
+            ide_versions::resolve_ide_version,
+            terminal::list_terminals,
+            terminal::open_terminal,
+            terminal::write_terminal,
+            terminal::resize_terminal,
+            terminal::get_terminal_scrollback,
+            terminal::close_terminal,
+            hostnames::get_workspace_hostnames,
+            hostnames::set_workspace_hostname_port,
+            hostnames::get_workspace_url,
+            crash_reporter::get_crash_reports,
+            crash_reporter::send_crash_reports,
+            crash_reporter::dismiss_crash_reports,
+            log_stream::stream_logs,
+            log_stream::set_log_stream_filter,
+            log_stream::pause_log_stream,
+            log_stream::resume_log_stream,
+            log_stream::stop_log_stream,
+        ]));
     }
 
     let app = app_builder
@@ -158,8 +637,6 @@ fn main() -> anyhow::Result<()> {
 
     info!("Run");
 
-    #[cfg(feature = "enable-updater")]
-    let config = app.config();
     app.run(move |app_handle, event| {
         let exit_requested_tx = tx.clone();
 
@@ -167,10 +644,9 @@ fn main() -> anyhow::Result<()> {
             #[cfg(feature = "enable-updater")]
             tauri::RunEvent::Updater(updater_event) => {
                 let app_handle = app_handle.clone();
-                let app_identifier = config.tauri.bundle.identifier.clone();
                 tauri::async_runtime::spawn(async move {
                     updates::UpdateHelper::new(&app_handle)
-                        .handle_event(updater_event, &app_identifier)
+                        .handle_event(updater_event)
                         .await;
                 });
             }
@@ -196,9 +672,20 @@ fn main() -> anyhow::Result<()> {
                         }
                     }
                 }
+                if let tauri::WindowEvent::FileDrop(tauri::FileDropEvent::Dropped(paths)) = event {
+                    if label == "main" {
+                        let paths = paths
+                            .iter()
+                            .map(|path| path.to_string_lossy().to_string())
+                            .collect();
+                        drag_drop::handle_drop(app_handle, paths);
+                    }
+                }
             }
             tauri::RunEvent::Exit => {
                 providers::check_dangling_provider(app_handle);
+                ports::stop_all();
+                terminal::close_all();
             }
             _ => {}
         }
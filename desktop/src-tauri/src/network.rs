@@ -0,0 +1,111 @@
+use crate::AppHandle;
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+    net::{TcpStream, ToSocketAddrs},
+    sync::Mutex,
+    thread,
+    time::Duration,
+};
+use tauri::Manager;
+use tokio::sync::watch;
+use ts_rs::TS;
+
+// WARN: needs to match the channel in `client.ts`
+const NETWORK_STATUS_EVENT: &str = "network_status";
+const PROBE_INTERVAL: Duration = Duration::from_secs(30);
+// Connectivity is checked more often while offline so that we resume quickly
+const OFFLINE_PROBE_INTERVAL: Duration = Duration::from_secs(5);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+// Releases and the CLI are hosted on GitHub, the second one doesn't need DNS
+const PROBE_ADDRESSES: [&str; 2] = ["github.com:443", "1.1.1.1:443"];
+
+lazy_static! {
+    static ref ONLINE: watch::Sender<bool> = watch::channel(true).0;
+    static ref SINCE: Mutex<DateTime<Utc>> = Mutex::new(Utc::now());
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct NetworkStatus {
+    online: bool,
+    /// When the network became available or unavailable
+    since: DateTime<Utc>,
+}
+
+/// Whether the internet can be reached, assumed until the first check says otherwise.
+pub fn is_online() -> bool {
+    *ONLINE.borrow()
+}
+
+/// Returns once the internet can be reached, background tasks wait for this instead of failing while offline.
+#[cfg(feature = "enable-updater")]
+pub async fn wait_until_online() {
+    let mut online = ONLINE.subscribe();
+    while !*online.borrow_and_update() {
+        if online.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+fn status() -> NetworkStatus {
+    NetworkStatus {
+        online: is_online(),
+        since: *SINCE.lock().unwrap(),
+    }
+}
+
+fn probe() -> bool {
+    PROBE_ADDRESSES.iter().any(|address| {
+        let addr = match address
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+        {
+            Some(addr) => addr,
+            None => return false,
+        };
+
+        TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).is_ok()
+    })
+}
+
+/// Periodically checks connectivity and lets the UI know when it changes.
+pub fn setup(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    thread::spawn(move || loop {
+        let online = probe();
+        let changed = ONLINE.send_if_modified(|current| {
+            let changed = *current != online;
+            *current = online;
+            changed
+        });
+
+        if changed {
+            *SINCE.lock().unwrap() = Utc::now();
+            if online {
+                info!("Network is available again, resuming background tasks");
+            } else {
+                warn!("Network is unavailable, entering offline mode");
+            }
+            if let Err(err) = app_handle.emit_all(NETWORK_STATUS_EVENT, status()) {
+                warn!("Failed to send network status: {}", err);
+            }
+        }
+
+        thread::sleep(if online {
+            PROBE_INTERVAL
+        } else {
+            OFFLINE_PROBE_INTERVAL
+        });
+    });
+}
+
+#[tauri::command]
+pub fn get_network_status() -> NetworkStatus {
+    status()
+}
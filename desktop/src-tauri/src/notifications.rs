@@ -0,0 +1,246 @@
+use crate::{
+    custom_protocol::{CustomProtocol, OpenWorkspaceMsg},
+    port_detection,
+    settings::Settings,
+    ui_messages::UiMessage,
+    AppHandle, AppState,
+};
+use chrono::{Local, NaiveTime};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use tauri::{api::notification::Notification, Manager};
+use ts_rs::TS;
+
+const QUIET_HOURS_FORMAT: &str = "%H:%M";
+
+/// Kinds of notifications that can be turned off individually in the settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum NotificationEvent {
+    WorkspaceReady,
+    WorkspaceStopped,
+    WorkspaceFailed,
+    UpdateAvailable,
+    CliUpdateAvailable,
+    LowDiskSpace,
+    ClipboardGitUrl,
+    PortDetected,
+    ConnectionLost,
+}
+
+/// Local time range without any notifications, formatted as `HH:MM`. Wraps around midnight if `end` is before `start`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct QuietHours {
+    start: String,
+    end: String,
+}
+
+impl QuietHours {
+    fn contains(&self, time: NaiveTime) -> bool {
+        let start = NaiveTime::parse_from_str(&self.start, QUIET_HOURS_FORMAT);
+        let end = NaiveTime::parse_from_str(&self.end, QUIET_HOURS_FORMAT);
+        let (start, end) = match (start, end) {
+            (Ok(start), Ok(end)) => (start, end),
+            _ => return false,
+        };
+
+        if start <= end {
+            time >= start && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+/// Button shown on a notification, only supported by some platforms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotificationAction {
+    OpenWorkspace(String),
+    CleanUpDiskSpace,
+    OpenDeepLink(String),
+    ForwardPort(String, u16),
+}
+
+// Buttons are only implemented on Linux so far
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+impl NotificationAction {
+    fn id(&self) -> &'static str {
+        match self {
+            Self::OpenWorkspace(_) => "open_workspace",
+            Self::CleanUpDiskSpace => "clean_up_disk_space",
+            Self::OpenDeepLink(_) => "open_deep_link",
+            Self::ForwardPort(..) => "forward_port",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::OpenWorkspace(_) => "Open IDE",
+            Self::CleanUpDiskSpace => "Clean Up",
+            Self::OpenDeepLink(_) => "Create Workspace",
+            Self::ForwardPort(..) => "Forward",
+        }
+    }
+
+    fn run(self, app_handle: &AppHandle) {
+        let msg = match self {
+            Self::OpenWorkspace(workspace_id) => {
+                UiMessage::OpenWorkspace(OpenWorkspaceMsg::with_id(workspace_id))
+            }
+            Self::CleanUpDiskSpace => UiMessage::ShowDiskCleanup,
+            Self::OpenDeepLink(url) => return CustomProtocol::handle_app_url(app_handle, &url),
+            Self::ForwardPort(workspace_id, port) => {
+                if let Err(err) = port_detection::decide_detected_port(
+                    app_handle.clone(),
+                    workspace_id,
+                    port,
+                    true,
+                    false,
+                ) {
+                    error!("Failed to forward port {}: {}", port, err);
+                }
+                return;
+            }
+        };
+
+        let app_state = app_handle.state::<AppState>();
+        tauri::async_runtime::block_on(async move {
+            if let Err(err) = app_state.ui_messages.send(msg).await {
+                error!("Failed to broadcast notification action: {}", err);
+            }
+        });
+    }
+}
+
+/// Shows a native notification unless `event` has been turned off or it's during quiet hours.
+pub fn notify(
+    app_handle: &AppHandle,
+    event: NotificationEvent,
+    title: &str,
+    body: &str,
+    actions: Vec<NotificationAction>,
+) -> anyhow::Result<()> {
+    if Settings::disabled_notifications(app_handle).contains(&event) {
+        return Ok(());
+    }
+    if Settings::quiet_hours(app_handle)
+        .is_some_and(|quiet_hours| quiet_hours.contains(Local::now().time()))
+    {
+        info!("Suppressing {:?} notification during quiet hours", event);
+        return Ok(());
+    }
+
+    let identifier = &app_handle.config().tauri.bundle.identifier;
+    #[cfg(target_os = "linux")]
+    if !actions.is_empty() {
+        return linux::notify_with_actions(app_handle, identifier, title, body, actions);
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = actions;
+
+    Notification::new(identifier)
+        .title(title)
+        .body(body)
+        .show()?;
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::NotificationAction;
+    use crate::{debug_console, AppHandle};
+    use log::warn;
+    use std::{
+        process::{Command, Stdio},
+        thread,
+    };
+    use tauri::api::notification::Notification;
+
+    /// `notify-send` blocks until the notification is closed and prints the id of the clicked action.
+    /// Falls back to a notification without buttons on systems where it's missing or too old to support actions.
+    pub fn notify_with_actions(
+        app_handle: &AppHandle,
+        identifier: &str,
+        title: &str,
+        body: &str,
+        actions: Vec<NotificationAction>,
+    ) -> anyhow::Result<()> {
+        let mut args = vec!["--app-name=DevPod".to_string(), "--wait".to_string()];
+        args.extend(
+            actions
+                .iter()
+                .map(|action| format!("--action={}={}", action.id(), action.label())),
+        );
+        args.extend([title.to_string(), body.to_string()]);
+
+        let fallback = Notification::new(identifier).title(title).body(body);
+        let child = Command::new("notify-send")
+            .args(&args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let child = match child {
+            Ok(child) => child,
+            Err(_) => {
+                fallback.show()?;
+                return Ok(());
+            }
+        };
+
+        let app_handle = app_handle.clone();
+        thread::spawn(move || {
+            let arg_refs: Vec<_> = args.iter().map(String::as_str).collect();
+            let _process = debug_console::track_process("notify-send", &arg_refs);
+            let output = match child.wait_with_output() {
+                Ok(output) if output.status.success() => output,
+                _ => {
+                    if let Err(err) = fallback.show() {
+                        warn!("Failed to show notification: {}", err);
+                    }
+                    return;
+                }
+            };
+
+            let clicked = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if let Some(action) = actions.into_iter().find(|action| action.id() == clicked) {
+                action.run(&app_handle);
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_quiet_hours() {
+        let time = |time| NaiveTime::parse_from_str(time, QUIET_HOURS_FORMAT).unwrap();
+        let overnight = QuietHours {
+            start: "22:00".to_string(),
+            end: "07:30".to_string(),
+        };
+        assert!(overnight.contains(time("23:15")));
+        assert!(overnight.contains(time("03:00")));
+        assert!(!overnight.contains(time("07:30")));
+        assert!(!overnight.contains(time("12:00")));
+
+        let lunch = QuietHours {
+            start: "12:00".to_string(),
+            end: "13:00".to_string(),
+        };
+        assert!(lunch.contains(time("12:30")));
+        assert!(!lunch.contains(time("13:30")));
+
+        let invalid = QuietHours {
+            start: "noon".to_string(),
+            end: "13:00".to_string(),
+        };
+        assert!(!invalid.contains(time("12:30")));
+    }
+}
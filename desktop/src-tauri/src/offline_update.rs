@@ -0,0 +1,313 @@
+use crate::{
+    cli_manager::{self, CliManager, CliUpdateError},
+    rollback,
+    update_safety::{self, UpdateSafetyError},
+    verification::{
+        self, VerificationError, VerifiedManifest, CHECKSUM_MANIFEST_NAME,
+        CHECKSUM_MANIFEST_SIGNATURE_NAME,
+    },
+    AppHandle,
+};
+use anyhow::Context;
+use log::{error, info, warn};
+use serde::Serialize;
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+// Suffixes of the updater artifacts produced by the tauri bundler
+#[cfg(target_os = "macos")]
+const APP_ARTIFACT_SUFFIX: &str = ".app.tar.gz";
+#[cfg(target_os = "linux")]
+const APP_ARTIFACT_SUFFIX: &str = ".AppImage.tar.gz";
+#[cfg(target_os = "windows")]
+const APP_ARTIFACT_SUFFIX: &str = ".msi.zip";
+
+#[derive(Error, Debug)]
+pub enum OfflineUpdateError {
+    #[error("unable to read update bundle {0}")]
+    Bundle(#[source] anyhow::Error),
+    #[error("update bundle contains neither an app nor a cli update for this platform")]
+    Empty,
+    #[error("failed to verify update bundle {0}")]
+    Verification(#[from] VerificationError),
+    #[error(transparent)]
+    Cli(#[from] CliUpdateError),
+    #[error("failed to install app update {0}")]
+    App(#[source] anyhow::Error),
+    #[error(transparent)]
+    Safety(#[from] UpdateSafetyError),
+}
+impl serde::Serialize for OfflineUpdateError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Default, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct OfflineUpdateResult {
+    cli_installed: bool,
+    app_installed: bool,
+}
+
+/// Installs an update bundle without network access. A bundle is a zip archive containing a signed checksum
+/// manifest and the cli binary and/or app updater artifact for this platform, as published with every release.
+#[tauri::command]
+pub async fn install_offline_update(
+    app_handle: AppHandle,
+    path: PathBuf,
+    force: Option<bool>,
+) -> Result<OfflineUpdateResult, OfflineUpdateError> {
+    let result = install(&app_handle, &path, force.unwrap_or(false)).await;
+    if let Err(err) = &result {
+        error!("Failed to install update bundle {:?}: {}", path, err);
+    }
+
+    result
+}
+
+async fn install(
+    app_handle: &AppHandle,
+    path: &Path,
+    force: bool,
+) -> Result<OfflineUpdateResult, OfflineUpdateError> {
+    // bundles are picked by the user, so rather than queueing we let them decide
+    if !force {
+        update_safety::check_idle(app_handle)?;
+    }
+
+    let mut bundle = UpdateBundle::open(path).map_err(OfflineUpdateError::Bundle)?;
+    let manifest = match bundle.manifest() {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            if let OfflineUpdateError::Verification(err) = &err {
+                verification::report_failure(app_handle, CHECKSUM_MANIFEST_NAME, err).await;
+            }
+            return Err(err);
+        }
+    };
+
+    let cli_artifact_name = cli_manager::cli_asset_name();
+    let cli_binary = read_verified(app_handle, &mut bundle, &manifest, &cli_artifact_name).await?;
+    let app_artifact = match bundle.find(|name| name.ends_with(APP_ARTIFACT_SUFFIX)) {
+        Some(name) => read_verified(app_handle, &mut bundle, &manifest, &name).await?,
+        None => None,
+    };
+    if cli_binary.is_none() && app_artifact.is_none() {
+        return Err(OfflineUpdateError::Empty);
+    }
+
+    let mut result = OfflineUpdateResult::default();
+    if let Some(cli_binary) = cli_binary {
+        info!("Installing cli from update bundle");
+        let cli_app_handle = app_handle.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            install_cli(&cli_app_handle, &cli_binary, force)
+        })
+        .await
+        .map_err(CliUpdateError::Join)??;
+        result.cli_installed = true;
+    }
+    if let Some(app_artifact) = app_artifact {
+        info!("Installing app from update bundle");
+        if let Err(err) = rollback::backup_app(app_handle) {
+            warn!("Failed to back up app before updating: {}", err);
+        }
+        install_app(app_handle, &app_artifact).map_err(OfflineUpdateError::App)?;
+        result.app_installed = true;
+
+        // the windows installer takes care of starting the new version
+        #[cfg(target_os = "windows")]
+        app_handle.exit(0);
+        #[cfg(not(target_os = "windows"))]
+        app_handle.restart();
+    }
+
+    Ok(result)
+}
+
+/// Installs `cli_binary` unless it's incompatible with the agents of existing workspaces and `force` isn't set.
+/// Runs both binaries to compare their versions, so it blocks.
+fn install_cli(
+    app_handle: &AppHandle,
+    cli_binary: &[u8],
+    force: bool,
+) -> Result<(), OfflineUpdateError> {
+    let cli_manager = CliManager::new(app_handle);
+    if !force {
+        if let Some(bundle_version) = cli_manager.bundled_version(cli_binary) {
+            update_safety::check_protocol_compatibility(
+                app_handle,
+                cli_manager.installed_version().as_deref(),
+                &bundle_version,
+            )?;
+        }
+    }
+    cli_manager.install(cli_binary, None)?;
+
+    Ok(())
+}
+
+async fn read_verified(
+    app_handle: &AppHandle,
+    bundle: &mut UpdateBundle,
+    manifest: &VerifiedManifest,
+    name: &str,
+) -> Result<Option<Vec<u8>>, OfflineUpdateError> {
+    let data = match bundle.read(name).map_err(OfflineUpdateError::Bundle)? {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+    if let Err(err) = manifest.verify(name, &data) {
+        verification::report_failure(app_handle, name, &err).await;
+        return Err(err.into());
+    }
+
+    Ok(Some(data))
+}
+
+struct UpdateBundle {
+    archive: zip::ZipArchive<fs::File>,
+}
+
+impl UpdateBundle {
+    fn open(path: &Path) -> anyhow::Result<Self> {
+        let file = fs::File::open(path).with_context(|| format!("open {:?}", path))?;
+        let archive = zip::ZipArchive::new(file).context("read zip archive")?;
+
+        Ok(Self { archive })
+    }
+
+    fn manifest(&mut self) -> Result<VerifiedManifest, OfflineUpdateError> {
+        let manifest = self
+            .read(CHECKSUM_MANIFEST_NAME)
+            .and_then(|manifest| manifest.context("missing checksum manifest"))
+            .map_err(OfflineUpdateError::Bundle)?;
+        let signature = self
+            .read(CHECKSUM_MANIFEST_SIGNATURE_NAME)
+            .and_then(|signature| signature.context("missing checksum manifest signature"))
+            .map_err(OfflineUpdateError::Bundle)?;
+
+        VerifiedManifest::new(
+            &String::from_utf8_lossy(&manifest),
+            &String::from_utf8_lossy(&signature),
+        )
+        .map_err(OfflineUpdateError::from)
+    }
+
+    fn find<P: Fn(&str) -> bool>(&self, predicate: P) -> Option<String> {
+        self.archive
+            .file_names()
+            .find(|name| predicate(name))
+            .map(String::from)
+    }
+
+    fn read(&mut self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let mut file = match self.archive.by_name(name) {
+            Ok(file) => file,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(err) => return Err(err).with_context(|| format!("read {}", name)),
+        };
+        let mut data = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut data)
+            .with_context(|| format!("read {}", name))?;
+
+        Ok(Some(data))
+    }
+}
+
+fn staging_dir(app_handle: &AppHandle) -> anyhow::Result<PathBuf> {
+    let mut dir = app_handle
+        .path_resolver()
+        .app_cache_dir()
+        .context("no app cache dir")?;
+    dir.push("offline_update");
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+
+    Ok(dir)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn extract_tar_gz(data: &[u8], target: &Path) -> anyhow::Result<()> {
+    let decoder = flate2::read::GzDecoder::new(data);
+    tar::Archive::new(decoder)
+        .unpack(target)
+        .context("extract artifact")
+}
+
+#[cfg(target_os = "macos")]
+fn install_app(app_handle: &AppHandle, artifact: &[u8]) -> anyhow::Result<()> {
+    let staging_dir = staging_dir(app_handle)?;
+    extract_tar_gz(artifact, &staging_dir)?;
+
+    let new_bundle = fs::read_dir(&staging_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.extension().map_or(false, |ext| ext == "app"))
+        .context("artifact does not contain an app bundle")?;
+    let installed_bundle = std::env::current_exe()?
+        .ancestors()
+        .find(|path| path.extension().map_or(false, |ext| ext == "app"))
+        .map(Path::to_path_buf)
+        .context("app is not running from an app bundle")?;
+
+    rollback::replace_app(&new_bundle, &installed_bundle).context("move new app into place")
+}
+
+#[cfg(target_os = "linux")]
+fn install_app(app_handle: &AppHandle, artifact: &[u8]) -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let installed_app_image = std::env::var_os("APPIMAGE")
+        .map(PathBuf::from)
+        .context("app updates are only supported for AppImages")?;
+    let staging_dir = staging_dir(app_handle)?;
+    extract_tar_gz(artifact, &staging_dir)?;
+
+    let new_app_image = fs::read_dir(&staging_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "AppImage"))
+        .context("artifact does not contain an AppImage")?;
+    fs::set_permissions(&new_app_image, fs::Permissions::from_mode(0o755))?;
+
+    rollback::replace_app(&new_app_image, &installed_app_image)
+        .context("move new AppImage into place")
+}
+
+#[cfg(target_os = "windows")]
+fn install_app(app_handle: &AppHandle, artifact: &[u8]) -> anyhow::Result<()> {
+    let staging_dir = staging_dir(app_handle)?;
+    zip::ZipArchive::new(std::io::Cursor::new(artifact))
+        .context("read artifact")?
+        .extract(&staging_dir)
+        .context("extract artifact")?;
+
+    let msi = fs::read_dir(&staging_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.extension().map_or(false, |ext| ext == "msi"))
+        .context("artifact does not contain an installer")?;
+
+    // The installer replaces the running app, it takes over from here
+    std::process::Command::new("msiexec.exe")
+        .arg("/i")
+        .arg(msi)
+        .arg("/passive")
+        .spawn()
+        .context("run installer")?;
+
+    Ok(())
+}
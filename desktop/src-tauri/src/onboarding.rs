@@ -0,0 +1,360 @@
+use crate::{
+    commands::{
+        add_provider::AddProviderCommand, list_providers::ListProvidersCommand, DevpodCommandConfig,
+    },
+    debug_console,
+    doctor::{self, CheckStatus, DoctorCheck},
+    AppHandle,
+};
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::Manager;
+use thiserror::Error;
+use ts_rs::TS;
+
+const ONBOARDING_FILE_NAME: &str = "onboarding.json";
+const DOCKER_DOWNLOAD_URL: &str = "https://docs.docker.com/get-docker/";
+const DEFAULT_PROVIDER: &str = "docker";
+// Docker Desktop takes a while until its daemon answers
+const DOCKER_START_TIMEOUT: Duration = Duration::from_secs(90);
+const DOCKER_START_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum OnboardingStepId {
+    ContainerRuntime,
+    Provider,
+    ProtocolHandler,
+}
+
+/// What the backend can do about a step that isn't done yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum OnboardingFix {
+    /// Opens the download page, installing Docker needs the user
+    InstallDocker,
+    StartDocker,
+    AddDockerProvider,
+    RegisterProtocolHandler,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct OnboardingStep {
+    id: OnboardingStepId,
+    name: String,
+    status: CheckStatus,
+    message: String,
+    remediation: Option<String>,
+    fix: Option<OnboardingFix>,
+    /// Skipped steps don't hold up finishing the onboarding
+    skipped: bool,
+}
+
+/// What's kept on disk, the state of the steps is checked anew every time.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnboardingRecord {
+    completed_at: Option<DateTime<Utc>>,
+    /// The app version it was completed with
+    completed_version: Option<String>,
+    skipped: BTreeSet<OnboardingStepId>,
+}
+
+#[derive(Error, Debug)]
+pub enum OnboardingError {
+    #[error("{0:?} can't be fixed right now")]
+    NoFix(OnboardingStepId),
+    #[error("unable to {0}")]
+    Fix(&'static str, #[source] anyhow::Error),
+    #[error("unable to save onboarding progress")]
+    Persist(#[source] anyhow::Error),
+    #[error("unable to check prerequisites")]
+    Join(#[source] tauri::Error),
+}
+impl serde::Serialize for OnboardingError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn onboarding_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(ONBOARDING_FILE_NAME))
+}
+
+fn load(app_handle: &AppHandle) -> Option<OnboardingRecord> {
+    let content = fs::read(onboarding_path(app_handle)?).ok()?;
+
+    serde_json::from_slice(&content).ok()
+}
+
+fn save(app_handle: &AppHandle, record: &OnboardingRecord) -> Result<(), OnboardingError> {
+    let path = onboarding_path(app_handle)
+        .ok_or_else(|| OnboardingError::Persist(anyhow::anyhow!("unable to find app data dir")))?;
+    let content =
+        serde_json::to_vec_pretty(record).map_err(|err| OnboardingError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| OnboardingError::Persist(err.into()))
+}
+
+fn complete(app_handle: &AppHandle, record: &mut OnboardingRecord) -> Result<(), OnboardingError> {
+    record.completed_at = Some(Utc::now());
+    record.completed_version = Some(app_handle.package_info().version.to_string());
+
+    save(app_handle, record)
+}
+
+fn provider_count(app_handle: &AppHandle) -> Result<usize, String> {
+    let providers = ListProvidersCommand::new()
+        .exec(app_handle)
+        .map_err(|err| err.to_string())?;
+
+    Ok(providers.as_object().map_or(0, |providers| providers.len()))
+}
+
+/// The record of the onboarding, created on the first run. Installs from before the onboarding existed already have
+/// a provider, those count as completed so that it doesn't show up for users who are long set up.
+fn record(app_handle: &AppHandle) -> Result<OnboardingRecord, OnboardingError> {
+    if let Some(record) = load(app_handle) {
+        return Ok(record);
+    }
+
+    let mut record = OnboardingRecord::default();
+    match provider_count(app_handle) {
+        Ok(count) if count > 0 => {
+            info!(
+                "Found {} providers on first start, skipping onboarding",
+                count
+            );
+            complete(app_handle, &mut record)?;
+        }
+        _ => {
+            info!("First run, starting onboarding");
+            save(app_handle, &record)?;
+        }
+    }
+
+    Ok(record)
+}
+
+fn from_check(id: OnboardingStepId, name: &str, check: DoctorCheck) -> OnboardingStep {
+    OnboardingStep {
+        id,
+        name: name.to_string(),
+        status: check.status(),
+        message: check.message().to_string(),
+        remediation: check.remediation_hint().map(String::from),
+        fix: None,
+        skipped: false,
+    }
+}
+
+fn docker_installed() -> bool {
+    doctor::run("docker", &["--version"]).is_ok()
+}
+
+fn check(app_handle: &AppHandle, id: OnboardingStepId) -> OnboardingStep {
+    match id {
+        OnboardingStepId::ContainerRuntime => {
+            let mut step = from_check(id, "Container runtime", doctor::check_container_runtime());
+            if step.status != CheckStatus::Pass {
+                step.fix = Some(if docker_installed() {
+                    OnboardingFix::StartDocker
+                } else {
+                    OnboardingFix::InstallDocker
+                });
+            }
+
+            step
+        }
+        OnboardingStepId::Provider => {
+            let (status, message) = match provider_count(app_handle) {
+                Ok(0) => (CheckStatus::Fail, "No provider configured yet".to_string()),
+                Ok(1) => (CheckStatus::Pass, "1 provider configured".to_string()),
+                Ok(count) => (CheckStatus::Pass, format!("{} providers configured", count)),
+                Err(err) => (
+                    CheckStatus::Fail,
+                    format!("Unable to list providers: {}", err),
+                ),
+            };
+            // The docker provider only works with a local container runtime, others need to be configured by the user
+            let fix = (status != CheckStatus::Pass
+                && doctor::check_container_runtime().status() == CheckStatus::Pass)
+                .then_some(OnboardingFix::AddDockerProvider);
+
+            OnboardingStep {
+                id,
+                name: "Provider".to_string(),
+                status,
+                message,
+                remediation: Some("Add a provider from the Providers tab.".to_string()),
+                fix,
+                skipped: false,
+            }
+        }
+        OnboardingStepId::ProtocolHandler => {
+            let mut step = from_check(id, "devpod:// links", doctor::check_protocol_handler());
+            if step.status != CheckStatus::Pass && cfg!(target_os = "linux") {
+                step.fix = Some(OnboardingFix::RegisterProtocolHandler);
+            }
+
+            step
+        }
+    }
+}
+
+fn steps(app_handle: &AppHandle, record: &OnboardingRecord) -> Vec<OnboardingStep> {
+    [
+        OnboardingStepId::ContainerRuntime,
+        OnboardingStepId::Provider,
+        OnboardingStepId::ProtocolHandler,
+    ]
+    .into_iter()
+    .map(|id| OnboardingStep {
+        skipped: record.skipped.contains(&id),
+        ..check(app_handle, id)
+    })
+    .collect()
+}
+
+fn start_docker() -> anyhow::Result<()> {
+    #[cfg(target_os = "macos")]
+    doctor::run("open", &["-a", "Docker"]).map_err(anyhow::Error::msg)?;
+    // Docker Desktop runs as a user service, the engine's system service would need root
+    #[cfg(target_os = "linux")]
+    doctor::run("systemctl", &["--user", "start", "docker-desktop"]).map_err(anyhow::Error::msg)?;
+    #[cfg(target_os = "windows")]
+    {
+        let program_files =
+            std::env::var("ProgramFiles").unwrap_or_else(|_| "C:\\Program Files".to_string());
+        std::process::Command::new(
+            PathBuf::from(program_files).join("Docker\\Docker\\Docker Desktop.exe"),
+        )
+        .spawn()?;
+    }
+
+    let started = Instant::now();
+    while started.elapsed() < DOCKER_START_TIMEOUT {
+        if doctor::check_container_runtime().status() == CheckStatus::Pass {
+            return Ok(());
+        }
+        thread::sleep(DOCKER_START_CHECK_INTERVAL);
+    }
+
+    anyhow::bail!("Docker didn't come up within {:?}", DOCKER_START_TIMEOUT)
+}
+
+fn apply(app_handle: &AppHandle, fix: OnboardingFix) -> Result<(), OnboardingError> {
+    info!("Applying onboarding fix {:?}", fix);
+    match fix {
+        OnboardingFix::InstallDocker => {
+            tauri::api::shell::open(&app_handle.shell_scope(), DOCKER_DOWNLOAD_URL, None)
+                .map_err(|err| OnboardingError::Fix("open the Docker download page", err.into()))
+        }
+        OnboardingFix::StartDocker => {
+            start_docker().map_err(|err| OnboardingError::Fix("start Docker", err))
+        }
+        OnboardingFix::AddDockerProvider => AddProviderCommand::new(DEFAULT_PROVIDER.to_string())
+            .exec(app_handle)
+            .map_err(|err| OnboardingError::Fix("add the docker provider", err.into())),
+        OnboardingFix::RegisterProtocolHandler => {
+            #[cfg(target_os = "linux")]
+            {
+                let failed = crate::custom_protocol::register_again();
+                if !failed.is_empty() {
+                    return Err(OnboardingError::Fix(
+                        "register for devpod:// links",
+                        anyhow::anyhow!("failed: {}", failed.join(", ")),
+                    ));
+                }
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Whether the user has been through the onboarding, only false until it's completed on the first run.
+#[tauri::command]
+pub async fn is_onboarding_completed(app_handle: AppHandle) -> Result<bool, OnboardingError> {
+    tauri::async_runtime::spawn_blocking(move || Ok(record(&app_handle)?.completed_at.is_some()))
+        .await
+        .map_err(OnboardingError::Join)?
+}
+
+/// Checks the prerequisites, each with what can be done about it.
+#[tauri::command]
+pub async fn get_onboarding_steps(
+    app_handle: AppHandle,
+) -> Result<Vec<OnboardingStep>, OnboardingError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let _task = debug_console::track_task("onboarding checks", None);
+
+        Ok(steps(&app_handle, &record(&app_handle)?))
+    })
+    .await
+    .map_err(OnboardingError::Join)?
+}
+
+/// Applies the step's fix and checks it again.
+#[tauri::command]
+pub async fn fix_onboarding_step(
+    app_handle: AppHandle,
+    step: OnboardingStepId,
+) -> Result<OnboardingStep, OnboardingError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let _task = debug_console::track_task("onboarding fix", None);
+        let fix = check(&app_handle, step)
+            .fix
+            .ok_or(OnboardingError::NoFix(step))?;
+        apply(&app_handle, fix)?;
+
+        let mut record = record(&app_handle)?;
+        if record.skipped.remove(&step) {
+            save(&app_handle, &record)?;
+        }
+
+        Ok(check(&app_handle, step))
+    })
+    .await
+    .map_err(OnboardingError::Join)?
+}
+
+#[tauri::command]
+pub fn skip_onboarding_step(
+    app_handle: AppHandle,
+    step: OnboardingStepId,
+) -> Result<(), OnboardingError> {
+    let mut record = record(&app_handle)?;
+    record.skipped.insert(step);
+
+    save(&app_handle, &record)
+}
+
+/// Records that the user finished the onboarding, it's not shown on its own again afterwards.
+#[tauri::command]
+pub fn complete_onboarding(app_handle: AppHandle) -> Result<(), OnboardingError> {
+    let mut record = load(&app_handle).unwrap_or_default();
+    if record.completed_at.is_some() {
+        return Ok(());
+    }
+
+    complete(&app_handle, &mut record)
+}
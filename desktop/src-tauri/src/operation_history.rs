@@ -0,0 +1,412 @@
+use crate::{
+    browser_ide, drag_drop,
+    notifications::{self, NotificationAction, NotificationEvent},
+    redaction::{self, REDACTED},
+    telemetry::Span,
+    AppHandle,
+};
+use chrono::{DateTime, Duration, Utc};
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::SystemTime,
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+const HISTORY_FILE_NAME: &str = "operation_history.jsonl";
+const MAX_RECORDS: usize = 10_000;
+const MAX_RECORD_AGE_DAYS: i64 = 90;
+const DEFAULT_QUERY_LIMIT: usize = 500;
+// Flags whose value is passed as the next argument and might hold credentials
+const SENSITIVE_FLAGS: [&str; 5] = ["--password", "--token", "--access-key", "--key", "--option"];
+
+lazy_static! {
+    // Set once the app data dir is known, operations before that aren't recorded
+    static ref HISTORY_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Error, Debug)]
+pub enum OperationHistoryError {
+    #[error("operation history is not available")]
+    NotAvailable,
+    #[error("unable to read operation history")]
+    Read(#[source] std::io::Error),
+    #[error("unable to write operation history")]
+    Write(#[source] anyhow::Error),
+}
+impl serde::Serialize for OperationHistoryError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum OperationStatus {
+    Success,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum OperationSource {
+    /// Cli commands run by the backend itself
+    Backend,
+    /// Workspace actions run by the UI
+    Ui,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct OperationRecord {
+    id: String,
+    source: OperationSource,
+    command: String,
+    args: Vec<String>,
+    workspace_id: Option<String>,
+    started_at: DateTime<Utc>,
+    #[ts(type = "number")]
+    duration_ms: u64,
+    status: OperationStatus,
+    exit_code: Option<i32>,
+}
+
+impl OperationRecord {
+    pub fn new(
+        command: &str,
+        args: &[&str],
+        workspace_id: Option<&str>,
+        started_at: DateTime<Utc>,
+        exit_code: Option<i32>,
+    ) -> Self {
+        let duration_ms = (Utc::now() - started_at).num_milliseconds().max(0) as u64;
+        let status = match exit_code {
+            Some(0) => OperationStatus::Success,
+            _ => OperationStatus::Failed,
+        };
+
+        Self {
+            id: format!(
+                "{}-{}",
+                started_at.timestamp_millis(),
+                NEXT_ID.fetch_add(1, Ordering::Relaxed)
+            ),
+            source: OperationSource::Backend,
+            command: command.to_string(),
+            args: redact_args(args.iter().copied()),
+            workspace_id: workspace_id.map(String::from),
+            started_at,
+            duration_ms,
+            status,
+            exit_code,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct OperationHistoryFilter {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    workspace_id: Option<String>,
+    limit: Option<usize>,
+}
+
+impl OperationHistoryFilter {
+    fn matches(&self, record: &OperationRecord) -> bool {
+        self.from.is_none_or(|from| record.started_at >= from)
+            && self.to.is_none_or(|to| record.started_at <= to)
+            && self
+                .workspace_id
+                .as_ref()
+                .is_none_or(|workspace_id| record.workspace_id.as_ref() == Some(workspace_id))
+    }
+}
+
+/// Replaces values of credential flags and of `KEY=VALUE` arguments.
+fn redact_args<'a>(args: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut redacted = vec![];
+    let mut redact_next = false;
+    for arg in args {
+        if redact_next {
+            redacted.push(REDACTED.to_string());
+            redact_next = false;
+            continue;
+        }
+
+        match arg.split_once('=') {
+            Some((flag, _)) if flag.starts_with('-') && SENSITIVE_FLAGS.contains(&flag) => {
+                redacted.push(format!("{}={}", flag, REDACTED))
+            }
+            Some((key, _)) if !key.starts_with('-') => {
+                redacted.push(format!("{}={}", key, REDACTED))
+            }
+            _ => {
+                redact_next = SENSITIVE_FLAGS.contains(&arg);
+                redacted.push(redaction::redact(arg).into_owned());
+            }
+        }
+    }
+
+    redacted
+}
+
+/// Reports a finished operation as a span, the duration of UI actions like `start` is what workspace startup takes.
+fn trace(record: &OperationRecord) {
+    let name = match record.source {
+        OperationSource::Backend => {
+            let subcommand: Vec<_> = record
+                .args
+                .iter()
+                .take_while(|arg| !arg.starts_with('-'))
+                .take(2)
+                .map(String::as_str)
+                .collect();
+            format!("{} {}", record.command, subcommand.join(" "))
+        }
+        OperationSource::Ui => format!("workspace {}", record.command),
+    };
+    let start: SystemTime = record.started_at.into();
+
+    let mut span = Span::start_at(name.trim_end(), start);
+    span.set_attribute("operation.id", &record.id);
+    if let Some(workspace_id) = &record.workspace_id {
+        span.set_attribute("workspace.id", workspace_id);
+    }
+    if let Some(exit_code) = record.exit_code {
+        span.set_attribute("process.exit_code", exit_code);
+    }
+    if record.status == OperationStatus::Failed {
+        span.set_error();
+    }
+    span.end_at(start + std::time::Duration::from_millis(record.duration_ms));
+}
+
+/// Lets the user know about finished workspace actions, they usually take a while and happen in the background.
+fn notify(app_handle: &AppHandle, record: &OperationRecord) {
+    let workspace_id = match &record.workspace_id {
+        Some(workspace_id) => workspace_id,
+        None => return,
+    };
+
+    let (event, title, body, actions) = match (record.command.as_str(), record.status) {
+        ("start" | "rebuild", OperationStatus::Success) => (
+            NotificationEvent::WorkspaceReady,
+            "Workspace ready".to_string(),
+            format!("{} is up and running", workspace_id),
+            vec![NotificationAction::OpenWorkspace(workspace_id.clone())],
+        ),
+        ("stop", OperationStatus::Success) => (
+            NotificationEvent::WorkspaceStopped,
+            "Workspace stopped".to_string(),
+            format!("{} has been stopped", workspace_id),
+            vec![],
+        ),
+        ("checkStatus", _) => return,
+        (command, OperationStatus::Failed) => (
+            NotificationEvent::WorkspaceFailed,
+            "Workspace action failed".to_string(),
+            format!("Failed to {} {}", command, workspace_id),
+            vec![],
+        ),
+        _ => return,
+    };
+
+    if let Err(err) = notifications::notify(app_handle, event, &title, &body, actions) {
+        warn!("Failed to show {:?} notification: {}", event, err);
+    }
+}
+
+/// Appends an operation to the history, failures are only logged to never get in the way of the operation itself.
+pub fn record(record: OperationRecord) {
+    trace(&record);
+    let path = HISTORY_PATH.lock().unwrap();
+    let path = match path.as_ref() {
+        Some(path) => path,
+        None => return,
+    };
+
+    if let Err(err) = append(path, &record) {
+        error!("Failed to record operation {}: {}", record.command, err);
+    }
+}
+
+fn append(path: &Path, record: &OperationRecord) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(format!("{}\n", serde_json::to_string(record)?).as_bytes())?;
+
+    Ok(())
+}
+
+fn read_records(path: &Path) -> Result<Vec<OperationRecord>, std::io::Error> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+
+    // Skip lines we can't parse, i.e. one that got cut off when the app was killed
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Drops records that are too old or exceed the maximum history size.
+fn prune(records: Vec<OperationRecord>, now: DateTime<Utc>) -> Vec<OperationRecord> {
+    let cutoff = now - Duration::days(MAX_RECORD_AGE_DAYS);
+    let mut records: Vec<_> = records
+        .into_iter()
+        .filter(|record| record.started_at >= cutoff)
+        .collect();
+    let overflow = records.len().saturating_sub(MAX_RECORDS);
+    records.drain(..overflow);
+
+    records
+}
+
+/// Returns the operations matching `filter`, newest first.
+#[tauri::command]
+pub fn query_operation_history(
+    filter: Option<OperationHistoryFilter>,
+) -> Result<Vec<OperationRecord>, OperationHistoryError> {
+    let filter = filter.unwrap_or_default();
+    let path = HISTORY_PATH.lock().unwrap();
+    let path = path.as_ref().ok_or(OperationHistoryError::NotAvailable)?;
+
+    let mut records: Vec<_> = read_records(path)
+        .map_err(OperationHistoryError::Read)?
+        .into_iter()
+        .filter(|record| filter.matches(record))
+        .collect();
+    records.sort_by_key(|record| std::cmp::Reverse(record.started_at));
+    records.truncate(filter.limit.unwrap_or(DEFAULT_QUERY_LIMIT));
+
+    Ok(records)
+}
+
+/// Records a workspace action that was run by the UI.
+#[tauri::command]
+pub fn record_operation(
+    app_handle: AppHandle,
+    operation: OperationRecord,
+) -> Result<(), OperationHistoryError> {
+    let path = HISTORY_PATH.lock().unwrap();
+    let path = path.as_ref().ok_or(OperationHistoryError::NotAvailable)?;
+
+    let operation = OperationRecord {
+        source: OperationSource::Ui,
+        args: redact_args(operation.args.iter().map(String::as_str)),
+        ..operation
+    };
+    trace(&operation);
+    notify(&app_handle, &operation);
+    if let ("stop" | "delete", OperationStatus::Success, Some(workspace_id)) = (
+        operation.command.as_str(),
+        operation.status,
+        &operation.workspace_id,
+    ) {
+        browser_ide::close_workspace_sessions(&app_handle, workspace_id);
+    }
+    drag_drop::advance(&app_handle);
+
+    append(path, &operation).map_err(OperationHistoryError::Write)
+}
+
+pub fn setup(app_handle: &AppHandle) {
+    let path = match app_handle.path_resolver().app_data_dir() {
+        Some(dir) => dir.join(HISTORY_FILE_NAME),
+        None => return,
+    };
+
+    match read_records(&path) {
+        Ok(records) => {
+            let count = records.len();
+            let records = prune(records, Utc::now());
+            if records.len() < count {
+                info!("Pruning {} operations from history", count - records.len());
+                let content: String = records
+                    .iter()
+                    .filter_map(|record| serde_json::to_string(record).ok())
+                    .map(|line| format!("{}\n", line))
+                    .collect();
+                if let Err(err) = fs::write(&path, content) {
+                    error!("Failed to prune operation history: {}", err);
+                }
+            }
+        }
+        Err(err) => error!("Failed to read operation history: {}", err),
+    }
+
+    *HISTORY_PATH.lock().unwrap() = Some(path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_redact_args() {
+        let args = [
+            "provider",
+            "add",
+            "aws",
+            "--option",
+            "AWS_SECRET=abc",
+            "-o",
+            "REGION=us-east-1",
+            "--token=123",
+            "--debug",
+        ];
+
+        assert_eq!(
+            redact_args(args.into_iter()),
+            vec![
+                "provider",
+                "add",
+                "aws",
+                "--option",
+                REDACTED,
+                "-o",
+                "REGION=<redacted>",
+                "--token=<redacted>",
+                "--debug",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_filter_and_prune() {
+        let now = Utc::now();
+        let old = OperationRecord::new("up", &[], Some("a"), now - Duration::days(100), Some(0));
+        let recent = OperationRecord::new("stop", &[], Some("b"), now, Some(1));
+
+        let records = prune(vec![old, recent.clone()], now);
+        assert_eq!(records, vec![recent.clone()]);
+        assert_eq!(recent.status, OperationStatus::Failed);
+
+        let filter = OperationHistoryFilter {
+            workspace_id: Some("a".to_string()),
+            ..OperationHistoryFilter::default()
+        };
+        assert!(!filter.matches(&recent));
+    }
+}
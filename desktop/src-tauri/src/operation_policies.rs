@@ -0,0 +1,117 @@
+use crate::{settings::Settings, AppHandle};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use ts_rs::TS;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// Errors of cloud APIs and networks that usually go away when trying again, matched case insensitively
+const TRANSIENT_ERRORS: [&str; 11] = [
+    "timeout",
+    "timed out",
+    "connection reset",
+    "temporarily unavailable",
+    "too many requests",
+    "rate exceeded",
+    "throttl",
+    "service unavailable",
+    "bad gateway",
+    "unexpected eof",
+    "no such host",
+];
+
+/// How long a CLI command may run and how often it's tried again, configured per command, e.g. `up` or `status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct OperationPolicy {
+    /// Seconds before the command is killed, `None` lets it run as long as it takes
+    timeout_seconds: Option<u32>,
+    /// Attempts after the first one, only made if it timed out or failed with a transient error
+    retries: u32,
+}
+
+impl OperationPolicy {
+    const fn new(timeout_seconds: Option<u32>, retries: u32) -> Self {
+        Self {
+            timeout_seconds,
+            retries,
+        }
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_seconds
+            .filter(|seconds| *seconds > 0)
+            .map(|seconds| Duration::from_secs(seconds as u64))
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Builds can take a long time, quick lookups are cheap to repeat.
+    fn default_for(command: &str) -> Self {
+        match command {
+            "up" | "build" => Self::new(Some(60 * 60), 0),
+            "list" | "status" | "version" | "logs" => Self::new(Some(60), 2),
+            "stop" => Self::new(Some(5 * 60), 1),
+            "delete" => Self::new(Some(10 * 60), 1),
+            _ => Self::new(Some(10 * 60), 0),
+        }
+    }
+}
+
+/// The CLI command the arguments run, i.e. the first one that isn't a flag.
+fn command(args: &[String]) -> Option<&str> {
+    args.iter()
+        .map(String::as_str)
+        .find(|arg| !arg.starts_with('-'))
+}
+
+/// The policy for a CLI call, configured ones take precedence over the defaults.
+pub fn for_args(app_handle: &AppHandle, args: &[String]) -> OperationPolicy {
+    let command = command(args).unwrap_or_default();
+
+    Settings::operation_policies(app_handle)
+        .remove(command)
+        .unwrap_or_else(|| OperationPolicy::default_for(command))
+}
+
+/// Whether the error output looks like it's worth trying again.
+pub fn is_transient(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+
+    TRANSIENT_ERRORS.iter().any(|error| stderr.contains(error))
+}
+
+/// Doubles for every attempt, up to `MAX_BACKOFF`.
+pub fn backoff(attempt: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_retry_transient_errors() {
+        assert!(is_transient(
+            "error: RequestLimitExceeded: Request rate exceeded."
+        ));
+        assert!(is_transient("dial tcp: i/o timeout"));
+        assert!(!is_transient("Error: workspace my-project doesn't exist"));
+    }
+
+    #[test]
+    fn should_back_off_exponentially() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert_eq!(backoff(10), MAX_BACKOFF);
+        assert_eq!(
+            command(&["--debug".to_string(), "status".to_string()]),
+            Some("status")
+        );
+    }
+}
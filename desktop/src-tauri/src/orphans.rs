@@ -0,0 +1,175 @@
+use crate::{
+    process::{self, RunningProcess},
+    AppHandle,
+};
+use log::{info, warn};
+use std::{
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+// Leftovers get this long to exit on their own before they're killed
+const TERMINATE_GRACE: Duration = Duration::from_secs(3);
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+// Worth waiting for rather than interrupting halfway through, anything else is restarted when needed
+const ADOPTED_COMMANDS: [&str; 2] = ["up", "build"];
+
+/// The executable of the process as the OS lists it, `None` if nothing runs under the PID.
+fn executable(pid: u32) -> Option<String> {
+    #[cfg(unix)]
+    let mut command = {
+        let mut command = Command::new("ps");
+        command.args(["-p", &pid.to_string(), "-o", "comm="]);
+        command
+    };
+    #[cfg(windows)]
+    let mut command = {
+        use std::os::windows::process::CommandExt;
+        let mut command = Command::new("tasklist");
+        command.args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"]);
+        // CREATE_NO_WINDOW
+        command.creation_flags(0x08000000);
+        command
+    };
+
+    let output = command.stderr(Stdio::null()).output().ok()?;
+    let line = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    // `tasklist` prints an info message rather than nothing
+    if !output.status.success() || line.is_empty() || line.starts_with("INFO:") {
+        return None;
+    }
+
+    Some(line)
+}
+
+/// The file name of the executable in a line of `ps` or `tasklist`, without the `.exe` extension.
+fn executable_name(line: &str) -> &str {
+    // `tasklist` lists the image name as the first of its quoted fields
+    let executable = match line.strip_prefix('"') {
+        Some(fields) => fields.split('"').next().unwrap_or_default(),
+        None => line,
+    };
+    let name = executable.rsplit(['/', '\\']).next().unwrap_or(executable);
+
+    name.strip_suffix(".exe").unwrap_or(name)
+}
+
+/// Whether the PID still belongs to the program, it may have been reused by an unrelated process since.
+fn is_running(process: &RunningProcess) -> bool {
+    let Some(pid) = process.pid() else {
+        return false;
+    };
+
+    executable(pid).is_some_and(|line| executable_name(&line) == process.program())
+}
+
+fn should_adopt(process: &RunningProcess) -> bool {
+    process
+        .args()
+        .split_whitespace()
+        .next()
+        .is_some_and(|command| ADOPTED_COMMANDS.contains(&command))
+}
+
+/// Lists the adopted process until it exits.
+fn watch(process: RunningProcess) {
+    info!(
+        "Adopting {} {} from a previous run",
+        process.program(),
+        process.args()
+    );
+    let id = process::adopt(process.clone());
+    thread::spawn(move || {
+        while is_running(&process) {
+            thread::sleep(CHECK_INTERVAL);
+        }
+        process::release(id);
+    });
+}
+
+fn terminate(leftovers: Vec<RunningProcess>) {
+    for process in &leftovers {
+        info!(
+            "Terminating {} {} left behind by a previous run",
+            process.program(),
+            process.args()
+        );
+        if let Some(pid) = process.pid() {
+            if let Err(err) = process::signal(pid, false) {
+                warn!("Failed to terminate process {}: {}", pid, err);
+            }
+        }
+    }
+
+    thread::sleep(TERMINATE_GRACE);
+    for process in leftovers.iter().filter(|process| is_running(process)) {
+        warn!("Killing {} after it didn't exit", process.program());
+        if let Some(pid) = process.pid() {
+            if let Err(err) = process::signal(pid, true) {
+                warn!("Failed to kill process {}: {}", pid, err);
+            }
+        }
+    }
+}
+
+/// Cleans up after a crash: long running CLI commands of the previous run are adopted, other leftovers terminated.
+pub fn setup(app_handle: &AppHandle) {
+    let leftovers: Vec<RunningProcess> = process::take_registry(app_handle)
+        .into_iter()
+        .filter(is_running)
+        .collect();
+    if leftovers.is_empty() {
+        return;
+    }
+
+    let (adopted, orphaned): (Vec<RunningProcess>, Vec<RunningProcess>) =
+        leftovers.into_iter().partition(should_adopt);
+    for process in adopted {
+        watch(process);
+    }
+    if !orphaned.is_empty() {
+        thread::spawn(move || terminate(orphaned));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(args: &str) -> RunningProcess {
+        serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "pid": 4242,
+            "program": "devpod-cli",
+            "args": args,
+            "workspaceId": "my-project",
+            "operationId": null,
+            "startedAt": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn should_only_adopt_long_running_commands() {
+        assert!(should_adopt(&process("up my-project --ide vscode")));
+        assert!(should_adopt(&process("build my-project")));
+        assert!(!should_adopt(&process("ssh my-project --stdio")));
+        assert!(!should_adopt(&process("")));
+    }
+
+    #[test]
+    fn should_only_match_the_exact_executable() {
+        assert_eq!(
+            executable_name("/Applications/DevPod.app/Contents/MacOS/devpod-cli"),
+            "devpod-cli"
+        );
+        assert_eq!(executable_name("devpod-cli"), "devpod-cli");
+        assert_eq!(
+            executable_name(r#""devpod-cli.exe","4242","Console","1","12,345 K""#),
+            "devpod-cli"
+        );
+        assert_eq!(executable_name("/usr/sbin/sshd"), "sshd");
+        assert_ne!(executable_name("devpod-cli-old"), "devpod-cli");
+    }
+}
@@ -0,0 +1,237 @@
+use crate::AppHandle;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tauri::{
+    http::{Request, Response, ResponseBuilder},
+    Manager, Window,
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+// WARN: needs to match the protocol in `payloads.ts`
+pub const PAYLOAD_PROTOCOL: &str = "payload";
+// Below this, fetching a payload separately takes longer than parsing it as JSON
+const BINARY_THRESHOLD_BYTES: usize = 64 * 1024;
+// Windows fetch their payloads as the event comes in, the ones that didn't by then never will
+const PAYLOAD_TTL: Duration = Duration::from_secs(30);
+const MESSAGE_PACK_MIME_TYPE: &str = "application/msgpack";
+
+lazy_static! {
+    /// By window label, windows that didn't negotiate get JSON.
+    static ref ENCODINGS: Mutex<HashMap<String, PayloadEncoding>> = Mutex::new(HashMap::new());
+    static ref PAYLOADS: Mutex<HashMap<u64, StoredPayload>> = Mutex::new(HashMap::new());
+}
+static NEXT_PAYLOAD_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum PayloadEncoding {
+    Json,
+    MessagePack,
+}
+
+/// Sent in place of a large payload to windows that can decode it, they fetch the payload from the payload protocol.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct BinaryPayload {
+    #[ts(type = "number")]
+    binary_payload_id: u64,
+    encoding: PayloadEncoding,
+    #[ts(type = "number")]
+    size: usize,
+}
+
+#[derive(Debug)]
+struct StoredPayload {
+    bytes: Arc<Vec<u8>>,
+    stored_at: Instant,
+}
+
+#[derive(Error, Debug)]
+pub enum PayloadError {
+    #[error("unable to encode payload")]
+    Encode(#[from] rmp_serde::encode::Error),
+    #[error("unable to emit event")]
+    Emit(#[from] tauri::Error),
+}
+
+fn store(bytes: Vec<u8>, now: Instant) -> BinaryPayload {
+    let id = NEXT_PAYLOAD_ID.fetch_add(1, Ordering::Relaxed);
+    let size = bytes.len();
+    let mut payloads = PAYLOADS.lock().unwrap();
+    payloads.retain(|_, payload| now.duration_since(payload.stored_at) < PAYLOAD_TTL);
+    payloads.insert(
+        id,
+        StoredPayload {
+            bytes: Arc::new(bytes),
+            stored_at: now,
+        },
+    );
+
+    BinaryPayload {
+        binary_payload_id: id,
+        encoding: PayloadEncoding::MessagePack,
+        size,
+    }
+}
+
+fn lookup(id: u64, now: Instant) -> Option<Arc<Vec<u8>>> {
+    let payloads = PAYLOADS.lock().unwrap();
+    let payload = payloads.get(&id)?;
+    if now.duration_since(payload.stored_at) >= PAYLOAD_TTL {
+        return None;
+    }
+
+    Some(Arc::clone(&payload.bytes))
+}
+
+fn encoding(label: &str) -> PayloadEncoding {
+    ENCODINGS
+        .lock()
+        .unwrap()
+        .get(label)
+        .copied()
+        .unwrap_or(PayloadEncoding::Json)
+}
+
+/// The payload as MessagePack if it's large enough to be worth fetching separately.
+fn encode_large<S: Serialize>(payload: &S) -> Result<Option<BinaryPayload>, PayloadError> {
+    let bytes = rmp_serde::to_vec_named(payload)?;
+    if bytes.len() < BINARY_THRESHOLD_BYTES {
+        return Ok(None);
+    }
+
+    Ok(Some(store(bytes, Instant::now())))
+}
+
+/// Like `Window::emit`, large payloads go out binary if the window negotiated it.
+pub fn emit<S: Serialize + Clone>(
+    window: &Window,
+    event: &str,
+    payload: S,
+) -> Result<(), PayloadError> {
+    if encoding(window.label()) == PayloadEncoding::MessagePack {
+        if let Some(binary) = encode_large(&payload)? {
+            return Ok(window.emit(event, binary)?);
+        }
+    }
+
+    Ok(window.emit(event, payload)?)
+}
+
+/// Like `AppHandle::emit_all`, large payloads go out binary to the windows that negotiated it and as JSON to the
+/// others. The payload is only encoded once.
+pub fn emit_all<S: Serialize + Clone>(
+    app_handle: &AppHandle,
+    event: &str,
+    payload: S,
+) -> Result<(), PayloadError> {
+    let any_binary = ENCODINGS
+        .lock()
+        .unwrap()
+        .values()
+        .any(|encoding| *encoding == PayloadEncoding::MessagePack);
+    let binary = if any_binary {
+        encode_large(&payload)?
+    } else {
+        None
+    };
+    let Some(binary) = binary else {
+        return Ok(app_handle.emit_all(event, payload)?);
+    };
+
+    for (label, window) in app_handle.windows() {
+        match encoding(&label) {
+            PayloadEncoding::MessagePack => window.emit(event, binary.clone())?,
+            PayloadEncoding::Json => window.emit(event, payload.clone())?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Serves the stored payloads, e.g. `payload://localhost/42`.
+pub fn handle_request(
+    _app_handle: &AppHandle,
+    request: &Request,
+) -> Result<Response, Box<dyn std::error::Error>> {
+    let payload = request
+        .uri()
+        .rsplit('/')
+        .next()
+        .and_then(|id| id.parse().ok())
+        .and_then(|id| lookup(id, Instant::now()));
+
+    match payload {
+        Some(bytes) => ResponseBuilder::new()
+            .status(200)
+            .mimetype(MESSAGE_PACK_MIME_TYPE)
+            // The protocol is a different origin than the one the windows are served from
+            .header("Access-Control-Allow-Origin", "*")
+            .body(bytes.to_vec()),
+        None => ResponseBuilder::new()
+            .status(404)
+            .header("Access-Control-Allow-Origin", "*")
+            .body(vec![]),
+    }
+}
+
+/// The window lists the encodings it can decode, large payloads are sent to it in the most compact of them.
+#[tauri::command]
+pub fn negotiate_payload_encoding(
+    window: Window,
+    supported: Vec<PayloadEncoding>,
+) -> PayloadEncoding {
+    let encoding = if supported.contains(&PayloadEncoding::MessagePack) {
+        PayloadEncoding::MessagePack
+    } else {
+        PayloadEncoding::Json
+    };
+    ENCODINGS
+        .lock()
+        .unwrap()
+        .insert(window.label().to_string(), encoding);
+
+    encoding
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_serve_payloads_until_they_expire() {
+        let now = Instant::now();
+        let first = store(vec![1, 2, 3], now);
+        let second = store(vec![4], now + PAYLOAD_TTL / 2);
+
+        assert_eq!(first.size, 3);
+        assert_ne!(first.binary_payload_id, second.binary_payload_id);
+        assert_eq!(
+            lookup(first.binary_payload_id, now).as_deref(),
+            Some(&vec![1, 2, 3])
+        );
+        assert_eq!(lookup(first.binary_payload_id, now + PAYLOAD_TTL), None);
+        assert_eq!(
+            lookup(second.binary_payload_id, now + PAYLOAD_TTL).as_deref(),
+            Some(&vec![4])
+        );
+
+        // Storing drops what expired in the meantime
+        store(vec![], now + PAYLOAD_TTL);
+        assert!(!PAYLOADS
+            .lock()
+            .unwrap()
+            .contains_key(&first.binary_payload_id));
+    }
+}
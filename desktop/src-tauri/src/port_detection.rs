@@ -0,0 +1,310 @@
+use crate::{
+    commands::{
+        workspace_ssh::WorkspaceSshCommand, workspace_status::WorkspaceStatusCommand,
+        DevpodCommandConfig,
+    },
+    debug_console,
+    notifications::{self, NotificationAction, NotificationEvent},
+    ports::{self, PortsError},
+    settings::Settings,
+    ui_messages::{PortDetectedMsg, UiMessage},
+    AppHandle, AppState,
+};
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+    thread,
+    time::Duration,
+};
+use tauri::Manager;
+use thiserror::Error;
+use ts_rs::TS;
+
+const DECISIONS_FILE_NAME: &str = "port_decisions.json";
+const SCAN_INTERVAL: Duration = Duration::from_secs(30);
+const RUNNING_STATE: &str = "Running";
+// Listening sockets of IPv4 and IPv6, busybox based images don't ship `ss` or `netstat`
+pub(crate) const LIST_SOCKETS_COMMAND: &str = "cat /proc/net/tcp /proc/net/tcp6 2>/dev/null";
+const TCP_ESTABLISHED: &str = "01";
+const TCP_LISTEN: &str = "0A";
+
+lazy_static! {
+    // Ports that have been listening during the last scan, by workspace
+    static ref SEEN: Mutex<HashMap<String, BTreeSet<u16>>> = Mutex::new(HashMap::new());
+}
+
+/// What to do whenever a port starts listening in a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum PortDecision {
+    Always,
+    Never,
+}
+
+type Decisions = HashMap<String, HashMap<u16, PortDecision>>;
+
+#[derive(Error, Debug)]
+pub enum PortDetectionError {
+    #[error(transparent)]
+    Forward(#[from] PortsError),
+    #[error("unable to save port decision")]
+    Persist(#[source] anyhow::Error),
+}
+impl serde::Serialize for PortDetectionError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn decisions_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(DECISIONS_FILE_NAME))
+}
+
+fn load_decisions(app_handle: &AppHandle) -> Decisions {
+    decisions_path(app_handle)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_decisions(app_handle: &AppHandle, decisions: &Decisions) -> Result<(), PortDetectionError> {
+    let path = decisions_path(app_handle).ok_or_else(|| {
+        PortDetectionError::Persist(anyhow::anyhow!("unable to find app data dir"))
+    })?;
+    let content = serde_json::to_vec_pretty(decisions)
+        .map_err(|err| PortDetectionError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| PortDetectionError::Persist(err.into()))
+}
+
+/// Local ports of the sockets in `tcp_state` from the content of `/proc/net/tcp` and `/proc/net/tcp6`.
+fn ports_in_state(proc_net_tcp: &str, tcp_state: &str) -> BTreeSet<u16> {
+    proc_net_tcp
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace().skip(1);
+            let local_address = columns.next()?;
+            let state = columns.nth(1)?;
+            if state != tcp_state {
+                return None;
+            }
+
+            let (_, port) = local_address.rsplit_once(':')?;
+            u16::from_str_radix(port, 16).ok()
+        })
+        .collect()
+}
+
+pub(crate) fn listening_ports(proc_net_tcp: &str) -> BTreeSet<u16> {
+    ports_in_state(proc_net_tcp, TCP_LISTEN)
+}
+
+/// Ports that have open connections, e.g. to check whether a server is still in use.
+pub(crate) fn established_ports(proc_net_tcp: &str) -> BTreeSet<u16> {
+    ports_in_state(proc_net_tcp, TCP_ESTABLISHED)
+}
+
+/// The listening ports, `None` if they couldn't be determined. Stopped workspaces don't listen on any.
+fn scan(app_handle: &AppHandle, workspace_id: &str) -> Option<BTreeSet<u16>> {
+    let _task = debug_console::track_task("port scan", Some(workspace_id.to_string()));
+    let state = WorkspaceStatusCommand::new(workspace_id.to_string())
+        .exec(app_handle)
+        .ok()?;
+    if state != RUNNING_STATE {
+        return Some(BTreeSet::new());
+    }
+
+    WorkspaceSshCommand::new(workspace_id.to_string(), LIST_SOCKETS_COMMAND.to_string())
+        .exec(app_handle)
+        .ok()
+        .map(|output| listening_ports(&output))
+}
+
+fn offer(app_handle: &AppHandle, workspace_id: &str, port: u16) {
+    info!("Port {} started listening in {}", port, workspace_id);
+    let visible = app_handle
+        .get_window("main")
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
+
+    // Only show a toast if the window is open anyway, sending it would bring the window up otherwise
+    if visible {
+        let msg = UiMessage::PortDetected(PortDetectedMsg::new(workspace_id.to_string(), port));
+        let app_state = app_handle.state::<AppState>();
+        tauri::async_runtime::block_on(async move {
+            if let Err(err) = app_state.ui_messages.send(msg).await {
+                error!("Failed to broadcast port detected message: {}", err);
+            }
+        });
+        return;
+    }
+
+    if let Err(err) = notifications::notify(
+        app_handle,
+        NotificationEvent::PortDetected,
+        "Port detected",
+        &format!("{} is listening on port {}", workspace_id, port),
+        vec![NotificationAction::ForwardPort(
+            workspace_id.to_string(),
+            port,
+        )],
+    ) {
+        warn!("Failed to show port detected notification: {}", err);
+    }
+}
+
+/// Forwards or offers ports that started listening since the last scan.
+fn handle_new_ports(app_handle: &AppHandle, workspace_id: &str, ports: &BTreeSet<u16>) {
+    let decisions = load_decisions(app_handle);
+    let decisions = decisions.get(workspace_id);
+    for &port in ports {
+        if ports::is_forwarded(workspace_id, port) {
+            continue;
+        }
+
+        match decisions.and_then(|decisions| decisions.get(&port)) {
+            Some(PortDecision::Never) => {}
+            Some(PortDecision::Always) => {
+                if let Err(err) =
+                    ports::forward_port(app_handle.clone(), workspace_id.to_string(), port, port)
+                {
+                    warn!(
+                        "Failed to forward port {} of {}: {}",
+                        port, workspace_id, err
+                    );
+                }
+            }
+            None => offer(app_handle, workspace_id, port),
+        }
+    }
+}
+
+/// Periodically looks for new listening ports in running workspaces, only while turned on in the settings.
+pub fn setup(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    thread::spawn(move || loop {
+        thread::sleep(SCAN_INTERVAL);
+        if !Settings::detect_ports(&app_handle) {
+            continue;
+        }
+
+        let workspace_ids: Vec<String> = {
+            let app_state = app_handle.state::<AppState>();
+            let workspaces = app_state.workspaces.lock().unwrap();
+            workspaces
+                .ids_with_context()
+                .into_iter()
+                .map(|(id, _)| id.to_string())
+                .collect()
+        };
+
+        for workspace_id in workspace_ids {
+            let ports = match scan(&app_handle, &workspace_id) {
+                Some(ports) => ports,
+                None => continue,
+            };
+            let new_ports: BTreeSet<u16> = {
+                let mut seen = SEEN.lock().unwrap();
+                let previous = seen.insert(workspace_id.clone(), ports.clone());
+                ports
+                    .difference(&previous.unwrap_or_default())
+                    .copied()
+                    .collect()
+            };
+
+            if !new_ports.is_empty() {
+                handle_new_ports(&app_handle, &workspace_id, &new_ports);
+            }
+        }
+    });
+}
+
+/// Forwards or dismisses a detected port, remembering the choice for the workspace if `remember` is set.
+#[tauri::command]
+pub fn decide_detected_port(
+    app_handle: AppHandle,
+    workspace_id: String,
+    port: u16,
+    forward: bool,
+    remember: bool,
+) -> Result<(), PortDetectionError> {
+    if remember {
+        let decision = if forward {
+            PortDecision::Always
+        } else {
+            PortDecision::Never
+        };
+        set_port_decision(
+            app_handle.clone(),
+            workspace_id.clone(),
+            port,
+            Some(decision),
+        )?;
+    }
+    if forward && !ports::is_forwarded(&workspace_id, port) {
+        ports::forward_port(app_handle, workspace_id, port, port)?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_port_decisions(
+    app_handle: AppHandle,
+    workspace_id: String,
+) -> HashMap<u16, PortDecision> {
+    load_decisions(&app_handle)
+        .remove(&workspace_id)
+        .unwrap_or_default()
+}
+
+/// Remembers what to do with `port` of the workspace, `None` asks again next time.
+#[tauri::command]
+pub fn set_port_decision(
+    app_handle: AppHandle,
+    workspace_id: String,
+    port: u16,
+    decision: Option<PortDecision>,
+) -> Result<(), PortDetectionError> {
+    let mut decisions = load_decisions(&app_handle);
+    let workspace_decisions = decisions.entry(workspace_id.clone()).or_default();
+    match decision {
+        Some(decision) => workspace_decisions.insert(port, decision),
+        None => workspace_decisions.remove(&port),
+    };
+    if workspace_decisions.is_empty() {
+        decisions.remove(&workspace_id);
+    }
+
+    save_decisions(&app_handle, &decisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_listening_ports() {
+        let output = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 1234 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1538 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 1235 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:0BB8 0100007F:D2F0 01 00000000:00000000 00:00000000 00000000  1000        0 1236 1 0000000000000000 20 4 30 10 -1
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:1435 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 1237 1 0000000000000000 100 0 0 10 0";
+
+        assert_eq!(listening_ports(output), BTreeSet::from([3000, 5432, 5173]));
+        assert_eq!(established_ports(output), BTreeSet::from([3000]));
+    }
+}
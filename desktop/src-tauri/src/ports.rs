@@ -0,0 +1,986 @@
+use crate::{
+    commands::{workspace_status::WorkspaceStatusCommand, DevpodCommandConfig},
+    connections::{self, Connection, ConnectionKind},
+    debug_console,
+    notifications::{self, NotificationEvent},
+    process::{self, Tracked},
+    relay::Relay,
+    settings::Settings,
+    ssh_config::{cli_path, proxy_command},
+    workspaces, AppHandle,
+};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashSet, VecDeque},
+    fs,
+    io::{self, BufRead, BufReader, Read},
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+use tauri::Manager;
+use thiserror::Error;
+use ts_rs::TS;
+
+// WARN: needs to match the channel in `client.ts`
+const PORT_FORWARDS_EVENT: &str = "port_forwards";
+const FORWARDS_FILE_NAME: &str = "port_forwards.json";
+const SUPERVISE_INTERVAL: Duration = Duration::from_secs(2);
+// Every retry asks the CLI whether the workspace is running
+const RETRY_INTERVAL: Duration = Duration::from_secs(15);
+// First reconnect after a tunnel dropped, doubles with every failure after that
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(2);
+// Missed keep-alives until `ssh` gives up on the connection
+const KEEP_ALIVE_COUNT_MAX: u32 = 3;
+const RUNNING_STATE: &str = "Running";
+// Printed by `ssh` once it has connected and set up the forward
+const CONNECTED_MARKER: &str = "devpod-tunnel-connected";
+// `ssh` reports this when the port in the workspace is taken
+const REMOTE_FORWARD_FAILED: &str = "remote port forwarding failed";
+// Nothing listens on tcpmux, so opening a channel to it fails after exactly one round trip
+const PROBE_REMOTE_PORT: u16 = 1;
+const PROBE_INTERVAL: Duration = Duration::from_secs(10);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+// `ssh` logs every failed probe
+const PROBE_NOISE: &str = "open failed";
+const STDERR_LINES: usize = 20;
+// Waiting for the rest of the error once `ssh` exited
+const STDERR_TIMEOUT: Duration = Duration::from_secs(1);
+
+lazy_static! {
+    static ref TUNNELS: Mutex<Vec<Tunnel>> = Mutex::new(Vec::new());
+}
+// Set while tunnels are down, so that a sleeping laptop causes one notification instead of one per tunnel
+static CONNECTION_LOST: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum PortForwardDirection {
+    /// A port of the workspace is reachable on localhost
+    #[default]
+    Forward,
+    /// A local port is reachable in the workspace, e.g. for mock services or license servers
+    Reverse,
+}
+
+/// A port that is forwarded between localhost and a workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PortForward {
+    workspace_id: String,
+    local_port: u16,
+    remote_port: u16,
+    // Forwards saved before reverse forwards existed don't have a direction
+    #[serde(default)]
+    direction: PortForwardDirection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum PortForwardState {
+    /// Waiting for the workspace to run
+    Pending,
+    Active,
+    /// Another process is listening on the port, locally or in the workspace for reverse forwards
+    Conflict,
+    /// The tunnel failed or exited unexpectedly, retried periodically
+    Broken,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PortForwardStatus {
+    forward: PortForward,
+    state: PortForwardState,
+    error: Option<String>,
+    /// Opened by the app, e.g. for a browser IDE, and not restored after a restart
+    transient: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum PortsError {
+    #[error("local port {0} is already forwarded")]
+    AlreadyForwarded(u16),
+    #[error("port {0} is already exposed in the workspace")]
+    AlreadyExposed(u16),
+    #[error("port forward not found")]
+    NotFound,
+    #[error("unable to save port forwards")]
+    Persist(#[source] anyhow::Error),
+}
+impl serde::Serialize for PortsError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+struct StderrReader {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    reader: JoinHandle<()>,
+}
+
+struct Tunnel {
+    forward: PortForward,
+    state: PortForwardState,
+    error: Option<String>,
+    child: Option<Child>,
+    /// Keeps `ssh` in the process registry, so that it's cleaned up after a crash
+    process: Option<Tracked>,
+    /// Last lines `ssh` logged, read continuously so that the pipe never fills up
+    stderr: Option<StderrReader>,
+    /// Counts the traffic of the tunnel, sits between `ssh` and the local side
+    relay: Option<Relay>,
+    /// Local end of the forward that measures round trips
+    probe_port: Option<u16>,
+    last_probe: Option<Instant>,
+    last_attempt: Option<Instant>,
+    /// Attempts that failed since the tunnel was last connected, for the backoff
+    failures: u32,
+    /// Whether the tunnel broke after it had connected, only those wait for the user without auto-reconnect
+    dropped: bool,
+    /// Whether `ssh` has connected to the workspace
+    connected: Arc<AtomicBool>,
+    transient: bool,
+}
+
+impl Tunnel {
+    fn new(forward: PortForward, transient: bool) -> Self {
+        Self {
+            forward,
+            state: PortForwardState::Pending,
+            error: None,
+            child: None,
+            process: None,
+            stderr: None,
+            relay: None,
+            probe_port: None,
+            last_probe: None,
+            last_attempt: None,
+            failures: 0,
+            dropped: false,
+            connected: Arc::new(AtomicBool::new(false)),
+            transient,
+        }
+    }
+
+    fn connection_id(&self) -> String {
+        match self.forward.direction {
+            PortForwardDirection::Forward => format!(
+                "port-forward:{}:{}",
+                self.forward.workspace_id, self.forward.local_port
+            ),
+            PortForwardDirection::Reverse => format!(
+                "reverse-forward:{}:{}",
+                self.forward.workspace_id, self.forward.remote_port
+            ),
+        }
+    }
+
+    fn status(&self) -> PortForwardStatus {
+        PortForwardStatus {
+            forward: self.forward.clone(),
+            state: self.state,
+            error: self.error.clone(),
+            transient: self.transient,
+        }
+    }
+
+    fn set_state(&mut self, state: PortForwardState, error: Option<String>) {
+        self.state = state;
+        self.error = error;
+    }
+
+    fn fail(&mut self, state: PortForwardState, error: String) {
+        self.failures += 1;
+        self.set_state(state, Some(error));
+    }
+
+    fn due(&self, now: Instant, policy: &ReconnectPolicy) -> bool {
+        if self.child.is_some() || (self.dropped && !policy.auto_reconnect) {
+            return false;
+        }
+        let delay = match self.failures {
+            0 => RETRY_INTERVAL,
+            failures => reconnect_delay(failures, policy.max_delay),
+        };
+
+        self.last_attempt
+            .is_none_or(|last_attempt| now - last_attempt >= delay)
+    }
+
+    /// Checks whether the tunnel is still up, marks it as broken if `ssh` exited.
+    /// Returns whether a connected tunnel dropped.
+    fn poll(&mut self, app_handle: &AppHandle) -> bool {
+        let child = match self.child.as_mut() {
+            Some(child) => child,
+            None => return false,
+        };
+        let status = match child.try_wait() {
+            Ok(Some(status)) => status,
+            _ => {
+                if self.connected.load(Ordering::SeqCst) {
+                    self.failures = 0;
+                    self.dropped = false;
+                }
+                return false;
+            }
+        };
+
+        let stderr = self.stderr.take().map(|StderrReader { lines, reader }| {
+            let exited_at = Instant::now();
+            while !reader.is_finished() && exited_at.elapsed() < STDERR_TIMEOUT {
+                thread::sleep(Duration::from_millis(10));
+            }
+            let lines = lines.lock().unwrap();
+            lines.iter().cloned().collect::<Vec<String>>().join("\n")
+        });
+        let error = match stderr.as_deref().map(str::trim) {
+            None | Some("") => format!("ssh exited with {}", status),
+            Some(stderr) => stderr.to_string(),
+        };
+        warn!(
+            "Port forward {} of {} broke: {}",
+            self.forward.local_port, self.forward.workspace_id, error
+        );
+        self.child = None;
+        self.process = None;
+        self.relay = None;
+        self.probe_port = None;
+        let dropped = self.disconnect();
+        if dropped {
+            self.dropped = true;
+            connections::disconnect(app_handle, &self.connection_id(), Some(error.clone()));
+        }
+        if error.contains(REMOTE_FORWARD_FAILED) {
+            self.fail(
+                PortForwardState::Conflict,
+                format!(
+                    "port {} is already in use in the workspace",
+                    self.forward.remote_port
+                ),
+            );
+        } else {
+            self.fail(PortForwardState::Broken, error);
+        }
+
+        dropped
+    }
+
+    /// `ssh` prints the marker once it's connected.
+    fn watch_connect(&self, app_handle: &AppHandle, stdout: impl Read + Send + 'static) {
+        let app_handle = app_handle.clone();
+        let connected = Arc::clone(&self.connected);
+        let id = self.connection_id();
+        let (kind, description) = match self.forward.direction {
+            PortForwardDirection::Forward => (
+                ConnectionKind::PortForward,
+                format!(
+                    "localhost:{} → {}",
+                    self.forward.local_port, self.forward.remote_port
+                ),
+            ),
+            PortForwardDirection::Reverse => (
+                ConnectionKind::ReverseForward,
+                format!(
+                    "{} → localhost:{}",
+                    self.forward.remote_port, self.forward.local_port
+                ),
+            ),
+        };
+        let connection = Connection::new(
+            id.clone(),
+            self.forward.workspace_id.clone(),
+            kind,
+            description,
+        );
+
+        thread::spawn(move || {
+            // Ends once `ssh` exits
+            let mut lines = BufReader::new(stdout).lines();
+            let marked = lines.any(|line| line.is_ok_and(|line| line.trim() == CONNECTED_MARKER));
+            // The tunnel lets go of its handle once it's stopped
+            if marked && Arc::strong_count(&connected) > 1 {
+                connected.store(true, Ordering::SeqCst);
+                connections::connect(&app_handle, connection);
+            }
+        });
+    }
+
+    fn watch_stderr(&mut self, stderr: impl Read + Send + 'static) {
+        let lines = Arc::new(Mutex::new(VecDeque::new()));
+        let reader_lines = Arc::clone(&lines);
+        let reader = thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if line.contains(PROBE_NOISE) {
+                    continue;
+                }
+                let mut lines = reader_lines.lock().unwrap();
+                if lines.len() == STDERR_LINES {
+                    lines.pop_front();
+                }
+                lines.push_back(line);
+            }
+        });
+        self.stderr = Some(StderrReader { lines, reader });
+    }
+
+    /// Measures the round trip time in the background, at most every `PROBE_INTERVAL`.
+    fn probe(&mut self, app_handle: &AppHandle, now: Instant) {
+        let probe_port = match self.probe_port {
+            Some(probe_port) if self.connected.load(Ordering::SeqCst) => probe_port,
+            _ => return,
+        };
+        if self
+            .last_probe
+            .is_some_and(|last_probe| now - last_probe < PROBE_INTERVAL)
+        {
+            return;
+        }
+        self.last_probe = Some(now);
+
+        let app_handle = app_handle.clone();
+        let id = self.connection_id();
+        thread::spawn(move || {
+            if let Some(latency) = measure_round_trip(probe_port) {
+                connections::record_latency(&app_handle, &id, latency);
+            }
+        });
+    }
+
+    /// Passes the traffic so far on to the connection, reverse relays accept connections from the workspace.
+    fn sample(&self, app_handle: &AppHandle) {
+        let relay = match &self.relay {
+            Some(relay) if self.connected.load(Ordering::SeqCst) => relay,
+            _ => return,
+        };
+        let (sent, received) = match self.forward.direction {
+            PortForwardDirection::Forward => relay.totals(),
+            PortForwardDirection::Reverse => {
+                let (sent, received) = relay.totals();
+                (received, sent)
+            }
+        };
+
+        connections::record_throughput(app_handle, &self.connection_id(), sent, received);
+    }
+
+    /// Binds the relay and picks the ports `ssh` listens on locally.
+    fn prepare(&mut self) -> Result<(Relay, u16, u16), (PortForwardState, String)> {
+        let broken = |err: io::Error| (PortForwardState::Broken, err.to_string());
+        let (listener, ssh_port, target) = match self.forward.direction {
+            PortForwardDirection::Forward => {
+                let listener = bind_local(self.forward.local_port).map_err(|_| {
+                    (
+                        PortForwardState::Conflict,
+                        format!("localhost:{} is already in use", self.forward.local_port),
+                    )
+                })?;
+                let ssh_port = free_port().map_err(broken)?;
+                (listener, ssh_port, ssh_port)
+            }
+            // The local port belongs to the service that is exposed
+            PortForwardDirection::Reverse => {
+                let listener = bind_local(0).map_err(broken)?;
+                let ssh_port = listener.local_addr().map_err(broken)?.port();
+                (listener, ssh_port, self.forward.local_port)
+            }
+        };
+        let relay =
+            Relay::start(listener, SocketAddr::from(([127, 0, 0, 1], target))).map_err(broken)?;
+        let probe_port = free_port().map_err(broken)?;
+
+        Ok((relay, ssh_port, probe_port))
+    }
+
+    fn start(&mut self, app_handle: &AppHandle, context: Option<&str>) {
+        self.last_attempt = Some(Instant::now());
+        let (relay, ssh_port, probe_port) = match self.prepare() {
+            Ok(prepared) => prepared,
+            Err((state, error)) => return self.fail(state, error),
+        };
+        let cli = match cli_path(app_handle) {
+            Ok(cli) => cli,
+            Err(err) => return self.fail(PortForwardState::Broken, err.to_string()),
+        };
+
+        let args = ssh_args(
+            &self.forward,
+            &proxy_command(&cli, &self.forward.workspace_id, context),
+            ssh_port,
+            probe_port,
+            Settings::keep_alive_seconds(app_handle),
+        );
+        let mut command = Command::new("ssh");
+        command
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            // CREATE_NO_WINDOW, `ssh` is a console application
+            command.creation_flags(0x08000000);
+        }
+
+        match command.spawn() {
+            Ok(mut child) => {
+                info!(
+                    "Forwarding localhost:{} {} port {} of {}",
+                    self.forward.local_port,
+                    match self.forward.direction {
+                        PortForwardDirection::Forward => "to",
+                        PortForwardDirection::Reverse => "from",
+                    },
+                    self.forward.remote_port,
+                    self.forward.workspace_id
+                );
+                if let Some(stdout) = child.stdout.take() {
+                    self.watch_connect(app_handle, stdout);
+                }
+                if let Some(stderr) = child.stderr.take() {
+                    self.watch_stderr(stderr);
+                }
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                self.process = Some(process::track(
+                    Some(child.id()),
+                    "ssh",
+                    &args,
+                    Some(&self.forward.workspace_id),
+                ));
+                self.child = Some(child);
+                self.relay = Some(relay);
+                self.probe_port = Some(probe_port);
+                self.last_probe = None;
+                self.set_state(PortForwardState::Active, None);
+            }
+            Err(err) => self.fail(
+                PortForwardState::Broken,
+                format!("unable to run ssh: {}", err),
+            ),
+        }
+    }
+
+    /// Replaces the handle shared with `watch_connect`, returns whether the tunnel had been connected.
+    fn disconnect(&mut self) -> bool {
+        std::mem::replace(&mut self.connected, Arc::new(AtomicBool::new(false)))
+            .load(Ordering::SeqCst)
+    }
+
+    fn stop(&mut self) {
+        self.disconnect();
+        self.relay = None;
+        self.probe_port = None;
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        self.process = None;
+    }
+}
+
+struct ReconnectPolicy {
+    auto_reconnect: bool,
+    max_delay: Duration,
+}
+
+impl ReconnectPolicy {
+    fn load(app_handle: &AppHandle) -> Self {
+        Self {
+            auto_reconnect: Settings::auto_reconnect(app_handle),
+            max_delay: Duration::from_secs(
+                Settings::reconnect_max_delay_seconds(app_handle).into(),
+            ),
+        }
+    }
+}
+
+/// Exponential backoff after `failures` failed attempts in a row.
+fn reconnect_delay(failures: u32, max_delay: Duration) -> Duration {
+    RECONNECT_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+        .min(max_delay)
+}
+
+fn bind_local(port: u16) -> io::Result<TcpListener> {
+    TcpListener::bind(("127.0.0.1", port))
+}
+
+fn free_port() -> io::Result<u16> {
+    Ok(bind_local(0)?.local_addr()?.port())
+}
+
+/// The time until `ssh` gives up on a probe connection, which takes one round trip to the workspace.
+fn measure_round_trip(probe_port: u16) -> Option<Duration> {
+    let started_at = Instant::now();
+    let mut stream = TcpStream::connect(("127.0.0.1", probe_port)).ok()?;
+    stream.set_read_timeout(Some(PROBE_TIMEOUT)).ok()?;
+    match stream.read(&mut [0; 1]) {
+        Ok(0) => Some(started_at.elapsed()),
+        Err(err) if err.kind() == io::ErrorKind::ConnectionReset => Some(started_at.elapsed()),
+        // Something answered or the tunnel hangs, neither tells us the round trip time
+        _ => None,
+    }
+}
+
+/// `ssh` listens on `ssh_port` for forwards, reverse forwards connect to it. Either way the relay is on the
+/// other side of that port.
+fn ssh_args(
+    forward: &PortForward,
+    proxy_command: &str,
+    ssh_port: u16,
+    probe_port: u16,
+    keep_alive_seconds: u32,
+) -> Vec<String> {
+    let (flag, spec) = match forward.direction {
+        PortForwardDirection::Forward => (
+            "-L",
+            format!("127.0.0.1:{}:localhost:{}", ssh_port, forward.remote_port),
+        ),
+        // Only bind to the loopback interface of the workspace as well
+        PortForwardDirection::Reverse => (
+            "-R",
+            format!("127.0.0.1:{}:localhost:{}", forward.remote_port, ssh_port),
+        ),
+    };
+
+    vec![
+        "-N".to_string(),
+        "-o".to_string(),
+        "ExitOnForwardFailure=yes".to_string(),
+        "-o".to_string(),
+        format!("ServerAliveInterval={}", keep_alive_seconds),
+        "-o".to_string(),
+        format!("ServerAliveCountMax={}", KEEP_ALIVE_COUNT_MAX),
+        "-o".to_string(),
+        "StrictHostKeyChecking=no".to_string(),
+        "-o".to_string(),
+        "UserKnownHostsFile=/dev/null".to_string(),
+        "-o".to_string(),
+        "LogLevel=error".to_string(),
+        "-o".to_string(),
+        format!("ProxyCommand={}", proxy_command),
+        "-o".to_string(),
+        "PermitLocalCommand=yes".to_string(),
+        "-o".to_string(),
+        format!("LocalCommand=echo {}", CONNECTED_MARKER),
+        "-L".to_string(),
+        format!("127.0.0.1:{}:localhost:{}", probe_port, PROBE_REMOTE_PORT),
+        flag.to_string(),
+        spec,
+        format!("{}.devpod", forward.workspace_id),
+    ]
+}
+
+fn forwards_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(FORWARDS_FILE_NAME))
+}
+
+fn load(app_handle: &AppHandle) -> Vec<PortForward> {
+    forwards_path(app_handle)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn persist(app_handle: &AppHandle, tunnels: &[Tunnel]) -> Result<(), PortsError> {
+    let path = forwards_path(app_handle)
+        .ok_or_else(|| PortsError::Persist(anyhow::anyhow!("unable to find app data dir")))?;
+    let forwards: Vec<&PortForward> = tunnels
+        .iter()
+        .filter(|tunnel| !tunnel.transient)
+        .map(|tunnel| &tunnel.forward)
+        .collect();
+    let content =
+        serde_json::to_vec_pretty(&forwards).map_err(|err| PortsError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| PortsError::Persist(err.into()))
+}
+
+fn statuses(tunnels: &[Tunnel]) -> Vec<PortForwardStatus> {
+    tunnels.iter().map(Tunnel::status).collect()
+}
+
+fn emit(app_handle: &AppHandle, statuses: Vec<PortForwardStatus>) {
+    if let Err(err) = app_handle.emit_all(PORT_FORWARDS_EVENT, statuses) {
+        warn!("Failed to send port forwards: {}", err);
+    }
+}
+
+fn notify_connection_lost(
+    app_handle: &AppHandle,
+    workspaces: &BTreeSet<String>,
+    auto_reconnect: bool,
+) {
+    let workspaces = workspaces
+        .iter()
+        .cloned()
+        .collect::<Vec<String>>()
+        .join(", ");
+    let body = if auto_reconnect {
+        format!("Lost connection to {}, reconnecting", workspaces)
+    } else {
+        format!("Lost connection to {}", workspaces)
+    };
+    if let Err(err) = notifications::notify(
+        app_handle,
+        NotificationEvent::ConnectionLost,
+        "Port forwards dropped",
+        &body,
+        vec![],
+    ) {
+        warn!("Failed to show connection lost notification: {}", err);
+    }
+}
+
+/// (Re-)establishes forwards of running workspaces, e.g. after a workspace has been restarted.
+fn supervise(app_handle: &AppHandle) {
+    let now = Instant::now();
+    let policy = ReconnectPolicy::load(app_handle);
+    let (due, sampled, dropped, recovered) = {
+        let mut tunnels = TUNNELS.lock().unwrap();
+        let mut dropped = BTreeSet::new();
+        for tunnel in tunnels.iter_mut() {
+            if tunnel.poll(app_handle) {
+                dropped.insert(tunnel.forward.workspace_id.clone());
+            }
+            tunnel.sample(app_handle);
+            tunnel.probe(app_handle, now);
+        }
+        let due: HashSet<String> = tunnels
+            .iter()
+            .filter(|tunnel| tunnel.due(now, &policy))
+            .map(|tunnel| tunnel.forward.workspace_id.clone())
+            .collect();
+        let recovered = !tunnels.iter().any(|tunnel| tunnel.dropped);
+
+        (due, !tunnels.is_empty(), dropped, recovered)
+    };
+    if sampled {
+        connections::emit_metrics(app_handle);
+    }
+    if !dropped.is_empty() && !CONNECTION_LOST.swap(true, Ordering::SeqCst) {
+        notify_connection_lost(app_handle, &dropped, policy.auto_reconnect);
+    } else if recovered && CONNECTION_LOST.swap(false, Ordering::SeqCst) {
+        info!("All dropped port forwards reconnected");
+    }
+
+    // Asking the CLI takes a while, don't block the commands in the meantime
+    let running: HashSet<String> = due
+        .iter()
+        .filter(|workspace_id| {
+            let _task =
+                debug_console::track_task("workspace status", Some(workspace_id.to_string()));
+            WorkspaceStatusCommand::new(workspace_id.to_string())
+                .exec(app_handle)
+                .is_ok_and(|state| state == RUNNING_STATE)
+        })
+        .cloned()
+        .collect();
+
+    let mut tunnels = TUNNELS.lock().unwrap();
+    // Forwards added in the meantime are picked up next time
+    for tunnel in tunnels
+        .iter_mut()
+        .filter(|tunnel| tunnel.due(now, &policy) && due.contains(&tunnel.forward.workspace_id))
+    {
+        if running.contains(&tunnel.forward.workspace_id) {
+            let context = workspaces::context(app_handle, &tunnel.forward.workspace_id);
+            tunnel.start(app_handle, context.as_deref());
+        } else {
+            tunnel.last_attempt = Some(now);
+            tunnel.set_state(PortForwardState::Pending, None);
+        }
+    }
+}
+
+/// Restores the configured forwards and keeps them up while their workspaces are running.
+pub fn setup(app_handle: &AppHandle) {
+    *TUNNELS.lock().unwrap() = load(app_handle)
+        .into_iter()
+        .map(|forward| Tunnel::new(forward, false))
+        .collect();
+
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let mut last = Vec::new();
+        loop {
+            supervise(&app_handle);
+
+            let current = statuses(&TUNNELS.lock().unwrap());
+            if current != last {
+                emit(&app_handle, current.clone());
+                last = current;
+            }
+
+            thread::sleep(SUPERVISE_INTERVAL);
+        }
+    });
+}
+
+/// Whether `remote_port` of the workspace is forwarded already, no matter to which local port.
+pub fn is_forwarded(workspace_id: &str, remote_port: u16) -> bool {
+    TUNNELS.lock().unwrap().iter().any(|tunnel| {
+        tunnel.forward.direction == PortForwardDirection::Forward
+            && tunnel.forward.workspace_id == workspace_id
+            && tunnel.forward.remote_port == remote_port
+    })
+}
+
+fn add(
+    app_handle: &AppHandle,
+    forward: PortForward,
+    transient: bool,
+) -> Result<Vec<PortForwardStatus>, PortsError> {
+    let mut tunnels = TUNNELS.lock().unwrap();
+    let taken = tunnels.iter().any(|tunnel| {
+        tunnel.forward.direction == forward.direction
+            && match forward.direction {
+                PortForwardDirection::Forward => tunnel.forward.local_port == forward.local_port,
+                PortForwardDirection::Reverse => {
+                    tunnel.forward.workspace_id == forward.workspace_id
+                        && tunnel.forward.remote_port == forward.remote_port
+                }
+            }
+    });
+    if taken {
+        return Err(match forward.direction {
+            PortForwardDirection::Forward => PortsError::AlreadyForwarded(forward.local_port),
+            PortForwardDirection::Reverse => PortsError::AlreadyExposed(forward.remote_port),
+        });
+    }
+
+    tunnels.push(Tunnel::new(forward, transient));
+    persist(app_handle, &tunnels)?;
+    let statuses = statuses(&tunnels);
+    emit(app_handle, statuses.clone());
+
+    Ok(statuses)
+}
+
+/// Forwards a port until it's removed again or the app quits, returns the forward to remove it with.
+pub fn forward_transient(
+    app_handle: &AppHandle,
+    workspace_id: String,
+    local_port: u16,
+    remote_port: u16,
+) -> Result<PortForward, PortsError> {
+    let forward = PortForward {
+        workspace_id,
+        local_port,
+        remote_port,
+        direction: PortForwardDirection::Forward,
+    };
+    add(app_handle, forward.clone(), true)?;
+
+    Ok(forward)
+}
+
+/// Whether the tunnel of `forward` is up and connected to the workspace.
+pub fn is_connected(forward: &PortForward) -> bool {
+    TUNNELS
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|tunnel| tunnel.forward == *forward && tunnel.connected.load(Ordering::SeqCst))
+}
+
+/// Stops all tunnels, their `ssh` processes would outlive the app otherwise.
+pub fn stop_all() {
+    TUNNELS.lock().unwrap().iter_mut().for_each(Tunnel::stop);
+}
+
+#[tauri::command]
+pub fn list_port_forwards() -> Vec<PortForwardStatus> {
+    statuses(&TUNNELS.lock().unwrap())
+}
+
+/// Forwards `remote_port` of the workspace to `local_port`, it's established by the supervisor once the workspace runs.
+#[tauri::command]
+pub fn forward_port(
+    app_handle: AppHandle,
+    workspace_id: String,
+    local_port: u16,
+    remote_port: u16,
+) -> Result<Vec<PortForwardStatus>, PortsError> {
+    add(
+        &app_handle,
+        PortForward {
+            workspace_id,
+            local_port,
+            remote_port,
+            direction: PortForwardDirection::Forward,
+        },
+        false,
+    )
+}
+
+/// Makes `local_port` reachable as `remote_port` inside the workspace, the reverse of `forward_port`.
+#[tauri::command]
+pub fn expose_port(
+    app_handle: AppHandle,
+    workspace_id: String,
+    local_port: u16,
+    remote_port: u16,
+) -> Result<Vec<PortForwardStatus>, PortsError> {
+    add(
+        &app_handle,
+        PortForward {
+            workspace_id,
+            local_port,
+            remote_port,
+            direction: PortForwardDirection::Reverse,
+        },
+        false,
+    )
+}
+
+/// Stops and removes a forward of either direction.
+#[tauri::command]
+pub fn unforward_port(
+    app_handle: AppHandle,
+    forward: PortForward,
+) -> Result<Vec<PortForwardStatus>, PortsError> {
+    let mut tunnels = TUNNELS.lock().unwrap();
+    let index = tunnels
+        .iter()
+        .position(|tunnel| tunnel.forward == forward)
+        .ok_or(PortsError::NotFound)?;
+
+    let mut tunnel = tunnels.remove(index);
+    tunnel.stop();
+    connections::disconnect(&app_handle, &tunnel.connection_id(), None);
+    persist(&app_handle, &tunnels)?;
+    let statuses = statuses(&tunnels);
+    emit(&app_handle, statuses.clone());
+
+    Ok(statuses)
+}
+
+/// Retries broken forwards right away, of one workspace or all of them. Needed without auto-reconnect.
+#[tauri::command]
+pub fn reconnect_port_forwards(workspace_id: Option<String>) -> Vec<PortForwardStatus> {
+    let mut tunnels = TUNNELS.lock().unwrap();
+    for tunnel in tunnels.iter_mut().filter(|tunnel| {
+        tunnel.child.is_none()
+            && workspace_id
+                .as_deref()
+                .is_none_or(|workspace_id| tunnel.forward.workspace_id == workspace_id)
+    }) {
+        tunnel.failures = 0;
+        tunnel.dropped = false;
+        tunnel.last_attempt = None;
+    }
+
+    statuses(&tunnels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_forward_to_workspace_localhost() {
+        let forward = PortForward {
+            workspace_id: "my-project".to_string(),
+            local_port: 3001,
+            remote_port: 3000,
+            direction: PortForwardDirection::Forward,
+        };
+        let args = ssh_args(
+            &forward,
+            "\"devpod-cli\" ssh --stdio --context default my-project",
+            41000,
+            41001,
+            15,
+        );
+
+        assert!(args.contains(&"ExitOnForwardFailure=yes".to_string()));
+        assert!(args.contains(&"ServerAliveInterval=15".to_string()));
+        assert!(args.contains(
+            &"ProxyCommand=\"devpod-cli\" ssh --stdio --context default my-project".to_string()
+        ));
+        // The relay listens on the local port and passes connections on to `ssh`
+        assert_eq!(
+            &args[args.len() - 5..],
+            [
+                "-L",
+                "127.0.0.1:41001:localhost:1",
+                "-L",
+                "127.0.0.1:41000:localhost:3000",
+                "my-project.devpod"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_expose_local_port_in_workspace() {
+        let forward = PortForward {
+            workspace_id: "my-project".to_string(),
+            local_port: 27000,
+            remote_port: 27001,
+            direction: PortForwardDirection::Reverse,
+        };
+        let args = ssh_args(
+            &forward,
+            "devpod-cli ssh --stdio my-project",
+            41000,
+            41001,
+            15,
+        );
+
+        assert_eq!(
+            &args[args.len() - 3..],
+            ["-R", "127.0.0.1:27001:localhost:41000", "my-project.devpod"]
+        );
+    }
+
+    #[test]
+    fn should_load_forwards_without_direction() {
+        let forwards: Vec<PortForward> = serde_json::from_str(
+            r#"[{"workspaceId":"my-project","localPort":3000,"remotePort":3000}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(forwards[0].direction, PortForwardDirection::Forward);
+    }
+
+    #[test]
+    fn should_detect_port_conflicts() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        assert!(bind_local(port).is_err());
+        drop(listener);
+        assert!(bind_local(port).is_ok());
+    }
+
+    #[test]
+    fn should_back_off_reconnects() {
+        let max_delay = Duration::from_secs(60);
+
+        assert_eq!(reconnect_delay(1, max_delay), Duration::from_secs(2));
+        assert_eq!(reconnect_delay(2, max_delay), Duration::from_secs(4));
+        assert_eq!(reconnect_delay(4, max_delay), Duration::from_secs(16));
+        assert_eq!(reconnect_delay(10, max_delay), max_delay);
+        assert_eq!(reconnect_delay(64, max_delay), max_delay);
+    }
+}
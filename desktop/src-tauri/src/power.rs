@@ -0,0 +1,228 @@
+use crate::debug_console;
+use lazy_static::lazy_static;
+use log::{info, warn};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+};
+
+lazy_static! {
+    static ref INHIBITOR: Mutex<Inhibitor> = Mutex::new(Inhibitor::default());
+}
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The OS sleep inhibitor is shared by everything that wants to keep the machine awake.
+/// It's taken with the first guard and released once the last one is dropped.
+#[derive(Default)]
+struct Inhibitor {
+    reasons: Vec<(u64, String)>,
+    handle: Option<platform::Handle>,
+    task: Option<debug_console::Tracked>,
+}
+
+#[must_use]
+pub struct SleepGuard {
+    id: u64,
+}
+
+impl Drop for SleepGuard {
+    fn drop(&mut self) {
+        let mut inhibitor = INHIBITOR.lock().unwrap();
+        inhibitor.reasons.retain(|(id, _)| *id != self.id);
+        if !inhibitor.reasons.is_empty() {
+            return;
+        }
+
+        if let Some(handle) = inhibitor.handle.take() {
+            info!("Allowing system sleep again");
+            platform::release(handle);
+        }
+        inhibitor.task = None;
+    }
+}
+
+/// Keeps the system from going to sleep until the returned guard is dropped, i.e. during workspace builds.
+/// Failing to inhibit sleep is only logged, it must never get in the way of the operation itself.
+pub fn prevent_sleep(reason: &str) -> SleepGuard {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let mut inhibitor = INHIBITOR.lock().unwrap();
+    inhibitor.reasons.push((id, reason.to_string()));
+
+    if inhibitor.handle.is_none() {
+        match platform::acquire(reason) {
+            Ok(handle) => {
+                info!("Preventing system sleep: {}", reason);
+                inhibitor.handle = Some(handle);
+                inhibitor.task = Some(debug_console::track_task(
+                    "prevent sleep",
+                    Some(reason.to_string()),
+                ));
+            }
+            Err(err) => warn!("Failed to prevent system sleep: {:#}", err),
+        }
+    }
+
+    SleepGuard { id }
+}
+
+/// Releases the inhibitor even though guards are still held, for when the app is about to be killed anyway.
+pub fn release_all() {
+    let mut inhibitor = INHIBITOR.lock().unwrap();
+    if let Some(handle) = inhibitor.handle.take() {
+        info!("Releasing sleep inhibitor");
+        platform::release(handle);
+    }
+    inhibitor.task = None;
+}
+
+/// Whether the machine runs on battery power, `false` if that can't be told, e.g. on desktops.
+pub fn on_battery() -> bool {
+    platform::on_battery()
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use anyhow::Context;
+    use std::process::{Child, Command, Stdio};
+
+    pub type Handle = Child;
+
+    #[cfg(target_os = "macos")]
+    fn command(_reason: &str) -> Command {
+        // Creates an IOKit assertion that prevents idle sleep, `-w` ends it if we're gone
+        let mut cmd = Command::new("caffeinate");
+        cmd.args(["-i", "-w", &std::process::id().to_string()]);
+
+        cmd
+    }
+
+    #[cfg(target_os = "linux")]
+    fn command(reason: &str) -> Command {
+        // systemd-inhibit holds the lock as long as the wrapped command runs, which ends if we're gone
+        let mut cmd = Command::new("systemd-inhibit");
+        cmd.args([
+            "--what=sleep:idle",
+            "--who=DevPod",
+            &format!("--why={}", reason),
+            "--mode=block",
+            "sh",
+            "-c",
+            &format!(
+                "while kill -0 {} 2>/dev/null; do sleep 10; done",
+                std::process::id()
+            ),
+        ]);
+
+        cmd
+    }
+
+    pub fn acquire(reason: &str) -> anyhow::Result<Handle> {
+        command(reason)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("spawn sleep inhibitor")
+    }
+
+    pub fn release(mut handle: Handle) {
+        let _ = handle.kill();
+        let _ = handle.wait();
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn on_battery() -> bool {
+        // The first line reads `Now drawing from 'Battery Power'` or `'AC Power'`
+        Command::new("pmset")
+            .args(["-g", "batt"])
+            .stderr(Stdio::null())
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn on_battery() -> bool {
+        let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+            return false;
+        };
+
+        supplies.flatten().any(|supply| {
+            let read = |name| std::fs::read_to_string(supply.path().join(name)).unwrap_or_default();
+            read("type").trim() == "Battery" && read("status").trim() == "Discharging"
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::{
+        sync::mpsc::{self, Sender},
+        thread::{self, JoinHandle},
+    };
+    use windows::Win32::System::Power::{
+        GetSystemPowerStatus, SetThreadExecutionState, ES_CONTINUOUS, ES_SYSTEM_REQUIRED,
+        SYSTEM_POWER_STATUS,
+    };
+
+    pub type Handle = (Sender<()>, JoinHandle<()>);
+
+    // The execution state belongs to the thread that set it, so it gets a thread of its own
+    pub fn acquire(_reason: &str) -> anyhow::Result<Handle> {
+        let (tx, rx) = mpsc::channel();
+        let (result_tx, result_rx) = mpsc::channel();
+        let thread = thread::spawn(move || unsafe {
+            let previous = SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED);
+            let _ = result_tx.send(previous.0 != 0);
+            let _ = rx.recv();
+            SetThreadExecutionState(ES_CONTINUOUS);
+        });
+
+        if !result_rx.recv().unwrap_or(false) {
+            let _ = tx.send(());
+            anyhow::bail!("SetThreadExecutionState failed");
+        }
+
+        Ok((tx, thread))
+    }
+
+    pub fn release((tx, thread): Handle) {
+        let _ = tx.send(());
+        let _ = thread.join();
+    }
+
+    pub fn on_battery() -> bool {
+        let mut status = SYSTEM_POWER_STATUS::default();
+        // 0 is offline, 1 online and 255 unknown
+        unsafe { GetSystemPowerStatus(&mut status).as_bool() && status.ACLineStatus == 0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reasons() -> Vec<String> {
+        INHIBITOR
+            .lock()
+            .unwrap()
+            .reasons
+            .iter()
+            .map(|(_, reason)| reason.clone())
+            .collect()
+    }
+
+    #[test]
+    fn should_keep_reasons_until_last_guard_dropped() {
+        let build = prevent_sleep("test build");
+        let update = prevent_sleep("test update");
+        assert!(reasons().contains(&"test build".to_string()));
+
+        drop(build);
+        let remaining = reasons();
+        assert!(!remaining.contains(&"test build".to_string()));
+        assert!(remaining.contains(&"test update".to_string()));
+
+        drop(update);
+        assert!(!reasons().contains(&"test update".to_string()));
+    }
+}
@@ -0,0 +1,761 @@
+use crate::{
+    cli_integrity::{self, CliIntegrityError},
+    command_queue::{self, QueuePriority, Slot},
+    commands::constants::{DEVPOD_BINARY_NAME, DEVPOD_UI_ENV_VAR},
+    debug_console, environment, git_credentials, operation_policies, redaction, resource_limits,
+    AppHandle,
+};
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::api::process::{Command, CommandChild, CommandEvent};
+use thiserror::Error;
+use ts_rs::TS;
+
+lazy_static! {
+    static ref PROCESSES: Mutex<BTreeMap<u64, Supervised>> = Mutex::new(BTreeMap::new());
+    static ref OPERATIONS: Mutex<HashMap<String, CancellationToken>> = Mutex::new(HashMap::new());
+    // Where the running processes are written to, so that they can be cleaned up after a crash
+    static ref REGISTRY_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+// Set once the app is shutting down, nothing new is spawned from then on
+static STOPPED: AtomicBool = AtomicBool::new(false);
+const TERMINATE_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+const REGISTRY_FILE_NAME: &str = "processes.json";
+
+/// A CLI subprocess, from spawning it until it exits.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct RunningProcess {
+    #[ts(type = "number")]
+    id: u64,
+    pid: Option<u32>,
+    program: String,
+    /// Redacted
+    args: String,
+    workspace_id: Option<String>,
+    operation_id: Option<String>,
+    started_at: DateTime<Utc>,
+    /// Left behind by a previous run of the app, only its PID is known
+    #[serde(default)]
+    adopted: bool,
+}
+
+impl RunningProcess {
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+
+    pub fn args(&self) -> &str {
+        &self.args
+    }
+}
+
+struct Supervised {
+    status: RunningProcess,
+    /// `None` for processes spawned elsewhere, e.g. in a terminal, and once it has been killed
+    child: Option<CommandChild>,
+    /// Its place in the command queue, given up once it exits
+    _slot: Option<Slot>,
+}
+
+/// Shared by all processes of an operation, cancelling it kills the running ones and keeps new ones from starting.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitStatus {
+    code: Option<i32>,
+}
+
+impl ExitStatus {
+    /// `None` if the process was terminated by a signal.
+    pub fn code(&self) -> Option<i32> {
+        self.code
+    }
+
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Output {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[derive(Error, Debug)]
+pub enum ProcessError {
+    #[error("unable to find sidecar binary")]
+    Sidecar,
+    #[error("unable to spawn {0}")]
+    Spawn(String, #[source] tauri::api::Error),
+    #[error("operation {0} has been cancelled")]
+    Cancelled(String),
+    #[error("{0} didn't finish within {1:?}")]
+    TimedOut(String, Duration),
+    #[error("the app is shutting down")]
+    ShuttingDown,
+    #[error(transparent)]
+    Integrity(#[from] CliIntegrityError),
+}
+impl serde::Serialize for ProcessError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// The token of an operation, created on first use. Tokens of finished operations are dropped unless they have been
+/// cancelled, later steps of a cancelled operation must not start.
+pub fn operation_token(operation_id: &str) -> CancellationToken {
+    OPERATIONS
+        .lock()
+        .unwrap()
+        .entry(operation_id.to_string())
+        .or_default()
+        .clone()
+}
+
+/// Whether the operation has been cancelled, without creating a token for it.
+fn is_cancelled(operation_id: &str) -> bool {
+    OPERATIONS
+        .lock()
+        .unwrap()
+        .get(operation_id)
+        .is_some_and(CancellationToken::is_cancelled)
+}
+
+/// What every CLI call runs with: the variables derived from settings, the configured ones, the git credentials of the
+/// vault and a marker for the CLI.
+pub fn environment(app_handle: &AppHandle) -> HashMap<String, String> {
+    let mut environment = environment::injected();
+    environment.extend(environment::global());
+    environment.extend(git_credentials::environment(app_handle));
+    environment.insert(DEVPOD_UI_ENV_VAR.to_string(), "true".to_string());
+
+    environment
+}
+
+#[derive(Debug, Clone)]
+enum Program {
+    Sidecar(&'static str),
+    Path(PathBuf),
+}
+
+impl Program {
+    fn name(&self) -> String {
+        match self {
+            Program::Sidecar(name) => name.to_string(),
+            Program::Path(path) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string_lossy().to_string()),
+        }
+    }
+}
+
+/// A CLI call that goes through the supervisor, which tracks it until it exits and can cancel it.
+#[derive(Debug, Clone)]
+pub struct SupervisedCommand {
+    app_handle: AppHandle,
+    program: Program,
+    args: Vec<String>,
+    workspace_id: Option<String>,
+    operation_id: Option<String>,
+    priority: QueuePriority,
+    /// Which concurrency limit applies, the provider of the workspace if it's not set
+    provider: Option<String>,
+}
+
+impl SupervisedCommand {
+    /// A binary bundled with the app, e.g. the CLI.
+    pub fn sidecar(app_handle: &AppHandle, name: &'static str) -> Self {
+        Self::with_program(app_handle, Program::Sidecar(name))
+    }
+
+    pub fn new(app_handle: &AppHandle, path: PathBuf) -> Self {
+        Self::with_program(app_handle, Program::Path(path))
+    }
+
+    fn with_program(app_handle: &AppHandle, program: Program) -> Self {
+        Self {
+            app_handle: app_handle.clone(),
+            program,
+            args: vec![],
+            workspace_id: None,
+            operation_id: None,
+            priority: QueuePriority::default(),
+            provider: None,
+        }
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_string()));
+        self
+    }
+
+    pub fn workspace(mut self, workspace_id: Option<&str>) -> Self {
+        self.workspace_id = workspace_id.map(String::from);
+        self
+    }
+
+    pub fn operation(mut self, operation_id: Option<&str>) -> Self {
+        self.operation_id = operation_id.map(String::from);
+        self
+    }
+
+    pub fn priority(mut self, priority: QueuePriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn provider(mut self, provider: Option<&str>) -> Self {
+        self.provider = provider.map(String::from);
+        self
+    }
+
+    /// Whether the command may still start.
+    fn check(&self) -> Result<(), ProcessError> {
+        if STOPPED.load(Ordering::SeqCst) {
+            return Err(ProcessError::ShuttingDown);
+        }
+        if let Some(operation_id) = &self.operation_id {
+            if is_cancelled(operation_id) {
+                return Err(ProcessError::Cancelled(operation_id.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn start(&self) -> Result<(tauri::async_runtime::Receiver<CommandEvent>, u64), ProcessError> {
+        self.check()?;
+        let slot = command_queue::acquire(
+            &self.app_handle,
+            self.priority,
+            self.provider.as_deref(),
+            &self.program.name(),
+            &self.args,
+            self.workspace_id.as_deref(),
+            || self.check(),
+        )?;
+
+        let command = match &self.program {
+            Program::Sidecar(name) => {
+                // Checked right before spawning, the binary might have been replaced while waiting for the slot
+                cli_integrity::verify(&self.app_handle)?;
+                // Updates install the cli outside of the app bundle
+                match cli_integrity::installed_cli_path(&self.app_handle) {
+                    Ok(path) if *name == DEVPOD_BINARY_NAME && path.exists() => {
+                        Command::new(path.to_string_lossy())
+                    }
+                    _ => Command::new_sidecar(*name).map_err(|_| ProcessError::Sidecar)?,
+                }
+            }
+            Program::Path(path) => Command::new(path.to_string_lossy()),
+        };
+        let mut envs = environment(&self.app_handle);
+        if let Some(workspace_id) = &self.workspace_id {
+            envs.extend(environment::for_workspace(
+                &self.app_handle,
+                workspace_id,
+                self.provider.as_deref(),
+            ));
+        }
+        let (events, child) = command
+            .envs(envs)
+            .args(&self.args)
+            .spawn()
+            .map_err(|err| ProcessError::Spawn(self.program.name(), err))?;
+        resource_limits::apply(
+            child.pid(),
+            &resource_limits::for_workspace(&self.app_handle, self.workspace_id.as_deref()),
+        );
+        let id = register(
+            RunningProcess {
+                id: 0,
+                pid: Some(child.pid()),
+                program: self.program.name(),
+                args: redaction::redact(&self.args.join(" ")).into_owned(),
+                workspace_id: self.workspace_id.clone(),
+                operation_id: self.operation_id.clone(),
+                started_at: Utc::now(),
+                adopted: false,
+            },
+            Some(child),
+            Some(slot),
+        );
+
+        Ok((events, id))
+    }
+
+    /// Runs the command to completion, `on_line` sees the output while it's running. The policy of the command decides
+    /// when it's killed and whether it's tried again.
+    pub fn run(self, mut on_line: impl FnMut(Stream, &str)) -> Result<Output, ProcessError> {
+        let args: Vec<&str> = self.args.iter().map(String::as_str).collect();
+        let _process = debug_console::track_process(&self.program.name(), &args);
+        let policy = operation_policies::for_args(&self.app_handle, &self.args);
+
+        let mut attempt = 0;
+        loop {
+            let result = self.attempt(&mut on_line, policy.timeout());
+            let transient = match &result {
+                Ok(output) => {
+                    !output.status.success() && operation_policies::is_transient(&output.stderr)
+                }
+                Err(err) => matches!(err, ProcessError::TimedOut(..)),
+            };
+            if !transient || attempt >= policy.retries() {
+                return result;
+            }
+
+            attempt += 1;
+            let delay = operation_policies::backoff(attempt);
+            warn!(
+                "Retrying {} {} in {:?}, attempt {} of {}",
+                self.program.name(),
+                redaction::redact(&self.args.join(" ")),
+                delay,
+                attempt,
+                policy.retries()
+            );
+            self.wait(delay)?;
+        }
+    }
+
+    /// Sleeps before the next attempt, unless the command is cancelled in the meantime.
+    fn wait(&self, delay: Duration) -> Result<(), ProcessError> {
+        let deadline = Instant::now() + delay;
+        while Instant::now() < deadline {
+            self.check()?;
+            thread::sleep(TERMINATE_CHECK_INTERVAL.min(deadline - Instant::now()));
+        }
+
+        self.check()
+    }
+
+    fn attempt(
+        &self,
+        on_line: &mut impl FnMut(Stream, &str),
+        timeout: Option<Duration>,
+    ) -> Result<Output, ProcessError> {
+        let (mut events, id) = self.start()?;
+        let deadline = timeout.map(|timeout| tokio::time::Instant::now() + timeout);
+
+        let mut output = Output {
+            status: ExitStatus { code: None },
+            stdout: String::new(),
+            stderr: String::new(),
+        };
+        let timed_out = tauri::async_runtime::block_on(async {
+            loop {
+                let event = match deadline {
+                    Some(deadline) => {
+                        match tokio::time::timeout_at(deadline, events.recv()).await {
+                            Ok(event) => event,
+                            Err(_) => return true,
+                        }
+                    }
+                    None => events.recv().await,
+                };
+                let Some(event) = event else {
+                    return false;
+                };
+                match event {
+                    CommandEvent::Stdout(line) => {
+                        on_line(Stream::Stdout, &line);
+                        output.stdout.push_str(&line);
+                        output.stdout.push('\n');
+                    }
+                    CommandEvent::Stderr(line) | CommandEvent::Error(line) => {
+                        on_line(Stream::Stderr, &line);
+                        output.stderr.push_str(&line);
+                        output.stderr.push('\n');
+                    }
+                    CommandEvent::Terminated(payload) => output.status.code = payload.code,
+                    _ => {}
+                }
+            }
+        });
+        if timed_out {
+            kill(id);
+        }
+        let cancelled = finish(id, self.operation_id.as_deref());
+
+        match (&self.operation_id, timeout) {
+            (Some(operation_id), _) if cancelled => {
+                Err(ProcessError::Cancelled(operation_id.clone()))
+            }
+            (_, Some(timeout)) if timed_out => {
+                Err(ProcessError::TimedOut(self.program.name(), timeout))
+            }
+            _ => Ok(output),
+        }
+    }
+
+    /// Starts the command in the background and tracks it until it exits, its output is discarded.
+    pub fn spawn(self) -> Result<u64, ProcessError> {
+        let (mut events, id) = self.start()?;
+        let program = self.program.name();
+        thread::spawn(move || {
+            tauri::async_runtime::block_on(async {
+                while let Some(event) = events.recv().await {
+                    if let CommandEvent::Terminated(payload) = event {
+                        info!("{} exited with {:?}", program, payload.code);
+                    }
+                }
+            });
+            unregister(id);
+        });
+
+        Ok(id)
+    }
+}
+
+/// Writes the processes with a PID to the registry, failing only means a crash can't be cleaned up after.
+fn persist(processes: &BTreeMap<u64, Supervised>) {
+    let path = REGISTRY_PATH.lock().unwrap();
+    let Some(path) = path.as_ref() else {
+        return;
+    };
+
+    let registered: Vec<&RunningProcess> = processes
+        .values()
+        .map(|process| &process.status)
+        .filter(|status| status.pid.is_some())
+        .collect();
+    let result = serde_json::to_vec(&registered)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| fs::write(path, content).map_err(anyhow::Error::from));
+    if let Err(err) = result {
+        warn!("Failed to write process registry: {}", err);
+    }
+}
+
+fn register(mut status: RunningProcess, child: Option<CommandChild>, slot: Option<Slot>) -> u64 {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    status.id = id;
+    let mut processes = PROCESSES.lock().unwrap();
+    processes.insert(
+        id,
+        Supervised {
+            status,
+            child,
+            _slot: slot,
+        },
+    );
+    persist(&processes);
+
+    id
+}
+
+fn kill(id: u64) {
+    let mut processes = PROCESSES.lock().unwrap();
+    let Some(process) = processes.get_mut(&id) else {
+        return;
+    };
+    if let Some(child) = process.child.take() {
+        warn!("Killing {} after it timed out", process.status.program);
+        if let Err(err) = child.kill() {
+            warn!("Failed to kill {}: {}", process.status.program, err);
+        }
+    }
+}
+
+/// Unregisters a process that has exited and tells whether its operation has been cancelled in the meantime.
+fn finish(id: u64, operation_id: Option<&str>) -> bool {
+    let cancelled = operation_id.is_some_and(is_cancelled);
+    unregister(id);
+
+    cancelled
+}
+
+fn unregister(id: u64) {
+    let mut processes = PROCESSES.lock().unwrap();
+    let Some(process) = processes.remove(&id) else {
+        return;
+    };
+    persist(&processes);
+
+    // Keep cancelled tokens around, see `operation_token`
+    if let Some(operation_id) = process.status.operation_id {
+        let running = processes
+            .values()
+            .any(|other| other.status.operation_id.as_ref() == Some(&operation_id));
+        let mut operations = OPERATIONS.lock().unwrap();
+        if !running
+            && operations
+                .get(&operation_id)
+                .is_some_and(|token| !token.is_cancelled())
+        {
+            operations.remove(&operation_id);
+        }
+    }
+}
+
+/// Starts writing the registry, returns the processes that were in it when the app last exited.
+pub fn take_registry(app_handle: &AppHandle) -> Vec<RunningProcess> {
+    let Some(path) = app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(REGISTRY_FILE_NAME))
+    else {
+        return vec![];
+    };
+
+    let leftovers = fs::read(&path)
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default();
+    *REGISTRY_PATH.lock().unwrap() = Some(path);
+    persist(&PROCESSES.lock().unwrap());
+
+    leftovers
+}
+
+/// Lists a process of a previous run until it's released, quitting terminates it along with the others.
+pub fn adopt(mut process: RunningProcess) -> u64 {
+    process.adopted = true;
+    process.operation_id = None;
+
+    register(process, None, None)
+}
+
+pub fn release(id: u64) {
+    unregister(id);
+}
+
+/// Listed among the running processes until the guard is dropped, for processes the supervisor can't spawn itself.
+#[must_use]
+pub struct Tracked(u64);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        unregister(self.0);
+    }
+}
+
+pub fn track(
+    pid: Option<u32>,
+    program: &str,
+    args: &[&str],
+    workspace_id: Option<&str>,
+) -> Tracked {
+    Tracked(register(
+        RunningProcess {
+            id: 0,
+            pid,
+            program: program.to_string(),
+            args: redaction::redact(&args.join(" ")).into_owned(),
+            workspace_id: workspace_id.map(String::from),
+            operation_id: None,
+            started_at: Utc::now(),
+            adopted: false,
+        },
+        None,
+        None,
+    ))
+}
+
+/// Asks the process to exit, which gives it the chance to clean up, or kills it right away with `force`.
+pub fn signal(pid: u32, force: bool) -> std::io::Result<()> {
+    #[cfg(unix)]
+    let mut command = {
+        let mut command = std::process::Command::new("kill");
+        command.args([if force { "-KILL" } else { "-TERM" }, &pid.to_string()]);
+        command
+    };
+    #[cfg(windows)]
+    let mut command = {
+        use std::os::windows::process::CommandExt;
+        let mut command = std::process::Command::new("taskkill");
+        // Without `/F` it's a request to close
+        if force {
+            command.arg("/F");
+        }
+        command.args(["/PID", &pid.to_string()]);
+        // CREATE_NO_WINDOW
+        command.creation_flags(0x08000000);
+        command
+    };
+
+    command
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|_| ())
+}
+
+/// Stops spawning processes and terminates the running ones, those still running after `grace` are killed.
+pub fn terminate_all(grace: Duration) {
+    STOPPED.store(true, Ordering::SeqCst);
+    let pids: Vec<u32> = PROCESSES
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|process| process.child.is_some() || process.status.adopted)
+        .filter_map(|process| process.status.pid)
+        .collect();
+    if pids.is_empty() {
+        return;
+    }
+
+    info!("Terminating {} processes", pids.len());
+    for pid in pids {
+        if let Err(err) = signal(pid, false) {
+            warn!("Failed to terminate process {}: {}", pid, err);
+        }
+    }
+
+    let deadline = Instant::now() + grace;
+    while Instant::now() < deadline {
+        let running = PROCESSES
+            .lock()
+            .unwrap()
+            .values()
+            .any(|process| process.child.is_some() || process.status.adopted);
+        if !running {
+            return;
+        }
+        thread::sleep(TERMINATE_CHECK_INTERVAL);
+    }
+
+    for process in PROCESSES.lock().unwrap().values_mut() {
+        if let Some(child) = process.child.take() {
+            warn!("Killing {} after it didn't exit", process.status.program);
+            if let Err(err) = child.kill() {
+                warn!("Failed to kill {}: {}", process.status.program, err);
+            }
+        } else if let Some(pid) = process.status.pid.filter(|_| process.status.adopted) {
+            warn!("Killing {} after it didn't exit", process.status.program);
+            if let Err(err) = signal(pid, true) {
+                warn!("Failed to kill {}: {}", process.status.program, err);
+            }
+        }
+    }
+}
+
+fn running(processes: &BTreeMap<u64, Supervised>) -> Vec<RunningProcess> {
+    processes
+        .values()
+        .map(|process| process.status.clone())
+        .collect()
+}
+
+/// Processes spawned or adopted by the supervisor that are still running, without the ones it only tracks.
+pub fn supervised() -> Vec<RunningProcess> {
+    PROCESSES
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|process| process.child.is_some() || process.status.adopted)
+        .map(|process| process.status.clone())
+        .collect()
+}
+
+#[tauri::command]
+pub fn list_processes() -> Vec<RunningProcess> {
+    running(&PROCESSES.lock().unwrap())
+}
+
+/// Kills the running processes of the operation and keeps its remaining steps from starting.
+#[tauri::command]
+pub fn cancel_operation(operation_id: String) -> Vec<RunningProcess> {
+    operation_token(&operation_id).cancel();
+
+    let mut processes = PROCESSES.lock().unwrap();
+    let mut killed = vec![];
+    for process in processes.values_mut() {
+        if process.status.operation_id.as_ref() != Some(&operation_id) {
+            continue;
+        }
+        if let Some(child) = process.child.take() {
+            match child.kill() {
+                Ok(()) => killed.push(process.status.clone()),
+                Err(err) => warn!("Failed to kill {}: {}", process.status.program, err),
+            }
+        }
+    }
+    info!(
+        "Cancelled operation {}, killed {} processes",
+        operation_id,
+        killed.len()
+    );
+
+    killed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(operation_id: &str) -> RunningProcess {
+        RunningProcess {
+            id: 0,
+            pid: None,
+            program: "devpod-cli".to_string(),
+            args: "up".to_string(),
+            workspace_id: None,
+            operation_id: Some(operation_id.to_string()),
+            started_at: Utc::now(),
+            adopted: false,
+        }
+    }
+
+    #[test]
+    fn should_keep_cancelled_operations() {
+        let id = register(process("finished"), None, None);
+        operation_token("finished");
+        assert!(!finish(id, Some("finished")));
+        assert!(!OPERATIONS.lock().unwrap().contains_key("finished"));
+
+        let id = register(process("cancelled"), None, None);
+        cancel_operation("cancelled".to_string());
+        assert!(finish(id, Some("cancelled")));
+        assert!(is_cancelled("cancelled"));
+    }
+}
@@ -1,6 +1,9 @@
+use crate::audit_log::{self, AuditCategory, AuditInitiator};
 use crate::commands::delete_pro_instance::DeleteProInstanceCommand;
 use crate::commands::list_pro_instances::{ListProInstancesCommand, ProInstance};
-use crate::commands::{delete_provider::DeleteProviderCommand, DevpodCommandConfig};
+use crate::commands::{
+    delete_provider::DeleteProviderCommand, DevpodCommandConfig, DevpodCommandError,
+};
 use crate::util::with_data_store;
 use crate::AppHandle;
 use log::{debug, error, info, warn};
@@ -33,7 +36,7 @@ pub fn check_dangling_provider(app_handle: &AppHandle) {
             dangling_providers.join(", ")
         );
 
-        let pro_instances = match ListProInstancesCommand::new().exec() {
+        let pro_instances = match ListProInstancesCommand::new().exec(app_handle) {
             Ok(pro_instances) => pro_instances,
             Err(err) => {
                 error!("Failed to list pro instances, {}", err);
@@ -43,16 +46,22 @@ pub fn check_dangling_provider(app_handle: &AppHandle) {
 
         for dangling_provider in dangling_providers.iter() {
             // Make sure we clean up accompanying pro instances
-            check_pro_instances(&pro_instances, &dangling_provider);
+            check_pro_instances(app_handle, &pro_instances, &dangling_provider);
 
             if DeleteProviderCommand::new(dangling_provider.clone())
-                .exec()
+                .exec(app_handle)
                 .is_ok()
                 && store.delete(dangling_provider_key).is_ok()
             {
                 info!(
-                    "Successfully deleted dangling provider: {}",
-                    dangling_provider
+                    target: module_path!(), provider = dangling_provider.as_str();
+                    "Successfully deleted dangling provider"
+                );
+                audit_log::record(
+                    AuditInitiator::Backend,
+                    AuditCategory::Delete,
+                    "delete dangling provider",
+                    Some(dangling_provider.as_str()),
                 );
                 let _ = store.save();
             }
@@ -62,7 +71,45 @@ pub fn check_dangling_provider(app_handle: &AppHandle) {
     });
 }
 
-fn check_pro_instances(pro_instances: &Vec<ProInstance>, dangling_provider: &String) {
+/// Runs in the backend instead of the UI, the app lock can't be skipped this way.
+#[tauri::command]
+pub fn delete_provider(
+    app_handle: AppHandle,
+    provider_id: String,
+) -> Result<(), DevpodCommandError> {
+    DeleteProviderCommand::new(provider_id.clone()).exec(&app_handle)?;
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Delete,
+        "remove provider",
+        Some(&provider_id),
+    );
+
+    Ok(())
+}
+
+/// Runs in the backend instead of the UI, the app lock can't be skipped this way.
+#[tauri::command]
+pub fn delete_pro_instance(
+    app_handle: AppHandle,
+    pro_id: String,
+) -> Result<(), DevpodCommandError> {
+    DeleteProInstanceCommand::new(pro_id.clone()).exec(&app_handle)?;
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Delete,
+        "delete pro instance",
+        Some(&pro_id),
+    );
+
+    Ok(())
+}
+
+fn check_pro_instances(
+    app_handle: &AppHandle,
+    pro_instances: &Vec<ProInstance>,
+    dangling_provider: &String,
+) {
     if let Some(pro_instance) = pro_instances
         .iter()
         .find(|pro_instance| pro_instance.id() == Some(dangling_provider))
@@ -79,8 +126,16 @@ fn check_pro_instances(pro_instances: &Vec<ProInstance>, dangling_provider: &Str
             dangling_provider, pro_id
         );
 
-        match DeleteProInstanceCommand::new(pro_id.to_string()).exec() {
-            Ok(_) => info!("Successfully deleted pro instance {}", pro_id),
+        match DeleteProInstanceCommand::new(pro_id.to_string()).exec(app_handle) {
+            Ok(_) => {
+                info!("Successfully deleted pro instance {}", pro_id);
+                audit_log::record(
+                    AuditInitiator::Backend,
+                    AuditCategory::Delete,
+                    "delete pro instance",
+                    Some(pro_id.as_str()),
+                );
+            }
             Err(err) => error!("Failed to delete pro instance {}, {}", pro_id, err),
         }
     }
@@ -0,0 +1,156 @@
+use log::warn;
+use std::{
+    io::{self, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+// How often the accept loop checks whether the relay has been stopped
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+const BUFFER_SIZE: usize = 16 * 1024;
+
+/// Relays TCP connections from a listener to `target` and counts the bytes in both directions.
+/// Stops accepting connections once dropped, open connections end with their peers.
+pub struct Relay {
+    stopped: Arc<AtomicBool>,
+    /// From the accepted connections to `target`
+    sent: Arc<AtomicU64>,
+    /// From `target` back to the accepted connections
+    received: Arc<AtomicU64>,
+}
+
+impl Relay {
+    pub fn start(listener: TcpListener, target: SocketAddr) -> io::Result<Self> {
+        listener.set_nonblocking(true)?;
+        let relay = Self {
+            stopped: Arc::new(AtomicBool::new(false)),
+            sent: Arc::new(AtomicU64::new(0)),
+            received: Arc::new(AtomicU64::new(0)),
+        };
+
+        let stopped = Arc::clone(&relay.stopped);
+        let sent = Arc::clone(&relay.sent);
+        let received = Arc::clone(&relay.received);
+        thread::spawn(move || {
+            while !stopped.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((client, _)) => {
+                        if let Err(err) = relay_connection(
+                            client,
+                            target,
+                            Arc::clone(&sent),
+                            Arc::clone(&received),
+                        ) {
+                            warn!("Failed to relay connection to {}: {}", target, err);
+                        }
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_INTERVAL)
+                    }
+                    Err(err) => {
+                        warn!("Failed to accept connection for {}: {}", target, err);
+                        thread::sleep(ACCEPT_INTERVAL);
+                    }
+                }
+            }
+        });
+
+        Ok(relay)
+    }
+
+    /// Bytes sent and received so far.
+    pub fn totals(&self) -> (u64, u64) {
+        (
+            self.sent.load(Ordering::Relaxed),
+            self.received.load(Ordering::Relaxed),
+        )
+    }
+}
+
+impl Drop for Relay {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+fn relay_connection(
+    client: TcpStream,
+    target: SocketAddr,
+    sent: Arc<AtomicU64>,
+    received: Arc<AtomicU64>,
+) -> io::Result<()> {
+    // Accepted sockets inherit non-blocking mode on some platforms
+    client.set_nonblocking(false)?;
+    let upstream = TcpStream::connect(target)?;
+    let client_reader = client.try_clone()?;
+    let upstream_reader = upstream.try_clone()?;
+
+    thread::spawn(move || copy(client_reader, upstream, &sent));
+    thread::spawn(move || copy(upstream_reader, client, &received));
+
+    Ok(())
+}
+
+/// Copies between two connections in both directions, returns once both are done.
+pub fn splice(client: TcpStream, upstream: TcpStream) -> io::Result<()> {
+    let client_reader = client.try_clone()?;
+    let upstream_reader = upstream.try_clone()?;
+    let uploading = thread::spawn(move || copy(client_reader, upstream, &AtomicU64::new(0)));
+    copy(upstream_reader, client, &AtomicU64::new(0));
+    let _ = uploading.join();
+
+    Ok(())
+}
+
+/// Copies until either side closes, then lets the other side know.
+fn copy(mut from: TcpStream, mut to: TcpStream, counter: &AtomicU64) {
+    let mut buffer = [0; BUFFER_SIZE];
+    loop {
+        let read = match from.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        counter.fetch_add(read as u64, Ordering::Relaxed);
+        if to.write_all(&buffer[..read]).is_err() {
+            break;
+        }
+    }
+
+    let _ = to.shutdown(Shutdown::Write);
+    let _ = from.shutdown(Shutdown::Read);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_count_relayed_bytes() {
+        let target = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let target_address = target.local_addr().unwrap();
+        let echo = thread::spawn(move || {
+            let (mut stream, _) = target.accept().unwrap();
+            let mut request = [0; 5];
+            stream.read_exact(&mut request).unwrap();
+            stream.write_all(b"pong!!").unwrap();
+        });
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        let relay = Relay::start(listener, target_address).unwrap();
+
+        let mut client = TcpStream::connect(address).unwrap();
+        client.write_all(b"ping!").unwrap();
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).unwrap();
+        echo.join().unwrap();
+
+        assert_eq!(response, b"pong!!");
+        assert_eq!(relay.totals(), (5, 6));
+    }
+}
@@ -0,0 +1,298 @@
+use crate::{
+    settings::Settings,
+    updates::{Release, UpdateHelper},
+    AppHandle, AppState,
+};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tauri::Manager;
+use thiserror::Error;
+use ts_rs::TS;
+
+const RELEASE_NOTES_DIR: &str = "release_notes";
+
+#[derive(Error, Debug)]
+pub enum ReleaseNotesError {
+    #[error("invalid version {0}")]
+    InvalidVersion(String),
+    #[error("no release notes found for {0}")]
+    NotFound(String),
+    #[error("failed to fetch releases {0}")]
+    FetchRelease(#[source] anyhow::Error),
+}
+impl serde::Serialize for ReleaseNotesError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// The changes between two versions, newest release first.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReleaseNotes {
+    from_version: String,
+    to_version: String,
+    releases: Vec<ReleaseNote>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReleaseNote {
+    version: String,
+    published_at: Option<DateTime<Utc>>,
+    breaking: Vec<String>,
+    features: Vec<String>,
+    fixes: Vec<String>,
+    other: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Breaking,
+    Features,
+    Fixes,
+    Other,
+}
+
+/// Returns the release notes of all releases after `from_version` up to and including `to_version`.
+/// Defaults to the running app version and the pending update, or the latest release of the update channel.
+#[tauri::command]
+pub async fn get_release_notes(
+    app_handle: AppHandle,
+    from_version: Option<String>,
+    to_version: Option<String>,
+) -> Result<ReleaseNotes, ReleaseNotesError> {
+    let from_version =
+        from_version.unwrap_or_else(|| app_handle.package_info().version.to_string());
+    let from = parse_version(&from_version)?;
+
+    let update_helper = UpdateHelper::new(&app_handle);
+    let to_version = match to_version {
+        Some(to_version) => to_version,
+        None => {
+            let pending_update = app_handle
+                .state::<AppState>()
+                .pending_update
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|release| release.tag_name.clone());
+            match pending_update {
+                Some(tag_name) => tag_name,
+                None => {
+                    update_helper
+                        .latest_release(Settings::update_channel(&app_handle))
+                        .await
+                        .map_err(ReleaseNotesError::FetchRelease)?
+                        .tag_name
+                }
+            }
+        }
+    };
+    let to = parse_version(&to_version)?;
+
+    let notes = match update_helper.fetch_releases_since(&from).await {
+        Ok(releases) => releases
+            .iter()
+            .map(|release| cached_or_parse(&app_handle, release))
+            .collect(),
+        Err(err) => {
+            warn!(
+                "Failed to fetch releases, using cached release notes: {}",
+                err
+            );
+            load_cached(&app_handle)
+        }
+    };
+
+    let mut releases: Vec<_> = notes
+        .into_iter()
+        .filter_map(|note| Some((parse_version(&note.version).ok()?, note)))
+        .filter(|(version, _)| version > &from && version <= &to)
+        .collect();
+    if releases.is_empty() {
+        return Err(ReleaseNotesError::NotFound(to_version));
+    }
+    releases.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    Ok(ReleaseNotes {
+        from_version: normalize_version(&from_version),
+        to_version: normalize_version(&to_version),
+        releases: releases.into_iter().map(|(_, note)| note).collect(),
+    })
+}
+
+fn normalize_version(version: &str) -> String {
+    version.trim().trim_start_matches('v').to_string()
+}
+
+fn parse_version(version: &str) -> Result<semver::Version, ReleaseNotesError> {
+    semver::Version::parse(&normalize_version(version))
+        .map_err(|_| ReleaseNotesError::InvalidVersion(version.to_string()))
+}
+
+// Release bodies don't change after publishing, so we only parse each of them once
+fn cached_or_parse(app_handle: &AppHandle, release: &Release) -> ReleaseNote {
+    let cache_path = cache_path(app_handle, &release.tag_name);
+    if let Some(note) = cache_path
+        .as_ref()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|data| serde_json::from_slice(&data).ok())
+    {
+        return note;
+    }
+
+    let mut note = parse_release_notes(release.body.as_deref().unwrap_or_default());
+    note.version = normalize_version(&release.tag_name);
+    note.published_at = release.published_at;
+
+    if let Some(path) = cache_path {
+        if let Err(err) = write_cache(&path, &note) {
+            warn!(
+                "Failed to cache release notes for {}: {}",
+                release.tag_name, err
+            );
+        }
+    }
+
+    note
+}
+
+fn write_cache(path: &Path, note: &ReleaseNote) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_vec(note)?).context("write release notes")
+}
+
+fn load_cached(app_handle: &AppHandle) -> Vec<ReleaseNote> {
+    let dir = match app_handle.path_resolver().app_cache_dir() {
+        Some(dir) => dir.join(RELEASE_NOTES_DIR),
+        None => return vec![],
+    };
+
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| fs::read(entry.ok()?.path()).ok())
+                .filter_map(|data| serde_json::from_slice(&data).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn cache_path(app_handle: &AppHandle, tag_name: &str) -> Option<PathBuf> {
+    let mut path = app_handle.path_resolver().app_cache_dir()?;
+    path.push(RELEASE_NOTES_DIR);
+    path.push(format!("{}.json", tag_name));
+
+    Some(path)
+}
+
+/// Sorts the list items of a markdown release body into sections, based on the heading they are listed under.
+/// Items with a conventional commit prefix, i.e. `fix: ...` or `feat!: ...`, are sorted by their prefix instead.
+fn parse_release_notes(body: &str) -> ReleaseNote {
+    let mut note = ReleaseNote::default();
+    let mut section = Section::Other;
+
+    for line in body.lines().map(str::trim) {
+        if let Some(heading) = line.strip_prefix('#') {
+            section = heading_section(heading.trim_start_matches('#'));
+            continue;
+        }
+
+        let item = match line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+            Some(item) => item.trim(),
+            None => continue,
+        };
+        if item.is_empty() {
+            continue;
+        }
+
+        let items = match item_section(item).unwrap_or(section) {
+            Section::Breaking => &mut note.breaking,
+            Section::Features => &mut note.features,
+            Section::Fixes => &mut note.fixes,
+            Section::Other => &mut note.other,
+        };
+        items.push(item.to_string());
+    }
+
+    note
+}
+
+fn heading_section(heading: &str) -> Section {
+    let heading = heading.to_lowercase();
+    if heading.contains("breaking") {
+        Section::Breaking
+    } else if heading.contains("feature")
+        || heading.contains("enhancement")
+        || heading.contains("new")
+    {
+        Section::Features
+    } else if heading.contains("fix") || heading.contains("bug") {
+        Section::Fixes
+    } else {
+        Section::Other
+    }
+}
+
+fn item_section(item: &str) -> Option<Section> {
+    let (prefix, _) = item.split_once(':')?;
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.ends_with('!') || prefix.starts_with("breaking") {
+        return Some(Section::Breaking);
+    }
+    // strip the scope, i.e. `fix(ui)`
+    let kind = prefix.split('(').next().unwrap_or_default();
+
+    match kind {
+        "feat" | "feature" => Some(Section::Features),
+        "fix" | "bugfix" => Some(Section::Fixes),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_sections() {
+        let body = "## What's Changed\n\
+            - fix(ui): sidebar flickering\n\
+            - feat!: drop support for old providers\n\
+            ### New Features\n\
+            * Support for release channels\n\
+            ### Bug Fixes\n\
+            - Crash on startup\n\
+            Some text that isn't a list item\n";
+        let note = parse_release_notes(body);
+
+        assert_eq!(note.breaking, vec!["feat!: drop support for old providers"]);
+        assert_eq!(note.features, vec!["Support for release channels"]);
+        assert_eq!(
+            note.fixes,
+            vec!["fix(ui): sidebar flickering", "Crash on startup"]
+        );
+        assert!(note.other.is_empty());
+    }
+
+    #[test]
+    fn should_put_unknown_items_into_other() {
+        let note = parse_release_notes("- chore: bump dependencies\n- Improve docs");
+
+        assert_eq!(note.other, vec!["chore: bump dependencies", "Improve docs"]);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{
+    installed_ides::{self, IdeSource, InstalledIde},
+    process::SupervisedCommand,
+    ssh_config::{self, cli_path},
+    workspaces, AppHandle,
+};
+use log::info;
+use std::process::{Command, Stdio};
+use tauri::Manager;
+use thiserror::Error;
+
+/// Editors that connect to the workspace on their own rather than through a server the CLI deploys for them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RemoteEditor {
+    Cursor,
+    Zed,
+    Fleet,
+}
+
+#[derive(Error, Debug)]
+pub enum RemoteEditorError {
+    #[error("{0} is not supported, register it as a custom IDE instead")]
+    Unsupported(String),
+    #[error("{0} is not installed")]
+    NotInstalled(&'static str),
+    #[error("unable to launch {0}")]
+    Launch(&'static str, #[source] anyhow::Error),
+}
+impl serde::Serialize for RemoteEditorError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+impl RemoteEditor {
+    fn of(id: &str) -> Option<Self> {
+        match id {
+            "cursor" => Some(Self::Cursor),
+            "zed" => Some(Self::Zed),
+            "fleet" => Some(Self::Fleet),
+            _ => None,
+        }
+    }
+
+    fn id(&self) -> &'static str {
+        match self {
+            Self::Cursor => "cursor",
+            Self::Zed => "zed",
+            Self::Fleet => "fleet",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Cursor => "Cursor",
+            Self::Zed => "Zed",
+            Self::Fleet => "Fleet",
+        }
+    }
+
+    /// What the editor's launcher opens the workspace with.
+    fn args(&self, workspace_id: &str) -> Vec<String> {
+        let host = ssh_config::host_alias(workspace_id);
+        let folder = workspaces::remote_folder(workspace_id);
+        match self {
+            // A fork of VS Code, including its Remote SSH extension
+            Self::Cursor => vec![
+                "--folder-uri".to_string(),
+                format!("vscode-remote://ssh-remote+{}{}", host, folder),
+            ],
+            Self::Zed => vec![format!("ssh://{}{}", host, folder)],
+            // Fleet joins a workspace server, which the CLI starts in the workspace before handing over the link
+            Self::Fleet => vec!["up".to_string(), workspace_id.to_string()],
+        }
+    }
+
+    /// The URL handler the app registered, for installs without a launcher on the path.
+    fn url(&self, workspace_id: &str) -> Option<String> {
+        let host = ssh_config::host_alias(workspace_id);
+        let folder = workspaces::remote_folder(workspace_id);
+        match self {
+            Self::Cursor => Some(format!(
+                "cursor://vscode-remote/ssh-remote+{}{}",
+                host, folder
+            )),
+            Self::Zed => Some(format!("zed://ssh/{}{}", host, folder)),
+            Self::Fleet => None,
+        }
+    }
+}
+
+fn spawn(mut command: Command) -> std::io::Result<()> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW
+        command.creation_flags(0x08000000);
+    }
+
+    command.spawn().map(|_| ())
+}
+
+fn open_fleet(app_handle: &AppHandle, workspace_id: &str) -> anyhow::Result<()> {
+    let context =
+        workspaces::context(app_handle, workspace_id).unwrap_or_else(|| "default".to_string());
+    SupervisedCommand::new(app_handle, cli_path(app_handle)?)
+        .args(RemoteEditor::Fleet.args(workspace_id))
+        .args(["--ide", "fleet", "--context", &context])
+        .workspace(Some(workspace_id))
+        .spawn()?;
+
+    Ok(())
+}
+
+fn open_installed(
+    app_handle: &AppHandle,
+    editor: RemoteEditor,
+    installed: &InstalledIde,
+    workspace_id: &str,
+) -> anyhow::Result<()> {
+    if installed.source() == IdeSource::Path {
+        let mut command = Command::new(installed.path());
+        command.args(editor.args(workspace_id));
+        return Ok(spawn(command)?);
+    }
+
+    let url = editor
+        .url(workspace_id)
+        .ok_or_else(|| anyhow::anyhow!("no launcher found"))?;
+    Ok(tauri::api::shell::open(
+        &app_handle.shell_scope(),
+        url,
+        None,
+    )?)
+}
+
+/// Opens the workspace in Cursor, Zed or Fleet through their own remote workflows.
+#[tauri::command]
+pub fn open_remote_editor(
+    app_handle: AppHandle,
+    workspace_id: String,
+    editor: String,
+) -> Result<(), RemoteEditorError> {
+    let editor =
+        RemoteEditor::of(&editor).ok_or_else(|| RemoteEditorError::Unsupported(editor.clone()))?;
+    let installed = installed_ides::installed(false)
+        .into_iter()
+        .find(|installed| installed.id() == editor.id())
+        .ok_or(RemoteEditorError::NotInstalled(editor.name()))?;
+
+    info!("Opening {} in {}", workspace_id, editor.name());
+    match editor {
+        RemoteEditor::Fleet => open_fleet(&app_handle, &workspace_id),
+        _ => open_installed(&app_handle, editor, &installed, &workspace_id),
+    }
+    .map_err(|err| RemoteEditorError::Launch(editor.name(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_remote_targets() {
+        assert_eq!(
+            RemoteEditor::Zed.args("my-project"),
+            vec!["ssh://my-project.devpod/workspaces/my-project"]
+        );
+        assert_eq!(
+            RemoteEditor::Cursor.url("my-project").unwrap(),
+            "cursor://vscode-remote/ssh-remote+my-project.devpod/workspaces/my-project"
+        );
+        assert!(RemoteEditor::Fleet.url("my-project").is_none());
+        assert!(RemoteEditor::of("vscodium").is_none());
+    }
+}
@@ -0,0 +1,281 @@
+use crate::{settings::Settings, AppHandle};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Caps for CLI subprocesses, so that a workspace build doesn't make the host unusable. Processes they start, e.g.
+/// `docker` or `ssh`, are covered as well, containers run by a daemon are not.
+///
+/// Linux puts the process into a systemd scope with the CPU and memory limits, Windows into a job object. macOS
+/// doesn't enforce memory limits of processes, only the niceness is applied there.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ResourceLimits {
+    /// Percent of a single core, `200` allows two full cores
+    cpu_percent: Option<u32>,
+    memory_mb: Option<u32>,
+    /// 0 to 19, higher values leave more room for other programs
+    nice: Option<u8>,
+}
+
+impl ResourceLimits {
+    /// Unset limits fall back to the ones of `fallback`.
+    fn or(self, fallback: Self) -> Self {
+        Self {
+            cpu_percent: self.cpu_percent.or(fallback.cpu_percent),
+            memory_mb: self.memory_mb.or(fallback.memory_mb),
+            nice: self.nice.or(fallback.nice),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The limits for processes of the workspace, the workspace's own take precedence over the global ones.
+pub fn for_workspace(app_handle: &AppHandle, workspace_id: Option<&str>) -> ResourceLimits {
+    let global = Settings::resource_limits(app_handle);
+    workspace_id
+        .and_then(|workspace_id| {
+            Settings::workspace_resource_limits(app_handle).remove(workspace_id)
+        })
+        .map_or(global, |limits| limits.or(global))
+}
+
+/// Limits the running process. Failing to only means it runs unrestricted, so it's logged rather than returned.
+pub fn apply(pid: u32, limits: &ResourceLimits) {
+    if limits.is_empty() {
+        return;
+    }
+
+    debug!("Limiting process {} to {:?}", pid, limits);
+    if let Err(err) = platform::apply(pid, limits) {
+        warn!("Failed to limit resources of process {}: {:#}", pid, err);
+    }
+}
+
+#[cfg(unix)]
+fn renice(pid: u32, nice: u8) -> anyhow::Result<()> {
+    use anyhow::Context;
+    use std::process::{Command, Stdio};
+
+    let status = Command::new("renice")
+        .args(["-n", &nice.min(19).to_string(), "-p", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("run renice")?;
+    anyhow::ensure!(status.success(), "renice exited with {}", status);
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{renice, ResourceLimits};
+    use anyhow::Context;
+    use std::process::{Command, Stdio};
+
+    /// Arguments for `busctl` that move the process into a transient scope, which is what `systemd-run --scope`
+    /// does for the processes it starts. The scope goes away once the process and its children have exited.
+    fn transient_scope_args(
+        pid: u32,
+        cpu_percent: Option<u32>,
+        memory_mb: Option<u32>,
+    ) -> Vec<String> {
+        let mut properties = vec![vec![
+            "PIDs".to_string(),
+            "au".to_string(),
+            "1".to_string(),
+            pid.to_string(),
+        ]];
+        if let Some(cpu_percent) = cpu_percent {
+            let usec_per_sec = u64::from(cpu_percent.max(1)) * 10_000;
+            properties.push(vec![
+                "CPUQuotaPerSecUSec".to_string(),
+                "t".to_string(),
+                usec_per_sec.to_string(),
+            ]);
+        }
+        if let Some(memory_mb) = memory_mb {
+            let bytes = u64::from(memory_mb) * 1024 * 1024;
+            properties.push(vec![
+                "MemoryMax".to_string(),
+                "t".to_string(),
+                bytes.to_string(),
+            ]);
+        }
+
+        let mut args = [
+            "--user",
+            "call",
+            "org.freedesktop.systemd1",
+            "/org/freedesktop/systemd1",
+            "org.freedesktop.systemd1.Manager",
+            "StartTransientUnit",
+            "ssa(sv)a(sa(sv))",
+        ]
+        .map(String::from)
+        .to_vec();
+        args.push(format!("devpod-cli-{}.scope", pid));
+        args.push("fail".to_string());
+        args.push(properties.len().to_string());
+        args.extend(properties.into_iter().flatten());
+        // No auxiliary units
+        args.push("0".to_string());
+
+        args
+    }
+
+    pub fn apply(pid: u32, limits: &ResourceLimits) -> anyhow::Result<()> {
+        if limits.cpu_percent.is_some() || limits.memory_mb.is_some() {
+            let output = Command::new("busctl")
+                .args(transient_scope_args(
+                    pid,
+                    limits.cpu_percent,
+                    limits.memory_mb,
+                ))
+                .stdin(Stdio::null())
+                .output()
+                .context("run busctl")?;
+            anyhow::ensure!(
+                output.status.success(),
+                "unable to create systemd scope: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        if let Some(nice) = limits.nice {
+            renice(pid, nice)?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn should_create_scope_with_limits() {
+            let args = transient_scope_args(4242, Some(150), Some(2048));
+            let properties = &args[args.iter().position(|arg| arg == "fail").unwrap() + 1..];
+            assert_eq!(
+                properties,
+                [
+                    "3",
+                    "PIDs",
+                    "au",
+                    "1",
+                    "4242",
+                    "CPUQuotaPerSecUSec",
+                    "t",
+                    "1500000",
+                    "MemoryMax",
+                    "t",
+                    "2147483648",
+                    "0"
+                ]
+            );
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{renice, ResourceLimits};
+
+    pub fn apply(pid: u32, limits: &ResourceLimits) -> anyhow::Result<()> {
+        match limits.nice {
+            Some(nice) => renice(pid, nice),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::ResourceLimits;
+    use std::{ffi::c_void, mem::size_of};
+    use windows::{
+        core::PCWSTR,
+        Win32::{
+            Foundation::{CloseHandle, HANDLE},
+            System::{
+                JobObjects::{
+                    AssignProcessToJobObject, CreateJobObjectW, JobObjectCpuRateControlInformation,
+                    JobObjectExtendedLimitInformation, SetInformationJobObject,
+                    JOBOBJECT_CPU_RATE_CONTROL_INFORMATION, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+                    JOB_OBJECT_CPU_RATE_CONTROL_ENABLE, JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+                    JOB_OBJECT_LIMIT_JOB_MEMORY, JOB_OBJECT_LIMIT_PRIORITY_CLASS,
+                },
+                Threading::{
+                    OpenProcess, BELOW_NORMAL_PRIORITY_CLASS, IDLE_PRIORITY_CLASS,
+                    PROCESS_SET_QUOTA, PROCESS_TERMINATE,
+                },
+            },
+        },
+    };
+
+    // From this niceness on processes only run when nothing else wants the CPU
+    const IDLE_NICE: u8 = 15;
+
+    unsafe fn limit_job(job: HANDLE, limits: &ResourceLimits) -> anyhow::Result<()> {
+        let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+        if let Some(memory_mb) = limits.memory_mb {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_MEMORY;
+            info.JobMemoryLimit = memory_mb as usize * 1024 * 1024;
+        }
+        if let Some(nice) = limits.nice.filter(|nice| *nice > 0) {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PRIORITY_CLASS;
+            info.BasicLimitInformation.PriorityClass = if nice >= IDLE_NICE {
+                IDLE_PRIORITY_CLASS.0
+            } else {
+                BELOW_NORMAL_PRIORITY_CLASS.0
+            };
+        }
+        SetInformationJobObject(
+            job,
+            JobObjectExtendedLimitInformation,
+            &info as *const _ as *const c_void,
+            size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        )
+        .ok()?;
+
+        if let Some(cpu_percent) = limits.cpu_percent {
+            // The rate is in hundredths of a percent of all cores
+            let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get()) as u32;
+            let mut rate = JOBOBJECT_CPU_RATE_CONTROL_INFORMATION::default();
+            rate.ControlFlags =
+                JOB_OBJECT_CPU_RATE_CONTROL_ENABLE | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP;
+            rate.Anonymous.CpuRate = (cpu_percent * 100 / cores).clamp(1, 10_000);
+            SetInformationJobObject(
+                job,
+                JobObjectCpuRateControlInformation,
+                &rate as *const _ as *const c_void,
+                size_of::<JOBOBJECT_CPU_RATE_CONTROL_INFORMATION>() as u32,
+            )
+            .ok()?;
+        }
+
+        Ok(())
+    }
+
+    /// The job lives on as long as the process or one of its children does, its handle isn't needed afterwards.
+    pub fn apply(pid: u32, limits: &ResourceLimits) -> anyhow::Result<()> {
+        unsafe {
+            let job = CreateJobObjectW(None, PCWSTR::null())?;
+            let result = limit_job(job, limits).and_then(|_| {
+                let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, false, pid)?;
+                let assigned = AssignProcessToJobObject(job, process).ok();
+                CloseHandle(process);
+
+                assigned.map_err(anyhow::Error::from)
+            });
+            CloseHandle(job);
+
+            result
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use crate::{
+    action_logs, cli_runner,
+    commands::{
+        workspace_logs::WorkspaceLogsCommand, workspace_status::WorkspaceStatusCommand,
+        DevpodCommandConfig, DEVPOD_BINARY_NAME,
+    },
+    process::{Stream, SupervisedCommand},
+    ui_messages::{OperationInterruptedMsg, ShowToastMsg, ToastStatus, UiMessage},
+    AppHandle, AppState,
+};
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+    thread,
+};
+use tauri::Manager;
+use thiserror::Error;
+use ts_rs::TS;
+
+const OPERATIONS_FILE_NAME: &str = "resumable_operations.json";
+
+lazy_static! {
+    // Left over from the last run and neither resumed nor rolled back yet
+    static ref INTERRUPTED: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum ResumableKind {
+    Up,
+    Migrate,
+    Archive,
+}
+
+/// A long running workspace operation, kept on disk until it finishes so that it can be picked up after a restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ResumableOperation {
+    /// The action it runs as, resuming continues its log
+    id: String,
+    kind: ResumableKind,
+    workspace_id: String,
+    /// CLI arguments that continue where it stopped, e.g. running `up` again
+    resume_args: Vec<String>,
+    /// CLI arguments that undo what has been done so far, `None` if it can't be undone
+    rollback_args: Option<Vec<String>>,
+    started_at: DateTime<Utc>,
+}
+
+impl ResumableOperation {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn kind(&self) -> ResumableKind {
+        self.kind
+    }
+
+    pub fn workspace_id(&self) -> &str {
+        &self.workspace_id
+    }
+
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ResumableOperationError {
+    #[error("operation {0} not found")]
+    NotFound(String),
+    #[error("operation {0} can't be rolled back")]
+    NoRollback(String),
+    #[error("operation {0} can't be resumed by deleting data")]
+    Destructive(String),
+    #[error("unable to save operations")]
+    Persist(#[source] anyhow::Error),
+}
+impl serde::Serialize for ResumableOperationError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn operations_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(OPERATIONS_FILE_NAME))
+}
+
+fn load(app_handle: &AppHandle) -> BTreeMap<String, ResumableOperation> {
+    operations_path(app_handle)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(
+    app_handle: &AppHandle,
+    operations: &BTreeMap<String, ResumableOperation>,
+) -> Result<(), ResumableOperationError> {
+    let path = operations_path(app_handle).ok_or_else(|| {
+        ResumableOperationError::Persist(anyhow::anyhow!("unable to find app data dir"))
+    })?;
+    let content = serde_json::to_vec_pretty(operations)
+        .map_err(|err| ResumableOperationError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| ResumableOperationError::Persist(err.into()))
+}
+
+fn find(app_handle: &AppHandle, id: &str) -> Result<ResumableOperation, ResumableOperationError> {
+    load(app_handle)
+        .remove(id)
+        .ok_or_else(|| ResumableOperationError::NotFound(id.to_string()))
+}
+
+fn take(app_handle: &AppHandle, id: &str) -> Result<ResumableOperation, ResumableOperationError> {
+    let mut operations = load(app_handle);
+    let operation = operations
+        .remove(id)
+        .ok_or_else(|| ResumableOperationError::NotFound(id.to_string()))?;
+    save(app_handle, &operations)?;
+    INTERRUPTED.lock().unwrap().remove(id);
+
+    Ok(operation)
+}
+
+/// The operations that are currently running, interrupted ones only once they've been resumed or rolled back.
+pub fn running(app_handle: &AppHandle) -> Vec<ResumableOperation> {
+    let interrupted = INTERRUPTED.lock().unwrap();
+
+    load(app_handle)
+        .into_values()
+        .filter(|operation| !interrupted.contains(&operation.id))
+        .collect()
+}
+
+/// A line of CLI output as the UI logs it, see `TStreamEvent`.
+fn stream_event(stream: Stream, line: &str) -> String {
+    let output = serde_json::from_str::<serde_json::Value>(line).unwrap_or_else(
+        |_| serde_json::json!({ "time": Utc::now(), "level": "info", "message": line }),
+    );
+    match stream {
+        Stream::Stdout => serde_json::json!({ "type": "data", "data": output }),
+        Stream::Stderr => serde_json::json!({ "type": "error", "error": output }),
+    }
+    .to_string()
+}
+
+fn append_log(app_handle: &AppHandle, operation: &ResumableOperation, stream: Stream, line: &str) {
+    if let Err(err) = action_logs::append(app_handle, &operation.id, &stream_event(stream, line)) {
+        warn!("Failed to write log of operation {}: {}", operation.id, err);
+    }
+}
+
+fn send(app_handle: &AppHandle, msg: UiMessage) {
+    let app_state = app_handle.state::<AppState>();
+    tauri::async_runtime::block_on(async move {
+        if let Err(err) = app_state.ui_messages.send(msg).await {
+            error!("Failed to broadcast operation message: {}", err);
+        }
+    });
+}
+
+/// Runs the CLI in the background, its output continues the log of the operation. The operation stays on disk until
+/// the CLI exits, in case the app is restarted again.
+fn run(app_handle: &AppHandle, operation: ResumableOperation, args: Vec<String>, verb: &str) {
+    let app_handle = app_handle.clone();
+    let verb = verb.to_string();
+    INTERRUPTED.lock().unwrap().remove(&operation.id);
+    thread::spawn(move || {
+        info!(
+            "{} operation {} of {}",
+            verb, operation.id, operation.workspace_id
+        );
+        let result = SupervisedCommand::sidecar(&app_handle, DEVPOD_BINARY_NAME)
+            .args(&args)
+            .workspace(Some(&operation.workspace_id))
+            .operation(Some(&operation.id))
+            .run(|stream, line| append_log(&app_handle, &operation, stream, line));
+        let succeeded = match result {
+            Ok(output) => output.status.success(),
+            Err(err) => {
+                error!("Failed to run operation {}: {}", operation.id, err);
+                false
+            }
+        };
+        if let Err(err) = take(&app_handle, &operation.id) {
+            warn!("Failed to remove operation {}: {}", operation.id, err);
+        }
+        let msg = if succeeded {
+            ShowToastMsg::new(
+                format!("{} {} succeeded", verb, operation.workspace_id),
+                "The log continues in the workspace's action history".to_string(),
+                ToastStatus::Success,
+            )
+        } else {
+            ShowToastMsg::new(
+                format!("{} {} failed", verb, operation.workspace_id),
+                "See the workspace's action history for details".to_string(),
+                ToastStatus::Error,
+            )
+        };
+        send(&app_handle, UiMessage::ShowToast(msg));
+    });
+}
+
+/// Where the interrupted operation left the workspace, the agent log shows how far it got if the provider keeps one.
+fn inspect(app_handle: &AppHandle, operation: &ResumableOperation) -> Option<String> {
+    match WorkspaceLogsCommand::new(operation.workspace_id.clone()).exec(app_handle) {
+        Ok(logs) => {
+            append_log(
+                app_handle,
+                operation,
+                Stream::Stdout,
+                "Interrupted by an app restart, agent log:",
+            );
+            for line in logs.lines() {
+                append_log(app_handle, operation, Stream::Stdout, line);
+            }
+        }
+        Err(err) => info!("No agent log for {}: {}", operation.workspace_id, err),
+    }
+
+    WorkspaceStatusCommand::new(operation.workspace_id.clone())
+        .exec(app_handle)
+        .ok()
+}
+
+/// Offers to resume or roll back the operations that were still running when the app last exited.
+pub fn setup(app_handle: &AppHandle) {
+    let interrupted = load(app_handle);
+    if interrupted.is_empty() {
+        return;
+    }
+    INTERRUPTED
+        .lock()
+        .unwrap()
+        .extend(interrupted.keys().cloned());
+
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        for operation in interrupted.into_values() {
+            warn!(
+                target: module_path!(),
+                operation_id = operation.id.as_str();
+                "Operation on {} interrupted", operation.workspace_id
+            );
+            let workspace_state = inspect(&app_handle, &operation);
+            send(
+                &app_handle,
+                UiMessage::OperationInterrupted(OperationInterruptedMsg::new(
+                    operation.id.clone(),
+                    operation.kind,
+                    operation.workspace_id.clone(),
+                    workspace_state,
+                    operation.rollback_args.is_some(),
+                )),
+            );
+        }
+    });
+}
+
+/// Keeps the operation on disk while it runs.
+#[tauri::command]
+pub fn begin_resumable_operation(
+    app_handle: AppHandle,
+    operation: ResumableOperation,
+) -> Result<(), ResumableOperationError> {
+    // Resuming isn't guarded by the app lock, unlike rolling back
+    if cli_runner::is_destructive(&operation.resume_args) {
+        return Err(ResumableOperationError::Destructive(operation.id));
+    }
+
+    let mut operations = load(&app_handle);
+    operations.insert(operation.id.clone(), operation);
+
+    save(&app_handle, &operations)
+}
+
+/// Forgets the operation once it has finished, or when the user doesn't want to pick it up again.
+#[tauri::command]
+pub fn end_resumable_operation(
+    app_handle: AppHandle,
+    operation_id: String,
+) -> Result<(), ResumableOperationError> {
+    match take(&app_handle, &operation_id) {
+        Err(ResumableOperationError::NotFound(_)) => Ok(()),
+        result => result.map(|_| ()),
+    }
+}
+
+#[tauri::command]
+pub fn resume_operation(
+    app_handle: AppHandle,
+    operation_id: String,
+) -> Result<(), ResumableOperationError> {
+    let operation = find(&app_handle, &operation_id)?;
+    let args = operation.resume_args.clone();
+    run(&app_handle, operation, args, "Resuming");
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn roll_back_operation(
+    app_handle: AppHandle,
+    operation_id: String,
+) -> Result<(), ResumableOperationError> {
+    let operation = find(&app_handle, &operation_id)?;
+    let Some(args) = operation.rollback_args.clone() else {
+        return Err(ResumableOperationError::NoRollback(operation_id));
+    };
+    run(&app_handle, operation, args, "Rolling back");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_log_output_as_stream_events() {
+        let event: serde_json::Value = serde_json::from_str(&stream_event(
+            Stream::Stdout,
+            r#"{"time":"2024-01-01T00:00:00Z","level":"info","message":"Creating devcontainer..."}"#,
+        ))
+        .unwrap();
+        assert_eq!(event["type"], "data");
+        assert_eq!(event["data"]["message"], "Creating devcontainer...");
+
+        let event: serde_json::Value =
+            serde_json::from_str(&stream_event(Stream::Stderr, "exit status 1")).unwrap();
+        assert_eq!(event["type"], "error");
+        assert_eq!(event["error"]["message"], "exit status 1");
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{
+    cli_manager::{CliManager, CliUpdateError},
+    AppHandle,
+};
+use anyhow::Context;
+use log::{error, info, warn};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use thiserror::Error;
+
+const ROLLBACK_DIR: &str = "rollback";
+
+#[derive(Error, Debug)]
+pub enum RollbackError {
+    #[error("rolling back the app is not supported on this platform")]
+    Unsupported,
+    #[error("no previous app version to roll back to")]
+    NoPreviousVersion,
+    #[error("failed to restore previous app version {0}")]
+    Restore(#[source] anyhow::Error),
+    #[error(transparent)]
+    Cli(#[from] CliUpdateError),
+    #[error("unable to roll back")]
+    Join(#[source] tauri::Error),
+}
+impl serde::Serialize for RollbackError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RollbackTarget {
+    App,
+    Cli,
+}
+
+#[tauri::command]
+pub async fn rollback_update(
+    app_handle: AppHandle,
+    target: RollbackTarget,
+) -> Result<(), RollbackError> {
+    // Copying the app and checking the version of the restored cli block
+    let result = tauri::async_runtime::spawn_blocking(move || match target {
+        RollbackTarget::App => rollback_app(&app_handle),
+        RollbackTarget::Cli => CliManager::new(&app_handle)
+            .rollback()
+            .map_err(RollbackError::from),
+    })
+    .await
+    .map_err(RollbackError::Join)
+    .and_then(|result| result);
+    if let Err(err) = &result {
+        error!("Failed to roll back {:?}: {}", target, err);
+    }
+
+    result
+}
+
+/// Rolls back whichever of app and cli has been updated most recently.
+pub fn rollback_latest(app_handle: &AppHandle) -> Result<(), RollbackError> {
+    let app_backup = app_backup_path(app_handle).and_then(|path| modified(&path));
+    let cli_manager = CliManager::new(app_handle);
+    let cli_backup = cli_manager
+        .cli_path()
+        .ok()
+        .and_then(|path| modified(&previous_cli_path(&path)));
+
+    match (app_backup, cli_backup) {
+        (Some(app), Some(cli)) if app > cli => rollback_app(app_handle),
+        (Some(_), None) => rollback_app(app_handle),
+        (_, Some(_)) => cli_manager.rollback().map_err(RollbackError::from),
+        (None, None) => Err(RollbackError::NoPreviousVersion),
+    }
+}
+
+/// Keeps a copy of the currently installed app around before the updater replaces it.
+pub fn backup_app(app_handle: &AppHandle) -> anyhow::Result<()> {
+    let installed_path = match installed_app_path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    let backup_path = app_backup_path(app_handle).context("no app data dir")?;
+
+    if backup_path.exists() {
+        remove(&backup_path)?;
+    }
+    if let Some(parent) = backup_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    copy_app(&installed_path, &backup_path)?;
+    info!("Backed up {:?} to {:?}", installed_path, backup_path);
+
+    Ok(())
+}
+
+fn rollback_app(app_handle: &AppHandle) -> Result<(), RollbackError> {
+    let installed_path = installed_app_path().ok_or(RollbackError::Unsupported)?;
+    let backup_path = app_backup_path(app_handle)
+        .filter(|path| path.exists())
+        .ok_or(RollbackError::NoPreviousVersion)?;
+
+    replace_app(&backup_path, &installed_path).map_err(RollbackError::Restore)?;
+    info!("Restored {:?} from {:?}", installed_path, backup_path);
+
+    app_handle.restart();
+
+    Ok(())
+}
+
+fn app_backup_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    let file_name = installed_app_path()?.file_name()?.to_owned();
+    let mut path = app_handle.path_resolver().app_data_dir()?;
+    path.push(ROLLBACK_DIR);
+    path.push(file_name);
+
+    Some(path)
+}
+
+// Matches the location `CliManager` keeps the replaced cli at
+fn previous_cli_path(cli_path: &Path) -> PathBuf {
+    sibling_path(cli_path, "old")
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::symlink_metadata(path).ok()?.modified().ok()
+}
+
+/// The artifact the updater replaces, the `.app` bundle on macOS and the AppImage on Linux.
+#[cfg(target_os = "macos")]
+fn installed_app_path() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()?
+        .ancestors()
+        .find(|path| path.extension().map_or(false, |ext| ext == "app"))
+        .map(Path::to_path_buf)
+}
+
+#[cfg(target_os = "linux")]
+fn installed_app_path() -> Option<PathBuf> {
+    std::env::var_os("APPIMAGE").map(PathBuf::from)
+}
+
+// Windows updates run through the installer, so there is nothing we can keep around
+#[cfg(target_os = "windows")]
+fn installed_app_path() -> Option<PathBuf> {
+    None
+}
+
+// app bundles contain symlinks and extended attributes that need to survive the copy
+#[cfg(target_os = "macos")]
+fn copy_app(from: &Path, to: &Path) -> anyhow::Result<()> {
+    let status = std::process::Command::new("ditto")
+        .arg(from)
+        .arg(to)
+        .status()
+        .context("run ditto")?;
+    anyhow::ensure!(status.success(), "ditto exited with {}", status);
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn copy_app(from: &Path, to: &Path) -> anyhow::Result<()> {
+    fs::copy(from, to).context("copy app")?;
+
+    Ok(())
+}
+
+/// Copies `from` over the installed app at `to`. The copy is staged next to `to`, on the same volume, and only swapped in
+/// once it's complete. Until then the installed app is kept aside.
+pub(crate) fn replace_app(from: &Path, to: &Path) -> anyhow::Result<()> {
+    let staged_path = sibling_path(to, "new");
+    let previous_path = sibling_path(to, "old");
+    for path in [&staged_path, &previous_path] {
+        if fs::symlink_metadata(path).is_ok() {
+            remove(path)?;
+        }
+    }
+    copy_app(from, &staged_path)?;
+
+    fs::rename(to, &previous_path).with_context(|| format!("move {:?} aside", to))?;
+    if let Err(err) = fs::rename(&staged_path, to) {
+        // Put the installed app back rather than leaving none at all
+        let _ = fs::rename(&previous_path, to);
+        let _ = remove(&staged_path);
+        return Err(err).with_context(|| format!("move {:?} into place", staged_path));
+    }
+    if let Err(err) = remove(&previous_path) {
+        warn!("Failed to remove previous app {:?}: {}", previous_path, err);
+    }
+
+    Ok(())
+}
+
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let mut sibling = path.as_os_str().to_owned();
+    sibling.push(".");
+    sibling.push(extension);
+
+    PathBuf::from(sibling)
+}
+
+fn remove(path: &Path) -> anyhow::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path).with_context(|| format!("remove {:?}", path))
+    } else {
+        fs::remove_file(path).with_context(|| format!("remove {:?}", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn should_replace_app_without_leftovers() {
+        let dir = std::env::temp_dir().join(format!("devpod-replace-app-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let backup = dir.join("backup.AppImage");
+        let installed = dir.join("DevPod.AppImage");
+        fs::write(&backup, "previous").unwrap();
+        fs::write(&installed, "current").unwrap();
+
+        replace_app(&backup, &installed).unwrap();
+        assert_eq!(fs::read_to_string(&installed).unwrap(), "previous");
+        assert!(backup.exists());
+        assert!(!sibling_path(&installed, "new").exists());
+        assert!(!sibling_path(&installed, "old").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
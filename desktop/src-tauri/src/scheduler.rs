@@ -0,0 +1,610 @@
+use crate::{debug_console, power, AppHandle};
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeZone, Timelike, Utc};
+use lazy_static::lazy_static;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::RandomState, BTreeMap},
+    fmt, fs,
+    hash::{BuildHasher, Hasher},
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+lazy_static! {
+    static ref SCHEDULER: Mutex<Scheduler> = Mutex::new(Scheduler::default());
+}
+const SCHEDULES_FILE_NAME: &str = "scheduled_tasks.json";
+const TICK_INTERVAL: Duration = Duration::from_secs(5);
+// Tasks deferred while on battery check again this often whether the machine has been plugged in
+const BATTERY_RECHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+// Cron expressions that can't match within this many days, e.g. `0 0 31 2 *`, never run
+const MAX_CRON_SEARCH_DAYS: i64 = 5 * 366;
+
+#[derive(Error, Debug)]
+pub enum SchedulerError {
+    #[error("invalid schedule {0:?}: {1}")]
+    InvalidSchedule(String, &'static str),
+    #[error("no task named {0}")]
+    UnknownTask(String),
+    #[error("unable to save schedules")]
+    Persist(#[source] anyhow::Error),
+}
+impl serde::Serialize for SchedulerError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// When a task runs: `@every 1h30m`, a cron expression `minute hour day-of-month month day-of-week` in local time,
+/// one of its shorthands `@hourly`, `@daily` and `@weekly`, or `@never`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    Every(Duration),
+    Cron(Cron),
+    Never,
+}
+
+impl Schedule {
+    pub fn every(interval: Duration) -> Self {
+        Self::Every(interval)
+    }
+
+    /// The first run after `now`. Intervals count from the last run, runs missed while the app wasn't running
+    /// happen right away.
+    fn next_run(
+        &self,
+        last_run: Option<DateTime<Local>>,
+        now: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        match self {
+            Schedule::Every(interval) => Some(
+                last_run
+                    .map_or(now, |last_run| later(last_run, *interval))
+                    .max(now),
+            ),
+            Schedule::Cron(cron) => cron.next_after(now),
+            Schedule::Never => None,
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = SchedulerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = |reason| SchedulerError::InvalidSchedule(s.to_string(), reason);
+
+        match s {
+            "@never" => Ok(Schedule::Never),
+            "@hourly" | "@daily" | "@weekly" => Ok(Schedule::Cron(Cron::parse(s)?)),
+            _ => match s.strip_prefix("@every ") {
+                Some(interval) => parse_duration(interval.trim())
+                    .map(Schedule::Every)
+                    .ok_or_else(|| invalid("expected an interval like 30m or 1h30m")),
+                None => Cron::parse(s).map(Schedule::Cron),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Every(interval) => write!(f, "@every {}", format_duration(*interval)),
+            Schedule::Cron(cron) => f.write_str(&cron.expression),
+            Schedule::Never => f.write_str("@never"),
+        }
+    }
+}
+
+/// `1h30m`, `90s` or `2d`.
+fn parse_duration(s: &str) -> Option<Duration> {
+    let mut seconds = 0u64;
+    let mut number = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return None,
+        };
+        seconds = seconds.checked_add(number.parse::<u64>().ok()?.checked_mul(unit)?)?;
+        number.clear();
+    }
+    if !number.is_empty() || seconds == 0 {
+        return None;
+    }
+
+    Some(Duration::from_secs(seconds))
+}
+
+fn format_duration(duration: Duration) -> String {
+    let mut seconds = duration.as_secs();
+    let mut formatted = String::new();
+    for (unit, length) in [("d", 24 * 60 * 60), ("h", 60 * 60), ("m", 60), ("s", 1)] {
+        if seconds >= length {
+            formatted.push_str(&format!("{}{}", seconds / length, unit));
+            seconds %= length;
+        }
+    }
+
+    formatted
+}
+
+fn later(time: DateTime<Local>, duration: Duration) -> DateTime<Local> {
+    time + chrono::Duration::from_std(duration).unwrap_or_else(|_| chrono::Duration::days(365))
+}
+
+/// The values a cron field matches, as bits.
+fn parse_field(field: &str, min: u32, max: u32) -> Option<u64> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|step| *step > 0)?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+                // `5/15` starts at 5 and continues to the end of the range
+                None if step > 1 => (range.parse().ok()?, max),
+                None => (range.parse().ok()?, range.parse().ok()?),
+            },
+        };
+        if start < min || end > max || start > end {
+            return None;
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+
+    Some(bits)
+}
+
+fn matches(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    // Like cron, a day matches either field if both are restricted
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl Cron {
+    fn parse(expression: &str) -> Result<Self, SchedulerError> {
+        let invalid = |reason| SchedulerError::InvalidSchedule(expression.to_string(), reason);
+        let fields = match expression {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            _ => expression,
+        }
+        .split_whitespace()
+        .collect::<Vec<_>>();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(invalid(
+                "expected 5 fields: minute hour day-of-month month day-of-week",
+            ));
+        };
+
+        let mut weekday_bits =
+            parse_field(weekdays, 0, 7).ok_or_else(|| invalid("bad day-of-week"))?;
+        // Both 0 and 7 are Sunday
+        if matches(weekday_bits, 7) {
+            weekday_bits |= 1;
+        }
+
+        Ok(Self {
+            expression: expression.to_string(),
+            minutes: parse_field(minutes, 0, 59).ok_or_else(|| invalid("bad minute"))?,
+            hours: parse_field(hours, 0, 23).ok_or_else(|| invalid("bad hour"))?,
+            days: parse_field(days, 1, 31).ok_or_else(|| invalid("bad day-of-month"))?,
+            months: parse_field(months, 1, 12).ok_or_else(|| invalid("bad month"))?,
+            weekdays: weekday_bits,
+            days_restricted: days != "*",
+            weekdays_restricted: weekdays != "*",
+        })
+    }
+
+    fn matches_day(&self, time: &NaiveDateTime) -> bool {
+        let day = matches(self.days, time.day());
+        let weekday = matches(self.weekdays, time.weekday().num_days_from_sunday());
+
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    /// The first matching minute after `time`, skipping days, then hours, that can't match.
+    fn next_naive(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+        let limit = time + chrono::Duration::days(MAX_CRON_SEARCH_DAYS);
+        let mut time = time.with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        while time < limit {
+            if !matches(self.months, time.month()) || !self.matches_day(&time) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if !matches(self.hours, time.hour()) {
+                time = time.date().and_hms_opt(time.hour(), 0, 0)? + chrono::Duration::hours(1);
+            } else if !matches(self.minutes, time.minute()) {
+                time += chrono::Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+
+        None
+    }
+
+    fn next_after(&self, time: DateTime<Local>) -> Option<DateTime<Local>> {
+        let mut naive = time.naive_local();
+        loop {
+            naive = self.next_naive(naive)?;
+            // Times skipped by a switch to daylight saving time don't exist
+            if let Some(time) = Local.from_local_datetime(&naive).earliest() {
+                return Some(time);
+            }
+        }
+    }
+}
+
+/// A periodic background job, e.g. closing idle sessions or checking for updates.
+pub struct Task {
+    name: &'static str,
+    schedule: Schedule,
+    jitter: Duration,
+    defer_on_battery: bool,
+    run_at_startup: bool,
+    run: Arc<dyn Fn(&AppHandle) + Send + Sync>,
+}
+
+impl Task {
+    pub fn new(
+        name: &'static str,
+        schedule: Schedule,
+        run: impl Fn(&AppHandle) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name,
+            schedule,
+            jitter: Duration::ZERO,
+            defer_on_battery: false,
+            run_at_startup: false,
+            run: Arc::new(run),
+        }
+    }
+
+    /// Delays every run by a random amount up to `jitter`, so that tasks on the same schedule don't all start at once.
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Holds the task back while the machine runs on battery.
+    pub fn defer_on_battery(mut self) -> Self {
+        self.defer_on_battery = true;
+        self
+    }
+
+    /// Runs the task once right away, for tasks that keep their results in memory.
+    pub fn run_at_startup(mut self) -> Self {
+        self.run_at_startup = true;
+        self
+    }
+
+    pub fn register(self) {
+        SCHEDULER.lock().unwrap().register(self, Local::now());
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedTask {
+    /// Replaces the default schedule of the task
+    schedule: Option<String>,
+    last_run: Option<DateTime<Utc>>,
+}
+
+struct Entry {
+    task: Task,
+    schedule: Schedule,
+    last_run: Option<DateTime<Local>>,
+    next_run: Option<DateTime<Local>>,
+    running: bool,
+}
+
+impl Entry {
+    fn reschedule(&mut self, now: DateTime<Local>) {
+        self.next_run = self
+            .schedule
+            .next_run(self.last_run, now)
+            .map(|next_run| later(next_run, random_jitter(self.task.jitter)));
+    }
+
+    fn status(&self) -> ScheduledTask {
+        ScheduledTask {
+            name: self.task.name.to_string(),
+            schedule: self.schedule.to_string(),
+            default_schedule: self.task.schedule.to_string(),
+            defer_on_battery: self.task.defer_on_battery,
+            last_run: self.last_run.map(DateTime::from),
+            next_run: self.next_run.map(DateTime::from),
+            running: self.running,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Scheduler {
+    tasks: BTreeMap<&'static str, Entry>,
+    // Includes tasks that aren't registered in this build
+    persisted: BTreeMap<String, PersistedTask>,
+}
+
+impl Scheduler {
+    fn register(&mut self, task: Task, now: DateTime<Local>) {
+        let persisted = self.persisted.get(task.name).cloned().unwrap_or_default();
+        let schedule = persisted
+            .schedule
+            .and_then(|schedule| match schedule.parse() {
+                Ok(schedule) => Some(schedule),
+                Err(err) => {
+                    warn!("Ignoring schedule of {}: {}", task.name, err);
+                    None
+                }
+            })
+            .unwrap_or_else(|| task.schedule.clone());
+        let mut entry = Entry {
+            schedule,
+            last_run: persisted.last_run.map(DateTime::from),
+            next_run: None,
+            running: false,
+            task,
+        };
+        entry.reschedule(now);
+        if entry.task.run_at_startup && entry.schedule != Schedule::Never {
+            entry.next_run = Some(now);
+        }
+
+        self.tasks.insert(entry.task.name, entry);
+    }
+
+    /// Tasks whose time has come and that aren't still running from last time.
+    fn due(&self, now: DateTime<Local>) -> Vec<&'static str> {
+        self.tasks
+            .values()
+            .filter(|entry| {
+                !entry.running && entry.next_run.is_some_and(|next_run| next_run <= now)
+            })
+            .map(|entry| entry.task.name)
+            .collect()
+    }
+
+    fn finished(&mut self, name: &str, now: DateTime<Local>) {
+        let Some(entry) = self.tasks.get_mut(name) else {
+            return;
+        };
+        entry.running = false;
+        entry.last_run = Some(now);
+        entry.reschedule(now);
+        self.persisted.entry(name.to_string()).or_default().last_run = Some(now.into());
+    }
+}
+
+fn random_jitter(max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(Utc::now().timestamp_nanos() as u128);
+
+    Duration::from_millis(hasher.finish() % max.as_millis().max(1) as u64)
+}
+
+fn schedules_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(SCHEDULES_FILE_NAME))
+}
+
+fn load(app_handle: &AppHandle) -> BTreeMap<String, PersistedTask> {
+    schedules_path(app_handle)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(
+    app_handle: &AppHandle,
+    tasks: &BTreeMap<String, PersistedTask>,
+) -> Result<(), SchedulerError> {
+    let path = schedules_path(app_handle)
+        .ok_or_else(|| SchedulerError::Persist(anyhow::anyhow!("unable to find app data dir")))?;
+    let content =
+        serde_json::to_vec_pretty(tasks).map_err(|err| SchedulerError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| SchedulerError::Persist(err.into()))
+}
+
+fn run(app_handle: &AppHandle, name: &'static str, job: Arc<dyn Fn(&AppHandle) + Send + Sync>) {
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let task = debug_console::track_task(name, None);
+        debug!("Running scheduled task {}", name);
+        job(&app_handle);
+        drop(task);
+
+        let mut scheduler = SCHEDULER.lock().unwrap();
+        scheduler.finished(name, Local::now());
+        if let Err(err) = save(&app_handle, &scheduler.persisted) {
+            warn!("Failed to save last run of {}: {}", name, err);
+        }
+    });
+}
+
+fn tick(app_handle: &AppHandle) {
+    let now = Local::now();
+    let mut scheduler = SCHEDULER.lock().unwrap();
+    // Only looked up if a task might have to wait for it
+    let mut on_battery = None;
+    for name in scheduler.due(now) {
+        let entry = scheduler.tasks.get_mut(name).unwrap();
+        if entry.task.defer_on_battery && *on_battery.get_or_insert_with(power::on_battery) {
+            debug!("Deferring {} while on battery", name);
+            entry.next_run = Some(later(now, BATTERY_RECHECK_INTERVAL));
+            continue;
+        }
+
+        entry.running = true;
+        run(app_handle, name, entry.task.run.clone());
+    }
+}
+
+/// Starts running registered tasks, needs to be called before any are registered to pick up their saved schedules.
+pub fn setup(app_handle: &AppHandle) {
+    SCHEDULER.lock().unwrap().persisted = load(app_handle);
+
+    let app_handle = app_handle.clone();
+    thread::spawn(move || loop {
+        thread::sleep(TICK_INTERVAL);
+        tick(&app_handle);
+    });
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ScheduledTask {
+    name: String,
+    schedule: String,
+    default_schedule: String,
+    defer_on_battery: bool,
+    last_run: Option<DateTime<Utc>>,
+    /// `None` if it's disabled
+    next_run: Option<DateTime<Utc>>,
+    running: bool,
+}
+
+/// The registered tasks, soonest first.
+#[tauri::command]
+pub fn list_scheduled_tasks() -> Vec<ScheduledTask> {
+    let mut tasks = SCHEDULER
+        .lock()
+        .unwrap()
+        .tasks
+        .values()
+        .map(Entry::status)
+        .collect::<Vec<_>>();
+    tasks.sort_by_key(|task| (task.next_run.is_none(), task.next_run));
+
+    tasks
+}
+
+/// Changes when the task runs, `None` restores its default schedule.
+#[tauri::command]
+pub fn set_task_schedule(
+    app_handle: AppHandle,
+    name: String,
+    schedule: Option<String>,
+) -> Result<ScheduledTask, SchedulerError> {
+    let parsed = schedule.as_deref().map(Schedule::from_str).transpose()?;
+
+    let mut scheduler = SCHEDULER.lock().unwrap();
+    let entry = scheduler
+        .tasks
+        .get_mut(name.as_str())
+        .ok_or_else(|| SchedulerError::UnknownTask(name.clone()))?;
+    entry.schedule = parsed.unwrap_or_else(|| entry.task.schedule.clone());
+    entry.reschedule(Local::now());
+    info!("Scheduled {} {}", name, entry.schedule);
+
+    let schedule = schedule.map(|_| entry.schedule.to_string());
+    scheduler
+        .persisted
+        .entry(name.clone())
+        .or_default()
+        .schedule = schedule;
+    save(&app_handle, &scheduler.persisted)?;
+
+    Ok(scheduler.tasks[name.as_str()].status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(s: &str) -> DateTime<Local> {
+        Local
+            .from_local_datetime(&NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn should_parse_schedules() {
+        assert_eq!(
+            "@every 1h30m".parse::<Schedule>().unwrap(),
+            Schedule::Every(Duration::from_secs(90 * 60))
+        );
+        assert_eq!(
+            "@every 90m".parse::<Schedule>().unwrap().to_string(),
+            "@every 1h30m"
+        );
+        assert_eq!("@never".parse::<Schedule>().unwrap(), Schedule::Never);
+        assert!("*/15 9-17 * * 1-5".parse::<Schedule>().is_ok());
+        assert!("@every 0s".parse::<Schedule>().is_err());
+        assert!("60 * * * *".parse::<Schedule>().is_err());
+        assert!("* * * *".parse::<Schedule>().is_err());
+    }
+
+    #[test]
+    fn should_find_next_run() {
+        let cron: Schedule = "30 9 * * 1-5".parse().unwrap();
+        // A Saturday
+        let now = local("2024-01-06 12:00");
+        assert_eq!(cron.next_run(None, now), Some(local("2024-01-08 09:30")));
+
+        let every = Schedule::every(Duration::from_secs(60 * 60));
+        assert_eq!(
+            every.next_run(Some(local("2024-01-06 11:30")), now),
+            Some(local("2024-01-06 12:30"))
+        );
+        // Overdue runs happen right away
+        assert_eq!(
+            every.next_run(Some(local("2024-01-05 11:30")), now),
+            Some(now)
+        );
+        assert_eq!(
+            "0 0 31 2 *"
+                .parse::<Schedule>()
+                .unwrap()
+                .next_run(None, now),
+            None
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{
+    audit_log::{self, AuditCategory, AuditInitiator},
+    keychain::{self, KeychainError},
+    vault::{self, VaultError},
+};
+use lazy_static::lazy_static;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::RwLock};
+use thiserror::Error;
+use ts_rs::TS;
+
+lazy_static! {
+    // Keychain lookups can be slow or even prompt the user, so every secret is only read once.
+    // `None` remembers that there is no such secret.
+    static ref CACHE: RwLock<HashMap<(SecretKind, String), Option<String>>> =
+        RwLock::new(HashMap::new());
+}
+
+/// What a secret is used for, each kind is stored separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum SecretKind {
+    EnvironmentVariable,
+    /// Personal access tokens of git hosts
+    GitToken,
+    SshKeyPassphrase,
+}
+
+enum Storage {
+    /// An item of its own under this keychain service
+    Keychain(&'static str),
+    /// An entry of the vault under this prefix, only the vault's key is in the keychain
+    Vault(&'static str),
+}
+
+impl Storage {
+    fn name(&self) -> &'static str {
+        match self {
+            Storage::Keychain(name) | Storage::Vault(name) => name,
+        }
+    }
+}
+
+impl SecretKind {
+    fn storage(&self) -> Storage {
+        match self {
+            // Predates this module, kept so that existing items are still found
+            SecretKind::EnvironmentVariable => Storage::Keychain("sh.loft.devpod.env"),
+            // One per git host, the vault only needs the keychain to be unlocked once for all of them
+            SecretKind::GitToken => Storage::Vault("git"),
+            SecretKind::SshKeyPassphrase => Storage::Keychain("sh.loft.devpod.ssh-key"),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SecretError {
+    #[error(transparent)]
+    Keychain(#[from] KeychainError),
+    #[error(transparent)]
+    Vault(#[from] VaultError),
+}
+impl serde::Serialize for SecretError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn vault_entry(prefix: &str, account: &str) -> String {
+    format!("{}/{}", prefix, account)
+}
+
+fn audit(kind: SecretKind, account: &str, access: &str) {
+    info!(
+        target: module_path!(),
+        secret_service = kind.storage().name(),
+        secret_account = account,
+        access = access;
+        "Secret accessed"
+    );
+}
+
+/// Reads a secret, `None` if it doesn't exist.
+pub fn get(kind: SecretKind, account: &str) -> Result<Option<String>, SecretError> {
+    let key = (kind, account.to_string());
+    if let Some(value) = CACHE.read().unwrap().get(&key) {
+        audit(kind, account, "read cached");
+        return Ok(value.clone());
+    }
+
+    audit(kind, account, "read");
+    let value = match kind.storage() {
+        Storage::Keychain(service) => match keychain::get_password(service, account) {
+            Ok(value) => Some(value),
+            Err(KeychainError::NotFound(..)) => None,
+            Err(err) => return Err(err.into()),
+        },
+        Storage::Vault(prefix) => vault::get(&vault_entry(prefix, account))?,
+    };
+    CACHE.write().unwrap().insert(key, value.clone());
+
+    Ok(value)
+}
+
+pub fn set(kind: SecretKind, account: &str, value: &str) -> Result<(), SecretError> {
+    audit(kind, account, "write");
+    match kind.storage() {
+        Storage::Keychain(service) => keychain::set_password(service, account, value)?,
+        Storage::Vault(prefix) => vault::set(&vault_entry(prefix, account), value)?,
+    }
+    CACHE
+        .write()
+        .unwrap()
+        .insert((kind, account.to_string()), Some(value.to_string()));
+
+    Ok(())
+}
+
+pub fn delete(kind: SecretKind, account: &str) -> Result<(), SecretError> {
+    audit(kind, account, "delete");
+    match kind.storage() {
+        Storage::Keychain(service) => keychain::delete_password(service, account)?,
+        Storage::Vault(prefix) => vault::delete(&vault_entry(prefix, account))?,
+    }
+    CACHE
+        .write()
+        .unwrap()
+        .insert((kind, account.to_string()), None);
+
+    Ok(())
+}
+
+/// Stores a secret entered in the UI, values never travel back to the UI.
+#[tauri::command]
+pub fn set_secret(kind: SecretKind, account: String, value: String) -> Result<(), SecretError> {
+    set(kind, &account, &value)?;
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Credentials,
+        "set secret",
+        Some(&account),
+    );
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_secret(kind: SecretKind, account: String) -> Result<(), SecretError> {
+    delete(kind, &account)?;
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Credentials,
+        "delete secret",
+        Some(&account),
+    );
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn has_secret(kind: SecretKind, account: String) -> Result<bool, SecretError> {
+    Ok(get(kind, &account)?.is_some())
+}
@@ -0,0 +1,402 @@
+use crate::{
+    autostart,
+    commands::{stop_workspace::StopWorkspaceCommand, DevpodCommandConfig},
+    error_reporting, ports, power,
+    settings::Settings,
+    telemetry,
+    ui_messages::{ShowToastMsg, ToastStatus, UiMessage},
+    AppHandle, AppState,
+};
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::Manager;
+
+const INTERRUPTED_ACTIONS_FILE_NAME: &str = "interrupted_actions.json";
+// The OS only waits a couple of seconds before killing us, whatever doesn't finish in time is given up
+const STOP_WORKSPACES_TIMEOUT: Duration = Duration::from_secs(20);
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Some platforms report the end of a session more than once
+static ENDED: AtomicBool = AtomicBool::new(false);
+
+/// Workspace actions that were still running when the session ended, reported on the next launch.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InterruptedActions {
+    ended_at: DateTime<Utc>,
+    actions: Vec<String>,
+}
+
+fn interrupted_actions_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(INTERRUPTED_ACTIONS_FILE_NAME))
+}
+
+fn persist_active_actions(app_handle: &AppHandle) {
+    let actions: Vec<String> = {
+        let state = app_handle.state::<AppState>();
+        let active_actions = state.active_actions.lock().unwrap();
+        active_actions.iter().cloned().collect()
+    };
+    if actions.is_empty() {
+        return;
+    }
+    let path = match interrupted_actions_path(app_handle) {
+        Some(path) => path,
+        None => return,
+    };
+
+    let interrupted = InterruptedActions {
+        ended_at: Utc::now(),
+        actions,
+    };
+    let result = serde_json::to_vec(&interrupted)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| fs::write(&path, content).map_err(anyhow::Error::from));
+    match result {
+        Ok(_) => info!("Persisted {} active actions", interrupted.actions.len()),
+        Err(err) => error!("Failed to persist active actions: {}", err),
+    }
+}
+
+/// Stops all workspaces in parallel, gives up on the ones that take too long.
+fn stop_workspaces(app_handle: &AppHandle) {
+    let ids: Vec<String> = {
+        let state = app_handle.state::<AppState>();
+        let workspaces = state.workspaces.lock().unwrap();
+        workspaces
+            .recent_ids()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    };
+    info!("Stopping {} workspaces", ids.len());
+
+    let (tx, rx) = mpsc::channel();
+    for id in &ids {
+        let tx = tx.clone();
+        let id = id.clone();
+        let app_handle = app_handle.clone();
+        thread::spawn(move || {
+            let result = StopWorkspaceCommand::new(id.clone()).exec(&app_handle);
+            let _ = tx.send((id, result));
+        });
+    }
+    drop(tx);
+
+    let deadline = Instant::now() + STOP_WORKSPACES_TIMEOUT;
+    for _ in &ids {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match rx.recv_timeout(timeout) {
+            Ok((id, Err(err))) => warn!("Failed to stop workspace {}: {}", id, err),
+            Ok(_) => {}
+            Err(_) => {
+                warn!("Timed out stopping workspaces");
+                break;
+            }
+        }
+    }
+}
+
+/// Gets the app into a state that's safe to be killed in, called once the user logs out or the system shuts down.
+pub fn handle(app_handle: &AppHandle, reason: &str) {
+    if ENDED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    info!("Session ending: {}", reason);
+
+    persist_active_actions(app_handle);
+    if Settings::stop_workspaces_on_shutdown(app_handle) {
+        stop_workspaces(app_handle);
+    }
+
+    tauri::async_runtime::block_on(async {
+        let flush = async {
+            telemetry::flush(app_handle).await;
+            error_reporting::flush(app_handle).await;
+        };
+        if tokio::time::timeout(FLUSH_TIMEOUT, flush).await.is_err() {
+            warn!("Timed out flushing pending telemetry and error reports");
+        }
+    });
+
+    power::release_all();
+    ports::stop_all();
+    info!("Ready for session end");
+}
+
+/// Reports actions interrupted by the last session end and starts listening for the end of this one.
+pub fn setup(app_handle: &AppHandle) {
+    if let Some(path) = interrupted_actions_path(app_handle) {
+        report_interrupted_actions(app_handle, &path);
+    }
+
+    platform::listen(app_handle);
+}
+
+fn report_interrupted_actions(app_handle: &AppHandle, path: &Path) {
+    let interrupted: InterruptedActions = match fs::read(path)
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+    {
+        Some(interrupted) => interrupted,
+        None => return,
+    };
+    let _ = fs::remove_file(path);
+
+    for action in &interrupted.actions {
+        warn!(
+            target: module_path!(),
+            operation_id = action.as_str();
+            "Action interrupted by session end at {}", interrupted.ended_at
+        );
+    }
+
+    // Don't bring up the window just for this when started at login
+    if autostart::launched_minimized() {
+        return;
+    }
+    let msg = UiMessage::ShowToast(ShowToastMsg::new(
+        "Workspace actions interrupted".to_string(),
+        format!(
+            "{} workspace actions were still running when the system shut down, you might have to run them again",
+            interrupted.actions.len()
+        ),
+        ToastStatus::Warning,
+    ));
+    let app_state = app_handle.state::<AppState>();
+    tauri::async_runtime::block_on(async move {
+        if let Err(err) = app_state.ui_messages.send(msg).await {
+            error!("Failed to broadcast interrupted actions message: {}", err);
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use crate::AppHandle;
+    use log::{info, warn};
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Command, Stdio},
+        thread,
+    };
+
+    pub(super) fn is_prepare_for_shutdown(line: &str) -> bool {
+        line.contains("org.freedesktop.login1.Manager.PrepareForShutdown") && line.contains("true")
+    }
+
+    /// Watches logind for `PrepareForShutdown` while holding a delay lock, so that logind waits for us to clean up.
+    /// Ending the session without shutting down just kills the app, there's nothing to hook into for that.
+    pub fn listen(app_handle: &AppHandle) {
+        let child = Command::new("systemd-inhibit")
+            .args([
+                "--what=shutdown",
+                "--who=DevPod",
+                "--why=Stopping workspaces",
+                "--mode=delay",
+                "gdbus",
+                "monitor",
+                "--system",
+                "--dest",
+                "org.freedesktop.login1",
+                "--object-path",
+                "/org/freedesktop/login1",
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                warn!("Unable to watch for system shutdown: {}", err);
+                return;
+            }
+        };
+
+        let app_handle = app_handle.clone();
+        thread::spawn(move || {
+            let stdout = match child.stdout.take() {
+                Some(stdout) => stdout,
+                None => return,
+            };
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if is_prepare_for_shutdown(&line) {
+                    super::handle(&app_handle, "system shutdown");
+                    break;
+                }
+            }
+
+            // releases the delay lock
+            info!("Releasing shutdown delay lock");
+            let _ = child.kill();
+            let _ = child.wait();
+        });
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use crate::AppHandle;
+    use cocoa::{
+        base::{id, nil},
+        foundation::NSString,
+    };
+    use objc::{
+        declare::ClassDecl,
+        runtime::{Object, Sel},
+    };
+
+    extern "C" fn will_power_off(this: &Object, _cmd: Sel, _notification: id) {
+        // The observer lives as long as the app, so its handle is never freed
+        let app_handle = unsafe { &*(*this.get_ivar::<usize>("appHandle") as *const AppHandle) };
+        super::handle(app_handle, "logout or system shutdown");
+    }
+
+    /// Sent on logout, restart and shutdown before apps are asked to quit.
+    pub fn listen(app_handle: &AppHandle) {
+        let handle = app_handle.clone();
+        let _ = app_handle.run_on_main_thread(move || unsafe {
+            let mut decl = match ClassDecl::new("DevPodSessionObserver", class!(NSObject)) {
+                Some(decl) => decl,
+                None => return,
+            };
+            decl.add_method(
+                sel!(willPowerOff:),
+                will_power_off as extern "C" fn(&Object, Sel, id),
+            );
+            decl.add_ivar::<usize>("appHandle");
+            let observer: id = msg_send![decl.register(), new];
+            (*observer).set_ivar("appHandle", Box::into_raw(Box::new(handle)) as usize);
+
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let center: id = msg_send![workspace, notificationCenter];
+            let name = NSString::alloc(nil).init_str("NSWorkspaceWillPowerOffNotification");
+            let _: () = msg_send![center, addObserver: observer selector: sel!(willPowerOff:) name: name object: nil];
+        });
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use crate::AppHandle;
+    use log::warn;
+    use std::thread;
+    use windows::{
+        w,
+        Win32::{
+            Foundation::{HMODULE, HWND, LPARAM, LRESULT, WPARAM},
+            System::Shutdown::{ShutdownBlockReasonCreate, ShutdownBlockReasonDestroy},
+            UI::WindowsAndMessaging::{
+                CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, GetWindowLongPtrW,
+                RegisterClassW, SetWindowLongPtrW, TranslateMessage, GWLP_USERDATA, HMENU, MSG,
+                WINDOW_EX_STYLE, WINDOW_STYLE, WM_ENDSESSION, WM_QUERYENDSESSION, WNDCLASSW,
+            },
+        },
+    };
+
+    unsafe extern "system" fn window_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        match msg {
+            // Shows why we're holding up the shutdown while we clean up
+            WM_QUERYENDSESSION => {
+                ShutdownBlockReasonCreate(hwnd, w!("Stopping workspaces"));
+                LRESULT(1)
+            }
+            WM_ENDSESSION => {
+                // Points at the app handle owned by the window's thread
+                let app_handle = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *const AppHandle;
+                if wparam.0 != 0 && !app_handle.is_null() {
+                    super::handle(&*app_handle, "logout or system shutdown");
+                }
+                ShutdownBlockReasonDestroy(hwnd);
+                LRESULT(0)
+            }
+            _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+        }
+    }
+
+    /// Session end messages are only sent to top-level windows, so we create a hidden one on a thread of its own.
+    pub fn listen(app_handle: &AppHandle) {
+        let app_handle = app_handle.clone();
+        thread::spawn(move || unsafe {
+            let class_name = w!("DevPodSessionEnd");
+            let class = WNDCLASSW {
+                lpfnWndProc: Some(window_proc),
+                lpszClassName: class_name,
+                ..Default::default()
+            };
+            if RegisterClassW(&class) == 0 {
+                warn!("Unable to watch for session end: failed to register window class");
+                return;
+            }
+            let hwnd = CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                class_name,
+                w!("DevPod"),
+                WINDOW_STYLE::default(),
+                0,
+                0,
+                0,
+                0,
+                HWND::default(),
+                HMENU::default(),
+                HMODULE::default(),
+                None,
+            );
+            if hwnd.0 == 0 {
+                warn!("Unable to watch for session end: failed to create window");
+                return;
+            }
+            // The message loop below keeps this thread, and so the handle, alive
+            SetWindowLongPtrW(
+                hwnd,
+                GWLP_USERDATA,
+                &app_handle as *const AppHandle as isize,
+            );
+
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, HWND::default(), 0, 0).as_bool() {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn should_detect_prepare_for_shutdown() {
+        use super::platform::is_prepare_for_shutdown;
+
+        assert!(is_prepare_for_shutdown(
+            "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForShutdown (true,)"
+        ));
+        assert!(!is_prepare_for_shutdown(
+            "/org/freedesktop/login1: org.freedesktop.login1.Manager.PrepareForShutdown (false,)"
+        ));
+        assert!(!is_prepare_for_shutdown(
+            "/org/freedesktop/login1: org.freedesktop.login1.Manager.SessionNew ('3', objectpath '/org/freedesktop/login1/session/_33')"
+        ));
+    }
+}
@@ -1,10 +1,28 @@
 #![allow(dead_code)]
 
-use crate::{util::with_data_store, AppHandle};
-use serde::Serialize;
+use crate::{
+    custom_ides::CustomIde,
+    environment::EnvironmentVariable,
+    notifications::{NotificationEvent, QuietHours},
+    operation_policies::OperationPolicy,
+    resource_limits::ResourceLimits,
+    util::with_data_store,
+    AppHandle,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
 use ts_rs::TS;
 
 const SETTINGS_FILE_NAME: &str = ".settings.json";
+const DEFAULT_LOG_RETENTION_DAYS: u32 = 7;
+const DEFAULT_LOW_DISK_SPACE_WARNING_GB: u32 = 10;
+const DEFAULT_LOW_DISK_SPACE_FLOOR_GB: u32 = 2;
+const DEFAULT_WORKSPACE_HOSTNAMES_PORT: u16 = 8470;
+const DEFAULT_KEEP_ALIVE_SECONDS: u32 = 15;
+const DEFAULT_RECONNECT_MAX_DELAY_SECONDS: u32 = 300;
+const DEFAULT_TERMINAL_SCROLLBACK_KB: u32 = 512;
+const DEFAULT_BROWSER_IDE_IDLE_TIMEOUT_MINUTES: u32 = 30;
+const DEFAULT_MAX_CONCURRENT_COMMANDS: u32 = 4;
 
 #[derive(Debug, Serialize, TS)]
 #[ts(rename_all = "camelCase")]
@@ -18,6 +36,9 @@ pub struct Settings {
     zoom: Zoom,
     transparency: bool,
     auto_update: bool,
+    update_channel: ReleaseChannel,
+    update_schedule: UpdateSchedule,
+    update_while_busy: UpdateWhileBusy,
     #[serde(rename = "experimental_multiDevcontainer")]
     experimental_multi_devcontainer: bool,
     #[serde(rename = "experimental_fleet")]
@@ -26,6 +47,42 @@ pub struct Settings {
     experimental_jupyter_notebooks: bool,
     #[serde(rename = "experimental_devPodPro")]
     experimental_devpod_pro: bool,
+    environment_variables: Vec<EnvironmentVariable>,
+    log_retention_days: u32,
+    log_format: LogFormat,
+    otlp_endpoint: Option<String>,
+    error_reporting: bool,
+    error_reporting_dsn: Option<String>,
+    disabled_notifications: Vec<NotificationEvent>,
+    quiet_hours: Option<QuietHours>,
+    pinned_workspaces: Vec<String>,
+    stop_workspaces_on_shutdown: bool,
+    low_disk_space_warning_gb: u32,
+    low_disk_space_floor_gb: u32,
+    watch_clipboard: bool,
+    manage_ssh_config: bool,
+    detect_ports: bool,
+    workspace_hostnames: bool,
+    workspace_hostnames_port: u16,
+    keep_alive_seconds: u32,
+    auto_reconnect: bool,
+    reconnect_max_delay_seconds: u32,
+    custom_ides: Vec<CustomIde>,
+    terminal_scrollback_kb: u32,
+    vscode_profile: Option<String>,
+    vscode_reuse_window: bool,
+    browser_ide_idle_timeout_minutes: u32,
+    http_proxy: Option<String>,
+    no_proxy: Option<String>,
+    max_concurrent_commands: u32,
+    provider_concurrency_limits: HashMap<String, u32>,
+    operation_policies: HashMap<String, OperationPolicy>,
+    resource_limits: ResourceLimits,
+    workspace_resource_limits: HashMap<String, ResourceLimits>,
+    status_widget: bool,
+    fast_start: bool,
+    trusted_link_domains: Vec<String>,
+    workspace_tags: HashMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -46,6 +103,50 @@ enum Zoom {
     Xl,
 }
 
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS,
+)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum ReleaseChannel {
+    #[default]
+    Stable,
+    Beta,
+    Nightly,
+}
+
+/// Restricts when the updater is allowed to install updates automatically.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum UpdateSchedule {
+    #[default]
+    Anytime,
+    Weekends,
+    NotifyOnly,
+}
+
+/// What to do with an update while workspace actions are running.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum UpdateWhileBusy {
+    #[default]
+    Wait,
+    Prompt,
+    Queue,
+}
+
+/// Format of the backend log file, JSON can be ingested by log pipelines.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
 impl Settings {
     pub fn auto_update_enabled(app_handle: &AppHandle) -> bool {
         let mut is_enabled = false;
@@ -60,4 +161,256 @@ impl Settings {
 
         return is_enabled;
     }
+
+    pub fn update_channel(app_handle: &AppHandle) -> ReleaseChannel {
+        Self::get(app_handle, "updateChannel").unwrap_or_default()
+    }
+
+    pub fn update_schedule(app_handle: &AppHandle) -> UpdateSchedule {
+        Self::get(app_handle, "updateSchedule").unwrap_or_default()
+    }
+
+    pub fn update_while_busy(app_handle: &AppHandle) -> UpdateWhileBusy {
+        Self::get(app_handle, "updateWhileBusy").unwrap_or_default()
+    }
+
+    pub fn environment_variables(app_handle: &AppHandle) -> Vec<EnvironmentVariable> {
+        Self::get(app_handle, "environmentVariables").unwrap_or_default()
+    }
+
+    pub fn set_environment_variables(
+        app_handle: &AppHandle,
+        variables: &[EnvironmentVariable],
+    ) -> anyhow::Result<()> {
+        Self::set(app_handle, "environmentVariables", &variables)
+    }
+
+    pub fn log_retention_days(app_handle: &AppHandle) -> u32 {
+        Self::get(app_handle, "logRetentionDays").unwrap_or(DEFAULT_LOG_RETENTION_DAYS)
+    }
+
+    pub fn log_format(app_handle: &AppHandle) -> LogFormat {
+        Self::get(app_handle, "logFormat").unwrap_or_default()
+    }
+
+    /// Base url of an OTLP/HTTP collector, traces are only exported if this is set.
+    pub fn otlp_endpoint(app_handle: &AppHandle) -> Option<String> {
+        Self::get::<String>(app_handle, "otlpEndpoint").filter(|endpoint| !endpoint.is_empty())
+    }
+
+    /// Errors are only reported if the user explicitly opted in.
+    pub fn error_reporting_enabled(app_handle: &AppHandle) -> bool {
+        Self::get(app_handle, "errorReporting").unwrap_or(false)
+    }
+
+    /// Sentry compatible DSN errors are reported to.
+    pub fn error_reporting_dsn(app_handle: &AppHandle) -> Option<String> {
+        Self::get::<String>(app_handle, "errorReportingDsn").filter(|dsn| !dsn.is_empty())
+    }
+
+    pub fn disabled_notifications(app_handle: &AppHandle) -> Vec<NotificationEvent> {
+        Self::get(app_handle, "disabledNotifications").unwrap_or_default()
+    }
+
+    pub fn quiet_hours(app_handle: &AppHandle) -> Option<QuietHours> {
+        Self::get(app_handle, "quietHours")
+    }
+
+    pub fn pinned_workspaces(app_handle: &AppHandle) -> Vec<String> {
+        Self::get(app_handle, "pinnedWorkspaces").unwrap_or_default()
+    }
+
+    /// Whether workspaces are stopped when the user logs out or the system shuts down.
+    pub fn stop_workspaces_on_shutdown(app_handle: &AppHandle) -> bool {
+        Self::get(app_handle, "stopWorkspacesOnShutdown").unwrap_or(false)
+    }
+
+    /// Free space below which the user is warned about running out of disk space.
+    pub fn low_disk_space_warning_gb(app_handle: &AppHandle) -> u32 {
+        Self::get(app_handle, "lowDiskSpaceWarningGb").unwrap_or(DEFAULT_LOW_DISK_SPACE_WARNING_GB)
+    }
+
+    /// Free space below which no new workspaces are built.
+    pub fn low_disk_space_floor_gb(app_handle: &AppHandle) -> u32 {
+        Self::get(app_handle, "lowDiskSpaceFloorGb").unwrap_or(DEFAULT_LOW_DISK_SPACE_FLOOR_GB)
+    }
+
+    /// Whether copied git URLs are offered as new workspaces, the clipboard is never read unless the user opted in.
+    pub fn watch_clipboard(app_handle: &AppHandle) -> bool {
+        Self::get(app_handle, "watchClipboard").unwrap_or(false)
+    }
+
+    /// Whether workspaces get a host entry in the user's SSH config.
+    pub fn manage_ssh_config(app_handle: &AppHandle) -> bool {
+        Self::get(app_handle, "manageSshConfig").unwrap_or(false)
+    }
+
+    pub fn set_manage_ssh_config(app_handle: &AppHandle, enabled: bool) -> anyhow::Result<()> {
+        Self::set(app_handle, "manageSshConfig", &enabled)
+    }
+
+    /// Whether running workspaces are checked for new listening ports.
+    pub fn detect_ports(app_handle: &AppHandle) -> bool {
+        Self::get(app_handle, "detectPorts").unwrap_or(true)
+    }
+
+    /// Whether workspaces are reachable as `<name>.devpod.localhost`, nothing listens for them unless the user opted in.
+    pub fn workspace_hostnames(app_handle: &AppHandle) -> bool {
+        Self::get(app_handle, "workspaceHostnames").unwrap_or(false)
+    }
+
+    /// Local port the hostnames are served on, binding port 80 would need elevated privileges.
+    pub fn workspace_hostnames_port(app_handle: &AppHandle) -> u16 {
+        Self::get(app_handle, "workspaceHostnamesPort").unwrap_or(DEFAULT_WORKSPACE_HOSTNAMES_PORT)
+    }
+
+    /// How often tunnels check that the workspace still answers, `0` turns keep-alives off.
+    pub fn keep_alive_seconds(app_handle: &AppHandle) -> u32 {
+        Self::get(app_handle, "keepAliveSeconds").unwrap_or(DEFAULT_KEEP_ALIVE_SECONDS)
+    }
+
+    /// Whether dropped tunnels are re-established without the user asking for it.
+    pub fn auto_reconnect(app_handle: &AppHandle) -> bool {
+        Self::get(app_handle, "autoReconnect").unwrap_or(true)
+    }
+
+    /// Upper bound of the backoff between reconnect attempts.
+    pub fn reconnect_max_delay_seconds(app_handle: &AppHandle) -> u32 {
+        Self::get(app_handle, "reconnectMaxDelaySeconds")
+            .unwrap_or(DEFAULT_RECONNECT_MAX_DELAY_SECONDS)
+    }
+
+    /// IDEs the user registered with their own launch command.
+    pub fn custom_ides(app_handle: &AppHandle) -> Vec<CustomIde> {
+        Self::get(app_handle, "customIdes").unwrap_or_default()
+    }
+
+    pub fn set_custom_ides(app_handle: &AppHandle, ides: &[CustomIde]) -> anyhow::Result<()> {
+        Self::set(app_handle, "customIdes", &ides)
+    }
+
+    /// Output kept per terminal session to redraw it.
+    pub fn terminal_scrollback_kb(app_handle: &AppHandle) -> u32 {
+        Self::get(app_handle, "terminalScrollbackKb").unwrap_or(DEFAULT_TERMINAL_SCROLLBACK_KB)
+    }
+
+    /// VS Code profile workspaces are opened with, VS Code picks its default profile if not set.
+    pub fn vscode_profile(app_handle: &AppHandle) -> Option<String> {
+        Self::get::<String>(app_handle, "vscodeProfile").filter(|profile| !profile.is_empty())
+    }
+
+    /// Whether VS Code opens workspaces in the last active window instead of a new one.
+    pub fn vscode_reuse_window(app_handle: &AppHandle) -> bool {
+        Self::get(app_handle, "vscodeReuseWindow").unwrap_or(false)
+    }
+
+    /// Minutes nobody is connected to a browser IDE before it's stopped, `0` keeps them running.
+    pub fn browser_ide_idle_timeout_minutes(app_handle: &AppHandle) -> u32 {
+        Self::get(app_handle, "browserIdeIdleTimeoutMinutes")
+            .unwrap_or(DEFAULT_BROWSER_IDE_IDLE_TIMEOUT_MINUTES)
+    }
+
+    /// Proxy the CLI reaches providers and registries through.
+    pub fn http_proxy(app_handle: &AppHandle) -> Option<String> {
+        Self::get::<String>(app_handle, "httpProxy").filter(|proxy| !proxy.is_empty())
+    }
+
+    /// Comma separated hosts that bypass the proxy.
+    pub fn no_proxy(app_handle: &AppHandle) -> Option<String> {
+        Self::get::<String>(app_handle, "noProxy").filter(|hosts| !hosts.is_empty())
+    }
+
+    /// CLI commands the backend runs at the same time, `0` doesn't limit them.
+    pub fn max_concurrent_commands(app_handle: &AppHandle) -> u32 {
+        Self::get(app_handle, "maxConcurrentCommands").unwrap_or(DEFAULT_MAX_CONCURRENT_COMMANDS)
+    }
+
+    /// CLI commands per provider that run at the same time, e.g. to create one machine at a time.
+    pub fn provider_concurrency_limits(app_handle: &AppHandle) -> HashMap<String, u32> {
+        Self::get(app_handle, "providerConcurrencyLimits").unwrap_or_default()
+    }
+
+    /// Timeouts and retries by CLI command, commands without one use the defaults.
+    pub fn operation_policies(app_handle: &AppHandle) -> HashMap<String, OperationPolicy> {
+        Self::get(app_handle, "operationPolicies").unwrap_or_default()
+    }
+
+    /// Applied to all CLI subprocesses, nothing is limited by default.
+    pub fn resource_limits(app_handle: &AppHandle) -> ResourceLimits {
+        Self::get(app_handle, "resourceLimits").unwrap_or_default()
+    }
+
+    /// Limits by workspace ID, they take precedence over the global ones.
+    pub fn workspace_resource_limits(app_handle: &AppHandle) -> HashMap<String, ResourceLimits> {
+        Self::get(app_handle, "workspaceResourceLimits").unwrap_or_default()
+    }
+
+    /// Whether the status widget was open when the app last quit.
+    pub fn status_widget(app_handle: &AppHandle) -> bool {
+        Self::get(app_handle, "statusWidget").unwrap_or(false)
+    }
+
+    pub fn set_status_widget(app_handle: &AppHandle, open: bool) -> anyhow::Result<()> {
+        Self::set(app_handle, "statusWidget", &open)
+    }
+
+    /// Whether setup that the main window doesn't need waits until it rendered.
+    pub fn fast_start(app_handle: &AppHandle) -> bool {
+        Self::get(app_handle, "fastStart").unwrap_or(false)
+    }
+
+    /// Domains whose `devpod://` links run without asking, added when the user allows one for good.
+    pub fn trusted_link_domains(app_handle: &AppHandle) -> Vec<String> {
+        Self::get(app_handle, "trustedLinkDomains").unwrap_or_default()
+    }
+
+    pub fn set_trusted_link_domains(
+        app_handle: &AppHandle,
+        domains: &[String],
+    ) -> anyhow::Result<()> {
+        Self::set(app_handle, "trustedLinkDomains", &domains)
+    }
+
+    /// Tags by workspace ID, environment variables can be scoped to them.
+    pub fn workspace_tags(app_handle: &AppHandle) -> HashMap<String, Vec<String>> {
+        Self::get(app_handle, "workspaceTags").unwrap_or_default()
+    }
+
+    /// All stored settings as they have been written by the UI.
+    pub fn all(app_handle: &AppHandle) -> serde_json::Map<String, serde_json::Value> {
+        let mut settings = serde_json::Map::new();
+        let _ = with_data_store(app_handle, SETTINGS_FILE_NAME, |store| {
+            settings.extend(
+                store
+                    .entries()
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            );
+
+            Ok(())
+        });
+
+        settings
+    }
+
+    /// Overwrites a setting, only meant for migrations. The UI doesn't pick up the change until it's reloaded.
+    fn set<T: Serialize>(app_handle: &AppHandle, key: &str, value: &T) -> anyhow::Result<()> {
+        let value = serde_json::to_value(value)?;
+        with_data_store(app_handle, SETTINGS_FILE_NAME, |store| {
+            store.insert(key.to_string(), value)?;
+            store.save()
+        })
+    }
+
+    fn get<T: DeserializeOwned>(app_handle: &AppHandle, key: &str) -> Option<T> {
+        let mut value = None;
+        let _ = with_data_store(app_handle, SETTINGS_FILE_NAME, |store| {
+            value = store
+                .get(key)
+                .and_then(|v| serde_json::from_value(v.clone()).ok());
+
+            Ok(())
+        });
+
+        value
+    }
 }
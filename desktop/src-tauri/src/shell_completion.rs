@@ -0,0 +1,320 @@
+use crate::{
+    commands::{completion::CompletionCommand, DevpodCommandConfig, DevpodCommandError},
+    AppHandle,
+};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+// Marks the lines we add to shell profiles so they're only added once
+const PROFILE_MARKER: &str = "# DevPod shell completion";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ShellCompletion {
+    shell: Shell,
+    /// The shell is installed on this machine
+    detected: bool,
+    installed: bool,
+    path: String,
+}
+
+#[derive(Error, Debug)]
+pub enum ShellCompletionError {
+    #[error("Unable to find home directory")]
+    NoHomeDir,
+    #[error("Unable to generate {0:?} completions")]
+    Generate(Shell, #[source] DevpodCommandError),
+    #[error("Unable to write completions to {0}")]
+    Write(String, #[source] std::io::Error),
+}
+impl serde::Serialize for ShellCompletionError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+impl Shell {
+    #[cfg(not(target_os = "windows"))]
+    const SUPPORTED: [Shell; 4] = [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::Powershell];
+    #[cfg(target_os = "windows")]
+    const SUPPORTED: [Shell; 1] = [Shell::Powershell];
+
+    /// Argument of `devpod completion`.
+    fn cli_name(&self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            Shell::Powershell => "powershell",
+        }
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        match self {
+            Shell::Bash => &["bash"],
+            Shell::Zsh => &["zsh"],
+            Shell::Fish => &["fish"],
+            Shell::Powershell => &["pwsh", "powershell"],
+        }
+    }
+
+    fn is_detected(&self) -> bool {
+        let path = env::var_os("PATH").unwrap_or_default();
+
+        self.binaries()
+            .iter()
+            .any(|binary| find_in_path(binary, &path).is_some())
+    }
+
+    /// Where the completion script goes, and the profile that has to load it if the shell doesn't on its own.
+    fn locations(&self, home: &Path) -> (PathBuf, Option<PathBuf>) {
+        let xdg_dir = |var: &str, default: &str| {
+            env::var_os(var)
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .unwrap_or_else(|| home.join(default))
+        };
+
+        match self {
+            // picked up by bash-completion on demand
+            Shell::Bash => (
+                xdg_dir("XDG_DATA_HOME", ".local/share")
+                    .join("bash-completion")
+                    .join("completions")
+                    .join("devpod"),
+                None,
+            ),
+            Shell::Zsh => (
+                home.join(".zsh").join("completions").join("_devpod"),
+                Some(home.join(".zshrc")),
+            ),
+            // fish loads everything in its completions directory
+            Shell::Fish => (
+                xdg_dir("XDG_CONFIG_HOME", ".config")
+                    .join("fish")
+                    .join("completions")
+                    .join("devpod.fish"),
+                None,
+            ),
+            Shell::Powershell => {
+                #[cfg(target_os = "windows")]
+                let dir = dirs::document_dir()
+                    .unwrap_or_else(|| home.join("Documents"))
+                    .join("PowerShell");
+                #[cfg(not(target_os = "windows"))]
+                let dir = xdg_dir("XDG_CONFIG_HOME", ".config").join("powershell");
+
+                (
+                    dir.join("devpod-completion.ps1"),
+                    Some(dir.join("Microsoft.PowerShell_profile.ps1")),
+                )
+            }
+        }
+    }
+
+    fn profile_lines(&self, script: &Path) -> Vec<String> {
+        match self {
+            Shell::Zsh => vec![
+                format!(
+                    "fpath=(\"{}\" $fpath)",
+                    script.parent().unwrap_or(script).display()
+                ),
+                "autoload -Uz compinit && compinit".to_string(),
+            ],
+            _ => vec![format!(". \"{}\"", script.display())],
+        }
+    }
+}
+
+fn find_in_path(binary: &str, path: &OsStr) -> Option<PathBuf> {
+    env::split_paths(path).find_map(|dir| {
+        let candidate = dir.join(binary);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let candidate = candidate.with_extension("exe");
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+
+        None
+    })
+}
+
+/// Appends `lines` to the profile unless they've been added before.
+fn add_to_profile(profile: &Path, lines: &[String]) -> std::io::Result<()> {
+    let content = match fs::read_to_string(profile) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    if content.contains(PROFILE_MARKER) {
+        return Ok(());
+    }
+
+    let mut content = content;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("\n{}\n{}\n", PROFILE_MARKER, lines.join("\n")));
+    if let Some(dir) = profile.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(profile, content)
+}
+
+fn install(
+    app_handle: &AppHandle,
+    shell: Shell,
+    home: &Path,
+) -> Result<ShellCompletion, ShellCompletionError> {
+    let (script_path, profile) = shell.locations(home);
+    let write_err = |path: &Path| {
+        let path = path.to_string_lossy().to_string();
+        move |err| ShellCompletionError::Write(path, err)
+    };
+
+    let script = CompletionCommand::new(shell.cli_name())
+        .exec(app_handle)
+        .map_err(|err| ShellCompletionError::Generate(shell, err))?;
+    if let Some(dir) = script_path.parent() {
+        fs::create_dir_all(dir).map_err(write_err(dir))?;
+    }
+    fs::write(&script_path, script).map_err(write_err(&script_path))?;
+    if let Some(profile) = profile {
+        add_to_profile(&profile, &shell.profile_lines(&script_path))
+            .map_err(write_err(&profile))?;
+    }
+    info!(
+        "Installed {:?} completions to {}",
+        shell,
+        script_path.display()
+    );
+
+    Ok(ShellCompletion {
+        shell,
+        detected: true,
+        installed: true,
+        path: script_path.to_string_lossy().to_string(),
+    })
+}
+
+fn status(shell: Shell, home: &Path) -> ShellCompletion {
+    let (script_path, _) = shell.locations(home);
+
+    ShellCompletion {
+        shell,
+        detected: shell.is_detected(),
+        installed: script_path.exists(),
+        path: script_path.to_string_lossy().to_string(),
+    }
+}
+
+#[tauri::command]
+pub fn get_shell_completions() -> Result<Vec<ShellCompletion>, ShellCompletionError> {
+    let home = dirs::home_dir().ok_or(ShellCompletionError::NoHomeDir)?;
+
+    Ok(Shell::SUPPORTED
+        .iter()
+        .map(|shell| status(*shell, &home))
+        .collect())
+}
+
+/// Installs completions for the given shells, or for every shell found on this machine if there are none.
+#[tauri::command]
+pub fn install_shell_completions(
+    app_handle: AppHandle,
+    shells: Vec<Shell>,
+) -> Result<Vec<ShellCompletion>, ShellCompletionError> {
+    let home = dirs::home_dir().ok_or(ShellCompletionError::NoHomeDir)?;
+    let shells: Vec<Shell> = if shells.is_empty() {
+        Shell::SUPPORTED
+            .into_iter()
+            .filter(|shell| shell.is_detected())
+            .collect()
+    } else {
+        shells
+    };
+
+    shells
+        .into_iter()
+        .map(|shell| {
+            install(&app_handle, shell, &home).map_err(|err| {
+                error!("{}", err);
+                err
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_binaries_in_path() {
+        let dir = env::temp_dir().join(format!(
+            "devpod-shell-completion-{:x}",
+            crate::util::random_id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("fish"), "").unwrap();
+
+        let path = env::join_paths([Path::new("/does/not/exist"), &dir]).unwrap();
+        assert_eq!(find_in_path("fish", &path), Some(dir.join("fish")));
+        assert_eq!(find_in_path("zsh", &path), None);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn should_add_to_profile_once() {
+        let dir = env::temp_dir().join(format!(
+            "devpod-shell-completion-{:x}",
+            crate::util::random_id()
+        ));
+        let profile = dir.join(".zshrc");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&profile, "export EDITOR=vim").unwrap();
+
+        let lines = Shell::Zsh.profile_lines(Path::new("/home/user/.zsh/completions/_devpod"));
+        add_to_profile(&profile, &lines).unwrap();
+        add_to_profile(&profile, &lines).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&profile).unwrap(),
+            format!(
+                "export EDITOR=vim\n\n{}\nfpath=(\"/home/user/.zsh/completions\" $fpath)\nautoload -Uz compinit && compinit\n",
+                PROFILE_MARKER
+            )
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
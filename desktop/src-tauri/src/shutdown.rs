@@ -0,0 +1,187 @@
+use crate::{
+    error_reporting, ports, power,
+    process::{self, RunningProcess},
+    providers, telemetry, terminal,
+    window::WindowHelper,
+    window_state, AppHandle, AppState,
+};
+use log::{error, info, warn};
+use serde::Serialize;
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::Manager;
+use ts_rs::TS;
+
+pub const SHUTDOWN_WINDOW_LABEL: &str = "shutdown";
+// WARN: needs to match the event name in `shutdown-window/src/main.tsx`
+const SHUTDOWN_PROGRESS_EVENT: &str = "shutdown_progress";
+// Waiting for running actions to finish on their own before they're cancelled
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(15);
+const TERMINATE_GRACE: Duration = Duration::from_secs(3);
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(3);
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+// Set from the progress window to give up on waiting
+static SKIP_DRAIN: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum ShutdownPhase {
+    /// Waiting for running work to finish
+    Draining,
+    Terminating,
+    Flushing,
+}
+
+/// What's holding up quitting, shown in the shutdown window.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ShutdownProgress {
+    phase: ShutdownPhase,
+    active_actions: Vec<String>,
+    processes: Vec<RunningProcess>,
+    /// Until running work gets cancelled
+    #[ts(type = "number")]
+    remaining_ms: u64,
+}
+
+fn active_actions(app_handle: &AppHandle) -> Vec<String> {
+    let state = app_handle.state::<AppState>();
+    let mut actions: Vec<String> = state
+        .active_actions
+        .lock()
+        .unwrap()
+        .iter()
+        .cloned()
+        .collect();
+    actions.sort();
+
+    actions
+}
+
+fn report(app_handle: &AppHandle, progress: ShutdownProgress) {
+    if let Some(window) = app_handle.get_window(SHUTDOWN_WINDOW_LABEL) {
+        if let Err(err) = window.emit(SHUTDOWN_PROGRESS_EVENT, progress) {
+            warn!("Failed to send shutdown progress: {}", err);
+        }
+    }
+}
+
+fn report_phase(app_handle: &AppHandle, phase: ShutdownPhase) {
+    report(
+        app_handle,
+        ShutdownProgress {
+            phase,
+            active_actions: vec![],
+            processes: vec![],
+            remaining_ms: 0,
+        },
+    );
+}
+
+/// Waits for actions and processes to finish, brings up the shutdown window if there are any.
+fn drain(app_handle: &AppHandle) {
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    let mut window_opened = false;
+    loop {
+        let active_actions = active_actions(app_handle);
+        let processes = process::supervised();
+        if active_actions.is_empty() && processes.is_empty() {
+            return;
+        }
+        if SKIP_DRAIN.load(Ordering::SeqCst) || Instant::now() >= deadline {
+            warn!(
+                "Cancelling {} actions and {} processes to quit",
+                active_actions.len(),
+                processes.len()
+            );
+            return;
+        }
+
+        if !window_opened {
+            info!(
+                "Waiting for {} actions and {} processes to quit",
+                active_actions.len(),
+                processes.len()
+            );
+            if let Err(err) = WindowHelper::new(app_handle.clone()).new_shutdown_window() {
+                error!("{}", err);
+            }
+            window_opened = true;
+        }
+        report(
+            app_handle,
+            ShutdownProgress {
+                phase: ShutdownPhase::Draining,
+                active_actions,
+                processes,
+                remaining_ms: deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_millis() as u64,
+            },
+        );
+        thread::sleep(PROGRESS_INTERVAL);
+    }
+}
+
+fn run(app_handle: &AppHandle) {
+    info!("Shutting down");
+    drain(app_handle);
+
+    window_state::persist(app_handle);
+
+    report_phase(app_handle, ShutdownPhase::Terminating);
+    process::terminate_all(TERMINATE_GRACE);
+    terminal::close_all();
+    ports::stop_all();
+    power::release_all();
+    providers::check_dangling_provider(app_handle);
+
+    report_phase(app_handle, ShutdownPhase::Flushing);
+    tauri::async_runtime::block_on(async {
+        let flush = async {
+            telemetry::flush(app_handle).await;
+            error_reporting::flush(app_handle).await;
+        };
+        if tokio::time::timeout(FLUSH_TIMEOUT, flush).await.is_err() {
+            warn!("Timed out flushing pending telemetry and error reports");
+        }
+    });
+    info!("Shutdown complete");
+}
+
+/// Quits the app once running work has finished or been cancelled, calling it again while shutting down does nothing.
+pub fn quit(app_handle: &AppHandle) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        run(&app_handle);
+        app_handle.exit(0);
+    });
+}
+
+/// Whether the app is shutting down, windows closing now aren't closed by the user.
+pub fn is_quitting() -> bool {
+    STARTED.load(Ordering::SeqCst)
+}
+
+#[tauri::command]
+pub fn quit_app(app_handle: AppHandle) {
+    quit(&app_handle);
+}
+
+/// Cancels running work right away instead of waiting for it.
+#[tauri::command]
+pub fn quit_now(app_handle: AppHandle) {
+    SKIP_DRAIN.store(true, Ordering::SeqCst);
+    quit(&app_handle);
+}
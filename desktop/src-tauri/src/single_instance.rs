@@ -0,0 +1,220 @@
+use crate::{
+    autostart::MINIMIZED_FLAG, custom_protocol::CustomProtocol, drag_drop, file_association,
+    ui_messages::UiMessage, util::random_id, AppHandle, AppState,
+};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    path::Path,
+    thread,
+    time::Duration,
+};
+use tauri::{Config, Manager};
+
+const INSTANCE_FILE_NAME: &str = "instance.json";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+// Sent back by the primary instance, without it the port might belong to an unrelated process
+const ACK: &str = "ok";
+
+/// Written by the primary instance so that later launches can find it.
+/// The token keeps other local users from sending arguments to it.
+#[derive(Debug, Serialize, Deserialize)]
+struct InstanceInfo {
+    port: u16,
+    token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ForwardedArgs {
+    token: String,
+    args: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SingleInstance {
+    listener: TcpListener,
+    token: String,
+}
+
+/// Forwards the command line arguments to an already running instance and exits if there is one,
+/// otherwise starts listening for arguments from later launches.
+/// Needs to run before anything else, a second instance must not touch any state of the primary one.
+pub fn init(config: &Config) -> Option<SingleInstance> {
+    let path = tauri::api::path::app_data_dir(config)?.join(INSTANCE_FILE_NAME);
+    // The primary instance runs in another working directory, so relative paths have to be resolved here
+    let args: Vec<String> = std::env::args()
+        .skip(1)
+        .map(|arg| match fs::canonicalize(&arg) {
+            Ok(path) if Path::new(&arg).is_relative() => path.to_string_lossy().to_string(),
+            _ => arg,
+        })
+        .collect();
+    if forward(&path, &args).is_ok() {
+        // Logging isn't set up yet
+        println!("DevPod is already running, forwarded arguments to it");
+        std::process::exit(0);
+    }
+
+    match listen(&path) {
+        Ok(instance) => Some(instance),
+        Err(err) => {
+            eprintln!("Failed to listen for other instances: {}", err);
+            None
+        }
+    }
+}
+
+fn forward(path: &Path, args: &[String]) -> io::Result<()> {
+    let info: InstanceInfo = serde_json::from_slice(&fs::read(path)?)?;
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, info.port));
+    let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+    let msg = ForwardedArgs {
+        token: info.token,
+        args: args.to_vec(),
+    };
+    writeln!(stream, "{}", serde_json::to_string(&msg)?)?;
+
+    let mut ack = String::new();
+    BufReader::new(stream).read_line(&mut ack)?;
+    if ack.trim_end() != ACK {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected response from primary instance",
+        ));
+    }
+
+    Ok(())
+}
+
+fn listen(path: &Path) -> io::Result<SingleInstance> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let info = InstanceInfo {
+        port: listener.local_addr()?.port(),
+        token: format!("{:016x}{:016x}", random_id(), random_id()),
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)?
+        .write_all(serde_json::to_string(&info)?.as_bytes())?;
+
+    Ok(SingleInstance {
+        listener,
+        token: info.token,
+    })
+}
+
+/// Reads the arguments sent by another instance and confirms that they have been received.
+fn receive(stream: TcpStream, token: &str) -> io::Result<Vec<String>> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let msg: ForwardedArgs = serde_json::from_str(&line)?;
+    if msg.token != token {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "invalid instance token",
+        ));
+    }
+    writeln!(reader.get_mut(), "{}", ACK)?;
+
+    Ok(msg.args)
+}
+
+/// Opens the URLs, files and folders another instance has been started with, or brings up the dashboard if there are none.
+/// Folders end up here when they're dropped onto the app icon.
+fn handle_args(app_handle: &AppHandle, args: Vec<String>) {
+    let mut opened = false;
+    let mut dropped = vec![];
+    for arg in &args {
+        if CustomProtocol::is_url(arg) {
+            CustomProtocol::handle_url(app_handle, arg);
+            opened = true;
+        } else if file_association::is_workspace_file(arg) || Path::new(arg).is_dir() {
+            dropped.push(arg.clone());
+        }
+    }
+    if !dropped.is_empty() {
+        drag_drop::handle_drop(app_handle, dropped);
+        opened = true;
+    }
+    if opened || args.iter().any(|arg| arg == MINIMIZED_FLAG) {
+        return;
+    }
+
+    let app_state = app_handle.state::<AppState>();
+    tauri::async_runtime::block_on(async move {
+        if let Err(err) = app_state.ui_messages.send(UiMessage::ShowDashboard).await {
+            error!("Failed to broadcast show dashboard message: {}", err);
+        };
+    });
+}
+
+impl SingleInstance {
+    pub fn setup(self, app_handle: &AppHandle) {
+        let app_handle = app_handle.clone();
+        thread::spawn(move || {
+            for stream in self.listener.incoming() {
+                let args = match stream.and_then(|stream| receive(stream, &self.token)) {
+                    Ok(args) => args,
+                    Err(err) => {
+                        warn!("Failed to receive arguments from other instance: {}", err);
+                        continue;
+                    }
+                };
+                info!("Another instance has been started with {:?}", args);
+                handle_args(&app_handle, args);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_forward_args() {
+        let path = std::env::temp_dir()
+            .join(format!("devpod-single-instance-{:x}", random_id()))
+            .join(INSTANCE_FILE_NAME);
+        let instance = listen(&path).unwrap();
+
+        let args = vec!["devpod://open?workspace=test".to_string()];
+        let forwarded = args.clone();
+        let forward_path = path.clone();
+        let sender = thread::spawn(move || forward(&forward_path, &forwarded));
+
+        let (stream, _) = instance.listener.accept().unwrap();
+        assert_eq!(receive(stream, &instance.token).unwrap(), args);
+        assert!(sender.join().unwrap().is_ok());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn should_not_forward_without_primary() {
+        let path = std::env::temp_dir()
+            .join(format!("devpod-single-instance-{:x}", random_id()))
+            .join(INSTANCE_FILE_NAME);
+
+        assert!(forward(&path, &[]).is_err());
+    }
+}
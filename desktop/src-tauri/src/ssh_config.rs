@@ -0,0 +1,296 @@
+use crate::{
+    audit_log::{self, AuditCategory, AuditInitiator},
+    connection_proxies,
+    install_cli::get_cli_path,
+    jump_hosts,
+    settings::Settings,
+    workspaces::WorkspacesState,
+    AppHandle, AppState,
+};
+use log::{info, warn};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tauri::Manager;
+use thiserror::Error;
+
+// Lives next to the user's config so that the include can be relative
+pub(crate) const MANAGED_FILE_NAME: &str = "devpod.config";
+const INCLUDE_MARKER: &str = "# Added by DevPod, remove it in the settings instead of editing it";
+const MANAGED_HEADER: &str =
+    "# Managed by DevPod, changes will be overwritten. Turn it off in the settings.";
+// Same alias as the CLI uses, so that IDEs connect the same way
+pub(crate) const HOST_SUFFIX: &str = ".devpod";
+
+#[derive(Error, Debug)]
+pub enum SshConfigError {
+    #[error("unable to find home directory")]
+    NoHomeDir,
+    #[error("unable to find the DevPod CLI")]
+    NoCliPath(#[source] io::Error),
+    #[error("unable to update SSH config")]
+    Write(#[source] io::Error),
+    #[error("unable to save setting")]
+    Setting(#[source] anyhow::Error),
+}
+impl serde::Serialize for SshConfigError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+pub(crate) fn ssh_dir() -> Result<PathBuf, SshConfigError> {
+    dirs::home_dir()
+        .map(|home| home.join(".ssh"))
+        .ok_or(SshConfigError::NoHomeDir)
+}
+
+pub fn cli_path(app_handle: &AppHandle) -> Result<PathBuf, SshConfigError> {
+    get_cli_path(app_handle)
+        .map(|path| path.with_extension(std::env::consts::EXE_EXTENSION))
+        .map_err(SshConfigError::NoCliPath)
+}
+
+/// Connects to the workspace through the CLI instead of a listening SSH server.
+pub fn proxy_command(cli: &Path, id: &str, context: Option<&str>) -> String {
+    format!(
+        "\"{}\" ssh --stdio --context {} {}",
+        cli.to_string_lossy(),
+        context.unwrap_or("default"),
+        id
+    )
+}
+
+/// The host IDEs reach the workspace with over SSH.
+pub fn host_alias(id: &str) -> String {
+    format!("{}{}", id, HOST_SUFFIX)
+}
+
+/// One host per workspace.
+fn render(cli: &Path, workspaces: &[(&str, Option<&str>)]) -> String {
+    let mut config = format!("{}\n", MANAGED_HEADER);
+    for (id, context) in workspaces {
+        config.push_str(&format!(
+            "\nHost {host}\n  ForwardAgent yes\n  LogLevel error\n  StrictHostKeyChecking no\n  UserKnownHostsFile /dev/null\n  ProxyCommand {proxy_command}\n",
+            host = host_alias(id),
+            proxy_command = proxy_command(cli, id, *context),
+        ));
+    }
+
+    config
+}
+
+/// Prepends the include, SSH only applies it to all hosts if it comes before the first `Host`.
+fn add_include(config: &str) -> String {
+    if config.lines().any(|line| line == INCLUDE_MARKER) {
+        return config.to_string();
+    }
+
+    format!(
+        "{}\nInclude {}\n\n{}",
+        INCLUDE_MARKER, MANAGED_FILE_NAME, config
+    )
+}
+
+fn remove_include(config: &str) -> String {
+    let include = format!("Include {}", MANAGED_FILE_NAME);
+    let mut lines = config.lines().peekable();
+    let mut result = Vec::new();
+    while let Some(line) = lines.next() {
+        if line == INCLUDE_MARKER && lines.peek() == Some(&include.as_str()) {
+            lines.next();
+            // drop the blank line that separated it from the user's config
+            lines.next_if(|line| line.is_empty());
+            continue;
+        }
+        result.push(line);
+    }
+
+    let mut config = result.join("\n");
+    if !config.is_empty() {
+        config.push('\n');
+    }
+
+    config
+}
+
+fn update_file(path: &Path, update: impl FnOnce(&str) -> String) -> io::Result<()> {
+    let current = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    let updated = update(&current);
+    if updated == current {
+        return Ok(());
+    }
+    fs::write(path, updated)?;
+
+    // SSH refuses config files that other users can write to
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+}
+
+/// Jump hosts and proxies need the managed file even if the workspace hosts aren't managed.
+fn has_connection_config(app_handle: &AppHandle) -> bool {
+    !jump_hosts::load(app_handle).is_empty() || !connection_proxies::load(app_handle).is_empty()
+}
+
+/// The workspace hosts if `manage_hosts` is set, jump hosts and proxies are always part of it.
+fn managed_config(
+    app_handle: &AppHandle,
+    workspaces: &WorkspacesState,
+    manage_hosts: bool,
+) -> Result<String, SshConfigError> {
+    let mut config = if manage_hosts {
+        render(&cli_path(app_handle)?, &workspaces.ids_with_context())
+    } else {
+        format!("{}\n", MANAGED_HEADER)
+    };
+    config.push_str(&jump_hosts::render(&jump_hosts::load(app_handle)));
+    config.push_str(&connection_proxies::render(&connection_proxies::load(
+        app_handle,
+    )));
+
+    Ok(config)
+}
+
+fn write_managed_file(
+    app_handle: &AppHandle,
+    workspaces: &WorkspacesState,
+    manage_hosts: bool,
+) -> Result<(), SshConfigError> {
+    let config = managed_config(app_handle, workspaces, manage_hosts)?;
+    update_file(&ssh_dir()?.join(MANAGED_FILE_NAME), |_| config).map_err(SshConfigError::Write)
+}
+
+fn install(
+    app_handle: &AppHandle,
+    workspaces: &WorkspacesState,
+    manage_hosts: bool,
+) -> Result<(), SshConfigError> {
+    let ssh_dir = ssh_dir()?;
+    fs::create_dir_all(&ssh_dir).map_err(SshConfigError::Write)?;
+    write_managed_file(app_handle, workspaces, manage_hosts)?;
+
+    update_file(&ssh_dir.join("config"), add_include).map_err(SshConfigError::Write)
+}
+
+fn uninstall() -> Result<(), SshConfigError> {
+    let ssh_dir = ssh_dir()?;
+    update_file(&ssh_dir.join("config"), remove_include).map_err(SshConfigError::Write)?;
+
+    match fs::remove_file(ssh_dir.join(MANAGED_FILE_NAME)) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(SshConfigError::Write(err)),
+        _ => Ok(()),
+    }
+}
+
+/// Rewrites the workspace hosts, called whenever workspaces have been created, renamed or deleted.
+pub fn sync(app_handle: &AppHandle, workspaces: &WorkspacesState) {
+    let manage_hosts = Settings::manage_ssh_config(app_handle);
+    if !manage_hosts && !has_connection_config(app_handle) {
+        return;
+    }
+
+    if let Err(err) = write_managed_file(app_handle, workspaces, manage_hosts) {
+        warn!("Failed to sync SSH config: {}", err);
+    }
+}
+
+/// Installs the managed file while there is anything to manage, called after jump hosts or proxies changed.
+/// The CLI's own SSH connections only pick them up through the include.
+pub fn apply(app_handle: &AppHandle) -> Result<(), SshConfigError> {
+    let manage_hosts = Settings::manage_ssh_config(app_handle);
+    if !manage_hosts && !has_connection_config(app_handle) {
+        return uninstall();
+    }
+
+    let app_state = app_handle.state::<AppState>();
+    let workspaces = app_state.workspaces.lock().unwrap();
+    install(app_handle, &workspaces, manage_hosts)
+}
+
+#[tauri::command]
+pub fn get_ssh_config_management(app_handle: AppHandle) -> bool {
+    Settings::manage_ssh_config(&app_handle)
+}
+
+/// Adds or removes the include of the managed file in `~/.ssh/config`.
+#[tauri::command]
+pub fn set_ssh_config_management(
+    app_handle: AppHandle,
+    enabled: bool,
+) -> Result<bool, SshConfigError> {
+    let app_state = app_handle.state::<AppState>();
+    let workspaces = app_state.workspaces.lock().unwrap();
+    if enabled {
+        install(&app_handle, &workspaces, true)?;
+    } else if has_connection_config(&app_handle) {
+        // Jump hosts and proxies still need the include
+        write_managed_file(&app_handle, &workspaces, false)?;
+    } else {
+        uninstall()?;
+    }
+    drop(workspaces);
+    Settings::set_manage_ssh_config(&app_handle, enabled).map_err(SshConfigError::Setting)?;
+    let state = if enabled { "enabled" } else { "disabled" };
+    info!("SSH config management {}", state);
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Settings,
+        &format!("ssh config management {}", state),
+        None,
+    );
+
+    Ok(enabled)
+}
+
+/// The entries that are written for the current workspaces, jump hosts and proxies.
+#[tauri::command]
+pub fn preview_ssh_config(app_handle: AppHandle) -> Result<String, SshConfigError> {
+    let app_state = app_handle.state::<AppState>();
+    let workspaces = app_state.workspaces.lock().unwrap();
+
+    managed_config(&app_handle, &workspaces, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render_hosts() {
+        let config = render(
+            Path::new("/opt/DevPod/devpod-cli"),
+            &[("my-project", Some("work")), ("other", None)],
+        );
+
+        assert!(config.starts_with(MANAGED_HEADER));
+        assert!(config.contains("\nHost my-project.devpod\n"));
+        assert!(config.contains(
+            "  ProxyCommand \"/opt/DevPod/devpod-cli\" ssh --stdio --context work my-project\n"
+        ));
+        assert!(config.contains("--context default other\n"));
+    }
+
+    #[test]
+    fn should_add_and_remove_include() {
+        let config = "Host example.com\n  User me\n";
+        let included = add_include(config);
+
+        assert!(included.starts_with(&format!("{}\nInclude devpod.config\n\n", INCLUDE_MARKER)));
+        assert_eq!(add_include(&included), included);
+        assert_eq!(remove_include(&included), config);
+        assert_eq!(remove_include(&add_include("")), "");
+    }
+}
@@ -0,0 +1,428 @@
+use crate::{
+    devcontainer,
+    ssh_config::{self, HOST_SUFFIX, MANAGED_FILE_NAME},
+};
+use log::warn;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use ts_rs::TS;
+
+// Same limit as OpenSSH, guards against includes that include themselves
+const MAX_INCLUDE_DEPTH: usize = 16;
+// Settings of the VS Code Remote-SSH extension
+const VSCODE_CONFIG_FILE_SETTING: &str = "remote.SSH.configFile";
+const VSCODE_REMOTE_PLATFORM_SETTING: &str = "remote.SSH.remotePlatform";
+// Options of the SSH provider
+const HOST_OPTION: &str = "HOST";
+const PORT_OPTION: &str = "PORT";
+const EXTRA_FLAGS_OPTION: &str = "EXTRA_FLAGS";
+
+/// A host of the user's SSH config or one VS Code Remote-SSH connected to, resolved the way `ssh <alias>` would.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SshHost {
+    alias: String,
+    host_name: String,
+    user: Option<String>,
+    port: Option<u16>,
+    identity_file: Option<String>,
+    proxy_jump: Option<String>,
+    /// VS Code Remote-SSH connected to it before
+    used_by_vscode: bool,
+    /// Name for an instance of the SSH provider that connects to the host
+    provider: String,
+    /// Options of the SSH provider for the host
+    provider_options: BTreeMap<String, String>,
+}
+
+/// A `Host` section, options before the first one apply to every host.
+#[derive(Debug)]
+struct Block {
+    patterns: Vec<String>,
+    /// Keys are lowercase, like OpenSSH they are case insensitive
+    options: Vec<(String, String)>,
+}
+
+impl Block {
+    fn every_host() -> Self {
+        Block {
+            patterns: vec!["*".to_string()],
+            options: vec![],
+        }
+    }
+
+    fn matches(&self, alias: &str) -> bool {
+        let negated = self.patterns.iter().any(|pattern| {
+            pattern
+                .strip_prefix('!')
+                .is_some_and(|pattern| matches_pattern(pattern, alias))
+        });
+
+        !negated
+            && self
+                .patterns
+                .iter()
+                .any(|pattern| !pattern.starts_with('!') && matches_pattern(pattern, alias))
+    }
+}
+
+/// `*` and `?` wildcards of host patterns and include paths.
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    match pattern.chars().next() {
+        None => text.is_empty(),
+        Some('*') => (0..=text.len())
+            .filter(|&index| text.is_char_boundary(index))
+            .any(|index| matches_pattern(&pattern[1..], &text[index..])),
+        Some(c) => text.chars().next().is_some_and(|first| {
+            (c == '?' || c.eq_ignore_ascii_case(&first))
+                && matches_pattern(&pattern[c.len_utf8()..], &text[first.len_utf8()..])
+        }),
+    }
+}
+
+fn is_concrete(pattern: &str) -> bool {
+    !pattern.contains(['*', '?', '!'])
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(path), Some(home)) => home.join(path),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Splits `Key value` and `Key=value` lines, quotes around the value are dropped.
+fn parse_line(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (key, value) = line.split_once(|c: char| c.is_whitespace() || c == '=')?;
+    let value = value.trim_start_matches(|c: char| c.is_whitespace() || c == '=');
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+
+    Some((key.to_lowercase(), value.to_string()))
+}
+
+/// The files an `Include` refers to, relative paths are relative to `~/.ssh`.
+fn include_paths(value: &str, ssh_dir: &Path) -> Vec<PathBuf> {
+    value
+        .split_whitespace()
+        .flat_map(|pattern| {
+            let path = expand_home(pattern);
+            let path = if path.is_relative() {
+                ssh_dir.join(path)
+            } else {
+                path
+            };
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if is_concrete(&file_name) {
+                return vec![path];
+            }
+
+            let Some(dir) = path.parent() else {
+                return vec![];
+            };
+            let mut paths = fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .is_some_and(|name| matches_pattern(&file_name, &name.to_string_lossy()))
+                })
+                .collect::<Vec<_>>();
+            paths.sort();
+            paths
+        })
+        // DevPod's own hosts are workspaces already
+        .filter(|path| {
+            path.file_name()
+                .is_none_or(|name| name != MANAGED_FILE_NAME)
+        })
+        .collect()
+}
+
+/// Included files are inlined where they are included, which is what OpenSSH does for includes outside of `Host`
+/// sections.
+fn parse(
+    content: &str,
+    ssh_dir: &Path,
+    read: &impl Fn(&Path) -> Option<String>,
+    depth: usize,
+    blocks: &mut Vec<Block>,
+) {
+    if blocks.is_empty() {
+        blocks.push(Block::every_host());
+    }
+
+    for (key, value) in content.lines().filter_map(parse_line) {
+        match key.as_str() {
+            "host" => blocks.push(Block {
+                patterns: value.split_whitespace().map(String::from).collect(),
+                options: vec![],
+            }),
+            // Conditions can't be evaluated without connecting, the section is left out
+            "match" => blocks.push(Block {
+                patterns: vec![],
+                options: vec![],
+            }),
+            "include" if depth < MAX_INCLUDE_DEPTH => {
+                for path in include_paths(&value, ssh_dir) {
+                    if let Some(content) = read(&path) {
+                        parse(&content, ssh_dir, read, depth + 1, blocks);
+                    }
+                }
+            }
+            "include" => warn!("Ignoring SSH config include {}, nested too deep", value),
+            _ => {
+                if let Some(block) = blocks.last_mut() {
+                    block.options.push((key, value));
+                }
+            }
+        }
+    }
+}
+
+/// The options for `alias`, the first value of an option wins like in OpenSSH.
+fn resolve(blocks: &[Block], alias: &str) -> BTreeMap<String, String> {
+    let mut options = BTreeMap::new();
+    for block in blocks.iter().filter(|block| block.matches(alias)) {
+        for (key, value) in &block.options {
+            options.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+
+    options
+}
+
+fn provider_name(alias: &str) -> String {
+    let name = alias
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>();
+
+    format!("ssh-{}", name.trim_matches('-'))
+}
+
+fn quote_flag(value: &str) -> String {
+    if value.contains(char::is_whitespace) {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn host(blocks: &[Block], alias: &str, used_by_vscode: bool) -> SshHost {
+    // VS Code Remote-SSH also connects to `user@host`, the user takes precedence over the config like with `ssh`
+    let (alias_user, target) = match alias.split_once('@') {
+        Some((user, target)) => (Some(user.to_string()), target),
+        None => (None, alias),
+    };
+    let mut options = resolve(blocks, target);
+    let host_name = options
+        .remove("hostname")
+        .map(|host_name| host_name.replace("%h", target))
+        .unwrap_or_else(|| target.to_string());
+    let user = alias_user.or(options.remove("user"));
+    let port = options.remove("port").and_then(|port| port.parse().ok());
+    let identity_file = options
+        .remove("identityfile")
+        .filter(|path| !path.eq_ignore_ascii_case("none"))
+        .map(|path| expand_home(&path).to_string_lossy().to_string());
+    let proxy_jump = options
+        .remove("proxyjump")
+        .filter(|jump| !jump.eq_ignore_ascii_case("none"));
+
+    let mut provider_options = BTreeMap::new();
+    provider_options.insert(
+        HOST_OPTION.to_string(),
+        match &user {
+            Some(user) => format!("{}@{}", user, host_name),
+            None => host_name.clone(),
+        },
+    );
+    if let Some(port) = port {
+        provider_options.insert(PORT_OPTION.to_string(), port.to_string());
+    }
+    let extra_flags = [
+        identity_file
+            .as_ref()
+            .map(|path| format!("-i {}", quote_flag(path))),
+        proxy_jump.as_ref().map(|jump| format!("-J {}", jump)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    if !extra_flags.is_empty() {
+        provider_options.insert(EXTRA_FLAGS_OPTION.to_string(), extra_flags.join(" "));
+    }
+
+    SshHost {
+        alias: alias.to_string(),
+        host_name,
+        user,
+        port,
+        identity_file,
+        proxy_jump,
+        used_by_vscode,
+        provider: provider_name(alias),
+        provider_options,
+    }
+}
+
+/// Hosts of the config in `blocks` and the ones VS Code connected to, without DevPod's own.
+fn hosts(blocks: &[Block], vscode_hosts: &[String]) -> Vec<SshHost> {
+    let mut aliases = blocks
+        .iter()
+        .flat_map(|block| block.patterns.iter())
+        .filter(|pattern| is_concrete(pattern))
+        .cloned()
+        .collect::<Vec<_>>();
+    aliases.extend(vscode_hosts.iter().cloned());
+
+    let mut seen = Vec::<String>::new();
+    aliases
+        .into_iter()
+        .filter(|alias| !alias.ends_with(HOST_SUFFIX))
+        .filter(|alias| {
+            let is_new = !seen.contains(alias);
+            seen.push(alias.clone());
+            is_new
+        })
+        .map(|alias| {
+            let used_by_vscode = vscode_hosts.contains(&alias);
+            host(blocks, &alias, used_by_vscode)
+        })
+        .collect()
+}
+
+/// The user settings of VS Code, Remote-SSH keeps its settings in there.
+fn vscode_settings() -> Option<Value> {
+    let path = dirs::config_dir()?
+        .join("Code")
+        .join("User")
+        .join("settings.json");
+
+    devcontainer::parse(&fs::read_to_string(path).ok()?)
+}
+
+/// Lists the hosts of the user's SSH config, or the one VS Code Remote-SSH is set up to use, along with the hosts
+/// Remote-SSH has connected to.
+#[tauri::command]
+pub fn list_ssh_hosts() -> Vec<SshHost> {
+    let Ok(ssh_dir) = ssh_config::ssh_dir() else {
+        return vec![];
+    };
+    let settings = vscode_settings();
+    let config_path = settings
+        .as_ref()
+        .and_then(|settings| settings.get(VSCODE_CONFIG_FILE_SETTING))
+        .and_then(Value::as_str)
+        .filter(|path| !path.is_empty())
+        .map(expand_home)
+        .unwrap_or_else(|| ssh_dir.join("config"));
+    let vscode_hosts = settings
+        .as_ref()
+        .and_then(|settings| settings.get(VSCODE_REMOTE_PLATFORM_SETTING))
+        .and_then(Value::as_object)
+        .map(|platforms| platforms.keys().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let read = |path: &Path| match fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(err) => {
+            warn!("Unable to read SSH config {:?}: {}", path, err);
+            None
+        }
+    };
+    let mut blocks = vec![];
+    if let Some(content) = read(&config_path) {
+        parse(&content, &ssh_dir, &read, 0, &mut blocks);
+    }
+
+    hosts(&blocks, &vscode_hosts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_resolve_ssh_hosts() {
+        let config = r#"
+Include work.conf
+
+Host build-box
+    HostName 10.0.0.12
+    Port 2222
+
+Host *.internal !db.internal
+    User deploy
+
+Host my-app.devpod
+    ProxyCommand devpod ssh --stdio my-app
+
+Host *
+    User me
+    IdentityFile "/keys/my key"
+"#;
+        let read = |path: &Path| {
+            (path == Path::new("/ssh/work.conf"))
+                .then(|| "Host gpu.internal\n  ProxyJump=bastion\n".to_string())
+        };
+        let mut blocks = vec![];
+        parse(config, Path::new("/ssh"), &read, 0, &mut blocks);
+
+        let hosts = hosts(&blocks, &["root@10.0.0.99".to_string()]);
+        let aliases = hosts
+            .iter()
+            .map(|host| host.alias.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(aliases, vec!["gpu.internal", "build-box", "root@10.0.0.99"]);
+
+        assert_eq!(hosts[0].user.as_deref(), Some("deploy"));
+        assert_eq!(
+            hosts[0]
+                .provider_options
+                .get(EXTRA_FLAGS_OPTION)
+                .map(String::as_str),
+            Some("-i \"/keys/my key\" -J bastion")
+        );
+        assert_eq!(hosts[1].provider, "ssh-build-box");
+        assert_eq!(
+            hosts[1].provider_options,
+            BTreeMap::from([
+                (HOST_OPTION.to_string(), "me@10.0.0.12".to_string()),
+                (PORT_OPTION.to_string(), "2222".to_string()),
+                (
+                    EXTRA_FLAGS_OPTION.to_string(),
+                    "-i \"/keys/my key\"".to_string()
+                ),
+            ])
+        );
+        assert_eq!(hosts[2].user.as_deref(), Some("root"));
+        assert!(hosts[2].used_by_vscode);
+
+        let db = Block {
+            patterns: vec!["*.internal".to_string(), "!db.internal".to_string()],
+            options: vec![],
+        };
+        assert!(!db.matches("db.internal"));
+    }
+}
@@ -0,0 +1,328 @@
+use crate::{
+    audit_log::{self, AuditCategory, AuditInitiator},
+    keychain::KeychainError,
+    secrets::{self, SecretError, SecretKind},
+    AppHandle,
+};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+use thiserror::Error;
+use ts_rs::TS;
+
+const KEYS_DIR_NAME: &str = "ssh_keys";
+const GLOBAL_KEY_NAME: &str = "global";
+const WORKSPACE_KEY_PREFIX: &str = "workspace-";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum SshKeyType {
+    #[default]
+    Ed25519,
+    /// For hosts that don't support ed25519 yet
+    Rsa,
+    Ecdsa,
+}
+
+impl SshKeyType {
+    fn keygen_args(&self) -> &'static [&'static str] {
+        match self {
+            SshKeyType::Ed25519 => &["-t", "ed25519"],
+            SshKeyType::Rsa => &["-t", "rsa", "-b", "4096"],
+            SshKeyType::Ecdsa => &["-t", "ecdsa", "-b", "521"],
+        }
+    }
+
+    fn file_name(&self) -> &'static str {
+        match self {
+            SshKeyType::Ed25519 => "id_ed25519",
+            SshKeyType::Rsa => "id_rsa",
+            SshKeyType::Ecdsa => "id_ecdsa",
+        }
+    }
+
+    fn from_file_name(file_name: &str) -> Option<Self> {
+        [SshKeyType::Ed25519, SshKeyType::Rsa, SshKeyType::Ecdsa]
+            .into_iter()
+            .find(|key_type| key_type.file_name() == file_name)
+    }
+}
+
+/// A key pair the app generated, either for a single workspace or for all of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SshKey {
+    /// `global` or `workspace-<id>`
+    name: String,
+    /// `None` for the global key
+    workspace_id: Option<String>,
+    key_type: SshKeyType,
+    public_key: String,
+    fingerprint: Option<String>,
+    /// The passphrase is stored in the keychain
+    has_passphrase: bool,
+    created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Error, Debug)]
+pub enum SshKeyError {
+    #[error("invalid workspace id {0}")]
+    InvalidWorkspaceId(String),
+    #[error("SSH key {0} already exists")]
+    AlreadyExists(String),
+    #[error("SSH key {0} not found")]
+    NotFound(String),
+    #[error("unable to find app data dir")]
+    NoDataDir,
+    #[error("unable to access SSH keys")]
+    Io(#[from] io::Error),
+    #[error("ssh-keygen failed: {0}")]
+    Keygen(String),
+    #[error(transparent)]
+    Secret(#[from] SecretError),
+}
+impl serde::Serialize for SshKeyError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn key_name(workspace_id: Option<&str>) -> Result<String, SshKeyError> {
+    match workspace_id {
+        None => Ok(GLOBAL_KEY_NAME.to_string()),
+        // Ids end up in paths, the CLI only allows these characters anyway
+        Some(id)
+            if !id.is_empty()
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+        {
+            Ok(format!("{}{}", WORKSPACE_KEY_PREFIX, id))
+        }
+        Some(id) => Err(SshKeyError::InvalidWorkspaceId(id.to_string())),
+    }
+}
+
+fn keys_dir(app_handle: &AppHandle) -> Result<PathBuf, SshKeyError> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(KEYS_DIR_NAME))
+        .ok_or(SshKeyError::NoDataDir)
+}
+
+fn ssh_keygen(configure: impl FnOnce(&mut Command)) -> io::Result<Output> {
+    let mut command = Command::new("ssh-keygen");
+    configure(&mut command);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW, `ssh-keygen` is a console application
+        command.creation_flags(0x08000000);
+    }
+
+    command.output()
+}
+
+/// `SHA256:...` from the output of `ssh-keygen -l`.
+fn parse_fingerprint(output: &str) -> Option<String> {
+    output
+        .split_whitespace()
+        .nth(1)
+        .filter(|fingerprint| fingerprint.contains(':'))
+        .map(String::from)
+}
+
+fn fingerprint(public_key_path: &Path) -> Option<String> {
+    let output = ssh_keygen(|command| {
+        command.arg("-l").arg("-f").arg(public_key_path);
+    })
+    .ok()?;
+
+    parse_fingerprint(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The key in `dir`, `None` if there is none.
+fn read_key(dir: &Path, name: &str) -> Result<Option<SshKey>, SshKeyError> {
+    let private_key = match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).find_map(|entry| {
+            let key_type = SshKeyType::from_file_name(&entry.file_name().to_string_lossy())?;
+            Some((entry.path(), key_type))
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+    let (private_key_path, key_type) = match private_key {
+        Some(private_key) => private_key,
+        None => return Ok(None),
+    };
+
+    let public_key_path = private_key_path.with_extension("pub");
+    let public_key = fs::read_to_string(&public_key_path)?.trim().to_string();
+    let has_passphrase = secrets::get(SecretKind::SshKeyPassphrase, name)?.is_some();
+    let created_at = fs::metadata(&private_key_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(DateTime::<Utc>::from);
+
+    Ok(Some(SshKey {
+        name: name.to_string(),
+        workspace_id: name.strip_prefix(WORKSPACE_KEY_PREFIX).map(String::from),
+        key_type,
+        public_key,
+        fingerprint: fingerprint(&public_key_path),
+        has_passphrase,
+        created_at,
+    }))
+}
+
+/// Generates a key pair for the workspace, or the global one if `workspace_id` is `None`.
+#[tauri::command]
+pub fn generate_ssh_key(
+    app_handle: AppHandle,
+    workspace_id: Option<String>,
+    key_type: Option<SshKeyType>,
+    passphrase: Option<String>,
+) -> Result<SshKey, SshKeyError> {
+    let name = key_name(workspace_id.as_deref())?;
+    let dir = keys_dir(&app_handle)?.join(&name);
+    if read_key(&dir, &name)?.is_some() {
+        return Err(SshKeyError::AlreadyExists(name));
+    }
+
+    let key_type = key_type.unwrap_or_default();
+    let passphrase = passphrase.filter(|passphrase| !passphrase.is_empty());
+    fs::create_dir_all(&dir)?;
+    let output = ssh_keygen(|command| {
+        command
+            .args(key_type.keygen_args())
+            .arg("-q")
+            .arg("-C")
+            .arg(format!("devpod-{}", name))
+            .arg("-N")
+            .arg(passphrase.as_deref().unwrap_or(""))
+            .arg("-f")
+            .arg(dir.join(key_type.file_name()));
+    })?;
+    if !output.status.success() {
+        return Err(SshKeyError::Keygen(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    if let Some(passphrase) = passphrase {
+        secrets::set(SecretKind::SshKeyPassphrase, &name, &passphrase)?;
+    }
+    info!("Generated {:?} SSH key {}", key_type, name);
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Credentials,
+        "generate ssh key",
+        Some(&name),
+    );
+
+    read_key(&dir, &name)?.ok_or(SshKeyError::NotFound(name))
+}
+
+/// The global key first, then the workspace keys.
+#[tauri::command]
+pub fn list_ssh_keys(app_handle: AppHandle) -> Result<Vec<SshKey>, SshKeyError> {
+    let dir = keys_dir(&app_handle)?;
+    let mut names: Vec<String> = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(err.into()),
+    };
+    names.sort_by_key(|name| (name != GLOBAL_KEY_NAME, name.clone()));
+
+    let mut keys = Vec::new();
+    for name in names {
+        if let Some(key) = read_key(&dir.join(&name), &name)? {
+            keys.push(key);
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Deletes the key pair and its passphrase.
+#[tauri::command]
+pub fn remove_ssh_key(
+    app_handle: AppHandle,
+    workspace_id: Option<String>,
+) -> Result<(), SshKeyError> {
+    let name = key_name(workspace_id.as_deref())?;
+    let dir = keys_dir(&app_handle)?.join(&name);
+    if read_key(&dir, &name)?.is_none() {
+        return Err(SshKeyError::NotFound(name));
+    }
+
+    fs::remove_dir_all(&dir)?;
+    match secrets::delete(SecretKind::SshKeyPassphrase, &name) {
+        Ok(()) | Err(SecretError::Keychain(KeychainError::NotFound(..))) => {}
+        Err(err) => warn!("Failed to delete passphrase of SSH key {}: {}", name, err),
+    }
+    info!("Removed SSH key {}", name);
+    audit_log::record(
+        AuditInitiator::Ui,
+        AuditCategory::Credentials,
+        "remove ssh key",
+        Some(&name),
+    );
+
+    Ok(())
+}
+
+/// The public key in `authorized_keys` format, e.g. to add it to a git host.
+#[tauri::command]
+pub fn export_ssh_public_key(
+    app_handle: AppHandle,
+    workspace_id: Option<String>,
+) -> Result<String, SshKeyError> {
+    let name = key_name(workspace_id.as_deref())?;
+    let dir = keys_dir(&app_handle)?.join(&name);
+
+    read_key(&dir, &name)?
+        .map(|key| key.public_key)
+        .ok_or(SshKeyError::NotFound(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_name_keys_by_scope() {
+        assert_eq!(key_name(None).unwrap(), "global");
+        assert_eq!(
+            key_name(Some("my-project")).unwrap(),
+            "workspace-my-project"
+        );
+        assert!(key_name(Some("../other")).is_err());
+        assert!(key_name(Some("")).is_err());
+    }
+
+    #[test]
+    fn should_parse_fingerprint() {
+        assert_eq!(
+            parse_fingerprint(
+                "256 SHA256:n4Sx9O1Nqm4Oy1+ESvj3OFgdKvsgXOz0aVJ9pwCl3OI devpod-global (ED25519)\n"
+            ),
+            Some("SHA256:n4Sx9O1Nqm4Oy1+ESvj3OFgdKvsgXOz0aVJ9pwCl3OI".to_string())
+        );
+        assert_eq!(parse_fingerprint(""), None);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{
+    cli_cache,
+    commands::{list_pro_instances::ListProInstancesCommand, DevpodCommandError},
+    debug_console, startup_profile, state_sync,
+    subsystems::{self, Subsystem},
+    workspaces, AppHandle,
+};
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, VecDeque},
+    sync::{Arc, Mutex},
+    thread,
+    time::Instant,
+};
+use tauri::Manager;
+use ts_rs::TS;
+
+// WARN: needs to match the event name in `client.ts`
+const STARTUP_DATA_EVENT: &str = "startup_data";
+// Every load spawns the CLI, running all of them at once slows down the one the window is waiting for
+const MAX_PARALLEL: usize = 2;
+// What the first paint needs comes first
+const ORDER: [StartupData; 4] = [
+    StartupData::Workspaces,
+    StartupData::Providers,
+    StartupData::ProInstances,
+    StartupData::CliVersion,
+];
+
+lazy_static! {
+    static ref LOADED: Mutex<BTreeSet<StartupData>> = Mutex::new(BTreeSet::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum StartupData {
+    Workspaces,
+    Providers,
+    ProInstances,
+    CliVersion,
+}
+
+/// Sent as each part of the startup data has been loaded. Workspaces and providers reach the frontend as state
+/// patches, the others come along with this.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct StartupDataEvent {
+    data: StartupData,
+    #[ts(type = "unknown")]
+    value: Option<serde_json::Value>,
+    /// Failed loads count as loaded, the regular polling picks them up again
+    error: Option<String>,
+}
+
+fn load(
+    app_handle: &AppHandle,
+    data: StartupData,
+) -> Result<Option<serde_json::Value>, DevpodCommandError> {
+    match data {
+        StartupData::Workspaces => {
+            let result = workspaces::refresh(app_handle);
+            workspaces::start_polling(app_handle);
+
+            result.map(|_| None)
+        }
+        StartupData::Providers => state_sync::refresh_providers(app_handle).map(|_| None),
+        StartupData::ProInstances => ListProInstancesCommand::new()
+            .exec_raw(app_handle)
+            .map(Some),
+        StartupData::CliVersion => {
+            cli_cache::version(app_handle).map(|version| Some(version.into()))
+        }
+    }
+}
+
+fn run(app_handle: &AppHandle, data: StartupData) {
+    let task = debug_console::track_task("startup load", None);
+    let started = Instant::now();
+    let result = load(app_handle, data);
+    drop(task);
+    startup_profile::record(format!("load {:?}", data), started, false);
+
+    let event = match result {
+        Ok(value) => {
+            info!("Loaded {:?} in {:?}", data, started.elapsed());
+            StartupDataEvent {
+                data,
+                value,
+                error: None,
+            }
+        }
+        Err(err) => {
+            warn!("Failed to load {:?}: {}", data, err);
+            StartupDataEvent {
+                data,
+                value: None,
+                error: Some(err.to_string()),
+            }
+        }
+    };
+    LOADED.lock().unwrap().insert(data);
+    if let Err(err) = app_handle.emit_all(STARTUP_DATA_EVENT, event) {
+        error!("Failed to send startup data event: {}", err);
+    }
+
+    // The frontend polls the instances from here on, starting off with the ones that came with the event
+    if data == StartupData::ProInstances {
+        subsystems::ensure(app_handle, Subsystem::Pro);
+    }
+}
+
+/// Loads what the main window shows first, a few parts at a time.
+pub fn setup(app_handle: &AppHandle) {
+    let queue = Arc::new(Mutex::new(VecDeque::from(ORDER)));
+    for _ in 0..MAX_PARALLEL {
+        let app_handle = app_handle.clone();
+        let queue = Arc::clone(&queue);
+        thread::spawn(move || loop {
+            let Some(data) = queue.lock().unwrap().pop_front() else {
+                break;
+            };
+            run(&app_handle, data);
+        });
+    }
+}
+
+#[tauri::command]
+pub fn get_loaded_startup_data() -> Vec<StartupData> {
+    LOADED.lock().unwrap().iter().copied().collect()
+}
@@ -0,0 +1,146 @@
+use crate::{telemetry, AppHandle};
+use lazy_static::lazy_static;
+use log::info;
+use serde::Serialize;
+use std::{sync::Mutex, time::Instant};
+use ts_rs::TS;
+
+type DeferredPhase = (&'static str, Box<dyn FnOnce(&AppHandle) + Send>);
+
+lazy_static! {
+    static ref PROCESS_START: Instant = Instant::now();
+    static ref PROFILE: Mutex<StartupProfile> = Mutex::new(StartupProfile::default());
+    static ref DEFERRED: Mutex<Vec<DeferredPhase>> = Mutex::new(vec![]);
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct StartupPhase {
+    name: String,
+    /// Since the process started
+    #[ts(type = "number")]
+    start_ms: u64,
+    #[ts(type = "number")]
+    duration_ms: u64,
+    /// Ran after the first render because of the fast-start mode
+    deferred: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct StartupProfile {
+    phases: Vec<StartupPhase>,
+    /// When the main window reported that it's ready, `None` until it did
+    #[ts(type = "number | null")]
+    first_render_ms: Option<u64>,
+    fast_start: bool,
+}
+
+fn since_start(instant: Instant) -> u64 {
+    instant.duration_since(*PROCESS_START).as_millis() as u64
+}
+
+/// Starts the clock, everything in the profile is relative to this.
+pub fn init() {
+    lazy_static::initialize(&PROCESS_START);
+}
+
+/// Adds a phase that started at `started` and just ended.
+pub fn record(name: impl Into<String>, started: Instant, deferred: bool) {
+    PROFILE.lock().unwrap().phases.push(StartupPhase {
+        name: name.into(),
+        start_ms: since_start(started),
+        duration_ms: started.elapsed().as_millis() as u64,
+        deferred,
+    });
+}
+
+/// Runs a phase of the setup, it shows up in the profile and as a child of `parent` in the traces.
+pub fn phase<T>(parent: &telemetry::Span, name: &'static str, f: impl FnOnce() -> T) -> T {
+    let _span = parent.child(name);
+    let started = Instant::now();
+    let result = f();
+    record(name, started, false);
+
+    result
+}
+
+/// Like `phase` for the parts of the setup the window doesn't need, in fast-start mode they wait for the first
+/// render.
+pub fn non_essential<F>(
+    app_handle: &AppHandle,
+    parent: &telemetry::Span,
+    fast_start: bool,
+    name: &'static str,
+    f: F,
+) where
+    F: FnOnce(&AppHandle) + Send + 'static,
+{
+    PROFILE.lock().unwrap().fast_start = fast_start;
+    if fast_start {
+        DEFERRED.lock().unwrap().push((name, Box::new(f)));
+    } else {
+        phase(parent, name, || f(app_handle));
+    }
+}
+
+/// Called once the main window rendered, runs whatever the fast-start mode held back.
+pub fn first_render(app_handle: &AppHandle) {
+    {
+        let mut profile = PROFILE.lock().unwrap();
+        if profile.first_render_ms.is_some() {
+            return;
+        }
+        let first_render_ms = since_start(Instant::now());
+        profile.first_render_ms = Some(first_render_ms);
+        info!("First render after {}ms", first_render_ms);
+    }
+
+    let deferred = std::mem::take(&mut *DEFERRED.lock().unwrap());
+    if deferred.is_empty() {
+        return;
+    }
+    // Most of them set up native menus and window hooks, which have to happen on the main thread
+    let main_thread_handle = app_handle.clone();
+    let _ = app_handle.run_on_main_thread(move || {
+        let span = telemetry::Span::start("deferred setup");
+        for (name, f) in deferred {
+            let _span = span.child(name);
+            let started = Instant::now();
+            f(&main_thread_handle);
+            record(name, started, true);
+        }
+    });
+}
+
+/// How long the last start took, phase by phase.
+#[tauri::command]
+pub fn get_startup_profile() -> StartupProfile {
+    PROFILE.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_record_phases_in_order() {
+        init();
+        let span = telemetry::Span::start("app setup");
+        let value = phase(&span, "first", || 42);
+        phase(&span, "second", || ());
+
+        assert_eq!(value, 42);
+        let profile = get_startup_profile();
+        let names: Vec<_> = profile
+            .phases
+            .iter()
+            .map(|phase| phase.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["first", "second"]);
+        assert!(profile.phases[0].start_ms <= profile.phases[1].start_ms);
+        assert!(profile.phases.iter().all(|phase| !phase.deferred));
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{
+    cli_cache::{self, CliCacheScope},
+    commands::{list_providers::ListProvidersCommand, DevpodCommandConfig, DevpodCommandError},
+    debug_console, home_watcher, payloads, AppHandle,
+};
+use lazy_static::lazy_static;
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Mutex, thread, time::Duration};
+use tauri::Manager;
+use ts_rs::TS;
+
+// WARN: needs to match the event name in `client.ts`
+const STATE_PATCH_EVENT: &str = "state_patch";
+// Changes within this window go out as one patch, so that a burst of them only causes one render
+const DEBOUNCE: Duration = Duration::from_millis(300);
+const PROVIDERS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const WATCHED_PROVIDERS_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+lazy_static! {
+    static ref COLLECTIONS: Mutex<BTreeMap<SyncedCollection, Collection>> =
+        Mutex::new(BTreeMap::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum SyncedCollection {
+    Workspaces,
+    Providers,
+}
+
+/// The items of a collection that changed since the previous patch.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct StatePatch {
+    collection: SyncedCollection,
+    /// Goes up by one with every patch, the frontend asks for a snapshot when it missed one
+    #[ts(type = "number")]
+    revision: u64,
+    #[ts(type = "Record<string, unknown>")]
+    upserted: BTreeMap<String, serde_json::Value>,
+    removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct StateSnapshot {
+    /// 0 until the collection has been loaded for the first time
+    #[ts(type = "number")]
+    revision: u64,
+    #[ts(type = "Record<string, unknown>")]
+    items: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Default)]
+struct Collection {
+    revision: u64,
+    /// What the frontend has been sent so far
+    sent: BTreeMap<String, serde_json::Value>,
+    /// Waiting for the debounce window to pass
+    pending: Option<BTreeMap<String, serde_json::Value>>,
+}
+
+/// Items that are new or different in `next`, and the keys of the ones that are gone.
+fn diff(
+    sent: &BTreeMap<String, serde_json::Value>,
+    next: &BTreeMap<String, serde_json::Value>,
+) -> (BTreeMap<String, serde_json::Value>, Vec<String>) {
+    let upserted = next
+        .iter()
+        .filter(|(key, item)| sent.get(*key) != Some(*item))
+        .map(|(key, item)| (key.clone(), item.clone()))
+        .collect();
+    let removed = sent
+        .keys()
+        .filter(|key| !next.contains_key(*key))
+        .cloned()
+        .collect();
+
+    (upserted, removed)
+}
+
+fn flush(app_handle: &AppHandle, collection: SyncedCollection) {
+    let patch = {
+        let mut collections = COLLECTIONS.lock().unwrap();
+        let state = collections.entry(collection).or_default();
+        let Some(next) = state.pending.take() else {
+            return;
+        };
+        let (upserted, removed) = diff(&state.sent, &next);
+        // The first load is always sent, so that the frontend knows the collection is empty rather than not loaded
+        if upserted.is_empty() && removed.is_empty() && state.revision > 0 {
+            return;
+        }
+
+        // A workspace or provider that changed in the list may have changed in what the CLI reports about it too
+        for key in upserted.keys().chain(removed.iter()) {
+            cli_cache::invalidate(&match collection {
+                SyncedCollection::Workspaces => CliCacheScope::Workspace(key.clone()),
+                SyncedCollection::Providers => CliCacheScope::Provider(key.clone()),
+            });
+        }
+
+        state.revision += 1;
+        state.sent = next;
+        StatePatch {
+            collection,
+            revision: state.revision,
+            upserted,
+            removed,
+        }
+    };
+
+    if let Err(err) = payloads::emit_all(app_handle, STATE_PATCH_EVENT, patch) {
+        error!("Failed to send state patch: {}", err);
+    }
+}
+
+/// Takes the latest state of a collection, the frontend gets what changed once the debounce window passed. The
+/// first load goes out right away, there's nothing to batch it with and the window is waiting for it.
+pub fn update(
+    app_handle: &AppHandle,
+    collection: SyncedCollection,
+    items: BTreeMap<String, serde_json::Value>,
+) {
+    let (schedule, is_first) = {
+        let mut collections = COLLECTIONS.lock().unwrap();
+        let state = collections.entry(collection).or_default();
+
+        (state.pending.replace(items).is_none(), state.revision == 0)
+    };
+    if !schedule {
+        return;
+    }
+    if is_first {
+        flush(app_handle, collection);
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        thread::sleep(DEBOUNCE);
+        flush(&app_handle, collection);
+    });
+}
+
+/// Workspaces as listed by the CLI, keyed by their id. The ones without an id are half created and left out.
+pub fn by_id(items: Vec<serde_json::Value>) -> BTreeMap<String, serde_json::Value> {
+    items
+        .into_iter()
+        .filter_map(|item| {
+            let id = item.get("id")?.as_str()?.to_string();
+
+            Some((id, item))
+        })
+        .collect()
+}
+
+/// Lists the providers once, the frontend gets them once the debounce window passed.
+pub fn refresh_providers(app_handle: &AppHandle) -> Result<(), DevpodCommandError> {
+    let _task = debug_console::track_task("providers poll", None);
+    match ListProvidersCommand::new().exec(app_handle)? {
+        serde_json::Value::Object(providers) => update(
+            app_handle,
+            SyncedCollection::Providers,
+            providers.into_iter().collect(),
+        ),
+        _ => warn!("Unexpected provider list"),
+    }
+
+    Ok(())
+}
+
+/// Polls the providers, the workspaces are polled along with the rest of the workspace state. Changes made with the
+/// CLI are picked up by the home watcher while it runs, polling only has to catch what it misses then.
+pub fn setup(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    thread::spawn(move || loop {
+        if let Err(err) = refresh_providers(&app_handle) {
+            warn!("Failed to list providers: {}", err);
+        }
+
+        thread::sleep(if home_watcher::is_active() {
+            WATCHED_PROVIDERS_POLL_INTERVAL
+        } else {
+            PROVIDERS_POLL_INTERVAL
+        });
+    });
+}
+
+/// The items of a collection as the frontend knows them.
+pub fn items(collection: SyncedCollection) -> BTreeMap<String, serde_json::Value> {
+    COLLECTIONS
+        .lock()
+        .unwrap()
+        .get(&collection)
+        .map(|state| state.sent.clone())
+        .unwrap_or_default()
+}
+
+/// Everything the frontend has been sent for a collection, to start from or to catch up after missing a patch.
+#[tauri::command]
+pub fn get_state_snapshot(collection: SyncedCollection) -> StateSnapshot {
+    let collections = COLLECTIONS.lock().unwrap();
+
+    collections
+        .get(&collection)
+        .map(|state| StateSnapshot {
+            revision: state.revision,
+            items: state.sent.clone(),
+        })
+        .unwrap_or(StateSnapshot {
+            revision: 0,
+            items: BTreeMap::new(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_only_send_changed_items() {
+        let sent = by_id(vec![
+            json!({"id": "a", "lastUsed": "2024-01-01T00:00:00Z"}),
+            json!({"id": "b"}),
+            json!({"id": "c"}),
+        ]);
+        let next = by_id(vec![
+            json!({"id": "a", "lastUsed": "2024-02-01T00:00:00Z"}),
+            json!({"id": "b"}),
+            json!({"id": "d"}),
+            json!({"id": null}),
+        ]);
+
+        let (upserted, removed) = diff(&sent, &next);
+        assert_eq!(upserted.keys().collect::<Vec<_>>(), vec!["a", "d"]);
+        assert_eq!(removed, vec!["c".to_string()]);
+    }
+}
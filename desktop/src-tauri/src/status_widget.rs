@@ -0,0 +1,281 @@
+use crate::{
+    action_logs, browser_ide,
+    commands::{
+        stop_workspace::StopWorkspaceCommand, workspace_status::WorkspaceStatusCommand,
+        DevpodCommandConfig,
+    },
+    custom_protocol::OpenWorkspaceMsg,
+    resumable_operations::{self, ResumableKind},
+    settings::Settings,
+    shutdown,
+    ui_messages::{ShowToastMsg, ToastStatus, UiMessage},
+    window::WindowHelper,
+    AppHandle, AppState,
+};
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+use tauri::Manager;
+use ts_rs::TS;
+
+pub const STATUS_WIDGET_WINDOW_LABEL: &str = "status_widget";
+// WARN: needs to match the event name in `client.ts`
+const SNAPSHOT_EVENT: &str = "status_widget_snapshot";
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(2);
+// Asking the providers is slow, so states are refreshed less often than builds
+const STATE_REFRESH_TICKS: u32 = 15;
+const WINDOW_OPEN_TIMEOUT_TICKS: u32 = 10;
+const RUNNING_STATE: &str = "Running";
+
+// Bumped whenever the widget is opened, so that the thread of a previous window stops rather than feeding the new one
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    // Workspace states as last reported by their providers, only refreshed while the widget is open
+    static ref STATES: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct WidgetBuild {
+    kind: ResumableKind,
+    started_at: DateTime<Utc>,
+    /// The latest line of its log
+    message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct WidgetWorkspace {
+    id: String,
+    state: Option<String>,
+    build: Option<WidgetBuild>,
+}
+
+/// Running workspaces and the ones being built, most recently used first.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct StatusWidgetSnapshot {
+    workspaces: Vec<WidgetWorkspace>,
+}
+
+/// The message of a stream event in an action log, see `TStreamEvent`.
+fn log_message(line: &str) -> Option<String> {
+    let event: serde_json::Value = serde_json::from_str(line).ok()?;
+    let message = event
+        .pointer("/data/message")
+        .or_else(|| event.pointer("/error/message"))?
+        .as_str()?
+        .trim();
+
+    (!message.is_empty()).then(|| message.to_string())
+}
+
+fn workspace_ids(app_handle: &AppHandle) -> Vec<String> {
+    let state = app_handle.state::<AppState>();
+    let workspaces = state.workspaces.lock().unwrap();
+
+    workspaces
+        .recent_ids()
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn refresh_states(app_handle: &AppHandle) {
+    for id in workspace_ids(app_handle) {
+        match WorkspaceStatusCommand::new(id.clone()).exec(app_handle) {
+            Ok(state) => {
+                STATES.lock().unwrap().insert(id, state);
+            }
+            Err(err) => warn!("Failed to get state of {}: {}", id, err),
+        }
+    }
+}
+
+fn snapshot(app_handle: &AppHandle) -> StatusWidgetSnapshot {
+    let builds: HashMap<String, WidgetBuild> = resumable_operations::running(app_handle)
+        .into_iter()
+        .map(|operation| {
+            let message = action_logs::last_line(app_handle, operation.id())
+                .as_deref()
+                .and_then(log_message);
+            let build = WidgetBuild {
+                kind: operation.kind(),
+                started_at: operation.started_at(),
+                message,
+            };
+
+            (operation.workspace_id().to_string(), build)
+        })
+        .collect();
+    let states = STATES.lock().unwrap();
+
+    let workspaces = workspace_ids(app_handle)
+        .into_iter()
+        .filter_map(|id| {
+            let state = states.get(&id).cloned();
+            let build = builds.get(&id).cloned();
+            if build.is_none() && state.as_deref() != Some(RUNNING_STATE) {
+                return None;
+            }
+
+            Some(WidgetWorkspace { id, state, build })
+        })
+        .collect();
+
+    StatusWidgetSnapshot { workspaces }
+}
+
+fn open(app_handle: &AppHandle) {
+    if let Err(err) = WindowHelper::new(app_handle.clone()).new_status_widget_window() {
+        error!("{}", err);
+        return;
+    }
+
+    // Pushes snapshots for as long as the window is open, it's created asynchronously on the main thread
+    let app_handle = app_handle.clone();
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    thread::spawn(move || {
+        let mut opened = false;
+        for tick in 0.. {
+            if GENERATION.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            if tick % STATE_REFRESH_TICKS == 0 {
+                refresh_states(&app_handle);
+            }
+            let window = match app_handle.get_window(STATUS_WIDGET_WINDOW_LABEL) {
+                Some(window) => window,
+                None if !opened && tick < WINDOW_OPEN_TIMEOUT_TICKS => {
+                    thread::sleep(SNAPSHOT_INTERVAL);
+                    continue;
+                }
+                None => break,
+            };
+            opened = true;
+            if let Err(err) = window.emit(SNAPSHOT_EVENT, snapshot(&app_handle)) {
+                error!("Failed to send status widget snapshot: {}", err);
+            }
+            thread::sleep(SNAPSHOT_INTERVAL);
+        }
+
+        // Closed by the user rather than by quitting, don't bring it back next time
+        if opened && !shutdown::is_quitting() {
+            remember(&app_handle, false);
+        }
+    });
+}
+
+fn remember(app_handle: &AppHandle, open: bool) {
+    if let Err(err) = Settings::set_status_widget(app_handle, open) {
+        warn!("Failed to remember status widget: {}", err);
+    }
+}
+
+fn send(app_handle: &AppHandle, msg: UiMessage) {
+    let app_state = app_handle.state::<AppState>();
+    tauri::async_runtime::block_on(async move {
+        if let Err(err) = app_state.ui_messages.send(msg).await {
+            error!("Failed to broadcast status widget message: {}", err);
+        }
+    });
+}
+
+/// Opens the widget again if it was open when the app last quit.
+pub fn setup(app_handle: &AppHandle) {
+    if Settings::status_widget(app_handle) {
+        open(app_handle);
+    }
+}
+
+/// Opens the widget, or closes it if it's already open. It stays on top of other windows.
+#[tauri::command]
+pub fn toggle_status_widget(app_handle: AppHandle) {
+    if let Some(window) = app_handle.get_window(STATUS_WIDGET_WINDOW_LABEL) {
+        if let Err(err) = window.close() {
+            error!("Failed to close status widget: {}", err);
+        }
+        return;
+    }
+
+    info!("Opening status widget");
+    remember(&app_handle, true);
+    open(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_status_widget_snapshot(app_handle: AppHandle) -> StatusWidgetSnapshot {
+    snapshot(&app_handle)
+}
+
+/// Shows the workspace in the main window.
+#[tauri::command]
+pub fn open_widget_workspace(app_handle: AppHandle, workspace_id: String) {
+    send(
+        &app_handle,
+        UiMessage::OpenWorkspace(OpenWorkspaceMsg::with_id(workspace_id)),
+    );
+}
+
+/// Stops the workspace in the background, failures are reported as a toast.
+#[tauri::command]
+pub fn stop_widget_workspace(app_handle: AppHandle, workspace_id: String) {
+    thread::spawn(move || {
+        info!("Stopping {} from status widget", workspace_id);
+        match StopWorkspaceCommand::new(workspace_id.clone()).exec(&app_handle) {
+            Ok(()) => {
+                browser_ide::close_workspace_sessions(&app_handle, &workspace_id);
+                STATES
+                    .lock()
+                    .unwrap()
+                    .insert(workspace_id, "Stopped".to_string());
+            }
+            Err(err) => {
+                error!("Failed to stop {}: {}", workspace_id, err);
+                send(
+                    &app_handle,
+                    UiMessage::ShowToast(ShowToastMsg::new(
+                        format!("Unable to stop {}", workspace_id),
+                        err.to_string(),
+                        ToastStatus::Error,
+                    )),
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_show_latest_log_message() {
+        assert_eq!(
+            log_message(
+                r#"{"type":"data","data":{"time":"2024-01-01T00:00:00Z","level":"info","message":"Building image..."}}"#
+            )
+            .as_deref(),
+            Some("Building image...")
+        );
+        assert_eq!(
+            log_message(r#"{"type":"error","error":{"message":"exit status 1"}}"#).as_deref(),
+            Some("exit status 1")
+        );
+        assert_eq!(log_message("not json"), None);
+    }
+}
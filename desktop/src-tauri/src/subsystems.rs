@@ -0,0 +1,124 @@
+use crate::{
+    clipboard_watcher, community_contributions, debug_console, disk_space, home_watcher,
+    port_detection, state_sync, AppHandle, AppState,
+};
+use lazy_static::lazy_static;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::Manager;
+use ts_rs::TS;
+
+// WARN: needs to match the event name in `client.ts`
+const SUBSYSTEM_READY_EVENT: &str = "subsystem_ready";
+// Leaves the first render of the window to itself
+const DEFERRED_START_DELAY: Duration = Duration::from_secs(3);
+const ALL: [Subsystem; 4] = [
+    Subsystem::Watchers,
+    Subsystem::Registry,
+    Subsystem::Updates,
+    Subsystem::Pro,
+];
+
+lazy_static! {
+    static ref STARTED: Mutex<BTreeSet<Subsystem>> = Mutex::new(BTreeSet::new());
+    static ref READY: Mutex<BTreeSet<Subsystem>> = Mutex::new(BTreeSet::new());
+}
+
+/// Parts of the app that aren't needed to show the window, they start once it's up or when they're first used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum Subsystem {
+    /// Clipboard, disk space, port and DevPod home watchers
+    Watchers,
+    /// Provider list and community contributions
+    Registry,
+    /// App and CLI update checks
+    Updates,
+    /// Pro instances, polled by the frontend once this is ready
+    Pro,
+}
+
+fn start(app_handle: &AppHandle, subsystem: Subsystem) {
+    match subsystem {
+        Subsystem::Watchers => {
+            disk_space::setup(app_handle);
+            clipboard_watcher::setup(app_handle);
+            port_detection::setup(app_handle);
+            home_watcher::setup(app_handle);
+        }
+        Subsystem::Registry => {
+            state_sync::setup(app_handle);
+            community_contributions::setup(app_handle.state::<AppState>());
+        }
+        Subsystem::Updates => {
+            #[cfg(feature = "enable-updater")]
+            {
+                crate::cli_manager::setup();
+                crate::update_safety::setup(app_handle);
+                crate::updates::setup();
+
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::block_on(async move {
+                    let update_helper = crate::updates::UpdateHelper::new(&app_handle);
+                    if let Ok(releases) = update_helper.fetch_releases().await {
+                        let state = app_handle.state::<AppState>();
+                        let mut releases_state = state.releases.lock().unwrap();
+                        *releases_state = releases;
+                    }
+                });
+            }
+        }
+        Subsystem::Pro => {}
+    }
+}
+
+/// Starts the subsystem unless it has been started already. Returns right away, a `subsystem_ready` event is sent
+/// once it's up.
+pub fn ensure(app_handle: &AppHandle, subsystem: Subsystem) {
+    if !STARTED.lock().unwrap().insert(subsystem) {
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        let task = debug_console::track_task("subsystem start", None);
+        let started = Instant::now();
+        start(&app_handle, subsystem);
+        drop(task);
+        info!("Started {:?} in {:?}", subsystem, started.elapsed());
+
+        READY.lock().unwrap().insert(subsystem);
+        if let Err(err) = app_handle.emit_all(SUBSYSTEM_READY_EVENT, subsystem) {
+            error!("Failed to send subsystem ready event: {}", err);
+        }
+    });
+}
+
+/// Starts whatever hasn't been used yet, a little after the main window is ready.
+pub fn start_deferred(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    thread::spawn(move || {
+        thread::sleep(DEFERRED_START_DELAY);
+        for subsystem in ALL {
+            ensure(&app_handle, subsystem);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_ready_subsystems() -> Vec<Subsystem> {
+    READY.lock().unwrap().iter().copied().collect()
+}
+
+/// Starts the subsystem now rather than with the deferred ones, e.g. when a view needs it.
+#[tauri::command]
+pub fn ensure_subsystem(app_handle: AppHandle, subsystem: Subsystem) {
+    ensure(&app_handle, subsystem);
+}
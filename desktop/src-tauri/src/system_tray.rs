@@ -1,4 +1,7 @@
-use crate::{workspaces::WorkspacesState, AppHandle, AppState, UiMessage};
+use crate::{
+    debug_console, logging, shutdown, status_widget, ui_messages::ShowSettingsMsg,
+    workspaces::WorkspacesState, AppHandle, AppState, UiMessage,
+};
 use log::{error, warn};
 use tauri::{
     CustomMenuItem, Manager, State, SystemTray as TauriSystemTray, SystemTrayEvent, SystemTrayMenu,
@@ -23,6 +26,12 @@ impl SystemTray {
 impl SystemTray {
     const QUIT_ID: &str = "quit";
     const SHOW_DASHBOARD_ID: &str = "show_dashboard";
+    const SHOW_SETTINGS_ID: &str = "show_settings";
+    const TOGGLE_DEBUG_LOGGING_ID: &str = "toggle_debug_logging";
+    const TOGGLE_DEBUG_CONSOLE_ID: &str = "toggle_debug_console";
+    const TOGGLE_STATUS_WIDGET_ID: &str = "toggle_status_widget";
+    #[cfg(feature = "enable-updater")]
+    const ROLLBACK_UPDATE_ID: &str = "rollback_update";
 }
 
 impl SystemTray {
@@ -31,10 +40,15 @@ impl SystemTray {
         submenu_builders: Vec<Box<&dyn ToSystemTraySubmenu>>,
     ) -> SystemTrayMenu {
         let show_dashboard = CustomMenuItem::new(Self::SHOW_DASHBOARD_ID, "Show Dashboard");
+        let show_settings = CustomMenuItem::new(Self::SHOW_SETTINGS_ID, "Settings...");
+        let toggle_status_widget =
+            CustomMenuItem::new(Self::TOGGLE_STATUS_WIDGET_ID, "Toggle Status Widget");
         let quit = CustomMenuItem::new(Self::QUIT_ID, "Quit");
 
         let mut tray_menu = SystemTrayMenu::new()
             .add_item(show_dashboard)
+            .add_item(show_settings)
+            .add_item(toggle_status_widget)
             .add_native_item(SystemTrayMenuItem::Separator);
 
         for builder in submenu_builders {
@@ -42,13 +56,37 @@ impl SystemTray {
             tray_menu = tray_menu.add_submenu(submenu)
         }
 
-        tray_menu = tray_menu
-            .add_native_item(SystemTrayMenuItem::Separator)
-            .add_item(quit);
+        tray_menu = tray_menu.add_native_item(SystemTrayMenuItem::Separator);
+
+        let toggle_debug_logging = CustomMenuItem::new(
+            Self::TOGGLE_DEBUG_LOGGING_ID,
+            Self::debug_logging_title(logging::is_debug_logging_enabled()),
+        );
+        tray_menu = tray_menu.add_item(toggle_debug_logging);
+        let toggle_debug_console =
+            CustomMenuItem::new(Self::TOGGLE_DEBUG_CONSOLE_ID, "Toggle Debug Console");
+        tray_menu = tray_menu.add_item(toggle_debug_console);
+
+        #[cfg(feature = "enable-updater")]
+        {
+            let rollback_update =
+                CustomMenuItem::new(Self::ROLLBACK_UPDATE_ID, "Roll Back Last Update");
+            tray_menu = tray_menu.add_item(rollback_update);
+        }
+
+        tray_menu = tray_menu.add_item(quit);
 
         tray_menu
     }
 
+    fn debug_logging_title(enabled: bool) -> &'static str {
+        if enabled {
+            "Disable Debug Logging"
+        } else {
+            "Enable Debug Logging"
+        }
+    }
+
     pub fn build_tray(
         &self,
         submenu_builders: Vec<Box<&dyn ToSystemTraySubmenu>>,
@@ -62,7 +100,7 @@ impl SystemTray {
         |app, event| match event {
             SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
                 Self::QUIT_ID => {
-                    std::process::exit(0);
+                    shutdown::quit(app);
                 }
                 Self::SHOW_DASHBOARD_ID => {
                     let app_state = app.state::<AppState>();
@@ -74,6 +112,35 @@ impl SystemTray {
                         };
                     });
                 }
+                Self::SHOW_SETTINGS_ID => {
+                    let app_state = app.state::<AppState>();
+                    let msg = UiMessage::ShowSettings(ShowSettingsMsg::new(None));
+
+                    tauri::async_runtime::block_on(async move {
+                        if let Err(err) = app_state.ui_messages.send(msg).await {
+                            error!("Failed to broadcast show settings message: {}", err);
+                        };
+                    });
+                }
+                Self::TOGGLE_STATUS_WIDGET_ID => {
+                    status_widget::toggle_status_widget(app.clone());
+                }
+                Self::TOGGLE_DEBUG_LOGGING_ID => {
+                    let enabled = logging::toggle_debug_logging();
+                    let _ = app
+                        .tray_handle()
+                        .get_item(Self::TOGGLE_DEBUG_LOGGING_ID)
+                        .set_title(Self::debug_logging_title(enabled));
+                }
+                Self::TOGGLE_DEBUG_CONSOLE_ID => {
+                    debug_console::toggle_debug_console(app.clone());
+                }
+                #[cfg(feature = "enable-updater")]
+                Self::ROLLBACK_UPDATE_ID => {
+                    if let Err(err) = crate::rollback::rollback_latest(app) {
+                        error!("Failed to roll back update: {}", err);
+                    }
+                }
                 id => {
                     let app_state = app.state::<AppState>();
                     let mut maybe_handler: Option<_> = None;
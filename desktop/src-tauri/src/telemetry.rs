@@ -0,0 +1,297 @@
+use crate::{debug_console, network, settings::Settings, util::random_id, AppHandle};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use serde_json::{json, Value};
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const SERVICE_NAME: &str = "devpod-desktop";
+const EXPORT_INTERVAL: Duration = Duration::from_secs(5);
+// Spans are dropped once the collector falls this far behind
+const MAX_PENDING_SPANS: usize = 2048;
+// OTLP span kind `INTERNAL`
+const SPAN_KIND_INTERNAL: u8 = 1;
+const STATUS_CODE_OK: u8 = 1;
+const STATUS_CODE_ERROR: u8 = 2;
+
+lazy_static! {
+    static ref PENDING_SPANS: Mutex<Vec<FinishedSpan>> = Mutex::new(vec![]);
+}
+// Spans are only collected while an exporter is configured
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, PartialEq)]
+struct FinishedSpan {
+    trace_id: u128,
+    span_id: u64,
+    parent_span_id: Option<u64>,
+    name: String,
+    start: SystemTime,
+    end: SystemTime,
+    attributes: Vec<(String, String)>,
+    error: bool,
+}
+
+/// A unit of work that is exported to the OTLP collector once it's dropped or ended.
+#[derive(Debug)]
+pub struct Span {
+    inner: Option<FinishedSpan>,
+}
+
+impl Span {
+    pub fn start(name: impl Into<String>) -> Self {
+        Self::start_at(name, SystemTime::now())
+    }
+
+    /// Starts a span in the past, i.e. for operations that are only reported after they finished.
+    pub fn start_at(name: impl Into<String>, start: SystemTime) -> Self {
+        Self::new(
+            name.into(),
+            start,
+            (random_id() as u128) << 64 | random_id() as u128,
+            None,
+        )
+    }
+
+    pub fn child(&self, name: impl Into<String>) -> Self {
+        match &self.inner {
+            Some(parent) => Self::new(
+                name.into(),
+                SystemTime::now(),
+                parent.trace_id,
+                Some(parent.span_id),
+            ),
+            None => Self { inner: None },
+        }
+    }
+
+    fn new(name: String, start: SystemTime, trace_id: u128, parent_span_id: Option<u64>) -> Self {
+        if !ENABLED.load(Ordering::Relaxed) {
+            return Self { inner: None };
+        }
+
+        Self {
+            inner: Some(FinishedSpan {
+                trace_id,
+                span_id: random_id(),
+                parent_span_id,
+                name,
+                start,
+                end: start,
+                attributes: vec![],
+                error: false,
+            }),
+        }
+    }
+
+    pub fn set_attribute(&mut self, key: &str, value: impl ToString) {
+        if let Some(span) = self.inner.as_mut() {
+            span.attributes.push((key.to_string(), value.to_string()));
+        }
+    }
+
+    pub fn set_error(&mut self) {
+        if let Some(span) = self.inner.as_mut() {
+            span.error = true;
+        }
+    }
+
+    pub fn end_at(mut self, end: SystemTime) {
+        if let Some(mut span) = self.inner.take() {
+            span.end = end;
+            push(span);
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(mut span) = self.inner.take() {
+            span.end = SystemTime::now();
+            push(span);
+        }
+    }
+}
+
+fn push(span: FinishedSpan) {
+    let mut pending = PENDING_SPANS.lock().unwrap();
+    if pending.len() < MAX_PENDING_SPANS {
+        pending.push(span);
+    }
+}
+
+/// Number of finished spans waiting to be exported.
+pub fn pending_spans() -> usize {
+    PENDING_SPANS.lock().unwrap().len()
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+/// Builds an OTLP/HTTP JSON `ExportTraceServiceRequest`.
+fn export_request(spans: &[FinishedSpan], app_version: &str) -> Value {
+    let attribute =
+        |key: &str, value: &str| json!({ "key": key, "value": { "stringValue": value } });
+    let spans: Vec<_> = spans
+        .iter()
+        .map(|span| {
+            let parent_span_id = span.parent_span_id.map(|id| format!("{:016x}", id));
+            json!({
+                "traceId": format!("{:032x}", span.trace_id),
+                "spanId": format!("{:016x}", span.span_id),
+                "parentSpanId": parent_span_id.unwrap_or_default(),
+                "name": span.name,
+                "kind": SPAN_KIND_INTERNAL,
+                "startTimeUnixNano": unix_nanos(span.start),
+                "endTimeUnixNano": unix_nanos(span.end),
+                "attributes": span
+                    .attributes
+                    .iter()
+                    .map(|(key, value)| attribute(key, value))
+                    .collect::<Vec<_>>(),
+                "status": { "code": if span.error { STATUS_CODE_ERROR } else { STATUS_CODE_OK } },
+            })
+        })
+        .collect();
+
+    json!({
+        "resourceSpans": [{
+            "resource": { "attributes": [
+                attribute("service.name", SERVICE_NAME),
+                attribute("service.version", app_version),
+                attribute("os.type", std::env::consts::OS),
+            ] },
+            "scopeSpans": [{ "scope": { "name": SERVICE_NAME }, "spans": spans }],
+        }],
+    })
+}
+
+async fn export(
+    client: &reqwest::Client,
+    endpoint: &str,
+    spans: &[FinishedSpan],
+    app_version: &str,
+) -> anyhow::Result<()> {
+    client
+        .post(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .json(&export_request(spans, app_version))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// Exports the spans that haven't been exported yet right away, i.e. before the system shuts down.
+pub async fn flush(app_handle: &AppHandle) {
+    let spans = mem::take(&mut *PENDING_SPANS.lock().unwrap());
+    let endpoint = match Settings::otlp_endpoint(app_handle) {
+        Some(endpoint) if !spans.is_empty() => endpoint,
+        _ => return,
+    };
+
+    let app_version = app_handle.package_info().version.to_string();
+    if let Err(err) = export(&reqwest::Client::new(), &endpoint, &spans, &app_version).await {
+        warn!(
+            "Failed to flush {} spans to {}: {}",
+            spans.len(),
+            endpoint,
+            err
+        );
+    }
+}
+
+/// Periodically sends finished spans to the OTLP endpoint from the settings.
+/// The setting is checked on every run so that the exporter can be turned on or off without restarting.
+pub fn setup(app_handle: &AppHandle) {
+    ENABLED.store(
+        Settings::otlp_endpoint(app_handle).is_some(),
+        Ordering::Relaxed,
+    );
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let client = reqwest::Client::new();
+        let app_version = app_handle.package_info().version.to_string();
+        let mut was_enabled = false;
+
+        loop {
+            tokio::time::sleep(EXPORT_INTERVAL).await;
+
+            let endpoint = Settings::otlp_endpoint(&app_handle);
+            ENABLED.store(endpoint.is_some(), Ordering::Relaxed);
+            if endpoint.is_some() != was_enabled {
+                info!(
+                    "OTLP trace export {}",
+                    if endpoint.is_some() {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    }
+                );
+                was_enabled = endpoint.is_some();
+            }
+
+            // Keep the spans around until we're back online
+            if !network::is_online() {
+                continue;
+            }
+            let spans = mem::take(&mut *PENDING_SPANS.lock().unwrap());
+            let endpoint = match endpoint {
+                Some(endpoint) if !spans.is_empty() => endpoint,
+                _ => continue,
+            };
+            let _task = debug_console::track_task("export spans", Some(endpoint.clone()));
+            if let Err(err) = export(&client, &endpoint, &spans, &app_version).await {
+                warn!(
+                    "Failed to export {} spans to {}: {}",
+                    spans.len(),
+                    endpoint,
+                    err
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_export_request() {
+        let start = UNIX_EPOCH + Duration::from_secs(1);
+        let span = FinishedSpan {
+            trace_id: 1,
+            span_id: 2,
+            parent_span_id: Some(3),
+            name: "workspace start".to_string(),
+            start,
+            end: start + Duration::from_millis(1500),
+            attributes: vec![("workspace.id".to_string(), "my-workspace".to_string())],
+            error: true,
+        };
+
+        let request = export_request(&[span], "0.1.0");
+        let span = &request["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+        assert_eq!(span["traceId"], "00000000000000000000000000000001");
+        assert_eq!(span["parentSpanId"], "0000000000000003");
+        assert_eq!(span["startTimeUnixNano"], "1000000000");
+        assert_eq!(span["endTimeUnixNano"], "2500000000");
+        assert_eq!(
+            span["attributes"][0]["value"]["stringValue"],
+            "my-workspace"
+        );
+        assert_eq!(span["status"]["code"], STATUS_CODE_ERROR);
+    }
+}
@@ -0,0 +1,388 @@
+use crate::{
+    debug_console, environment,
+    process::{self, Tracked},
+    settings::Settings,
+    ssh_config::cli_path,
+    util::random_id,
+    workspaces, AppHandle,
+};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use portable_pty::{native_pty_system, Child, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{Read, Write},
+    sync::{Arc, Mutex},
+    thread,
+};
+use tauri::Manager;
+use thiserror::Error;
+use ts_rs::TS;
+
+const TERMINAL_OUTPUT_EVENT: &str = "terminal_output";
+const TERMINAL_SESSIONS_EVENT: &str = "terminal_sessions";
+const READ_BUFFER_SIZE: usize = 8 * 1024;
+// Every session is an `ssh` process and a reader thread
+const MAX_SESSIONS: usize = 16;
+const DEFAULT_COLS: u16 = 80;
+const DEFAULT_ROWS: u16 = 24;
+
+lazy_static! {
+    static ref SESSIONS: Mutex<HashMap<String, Session>> = Mutex::new(HashMap::new());
+}
+
+/// A shell in a workspace, its output is sent as `terminal_output` events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TerminalSession {
+    id: String,
+    workspace_id: String,
+    cols: u16,
+    rows: u16,
+    /// Set once the shell has exited, the session stays around until it's closed
+    exit_code: Option<u32>,
+    exited: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TerminalOutput {
+    session_id: String,
+    data: String,
+}
+
+struct Session {
+    status: TerminalSession,
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+    scrollback: Arc<Mutex<Scrollback>>,
+    _process: Tracked,
+}
+
+#[derive(Error, Debug)]
+pub enum TerminalError {
+    #[error("terminal {0} not found")]
+    NotFound(String),
+    #[error("too many terminals, close one first")]
+    TooMany,
+    #[error("unable to open terminal")]
+    Open(#[source] anyhow::Error),
+    #[error("unable to write to terminal")]
+    Write(#[source] std::io::Error),
+    #[error("unable to resize terminal")]
+    Resize(#[source] anyhow::Error),
+}
+impl serde::Serialize for TerminalError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// The most recent output, so that a terminal can be redrawn after the view showing it has been closed.
+struct Scrollback {
+    limit: usize,
+    data: VecDeque<u8>,
+}
+
+impl Scrollback {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            data: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.data.extend(bytes);
+        let overflow = self.data.len().saturating_sub(self.limit);
+        self.data.drain(..overflow);
+    }
+
+    fn text(&self) -> String {
+        let (front, back) = self.data.as_slices();
+        let bytes = [front, back].concat();
+
+        // Trimming may have cut a character in half
+        let start = bytes
+            .iter()
+            .position(|byte| (*byte as i8) >= -0x40)
+            .unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[start..]).to_string()
+    }
+}
+
+/// Splits off the longest valid UTF-8 prefix, an incomplete character at the end is kept for the next read.
+fn take_utf8(pending: &mut Vec<u8>) -> String {
+    let valid = match std::str::from_utf8(pending) {
+        Ok(_) => pending.len(),
+        // Invalid bytes in the middle won't become valid, only wait for a truncated character at the end
+        Err(err) if err.error_len().is_none() => err.valid_up_to(),
+        Err(_) => pending.len(),
+    };
+    let rest = pending.split_off(valid);
+
+    String::from_utf8_lossy(&std::mem::replace(pending, rest)).to_string()
+}
+
+fn statuses(sessions: &HashMap<String, Session>) -> Vec<TerminalSession> {
+    let mut statuses: Vec<TerminalSession> = sessions
+        .values()
+        .map(|session| session.status.clone())
+        .collect();
+    statuses.sort_by(|a, b| a.id.cmp(&b.id));
+
+    statuses
+}
+
+fn emit_sessions(app_handle: &AppHandle, sessions: &HashMap<String, Session>) {
+    if let Err(err) = app_handle.emit_all(TERMINAL_SESSIONS_EVENT, statuses(sessions)) {
+        warn!("Failed to send terminal sessions: {}", err);
+    }
+}
+
+fn pty_size(cols: u16, rows: u16) -> PtySize {
+    PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+/// Forwards the output until the shell exits.
+fn read_output(
+    app_handle: AppHandle,
+    session_id: String,
+    mut reader: Box<dyn Read + Send>,
+    scrollback: Arc<Mutex<Scrollback>>,
+) {
+    let _task = debug_console::track_task("terminal", Some(session_id.clone()));
+    let mut buffer = [0; READ_BUFFER_SIZE];
+    let mut pending = Vec::new();
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        scrollback.lock().unwrap().push(&buffer[..read]);
+        pending.extend_from_slice(&buffer[..read]);
+        let data = take_utf8(&mut pending);
+        if data.is_empty() {
+            continue;
+        }
+
+        let output = TerminalOutput {
+            session_id: session_id.clone(),
+            data,
+        };
+        if let Err(err) = app_handle.emit_all(TERMINAL_OUTPUT_EVENT, output) {
+            warn!("Failed to send terminal output: {}", err);
+        }
+    }
+
+    let mut sessions = SESSIONS.lock().unwrap();
+    // Closed by the user
+    let Some(session) = sessions.get_mut(&session_id) else {
+        return;
+    };
+    let exit_code = session.child.wait().ok().map(|status| status.exit_code());
+    info!(
+        "Terminal {} of {} exited with {:?}",
+        session_id, session.status.workspace_id, exit_code
+    );
+    session.status.exited = true;
+    session.status.exit_code = exit_code;
+    emit_sessions(&app_handle, &sessions);
+}
+
+fn spawn(
+    app_handle: &AppHandle,
+    workspace_id: &str,
+    cols: u16,
+    rows: u16,
+) -> anyhow::Result<Session> {
+    let cli = cli_path(app_handle)?;
+    let pair = native_pty_system().openpty(pty_size(cols, rows))?;
+
+    let context =
+        workspaces::context(app_handle, workspace_id).unwrap_or_else(|| "default".to_string());
+    let args = ["ssh", "--context", &context, workspace_id];
+    let mut command = CommandBuilder::new(&cli);
+    command.args(args);
+    for (key, value) in process::environment(app_handle) {
+        command.env(key, value);
+    }
+    for (key, value) in environment::for_workspace(app_handle, workspace_id, None) {
+        command.env(key, value);
+    }
+    let child = pair.slave.spawn_command(command)?;
+    let tracked = process::track(
+        child.process_id(),
+        &cli.file_name().unwrap_or_default().to_string_lossy(),
+        &args,
+        Some(workspace_id),
+    );
+    // Otherwise reading never ends, the shell isn't the only one holding the terminal open
+    drop(pair.slave);
+
+    let reader = pair.master.try_clone_reader()?;
+    let writer = pair.master.take_writer()?;
+    let id = format!("{:016x}", random_id());
+    let scrollback = Arc::new(Mutex::new(Scrollback::new(
+        Settings::terminal_scrollback_kb(app_handle) as usize * 1024,
+    )));
+
+    let app_handle = app_handle.clone();
+    let session_id = id.clone();
+    let output_scrollback = scrollback.clone();
+    thread::spawn(move || read_output(app_handle, session_id, reader, output_scrollback));
+
+    Ok(Session {
+        status: TerminalSession {
+            id,
+            workspace_id: workspace_id.to_string(),
+            cols,
+            rows,
+            exit_code: None,
+            exited: false,
+        },
+        master: pair.master,
+        writer,
+        child,
+        scrollback,
+        _process: tracked,
+    })
+}
+
+/// Kills the shells of all sessions, e.g. when the app exits.
+pub fn close_all() {
+    for (_, mut session) in SESSIONS.lock().unwrap().drain() {
+        let _ = session.child.kill();
+    }
+}
+
+#[tauri::command]
+pub fn list_terminals() -> Vec<TerminalSession> {
+    statuses(&SESSIONS.lock().unwrap())
+}
+
+/// Opens a shell in the workspace, any number of them can run side by side.
+#[tauri::command]
+pub fn open_terminal(
+    app_handle: AppHandle,
+    workspace_id: String,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<TerminalSession, TerminalError> {
+    let mut sessions = SESSIONS.lock().unwrap();
+    if sessions.len() >= MAX_SESSIONS {
+        return Err(TerminalError::TooMany);
+    }
+
+    let session = spawn(
+        &app_handle,
+        &workspace_id,
+        cols.unwrap_or(DEFAULT_COLS),
+        rows.unwrap_or(DEFAULT_ROWS),
+    )
+    .map_err(TerminalError::Open)?;
+    info!("Opened terminal {} in {}", session.status.id, workspace_id);
+    let status = session.status.clone();
+    sessions.insert(status.id.clone(), session);
+    emit_sessions(&app_handle, &sessions);
+
+    Ok(status)
+}
+
+/// Sends keystrokes or pasted text to the shell.
+#[tauri::command]
+pub fn write_terminal(session_id: String, data: String) -> Result<(), TerminalError> {
+    let mut sessions = SESSIONS.lock().unwrap();
+    let session = sessions
+        .get_mut(&session_id)
+        .ok_or(TerminalError::NotFound(session_id))?;
+
+    session
+        .writer
+        .write_all(data.as_bytes())
+        .and_then(|_| session.writer.flush())
+        .map_err(TerminalError::Write)
+}
+
+#[tauri::command]
+pub fn resize_terminal(session_id: String, cols: u16, rows: u16) -> Result<(), TerminalError> {
+    let mut sessions = SESSIONS.lock().unwrap();
+    let session = sessions
+        .get_mut(&session_id)
+        .ok_or(TerminalError::NotFound(session_id))?;
+
+    session
+        .master
+        .resize(pty_size(cols, rows))
+        .map_err(TerminalError::Resize)?;
+    session.status.cols = cols;
+    session.status.rows = rows;
+
+    Ok(())
+}
+
+/// The output kept for the session, up to the scrollback limit of the settings.
+#[tauri::command]
+pub fn get_terminal_scrollback(session_id: String) -> Result<String, TerminalError> {
+    let sessions = SESSIONS.lock().unwrap();
+    let session = sessions
+        .get(&session_id)
+        .ok_or(TerminalError::NotFound(session_id))?;
+    let scrollback = session.scrollback.lock().unwrap().text();
+
+    Ok(scrollback)
+}
+
+/// Kills the shell if it's still running and forgets the session.
+#[tauri::command]
+pub fn close_terminal(app_handle: AppHandle, session_id: String) -> Result<(), TerminalError> {
+    let mut sessions = SESSIONS.lock().unwrap();
+    let mut session = sessions
+        .remove(&session_id)
+        .ok_or(TerminalError::NotFound(session_id))?;
+    if !session.status.exited {
+        let _ = session.child.kill();
+    }
+    emit_sessions(&app_handle, &sessions);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_incomplete_characters() {
+        let mut pending = "ab€".as_bytes().to_vec();
+        pending.pop();
+
+        assert_eq!(take_utf8(&mut pending), "ab");
+        assert_eq!(pending.len(), 2);
+        pending.push(0xac);
+        assert_eq!(take_utf8(&mut pending), "€");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn should_limit_scrollback() {
+        let mut scrollback = Scrollback::new(5);
+        scrollback.push(b"ab");
+        scrollback.push("€€".as_bytes());
+
+        // The first `€` has been cut in half
+        assert_eq!(scrollback.data.len(), 5);
+        assert_eq!(scrollback.text(), "€");
+    }
+}
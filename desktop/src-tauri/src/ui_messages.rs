@@ -1,15 +1,56 @@
 use crate::{
+    autostart,
+    commands::DevpodCommandError,
+    credential_consent::CredentialRequest,
     custom_protocol::{OpenWorkspaceMsg, ParseError},
+    resumable_operations::ResumableKind,
+    startup_profile, subsystems,
     window::WindowHelper,
-    AppHandle,
+    AppHandle, AppState,
 };
-use log::warn;
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use log::{error, warn};
 use serde::Serialize;
-use std::collections::VecDeque;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Mutex,
+};
 use tauri::Manager;
 use tokio::sync::mpsc::Receiver;
 use crate::custom_protocol::ImportWorkspaceMsg;
 
+// Number of messages kept around for diagnostics
+const MESSAGE_HISTORY_SIZE: usize = 100;
+pub const SETTINGS_WINDOW_LABEL: &str = "settings";
+
+lazy_static! {
+    static ref MESSAGE_HISTORY: Mutex<VecDeque<UiMessageRecord>> = Mutex::new(VecDeque::new());
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UiMessageRecord {
+    timestamp: DateTime<Utc>,
+    message: UiMessage,
+}
+
+/// The most recent messages sent to the UI, oldest first.
+pub fn recent_messages() -> Vec<UiMessageRecord> {
+    MESSAGE_HISTORY.lock().unwrap().iter().cloned().collect()
+}
+
+fn record_message(message: &UiMessage) {
+    let mut history = MESSAGE_HISTORY.lock().unwrap();
+    if history.len() >= MESSAGE_HISTORY_SIZE {
+        history.pop_front();
+    }
+    history.push_back(UiMessageRecord {
+        timestamp: Utc::now(),
+        message: message.clone(),
+    });
+}
+
 #[derive(Debug, Clone)]
 pub struct UiMessageHelper {
     app_handle: AppHandle,
@@ -17,6 +58,11 @@ pub struct UiMessageHelper {
     window_helper: WindowHelper,
     message_buffer: VecDeque<UiMessage>,
     is_ready: bool,
+    // Started at login, the main window stays in the tray until opened from there
+    start_minimized: bool,
+    // Windows other than the main one that listen for their own messages, and what's waiting for them to load
+    ready_windows: HashSet<String>,
+    window_buffers: HashMap<String, VecDeque<UiMessage>>,
 }
 
 impl UiMessageHelper {
@@ -27,23 +73,44 @@ impl UiMessageHelper {
             window_helper,
             message_buffer: VecDeque::new(),
             is_ready: false,
+            start_minimized: autostart::launched_minimized(),
+            ready_windows: HashSet::new(),
+            window_buffers: HashMap::new(),
         }
     }
 
     pub async fn listen(&mut self, mut receiver: Receiver<UiMessage>) {
         while let Some(ui_msg) = receiver.recv().await {
+            record_message(&ui_msg);
             match ui_msg {
                 UiMessage::Ready => {
                     self.is_ready = true;
+                    subsystems::start_deferred(&self.app_handle);
+                    startup_profile::first_render(&self.app_handle);
 
-                    self.app_handle.get_window("main").map(|w| w.show());
+                    if self.start_minimized {
+                        self.start_minimized = false;
+                    } else {
+                        self.app_handle.get_window("main").map(|w| w.show());
+                    }
                     while let Some(msg) = self.message_buffer.pop_front() {
-                        let emit_result = self.app_handle.emit_all("event", msg);
+                        let emit_result = self.app_handle.emit_to("main", "event", msg);
                         if let Err(err) = emit_result {
                             warn!("Error sending message: {}", err);
                         }
                     }
                 }
+                UiMessage::WindowReady(WindowReadyMsg { label }) => {
+                    if let Some(window) = self.app_handle.get_window(&label) {
+                        let buffer = self.window_buffers.remove(&label).unwrap_or_default();
+                        for msg in buffer {
+                            if let Err(err) = window.emit("event", msg) {
+                                warn!("Error sending message to {}: {}", label, err);
+                            }
+                        }
+                    }
+                    self.ready_windows.insert(label);
+                }
                 UiMessage::ExitRequested => {
                     self.is_ready = false;
                 }
@@ -54,15 +121,52 @@ impl UiMessageHelper {
     }
 
     fn handle_msg(&mut self, msg: UiMessage) {
+        if let Some(label) = msg.window() {
+            self.handle_window_msg(label, msg);
+            return;
+        }
+
         if self.is_ready {
             self.app_handle.get_window("main").map(|w| w.show());
-            let _ = self.app_handle.emit_all("event", msg);
+            let _ = self.app_handle.emit_to("main", "event", msg);
         } else {
             // recreate window
             self.message_buffer.push_back(msg);
             let _ = self.window_helper.new_main(self.app_name.clone());
         }
     }
+
+    /// Sends the message to its window once it has loaded, opening the window if necessary.
+    fn handle_window_msg(&mut self, label: &'static str, msg: UiMessage) {
+        match self.app_handle.get_window(label) {
+            Some(window) if self.ready_windows.contains(label) => {
+                let _ = window.set_focus();
+                if let Err(err) = window.emit("event", msg) {
+                    warn!("Error sending message to {}: {}", label, err);
+                }
+            }
+            // Still loading, it picks the message up once it's ready
+            Some(_) => self
+                .window_buffers
+                .entry(label.to_string())
+                .or_default()
+                .push_back(msg),
+            None => {
+                self.ready_windows.remove(label);
+                self.window_buffers
+                    .entry(label.to_string())
+                    .or_default()
+                    .push_back(msg);
+                let result = match label {
+                    SETTINGS_WINDOW_LABEL => self.window_helper.new_settings_window(),
+                    _ => Ok(()),
+                };
+                if let Err(err) = result {
+                    error!("{}", err);
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -76,6 +180,26 @@ pub enum UiMessage {
     OpenWorkspace(OpenWorkspaceMsg),
     ImportWorkspace(ImportWorkspaceMsg),
     CommandFailed(ParseError),
+    VerificationFailed(VerificationFailedMsg),
+    CliTampered(CliTamperedMsg),
+    CredentialRequested(CredentialRequest),
+    ShowDiskCleanup,
+    ClipboardUrl(ClipboardUrlMsg),
+    PortDetected(PortDetectedMsg),
+    OperationInterrupted(OperationInterruptedMsg),
+    WindowReady(WindowReadyMsg),
+    ShowSettings(ShowSettingsMsg),
+}
+
+impl UiMessage {
+    /// The window the message is meant for if it's not the main one, e.g. so that setting up a provider isn't
+    /// interrupted by the main window navigating elsewhere.
+    fn window(&self) -> Option<&'static str> {
+        match self {
+            UiMessage::ShowSettings(_) => Some(SETTINGS_WINDOW_LABEL),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -95,6 +219,125 @@ impl ShowToastMsg {
     }
 }
 
+#[derive(Debug, Serialize, Clone)]
+pub struct VerificationFailedMsg {
+    artifact: String,
+    reason: String,
+}
+
+impl VerificationFailedMsg {
+    pub fn new(artifact: String, reason: String) -> Self {
+        Self { artifact, reason }
+    }
+}
+
+/// The installed CLI doesn't match the binary the app installed and won't be run. `can_repair` if the app is able to
+/// download it again, otherwise DevPod has to be reinstalled.
+#[derive(Debug, Serialize, Clone)]
+pub struct CliTamperedMsg {
+    path: String,
+    can_repair: bool,
+}
+
+impl CliTamperedMsg {
+    pub fn new(path: String, can_repair: bool) -> Self {
+        Self { path, can_repair }
+    }
+}
+
+/// Git URL the user copied, `url` creates a workspace from it.
+#[derive(Debug, Serialize, Clone)]
+pub struct ClipboardUrlMsg {
+    source: String,
+    url: String,
+}
+
+impl ClipboardUrlMsg {
+    pub fn new(source: String, url: String) -> Self {
+        Self { source, url }
+    }
+}
+
+/// Port that started listening in a workspace and hasn't been decided on yet.
+#[derive(Debug, Serialize, Clone)]
+pub struct PortDetectedMsg {
+    workspace_id: String,
+    port: u16,
+}
+
+impl PortDetectedMsg {
+    pub fn new(workspace_id: String, port: u16) -> Self {
+        Self { workspace_id, port }
+    }
+}
+
+/// Long running operation the app was restarted in the middle of, the user decides whether to resume it.
+#[derive(Debug, Serialize, Clone)]
+pub struct OperationInterruptedMsg {
+    operation_id: String,
+    kind: ResumableKind,
+    workspace_id: String,
+    /// As reported by the provider, e.g. `Running` or `NotFound`
+    workspace_state: Option<String>,
+    can_roll_back: bool,
+}
+
+impl OperationInterruptedMsg {
+    pub fn new(
+        operation_id: String,
+        kind: ResumableKind,
+        workspace_id: String,
+        workspace_state: Option<String>,
+        can_roll_back: bool,
+    ) -> Self {
+        Self {
+            operation_id,
+            kind,
+            workspace_id,
+            workspace_state,
+            can_roll_back,
+        }
+    }
+}
+
+/// Sent by windows other than the main one once they're listening for messages.
+#[derive(Debug, Serialize, Clone)]
+pub struct WindowReadyMsg {
+    label: String,
+}
+
+impl WindowReadyMsg {
+    pub fn new(label: String) -> Self {
+        Self { label }
+    }
+}
+
+/// Opens the settings window, at the configuration of `provider` if set.
+#[derive(Debug, Serialize, Clone)]
+pub struct ShowSettingsMsg {
+    provider: Option<String>,
+}
+
+impl ShowSettingsMsg {
+    pub fn new(provider: Option<String>) -> Self {
+        Self { provider }
+    }
+}
+
+/// Shows the settings in a window of their own, so that the main window can be used while they're open.
+#[tauri::command]
+pub async fn show_settings(
+    state: tauri::State<'_, AppState>,
+    provider: Option<String>,
+) -> Result<(), DevpodCommandError> {
+    let msg = UiMessage::ShowSettings(ShowSettingsMsg::new(provider));
+    if let Err(err) = state.ui_messages.send(msg).await {
+        error!("Failed to broadcast show settings message: {}", err);
+    }
+
+    Ok(())
+}
+
 // WARN: Needs to match the UI's toast status
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "lowercase")]
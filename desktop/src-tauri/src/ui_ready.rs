@@ -1,10 +1,17 @@
-use crate::{commands::DevpodCommandError, AppState, UiMessage};
+use crate::{commands::DevpodCommandError, ui_messages::WindowReadyMsg, AppState, UiMessage};
 use log::error;
 
 // This command is expected to be invoked exactly once per window
 #[tauri::command]
-pub async fn ui_ready(state: tauri::State<'_, AppState>) -> Result<(), DevpodCommandError> {
-    if let Err(err) = state.ui_messages.send(UiMessage::Ready).await {
+pub async fn ui_ready(
+    state: tauri::State<'_, AppState>,
+    window: tauri::Window,
+) -> Result<(), DevpodCommandError> {
+    let msg = match window.label() {
+        "main" => UiMessage::Ready,
+        label => UiMessage::WindowReady(WindowReadyMsg::new(label.to_string())),
+    };
+    if let Err(err) = state.ui_messages.send(msg).await {
         error!("Failed to broadcast UI ready message: {:?}", err);
     }
 
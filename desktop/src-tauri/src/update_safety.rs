@@ -0,0 +1,218 @@
+use crate::{
+    audit_log::{self, AuditCategory, AuditInitiator},
+    cli_manager::CliManager,
+    rollback,
+    settings::{Settings, UpdateWhileBusy},
+    updates, AppHandle, AppState,
+};
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+use std::{collections::HashSet, sync::Mutex, time::Duration};
+use tauri::Manager;
+use thiserror::Error;
+
+const BUSY_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+// Give up waiting eventually, a build can get stuck indefinitely
+const MAX_BUSY_WAIT: Duration = Duration::from_secs(60 * 30);
+
+lazy_static! {
+    // Updates that were requested while workspace actions were running, installed once they finish
+    static ref QUEUED_UPDATES: Mutex<HashSet<UpdateTarget>> = Mutex::new(HashSet::new());
+}
+
+#[derive(Error, Debug)]
+pub enum UpdateSafetyError {
+    #[error("{0} workspace action(s) are still running")]
+    Busy(usize),
+    #[error("update will be installed once all workspace actions are done")]
+    Queued,
+    #[error("cli {latest} is not compatible with the agents of existing workspaces, installed cli is {installed}")]
+    Incompatible { installed: String, latest: String },
+}
+impl serde::Serialize for UpdateSafetyError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UpdateTarget {
+    App,
+    Cli,
+}
+
+fn active_actions(app_handle: &AppHandle) -> usize {
+    app_handle
+        .state::<AppState>()
+        .active_actions
+        .lock()
+        .unwrap()
+        .len()
+}
+
+/// Fails if workspace actions are running, regardless of the configured policy.
+pub fn check_idle(app_handle: &AppHandle) -> Result<(), UpdateSafetyError> {
+    match active_actions(app_handle) {
+        0 => Ok(()),
+        count => Err(UpdateSafetyError::Busy(count)),
+    }
+}
+
+/// Makes sure no workspace action is running before `target` gets replaced. Depending on the `updateWhileBusy`
+/// setting this waits for running actions to finish, fails so that the UI can ask the user, or queues the update.
+pub async fn ensure_idle(
+    app_handle: &AppHandle,
+    target: UpdateTarget,
+) -> Result<(), UpdateSafetyError> {
+    if check_idle(app_handle).is_ok() {
+        return Ok(());
+    }
+
+    match Settings::update_while_busy(app_handle) {
+        UpdateWhileBusy::Wait => {
+            info!(
+                "Waiting for workspace actions to finish before updating {:?}",
+                target
+            );
+            let mut waited = Duration::ZERO;
+            while waited < MAX_BUSY_WAIT {
+                tokio::time::sleep(BUSY_CHECK_INTERVAL).await;
+                waited += BUSY_CHECK_INTERVAL;
+                if check_idle(app_handle).is_ok() {
+                    return Ok(());
+                }
+            }
+
+            check_idle(app_handle)
+        }
+        UpdateWhileBusy::Prompt => check_idle(app_handle),
+        UpdateWhileBusy::Queue => {
+            info!(
+                "Queueing {:?} update until workspace actions are done",
+                target
+            );
+            QUEUED_UPDATES.lock().unwrap().insert(target);
+
+            Err(UpdateSafetyError::Queued)
+        }
+    }
+}
+
+/// Agents running in existing workspaces were injected by the installed cli. They only understand clis with the same
+/// major version, or the same minor version before 1.0.
+pub fn check_protocol_compatibility(
+    app_handle: &AppHandle,
+    installed: Option<&str>,
+    latest: &str,
+) -> Result<(), UpdateSafetyError> {
+    let installed = match installed {
+        Some(installed) => installed,
+        None => return Ok(()),
+    };
+    let state = app_handle.state::<AppState>();
+    if state.workspaces.lock().unwrap().is_empty() {
+        return Ok(());
+    }
+
+    if is_protocol_compatible(installed, latest) {
+        return Ok(());
+    }
+
+    Err(UpdateSafetyError::Incompatible {
+        installed: installed.to_string(),
+        latest: latest.to_string(),
+    })
+}
+
+fn is_protocol_compatible(installed: &str, latest: &str) -> bool {
+    match (
+        semver::Version::parse(installed),
+        semver::Version::parse(latest),
+    ) {
+        (Ok(installed), Ok(latest)) if installed.major == 0 && latest.major == 0 => {
+            installed.minor == latest.minor
+        }
+        (Ok(installed), Ok(latest)) => installed.major == latest.major,
+        // can't tell, don't get in the way
+        _ => true,
+    }
+}
+
+async fn install_queued(app_handle: &AppHandle) {
+    let queued: Vec<_> = {
+        let mut queued = QUEUED_UPDATES.lock().unwrap();
+        if queued.is_empty() || check_idle(app_handle).is_err() {
+            return;
+        }
+
+        queued.drain().collect()
+    };
+
+    for target in queued {
+        info!("Installing queued {:?} update", target);
+        match target {
+            UpdateTarget::Cli => match CliManager::new(app_handle).update(false, false).await {
+                Ok(()) => audit_log::record(
+                    AuditInitiator::Scheduler,
+                    AuditCategory::Install,
+                    "update cli",
+                    None,
+                ),
+                Err(err) => error!("Failed to install queued cli update: {}", err),
+            },
+            UpdateTarget::App => {
+                if let Err(err) = updates::check_updater_pins(app_handle).await {
+                    error!("Failed to install queued app update: {}", err);
+                    continue;
+                }
+                let update = match tauri::updater::builder(app_handle.clone()).check().await {
+                    Ok(update) if update.is_update_available() => update,
+                    Ok(_) => continue,
+                    Err(err) => {
+                        error!("Failed to check for queued app update: {}", err);
+                        continue;
+                    }
+                };
+                if let Err(err) = rollback::backup_app(app_handle) {
+                    warn!("Failed to back up app before updating: {}", err);
+                }
+                match update.download_and_install().await {
+                    Ok(()) => audit_log::record(
+                        AuditInitiator::Scheduler,
+                        AuditCategory::Install,
+                        "update app",
+                        Some(update.latest_version()),
+                    ),
+                    Err(err) => error!("Failed to install queued app update: {}", err),
+                }
+            }
+        }
+    }
+}
+
+pub fn setup(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            install_queued(&app_handle).await;
+            tokio::time::sleep(BUSY_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_check_protocol_compatibility() {
+        assert!(is_protocol_compatible("0.3.6", "0.3.7"));
+        assert!(!is_protocol_compatible("0.3.6", "0.4.0"));
+        assert!(is_protocol_compatible("1.2.0", "1.5.1"));
+        assert!(!is_protocol_compatible("1.2.0", "2.0.0"));
+        assert!(is_protocol_compatible("0.3.6", "dev"));
+    }
+}
@@ -1,20 +1,46 @@
 use std::collections::HashMap;
 
-use crate::{settings::Settings, window::WindowHelper, AppHandle, AppState};
+use crate::{
+    audit_log::{self, AuditCategory, AuditInitiator},
+    cert_pinning::{self, PinError},
+    network,
+    notifications::{self, NotificationEvent},
+    power::{self, SleepGuard},
+    rollback,
+    scheduler::{self, Schedule},
+    settings::{ReleaseChannel, Settings, UpdateSchedule},
+    subsystems::{self, Subsystem},
+    telemetry::Span,
+    update_safety::{self, UpdateTarget},
+    window::WindowHelper,
+    AppHandle, AppState,
+};
 use anyhow::Context;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Local, Utc, Weekday};
 use lazy_static::lazy_static;
 use log::{debug, error, warn};
 use regex::Regex;
-use reqwest::{Client, Method};
+use reqwest::{
+    header::{HeaderMap, LINK},
+    Method,
+};
 use serde::{Deserialize, Serialize};
-use tauri::{api::notification::Notification, Manager};
+use tauri::Manager;
 use thiserror::Error;
 use tokio::fs::File;
 use ts_rs::TS;
 
 const UPDATE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 10);
+const UPDATE_CHECK_JITTER: std::time::Duration = std::time::Duration::from_secs(60);
 const RELEASES_URL: &str = "https://api.github.com/repos/loft-sh/devpod/releases";
+const FIRST_RELEASES_PAGE: &[(&str, u32)] = &[("per_page", 50), ("page", 1)];
+// Marker in the app cache dir holding the time until which update notifications and installs are postponed
+const DEFERRED_UNTIL_FILE_NAME: &str = "update_deferred_until";
+
+lazy_static! {
+    // Held from the start of the download until the update is installed or failed
+    static ref UPDATE_SLEEP_GUARD: std::sync::Mutex<Option<SleepGuard>> = std::sync::Mutex::new(None);
+}
 
 #[derive(Error, Debug)]
 pub enum UpdateError {
@@ -24,6 +50,10 @@ pub enum UpdateError {
     CheckUpdate(#[from] tauri::updater::Error),
     #[error("failed to fetch releases {0}")]
     FetchRelease(#[from] anyhow::Error),
+    #[error("failed to defer update {0}")]
+    Defer(#[source] std::io::Error),
+    #[error("{0}")]
+    CertificatePin(#[from] PinError),
 }
 impl serde::Serialize for UpdateError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -74,6 +104,27 @@ impl Release {
         }
         PRE_REPL_REGEX.replace_all(&self.tag_name, "").to_string()
     }
+
+    pub fn is_nightly(&self) -> bool {
+        lazy_static! {
+            static ref NIGHTLY_REGEX: Regex = Regex::new(r"^.*-(nightly|next).*$").unwrap();
+        }
+        NIGHTLY_REGEX.is_match(&self.tag_name)
+    }
+
+    pub fn channel(&self) -> ReleaseChannel {
+        if self.is_nightly() {
+            ReleaseChannel::Nightly
+        } else if self.prerelease || self.is_pre() {
+            ReleaseChannel::Beta
+        } else {
+            ReleaseChannel::Stable
+        }
+    }
+
+    pub fn version(&self) -> Option<semver::Version> {
+        semver::Version::parse(self.tag_name.trim_start_matches('v')).ok()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
@@ -134,11 +185,41 @@ pub async fn get_pending_update(state: tauri::State<'_, AppState>) -> Result<Rel
     release.clone().ok_or(())
 }
 
+#[tauri::command]
+pub async fn get_update_channel(app_handle: AppHandle) -> Result<ReleaseChannel, ()> {
+    Ok(Settings::update_channel(&app_handle))
+}
+
+/// Postpones update notifications and automatic installs by `days`.
+#[tauri::command]
+pub async fn defer_update(app_handle: AppHandle, days: u32) -> Result<(), UpdateError> {
+    let deferred_until = Utc::now() + chrono::Duration::days(days.into());
+    if let Some(mut target) = app_handle.path_resolver().app_cache_dir() {
+        tokio::fs::create_dir_all(&target)
+            .await
+            .map_err(UpdateError::Defer)?;
+        target.push(DEFERRED_UNTIL_FILE_NAME);
+        tokio::fs::write(target, deferred_until.to_rfc3339())
+            .await
+            .map_err(UpdateError::Defer)?;
+    }
+    debug!("Deferred updates until {}", deferred_until);
+
+    let state = app_handle.state::<AppState>();
+    *state.pending_update.lock().unwrap() = None;
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn check_updates(app_handle: AppHandle) -> Result<bool, UpdateError> {
+    subsystems::ensure(&app_handle, Subsystem::Updates);
+    let mut span = Span::start("update check");
+    check_updater_pins(&app_handle).await?;
     match tauri::updater::builder(app_handle).check().await {
         Ok(update) => {
             let update_available = update.is_update_available();
+            span.set_attribute("update.available", update_available);
             debug!(
                 "Update check completed, update available: {}",
                 update_available
@@ -148,12 +229,35 @@ pub async fn check_updates(app_handle: AppHandle) -> Result<bool, UpdateError> {
         }
         Err(e) => {
             error!("Failed to get update: {}", e);
+            span.set_error();
 
             return Err(UpdateError::CheckUpdate(e));
         }
     }
 }
 
+/// The updater makes its own connections, its endpoints are checked against the certificate pins before each use. The
+/// updater's connections themselves aren't pinned, see `cert_pinning::check`.
+pub async fn check_updater_pins(app_handle: &AppHandle) -> Result<(), PinError> {
+    let endpoints = app_handle.config().tauri.updater.endpoints.clone();
+    for endpoint in endpoints.unwrap_or_default() {
+        cert_pinning::check(&endpoint.to_string()).await?;
+    }
+
+    Ok(())
+}
+
+pub fn setup() {
+    scheduler::Task::new(
+        "app update check",
+        Schedule::every(UPDATE_POLL_INTERVAL),
+        |app_handle| tauri::async_runtime::block_on(UpdateHelper::new(app_handle).poll()),
+    )
+    .jitter(UPDATE_CHECK_JITTER)
+    .defer_on_battery()
+    .register();
+}
+
 #[derive(Clone, Debug)]
 pub struct UpdateHelper<'a> {
     app_handle: &'a AppHandle,
@@ -167,27 +271,57 @@ impl<'a> UpdateHelper<'a> {
     }
 
     pub async fn poll(&self) {
-        {
-            loop {
-                // check if we have updated the app recently
-                // if so, show changelog in app
-
-                let app_handle = self.app_handle.clone();
-                if let Ok(update) = tauri::updater::builder(app_handle).check().await {
-                    if update.is_update_available() {
-                        let new_version = update.latest_version();
-                        let update_helper = UpdateHelper::new(&self.app_handle);
-                        if let Err(e) = update_helper.update_app_releases(new_version).await {
-                            error!("Failed to update app releases: {}", e);
-                        }
-                        if Settings::auto_update_enabled(&self.app_handle) {
-                            if let Err(err) = update.download_and_install().await {
-                                error!("Failed to download and install update: {}", err);
+        // update checks are paused while offline
+        network::wait_until_online().await;
+
+        // check if we have updated the app recently
+        // if so, show changelog in app
+
+        // the updater endpoint only serves stable releases, prereleases from other channels are announced separately
+        let channel = Settings::update_channel(self.app_handle);
+        if channel != ReleaseChannel::Stable {
+            if let Err(err) = self.check_channel_update(channel).await {
+                error!("Failed to check {:?} channel for updates: {}", channel, err);
+            }
+        }
+
+        if let Err(err) = check_updater_pins(self.app_handle).await {
+            error!("Skipping update check: {}", err);
+            return;
+        }
+        let app_handle = self.app_handle.clone();
+        if let Ok(update) = tauri::updater::builder(app_handle).check().await {
+            if update.is_update_available() {
+                let new_version = update.latest_version();
+                let update_helper = UpdateHelper::new(&self.app_handle);
+                if let Err(e) = update_helper.update_app_releases(new_version).await {
+                    error!("Failed to update app releases: {}", e);
+                }
+                if self.is_deferred() {
+                    debug!("Skipping update to {}, updates are deferred", new_version);
+                } else if self.may_install() {
+                    match update_safety::ensure_idle(self.app_handle, UpdateTarget::App).await {
+                        Ok(()) => {
+                            if let Err(err) = rollback::backup_app(self.app_handle) {
+                                warn!("Failed to back up app before updating: {}", err);
+                            }
+                            match update.download_and_install().await {
+                                Ok(()) => audit_log::record(
+                                    AuditInitiator::Scheduler,
+                                    AuditCategory::Install,
+                                    "update app",
+                                    Some(new_version),
+                                ),
+                                Err(err) => {
+                                    error!("Failed to download and install update: {}", err)
+                                }
                             }
                         }
+                        Err(err) => {
+                            debug!("Holding back update to {}: {}", new_version, err)
+                        }
                     }
                 }
-                tokio::time::sleep(UPDATE_POLL_INTERVAL).await;
             }
         }
     }
@@ -210,63 +344,150 @@ impl<'a> UpdateHelper<'a> {
             .clone())
     }
 
-    pub async fn fetch_releases(&self) -> anyhow::Result<Releases> {
-        let per_page = 50;
-        let page = 1;
-
-        let client = Client::builder().user_agent("loft-sh/devpod").build()?;
-        let request = client
-            .request(Method::GET, RELEASES_URL)
-            .query(&[("per_page", per_page), ("page", page)])
-            .header("Accept", "application/vnd.github+json")
-            .header("X-GitHub-Api-Version", "2022-11-28");
-
-        let releases = request
-            .send()
-            .await?
-            .json::<Vec<Release>>()
-            .await
-            .with_context(|| format!("Fetch releases from {}", RELEASES_URL))?;
+    /// Returns the newest release available in `channel`. Channels include all releases of more stable channels.
+    pub async fn latest_release(&self, channel: ReleaseChannel) -> anyhow::Result<Release> {
+        let releases = self.fetch_releases().await?;
+        let state = self.app_handle.state::<AppState>();
+        *state.releases.lock().unwrap() = releases.clone();
 
-        let releases = &releases
+        releases
             .into_iter()
-            .filter(|release| !release.draft)
-            .map(|release| (release.tag_name.clone(), release))
-            .collect::<HashMap<String, Release>>();
+            .filter(|release| release.channel() <= channel)
+            .filter_map(|release| Some((release.version()?, release)))
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, release)| release)
+            .with_context(|| format!("No release found for channel {:?}", channel))
+    }
 
-        let mut releases = releases
-            .into_iter()
-            .filter_map(|(_, release)| {
-                if release.prerelease || release.is_pre() {
-                    let stable_tag_name = release.trim_pre();
-                    if releases.get(&stable_tag_name).is_some() {
-                        return None;
-                    }
-                }
+    /// Whether the user postponed updates and the deferral has not expired yet.
+    fn is_deferred(&self) -> bool {
+        let mut target = match self.app_handle.path_resolver().app_cache_dir() {
+            Some(target) => target,
+            None => return false,
+        };
+        target.push(DEFERRED_UNTIL_FILE_NAME);
+
+        std::fs::read_to_string(target)
+            .ok()
+            .and_then(|deferred_until| DateTime::parse_from_rfc3339(deferred_until.trim()).ok())
+            .map_or(false, |deferred_until| deferred_until > Utc::now())
+    }
 
-                Some(release.clone())
-            })
-            .collect::<Vec<Release>>();
-        releases.sort_by(|a, b| b.tag_name.cmp(&a.tag_name));
+    /// Whether an update may be installed without asking right now. Running workspace actions are handled by
+    /// `update_safety` before actually installing.
+    fn may_install(&self) -> bool {
+        if !Settings::auto_update_enabled(self.app_handle) {
+            return false;
+        }
+
+        match Settings::update_schedule(self.app_handle) {
+            UpdateSchedule::Anytime => true,
+            UpdateSchedule::Weekends => {
+                matches!(Local::now().weekday(), Weekday::Sat | Weekday::Sun)
+            }
+            UpdateSchedule::NotifyOnly => false,
+        }
+    }
+
+    async fn check_channel_update(&self, channel: ReleaseChannel) -> anyhow::Result<()> {
+        if self.is_deferred() {
+            return Ok(());
+        }
+
+        let release = self.latest_release(channel).await?;
+        let current_version = &self.app_handle.package_info().version;
+        // Never offer a downgrade here, switching back to a more stable channel keeps the current version
+        // until the channel catches up
+        if release
+            .version()
+            .map_or(true, |version| &version <= current_version)
+        {
+            return Ok(());
+        }
+
+        let state = self.app_handle.state::<AppState>();
+        let already_pending = state
+            .pending_update
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(false, |pending| pending.tag_name == release.tag_name);
+        if already_pending {
+            return Ok(());
+        }
+
+        if let Err(err) = self.notify_update_available(&release).await {
+            warn!("Failed to send update notification: {}", err);
+        }
+        *state.pending_update.lock().unwrap() = Some(release);
+
+        Ok(())
+    }
+
+    /// The latest releases, only the first page of them.
+    pub async fn fetch_releases(&self) -> anyhow::Result<Releases> {
+        let _span = Span::start("fetch releases");
+        let (releases, _) = fetch_release_page(RELEASES_URL, FIRST_RELEASES_PAGE).await?;
+
+        Ok(select_releases(releases))
+    }
+
+    /// All releases down to `version`, following the pages until one of them reaches it.
+    pub async fn fetch_releases_since(
+        &self,
+        version: &semver::Version,
+    ) -> anyhow::Result<Releases> {
+        let _span = Span::start("fetch releases");
+        let mut releases = vec![];
+        let mut page = fetch_release_page(RELEASES_URL, FIRST_RELEASES_PAGE).await?;
+        loop {
+            let (page_releases, next_page) = page;
+            // Newest first, so the following pages only contain older releases
+            let reached = page_releases.iter().any(|release| {
+                semver::Version::parse(release.tag_name.trim_start_matches('v'))
+                    .is_ok_and(|release_version| &release_version <= version)
+            });
+            releases.extend(page_releases);
+
+            match next_page {
+                Some(next_page) if !reached => {
+                    page = fetch_release_page(&next_page, &[]).await?;
+                }
+                _ => break,
+            }
+        }
 
-        Ok(releases)
+        Ok(select_releases(releases))
     }
 
-    pub async fn handle_event(&self, updater_event: tauri::UpdaterEvent, app_identifier: &str) {
+    pub async fn handle_event(&self, updater_event: tauri::UpdaterEvent) {
+        let event = match &updater_event {
+            tauri::UpdaterEvent::UpdateAvailable { .. } => "update_available",
+            tauri::UpdaterEvent::Pending => "pending",
+            // Emitted for every chunk, too noisy to trace
+            tauri::UpdaterEvent::DownloadProgress { .. } => return,
+            tauri::UpdaterEvent::Downloaded => "downloaded",
+            tauri::UpdaterEvent::Updated => "updated",
+            tauri::UpdaterEvent::AlreadyUpToDate => "already_up_to_date",
+            tauri::UpdaterEvent::Error(_) => "error",
+        };
+        let mut span = Span::start("updater event");
+        span.set_attribute("updater.event", event);
+        if let tauri::UpdaterEvent::Error(_) = &updater_event {
+            span.set_error();
+        }
         match updater_event {
             tauri::UpdaterEvent::UpdateAvailable { version, .. } => {
                 let state = self.app_handle.state::<AppState>();
                 let update_installed_state = *state.update_installed.lock().unwrap();
 
-                if Settings::auto_update_enabled(self.app_handle) || update_installed_state {
+                if update_installed_state || self.is_deferred() || self.may_install() {
                     return;
                 }
 
                 match self.update_app_releases(&version).await {
                     Ok(release) => {
-                        if let Err(err) =
-                            self.notify_update_available(&release, app_identifier).await
-                        {
+                        if let Err(err) = self.notify_update_available(&release).await {
                             warn!("Failed to send update notification: {}", err);
                         }
 
@@ -283,6 +504,8 @@ impl<'a> UpdateHelper<'a> {
             // Emitted when the download is about to be started.
             tauri::UpdaterEvent::Pending => {
                 debug!("update is pending");
+                *UPDATE_SLEEP_GUARD.lock().unwrap() =
+                    Some(power::prevent_sleep("installing app update"));
             }
             tauri::UpdaterEvent::DownloadProgress { .. } => {}
             // Emtted when the download has finished and the update is about to be installed.
@@ -291,6 +514,7 @@ impl<'a> UpdateHelper<'a> {
             }
             // Emitted when the update was installed. You can then ask to restart the app.
             tauri::UpdaterEvent::Updated => {
+                *UPDATE_SLEEP_GUARD.lock().unwrap() = None;
                 let window_helper = WindowHelper::new(self.app_handle.clone());
                 let _ = window_helper.new_update_ready_window();
 
@@ -306,27 +530,19 @@ impl<'a> UpdateHelper<'a> {
             // Emitted when the app already has the latest version installed and an update is not needed.
             tauri::UpdaterEvent::AlreadyUpToDate => {
                 debug!("app is already up to date");
+                *UPDATE_SLEEP_GUARD.lock().unwrap() = None;
             }
             // Emitted when there is an error with the updater. We suggest to listen to this event even if the default dialog is enabled.
             tauri::UpdaterEvent::Error(error) => {
                 error!("failed to update: {}", error);
+                *UPDATE_SLEEP_GUARD.lock().unwrap() = None;
             }
         }
     }
 }
 
 impl UpdateHelper<'_> {
-    fn build_update_notification(&self, release: &Release, app_identifier: &str) -> Notification {
-        Notification::new(app_identifier)
-            .title("Update available")
-            .body(&format!("Version {} is available", release.tag_name))
-    }
-
-    async fn notify_update_available(
-        &self,
-        release: &Release,
-        app_identifier: &str,
-    ) -> anyhow::Result<()> {
+    async fn notify_update_available(&self, release: &Release) -> anyhow::Result<()> {
         if let Some(mut target) = self.app_handle.path_resolver().app_cache_dir() {
             target.push(format!("update_{}", release.tag_name.clone()));
 
@@ -336,10 +552,100 @@ impl UpdateHelper<'_> {
             let _ = File::create(target).await?;
         }
 
-        let _ = self
-            .build_update_notification(release, app_identifier)
-            .show()?;
+        notifications::notify(
+            self.app_handle,
+            NotificationEvent::UpdateAvailable,
+            "Update available",
+            &format!("Version {} is available", release.tag_name),
+            vec![],
+        )
+    }
+}
 
-        Ok(())
+/// Fetches a page of releases and the URL of the next one, if there is one.
+async fn fetch_release_page(
+    url: &str,
+    query: &[(&str, u32)],
+) -> anyhow::Result<(Releases, Option<String>)> {
+    let client = cert_pinning::client()?;
+    let request = client
+        .request(Method::GET, url)
+        .query(query)
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", "2022-11-28");
+
+    let response = cert_pinning::send(request).await?;
+    let next_page = next_page_url(response.headers());
+    let releases = response
+        .json::<Vec<Release>>()
+        .await
+        .with_context(|| format!("Fetch releases from {}", url))?;
+
+    Ok((releases, next_page))
+}
+
+// GitHub paginates with a `Link` header, i.e. `<https://api.github.com/...?page=2>; rel="next", <...>; rel="last"`
+fn next_page_url(headers: &HeaderMap) -> Option<String> {
+    lazy_static! {
+        static ref NEXT_LINK_REGEX: Regex = Regex::new(r#"<([^>]+)>;\s*rel="next""#).unwrap();
+    }
+    let link = headers.get(LINK)?.to_str().ok()?;
+
+    NEXT_LINK_REGEX
+        .captures(link)
+        .map(|captures| captures[1].to_string())
+}
+
+/// Drops drafts and prereleases that have been followed by their stable release, newest release first.
+fn select_releases(releases: Releases) -> Releases {
+    let releases = &releases
+        .into_iter()
+        .filter(|release| !release.draft)
+        .map(|release| (release.tag_name.clone(), release))
+        .collect::<HashMap<String, Release>>();
+
+    let mut releases = releases
+        .into_iter()
+        .filter_map(|(_, release)| {
+            if release.prerelease || release.is_pre() {
+                let stable_tag_name = release.trim_pre();
+                if releases.get(&stable_tag_name).is_some() {
+                    return None;
+                }
+            }
+
+            Some(release.clone())
+        })
+        .collect::<Vec<Release>>();
+    releases.sort_by(|a, b| b.tag_name.cmp(&a.tag_name));
+
+    releases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_next_page() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LINK,
+            r#"<https://api.github.com/releases?page=2>; rel="next", <https://api.github.com/releases?page=5>; rel="last""#
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(
+            next_page_url(&headers).as_deref(),
+            Some("https://api.github.com/releases?page=2")
+        );
+
+        headers.insert(
+            LINK,
+            r#"<https://api.github.com/releases?page=4>; rel="prev""#
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(next_page_url(&headers), None);
     }
 }
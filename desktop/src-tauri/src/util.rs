@@ -1,4 +1,10 @@
-use std::time::{Duration, Instant};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    path::PathBuf,
+    process::Command,
+    time::{Duration, Instant},
+};
 
 use tauri::{AppHandle, Manager, Wry};
 use tauri_plugin_store::{with_store, Store, StoreCollection};
@@ -15,6 +21,46 @@ where
     start.elapsed()
 }
 
+/// A random id, every `RandomState` is seeded differently. Good enough for ids that only need to be unique.
+pub fn random_id() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// The path the OS should launch the app with, AppImages run from a temporary mount so the image itself has to be started.
+pub fn app_path() -> std::io::Result<PathBuf> {
+    if let Some(app_image) = std::env::var_os("APPIMAGE") {
+        return Ok(PathBuf::from(app_image));
+    }
+
+    std::env::current_exe()
+}
+
+/// Where the CLI keeps its workspaces and provider data.
+pub fn devpod_home() -> Option<PathBuf> {
+    std::env::var_os("DEVPOD_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".devpod")))
+}
+
+/// Runs `command` through the platform's shell, for user provided command lines.
+pub fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            // CREATE_NO_WINDOW
+            shell.creation_flags(0x08000000);
+        }
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
 pub fn with_data_store<T, F: FnOnce(&mut Store<Wry>) -> Result<T, tauri_plugin_store::Error>>(
     app_handle: &AppHandle,
     filename: &str,
@@ -0,0 +1,198 @@
+use crate::{
+    keychain::{self, KeychainError},
+    AppHandle,
+};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use lazy_static::lazy_static;
+use log::warn;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use thiserror::Error;
+
+const VAULT_FILE_NAME: &str = "secrets.vault";
+// Only the key lives in the keychain, a single item the user may have to unlock once
+const KEY_SERVICE: &str = "sh.loft.devpod.vault";
+const KEY_ACCOUNT: &str = "default";
+const NONCE_LEN: usize = 12;
+
+lazy_static! {
+    static ref VAULT_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+    /// Loaded on first use, reading the key may prompt the user.
+    static ref VAULT: Mutex<Option<Vault>> = Mutex::new(None);
+}
+
+#[derive(Error, Debug)]
+pub enum VaultError {
+    #[error("vault is not available")]
+    NotAvailable,
+    #[error("unable to access the vault key")]
+    Key(#[from] KeychainError),
+    #[error("unable to read vault")]
+    Read(#[source] io::Error),
+    #[error("unable to write vault")]
+    Write(#[source] io::Error),
+    #[error("vault is corrupted")]
+    Corrupted,
+}
+
+struct Vault {
+    path: PathBuf,
+    key: Key,
+    entries: BTreeMap<String, String>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// The entries encrypted with `key`, prefixed with the nonce.
+fn seal(key: &Key, entries: &BTreeMap<String, String>) -> Result<Vec<u8>, VaultError> {
+    let plaintext = serde_json::to_vec(entries).map_err(|_| VaultError::Corrupted)?;
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = ChaCha20Poly1305::new(key)
+        .encrypt(&nonce, plaintext.as_ref())
+        .map_err(|_| VaultError::Corrupted)?;
+
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+fn open(key: &Key, sealed: &[u8]) -> Result<BTreeMap<String, String>, VaultError> {
+    if sealed.len() < NONCE_LEN {
+        return Err(VaultError::Corrupted);
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let plaintext = ChaCha20Poly1305::new(key)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| VaultError::Corrupted)?;
+
+    serde_json::from_slice(&plaintext).map_err(|_| VaultError::Corrupted)
+}
+
+fn read_key() -> Result<Option<Key>, VaultError> {
+    match keychain::get_password(KEY_SERVICE, KEY_ACCOUNT) {
+        Ok(hex) => from_hex(&hex)
+            .filter(|key| key.len() == 32)
+            .map(|key| Some(*Key::from_slice(&key)))
+            .ok_or(VaultError::Corrupted),
+        Err(KeychainError::NotFound(..)) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn load(path: &Path) -> Result<Vault, VaultError> {
+    if let Some(key) = read_key()? {
+        let entries = match fs::read(path) {
+            Ok(sealed) => open(&key, &sealed)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(VaultError::Read(err)),
+        };
+
+        return Ok(Vault {
+            path: path.to_path_buf(),
+            key,
+            entries,
+        });
+    }
+
+    // Without its key the vault can't be read anymore, e.g. after the keychain has been reset
+    if path.exists() {
+        warn!("Vault key is missing, starting with an empty vault");
+    }
+    let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+    keychain::set_password(KEY_SERVICE, KEY_ACCOUNT, &to_hex(&key))?;
+
+    Ok(Vault {
+        path: path.to_path_buf(),
+        key,
+        entries: BTreeMap::new(),
+    })
+}
+
+fn with_vault<T>(f: impl FnOnce(&mut Vault) -> Result<T, VaultError>) -> Result<T, VaultError> {
+    let mut vault = VAULT.lock().unwrap();
+    if vault.is_none() {
+        let path = VAULT_PATH.lock().unwrap().clone();
+        *vault = Some(load(&path.ok_or(VaultError::NotAvailable)?)?);
+    }
+
+    f(vault.as_mut().unwrap())
+}
+
+fn save(vault: &Vault) -> Result<(), VaultError> {
+    let sealed = seal(&vault.key, &vault.entries)?;
+    // Written next to the vault and moved over it, so that it's never half written
+    let tmp_path = vault.path.with_extension("vault.tmp");
+    fs::write(&tmp_path, sealed).map_err(VaultError::Write)?;
+    fs::rename(&tmp_path, &vault.path).map_err(VaultError::Write)
+}
+
+/// Reads an entry, `None` if it doesn't exist. Secrets are stored through `secrets`, which audits every access.
+pub(crate) fn get(entry: &str) -> Result<Option<String>, VaultError> {
+    with_vault(|vault| Ok(vault.entries.get(entry).cloned()))
+}
+
+pub(crate) fn set(entry: &str, value: &str) -> Result<(), VaultError> {
+    with_vault(|vault| {
+        vault.entries.insert(entry.to_string(), value.to_string());
+        save(vault)
+    })
+}
+
+pub(crate) fn delete(entry: &str) -> Result<(), VaultError> {
+    with_vault(|vault| {
+        if vault.entries.remove(entry).is_none() {
+            return Ok(());
+        }
+        save(vault)
+    })
+}
+
+pub fn setup(app_handle: &AppHandle) {
+    if let Some(dir) = app_handle.path_resolver().app_data_dir() {
+        *VAULT_PATH.lock().unwrap() = Some(dir.join(VAULT_FILE_NAME));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_open_with_the_same_key() {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let entries = BTreeMap::from([("git/github.com".to_string(), "token".to_string())]);
+
+        let sealed = seal(&key, &entries).unwrap();
+        assert!(!String::from_utf8_lossy(&sealed).contains("token"));
+        assert_eq!(open(&key, &sealed).unwrap(), entries);
+
+        let other_key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        assert!(matches!(
+            open(&other_key, &sealed),
+            Err(VaultError::Corrupted)
+        ));
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(open(&key, &tampered), Err(VaultError::Corrupted)));
+
+        assert_eq!(from_hex(&to_hex(&key)).as_deref(), Some(key.as_slice()));
+    }
+}
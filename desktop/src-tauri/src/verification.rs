@@ -0,0 +1,157 @@
+use crate::{ui_messages::VerificationFailedMsg, AppHandle, AppState, UiMessage};
+use log::error;
+use minisign_verify::{PublicKey, Signature};
+use sha2::{Digest, Sha256};
+use tauri::Manager;
+use thiserror::Error;
+
+// Should match the updater pubkey from "tauri.conf.json"
+const PUBLIC_KEY: &str = "RWS3J1QwGFRI9Xr5KEsld4VrNa6Gl4u4WLQz9ELIm7zJaz9O3u53aGP+";
+
+// Release assets describing all other artifacts of a release
+#[cfg(feature = "enable-updater")]
+pub const CHECKSUM_MANIFEST_NAME: &str = "checksums.txt";
+#[cfg(feature = "enable-updater")]
+pub const CHECKSUM_MANIFEST_SIGNATURE_NAME: &str = "checksums.txt.sig";
+
+#[derive(Error, Debug)]
+pub enum VerificationError {
+    #[error("invalid public key")]
+    PublicKey(#[source] minisign_verify::Error),
+    #[error("invalid signature")]
+    InvalidSignature(#[source] minisign_verify::Error),
+    #[error("signature does not match checksum manifest")]
+    SignatureMismatch(#[source] minisign_verify::Error),
+    #[error("{0} is not listed in the checksum manifest")]
+    NotInManifest(String),
+    #[error("checksum mismatch for {artifact}, expected {expected} but got {actual}")]
+    Checksum {
+        artifact: String,
+        expected: String,
+        actual: String,
+    },
+}
+impl serde::Serialize for VerificationError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// A checksum manifest in `sha256sum` format whose detached signature has been verified.
+#[derive(Debug, Clone)]
+pub struct VerifiedManifest {
+    checksums: Vec<(String, String)>,
+}
+
+impl VerifiedManifest {
+    /// Verifies the minisign `signature` of `manifest` against the embedded public key.
+    pub fn new(manifest: &str, signature: &str) -> Result<Self, VerificationError> {
+        let public_key =
+            PublicKey::from_base64(PUBLIC_KEY).map_err(VerificationError::PublicKey)?;
+        let signature =
+            Signature::decode(signature).map_err(VerificationError::InvalidSignature)?;
+        public_key
+            .verify(manifest.as_bytes(), &signature, false)
+            .map_err(VerificationError::SignatureMismatch)?;
+
+        Ok(Self {
+            checksums: parse_manifest(manifest),
+        })
+    }
+
+    pub fn checksum(&self, artifact: &str) -> Option<&str> {
+        self.checksums
+            .iter()
+            .find(|(name, _)| name == artifact)
+            .map(|(_, checksum)| checksum.as_str())
+    }
+
+    /// Checks that `data` matches the checksum listed for `artifact`.
+    pub fn verify(&self, artifact: &str, data: &[u8]) -> Result<(), VerificationError> {
+        let expected = self
+            .checksum(artifact)
+            .ok_or_else(|| VerificationError::NotInManifest(artifact.to_string()))?;
+        let actual = format!("{:x}", Sha256::digest(data));
+
+        if expected != actual {
+            return Err(VerificationError::Checksum {
+                artifact: artifact.to_string(),
+                expected: expected.to_string(),
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+// `sha256sum` format: `<hex digest>  <file name>`, binary mode prefixes the name with `*`
+fn parse_manifest(manifest: &str) -> Vec<(String, String)> {
+    manifest
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let checksum = columns.next()?.to_lowercase();
+            let name = columns.next()?.trim_start_matches('*').to_string();
+
+            Some((name, checksum))
+        })
+        .collect()
+}
+
+/// Informs the UI that a downloaded artifact failed verification and will not be used.
+pub async fn report_failure(app_handle: &AppHandle, artifact: &str, err: &VerificationError) {
+    error!("Verification of {} failed: {}", artifact, err);
+
+    let state = app_handle.state::<AppState>();
+    let msg = VerificationFailedMsg::new(artifact.to_string(), err.to_string());
+    if let Err(err) = state
+        .ui_messages
+        .send(UiMessage::VerificationFailed(msg))
+        .await
+    {
+        error!("Failed to broadcast verification failed message: {:?}", err);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_manifest() {
+        let manifest = "abc123  devpod-linux-amd64\nDEF456 *devpod-windows-amd64.exe\n\n";
+        let checksums = parse_manifest(manifest);
+
+        assert_eq!(
+            checksums,
+            vec![
+                ("devpod-linux-amd64".to_string(), "abc123".to_string()),
+                ("devpod-windows-amd64.exe".to_string(), "def456".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_verify_checksum() {
+        let manifest = VerifiedManifest {
+            checksums: parse_manifest(
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  hello",
+            ),
+        };
+
+        assert!(manifest.verify("hello", b"hello").is_ok());
+        assert!(manifest.verify("hello", b"world").is_err());
+        assert!(manifest.verify("missing", b"hello").is_err());
+    }
+
+    #[test]
+    fn should_reject_invalid_signature() {
+        let got = VerifiedManifest::new("abc123  devpod", "not a signature");
+
+        assert!(matches!(got, Err(VerificationError::InvalidSignature(_))));
+    }
+}
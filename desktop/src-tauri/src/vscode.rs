@@ -0,0 +1,136 @@
+use crate::{
+    installed_ides::{self, IdeSource, InstalledIde},
+    settings::Settings,
+    ssh_config, workspaces, AppHandle,
+};
+use log::info;
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+use thiserror::Error;
+
+const VSCODE_ID: &str = "vscode";
+
+#[derive(Error, Debug)]
+pub enum VscodeError {
+    #[error("Visual Studio Code is not installed")]
+    NotInstalled,
+    #[error("invalid profile")]
+    InvalidProfile,
+    #[error("unable to launch Visual Studio Code")]
+    Launch(#[source] std::io::Error),
+}
+impl serde::Serialize for VscodeError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// The `code` launcher, application bundles ship it next to the app.
+fn launcher(vscode: &InstalledIde) -> PathBuf {
+    let path = Path::new(vscode.path());
+    match vscode.source() {
+        IdeSource::Application if cfg!(target_os = "macos") => {
+            path.join("Contents/Resources/app/bin/code")
+        }
+        IdeSource::Application if cfg!(windows) => path
+            .parent()
+            .map(|dir| dir.join("bin").join("code.cmd"))
+            .unwrap_or_else(|| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn args(workspace_id: &str, profile: Option<&str>, reuse_window: bool) -> Vec<String> {
+    let mut args = vec![
+        "--folder-uri".to_string(),
+        format!(
+            "vscode-remote://ssh-remote+{}{}",
+            ssh_config::host_alias(workspace_id),
+            workspaces::remote_folder(workspace_id)
+        ),
+    ];
+    if let Some(profile) = profile {
+        args.extend(["--profile".to_string(), profile.to_string()]);
+    }
+    args.push(
+        if reuse_window {
+            "--reuse-window"
+        } else {
+            "--new-window"
+        }
+        .to_string(),
+    );
+
+    args
+}
+
+/// Opens the workspace over Remote SSH, `profile` and `reuse_window` fall back to the settings.
+#[tauri::command]
+pub fn open_vscode(
+    app_handle: AppHandle,
+    workspace_id: String,
+    profile: Option<String>,
+    reuse_window: Option<bool>,
+) -> Result<(), VscodeError> {
+    let profile = profile
+        .or_else(|| Settings::vscode_profile(&app_handle))
+        .filter(|profile| !profile.trim().is_empty());
+    if profile
+        .as_deref()
+        .is_some_and(|profile| profile.contains(['\n', '\r']))
+    {
+        return Err(VscodeError::InvalidProfile);
+    }
+    let reuse_window = reuse_window.unwrap_or_else(|| Settings::vscode_reuse_window(&app_handle));
+    let vscode = installed_ides::installed(false)
+        .into_iter()
+        .find(|installed| installed.id() == VSCODE_ID)
+        .ok_or(VscodeError::NotInstalled)?;
+
+    info!(
+        "Opening {} in VS Code, profile {:?}, reusing window: {}",
+        workspace_id, profile, reuse_window
+    );
+    let mut command = Command::new(launcher(&vscode));
+    command
+        .args(args(&workspace_id, profile.as_deref(), reuse_window))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW
+        command.creation_flags(0x08000000);
+    }
+
+    command.spawn().map(|_| ()).map_err(VscodeError::Launch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_pass_profile_and_window() {
+        assert_eq!(
+            args("my-project", Some("Go"), true),
+            vec![
+                "--folder-uri",
+                "vscode-remote://ssh-remote+my-project.devpod/workspaces/my-project",
+                "--profile",
+                "Go",
+                "--reuse-window"
+            ]
+        );
+        assert_eq!(
+            args("my-project", None, false).last().unwrap(),
+            "--new-window"
+        );
+    }
+}
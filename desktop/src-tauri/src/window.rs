@@ -1,4 +1,8 @@
-use crate::AppHandle;
+use crate::{
+    debug_console::DEBUG_CONSOLE_WINDOW_LABEL, shutdown::SHUTDOWN_WINDOW_LABEL,
+    status_widget::STATUS_WIDGET_WINDOW_LABEL, ui_messages::SETTINGS_WINDOW_LABEL, window_state,
+    AppHandle,
+};
 use anyhow::{Context, Result};
 use log::error;
 use tauri::{Window, WindowBuilder, WindowUrl, Wry};
@@ -14,6 +18,8 @@ impl WindowHelper {
     }
 
     pub fn setup(&self, window: &Window<Wry>) {
+        window_state::track(window);
+
         // open browser devtools automatically during development
         #[cfg(debug_assertions)]
         {
@@ -90,6 +96,134 @@ impl WindowHelper {
             })
             .context("Failed to create update ready window")
     }
+
+    pub fn new_debug_console_window(&self) -> Result<()> {
+        let handle = self.app_handle.clone();
+
+        self.app_handle
+            .run_on_main_thread(move || {
+                let window_builder = WindowBuilder::new(
+                    &handle,
+                    DEBUG_CONSOLE_WINDOW_LABEL.to_string(),
+                    WindowUrl::App("debug-console/index.html".into()),
+                )
+                .title("DevPod Debug Console")
+                .fullscreen(false)
+                .resizable(true)
+                .transparent(false)
+                .inner_size(900.0, 600.0)
+                .visible(true);
+
+                match window_builder.build() {
+                    Ok(window) => window_state::track(&window),
+                    Err(err) => error!("Failed to create debug console window: {}", err),
+                }
+            })
+            .context("Failed to create debug console window")
+    }
+
+    pub fn new_log_window(&self, label: String, workspace_id: &str) -> Result<()> {
+        let handle = self.app_handle.clone();
+        let title = format!("Logs: {}", workspace_id);
+
+        self.app_handle
+            .run_on_main_thread(move || {
+                let window_builder = WindowBuilder::new(
+                    &handle,
+                    label,
+                    WindowUrl::App("log-window/index.html".into()),
+                )
+                .title(title)
+                .fullscreen(false)
+                .resizable(true)
+                .transparent(false)
+                .inner_size(900.0, 600.0)
+                .visible(true);
+
+                match window_builder.build() {
+                    Ok(window) => window_state::track(&window),
+                    Err(err) => error!("Failed to create log window: {}", err),
+                }
+            })
+            .context("Failed to create log window")
+    }
+
+    pub fn new_shutdown_window(&self) -> Result<()> {
+        let handle = self.app_handle.clone();
+
+        self.app_handle
+            .run_on_main_thread(move || {
+                let window_builder = WindowBuilder::new(
+                    &handle,
+                    SHUTDOWN_WINDOW_LABEL.to_string(),
+                    WindowUrl::App("shutdown-window/index.html".into()),
+                )
+                .title("Quitting DevPod")
+                .fullscreen(false)
+                .resizable(false)
+                .transparent(false)
+                .inner_size(400.0, 300.0)
+                .visible(true);
+
+                if let Err(err) = window_builder.build() {
+                    error!("Failed to create shutdown window: {}", err);
+                }
+            })
+            .context("Failed to create shutdown window")
+    }
+
+    pub fn new_settings_window(&self) -> Result<()> {
+        let handle = self.app_handle.clone();
+
+        self.app_handle
+            .run_on_main_thread(move || {
+                let window_builder = WindowBuilder::new(
+                    &handle,
+                    SETTINGS_WINDOW_LABEL.to_string(),
+                    WindowUrl::App("settings-window/index.html".into()),
+                )
+                .title("DevPod Settings")
+                .fullscreen(false)
+                .resizable(true)
+                .transparent(false)
+                .min_inner_size(800.0, 600.0)
+                .inner_size(1000.0, 700.0)
+                .visible(true);
+
+                match window_builder.build() {
+                    Ok(window) => window_state::track(&window),
+                    Err(err) => error!("Failed to create settings window: {}", err),
+                }
+            })
+            .context("Failed to create settings window")
+    }
+
+    pub fn new_status_widget_window(&self) -> Result<()> {
+        let handle = self.app_handle.clone();
+
+        self.app_handle
+            .run_on_main_thread(move || {
+                let window_builder = WindowBuilder::new(
+                    &handle,
+                    STATUS_WIDGET_WINDOW_LABEL.to_string(),
+                    WindowUrl::App("status-widget/index.html".into()),
+                )
+                .title("DevPod Status")
+                .fullscreen(false)
+                .resizable(true)
+                .transparent(false)
+                .always_on_top(true)
+                .skip_taskbar(true)
+                .inner_size(320.0, 240.0)
+                .visible(true);
+
+                match window_builder.build() {
+                    Ok(window) => window_state::track(&window),
+                    Err(err) => error!("Failed to create status widget window: {}", err),
+                }
+            })
+            .context("Failed to create status widget window")
+    }
 }
 
 #[cfg(target_os = "macos")]
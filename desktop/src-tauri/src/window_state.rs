@@ -0,0 +1,326 @@
+use crate::AppHandle;
+use lazy_static::lazy_static;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf, sync::Mutex};
+use tauri::{Manager, Monitor, PhysicalPosition, PhysicalSize, Window, WindowEvent};
+use thiserror::Error;
+use ts_rs::TS;
+
+const WINDOW_STATE_FILE_NAME: &str = "window_state.json";
+// How much of a window has to be on a monitor for the user to be able to grab it
+const MIN_VISIBLE_PX: i64 = 64;
+
+lazy_static! {
+    // Loaded on first use, keyed by window label
+    static ref WINDOW_STATES: Mutex<Option<BTreeMap<String, WindowState>>> = Mutex::new(None);
+}
+
+/// Outer position and inner size in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Bounds {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl Bounds {
+    fn of_monitor(monitor: &Monitor) -> Self {
+        Self {
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+        }
+    }
+
+    fn overlap(&self, other: &Bounds) -> (i64, i64) {
+        let span = |start: i32, len: u32, other_start: i32, other_len: u32| {
+            let end = i64::from(start) + i64::from(len);
+            let other_end = i64::from(other_start) + i64::from(other_len);
+
+            end.min(other_end) - i64::from(start).max(i64::from(other_start))
+        };
+
+        (
+            span(self.x, self.width, other.x, other.width),
+            span(self.y, self.height, other.y, other.height),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WindowState {
+    /// Kept while the window is maximized, so that it can be unmaximized to where it was
+    bounds: Option<Bounds>,
+    maximized: bool,
+    monitor: Option<String>,
+    route: Option<String>,
+    ui: BTreeMap<String, serde_json::Value>,
+}
+
+/// What the frontend keeps per window, restored when the window is opened again.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct WindowUiState {
+    route: Option<String>,
+    #[ts(type = "Record<string, unknown>")]
+    ui: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Error, Debug)]
+pub enum WindowStateError {
+    #[error("unable to save window state")]
+    Persist(#[source] anyhow::Error),
+}
+impl serde::Serialize for WindowStateError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+fn window_state_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path_resolver()
+        .app_data_dir()
+        .map(|dir| dir.join(WINDOW_STATE_FILE_NAME))
+}
+
+fn load(app_handle: &AppHandle) -> BTreeMap<String, WindowState> {
+    window_state_path(app_handle)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(
+    app_handle: &AppHandle,
+    states: &BTreeMap<String, WindowState>,
+) -> Result<(), WindowStateError> {
+    let path = window_state_path(app_handle)
+        .ok_or_else(|| WindowStateError::Persist(anyhow::anyhow!("unable to find app data dir")))?;
+    let content =
+        serde_json::to_vec_pretty(states).map_err(|err| WindowStateError::Persist(err.into()))?;
+
+    fs::write(path, content).map_err(|err| WindowStateError::Persist(err.into()))
+}
+
+fn with_states<T>(
+    app_handle: &AppHandle,
+    f: impl FnOnce(&mut BTreeMap<String, WindowState>) -> T,
+) -> T {
+    let mut states = WINDOW_STATES.lock().unwrap();
+
+    f(states.get_or_insert_with(|| load(app_handle)))
+}
+
+/// Where to put a window that was last at `bounds`. It stays there as long as enough of it is on one of the
+/// `monitors`, otherwise it's centered on `fallback` and shrunk to fit it, e.g. when its monitor was disconnected.
+fn fit(bounds: Bounds, monitors: &[Bounds], fallback: Bounds) -> Bounds {
+    let min_visible = |monitor: &Bounds| {
+        let (width, height) = bounds.overlap(monitor);
+        width >= MIN_VISIBLE_PX.min(i64::from(bounds.width))
+            && height >= MIN_VISIBLE_PX.min(i64::from(bounds.height))
+    };
+    if monitors.iter().any(min_visible) {
+        return bounds;
+    }
+
+    let width = bounds.width.min(fallback.width);
+    let height = bounds.height.min(fallback.height);
+    Bounds {
+        x: fallback.x + ((fallback.width - width) / 2) as i32,
+        y: fallback.y + ((fallback.height - height) / 2) as i32,
+        width,
+        height,
+    }
+}
+
+fn restore(window: &Window, state: &WindowState) {
+    let Some(bounds) = state.bounds else {
+        return;
+    };
+    let monitors = window.available_monitors().unwrap_or_default();
+    let connected = state.monitor.as_ref().map_or(true, |name| {
+        monitors.iter().any(|monitor| monitor.name() == Some(name))
+    });
+    // Coordinates of a disconnected monitor may happen to overlap another one, so they aren't trusted at all
+    let candidates: Vec<Bounds> = if connected {
+        monitors.iter().map(Bounds::of_monitor).collect()
+    } else {
+        info!(
+            "Monitor of window {} is disconnected, moving it",
+            window.label()
+        );
+        vec![]
+    };
+    let fallback = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .or_else(|| window.primary_monitor().ok().flatten())
+        .map(|monitor| Bounds::of_monitor(&monitor));
+    let Some(fallback) = fallback.or_else(|| candidates.first().copied()) else {
+        return;
+    };
+
+    let bounds = fit(bounds, &candidates, fallback);
+    let result = window
+        .set_size(PhysicalSize::new(bounds.width, bounds.height))
+        .and_then(|_| window.set_position(PhysicalPosition::new(bounds.x, bounds.y)))
+        .and_then(|_| {
+            if state.maximized {
+                window.maximize()
+            } else {
+                Ok(())
+            }
+        });
+    if let Err(err) = result {
+        warn!("Failed to restore window {}: {}", window.label(), err);
+    }
+}
+
+/// Takes the window's current geometry into its state.
+fn update(app_handle: &AppHandle, window: &Window) {
+    let maximized = window.is_maximized().unwrap_or(false);
+    let bounds = match (window.outer_position(), window.inner_size()) {
+        (Ok(position), Ok(size)) => Some(Bounds {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        }),
+        _ => None,
+    };
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|monitor| monitor.name().cloned());
+
+    with_states(app_handle, |states| {
+        let state = states.entry(window.label().to_string()).or_default();
+        state.maximized = maximized;
+        // Minimized windows report an empty size, and maximized ones the size of the monitor
+        if let Some(bounds) = bounds.filter(|b| !maximized && b.width > 0 && b.height > 0) {
+            state.bounds = Some(bounds);
+            state.monitor = monitor;
+        }
+    });
+}
+
+/// Writes the state of all windows to disk, the geometry only changes in memory until then.
+pub fn persist(app_handle: &AppHandle) {
+    with_states(app_handle, |states| {
+        if let Err(err) = save(app_handle, states) {
+            warn!("{}", err);
+        }
+    });
+}
+
+/// Moves the window to where it was when it was last closed, and keeps track of its geometry from now on.
+pub fn track(window: &Window) {
+    let app_handle = window.app_handle();
+    let state = with_states(&app_handle, |states| states.get(window.label()).cloned());
+    if let Some(state) = state {
+        restore(window, &state);
+    }
+
+    let tracked = window.clone();
+    window.on_window_event(move |event| match event {
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) => update(&app_handle, &tracked),
+        WindowEvent::Destroyed => persist(&app_handle),
+        _ => {}
+    });
+}
+
+#[tauri::command]
+pub fn get_window_ui_state(app_handle: AppHandle, window: Window) -> WindowUiState {
+    with_states(&app_handle, |states| {
+        let state = states.get(window.label()).cloned().unwrap_or_default();
+
+        WindowUiState {
+            route: state.route,
+            ui: state.ui,
+        }
+    })
+}
+
+/// Remembers where the calling window navigated to, it starts there the next time.
+#[tauri::command]
+pub fn set_window_route(
+    app_handle: AppHandle,
+    window: Window,
+    route: String,
+) -> Result<(), WindowStateError> {
+    with_states(&app_handle, |states| {
+        states.entry(window.label().to_string()).or_default().route = Some(route);
+
+        save(&app_handle, states)
+    })
+}
+
+/// Keeps a piece of the calling window's UI state, e.g. the width of a panel. `null` forgets it.
+#[tauri::command]
+pub fn set_window_ui_state(
+    app_handle: AppHandle,
+    window: Window,
+    key: String,
+    value: serde_json::Value,
+) -> Result<(), WindowStateError> {
+    with_states(&app_handle, |states| {
+        let ui = &mut states.entry(window.label().to_string()).or_default().ui;
+        if value.is_null() {
+            ui.remove(&key);
+        } else {
+            ui.insert(key, value);
+        }
+
+        save(&app_handle, states)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_move_windows_that_are_off_screen() {
+        let primary = Bounds {
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        };
+        let on_screen = Bounds {
+            x: 100,
+            y: 100,
+            width: 1200,
+            height: 800,
+        };
+        assert_eq!(fit(on_screen, &[primary], primary), on_screen);
+
+        // Last seen on a monitor to the right that's gone now
+        let off_screen = Bounds {
+            x: 2500,
+            y: 200,
+            width: 2400,
+            height: 800,
+        };
+        assert_eq!(
+            fit(off_screen, &[primary], primary),
+            Bounds {
+                x: 0,
+                y: 140,
+                width: 1920,
+                height: 800,
+            }
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{
+    docker_contexts::{self, DockerContextError},
+    workspaces, AppHandle,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::Path};
+use thiserror::Error;
+use ts_rs::TS;
+
+// The label the CLI finds the container of a workspace by
+const WORKSPACE_LABEL: &str = "dev.containers.id";
+const DOCKERFILE_NAME: &str = "Dockerfile";
+// Docker unpacks local tar archives that are added to an image
+const ROOTFS_NAME: &str = "rootfs.tar";
+
+#[derive(Error, Debug)]
+pub enum WorkspaceExportError {
+    #[error("workspace {0} doesn't exist")]
+    UnknownWorkspace(String),
+    #[error("workspace {0} doesn't run in a container on a docker daemon")]
+    NotDockerWorkspace(String),
+    #[error("unable to find the container of workspace {0}, it needs to be built first")]
+    ContainerNotFound(String),
+    #[error("invalid image reference {0}")]
+    InvalidReference(String),
+    #[error("{0} already contains a Dockerfile")]
+    DockerfileExists(String),
+    #[error("unable to export workspace: {0}")]
+    Docker(String),
+    #[error("unable to write {0}")]
+    Write(String, #[source] io::Error),
+    #[error(transparent)]
+    DockerContext(#[from] DockerContextError),
+    #[error("unable to export workspace")]
+    Join(#[source] tauri::Error),
+}
+impl serde::Serialize for WorkspaceExportError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// Where a built workspace goes. Sources that are mounted into the workspace, e.g. from a local folder, aren't part of
+/// the export.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+#[ts(export)]
+pub enum WorkspaceExportTarget {
+    /// Commits the container to `reference`, the credentials of `docker login` are used to push it
+    Image { reference: String, push: bool },
+    /// Writes a Dockerfile along with the file system of the container into `folder`
+    Dockerfile { folder: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct WorkspaceExport {
+    /// The image reference or the path of the Dockerfile
+    location: String,
+    /// e.g. `registry.example.com/team/env@sha256:...`, once pushed
+    digest: Option<String>,
+}
+
+/// The part of `docker inspect` that describes how the container runs.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContainerConfig {
+    #[serde(default)]
+    env: Vec<String>,
+    #[serde(default)]
+    user: String,
+    #[serde(default)]
+    working_dir: String,
+    entrypoint: Option<Vec<String>>,
+    cmd: Option<Vec<String>>,
+    labels: Option<BTreeMap<String, String>>,
+}
+
+/// Image references are passed to `docker` as arguments, they must not look like flags.
+fn is_valid_reference(reference: &str) -> bool {
+    !reference.is_empty()
+        && !reference.starts_with('-')
+        && reference
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '/' | ':' | '@'))
+}
+
+/// Quotes `value` for instructions that expand variables.
+fn quote(value: &str) -> String {
+    serde_json::to_string(value)
+        .unwrap_or_default()
+        .replace('$', "\\$")
+}
+
+fn dockerfile(workspace_id: &str, config: &ContainerConfig) -> String {
+    let mut lines = vec![
+        format!("# Exported from the DevPod workspace {}", workspace_id),
+        "FROM scratch".to_string(),
+        format!("ADD {} /", ROOTFS_NAME),
+    ];
+    for variable in &config.env {
+        if let Some((name, value)) = variable.split_once('=') {
+            lines.push(format!("ENV {}={}", name, quote(value)));
+        }
+    }
+    // Labels of the container that belong to the workspace, e.g. its id, would make DevPod mistake new ones for it
+    for (name, value) in config.labels.iter().flatten() {
+        if !name.starts_with(WORKSPACE_LABEL) {
+            lines.push(format!("LABEL {}={}", quote(name), quote(value)));
+        }
+    }
+    if !config.user.is_empty() {
+        lines.push(format!("USER {}", config.user));
+    }
+    if !config.working_dir.is_empty() {
+        lines.push(format!("WORKDIR {}", config.working_dir));
+    }
+    if let Some(entrypoint) = &config.entrypoint {
+        lines.push(format!(
+            "ENTRYPOINT {}",
+            serde_json::to_string(entrypoint).unwrap_or_default()
+        ));
+    }
+    if let Some(cmd) = &config.cmd {
+        lines.push(format!(
+            "CMD {}",
+            serde_json::to_string(cmd).unwrap_or_default()
+        ));
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// The container of a workspace on the daemon of its provider.
+struct WorkspaceContainer {
+    workspace_id: String,
+    id: String,
+    docker_host: String,
+}
+
+impl WorkspaceContainer {
+    fn find(app_handle: &AppHandle, workspace_id: &str) -> Result<Self, WorkspaceExportError> {
+        let provider = workspaces::provider(app_handle, workspace_id)
+            .ok_or_else(|| WorkspaceExportError::UnknownWorkspace(workspace_id.to_string()))?;
+        let docker_host = docker_contexts::docker_host(app_handle, &provider)?
+            .ok_or_else(|| WorkspaceExportError::NotDockerWorkspace(workspace_id.to_string()))?;
+        let uid =
+            workspaces::uid(app_handle, workspace_id).unwrap_or_else(|| workspace_id.to_string());
+
+        let filter = format!("label={}={}", WORKSPACE_LABEL, uid);
+        let output = docker_contexts::docker(
+            Some(&docker_host),
+            &["ps", "--all", "--filter", &filter, "--format", "{{.ID}}"],
+        )
+        .map_err(WorkspaceExportError::Docker)?;
+        let id = output
+            .lines()
+            .next()
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| WorkspaceExportError::ContainerNotFound(workspace_id.to_string()))?;
+
+        Ok(WorkspaceContainer {
+            workspace_id: workspace_id.to_string(),
+            id: id.to_string(),
+            docker_host,
+        })
+    }
+
+    fn docker(&self, args: &[&str]) -> Result<String, WorkspaceExportError> {
+        docker_contexts::docker(Some(&self.docker_host), args).map_err(WorkspaceExportError::Docker)
+    }
+
+    fn export_image(
+        &self,
+        reference: &str,
+        push: bool,
+    ) -> Result<WorkspaceExport, WorkspaceExportError> {
+        if !is_valid_reference(reference) {
+            return Err(WorkspaceExportError::InvalidReference(
+                reference.to_string(),
+            ));
+        }
+
+        let message = format!("Exported from the DevPod workspace {}", self.workspace_id);
+        self.docker(&["commit", "--message", &message, &self.id, reference])?;
+        let digest = if push {
+            self.docker(&["push", reference])?;
+            let digest = self.docker(&[
+                "image",
+                "inspect",
+                "--format",
+                "{{index .RepoDigests 0}}",
+                reference,
+            ])?;
+            Some(digest).filter(|digest| !digest.is_empty())
+        } else {
+            None
+        };
+
+        Ok(WorkspaceExport {
+            location: reference.to_string(),
+            digest,
+        })
+    }
+
+    fn export_dockerfile(&self, folder: &str) -> Result<WorkspaceExport, WorkspaceExportError> {
+        let dockerfile_path = Path::new(folder).join(DOCKERFILE_NAME);
+        if dockerfile_path.exists() {
+            return Err(WorkspaceExportError::DockerfileExists(folder.to_string()));
+        }
+        fs::create_dir_all(folder)
+            .map_err(|err| WorkspaceExportError::Write(folder.to_string(), err))?;
+
+        let output = self.docker(&[
+            "container",
+            "inspect",
+            "--format",
+            "{{json .Config}}",
+            &self.id,
+        ])?;
+        let config: ContainerConfig = serde_json::from_str(&output)
+            .map_err(|err| WorkspaceExportError::Docker(err.to_string()))?;
+        let rootfs_path = Path::new(folder).join(ROOTFS_NAME);
+        self.docker(&[
+            "export",
+            "--output",
+            &rootfs_path.to_string_lossy(),
+            &self.id,
+        ])?;
+
+        let location = dockerfile_path.to_string_lossy().to_string();
+        fs::write(&dockerfile_path, dockerfile(&self.workspace_id, &config))
+            .map_err(|err| WorkspaceExportError::Write(location.clone(), err))?;
+
+        Ok(WorkspaceExport {
+            location,
+            digest: None,
+        })
+    }
+}
+
+/// Exports the environment of a built workspace, so that it can be used without building it again, e.g. in CI.
+#[tauri::command]
+pub async fn export_workspace(
+    app_handle: AppHandle,
+    workspace_id: String,
+    target: WorkspaceExportTarget,
+) -> Result<WorkspaceExport, WorkspaceExportError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let container = WorkspaceContainer::find(&app_handle, &workspace_id)?;
+        let export = match target {
+            WorkspaceExportTarget::Image { reference, push } => {
+                container.export_image(&reference, push)?
+            }
+            WorkspaceExportTarget::Dockerfile { folder } => container.export_dockerfile(&folder)?,
+        };
+        info!("Exported {} to {}", workspace_id, export.location);
+
+        Ok(export)
+    })
+    .await
+    .map_err(WorkspaceExportError::Join)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_generate_dockerfile() {
+        let config: ContainerConfig = serde_json::from_str(
+            r#"{
+                "Env": ["PATH=/usr/local/bin:/usr/bin", "GREETING=say \"hi\" to $USER"],
+                "User": "vscode",
+                "WorkingDir": "/workspaces/my-app",
+                "Entrypoint": null,
+                "Cmd": ["sleep", "infinity"],
+                "Labels": { "dev.containers.id": "abc", "devcontainer.metadata": "[]" }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            dockerfile("my-app", &config),
+            r#"# Exported from the DevPod workspace my-app
+FROM scratch
+ADD rootfs.tar /
+ENV PATH="/usr/local/bin:/usr/bin"
+ENV GREETING="say \"hi\" to \$USER"
+LABEL "devcontainer.metadata"="[]"
+USER vscode
+WORKDIR /workspaces/my-app
+CMD ["sleep","infinity"]
+"#
+        );
+        assert!(is_valid_reference("ghcr.io/team/env:2024-05"));
+        assert!(!is_valid_reference("--output=/tmp/x"));
+        assert!(!is_valid_reference("team/env latest"));
+    }
+}
@@ -0,0 +1,250 @@
+use crate::state_sync::{self, SyncedCollection};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use thiserror::Error;
+use ts_rs::TS;
+
+const MAX_LIMIT: u32 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum WorkspaceSort {
+    Name,
+    LastUsed,
+    Created,
+    Provider,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct WorkspaceQuery {
+    sort: WorkspaceSort,
+    direction: SortDirection,
+    /// Matched case insensitively against the id, source and provider
+    filter: Option<String>,
+    provider: Option<String>,
+    /// From the previous page, `None` for the first one
+    cursor: Option<String>,
+    limit: u32,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct WorkspacePage {
+    #[ts(type = "Array<unknown>")]
+    items: Vec<serde_json::Value>,
+    /// Workspaces matching the filters, on all pages
+    total: u32,
+    /// `None` on the last page
+    next_cursor: Option<String>,
+}
+
+#[derive(Error, Debug)]
+pub enum WorkspaceQueryError {
+    #[error("the cursor doesn't belong to this sort order")]
+    InvalidCursor,
+}
+impl serde::Serialize for WorkspaceQueryError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+/// What workspaces are ordered by, a missing timestamp counts as the oldest one.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(untagged)]
+enum SortKey {
+    Time(i64),
+    Text(String),
+}
+
+/// Points at the last workspace of a page. It holds its sort key rather than an offset, so that the next page
+/// neither skips nor repeats workspaces when others are added or removed in the meantime.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Cursor {
+    sort: WorkspaceSort,
+    key: SortKey,
+    id: String,
+}
+
+fn text<'a>(workspace: &'a serde_json::Value, pointer: &str) -> Option<&'a str> {
+    workspace.pointer(pointer)?.as_str()
+}
+
+fn sort_key(workspace: &serde_json::Value, sort: WorkspaceSort) -> SortKey {
+    let time = |pointer| {
+        let millis = text(workspace, pointer)
+            .and_then(|time| time.parse::<DateTime<Utc>>().ok())
+            .map_or(i64::MIN, |time| time.timestamp_millis());
+
+        SortKey::Time(millis)
+    };
+
+    match sort {
+        WorkspaceSort::Name => {
+            SortKey::Text(text(workspace, "/id").unwrap_or_default().to_lowercase())
+        }
+        WorkspaceSort::LastUsed => time("/lastUsed"),
+        WorkspaceSort::Created => time("/creationTimestamp"),
+        WorkspaceSort::Provider => SortKey::Text(
+            text(workspace, "/provider/name")
+                .unwrap_or_default()
+                .to_lowercase(),
+        ),
+    }
+}
+
+fn matches(workspace: &serde_json::Value, query: &WorkspaceQuery) -> bool {
+    if let Some(provider) = &query.provider {
+        if text(workspace, "/provider/name") != Some(provider.as_str()) {
+            return false;
+        }
+    }
+
+    let Some(filter) = query.filter.as_deref().map(str::to_lowercase) else {
+        return true;
+    };
+    [
+        "/id",
+        "/source/gitRepository",
+        "/source/localFolder",
+        "/source/image",
+        "/provider/name",
+    ]
+    .into_iter()
+    .filter_map(|pointer| text(workspace, pointer))
+    .any(|value| value.to_lowercase().contains(&filter))
+}
+
+fn run(
+    workspaces: Vec<(String, serde_json::Value)>,
+    query: &WorkspaceQuery,
+) -> Result<WorkspacePage, WorkspaceQueryError> {
+    let after = match &query.cursor {
+        Some(cursor) => {
+            let cursor: Cursor =
+                serde_json::from_str(cursor).map_err(|_| WorkspaceQueryError::InvalidCursor)?;
+            if cursor.sort != query.sort {
+                return Err(WorkspaceQueryError::InvalidCursor);
+            }
+
+            Some((cursor.key, cursor.id))
+        }
+        None => None,
+    };
+    let compare = |a: &(SortKey, String), b: &(SortKey, String)| -> Ordering {
+        match query.direction {
+            SortDirection::Asc => a.cmp(b),
+            SortDirection::Desc => b.cmp(a),
+        }
+    };
+
+    let mut matching: Vec<((SortKey, String), serde_json::Value)> = workspaces
+        .into_iter()
+        .filter(|(_, workspace)| matches(workspace, query))
+        .map(|(id, workspace)| ((sort_key(&workspace, query.sort), id), workspace))
+        .collect();
+    matching.sort_by(|(a, _), (b, _)| compare(a, b));
+    let total = matching.len() as u32;
+
+    let limit = query.limit.clamp(1, MAX_LIMIT) as usize;
+    let mut page: Vec<((SortKey, String), serde_json::Value)> = matching
+        .into_iter()
+        .filter(|(position, _)| {
+            after
+                .as_ref()
+                .map_or(true, |after| compare(position, after) == Ordering::Greater)
+        })
+        .take(limit + 1)
+        .collect();
+    let next_cursor = if page.len() > limit {
+        page.truncate(limit);
+        page.last().and_then(|((key, id), _)| {
+            serde_json::to_string(&Cursor {
+                sort: query.sort,
+                key: key.clone(),
+                id: id.clone(),
+            })
+            .ok()
+        })
+    } else {
+        None
+    };
+
+    Ok(WorkspacePage {
+        items: page.into_iter().map(|(_, workspace)| workspace).collect(),
+        total,
+        next_cursor,
+    })
+}
+
+/// A page of the workspaces the backend knows about, filtered and sorted.
+#[tauri::command]
+pub fn query_workspaces(query: WorkspaceQuery) -> Result<WorkspacePage, WorkspaceQueryError> {
+    let workspaces = state_sync::items(SyncedCollection::Workspaces);
+
+    run(workspaces.into_iter().collect(), &query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn should_page_through_workspaces_added_in_between() {
+        let workspace = |id: &str, last_used: &str| {
+            (
+                id.to_string(),
+                json!({"id": id, "lastUsed": last_used, "provider": {"name": "docker"}}),
+            )
+        };
+        let mut workspaces = vec![
+            workspace("a", "2024-01-04T00:00:00Z"),
+            workspace("b", "2024-01-03T00:00:00Z"),
+            workspace("c", "2024-01-02T00:00:00Z"),
+            workspace("d", "2024-01-01T00:00:00Z"),
+        ];
+        let mut query = WorkspaceQuery {
+            sort: WorkspaceSort::LastUsed,
+            direction: SortDirection::Desc,
+            filter: None,
+            provider: None,
+            cursor: None,
+            limit: 2,
+        };
+        let ids = |page: &WorkspacePage| {
+            page.items
+                .iter()
+                .map(|item| item["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let first = run(workspaces.clone(), &query).unwrap();
+        assert_eq!(ids(&first), vec!["a", "b"]);
+        assert_eq!(first.total, 4);
+
+        // Used most recently, so it lands on the page that has been loaded already
+        workspaces.push(workspace("e", "2024-01-05T00:00:00Z"));
+        query.cursor = first.next_cursor;
+        let second = run(workspaces, &query).unwrap();
+        assert_eq!(ids(&second), vec!["c", "d"]);
+        assert_eq!(second.total, 5);
+        assert_eq!(second.next_cursor, None);
+    }
+}
@@ -1,26 +1,46 @@
 use crate::{
-    commands::{list_workspaces::ListWorkspacesCommand, DevpodCommandConfig, DevpodCommandError},
+    audit_log::{self, AuditCategory, AuditInitiator},
+    commands::{
+        delete_workspace::DeleteWorkspaceCommand, list_workspaces::ListWorkspacesCommand,
+        DevpodCommandConfig, DevpodCommandError,
+    },
     custom_protocol::OpenWorkspaceMsg,
     system_tray::{SystemTrayClickHandler, ToSystemTraySubmenu},
 };
-use crate::{system_tray::SystemTray, AppHandle, AppState, UiMessage};
+use crate::{
+    browser_ide, debug_console, dock_menu, environment, home_watcher, jump_list, ssh_config,
+    state_sync::{self, SyncedCollection},
+    system_tray::SystemTray,
+    AppHandle, AppState, UiMessage,
+};
 use chrono::DateTime;
-use log::error;
+use lazy_static::lazy_static;
+use log::{error, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::{
-    sync::{mpsc, Arc},
+    sync::{mpsc, Arc, Mutex},
     thread, time,
 };
-use tauri::{CustomMenuItem, SystemTrayMenu, SystemTrayMenuItem, SystemTraySubmenu};
+use tauri::{CustomMenuItem, Manager, SystemTrayMenu, SystemTrayMenuItem, SystemTraySubmenu};
 use tokio::sync::OnceCell;
+use ts_rs::TS;
 
 static INIT: OnceCell<()> = OnceCell::const_new();
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(1_000);
+// Still catches what the CLI changes without touching the DevPod home, e.g. workspaces stopped by their provider
+const WATCHED_POLL_INTERVAL: time::Duration = time::Duration::from_secs(10);
+// Where the CLI puts the sources inside of the workspace
+const REMOTE_FOLDER_PREFIX: &str = "/workspaces/";
 
 enum Update {
     Workspaces(WorkspacesState),
 }
 
+lazy_static! {
+    static ref UPDATES: Mutex<Option<mpsc::Sender<Update>>> = Mutex::new(None);
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Eq, PartialEq)]
 #[serde(
     transparent,
@@ -40,14 +60,54 @@ impl WorkspacesState {
 }
 
 impl WorkspacesState {
-    pub fn load() -> Result<Self, DevpodCommandError> {
+    pub fn load(app_handle: &AppHandle) -> Result<Self, DevpodCommandError> {
         let list_workspaces_cmd = ListWorkspacesCommand::new();
 
-        list_workspaces_cmd.exec()
+        list_workspaces_cmd.exec(app_handle)
+    }
+
+    pub fn load_with_raw(
+        app_handle: &AppHandle,
+    ) -> Result<(Self, Vec<serde_json::Value>), DevpodCommandError> {
+        ListWorkspacesCommand::new().exec_with_raw(app_handle)
+    }
+}
+
+impl WorkspacesState {
+    #[cfg(feature = "enable-updater")]
+    pub fn is_empty(&self) -> bool {
+        self.workspaces.is_empty()
     }
 }
 
-impl WorkspacesState {}
+impl WorkspacesState {
+    /// Workspace ids, most recently used first.
+    pub fn recent_ids(&self) -> Vec<&str> {
+        let mut workspaces: Vec<&Workspace> = self
+            .workspaces
+            .iter()
+            .filter(|workspace| workspace.id.is_some())
+            .collect();
+        workspaces.sort_by_key(|workspace| {
+            std::cmp::Reverse(workspace.last_used.or(workspace.creation_timestamp))
+        });
+
+        workspaces
+            .into_iter()
+            .filter_map(|workspace| workspace.id.as_deref())
+            .collect()
+    }
+}
+
+impl WorkspacesState {
+    /// Workspace ids along with the context they have been created in.
+    pub fn ids_with_context(&self) -> Vec<(&str, Option<&str>)> {
+        self.workspaces
+            .iter()
+            .filter_map(|workspace| Some((workspace.id.as_deref()?, workspace.context.as_deref())))
+            .collect()
+    }
+}
 
 impl ToSystemTraySubmenu for WorkspacesState {
     fn to_submenu(&self) -> tauri::SystemTraySubmenu {
@@ -106,13 +166,16 @@ impl ToSystemTraySubmenu for WorkspacesState {
 #[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
 struct Workspace {
     id: Option<String>,
+    uid: Option<String>,
     folder: Option<String>,
     provider: Option<WorkspaceProvider>,
     #[serde(rename = "ide")]
     ide_config: Option<WorkspaceIDE>,
     source: Option<WorkspaceSource>,
     creation_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    last_used: Option<chrono::DateTime<chrono::Utc>>,
     context: Option<String>,
+    dev_container_path: Option<String>,
 }
 impl Workspace {
     pub fn id(&self) -> &Option<String> {
@@ -152,23 +215,153 @@ struct ProviderOption {
     retrieved: Option<DateTime<chrono::Utc>>,
 }
 
+/// The folder the sources of the workspace are in, inside of the workspace.
+pub fn remote_folder(id: &str) -> String {
+    format!("{}{}", REMOTE_FOLDER_PREFIX, id)
+}
+
+/// The context the workspace has been created in, `None` for the default context.
+pub fn context(app_handle: &AppHandle, workspace_id: &str) -> Option<String> {
+    let app_state = app_handle.state::<AppState>();
+    let workspaces = app_state.workspaces.lock().unwrap();
+
+    workspaces
+        .ids_with_context()
+        .into_iter()
+        .find(|(id, _)| *id == workspace_id)
+        .and_then(|(_, context)| context.map(String::from))
+}
+
+/// The provider the workspace has been created with.
+pub fn provider(app_handle: &AppHandle, workspace_id: &str) -> Option<String> {
+    let app_state = app_handle.state::<AppState>();
+    let workspaces = app_state.workspaces.lock().unwrap();
+
+    workspaces
+        .workspaces
+        .iter()
+        .find(|workspace| workspace.id.as_deref() == Some(workspace_id))
+        .and_then(|workspace| workspace.provider.as_ref())
+        .and_then(|provider| provider.name.clone())
+}
+
+/// The unique id the CLI gives the workspace on creation, it tells apart workspaces that reuse the id of a deleted
+/// one. Containers of the workspace are labelled with it.
+pub fn uid(app_handle: &AppHandle, workspace_id: &str) -> Option<String> {
+    let app_state = app_handle.state::<AppState>();
+    let workspaces = app_state.workspaces.lock().unwrap();
+
+    workspaces
+        .workspaces
+        .iter()
+        .find(|workspace| workspace.id.as_deref() == Some(workspace_id))
+        .and_then(|workspace| workspace.uid.clone())
+}
+
+/// The local folder the workspace has been created from and the path of its devcontainer config in there, if it
+/// overrides the default one. `None` for workspaces from other sources.
+pub fn local_source(
+    app_handle: &AppHandle,
+    workspace_id: &str,
+) -> Option<(String, Option<String>)> {
+    let app_state = app_handle.state::<AppState>();
+    let workspaces = app_state.workspaces.lock().unwrap();
+
+    workspaces
+        .workspaces
+        .iter()
+        .find(|workspace| workspace.id.as_deref() == Some(workspace_id))
+        .and_then(|workspace| {
+            let folder = workspace.source.as_ref()?.local_folder.clone()?;
+            let devcontainer_path = workspace
+                .dev_container_path
+                .clone()
+                .filter(|path| !path.is_empty());
+
+            Some((folder, devcontainer_path))
+        })
+}
+
+/// Lists the workspaces once and hands them to the tray, the menus and the frontend.
+pub fn refresh(app_handle: &AppHandle) -> Result<(), DevpodCommandError> {
+    let task = debug_console::track_task("workspaces poll", None);
+    // pick up changes to the global environment before talking to the CLI
+    environment::refresh_global(app_handle);
+    let (workspaces, raw) = WorkspacesState::load_with_raw(app_handle)?;
+    drop(task);
+
+    state_sync::update(
+        app_handle,
+        SyncedCollection::Workspaces,
+        state_sync::by_id(raw),
+    );
+    if let Some(tx) = UPDATES.lock().unwrap().as_ref() {
+        let _ = tx.send(Update::Workspaces(workspaces));
+    }
+
+    Ok(())
+}
+
+/// The logs of a deletion, the UI shows them like the ones of the actions it runs itself.
+#[derive(Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct WorkspaceDeletion {
+    success: bool,
+    stdout: Vec<String>,
+    stderr: Vec<String>,
+}
+
+/// Unlike the other workspace actions deletions run in the backend, the app lock can't be skipped this way.
+#[tauri::command]
+pub fn delete_workspace(
+    app_handle: AppHandle,
+    workspace_id: String,
+    force: bool,
+) -> Result<WorkspaceDeletion, DevpodCommandError> {
+    let output = DeleteWorkspaceCommand::new(workspace_id.clone(), force).exec(&app_handle)?;
+    let success = output.status.success();
+    if success {
+        browser_ide::close_workspace_sessions(&app_handle, &workspace_id);
+        audit_log::record(
+            AuditInitiator::Ui,
+            AuditCategory::Delete,
+            "delete workspace",
+            Some(&workspace_id),
+        );
+    }
+
+    Ok(WorkspaceDeletion {
+        success,
+        stdout: output.stdout.lines().map(String::from).collect(),
+        stderr: output.stderr.lines().map(String::from).collect(),
+    })
+}
+
+/// Keeps the workspaces up to date once they've been loaded for the first time.
+pub fn start_polling(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    thread::spawn(move || loop {
+        thread::sleep(if home_watcher::is_active() {
+            WATCHED_POLL_INTERVAL
+        } else {
+            POLL_INTERVAL
+        });
+        if let Err(err) = refresh(&app_handle) {
+            warn!("Failed to list workspaces: {}", err);
+        }
+    });
+}
+
 pub fn setup(app_handle: &AppHandle, state: tauri::State<'_, AppState>) {
     tauri::async_runtime::block_on(async {
         INIT.get_or_init(|| async {
-            let sleep_duration = time::Duration::from_millis(1_000);
             let (tx, rx) = mpsc::channel::<Update>();
-
-            let workspaces_tx = tx;
-
-            thread::spawn(move || loop {
-                let workspaces = WorkspacesState::load().unwrap();
-                workspaces_tx.send(Update::Workspaces(workspaces)).unwrap();
-
-                thread::sleep(sleep_duration);
-            });
+            *UPDATES.lock().unwrap() = Some(tx);
 
             let workspaces_state = Arc::clone(&state.workspaces);
             let tray_handle = app_handle.tray_handle();
+            let update_app_handle = app_handle.clone();
 
             // Handle updates from background threads.
             thread::spawn(move || {
@@ -186,7 +379,11 @@ pub fn setup(app_handle: &AppHandle, state: tauri::State<'_, AppState>) {
                                 tray_handle
                                     .set_menu(new_menu)
                                     .expect("should be able to set menu");
+                                dock_menu::update(&update_app_handle, current_workspaces);
+                                ssh_config::sync(&update_app_handle, current_workspaces);
                             }
+                            // also picks up changes to the pinned workspaces
+                            jump_list::update(&update_app_handle, current_workspaces);
                         }
                     }
                 }
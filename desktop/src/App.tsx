@@ -13,14 +13,28 @@ import {
   useColorModeValue,
   useToken,
 } from "@chakra-ui/react"
-import { useEffect, useMemo } from "react"
-import { Outlet, Link as RouterLink, useMatch, useNavigate, useRouteError } from "react-router-dom"
+import { useEffect, useMemo, useRef } from "react"
+import {
+  Outlet,
+  Link as RouterLink,
+  useLocation,
+  useMatch,
+  useNavigate,
+  useRouteError,
+} from "react-router-dom"
 import { useBorderColor } from "./Theme"
 import { Sidebar, SidebarMenuItem, StatusBar, Toolbar } from "./components"
 import { SIDEBAR_WIDTH, STATUS_BAR_HEIGHT } from "./constants"
-import { ToolbarProvider, useChangeSettings, useSettings } from "./contexts"
+import {
+  ToolbarProvider,
+  useAllWorkspaceActions,
+  useChangeSettings,
+  useSettings,
+  useWorkspaces,
+} from "./contexts"
 import { Briefcase, Cog, Stack3D } from "./icons"
 import { isLinux, isMacOS, isWindows } from "./lib"
+import { client } from "./client"
 import { Routes } from "./routes"
 import { useAppReady } from "./useAppReady"
 import { useWelcomeModal } from "./useWelcomeModal"
@@ -30,7 +44,6 @@ const showDevPodTitle = isMacOS || isLinux
 
 export function App() {
   const { modal: appReadyModal, changelogModal } = useAppReady()
-  const navigate = useNavigate()
   const rootRouteMatch = useMatch(Routes.ROOT)
   const { sidebarPosition } = useSettings()
   const contentBackgroundColor = useColorModeValue("white", "background.darkest")
@@ -49,14 +62,13 @@ export function App() {
     return { templateAreas: `"sidebar main"`, gridTemplateColumns: `${SIDEBAR_WIDTH} 1fr` }
   }, [sidebarPosition])
 
-  useEffect(() => {
-    if (rootRouteMatch !== null) {
-      navigate(Routes.WORKSPACES)
-    }
-  }, [navigate, rootRouteMatch])
+  useRestoredRoute(rootRouteMatch !== null)
 
-  const { modal: welcomeModal } = useWelcomeModal()
+  const { modal: welcomeModal } = useWelcomeModal({ showOnFirstRun: true })
   usePartyParrot()
+  useDebugConsoleShortcut()
+  useUrlDrop()
+  useDockBadge()
 
   return (
     <>
@@ -184,3 +196,103 @@ function usePartyParrot() {
     return () => document.addEventListener("keyup", handler)
   }, [setSettings, settings.partyParrot])
 }
+
+// Picks up where the window was when the app last quit, and remembers where it goes from now on
+function useRestoredRoute(isRoot: boolean) {
+  const navigate = useNavigate()
+  const { pathname } = useLocation()
+  const isRestoredRef = useRef(false)
+
+  useEffect(() => {
+    if (!isRoot) {
+      return
+    }
+
+    if (isRestoredRef.current) {
+      navigate(Routes.WORKSPACES)
+
+      return
+    }
+    isRestoredRef.current = true
+    client.getWindowUiState().then(({ route }) => navigate(route ?? Routes.WORKSPACES))
+  }, [isRoot, navigate])
+
+  useEffect(() => {
+    if (pathname !== Routes.ROOT) {
+      client.setWindowRoute(pathname)
+    }
+  }, [pathname])
+}
+
+function useDebugConsoleShortcut() {
+  useEffect(() => {
+    const handler = (event: KeyboardEvent) => {
+      if (event.shiftKey && (event.ctrlKey || event.metaKey) && event.key.toLowerCase() === "d") {
+        client.toggleDebugConsole()
+      }
+    }
+    document.addEventListener("keyup", handler)
+
+    return () => document.removeEventListener("keyup", handler)
+  }, [])
+}
+
+// Shows workspace activity on the dock icon or taskbar button, visible without opening the window
+function useDockBadge() {
+  const workspaces = useWorkspaces()
+  const { active, history } = useAllWorkspaceActions()
+
+  const running = workspaces.filter((workspace) => workspace.status === "Running").length
+  // Status checks fail whenever a provider is slow, only actions of the user count
+  const failed = workspaces.filter((workspace) => {
+    if (active.some((action) => action.targetID === workspace.id)) {
+      return false
+    }
+    const lastAction = history.find(
+      (action) => action.targetID === workspace.id && action.name !== "checkStatus"
+    )
+
+    return lastAction?.status === "error"
+  }).length
+
+  useEffect(() => {
+    client.setDockBadge({ running, failed })
+  }, [running, failed])
+}
+
+// Files and folders are handled by the window directly, only links and text end up in the webview
+function useUrlDrop() {
+  useEffect(() => {
+    const isEditable = (target: EventTarget | null) =>
+      target instanceof HTMLInputElement || target instanceof HTMLTextAreaElement
+
+    const handleDragOver = (event: DragEvent) => {
+      if (!isEditable(event.target)) {
+        event.preventDefault()
+      }
+    }
+    const handleDrop = (event: DragEvent) => {
+      if (isEditable(event.target) || event.dataTransfer === null) {
+        return
+      }
+      event.preventDefault()
+
+      const data =
+        event.dataTransfer.getData("text/uri-list") || event.dataTransfer.getData("text/plain")
+      const items = data
+        .split(/\r?\n/)
+        .map((line) => line.trim())
+        .filter((line) => line !== "" && !line.startsWith("#"))
+      if (items.length > 0) {
+        client.dropItems(items)
+      }
+    }
+    document.addEventListener("dragover", handleDragOver)
+    document.addEventListener("drop", handleDrop)
+
+    return () => {
+      document.removeEventListener("dragover", handleDragOver)
+      document.removeEventListener("drop", handleDrop)
+    }
+  }, [])
+}
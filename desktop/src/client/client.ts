@@ -12,7 +12,6 @@ import {
   updater,
   window as tauriWindow,
 } from "@tauri-apps/api"
-import { Command } from "@tauri-apps/api/shell"
 import { TSettings } from "../contexts"
 import { Result, Return, isError, noop } from "../lib"
 import { TCommunityContributions, TUnsubscribeFn } from "../types"
@@ -21,8 +20,74 @@ import { IDEsClient } from "./ides"
 import { ProvidersClient } from "./providers"
 import { WorkspacesClient } from "./workspaces"
 import { UseToastOptions } from "@chakra-ui/react"
-import { Release } from "../gen"
+import {
+  ActionOrigin,
+  AuditCategory,
+  AuditEntry,
+  AuditLogFilter,
+  AutostartStatus,
+  BrowserIde,
+  BrowserIdeSession,
+  CliStatus,
+  CodespacesCompatibility,
+  Connection,
+  ConnectionEvent,
+  ConnectionProxies,
+  ConnectionProxy,
+  ConnectionProxyPreset,
+  ConnectionProxyTest,
+  CrashReportPreview,
+  CredentialDecision,
+  CredentialRequest,
+  DebugSnapshot,
+  DeepLinkRegistration,
+  DevcontainerDiagnostic,
+  DiskSpaceStatus,
+  DockBadge,
+  DoctorReport,
+  GitCredential,
+  GitpodImport,
+  JumpHost,
+  JumpHostScope,
+  JumpHosts,
+  NetworkStatus,
+  OnboardingStep,
+  OnboardingStepId,
+  PortDecision,
+  PortForward,
+  PortForwardStatus,
+  QueueStatus,
+  LogLevels,
+  LogStreamEvent,
+  LogStreamFilter,
+  LogWindow,
+  Release,
+  ResumableKind,
+  RunningProcess,
+  ScheduledTask,
+  SecretKind,
+  Shell,
+  ShellCompletion,
+  ShutdownProgress,
+  StartupData,
+  StartupDataEvent,
+  StartupProfile,
+  SshKey,
+  SshKeyType,
+  StatePatch,
+  StateSnapshot,
+  StatusWidgetSnapshot,
+  Subsystem,
+  SyncedCollection,
+  TerminalSession,
+  WindowUiState,
+  WorkspaceHostnames,
+  WorkspaceMetrics,
+  WorkspacePage,
+  WorkspaceQuery,
+} from "../gen"
 import { ProClient } from "./pro"
+import { decodePayload, negotiatePayloadEncoding } from "./payloads"
 import { Theme as TauriTheme } from "@tauri-apps/api/window"
 
 // These types have to match the rust types! Make sure to update them as well!
@@ -35,13 +100,35 @@ type TChannels = {
         status: NonNullable<UseToastOptions["status"]>
       }>
     | Readonly<{ type: "ShowDashboard" }>
+    | Readonly<{ type: "ShowSettings"; provider: string | null }>
     | Readonly<{ type: "CommandFailed" }>
+    | Readonly<{ type: "ShowDiskCleanup" }>
+    | Readonly<{ type: "CliTampered"; path: string; can_repair: boolean }>
+    | Readonly<{ type: "CredentialRequested" } & CredentialRequest>
+    | Readonly<{ type: "ClipboardUrl"; source: string; url: string }>
+    | Readonly<{ type: "PortDetected"; workspace_id: string; port: number }>
+    | Readonly<{
+        type: "OperationInterrupted"
+        operation_id: string
+        kind: ResumableKind
+        workspace_id: string
+        workspace_state: string | null
+        can_roll_back: boolean
+      }>
     | Readonly<{
         type: "OpenWorkspace"
         workspace_id: string | null
         provider_id: string | null
         ide: string | null
         source: string
+        devcontainer_path: string | null
+        vscode_profile: string | null
+        reuse_window: boolean | null
+        ide_suggested: boolean
+        custom_ide: boolean
+        origin: ActionOrigin
+        consent_domain: string | null
+        trusted: boolean
       }>
     | Readonly<{
         type: "ImportWorkspace"
@@ -49,7 +136,38 @@ type TChannels = {
         workspace_uid: string
         devpod_pro_host: string
         options: Record<string, string> | null
+        origin: ActionOrigin
+        consent_domain: string | null
+        trusted: boolean
       }>
+  // WARN: needs to match the event name in `debug_console.rs`
+  debug_console_snapshot: DebugSnapshot
+  // WARN: needs to match the event name in `network.rs`
+  network_status: NetworkStatus
+  port_forwards: readonly PortForwardStatus[]
+  connections: ConnectionEvent
+  browser_ide_sessions: readonly BrowserIdeSession[]
+  // WARN: needs to match the event name in `connections.rs`
+  connection_metrics: readonly WorkspaceMetrics[]
+  // WARN: needs to match the event names in `terminal.rs`
+  terminal_output: Readonly<{ sessionId: string; data: string }>
+  terminal_sessions: readonly TerminalSession[]
+  // WARN: needs to match the event name in `shutdown.rs`
+  shutdown_progress: ShutdownProgress
+  // WARN: needs to match the event name in `command_queue.rs`
+  queue_changed: QueueStatus
+  // WARN: needs to match the event name in `log_stream.rs`
+  log_stream: LogStreamEvent
+  // WARN: needs to match the event name in `log_windows.rs`
+  log_windows_changed: readonly LogWindow[]
+  // WARN: needs to match the event name in `status_widget.rs`
+  status_widget_snapshot: StatusWidgetSnapshot
+  // WARN: needs to match the event name in `state_sync.rs`
+  state_patch: StatePatch
+  // WARN: needs to match the event name in `subsystems.rs`
+  subsystem_ready: Subsystem
+  // WARN: needs to match the event name in `startup.rs`
+  startup_data: StartupDataEvent
 }
 type TChannelName = keyof TChannels
 type TClientEventListener<TChannel extends TChannelName> = (payload: TChannels[TChannel]) => void
@@ -64,6 +182,12 @@ class Client {
   public readonly context = new ContextClient()
   public readonly pro = new ProClient()
 
+  constructor() {
+    negotiatePayloadEncoding().catch((err) => {
+      console.error("Failed to negotiate payload encoding", err)
+    })
+  }
+
   public setSetting<TSettingName extends keyof TClientSettings>(
     name: TSettingName,
     value: TSettings[TSettingName]
@@ -80,14 +204,44 @@ class Client {
     return invoke("ui_ready")
   }
 
+  public async showSettings(provider?: string): Promise<void> {
+    await invoke("show_settings", { provider: provider ?? null })
+  }
+
+  public getNetworkStatus(): Promise<NetworkStatus> {
+    return invoke("get_network_status")
+  }
+
+  public getDeepLinkRegistration(): Promise<DeepLinkRegistration | null> {
+    return invoke("get_deep_link_registration")
+  }
+
+  public async decideLinkAction(
+    action: string,
+    domain: string | null,
+    allowed: boolean,
+    remember: boolean
+  ): Promise<void> {
+    await invoke("decide_link_action", { action, domain, allowed, remember })
+  }
+
   public async subscribe<T extends TChannelName>(
     channel: T,
     listener: TClientEventListener<T>
   ): Promise<TUnsubscribeFn> {
     // `TClient` is strictly typed so we're fine casting the response as `any`.
     try {
+      // Binary payloads have to be fetched, the events after them wait so that they arrive in order
+      let pending = Promise.resolve()
       const unsubscribe = await event.listen<any>(channel, (event) => {
-        listener(event.payload)
+        pending = pending
+          .then(() => decodePayload(event.payload))
+          .then(
+            // Not waiting for async listeners, only the decoding is ordered
+            (payload) => void listener(payload as any),
+            (err) => console.error(`Failed to fetch ${channel} payload`, err)
+          )
+          .catch((err) => console.error(err))
       })
 
       return unsubscribe
@@ -194,14 +348,819 @@ class Client {
       return Return.Failed("Unable to install CLI")
     }
   }
-  public async isCLIInstalled(): Promise<Result<boolean>> {
+
+  public async uninstallCLI(force: boolean = false): Promise<Result<void>> {
     try {
-      const result = await new Command("run-path-devpod-cli", ["version"]).execute()
-      if (result.code !== 0) {
-        return Return.Value(false)
+      await invoke("uninstall_cli", { force })
+
+      return Return.Ok()
+    } catch (e) {
+      if (isError(e)) {
+        return Return.Failed(e.message)
       }
 
-      return Return.Value(true)
+      if (typeof e === "string") {
+        return Return.Failed(`Failed to uninstall CLI: ${e}`)
+      }
+
+      return Return.Failed("Unable to uninstall CLI")
+    }
+  }
+
+  public async setLogLevel(
+    level: string | null,
+    module: string | null = null
+  ): Promise<Result<LogLevels>> {
+    try {
+      const levels = await invoke<LogLevels>("set_log_level", { level, module })
+
+      return Return.Value(levels)
+    } catch (e) {
+      return Return.Failed(`Failed to set log level: ${e}`)
+    }
+  }
+
+  public async getCrashReports(): Promise<Result<readonly CrashReportPreview[]>> {
+    try {
+      const reports = await invoke<CrashReportPreview[]>("get_crash_reports")
+
+      return Return.Value(reports)
+    } catch (e) {
+      return Return.Failed(`Unable to read crash reports: ${e}`)
+    }
+  }
+
+  public async sendCrashReports(ids: readonly string[]): Promise<Result<void>> {
+    try {
+      await invoke("send_crash_reports", { ids })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`Failed to send crash reports: ${e}`)
+    }
+  }
+
+  public async dismissCrashReports(ids: readonly string[]): Promise<Result<void>> {
+    try {
+      await invoke("dismiss_crash_reports", { ids })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`Failed to dismiss crash reports: ${e}`)
+    }
+  }
+
+  public async runDoctor(): Promise<Result<DoctorReport>> {
+    try {
+      const report = await invoke<DoctorReport>("run_doctor")
+
+      return Return.Value(report)
+    } catch (e) {
+      return Return.Failed(`Failed to run doctor: ${e}`)
+    }
+  }
+
+  public isOnboardingCompleted(): Promise<boolean> {
+    return invoke("is_onboarding_completed")
+  }
+
+  public async getOnboardingSteps(): Promise<Result<readonly OnboardingStep[]>> {
+    try {
+      const steps = await invoke<readonly OnboardingStep[]>("get_onboarding_steps")
+
+      return Return.Value(steps)
+    } catch (e) {
+      return Return.Failed(`Failed to check prerequisites: ${e}`)
+    }
+  }
+
+  public async fixOnboardingStep(step: OnboardingStepId): Promise<Result<OnboardingStep>> {
+    try {
+      return Return.Value(await invoke<OnboardingStep>("fix_onboarding_step", { step }))
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async skipOnboardingStep(step: OnboardingStepId): Promise<void> {
+    await invoke("skip_onboarding_step", { step })
+  }
+
+  public async completeOnboarding(): Promise<void> {
+    await invoke("complete_onboarding")
+  }
+
+  public async toggleDebugConsole(): Promise<void> {
+    await invoke("toggle_debug_console")
+  }
+
+  public async getDebugSnapshot(): Promise<Result<DebugSnapshot>> {
+    try {
+      const snapshot = await invoke<DebugSnapshot>("get_debug_snapshot")
+
+      return Return.Value(snapshot)
+    } catch (e) {
+      return Return.Failed(`Failed to get debug snapshot: ${e}`)
+    }
+  }
+
+  public async getAutostart(): Promise<Result<AutostartStatus>> {
+    try {
+      const status = await invoke<AutostartStatus>("get_autostart")
+
+      return Return.Value(status)
+    } catch (e) {
+      return Return.Failed(`Failed to get autostart status: ${e}`)
+    }
+  }
+
+  public async setAutostart(enabled: boolean, minimized: boolean): Promise<Result<AutostartStatus>> {
+    try {
+      const status = await invoke<AutostartStatus>("set_autostart", { enabled, minimized })
+
+      return Return.Value(status)
+    } catch (e) {
+      return Return.Failed(`Failed to update autostart: ${e}`)
+    }
+  }
+
+  public async getContextMenu(): Promise<Result<boolean>> {
+    try {
+      const enabled = await invoke<boolean>("get_context_menu")
+
+      return Return.Value(enabled)
+    } catch (e) {
+      return Return.Failed(`Failed to get context menu status: ${e}`)
+    }
+  }
+
+  public async setContextMenu(enabled: boolean): Promise<Result<boolean>> {
+    try {
+      const status = await invoke<boolean>("set_context_menu", { enabled })
+
+      return Return.Value(status)
+    } catch (e) {
+      return Return.Failed(`Failed to update context menu: ${e}`)
+    }
+  }
+
+  public getSshConfigManagement(): Promise<boolean> {
+    return invoke("get_ssh_config_management")
+  }
+
+  public async setSshConfigManagement(enabled: boolean): Promise<Result<boolean>> {
+    try {
+      const status = await invoke<boolean>("set_ssh_config_management", { enabled })
+
+      return Return.Value(status)
+    } catch (e) {
+      return Return.Failed(`Failed to update SSH config: ${e}`)
+    }
+  }
+
+  public async previewSshConfig(): Promise<Result<string>> {
+    try {
+      const config = await invoke<string>("preview_ssh_config")
+
+      return Return.Value(config)
+    } catch (e) {
+      return Return.Failed(`Failed to render SSH config: ${e}`)
+    }
+  }
+
+  public getJumpHosts(): Promise<JumpHosts> {
+    return invoke("get_jump_hosts")
+  }
+
+  public async setJumpHost(
+    scope: JumpHostScope,
+    id: string,
+    jumpHost: JumpHost | null
+  ): Promise<Result<JumpHosts>> {
+    try {
+      const jumpHosts = await invoke<JumpHosts>("set_jump_host", { scope, id, jumpHost })
+
+      return Return.Value(jumpHosts)
+    } catch (e) {
+      return Return.Failed(`Failed to set jump host: ${e}`)
+    }
+  }
+
+  public getConnectionProxies(): Promise<ConnectionProxies> {
+    return invoke("get_connection_proxies")
+  }
+
+  public listConnectionProxyPresets(): Promise<readonly ConnectionProxyPreset[]> {
+    return invoke("list_connection_proxy_presets")
+  }
+
+  public async setConnectionProxy(
+    provider: string | null,
+    proxy: ConnectionProxy | null
+  ): Promise<Result<ConnectionProxies>> {
+    try {
+      const proxies = await invoke<ConnectionProxies>("set_connection_proxy", { provider, proxy })
+
+      return Return.Value(proxies)
+    } catch (e) {
+      return Return.Failed(`Failed to set connection proxy: ${e}`)
+    }
+  }
+
+  public async testConnectionProxy(
+    proxy: ConnectionProxy,
+    host: string,
+    port: number | null
+  ): Promise<Result<ConnectionProxyTest>> {
+    try {
+      const test = await invoke<ConnectionProxyTest>("test_connection_proxy", { proxy, host, port })
+
+      return Return.Value(test)
+    } catch (e) {
+      return Return.Failed(`Failed to test connection proxy: ${e}`)
+    }
+  }
+
+  public getWorkspaceHostnames(): Promise<WorkspaceHostnames> {
+    return invoke("get_workspace_hostnames")
+  }
+
+  public async setWorkspaceHostnamePort(
+    workspaceID: string,
+    port: number | null
+  ): Promise<Result<WorkspaceHostnames>> {
+    try {
+      const hostnames = await invoke<WorkspaceHostnames>("set_workspace_hostname_port", {
+        workspaceId: workspaceID,
+        port,
+      })
+
+      return Return.Value(hostnames)
+    } catch (e) {
+      return Return.Failed(`Failed to set hostname port of ${workspaceID}: ${e}`)
+    }
+  }
+
+  public async getWorkspaceUrl(workspaceID: string): Promise<Result<string>> {
+    try {
+      const url = await invoke<string>("get_workspace_url", { workspaceId: workspaceID })
+
+      return Return.Value(url)
+    } catch (e) {
+      return Return.Failed(`Failed to get URL of ${workspaceID}: ${e}`)
+    }
+  }
+
+  public listPortForwards(): Promise<readonly PortForwardStatus[]> {
+    return invoke("list_port_forwards")
+  }
+
+  public async forwardPort(
+    workspaceId: string,
+    localPort: number,
+    remotePort: number
+  ): Promise<Result<readonly PortForwardStatus[]>> {
+    try {
+      const forwards = await invoke<readonly PortForwardStatus[]>("forward_port", {
+        workspaceId,
+        localPort,
+        remotePort,
+      })
+
+      return Return.Value(forwards)
+    } catch (e) {
+      return Return.Failed(`Failed to forward port ${remotePort}: ${e}`)
+    }
+  }
+
+  public async exposePort(
+    workspaceId: string,
+    localPort: number,
+    remotePort: number
+  ): Promise<Result<readonly PortForwardStatus[]>> {
+    try {
+      const forwards = await invoke<readonly PortForwardStatus[]>("expose_port", {
+        workspaceId,
+        localPort,
+        remotePort,
+      })
+
+      return Return.Value(forwards)
+    } catch (e) {
+      return Return.Failed(`Failed to expose port ${localPort}: ${e}`)
+    }
+  }
+
+  public async unforwardPort(
+    forward: PortForward
+  ): Promise<Result<readonly PortForwardStatus[]>> {
+    try {
+      const forwards = await invoke<readonly PortForwardStatus[]>("unforward_port", { forward })
+
+      return Return.Value(forwards)
+    } catch (e) {
+      return Return.Failed(`Failed to stop forwarding port ${forward.localPort}: ${e}`)
+    }
+  }
+
+  public reconnectPortForwards(workspaceID: string | null): Promise<readonly PortForwardStatus[]> {
+    return invoke("reconnect_port_forwards", { workspaceId: workspaceID })
+  }
+
+  public async decideDetectedPort(
+    workspaceId: string,
+    port: number,
+    forward: boolean,
+    remember: boolean
+  ): Promise<Result<void>> {
+    try {
+      await invoke("decide_detected_port", { workspaceId, port, forward, remember })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`Failed to forward port ${port}: ${e}`)
+    }
+  }
+
+  public getPortDecisions(workspaceId: string): Promise<Readonly<Record<number, PortDecision>>> {
+    return invoke("get_port_decisions", { workspaceId })
+  }
+
+  public async setPortDecision(
+    workspaceId: string,
+    port: number,
+    decision: PortDecision | null
+  ): Promise<Result<void>> {
+    try {
+      await invoke("set_port_decision", { workspaceId, port, decision })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`Failed to save decision for port ${port}: ${e}`)
+    }
+  }
+
+  public getConnections(): Promise<readonly Connection[]> {
+    return invoke("get_connections")
+  }
+
+  public getConnectionMetrics(): Promise<readonly WorkspaceMetrics[]> {
+    return invoke("get_connection_metrics")
+  }
+
+  public listBrowserIdeSessions(): Promise<readonly BrowserIdeSession[]> {
+    return invoke("list_browser_ide_sessions")
+  }
+
+  public async openBrowserIde(
+    workspaceId: string,
+    ide: BrowserIde
+  ): Promise<Result<BrowserIdeSession>> {
+    try {
+      const session = await invoke<BrowserIdeSession>("open_browser_ide", { workspaceId, ide })
+
+      return Return.Value(session)
+    } catch (e) {
+      return Return.Failed(`Failed to open ${ide}: ${e}`)
+    }
+  }
+
+  public async closeBrowserIde(workspaceId: string, ide: BrowserIde): Promise<Result<void>> {
+    try {
+      await invoke("close_browser_ide", { workspaceId, ide })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`Failed to close ${ide}: ${e}`)
+    }
+  }
+
+  public listTerminals(): Promise<readonly TerminalSession[]> {
+    return invoke("list_terminals")
+  }
+
+  public async openTerminal(
+    workspaceId: string,
+    cols?: number,
+    rows?: number
+  ): Promise<Result<TerminalSession>> {
+    try {
+      const session = await invoke<TerminalSession>("open_terminal", { workspaceId, cols, rows })
+
+      return Return.Value(session)
+    } catch (e) {
+      return Return.Failed(`Failed to open terminal: ${e}`)
+    }
+  }
+
+  public writeTerminal(sessionId: string, data: string): Promise<void> {
+    return invoke("write_terminal", { sessionId, data })
+  }
+
+  public resizeTerminal(sessionId: string, cols: number, rows: number): Promise<void> {
+    return invoke("resize_terminal", { sessionId, cols, rows })
+  }
+
+  public getTerminalScrollback(sessionId: string): Promise<string> {
+    return invoke("get_terminal_scrollback", { sessionId })
+  }
+
+  public closeTerminal(sessionId: string): Promise<void> {
+    return invoke("close_terminal", { sessionId })
+  }
+
+  public listProcesses(): Promise<readonly RunningProcess[]> {
+    return invoke("list_processes")
+  }
+
+  public cancelOperation(operationId: string): Promise<readonly RunningProcess[]> {
+    return invoke("cancel_operation", { operationId })
+  }
+
+  public getCommandQueue(): Promise<QueueStatus> {
+    return invoke("get_command_queue")
+  }
+
+  public streamLogs(
+    streamID: string,
+    filter: LogStreamFilter | null,
+    workspaceID: string | null
+  ): Promise<void> {
+    return invoke("stream_logs", { streamId: streamID, filter, workspaceId: workspaceID })
+  }
+
+  public async openLogWindow(workspaceID: string): Promise<Result<LogWindow>> {
+    try {
+      return Return.Value(await invoke<LogWindow>("open_log_window", { workspaceId: workspaceID }))
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public closeLogWindow(workspaceID: string): Promise<void> {
+    return invoke("close_log_window", { workspaceId: workspaceID })
+  }
+
+  public listLogWindows(): Promise<readonly LogWindow[]> {
+    return invoke("list_log_windows")
+  }
+
+  public async getLogWindow(): Promise<Result<LogWindow>> {
+    try {
+      return Return.Value(await invoke<LogWindow>("get_log_window"))
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public setLogWindowFilter(filter: LogStreamFilter): Promise<void> {
+    return invoke("set_log_window_filter", { filter })
+  }
+
+  public async toggleStatusWidget(): Promise<void> {
+    await invoke("toggle_status_widget")
+  }
+
+  public getStatusWidgetSnapshot(): Promise<StatusWidgetSnapshot> {
+    return invoke("get_status_widget_snapshot")
+  }
+
+  public async openWidgetWorkspace(workspaceID: string): Promise<void> {
+    await invoke("open_widget_workspace", { workspaceId: workspaceID })
+  }
+
+  public async stopWidgetWorkspace(workspaceID: string): Promise<void> {
+    await invoke("stop_widget_workspace", { workspaceId: workspaceID })
+  }
+
+  public getStateSnapshot(collection: SyncedCollection): Promise<StateSnapshot> {
+    return invoke("get_state_snapshot", { collection })
+  }
+
+  public async queryWorkspaces(query: WorkspaceQuery): Promise<Result<WorkspacePage>> {
+    try {
+      const page = await invoke<WorkspacePage>("query_workspaces", { query })
+
+      return Return.Value(page)
+    } catch (e) {
+      if (isError(e)) {
+        return Return.Failed(e.message)
+      }
+
+      const errMsg = "Unable to query workspaces"
+      if (typeof e === "string") {
+        return Return.Failed(`${errMsg}: ${e}`)
+      }
+
+      return Return.Failed(errMsg)
+    }
+  }
+
+  public getLoadedStartupData(): Promise<readonly StartupData[]> {
+    return invoke("get_loaded_startup_data")
+  }
+
+  public getStartupProfile(): Promise<StartupProfile> {
+    return invoke("get_startup_profile")
+  }
+
+  public getAppLock(): Promise<boolean> {
+    return invoke("get_app_lock")
+  }
+
+  public async setAppLock(enabled: boolean): Promise<Result<boolean>> {
+    try {
+      const isEnabled = await invoke<boolean>("set_app_lock", { enabled })
+
+      return Return.Value(isEnabled)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async parseDevcontainer(content: string): Promise<Result<Record<string, unknown>>> {
+    try {
+      const config = await invoke<Record<string, unknown>>("parse_devcontainer", { content })
+
+      return Return.Value(config)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public validateDevcontainer(content: string): Promise<readonly DevcontainerDiagnostic[]> {
+    return invoke("validate_devcontainer", { content })
+  }
+
+  public checkCodespacesCompatibility(
+    source: string,
+    devcontainerPath: string | undefined,
+    provider: string | undefined
+  ): Promise<CodespacesCompatibility | null> {
+    return invoke("check_codespaces_compatibility", { source, devcontainerPath, provider })
+  }
+
+  public async importGitpodWorkspace(folder: string): Promise<Result<GitpodImport | null>> {
+    try {
+      const gitpodImport = await invoke<GitpodImport | null>("import_gitpod_workspace", { folder })
+
+      return Return.Value(gitpodImport)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public getCredentialDecisions(): Promise<readonly CredentialDecision[]> {
+    return invoke("get_credential_decisions")
+  }
+
+  public async decideCredentialRequest(
+    request: CredentialRequest,
+    allowed: boolean
+  ): Promise<Result<void>> {
+    try {
+      await invoke("decide_credential_request", { request, allowed })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async forgetCredentialDecisions(): Promise<Result<void>> {
+    try {
+      await invoke("forget_credential_decisions")
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public getGitCredentials(): Promise<readonly GitCredential[]> {
+    return invoke("get_git_credentials")
+  }
+
+  public async setGitCredential(
+    host: string,
+    username: string | null,
+    token: string
+  ): Promise<Result<void>> {
+    try {
+      await invoke("set_git_credential", { host, username, token })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async deleteGitCredential(host: string): Promise<Result<void>> {
+    try {
+      await invoke("delete_git_credential", { host })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async repairCli(): Promise<Result<CliStatus>> {
+    try {
+      const status = await invoke<CliStatus>("repair_cli")
+
+      return Return.Value(status)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public getReadySubsystems(): Promise<readonly Subsystem[]> {
+    return invoke("get_ready_subsystems")
+  }
+
+  public async ensureSubsystem(subsystem: Subsystem): Promise<void> {
+    await invoke("ensure_subsystem", { subsystem })
+  }
+
+  public async setDockBadge(badge: DockBadge): Promise<void> {
+    await invoke("set_dock_badge", { badge })
+  }
+
+  public getWindowUiState(): Promise<WindowUiState> {
+    return invoke("get_window_ui_state")
+  }
+
+  public async setWindowRoute(route: string): Promise<void> {
+    await invoke("set_window_route", { route })
+  }
+
+  public async setWindowUiState(key: string, value: unknown): Promise<void> {
+    await invoke("set_window_ui_state", { key, value })
+  }
+
+  public listScheduledTasks(): Promise<readonly ScheduledTask[]> {
+    return invoke("list_scheduled_tasks")
+  }
+
+  public async setTaskSchedule(
+    name: string,
+    schedule: string | null
+  ): Promise<Result<ScheduledTask>> {
+    try {
+      return Return.Value(await invoke<ScheduledTask>("set_task_schedule", { name, schedule }))
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async getDiskSpace(): Promise<Result<DiskSpaceStatus>> {
+    try {
+      const status = await invoke<DiskSpaceStatus>("get_disk_space")
+
+      return Return.Value(status)
+    } catch (e) {
+      return Return.Failed(`Failed to get disk space: ${e}`)
+    }
+  }
+
+  public async pruneDocker(): Promise<Result<string>> {
+    try {
+      const reclaimed = await invoke<string>("prune_docker")
+
+      return Return.Value(reclaimed)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async getShellCompletions(): Promise<Result<readonly ShellCompletion[]>> {
+    try {
+      const completions = await invoke<ShellCompletion[]>("get_shell_completions")
+
+      return Return.Value(completions)
+    } catch (e) {
+      return Return.Failed(`Failed to get shell completions: ${e}`)
+    }
+  }
+
+  public async installShellCompletions(
+    shells: readonly Shell[] = []
+  ): Promise<Result<readonly ShellCompletion[]>> {
+    try {
+      const completions = await invoke<ShellCompletion[]>("install_shell_completions", { shells })
+
+      return Return.Value(completions)
+    } catch (e) {
+      return Return.Failed(`Failed to install shell completions: ${e}`)
+    }
+  }
+
+  public async dropItems(items: readonly string[]): Promise<void> {
+    await invoke("drop_items", { items })
+  }
+
+  public async setSecret(kind: SecretKind, account: string, value: string): Promise<Result<void>> {
+    try {
+      await invoke("set_secret", { kind, account, value })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`Failed to store secret: ${e}`)
+    }
+  }
+
+  public async deleteSecret(kind: SecretKind, account: string): Promise<Result<void>> {
+    try {
+      await invoke("delete_secret", { kind, account })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`Failed to delete secret: ${e}`)
+    }
+  }
+
+  public async hasSecret(kind: SecretKind, account: string): Promise<Result<boolean>> {
+    try {
+      const exists = await invoke<boolean>("has_secret", { kind, account })
+
+      return Return.Value(exists)
+    } catch (e) {
+      return Return.Failed(`Failed to look up secret: ${e}`)
+    }
+  }
+
+  public async recordAuditEvent(
+    category: AuditCategory,
+    action: string,
+    target: string | null = null
+  ): Promise<void> {
+    return invoke("record_audit_event", { category, action, target })
+  }
+
+  public async queryAuditLog(
+    filter: Partial<AuditLogFilter> = {}
+  ): Promise<Result<readonly AuditEntry[]>> {
+    try {
+      const entries = await invoke<AuditEntry[]>("query_audit_log", {
+        filter: { from: null, to: null, category: null, initiator: null, limit: null, ...filter },
+      })
+
+      return Return.Value(entries)
+    } catch (e) {
+      return Return.Failed(`Unable to query audit log: ${e}`)
+    }
+  }
+
+  public async generateSshKey(
+    workspaceId: string | null,
+    keyType: SshKeyType | null,
+    passphrase: string | null
+  ): Promise<Result<SshKey>> {
+    try {
+      const key = await invoke<SshKey>("generate_ssh_key", { workspaceId, keyType, passphrase })
+
+      return Return.Value(key)
+    } catch (e) {
+      return Return.Failed(`Failed to generate SSH key: ${e}`)
+    }
+  }
+
+  public async listSshKeys(): Promise<Result<readonly SshKey[]>> {
+    try {
+      const keys = await invoke<readonly SshKey[]>("list_ssh_keys")
+
+      return Return.Value(keys)
+    } catch (e) {
+      return Return.Failed(`Failed to list SSH keys: ${e}`)
+    }
+  }
+
+  public async removeSshKey(workspaceId: string | null): Promise<Result<void>> {
+    try {
+      await invoke("remove_ssh_key", { workspaceId })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`Failed to remove SSH key: ${e}`)
+    }
+  }
+
+  public async exportSshPublicKey(workspaceId: string | null): Promise<Result<string>> {
+    try {
+      const publicKey = await invoke<string>("export_ssh_public_key", { workspaceId })
+
+      return Return.Value(publicKey)
+    } catch (e) {
+      return Return.Failed(`Failed to export SSH key: ${e}`)
+    }
+  }
+
+  public async isCLIInstalled(): Promise<Result<boolean>> {
+    try {
+      const isInstalled = await invoke<boolean>("is_cli_installed")
+
+      return Return.Value(isInstalled)
     } catch {
       return Return.Value(false)
     }
@@ -213,7 +1172,7 @@ class Client {
 
   public async quit(): Promise<Result<void>> {
     try {
-      await process.exit(0)
+      await invoke("quit_app")
 
       return Return.Ok()
     } catch {
@@ -284,6 +1243,10 @@ class Client {
     }
   }
 
+  public async quitNow(): Promise<void> {
+    await invoke("quit_now")
+  }
+
   public async restart(): Promise<void> {
     await process.relaunch()
   }
@@ -1,9 +1,14 @@
-import { ChildProcess, Command as ShellCommand, EventEmitter } from "@tauri-apps/api/shell"
+import { event, invoke } from "@tauri-apps/api"
+import { ChildProcess, EventEmitter } from "@tauri-apps/api/shell"
+import { CliOutput, CliOutputLine } from "../gen"
 import { debug, isError } from "../lib"
 import { Result, ResultError, Return } from "../lib/result"
-import { DEVPOD_BINARY, DEVPOD_FLAG_OPTION, DEVPOD_UI_ENV_VAR } from "./constants"
+import { DEVPOD_FLAG_OPTION } from "./constants"
 import { TStreamEvent } from "./types"
 
+// WARN: needs to match the event name in `cli_runner.rs`
+const CLI_OUTPUT_EVENT = "cli_output"
+
 export type TStreamEventListenerFn = (event: TStreamEvent) => void
 export type TEventListener<TEventName extends string> = Parameters<
   EventEmitter<TEventName>["addListener"]
@@ -13,17 +18,22 @@ export type TCommand<T> = {
   run(): Promise<Result<T>>
   stream(listener: TStreamEventListenerFn): Promise<ResultError>
 }
+export type TCommandContext = Readonly<{ workspaceID?: string; operationID?: string }>
+
+let nextStreamID = 0
 
 export class Command implements TCommand<ChildProcess> {
-  private sidecarCommand
   private args: string[]
+  private context: TCommandContext
 
-  constructor(args: string[]) {
+  constructor(args: string[], context: TCommandContext = {}) {
     debug("commands", "Creating Devpod command with args: ", args)
-    this.sidecarCommand = ShellCommand.sidecar(DEVPOD_BINARY, args, {
-      env: { [DEVPOD_UI_ENV_VAR]: "true" },
-    })
     this.args = args
+    this.context = context
+  }
+
+  public getArgs(): readonly string[] {
+    return this.args
   }
 
   public withConversion<T>(convert: (childProcess: ChildProcess) => Result<T>): TCommand<T> {
@@ -36,13 +46,14 @@ export class Command implements TCommand<ChildProcess> {
 
         return convert(result.val)
       },
-      stream: this.stream,
+      stream: (listener) => this.stream(listener),
     }
   }
 
   public async run(): Promise<Result<ChildProcess>> {
     try {
-      const rawResult = await this.sidecarCommand.execute()
+      const output = await this.invoke(null)
+      const rawResult: ChildProcess = { ...output, signal: null }
       debug("commands", `Result for command with args ${this.args}:`, rawResult)
 
       return Return.Value(rawResult)
@@ -52,10 +63,11 @@ export class Command implements TCommand<ChildProcess> {
   }
 
   public async stream(listener: TStreamEventListenerFn): Promise<ResultError> {
+    const streamID = `${Date.now()}-${nextStreamID++}`
+    let unlisten: (() => void) | undefined
     try {
-      await this.sidecarCommand.spawn()
       await new Promise((res, rej) => {
-        const stdoutListener: TEventListener<"data"> = (message) => {
+        const stdoutListener = (message: string) => {
           try {
             const data = JSON.parse(message)
 
@@ -72,7 +84,7 @@ export class Command implements TCommand<ChildProcess> {
             console.error("Failed to parse stdout message ", message, error)
           }
         }
-        const stderrListener: TEventListener<"data"> = (message) => {
+        const stderrListener = (message: string) => {
           try {
             const error = JSON.parse(message)
             listener({ type: "error", error })
@@ -81,27 +93,30 @@ export class Command implements TCommand<ChildProcess> {
           }
         }
 
-        this.sidecarCommand.stderr.addListener("data", stderrListener)
-        this.sidecarCommand.stdout.addListener("data", stdoutListener)
-
-        const cleanup = () => {
-          this.sidecarCommand.stderr.removeListener("data", stderrListener)
-          this.sidecarCommand.stdout.removeListener("data", stdoutListener)
-        }
-
-        this.sidecarCommand.on("close", (arg?: { code: number }) => {
-          cleanup()
-          if (arg?.code !== 0) {
-            rej(new Error("exit code: " + arg?.code))
-          } else {
-            res(Return.Ok())
-          }
-        })
-
-        this.sidecarCommand.on("error", (arg) => {
-          cleanup()
-          rej(arg)
-        })
+        event
+          .listen<CliOutputLine>(CLI_OUTPUT_EVENT, ({ payload }) => {
+            if (payload.streamId !== streamID) {
+              return
+            }
+            if (payload.stderr) {
+              stderrListener(payload.line)
+            } else {
+              stdoutListener(payload.line)
+            }
+          })
+          .then((unlistenFn) => {
+            unlisten = unlistenFn
+
+            return this.invoke(streamID)
+          })
+          .then((output) => {
+            if (output.code !== 0) {
+              rej(new Error("exit code: " + output.code))
+            } else {
+              res(Return.Ok())
+            }
+          })
+          .catch(rej)
       })
 
       return Return.Ok()
@@ -111,8 +126,20 @@ export class Command implements TCommand<ChildProcess> {
       }
 
       return Return.Failed("streaming failed")
+    } finally {
+      unlisten?.()
     }
   }
+
+  // The backend runs the cli under its supervision, with the same environment, queue and limits
+  private invoke(streamID: string | null): Promise<CliOutput> {
+    return invoke<CliOutput>("run_cli", {
+      args: this.args,
+      workspaceId: this.context.workspaceID ?? null,
+      operationId: this.context.operationID ?? null,
+      streamId: streamID,
+    })
+  }
 }
 
 export function isOk(result: ChildProcess): boolean {
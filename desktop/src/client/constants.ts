@@ -5,7 +5,6 @@ export const DEFAULT_STATIC_COMMAND_CONFIG = {
   debug: false,
 } as const
 
-export const DEVPOD_BINARY = "bin/devpod-cli"
 export const DEVPOD_COMMAND_LIST = "list"
 export const DEVPOD_COMMAND_STATUS = "status"
 export const DEVPOD_COMMAND_UP = "up"
@@ -50,4 +49,3 @@ export const DEVPOD_FLAG_DEVCONTAINER_PATH = "--devcontainer-path"
 export const DEVPOD_FLAG_WORKSPACE_ID = "--workspace-id"
 export const DEVPOD_FLAG_WORKSPACE_UID = "--workspace-uid"
 
-export const DEVPOD_UI_ENV_VAR = "DEVPOD_UI"
@@ -1,3 +1,5 @@
+import { invoke } from "@tauri-apps/api"
+import { CliCacheScope } from "../../gen"
 import { Result, ResultError } from "../../lib"
 import { TContextOptionName, TContextOptions } from "../../types"
 import { TDebuggable } from "../types"
@@ -11,7 +13,12 @@ export class ContextClient implements TDebuggable {
   }
 
   public async setOption(option: TContextOptionName, value: string): Promise<ResultError> {
-    return ContextCommands.SetOptions({ [option]: value })
+    const result = await ContextCommands.SetOptions({ [option]: value })
+    // Context options apply to everything the CLI reports
+    const scope: CliCacheScope = { kind: "cli" }
+    await invoke("invalidate_cli_cache", { scope })
+
+    return result
   }
 
   public async listOptions(): Promise<Result<TContextOptions>> {
@@ -1,6 +1,17 @@
+import { invoke } from "@tauri-apps/api"
 import { TDebuggable } from "../types"
 import { Result, ResultError } from "../../lib"
 import { TIDEs } from "../../types"
+import {
+  CustomIde,
+  ExtensionInstallReport,
+  ExtensionList,
+  IdeExtensions,
+  InstalledIde,
+  PinnedIdeVersion,
+  ResolvedIdeVersion,
+  WorkspaceExtensions,
+} from "../../gen"
 import { IDECommands } from "./ideCommands"
 
 export class IDEsClient implements TDebuggable {
@@ -17,4 +28,85 @@ export class IDEsClient implements TDebuggable {
   public async listAll(): Promise<Result<TIDEs>> {
     return IDECommands.ListIDEs()
   }
+
+  public listInstalled(refresh: boolean = false): Promise<readonly InstalledIde[]> {
+    return invoke("list_installed_ides", { refresh })
+  }
+
+  public listCustom(): Promise<readonly CustomIde[]> {
+    return invoke("list_custom_ides")
+  }
+
+  public registerCustom(ide: CustomIde): Promise<readonly CustomIde[]> {
+    return invoke("register_custom_ide", { ide })
+  }
+
+  public removeCustom(id: string): Promise<readonly CustomIde[]> {
+    return invoke("remove_custom_ide", { id })
+  }
+
+  public openCustom(workspaceID: string, id: string): Promise<void> {
+    return invoke("open_custom_ide", { workspaceId: workspaceID, id })
+  }
+
+  public getGatewayURL(workspaceID: string, ide: string, user?: string): Promise<string> {
+    return invoke("get_gateway_url", { workspaceId: workspaceID, ide, user })
+  }
+
+  public openInGateway(workspaceID: string, ide: string, user?: string): Promise<void> {
+    return invoke("open_in_gateway", { workspaceId: workspaceID, ide, user })
+  }
+
+  public openVSCode(
+    workspaceID: string,
+    profile?: string | null,
+    reuseWindow?: boolean | null
+  ): Promise<void> {
+    return invoke("open_vscode", { workspaceId: workspaceID, profile, reuseWindow })
+  }
+
+  public openRemoteEditor(workspaceID: string, editor: "cursor" | "zed" | "fleet"): Promise<void> {
+    return invoke("open_remote_editor", { workspaceId: workspaceID, editor })
+  }
+
+  public suggestForWorkspace(workspaceID: string): Promise<string | null> {
+    return invoke("suggest_workspace_ide", { workspaceId: workspaceID })
+  }
+
+  public getExtensions(): Promise<IdeExtensions> {
+    return invoke("get_ide_extensions")
+  }
+
+  public setExtensionTemplate(
+    name: string,
+    extensions: ExtensionList | null
+  ): Promise<IdeExtensions> {
+    return invoke("set_ide_extension_template", { name, extensions })
+  }
+
+  public setWorkspaceExtensions(
+    workspaceID: string,
+    extensions: WorkspaceExtensions | null
+  ): Promise<IdeExtensions> {
+    return invoke("set_workspace_ide_extensions", { workspaceId: workspaceID, extensions })
+  }
+
+  public installExtensions(workspaceID: string, ide: string): Promise<ExtensionInstallReport> {
+    return invoke("install_ide_extensions", { workspaceId: workspaceID, ide })
+  }
+
+  public getPinnedVersions(): Promise<Readonly<Record<string, PinnedIdeVersion>>> {
+    return invoke("get_pinned_ide_versions")
+  }
+
+  public pinVersion(
+    workspaceID: string,
+    pin: PinnedIdeVersion | null
+  ): Promise<Readonly<Record<string, PinnedIdeVersion>>> {
+    return invoke("pin_ide_version", { workspaceId: workspaceID, pin })
+  }
+
+  public resolveVersion(workspaceID: string, ide: string): Promise<ResolvedIdeVersion | null> {
+    return invoke("resolve_ide_version", { workspaceId: workspaceID, ide })
+  }
 }
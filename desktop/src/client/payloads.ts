@@ -0,0 +1,32 @@
+import { decode } from "@msgpack/msgpack"
+import { convertFileSrc, invoke } from "@tauri-apps/api/tauri"
+import { BinaryPayload, PayloadEncoding } from "../gen"
+
+// WARN: needs to match the protocol in `payloads.rs`
+const PAYLOAD_PROTOCOL = "payload"
+const SUPPORTED_ENCODINGS: readonly PayloadEncoding[] = ["messagePack", "json"]
+
+function isBinaryPayload(payload: unknown): payload is BinaryPayload {
+  return typeof payload === "object" && payload !== null && "binaryPayloadId" in payload
+}
+
+/** Lets the backend send large event payloads as MessagePack, until then they come as JSON. */
+export function negotiatePayloadEncoding(): Promise<PayloadEncoding> {
+  return invoke("negotiate_payload_encoding", { supported: SUPPORTED_ENCODINGS })
+}
+
+/** Fetches binary payloads from the backend, others are passed through as they are. */
+export async function decodePayload(payload: unknown): Promise<unknown> {
+  if (!isBinaryPayload(payload)) {
+    return payload
+  }
+
+  const response = await fetch(
+    convertFileSrc(payload.binaryPayloadId.toString(), PAYLOAD_PROTOCOL)
+  )
+  if (!response.ok) {
+    throw new Error(`Payload ${payload.binaryPayloadId} is no longer available`)
+  }
+
+  return decode(new Uint8Array(await response.arrayBuffer()))
+}
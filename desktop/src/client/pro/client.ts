@@ -1,4 +1,5 @@
-import { Result, ResultError } from "../../lib"
+import { invoke } from "@tauri-apps/api"
+import { Result, ResultError, Return } from "../../lib"
 import { TImportWorkspaceConfig, TProID, TProInstance } from "../../types"
 import { TDebuggable, TStreamEventListenerFn } from "../types"
 import { ProCommands } from "./proCommands"
@@ -15,6 +16,15 @@ export class ProClient implements TDebuggable {
     providerName?: string,
     listener?: TStreamEventListenerFn
   ): Promise<ResultError> {
+    // The CLI connects on its own, so the host is only checked against the certificate pins beforehand.
+    // Its own connection isn't pinned.
+    try {
+      const url = host.includes("://") ? host : `https://${host}`
+      await invoke("check_certificate_pins", { url })
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+
     return ProCommands.Login(host, providerName, listener)
   }
 
@@ -1,8 +1,8 @@
+import { invoke } from "@tauri-apps/api"
 import { Result, ResultError, Return, getErrorFromChildProcess } from "@/lib"
 import { TImportWorkspaceConfig, TProID, TProInstance } from "@/types"
 import { Command, isOk, serializeRawOptions, toFlagArg } from "../command"
 import {
-  DEVPOD_COMMAND_DELETE,
   DEVPOD_COMMAND_IMPORT_WORKSPACE,
   DEVPOD_COMMAND_LIST,
   DEVPOD_COMMAND_LOGIN,
@@ -77,22 +77,15 @@ export class ProCommands {
     return Return.Value(instances)
   }
 
-  static async RemoveProInstance(id: TProID) {
-    const result = await ProCommands.newCommand([
-      DEVPOD_COMMAND_PRO,
-      DEVPOD_COMMAND_DELETE,
-      id,
-      DEVPOD_FLAG_JSON_LOG_OUTPUT,
-    ]).run()
-    if (result.err) {
-      return result
-    }
+  // Runs in the backend, which opens the app lock first
+  static async RemoveProInstance(id: TProID): Promise<ResultError> {
+    try {
+      await invoke("delete_pro_instance", { proId: id })
 
-    if (!isOk(result.val)) {
-      return getErrorFromChildProcess(result.val)
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`Failed to remove pro instance ${id}: ${e}`)
     }
-
-    return Return.Ok()
   }
 
   static async ImportWorkspace(config: TImportWorkspaceConfig): Promise<ResultError> {
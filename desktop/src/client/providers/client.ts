@@ -1,3 +1,14 @@
+import { invoke } from "@tauri-apps/api"
+import {
+  AuditCategory,
+  CliCacheScope,
+  DockerContext,
+  DockerContextCheck,
+  KubeContext,
+  KubeContextCheck,
+  KubeProviderContext,
+  SshHost,
+} from "../../gen"
 import { FileStorageBackend, Result, ResultError, Return, Store, isEmpty } from "../../lib"
 import {
   TAddProviderConfig,
@@ -33,6 +44,15 @@ export class ProvidersClient implements TDebuggable {
     ProviderCommands.DEBUG = isEnabled
   }
 
+  private async invalidateCache(id: TProviderID) {
+    const scope: CliCacheScope = { kind: "provider", id }
+    await invoke("invalidate_cli_cache", { scope })
+  }
+
+  private async recordAudit(category: AuditCategory, action: string, id: TProviderID) {
+    await invoke("record_audit_event", { category, action, target: id })
+  }
+
   public async listAll(): Promise<Result<TProviders>> {
     return ProviderCommands.ListProviders()
   }
@@ -46,19 +66,154 @@ export class ProvidersClient implements TDebuggable {
   }
 
   public async update(id: TProviderID, source: TProviderSource): Promise<Result<void>> {
-    return ProviderCommands.UpdateProvider(id, source)
+    const result = await ProviderCommands.UpdateProvider(id, source)
+    await this.invalidateCache(id)
+    if (result.ok) {
+      await this.recordAudit("install", "update provider", id)
+    }
+
+    return result
   }
 
   public async add(rawSource: TProviderID, config: TAddProviderConfig): Promise<ResultError> {
-    return ProviderCommands.AddProvider(rawSource, config)
+    const result = await ProviderCommands.AddProvider(rawSource, config)
+    if (result.ok) {
+      await this.recordAudit("install", "add provider", config.name ?? rawSource)
+    }
+
+    return result
   }
 
   public async remove(id: TProviderID): Promise<ResultError> {
-    return ProviderCommands.RemoveProvider(id)
+    const result = await ProviderCommands.RemoveProvider(id)
+    await this.invalidateCache(id)
+
+    return result
   }
 
+  // Cached in the backend until the provider changes
   public async getOptions(id: TProviderID): Promise<Result<TProviderOptions>> {
-    return ProviderCommands.GetProviderOptions(id)
+    try {
+      const options = await invoke<TProviderOptions>("get_provider_options", { provider: id })
+
+      return Return.Value(options)
+    } catch (e) {
+      return Return.Failed(`Failed to get options for provider ${id}: ${e}`)
+    }
+  }
+
+  // Hosts of the SSH config and VS Code Remote-SSH, with the SSH provider options for each
+  public async listSshHosts(): Promise<readonly SshHost[]> {
+    return invoke<readonly SshHost[]>("list_ssh_hosts")
+  }
+
+  public async listDockerContexts(): Promise<Result<readonly DockerContext[]>> {
+    try {
+      const contexts = await invoke<readonly DockerContext[]>("list_docker_contexts")
+
+      return Return.Value(contexts)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async getDockerContext(id: TProviderID): Promise<Result<string | null>> {
+    try {
+      const context = await invoke<string | null>("get_docker_context", { provider: id })
+
+      return Return.Value(context)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  // Sets the `DOCKER_HOST` option of the provider, the backend invalidates its cache
+  public async setDockerContext(id: TProviderID, context: string | null): Promise<ResultError> {
+    try {
+      await invoke("set_docker_context", { provider: id, context })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async checkDockerContext(context: string): Promise<Result<DockerContextCheck>> {
+    try {
+      const check = await invoke<DockerContextCheck>("check_docker_context", { context })
+
+      return Return.Value(check)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async listKubeContexts(id: TProviderID): Promise<Result<readonly KubeContext[]>> {
+    try {
+      const contexts = await invoke<readonly KubeContext[]>("list_kube_contexts", { provider: id })
+
+      return Return.Value(contexts)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async listKubeNamespaces(
+    id: TProviderID,
+    context: string
+  ): Promise<Result<readonly string[]>> {
+    try {
+      const namespaces = await invoke<readonly string[]>("list_kube_namespaces", {
+        provider: id,
+        context,
+      })
+
+      return Return.Value(namespaces)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async getKubeContext(id: TProviderID): Promise<Result<KubeProviderContext>> {
+    try {
+      const context = await invoke<KubeProviderContext>("get_kube_context", { provider: id })
+
+      return Return.Value(context)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  // Sets the context and namespace options, the backend invalidates its cache
+  public async setKubeContext(
+    id: TProviderID,
+    { context, namespace }: KubeProviderContext
+  ): Promise<ResultError> {
+    try {
+      await invoke("set_kube_context", { provider: id, context, namespace })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async checkKubeContext(
+    id: TProviderID,
+    context: string,
+    namespace: string
+  ): Promise<Result<KubeContextCheck>> {
+    try {
+      const check = await invoke<KubeContextCheck>("check_kube_context", {
+        provider: id,
+        context,
+        namespace,
+      })
+
+      return Return.Value(check)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
   }
 
   public async useProvider(id: TProviderID): Promise<ResultError> {
@@ -77,6 +232,7 @@ export class ProvidersClient implements TDebuggable {
     { useAsDefaultProvider, reuseMachine, options }: TConfigureProviderConfig
   ): Promise<ResultError> {
     const setResult = await ProviderCommands.SetProviderOptions(id, options, !!reuseMachine)
+    await this.invalidateCache(id)
     if (setResult.err) {
       return setResult as ResultError
     }
@@ -1,3 +1,4 @@
+import { invoke } from "@tauri-apps/api"
 import { exists, getErrorFromChildProcess, Result, ResultError, Return } from "../../lib"
 import {
   TAddProviderConfig,
@@ -10,10 +11,8 @@ import {
 import { Command, isOk, serializeRawOptions, toFlagArg } from "../command"
 import {
   DEVPOD_COMMAND_ADD,
-  DEVPOD_COMMAND_DELETE,
   DEVPOD_COMMAND_GET_PROVIDER_NAME,
   DEVPOD_COMMAND_LIST,
-  DEVPOD_COMMAND_OPTIONS,
   DEVPOD_COMMAND_PROVIDER,
   DEVPOD_COMMAND_SET_OPTIONS,
   DEVPOD_COMMAND_UPDATE,
@@ -103,22 +102,15 @@ export class ProviderCommands {
     return Return.Ok()
   }
 
-  static async RemoveProvider(id: TProviderID) {
-    const result = await ProviderCommands.newCommand([
-      DEVPOD_COMMAND_PROVIDER,
-      DEVPOD_COMMAND_DELETE,
-      id,
-      DEVPOD_FLAG_JSON_LOG_OUTPUT,
-    ]).run()
-    if (result.err) {
-      return result
-    }
+  // Runs in the backend, which opens the app lock first
+  static async RemoveProvider(id: TProviderID): Promise<ResultError> {
+    try {
+      await invoke("delete_provider", { providerId: id })
 
-    if (!isOk(result.val)) {
-      return getErrorFromChildProcess(result.val)
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`Failed to remove provider ${id}: ${e}`)
     }
-
-    return Return.Ok()
   }
 
   static async UseProvider(
@@ -181,25 +173,6 @@ export class ProviderCommands {
     return Return.Ok()
   }
 
-  static async GetProviderOptions(id: TProviderID) {
-    const result = await new Command([
-      DEVPOD_COMMAND_PROVIDER,
-      DEVPOD_COMMAND_OPTIONS,
-      id,
-      DEVPOD_FLAG_JSON_OUTPUT,
-      DEVPOD_FLAG_JSON_LOG_OUTPUT,
-    ]).run()
-    if (result.err) {
-      return result
-    }
-
-    if (!isOk(result.val)) {
-      return getErrorFromChildProcess(result.val)
-    }
-
-    return Return.Value(JSON.parse(result.val.stdout) as TProviderOptions)
-  }
-
   static async CheckProviderUpdate(id: TProviderID) {
     const result = await new Command([
       DEVPOD_COMMAND_HELPER,
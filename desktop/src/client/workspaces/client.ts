@@ -1,8 +1,21 @@
 import { invoke } from "@tauri-apps/api"
 import { TActionID, TActionName, TActionObj } from "../../contexts"
+import {
+  CliCacheScope,
+  ComposeProject,
+  ComposeSidecarsStatus,
+  GitAccessCheck,
+  OperationHistoryFilter,
+  OperationRecord,
+  ResumableOperation,
+  WorkspaceDeletion,
+  WorkspaceExport,
+  WorkspaceExportTarget,
+} from "../../gen"
 import { Result, ResultError, Return, THandler, exists, noop } from "../../lib"
 import {
   TDevcontainerSetup,
+  TLogOutput,
   TStreamID,
   TUnsubscribeFn,
   TWorkspace,
@@ -10,7 +23,7 @@ import {
   TWorkspaceStartConfig,
   TWorkspaceWithoutStatus,
 } from "../../types"
-import { TCommand, TStreamEventListenerFn } from "../command"
+import { Command, TCommand, TStreamEventListenerFn } from "../command"
 import { CommandCache, TCommandCacheInfo } from "../commandCache"
 import { TDebuggable, TStreamEvent } from "../types"
 import { WorkspaceCommands } from "./workspaceCommands"
@@ -47,6 +60,11 @@ export class WorkspacesClient implements TDebuggable {
     await invoke("write_action_log", { actionId: actionID, data: JSON.stringify(event) })
   }
 
+  private async invalidateCache(id: TWorkspaceID) {
+    const scope: CliCacheScope = { kind: "workspace", id }
+    await invoke("invalidate_cli_cache", { scope })
+  }
+
   private async execActionCmd<T>(
     cmd: Readonly<TCommand<T>>,
     ctx: Readonly<{
@@ -70,6 +88,7 @@ export class WorkspacesClient implements TDebuggable {
     if (exists(maybeRunningCommand)) {
       maybeRunningCommand.stream?.(handler)
       await maybeRunningCommand.promise
+      await this.invalidateCache(ctx.id)
 
       return this.getStatus(ctx.id)
     }
@@ -79,6 +98,7 @@ export class WorkspacesClient implements TDebuggable {
 
     const result = await operation
     this.commandCache.clear(cacheInfo)
+    await this.invalidateCache(ctx.id)
 
     if (result.err) {
       return result
@@ -95,15 +115,15 @@ export class WorkspacesClient implements TDebuggable {
     return WorkspaceCommands.ListWorkspaces()
   }
 
+  // Cached in the backend, the actions of this client clear it for their workspace
   public async getStatus(id: TWorkspaceID): Promise<Result<TWorkspace["status"]>> {
-    const result = await WorkspaceCommands.FetchWorkspaceStatus(id)
-    if (result.err) {
-      return result
-    }
-
-    const { status } = result.val
+    try {
+      const status = await invoke<TWorkspace["status"]>("get_workspace_status", { workspaceId: id })
 
-    return Return.Value(status)
+      return Return.Value(status)
+    } catch (e) {
+      return Return.Failed(`Failed to get status for workspace ${id}: ${e}`)
+    }
   }
 
   public async newID(rawSource: string): Promise<Result<string>> {
@@ -115,11 +135,31 @@ export class WorkspacesClient implements TDebuggable {
     listener: TStreamEventListenerFn | undefined,
     ctx: TWorkspaceClientContext
   ): Promise<Result<TWorkspace["status"]>> {
-    const cmd = WorkspaceCommands.StartWorkspace(ctx.id, config)
+    const diskSpaceResult = await this.checkBuildDiskSpace()
+    if (diskSpaceResult.err) {
+      return diskSpaceResult
+    }
+
+    const gitAccessResult = await this.checkGitAccess(config.sourceConfig?.source)
+    if (gitAccessResult.err) {
+      return gitAccessResult
+    }
+
+    const cmd = WorkspaceCommands.StartWorkspace(ctx.id, config, ctx.actionID)
+    // Rolling back deletes a workspace that is being created, existing ones are only stopped
+    const rollbackCmd = exists(config.sourceConfig?.source)
+      ? WorkspaceCommands.RemoveWorkspace(ctx.id, true)
+      : WorkspaceCommands.StopWorkspace(ctx.id)
+    this.beginResumableOperation(ctx, cmd, rollbackCmd)
     const result = await this.execActionCmd(cmd, { ...ctx, listener, actionName: "start" })
+    this.endResumableOperation(ctx.actionID)
     if (result.err) {
       return result
     }
+    const sidecarsResult = await this.startComposeSidecars(ctx.id)
+    if (sidecarsResult.err) {
+      return sidecarsResult
+    }
 
     return this.getStatus(ctx.id)
   }
@@ -128,11 +168,15 @@ export class WorkspacesClient implements TDebuggable {
     listener: TStreamEventListenerFn | undefined,
     ctx: TWorkspaceClientContext
   ): Promise<Result<TWorkspace["status"]>> {
-    const cmd = WorkspaceCommands.StopWorkspace(ctx.id)
+    const cmd = WorkspaceCommands.StopWorkspace(ctx.id, ctx.actionID)
     const result = await this.execActionCmd(cmd, { ...ctx, listener, actionName: "stop" })
     if (result.err) {
       return result
     }
+    const sidecarsResult = await this.stopComposeSidecars(ctx.id)
+    if (sidecarsResult.err) {
+      return sidecarsResult
+    }
 
     return this.getStatus(ctx.id)
   }
@@ -141,11 +185,22 @@ export class WorkspacesClient implements TDebuggable {
     listener: TStreamEventListenerFn | undefined,
     ctx: TWorkspaceClientContext
   ): Promise<Result<TWorkspace["status"]>> {
-    const cmd = WorkspaceCommands.RebuildWorkspace(ctx.id)
+    const diskSpaceResult = await this.checkBuildDiskSpace()
+    if (diskSpaceResult.err) {
+      return diskSpaceResult
+    }
+
+    const cmd = WorkspaceCommands.RebuildWorkspace(ctx.id, ctx.actionID)
+    this.beginResumableOperation(ctx, cmd, WorkspaceCommands.StopWorkspace(ctx.id))
     const result = await this.execActionCmd(cmd, { ...ctx, listener, actionName: "rebuild" })
+    this.endResumableOperation(ctx.actionID)
     if (result.err) {
       return result
     }
+    const sidecarsResult = await this.startComposeSidecars(ctx.id)
+    if (sidecarsResult.err) {
+      return sidecarsResult
+    }
 
     return this.getStatus(ctx.id)
   }
@@ -155,7 +210,11 @@ export class WorkspacesClient implements TDebuggable {
     listener: TStreamEventListenerFn | undefined,
     ctx: TWorkspaceClientContext
   ): Promise<Result<TWorkspace["status"]>> {
-    const cmd = WorkspaceCommands.RemoveWorkspace(ctx.id, force)
+    // Runs in the backend, which opens the app lock first
+    const cmd: TCommand<undefined> = {
+      run: () => this.deleteWorkspace(ctx.id, force, noop),
+      stream: (listener) => this.deleteWorkspace(ctx.id, force, listener),
+    }
     const result = await this.execActionCmd(cmd, { ...ctx, listener, actionName: "remove" })
     if (result.err) {
       return result
@@ -164,6 +223,54 @@ export class WorkspacesClient implements TDebuggable {
     return result
   }
 
+  private async deleteWorkspace(
+    id: TWorkspaceID,
+    force: boolean,
+    listener: TStreamEventListenerFn
+  ): Promise<ResultError> {
+    try {
+      const deletion = await invoke<WorkspaceDeletion>("delete_workspace", {
+        workspaceId: id,
+        force,
+      })
+      for (const line of deletion.stdout) {
+        const data = parseLogLine(line)
+        if (exists(data)) {
+          listener({ type: "data", data })
+        }
+      }
+      for (const line of deletion.stderr) {
+        const error = parseLogLine(line)
+        if (exists(error)) {
+          listener({ type: "error", error })
+        }
+      }
+      if (!deletion.success) {
+        return Return.Failed(`Failed to delete workspace ${id}`)
+      }
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async export(
+    id: TWorkspaceID,
+    target: WorkspaceExportTarget
+  ): Promise<Result<WorkspaceExport>> {
+    try {
+      const workspaceExport = await invoke<WorkspaceExport>("export_workspace", {
+        workspaceId: id,
+        target,
+      })
+
+      return Return.Value(workspaceExport)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
   public async checkStatus(
     listener: TStreamEventListenerFn | undefined,
     ctx: TWorkspaceClientContext
@@ -240,7 +347,204 @@ export class WorkspacesClient implements TDebuggable {
     return unsubscribe
   }
 
+  // Builds fail halfway through when the disk runs full, so they aren't started below the hard floor
+  private async checkBuildDiskSpace(): Promise<Result<void>> {
+    try {
+      await invoke("check_build_disk_space")
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  // Clones of private repositories fail halfway through otherwise, with an obscure git error
+  private async checkGitAccess(source: string | undefined): Promise<Result<void>> {
+    if (!exists(source)) {
+      return Return.Ok()
+    }
+
+    try {
+      const check = await invoke<GitAccessCheck | null>("check_git_access", { source })
+      // Unreachable hosts are left to the CLI, it might get through a proxy of the provider
+      if (exists(check) && (check.access === "unauthorized" || check.access === "notFound")) {
+        return Return.Failed(check.message ?? `Access to ${check.url} was denied`)
+      }
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  // Compose services the user picked on top of the ones the CLI runs, they follow the workspace
+  private async startComposeSidecars(id: TWorkspaceID): Promise<ResultError> {
+    try {
+      await invoke("start_compose_sidecars", { workspaceId: id })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`Sidecar services of ${id} failed to start: ${e}`)
+    }
+  }
+
+  private async stopComposeSidecars(id: TWorkspaceID): Promise<ResultError> {
+    try {
+      await invoke("stop_compose_sidecars", { workspaceId: id })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`Sidecar services of ${id} failed to stop: ${e}`)
+    }
+  }
+
+  public async detectComposeProject(
+    source: string,
+    devcontainerPath: string | undefined
+  ): Promise<Result<ComposeProject | null>> {
+    try {
+      const project = await invoke<ComposeProject | null>("detect_compose_project", {
+        source,
+        devcontainerPath: devcontainerPath ?? null,
+      })
+
+      return Return.Value(project)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async getComposeProject(id: TWorkspaceID): Promise<Result<ComposeProject | null>> {
+    try {
+      const project = await invoke<ComposeProject | null>("get_workspace_compose_project", {
+        workspaceId: id,
+      })
+
+      return Return.Value(project)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async getComposeSidecars(id: TWorkspaceID): Promise<readonly string[]> {
+    return invoke<readonly string[]>("get_compose_sidecars", { workspaceId: id })
+  }
+
+  public async setComposeSidecars(
+    id: TWorkspaceID,
+    services: readonly string[]
+  ): Promise<ResultError> {
+    try {
+      await invoke("set_compose_sidecars", { workspaceId: id, services })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async getComposeSidecarsStatus(
+    id: TWorkspaceID
+  ): Promise<Result<ComposeSidecarsStatus | null>> {
+    try {
+      const status = await invoke<ComposeSidecarsStatus | null>("get_compose_sidecars_status", {
+        workspaceId: id,
+      })
+
+      return Return.Value(status)
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  // Kept by the backend until the action has finished, so that it can be resumed if the app restarts in between
+  private beginResumableOperation(
+    ctx: TWorkspaceClientContext,
+    cmd: Command,
+    rollbackCmd: Command | null
+  ) {
+    const operation: ResumableOperation = {
+      id: ctx.actionID,
+      kind: "up",
+      workspaceId: ctx.id,
+      resumeArgs: [...cmd.getArgs()],
+      rollbackArgs: rollbackCmd ? [...rollbackCmd.getArgs()] : null,
+      startedAt: new Date().toISOString(),
+    }
+    invoke("begin_resumable_operation", { operation })
+  }
+
+  public endResumableOperation(actionID: TActionID) {
+    invoke("end_resumable_operation", { operationId: actionID })
+  }
+
+  public async resumeOperation(actionID: TActionID): Promise<Result<void>> {
+    try {
+      await invoke("resume_operation", { operationId: actionID })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
+  public async rollBackOperation(actionID: TActionID): Promise<Result<void>> {
+    try {
+      await invoke("roll_back_operation", { operationId: actionID })
+
+      return Return.Ok()
+    } catch (e) {
+      return Return.Failed(`${e}`)
+    }
+  }
+
   public syncActionLogs(actionIDs: readonly string[]) {
     invoke("sync_action_logs", { actions: actionIDs })
   }
+
+  public syncActiveActions(actionIDs: readonly string[]) {
+    invoke("sync_active_actions", { actions: actionIDs })
+  }
+
+  public recordOperation(action: TActionObj) {
+    if (action.status === "pending") {
+      return
+    }
+    const operation: OperationRecord = {
+      id: action.id,
+      source: "ui",
+      command: action.name,
+      args: [],
+      workspaceId: action.targetID,
+      startedAt: new Date(action.createdAt).toISOString(),
+      durationMs: (action.finishedAt ?? Date.now()) - action.createdAt,
+      status: action.status === "error" ? "failed" : action.status,
+      exitCode: null,
+    }
+    invoke("record_operation", { operation })
+  }
+
+  public async queryOperationHistory(
+    filter: Partial<OperationHistoryFilter> = {}
+  ): Promise<Result<readonly OperationRecord[]>> {
+    try {
+      const records = await invoke<OperationRecord[]>("query_operation_history", {
+        filter: { from: null, to: null, workspaceId: null, limit: null, ...filter },
+      })
+
+      return Return.Value(records)
+    } catch (e) {
+      return Return.Failed(`Unable to query operation history: ${e}`)
+    }
+  }
+}
+
+function parseLogLine(line: string): TLogOutput | undefined {
+  try {
+    return JSON.parse(line)
+  } catch (error) {
+    console.error("Failed to parse log line ", line, error)
+
+    return undefined
+  }
 }
@@ -3,10 +3,9 @@ import {
   TWorkspace,
   TWorkspaceID,
   TWorkspaceStartConfig,
-  TWorkspaceStatusResult,
   TWorkspaceWithoutStatus,
 } from "../../types"
-import { Command, isOk, toFlagArg } from "../command"
+import { Command, isOk, TCommandContext, toFlagArg } from "../command"
 import {
   DEVPOD_COMMAND_DELETE,
   DEVPOD_COMMAND_GET_WORKSPACE_CONFIG,
@@ -35,8 +34,11 @@ type TRawWorkspaces = readonly (Omit<TWorkspace, "status" | "id"> &
 export class WorkspaceCommands {
   static DEBUG = false
 
-  private static newCommand(args: string[]): Command {
-    return new Command([...args, ...(WorkspaceCommands.DEBUG ? [DEVPOD_FLAG_DEBUG] : [])])
+  private static newCommand(args: string[], context?: TCommandContext): Command {
+    return new Command(
+      [...args, ...(WorkspaceCommands.DEBUG ? [DEVPOD_FLAG_DEBUG] : [])],
+      context
+    )
   }
 
   static async ListWorkspaces(): Promise<Result<TWorkspaceWithoutStatus[]>> {
@@ -54,23 +56,6 @@ export class WorkspaceCommands {
     )
   }
 
-  static async FetchWorkspaceStatus(
-    id: string
-  ): Promise<Result<Pick<TWorkspace, "id" | "status">>> {
-    const result = await new Command([DEVPOD_COMMAND_STATUS, id, DEVPOD_FLAG_JSON_OUTPUT]).run()
-    if (result.err) {
-      return result
-    }
-
-    if (!isOk(result.val)) {
-      return Return.Failed(`Failed to get status for workspace ${id}: ${result.val.stderr}`)
-    }
-
-    const { state } = JSON.parse(result.val.stdout) as TWorkspaceStatusResult
-
-    return Return.Value({ id, status: state })
-  }
-
   static async GetWorkspaceID(source: string) {
     const result = await new Command([
       DEVPOD_COMMAND_HELPER,
@@ -92,7 +77,7 @@ export class WorkspaceCommands {
     return new Command([DEVPOD_COMMAND_STATUS, id, DEVPOD_FLAG_JSON_LOG_OUTPUT])
   }
 
-  static StartWorkspace(id: TWorkspaceID, config: TWorkspaceStartConfig) {
+  static StartWorkspace(id: TWorkspaceID, config: TWorkspaceStartConfig, operationID?: string) {
     const maybeSource = config.sourceConfig?.source
     const maybeIDFlag = exists(maybeSource) ? [toFlagArg(DEVPOD_FLAG_ID, id)] : []
 
@@ -114,29 +99,33 @@ export class WorkspaceCommands {
 
     const identifier = exists(maybeSource) && exists(maybeIDFlag) ? maybeSource : id
 
-    return WorkspaceCommands.newCommand([
-      DEVPOD_COMMAND_UP,
-      identifier,
-      ...maybeIDFlag,
-      ...maybeIDEFlag,
-      ...maybeProviderFlag,
-      ...maybePrebuildRepositories,
-      ...maybeDevcontainerPath,
-      DEVPOD_FLAG_JSON_LOG_OUTPUT,
-    ])
+    return WorkspaceCommands.newCommand(
+      [
+        DEVPOD_COMMAND_UP,
+        identifier,
+        ...maybeIDFlag,
+        ...maybeIDEFlag,
+        ...maybeProviderFlag,
+        ...maybePrebuildRepositories,
+        ...maybeDevcontainerPath,
+        DEVPOD_FLAG_JSON_LOG_OUTPUT,
+      ],
+      { workspaceID: id, operationID }
+    )
   }
 
-  static StopWorkspace(id: TWorkspaceID) {
-    return WorkspaceCommands.newCommand([DEVPOD_COMMAND_STOP, id, DEVPOD_FLAG_JSON_LOG_OUTPUT])
+  static StopWorkspace(id: TWorkspaceID, operationID?: string) {
+    return WorkspaceCommands.newCommand([DEVPOD_COMMAND_STOP, id, DEVPOD_FLAG_JSON_LOG_OUTPUT], {
+      workspaceID: id,
+      operationID,
+    })
   }
 
-  static RebuildWorkspace(id: TWorkspaceID) {
-    return WorkspaceCommands.newCommand([
-      DEVPOD_COMMAND_UP,
-      id,
-      DEVPOD_FLAG_JSON_LOG_OUTPUT,
-      DEVPOD_FLAG_RECREATE,
-    ])
+  static RebuildWorkspace(id: TWorkspaceID, operationID?: string) {
+    return WorkspaceCommands.newCommand(
+      [DEVPOD_COMMAND_UP, id, DEVPOD_FLAG_JSON_LOG_OUTPUT, DEVPOD_FLAG_RECREATE],
+      { workspaceID: id, operationID }
+    )
   }
 
   static RemoveWorkspace(id: TWorkspaceID, force?: boolean) {
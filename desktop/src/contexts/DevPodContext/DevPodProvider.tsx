@@ -1,9 +1,11 @@
-import { useQuery } from "@tanstack/react-query"
-import { createContext, ReactNode, useMemo } from "react"
+import { useQuery, useQueryClient } from "@tanstack/react-query"
+import { createContext, ReactNode, useEffect, useMemo } from "react"
 import { client } from "../../client"
 import { QueryKeys } from "../../queryKeys"
-import { TProInstances, TProviders, TQueryResult } from "../../types"
-import { REFETCH_INTERVAL_MS, REFETCH_PROVIDER_INTERVAL_MS } from "./constants"
+import { useNetworkStatus } from "../../useNetworkStatus"
+import { useSubsystemReady } from "../../useSubsystemReady"
+import { TProInstances, TProvider, TProviders, TQueryResult } from "../../types"
+import { REFETCH_INTERVAL_MS } from "./constants"
 import { usePollWorkspaces } from "./workspaces"
 
 export type TDevpodContext = Readonly<{
@@ -14,17 +16,31 @@ export const DevPodContext = createContext<TDevpodContext>(null!)
 
 export function DevPodProvider({ children }: Readonly<{ children?: ReactNode }>) {
   usePollWorkspaces()
+  // Pro instances and the provider registry can't be reached while offline
+  const isOnline = useNetworkStatus()
+  const isProReady = useSubsystemReady("pro")
 
   const providersQuery = useQuery({
     queryKey: QueryKeys.PROVIDERS,
-    queryFn: async () => (await client.providers.listAll()).unwrap(),
-    refetchInterval: REFETCH_PROVIDER_INTERVAL_MS,
+    queryFn: async () => {
+      // The backend lists them on startup, the CLI is only asked if the window was faster
+      const snapshot = await client.getStateSnapshot("providers")
+      if (snapshot.revision > 0) {
+        return withProxyFlag({}, snapshot.items)
+      }
+
+      return (await client.providers.listAll()).unwrap()
+    },
   })
+  useProviderPatches()
+  useStartupProInstances()
 
   const proInstancesQuery = useQuery({
     queryKey: QueryKeys.PRO_INSTANCES,
     queryFn: async () => (await client.pro.listAll()).unwrap(),
-    refetchInterval: REFETCH_INTERVAL_MS,
+    refetchInterval: isOnline ? REFETCH_INTERVAL_MS : false,
+    staleTime: REFETCH_INTERVAL_MS,
+    enabled: isProReady,
   })
 
   const value = useMemo<TDevpodContext>(
@@ -50,3 +66,70 @@ export function DevPodProvider({ children }: Readonly<{ children?: ReactNode }>)
 
   return <DevPodContext.Provider value={value}>{children}</DevPodContext.Provider>
 }
+
+// The backend lists the providers and only sends the changed ones, they are patched into the query
+function useProviderPatches() {
+  const queryClient = useQueryClient()
+
+  useEffect(() => {
+    let revision: number | undefined = undefined
+    const unsubscribe = client.subscribe("state_patch", (patch) => {
+      if (patch.collection !== "providers") {
+        return
+      }
+      // Missed a patch, or this is the first one since the window opened
+      if (revision === undefined || patch.revision !== revision + 1) {
+        revision = patch.revision
+        queryClient.invalidateQueries(QueryKeys.PROVIDERS)
+
+        return
+      }
+      revision = patch.revision
+
+      queryClient.setQueryData<TProviders>(QueryKeys.PROVIDERS, (current) => {
+        const providers = withProxyFlag(current, patch.upserted)
+        for (const name of patch.removed) {
+          delete providers[name]
+        }
+
+        return providers
+      })
+    })
+
+    return () => {
+      unsubscribe.then((u) => u())
+    }
+  }, [queryClient])
+}
+
+function withProxyFlag(current: TProviders | undefined, upserted: Record<string, unknown>) {
+  const providers = { ...current }
+  for (const [name, provider] of Object.entries(upserted) as [string, TProvider][]) {
+    providers[name] = {
+      ...provider,
+      isProxyProvider: provider.config?.exec?.proxy !== undefined,
+    }
+  }
+
+  return providers
+}
+
+// The backend lists the pro instances on startup, which saves the first poll
+function useStartupProInstances() {
+  const queryClient = useQueryClient()
+
+  useEffect(() => {
+    const unsubscribe = client.subscribe("startup_data", (event) => {
+      if (event.data === "proInstances" && event.value !== null) {
+        queryClient.setQueryData<TProInstances>(
+          QueryKeys.PRO_INSTANCES,
+          event.value as TProInstances
+        )
+      }
+    })
+
+    return () => {
+      unsubscribe.then((u) => u())
+    }
+  }, [queryClient])
+}
@@ -41,10 +41,12 @@ export class ActionHistory {
 
   public addActive(targetID: string, action: Action): void {
     this.active.set(targetID, action)
+    this.syncActive()
   }
 
   public archive(action: Action): void {
     this.active.delete(action.targetID)
+    this.syncActive()
     this.history.push(action.getData())
 
     // Limit history size
@@ -55,5 +57,10 @@ export class ActionHistory {
 
     window.localStorage.setItem(HISTORY_KEY, JSON.stringify(this.history))
     client.workspaces.syncActionLogs(this.history.map((a) => a.id))
+    client.workspaces.recordOperation(action.getData())
+  }
+
+  private syncActive(): void {
+    client.workspaces.syncActiveActions(Array.from(this.active.values()).map((a) => a.id))
   }
 }
@@ -1,3 +1,4 @@
 export const REFETCH_INTERVAL_MS = 5_000
 
-export const REFETCH_PROVIDER_INTERVAL_MS = 1_000
+// Workspaces of these providers run on this machine and can be reached while offline
+export const LOCAL_PROVIDERS: readonly string[] = ["docker"]
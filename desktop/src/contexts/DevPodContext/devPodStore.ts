@@ -84,6 +84,21 @@ class DevPodStore {
     this.workspacesDidChange()
   }
 
+  // Only the changed workspaces are replaced, so that views of the others don't render again
+  public patchWorkspaces(
+    upserted: readonly TWorkspaceWithoutStatus[],
+    removed: readonly TWorkspaceID[]
+  ): void {
+    for (const workspace of upserted) {
+      const maybeExistingWorkspace = this.workspaces.get(workspace.id)
+      this.workspaces.set(workspace.id, { ...workspace, status: maybeExistingWorkspace?.status })
+    }
+    for (const workspaceID of removed) {
+      this.workspaces.delete(workspaceID)
+    }
+    this.workspacesDidChange()
+  }
+
   public removeWorkspace(workspaceID: TWorkspaceID): void {
     this.workspaces.delete(workspaceID)
     this.workspacesDidChange()
@@ -1,17 +1,14 @@
-import { useCallback, useEffect } from "react"
+import { useCallback, useEffect, useRef } from "react"
 import { client } from "../../../client"
-import { TWorkspaceID } from "../../../types"
-import { REFETCH_INTERVAL_MS } from "../constants"
+import { TWorkspaceID, TWorkspaceWithoutStatus } from "../../../types"
+import { useNetworkStatus } from "../../../useNetworkStatus"
+import { LOCAL_PROVIDERS, REFETCH_INTERVAL_MS } from "../constants"
 import { devPodStore } from "../devPodStore"
 
 export function usePollWorkspaces() {
-  const listWorkspaces = useCallback(async () => {
-    const result = await client.workspaces.listAll()
-    if (result.err) {
-      return
-    }
-    devPodStore.setWorkspaces(result.val)
-  }, [])
+  const isOnline = useNetworkStatus()
+  const isOnlineRef = useRef(isOnline)
+  isOnlineRef.current = isOnline
 
   const updateStatus = useCallback(async (ongoingRequests: Record<TWorkspaceID, true>) => {
     for (const workspace of devPodStore.getAll()) {
@@ -21,6 +18,12 @@ export function usePollWorkspaces() {
         continue
       }
 
+      // Cloud workspaces can't be reached while offline, that doesn't mean anything is wrong with them
+      if (!isOnlineRef.current && !LOCAL_PROVIDERS.includes(workspace.provider?.name ?? "")) {
+        devPodStore.setStatus(workspace.id, "Unreachable")
+        continue
+      }
+
       ongoingRequests[workspace.id] = true
       try {
         const result = await client.workspaces.getStatus(workspace.id)
@@ -36,23 +39,53 @@ export function usePollWorkspaces() {
     }
   }, [])
 
+  // The backend lists the workspaces and only sends the ones that changed
   useEffect(() => {
-    const workspacesIntervalID = setInterval(listWorkspaces, REFETCH_INTERVAL_MS)
+    let revision = 0
+    const loadSnapshot = async () => {
+      const snapshot = await client.getStateSnapshot("workspaces")
+      if (snapshot.revision === 0) {
+        return
+      }
+      revision = snapshot.revision
+      devPodStore.setWorkspaces(Object.values(snapshot.items) as TWorkspaceWithoutStatus[])
+    }
+
+    const unsubscribe = client.subscribe("state_patch", (patch) => {
+      if (patch.collection !== "workspaces") {
+        return
+      }
+      if (patch.revision !== revision + 1) {
+        loadSnapshot()
+
+        return
+      }
+      revision = patch.revision
+      devPodStore.patchWorkspaces(
+        Object.values(patch.upserted) as TWorkspaceWithoutStatus[],
+        patch.removed
+      )
+    })
+    loadSnapshot()
+
+    return () => {
+      unsubscribe.then((u) => u())
+    }
+  }, [])
 
+  useEffect(() => {
     const ongoingRequests: Record<TWorkspaceID, true> = {}
     const statusIntervalID = setInterval(async () => {
       await updateStatus(ongoingRequests)
     }, REFETCH_INTERVAL_MS)
 
     const initialTimeoutID = setTimeout(async () => {
-      await listWorkspaces()
       await updateStatus(ongoingRequests)
     }, 0)
 
     return () => {
-      clearInterval(workspacesIntervalID)
       clearInterval(statusIntervalID)
       clearTimeout(initialTimeoutID)
     }
-  }, [listWorkspaces, updateStatus])
+  }, [updateStatus])
 }
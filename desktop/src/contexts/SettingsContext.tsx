@@ -22,6 +22,20 @@ type TSettingsContext = Readonly<{
 }>
 
 const SettingsContext = createContext<TSettingsContext>(null!)
+// Changes to these end up in the audit log, their values never do
+const SECURITY_SETTINGS: readonly TSetting[] = [
+  "autoUpdate",
+  "updateChannel",
+  "environmentVariables",
+  "otlpEndpoint",
+  "errorReporting",
+  "errorReportingDsn",
+  "watchClipboard",
+  "workspaceHostnames",
+  "httpProxy",
+  "noProxy",
+  "trustedLinkDomains",
+]
 
 const initialSettings: TSettings = {
   sidebarPosition: "left",
@@ -31,10 +45,49 @@ const initialSettings: TSettings = {
   zoom: "md",
   transparency: false,
   autoUpdate: true,
+  updateChannel: "stable",
+  updateSchedule: "anytime",
+  updateWhileBusy: "wait",
   experimental_multiDevcontainer: false,
   experimental_fleet: true,
   experimental_jupyterNotebooks: true,
   experimental_devPodPro: false,
+  environmentVariables: [],
+  logRetentionDays: 7,
+  logFormat: "text",
+  otlpEndpoint: null,
+  errorReporting: false,
+  errorReportingDsn: null,
+  disabledNotifications: [],
+  quietHours: null,
+  pinnedWorkspaces: [],
+  stopWorkspacesOnShutdown: false,
+  lowDiskSpaceWarningGb: 10,
+  lowDiskSpaceFloorGb: 2,
+  watchClipboard: false,
+  manageSshConfig: false,
+  detectPorts: true,
+  workspaceHostnames: false,
+  workspaceHostnamesPort: 8470,
+  keepAliveSeconds: 15,
+  autoReconnect: true,
+  reconnectMaxDelaySeconds: 300,
+  customIdes: [],
+  terminalScrollbackKb: 512,
+  vscodeProfile: null,
+  vscodeReuseWindow: false,
+  browserIdeIdleTimeoutMinutes: 30,
+  httpProxy: null,
+  noProxy: null,
+  maxConcurrentCommands: 4,
+  providerConcurrencyLimits: {},
+  operationPolicies: {},
+  resourceLimits: { cpuPercent: null, memoryMb: null, nice: null },
+  workspaceResourceLimits: {},
+  statusWidget: false,
+  fastStart: false,
+  trustedLinkDomains: [],
+  workspaceTags: {},
 }
 function getSettingKeys(): readonly TSetting[] {
   return getKeys(initialSettings)
@@ -87,6 +140,9 @@ export function SettingsProvider({ children }: Readonly<{ children?: ReactNode }
 
   const set = useCallback<TSettingsContext["set"]>((key, value) => {
     settingsStore.set(key, value)
+    if (SECURITY_SETTINGS.includes(key)) {
+      client.recordAuditEvent("settings", "change setting", key)
+    }
   }, [])
 
   const value = useMemo(() => ({ settings, set }), [set, settings])
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AuditCategory } from "./AuditCategory"
+import type { AuditInitiator } from "./AuditInitiator"
+
+export interface AuditEntry {
+  at: string
+  initiator: AuditInitiator
+  category: AuditCategory
+  action: string
+  target: string | null
+}
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PayloadEncoding } from "./PayloadEncoding"
+
+export interface BinaryPayload {
+  binaryPayloadId: number
+  encoding: PayloadEncoding
+  size: number
+}
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BrowserIde } from "./BrowserIde"
+import type { BrowserIdeSessionState } from "./BrowserIdeSessionState"
+
+export interface BrowserIdeSession {
+  workspaceId: string
+  ide: BrowserIde
+  state: BrowserIdeSessionState
+  url: string
+  password: string | null
+  localPort: number
+  pid: number | null
+  error: string | null
+}
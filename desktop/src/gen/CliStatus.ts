@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReleaseChannel } from "./ReleaseChannel"
+
+export interface CliStatus {
+  path: string
+  channel: ReleaseChannel
+  installedVersion: string | null
+  latestVersion: string | null
+  updateAvailable: boolean
+  downgradeAvailable: boolean
+}
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CodespacesField } from "./CodespacesField"
+
+export interface CodespacesCompatibility {
+  fields: Array<CodespacesField>
+}
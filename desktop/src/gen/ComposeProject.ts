@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ComposeService } from "./ComposeService"
+
+export interface ComposeProject {
+  files: Array<string>
+  service: string
+  services: Array<ComposeService>
+}
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ComposeServiceStatus } from "./ComposeServiceStatus"
+import type { ComposeSidecarsState } from "./ComposeSidecarsState"
+
+export interface ComposeSidecarsStatus {
+  state: ComposeSidecarsState
+  services: Array<ComposeServiceStatus>
+}
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConnectionKind } from "./ConnectionKind"
+
+export interface Connection {
+  id: string
+  workspaceId: string
+  kind: ConnectionKind
+  description: string
+  connectedSince: string
+  latencyMs: number | null
+  degraded: boolean
+  bytesSent: number
+  bytesReceived: number
+  sendRate: number
+  receiveRate: number
+}
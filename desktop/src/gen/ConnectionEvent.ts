@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Connection } from "./Connection"
+import type { ConnectionEventKind } from "./ConnectionEventKind"
+
+export interface ConnectionEvent {
+  kind: ConnectionEventKind
+  connection: Connection
+  reason: string | null
+}
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConnectionProxy } from "./ConnectionProxy"
+
+export interface ConnectionProxies {
+  default: ConnectionProxy | null
+  providers: Record<string, ConnectionProxy>
+}
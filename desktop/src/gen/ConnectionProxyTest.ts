@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface ConnectionProxyTest {
+  reachable: boolean
+  banner: string | null
+  error: string | null
+  durationMs: number
+}
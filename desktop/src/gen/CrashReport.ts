@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CrashKind } from "./CrashKind"
+
+export interface CrashReport {
+  id: string
+  kind: CrashKind
+  timestamp: string
+  sessionStartedAt: string
+  appVersion: string
+  os: string
+  arch: string
+  thread: string | null
+  message: string | null
+  location: string | null
+  backtrace: string | null
+}
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CredentialRequester } from "./CredentialRequester"
+import type { SecretKind } from "./SecretKind"
+
+export interface CredentialRequest {
+  requester: CredentialRequester
+  kind: SecretKind
+  account: string
+}
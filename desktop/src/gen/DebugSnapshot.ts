@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Activity } from "./Activity"
+import type { QueueDepth } from "./QueueDepth"
+
+export interface DebugSnapshot {
+  timestamp: string
+  uiMessages: Array<{ timestamp: string; message: { type: string } }>
+  activeActions: Array<string>
+  tasks: Array<Activity>
+  processes: Array<Activity>
+  queues: Array<QueueDepth>
+}
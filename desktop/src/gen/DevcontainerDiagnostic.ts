@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DevcontainerDiagnosticKind } from "./DevcontainerDiagnosticKind"
+import type { DevcontainerSeverity } from "./DevcontainerSeverity"
+
+export interface DevcontainerDiagnostic {
+  severity: DevcontainerSeverity
+  kind: DevcontainerDiagnosticKind
+  path: string
+  message: string
+  line: number | null
+  column: number | null
+}
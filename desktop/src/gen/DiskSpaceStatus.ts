@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiskSpaceLevel } from "./DiskSpaceLevel"
+import type { Volume } from "./Volume"
+
+export interface DiskSpaceStatus {
+  level: DiskSpaceLevel
+  volumes: Array<Volume>
+}
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CheckStatus } from "./CheckStatus"
+
+export interface DoctorCheck {
+  id: string
+  name: string
+  status: CheckStatus
+  message: string
+  remediation: string | null
+}
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CheckStatus } from "./CheckStatus"
+import type { DoctorCheck } from "./DoctorCheck"
+
+export interface DoctorReport {
+  status: CheckStatus
+  checks: Array<DoctorCheck>
+}
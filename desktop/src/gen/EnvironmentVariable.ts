@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EnvironmentVariableScope } from "./EnvironmentVariableScope"
+import type { EnvironmentVariableSource } from "./EnvironmentVariableSource"
+
+export interface EnvironmentVariable {
+  name: string
+  value: string
+  source: EnvironmentVariableSource
+  scope: EnvironmentVariableScope
+  scopeValue: string | null
+}
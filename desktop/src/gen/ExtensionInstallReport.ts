@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExtensionFailure } from "./ExtensionFailure"
+
+export interface ExtensionInstallReport {
+  installed: Array<string>
+  failed: Array<ExtensionFailure>
+  fromDevcontainer: Array<string>
+}
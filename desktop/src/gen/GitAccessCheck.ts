@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GitAccess } from "./GitAccess"
+import type { GitCredentialSource } from "./GitCredentialSource"
+
+export interface GitAccessCheck {
+  host: string
+  url: string
+  access: GitAccess
+  credentialSource: GitCredentialSource
+  message: string | null
+}
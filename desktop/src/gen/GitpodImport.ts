@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GitpodUntranslated } from "./GitpodUntranslated"
+
+export interface GitpodImport {
+  devcontainerPath: string
+  untranslated: Array<GitpodUntranslated>
+}
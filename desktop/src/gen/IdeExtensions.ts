@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExtensionList } from "./ExtensionList"
+import type { WorkspaceExtensions } from "./WorkspaceExtensions"
+
+export interface IdeExtensions {
+  templates: Record<string, ExtensionList>
+  workspaces: Record<string, WorkspaceExtensions>
+}
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IdeSource } from "./IdeSource"
+
+export interface InstalledIde {
+  id: string
+  name: string
+  ide: string | null
+  version: string | null
+  path: string
+  source: IdeSource
+}
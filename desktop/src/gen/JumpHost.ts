@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface JumpHost {
+  host: string
+  port: number | null
+  user: string | null
+  identityFile: string | null
+}
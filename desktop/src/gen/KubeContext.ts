@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface KubeContext {
+  name: string
+  cluster: string
+  namespace: string | null
+  current: boolean
+}
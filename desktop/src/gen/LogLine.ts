@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LogLevel } from "./LogLevel"
+import type { LogSource } from "./LogSource"
+
+export interface LogLine {
+  source: LogSource
+  timestamp: string | null
+  level: LogLevel | null
+  target: string | null
+  message: string
+}
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LogLine } from "./LogLine"
+
+export interface LogStreamEvent {
+  streamId: string
+  lines: Array<LogLine>
+}
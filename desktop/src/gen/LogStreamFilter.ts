@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LogLevel } from "./LogLevel"
+
+export interface LogStreamFilter {
+  level: LogLevel | null
+  target: string | null
+  text: string | null
+}
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type NotificationEvent =
+  | "workspaceReady"
+  | "workspaceStopped"
+  | "workspaceFailed"
+  | "updateAvailable"
+  | "cliUpdateAvailable"
+  | "lowDiskSpace"
+  | "clipboardGitUrl"
+  | "portDetected"
+  | "connectionLost"
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CheckStatus } from "./CheckStatus"
+import type { OnboardingFix } from "./OnboardingFix"
+import type { OnboardingStepId } from "./OnboardingStepId"
+
+export interface OnboardingStep {
+  id: OnboardingStepId
+  name: string
+  status: CheckStatus
+  message: string
+  remediation: string | null
+  fix: OnboardingFix | null
+  skipped: boolean
+}
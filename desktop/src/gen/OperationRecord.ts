@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OperationSource } from "./OperationSource"
+import type { OperationStatus } from "./OperationStatus"
+
+export interface OperationRecord {
+  id: string
+  source: OperationSource
+  command: string
+  args: Array<string>
+  workspaceId: string | null
+  startedAt: string
+  durationMs: number
+  status: OperationStatus
+  exitCode: number | null
+}
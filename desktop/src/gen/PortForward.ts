@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PortForwardDirection } from "./PortForwardDirection"
+
+export interface PortForward {
+  workspaceId: string
+  localPort: number
+  remotePort: number
+  direction: PortForwardDirection
+}
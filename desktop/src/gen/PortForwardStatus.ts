@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PortForward } from "./PortForward"
+import type { PortForwardState } from "./PortForwardState"
+
+export interface PortForwardStatus {
+  forward: PortForward
+  state: PortForwardState
+  error: string | null
+  transient: boolean
+}
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QueuedCommand } from "./QueuedCommand"
+
+export interface QueueStatus {
+  running: Array<QueuedCommand>
+  waiting: Array<QueuedCommand>
+}
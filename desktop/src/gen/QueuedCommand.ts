@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QueuePriority } from "./QueuePriority"
+
+export interface QueuedCommand {
+  id: number
+  priority: QueuePriority
+  provider: string | null
+  program: string
+  args: string
+  workspaceId: string | null
+  queuedAt: string
+}
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface ReleaseNote {
+  version: string
+  publishedAt: string | null
+  breaking: Array<string>
+  features: Array<string>
+  fixes: Array<string>
+  other: Array<string>
+}
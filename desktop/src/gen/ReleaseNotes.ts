@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReleaseNote } from "./ReleaseNote"
+
+export interface ReleaseNotes {
+  fromVersion: string
+  toVersion: string
+  releases: Array<ReleaseNote>
+}
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ResumableKind } from "./ResumableKind"
+
+export interface ResumableOperation {
+  id: string
+  kind: ResumableKind
+  workspaceId: string
+  resumeArgs: Array<string>
+  rollbackArgs: Array<string> | null
+  startedAt: string
+}
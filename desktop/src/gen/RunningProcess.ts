@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface RunningProcess {
+  id: number
+  pid: number | null
+  program: string
+  args: string
+  workspaceId: string | null
+  operationId: string | null
+  startedAt: string
+  adopted: boolean
+}
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface ScheduledTask {
+  name: string
+  schedule: string
+  defaultSchedule: string
+  deferOnBattery: boolean
+  lastRun: string | null
+  nextRun: string | null
+  running: boolean
+}
@@ -1,5 +1,15 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CustomIde } from "./CustomIde"
+import type { EnvironmentVariable } from "./EnvironmentVariable"
+import type { LogFormat } from "./LogFormat"
+import type { NotificationEvent } from "./NotificationEvent"
+import type { OperationPolicy } from "./OperationPolicy"
+import type { QuietHours } from "./QuietHours"
+import type { ReleaseChannel } from "./ReleaseChannel"
+import type { ResourceLimits } from "./ResourceLimits"
 import type { SidebarPosition } from "./SidebarPosition"
+import type { UpdateSchedule } from "./UpdateSchedule"
+import type { UpdateWhileBusy } from "./UpdateWhileBusy"
 import type { Zoom } from "./Zoom"
 
 export interface Settings {
@@ -10,8 +20,47 @@ export interface Settings {
   zoom: Zoom
   transparency: boolean
   autoUpdate: boolean
+  updateChannel: ReleaseChannel
+  updateSchedule: UpdateSchedule
+  updateWhileBusy: UpdateWhileBusy
   experimental_multiDevcontainer: boolean
   experimental_fleet: boolean
   experimental_jupyterNotebooks: boolean
   experimental_devPodPro: boolean
+  environmentVariables: Array<EnvironmentVariable>
+  logRetentionDays: number
+  logFormat: LogFormat
+  otlpEndpoint: string | null
+  errorReporting: boolean
+  errorReportingDsn: string | null
+  disabledNotifications: Array<NotificationEvent>
+  quietHours: QuietHours | null
+  pinnedWorkspaces: Array<string>
+  stopWorkspacesOnShutdown: boolean
+  lowDiskSpaceWarningGb: number
+  lowDiskSpaceFloorGb: number
+  watchClipboard: boolean
+  manageSshConfig: boolean
+  detectPorts: boolean
+  workspaceHostnames: boolean
+  workspaceHostnamesPort: number
+  keepAliveSeconds: number
+  autoReconnect: boolean
+  reconnectMaxDelaySeconds: number
+  customIdes: Array<CustomIde>
+  terminalScrollbackKb: number
+  vscodeProfile: string | null
+  vscodeReuseWindow: boolean
+  browserIdeIdleTimeoutMinutes: number
+  httpProxy: string | null
+  noProxy: string | null
+  maxConcurrentCommands: number
+  providerConcurrencyLimits: Record<string, number>
+  operationPolicies: Record<string, OperationPolicy>
+  resourceLimits: ResourceLimits
+  workspaceResourceLimits: Record<string, ResourceLimits>
+  statusWidget: boolean
+  fastStart: boolean
+  trustedLinkDomains: Array<string>
+  workspaceTags: Record<string, Array<string>>
 }
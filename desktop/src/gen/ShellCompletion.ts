@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Shell } from "./Shell"
+
+export interface ShellCompletion {
+  shell: Shell
+  detected: boolean
+  installed: boolean
+  path: string
+}
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RunningProcess } from "./RunningProcess"
+import type { ShutdownPhase } from "./ShutdownPhase"
+
+export interface ShutdownProgress {
+  phase: ShutdownPhase
+  activeActions: Array<string>
+  processes: Array<RunningProcess>
+  remainingMs: number
+}
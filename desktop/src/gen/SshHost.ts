@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface SshHost {
+  alias: string
+  hostName: string
+  user: string | null
+  port: number | null
+  identityFile: string | null
+  proxyJump: string | null
+  usedByVscode: boolean
+  provider: string
+  providerOptions: Record<string, string>
+}
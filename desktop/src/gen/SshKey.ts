@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SshKeyType } from "./SshKeyType"
+
+export interface SshKey {
+  name: string
+  workspaceId: string | null
+  keyType: SshKeyType
+  publicKey: string
+  fingerprint: string | null
+  hasPassphrase: boolean
+  createdAt: string | null
+}
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StartupData } from "./StartupData"
+
+export interface StartupDataEvent {
+  data: StartupData
+  value: unknown
+  error: string | null
+}
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StartupPhase } from "./StartupPhase"
+
+export interface StartupProfile {
+  phases: Array<StartupPhase>
+  firstRenderMs: number | null
+  fastStart: boolean
+}
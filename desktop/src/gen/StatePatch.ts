@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SyncedCollection } from "./SyncedCollection"
+
+export interface StatePatch {
+  collection: SyncedCollection
+  revision: number
+  upserted: Record<string, unknown>
+  removed: Array<string>
+}
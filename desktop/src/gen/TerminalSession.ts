@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface TerminalSession {
+  id: string
+  workspaceId: string
+  cols: number
+  rows: number
+  exitCode: number | null
+  exited: boolean
+}
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiskSpaceLevel } from "./DiskSpaceLevel"
+
+export interface Volume {
+  name: string
+  path: string
+  availableBytes: number
+  level: DiskSpaceLevel
+}
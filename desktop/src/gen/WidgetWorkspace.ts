@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WidgetBuild } from "./WidgetBuild"
+
+export interface WidgetWorkspace {
+  id: string
+  state: string | null
+  build: WidgetBuild | null
+}
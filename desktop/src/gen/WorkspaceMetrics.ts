@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface WorkspaceMetrics {
+  workspaceId: string
+  connections: number
+  latencyMs: number | null
+  bytesSent: number
+  bytesReceived: number
+  sendRate: number
+  receiveRate: number
+}
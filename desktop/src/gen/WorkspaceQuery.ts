@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SortDirection } from "./SortDirection"
+import type { WorkspaceSort } from "./WorkspaceSort"
+
+export interface WorkspaceQuery {
+  sort: WorkspaceSort
+  direction: SortDirection
+  filter: string | null
+  provider: string | null
+  cursor: string | null
+  limit: number
+}
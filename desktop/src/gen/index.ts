@@ -1,7 +1,156 @@
+export * from "./ActionOrigin"
+export * from "./Activity"
 export * from "./Asset"
+export * from "./AuditCategory"
+export * from "./AuditEntry"
+export * from "./AuditInitiator"
+export * from "./AuditLogFilter"
 export * from "./Author"
+export * from "./AutostartStatus"
+export * from "./BinaryPayload"
+export * from "./BrowserIde"
+export * from "./BrowserIdeSession"
+export * from "./BrowserIdeSessionState"
+export * from "./CheckStatus"
+export * from "./CliCacheScope"
+export * from "./CliOutput"
+export * from "./CliOutputLine"
+export * from "./CliStatus"
+export * from "./CodespacesCompatibility"
+export * from "./CodespacesField"
+export * from "./CodespacesSupport"
+export * from "./ComposeProject"
+export * from "./ComposeService"
+export * from "./ComposeServiceState"
+export * from "./ComposeServiceStatus"
+export * from "./ComposeSidecarsState"
+export * from "./ComposeSidecarsStatus"
+export * from "./Connection"
+export * from "./ConnectionEvent"
+export * from "./ConnectionEventKind"
+export * from "./ConnectionKind"
+export * from "./ConnectionProxies"
+export * from "./ConnectionProxy"
+export * from "./ConnectionProxyPreset"
+export * from "./ConnectionProxyTest"
+export * from "./CrashKind"
+export * from "./CrashReport"
+export * from "./CrashReportPreview"
+export * from "./CredentialDecision"
+export * from "./CredentialRequest"
+export * from "./CredentialRequester"
+export * from "./CustomIde"
+export * from "./DebugSnapshot"
+export * from "./DeepLinkMechanism"
+export * from "./DeepLinkRegistration"
+export * from "./DevcontainerDiagnostic"
+export * from "./DevcontainerDiagnosticKind"
+export * from "./DevcontainerSeverity"
+export * from "./DiskSpaceLevel"
+export * from "./DiskSpaceStatus"
+export * from "./DockBadge"
+export * from "./DockerContext"
+export * from "./DockerContextCheck"
+export * from "./DoctorCheck"
+export * from "./DoctorReport"
+export * from "./EnvironmentVariable"
+export * from "./EnvironmentVariableScope"
+export * from "./EnvironmentVariableSource"
+export * from "./ExtensionFailure"
+export * from "./ExtensionInstallReport"
+export * from "./ExtensionList"
+export * from "./GitAccess"
+export * from "./GitAccessCheck"
+export * from "./GitCredential"
+export * from "./GitCredentialSource"
+export * from "./GitpodImport"
+export * from "./GitpodUntranslated"
+export * from "./IdeExtensions"
+export * from "./IdeSource"
+export * from "./InstalledIde"
+export * from "./JumpHost"
+export * from "./JumpHostScope"
+export * from "./JumpHosts"
+export * from "./KubeContext"
+export * from "./KubeContextCheck"
+export * from "./KubeProviderContext"
+export * from "./LogDiskUsage"
+export * from "./LogFormat"
+export * from "./LogLevel"
+export * from "./LogLevels"
+export * from "./LogLine"
+export * from "./LogSource"
+export * from "./LogStreamEvent"
+export * from "./LogStreamFilter"
+export * from "./LogWindow"
+export * from "./NetworkStatus"
+export * from "./NotificationEvent"
+export * from "./OfflineUpdateResult"
+export * from "./OnboardingFix"
+export * from "./OnboardingStep"
+export * from "./OnboardingStepId"
+export * from "./OperationHistoryFilter"
+export * from "./OperationPolicy"
+export * from "./OperationRecord"
+export * from "./OperationSource"
+export * from "./OperationStatus"
+export * from "./PayloadEncoding"
+export * from "./PinnedIdeVersion"
+export * from "./PortDecision"
+export * from "./PortForward"
+export * from "./PortForwardDirection"
+export * from "./PortForwardState"
+export * from "./PortForwardStatus"
+export * from "./QueuedCommand"
+export * from "./QueueDepth"
+export * from "./QueuePriority"
+export * from "./QueueStatus"
+export * from "./QuietHours"
 export * from "./Release"
+export * from "./ReleaseChannel"
+export * from "./ReleaseNote"
+export * from "./ReleaseNotes"
+export * from "./ResolvedIdeVersion"
+export * from "./ResourceLimits"
+export * from "./ResumableKind"
+export * from "./ResumableOperation"
+export * from "./RunningProcess"
+export * from "./ScheduledTask"
+export * from "./SecretKind"
 export * from "./Settings"
+export * from "./Shell"
+export * from "./ShellCompletion"
+export * from "./ShutdownPhase"
+export * from "./ShutdownProgress"
 export * from "./SidebarPosition"
+export * from "./SortDirection"
+export * from "./SshHost"
+export * from "./SshKey"
+export * from "./SshKeyType"
+export * from "./StartupData"
+export * from "./StartupDataEvent"
+export * from "./StartupPhase"
+export * from "./StartupProfile"
+export * from "./StatePatch"
+export * from "./StateSnapshot"
+export * from "./StatusWidgetSnapshot"
+export * from "./Subsystem"
+export * from "./SyncedCollection"
+export * from "./TerminalSession"
+export * from "./UpdateSchedule"
+export * from "./UpdateWhileBusy"
+export * from "./Volume"
+export * from "./WidgetBuild"
+export * from "./WidgetWorkspace"
+export * from "./WindowUiState"
+export * from "./WorkspaceDeletion"
+export * from "./WorkspaceExport"
+export * from "./WorkspaceExportTarget"
+export * from "./WorkspaceExtensions"
+export * from "./WorkspaceHostnames"
+export * from "./WorkspaceMetrics"
+export * from "./WorkspacePage"
+export * from "./WorkspaceQuery"
+export * from "./WorkspaceSort"
 export * from "./Zoom"
 export * from "./index"
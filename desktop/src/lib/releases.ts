@@ -2,13 +2,17 @@ import { useQuery } from "@tanstack/react-query"
 import { client } from "../client"
 import { Release } from "../gen"
 import { QueryKeys } from "../queryKeys"
+import { useSubsystemReady } from "../useSubsystemReady"
 
 export function useReleases(): readonly Release[] | undefined {
+  // Releases are fetched by the backend along with the update checks
+  const isReady = useSubsystemReady("updates", { ensure: true })
   const { data: releases } = useQuery({
     queryKey: QueryKeys.RELEASES,
     queryFn: async () => {
       return (await client.fetchReleases()).unwrap()
     },
+    enabled: isReady,
   })
 
   return releases
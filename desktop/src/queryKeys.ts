@@ -15,6 +15,16 @@ export const QueryKeys = {
   PENDING_UPDATE: ["pendingUpdate"],
   INSTALL_UPDATE: ["installUpdate"],
   PRO_INSTANCES: ["proInstances"],
+  NETWORK_STATUS: ["networkStatus"],
+  READY_SUBSYSTEMS: ["readySubsystems"],
+  LOADED_STARTUP_DATA: ["loadedStartupData"],
+  WORKSPACE_PAGES: ["workspacePages"],
+  STARTUP_PROFILE: ["startupProfile"],
+  APP_LOCK: ["appLock"],
+  CREDENTIAL_DECISIONS: ["credentialDecisions"],
+  GIT_CREDENTIALS: ["gitCredentials"],
+  DOCKER_CONTEXTS: ["dockerContexts"],
+  SSH_HOSTS: ["sshHosts"],
   workspace(id: TWorkspaceID): string[] {
     return [...QueryKeys.WORKSPACES, id]
   },
@@ -34,6 +44,18 @@ export const QueryKeys = {
   providerUpdate(id: TProviderID): string[] {
     return [...QueryKeys.provider(id), "update"]
   },
+  providerDockerContext(id: TProviderID): string[] {
+    return [...QueryKeys.provider(id), "docker-context"]
+  },
+  providerKubeContexts(id: TProviderID): string[] {
+    return [...QueryKeys.provider(id), "kube-contexts"]
+  },
+  providerKubeContext(id: TProviderID): string[] {
+    return [...QueryKeys.provider(id), "kube-context"]
+  },
+  providerKubeNamespaces(id: TProviderID, context: string): string[] {
+    return [...QueryKeys.provider(id), "kube-namespaces", context]
+  },
 }
 
 export const MutationKeys = {
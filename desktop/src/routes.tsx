@@ -32,6 +32,7 @@ export const Routes = {
       providerID: TProviderID | null
       ide: string | null
       rawSource: string | null
+      devcontainerPath: string | null
     }>
   ): Partial<Path> {
     const searchParams = new URLSearchParams()
@@ -63,6 +64,7 @@ export const Routes = {
       providerID: TProviderID
       ide: TSupportedIDE
       rawSource: string
+      devcontainerPath: string
     }>
   > {
     return {
@@ -70,6 +72,7 @@ export const Routes = {
       providerID: searchParams.get("providerID") ?? undefined,
       ide: (searchParams.get("ide") as TSupportedIDE | null) ?? undefined,
       rawSource: searchParams.get("rawSource") ?? undefined,
+      devcontainerPath: searchParams.get("devcontainerPath") ?? undefined,
     }
   },
   PROVIDERS: "/providers",
@@ -132,7 +132,8 @@ export type TWorkspace = Readonly<{
   picture: TMaybe<string>
   machine: TMaybe<Readonly<{ machineId: TMaybe<string> }>>
   provider: TMaybe<Readonly<{ name: TMaybe<string> }>>
-  status: TMaybe<"Running" | "Busy" | "Stopped" | "NotFound">
+  // `Unreachable` is only set by the UI, for cloud workspaces while offline
+  status: TMaybe<"Running" | "Busy" | "Stopped" | "NotFound" | "Unreachable">
   ide: TMaybe<{
     name: TMaybe<string>
   }>
@@ -1,6 +1,7 @@
 import {
   Box,
   Button,
+  HStack,
   Heading,
   Link,
   ListItem,
@@ -24,11 +25,17 @@ import { client } from "./client"
 import { ErrorMessageBox } from "./components"
 import { WORKSPACE_SOURCE_BRANCH_DELIMITER, WORKSPACE_SOURCE_COMMIT_DELIMITER } from "./constants"
 import { startWorkspaceAction } from "./contexts"
-import { Release } from "./gen"
+import { Release, ResumableKind } from "./gen"
 import { exists, useReleases, useVersion } from "./lib"
 import { Routes } from "./routes"
+import { getCredentialRequesterLabel } from "./views/Settings/useCredentialDecisions"
 
 const LAST_INSTALLED_VERSION_KEY = "devpod-last-installed-version"
+const RESUMABLE_KIND_LABELS: Record<ResumableKind, string> = {
+  up: "Building",
+  migrate: "Migrating",
+  archive: "Archiving",
+}
 type TLinkClickEvent = React.MouseEvent<HTMLLinkElement> & { target: HTMLLinkElement }
 
 export function useAppReady() {
@@ -137,13 +144,265 @@ export function useAppReady() {
       isReadyLockRef.current = true
       ;(async () => {
         const unsubscribe = await client.subscribe("event", async (event) => {
+          // Only offered while the window is visible, don't steal focus for it
+          if (event.type === "ClipboardUrl") {
+            const id = toast({
+              title: "Create a workspace from the copied URL?",
+              description: (
+                <Box>
+                  <Text wordBreak="break-all">{event.source}</Text>
+                  <Button
+                    marginTop="2"
+                    size="sm"
+                    onClick={() => {
+                      toast.close(id)
+                      client.dropItems([event.url])
+                    }}>
+                    Create Workspace
+                  </Button>
+                </Box>
+              ),
+              status: "info",
+              duration: 15_000,
+              isClosable: true,
+            })
+
+            return
+          }
+
+          if (event.type === "PortDetected") {
+            const decide = async (forward: boolean, remember: boolean) => {
+              toast.close(id)
+              const result = await client.decideDetectedPort(
+                event.workspace_id,
+                event.port,
+                forward,
+                remember
+              )
+              if (result.err) {
+                toast({
+                  title: result.val.message,
+                  status: "error",
+                  duration: 5_000,
+                  isClosable: true,
+                })
+              }
+            }
+            const id = toast({
+              title: `Port ${event.port} is listening in ${event.workspace_id}`,
+              description: (
+                <HStack marginTop="2">
+                  <Button size="sm" onClick={() => decide(true, false)}>
+                    Forward
+                  </Button>
+                  <Button size="sm" variant="outline" onClick={() => decide(true, true)}>
+                    Always
+                  </Button>
+                  <Button size="sm" variant="ghost" onClick={() => decide(false, true)}>
+                    Never
+                  </Button>
+                </HStack>
+              ),
+              status: "info",
+              duration: 15_000,
+              isClosable: true,
+            })
+
+            return
+          }
+
+          if (event.type === "OperationInterrupted") {
+            const decide = async (action: "resume" | "rollBack" | "dismiss") => {
+              toast.close(id)
+              if (action === "dismiss") {
+                client.workspaces.endResumableOperation(event.operation_id)
+
+                return
+              }
+              const result =
+                action === "resume"
+                  ? await client.workspaces.resumeOperation(event.operation_id)
+                  : await client.workspaces.rollBackOperation(event.operation_id)
+              if (result.err) {
+                toast({
+                  title: result.val.message,
+                  status: "error",
+                  duration: 5_000,
+                  isClosable: true,
+                })
+              }
+            }
+            const id = toast({
+              title: `${RESUMABLE_KIND_LABELS[event.kind]} ${event.workspace_id} was interrupted`,
+              description: (
+                <Box>
+                  {event.workspace_state !== null && (
+                    <Text>The workspace is {event.workspace_state.toLowerCase()}</Text>
+                  )}
+                  <HStack marginTop="2">
+                    <Button size="sm" onClick={() => decide("resume")}>
+                      Resume
+                    </Button>
+                    {event.can_roll_back && (
+                      <Button size="sm" variant="outline" onClick={() => decide("rollBack")}>
+                        Roll back
+                      </Button>
+                    )}
+                    <Button size="sm" variant="ghost" onClick={() => decide("dismiss")}>
+                      Dismiss
+                    </Button>
+                  </HStack>
+                </Box>
+              ),
+              status: "warning",
+              duration: null,
+              isClosable: true,
+            })
+
+            return
+          }
+
           await appWindow.setFocus()
+          // Links can come from any web page, they only run right away for domains the user trusts
+          if (
+            (event.type === "OpenWorkspace" || event.type === "ImportWorkspace") &&
+            event.origin === "deepLink" &&
+            !event.trusted
+          ) {
+            const isOpen = event.type === "OpenWorkspace"
+            const target = isOpen ? event.source || event.workspace_id : event.devpod_pro_host
+            const domain = event.consent_domain
+            const decision = await new Promise<Readonly<{ allowed: boolean; remember: boolean }>>(
+              (resolve) => {
+                const decide = (allowed: boolean, remember: boolean) => {
+                  resolve({ allowed, remember })
+                  toast.close(id)
+                }
+                const id = toast({
+                  title: isOpen
+                    ? "A link wants to open a workspace"
+                    : "A link wants to import a workspace",
+                  description: (
+                    <Box>
+                      <Text wordBreak="break-all">{target}</Text>
+                      <HStack marginTop="2">
+                        <Button size="sm" onClick={() => decide(true, false)}>
+                          Allow
+                        </Button>
+                        {domain !== null && (
+                          <Button size="sm" variant="outline" onClick={() => decide(true, true)}>
+                            Always allow {domain}
+                          </Button>
+                        )}
+                        <Button size="sm" variant="ghost" onClick={() => decide(false, false)}>
+                          Cancel
+                        </Button>
+                      </HStack>
+                    </Box>
+                  ),
+                  status: "warning",
+                  duration: null,
+                  isClosable: true,
+                  onCloseComplete: () => resolve({ allowed: false, remember: false }),
+                })
+              }
+            )
+            await client.decideLinkAction(
+              isOpen ? "open" : "import",
+              domain,
+              decision.allowed,
+              decision.remember
+            )
+            if (!decision.allowed) {
+              return
+            }
+          }
+
           if (event.type === "ShowDashboard") {
             navigate(Routes.WORKSPACES)
 
             return
           }
 
+          if (event.type === "CredentialRequested") {
+            const request = {
+              requester: event.requester,
+              kind: event.kind,
+              account: event.account,
+            }
+            const decide = async (allowed: boolean) => {
+              toast.close(id)
+              const result = await client.decideCredentialRequest(request, allowed)
+              if (result.err) {
+                toast({
+                  title: result.val.message,
+                  status: "error",
+                  duration: 5_000,
+                  isClosable: true,
+                })
+              }
+            }
+            const id = toast({
+              title: `Allow access to the stored secret ${request.account}?`,
+              description: (
+                <Box>
+                  <Text>
+                    {getCredentialRequesterLabel(request.requester)} want to use it, your decision
+                    is remembered.
+                  </Text>
+                  <HStack marginTop="2">
+                    <Button size="sm" onClick={() => decide(true)}>
+                      Allow
+                    </Button>
+                    <Button size="sm" variant="ghost" onClick={() => decide(false)}>
+                      Deny
+                    </Button>
+                  </HStack>
+                </Box>
+              ),
+              status: "warning",
+              duration: null,
+              isClosable: true,
+            })
+
+            return
+          }
+
+          if (event.type === "CliTampered") {
+            const repair = async () => {
+              toast.close(id)
+              const result = await client.repairCli()
+              toast({
+                title: result.ok ? "The CLI has been downloaded again" : result.val.message,
+                status: result.ok ? "success" : "error",
+                duration: 5_000,
+                isClosable: true,
+              })
+            }
+            const id = toast({
+              title: "The DevPod CLI has been modified",
+              description: (
+                <Box>
+                  <Text wordBreak="break-all">
+                    {event.path} doesn't match the version DevPod installed and won't be run.
+                  </Text>
+                  {event.can_repair ? (
+                    <Button marginTop="2" size="sm" onClick={repair}>
+                      Re-download
+                    </Button>
+                  ) : (
+                    <Text marginTop="2">Reinstall DevPod to keep using it.</Text>
+                  )}
+                </Box>
+              ),
+              status: "error",
+              duration: null,
+              isClosable: true,
+            })
+
+            return
+          }
+
           if (event.type === "ShowToast") {
             toast({
               title: event.title,
@@ -156,6 +415,32 @@ export function useAppReady() {
             return
           }
 
+          if (event.type === "ShowDiskCleanup") {
+            const diskSpaceResult = await client.getDiskSpace()
+            const volumes = diskSpaceResult.ok
+              ? diskSpaceResult.val.volumes
+                  .map((v) => `${v.name}: ${(v.availableBytes / 1024 ** 3).toFixed(1)} GiB free`)
+                  .join("\n")
+              : ""
+            // eslint-disable-next-line no-alert
+            if (!window.confirm(`${volumes}\n\nPrune stopped containers, unused images and the build cache?`)) {
+              return
+            }
+
+            const pruneResult = await client.pruneDocker()
+            toast({
+              title: pruneResult.ok ? "Docker data pruned" : "Unable to prune Docker data",
+              description: pruneResult.ok
+                ? `Reclaimed ${pruneResult.val}`
+                : pruneResult.val.message,
+              status: pruneResult.ok ? "success" : "error",
+              duration: 5_000,
+              isClosable: true,
+            })
+
+            return
+          }
+
           if (event.type === "CommandFailed") {
             const message = Object.entries(event)
               .filter(([key]) => key !== "type")
@@ -294,6 +579,7 @@ export function useAppReady() {
               providerID: event.provider_id,
               rawSource: event.source,
               ide: event.ide,
+              devcontainerPath: event.devcontainer_path,
             })
           )
         })
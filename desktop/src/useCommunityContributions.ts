@@ -2,11 +2,13 @@ import { useQuery } from "@tanstack/react-query"
 import { client } from "./client"
 import { TCommunityContributions } from "./types"
 import { QueryKeys } from "./queryKeys"
+import { useSubsystemReady } from "./useSubsystemReady"
 
 export function useCommunityContributions(): Readonly<{
   contributions: TCommunityContributions | undefined
   isLoading: boolean
 }> {
+  const isReady = useSubsystemReady("registry", { ensure: true })
   const { data, isLoading } = useQuery({
     queryKey: QueryKeys.COMMUNITY_CONTRIBUTIONS,
     queryFn: async () => {
@@ -15,7 +17,8 @@ export function useCommunityContributions(): Readonly<{
     refetchOnWindowFocus: false,
     refetchOnMount: false,
     refetchOnReconnect: false,
+    enabled: isReady,
   })
 
-  return { contributions: data, isLoading }
+  return { contributions: data, isLoading: !isReady || isLoading }
 }
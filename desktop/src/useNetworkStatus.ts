@@ -0,0 +1,37 @@
+import { useQuery, useQueryClient } from "@tanstack/react-query"
+import { useEffect } from "react"
+import { client } from "./client"
+import { QueryKeys } from "./queryKeys"
+
+// The backend checks connectivity and assumes to be online until the first check says otherwise
+export function useNetworkStatus(): boolean {
+  const queryClient = useQueryClient()
+  const { data } = useQuery({
+    queryKey: QueryKeys.NETWORK_STATUS,
+    queryFn: () => client.getNetworkStatus(),
+    refetchOnWindowFocus: false,
+  })
+
+  useEffect(() => {
+    let isCancelled = false
+    let unsubscribe: VoidFunction | undefined
+    client
+      .subscribe("network_status", (status) => {
+        queryClient.setQueryData(QueryKeys.NETWORK_STATUS, status)
+      })
+      .then((unsubscribeFn) => {
+        if (isCancelled) {
+          unsubscribeFn()
+        } else {
+          unsubscribe = unsubscribeFn
+        }
+      })
+
+    return () => {
+      isCancelled = true
+      unsubscribe?.()
+    }
+  }, [queryClient])
+
+  return data?.online ?? true
+}
@@ -0,0 +1,43 @@
+import { useQuery, useQueryClient } from "@tanstack/react-query"
+import { useEffect } from "react"
+import { client } from "./client"
+import { StartupData } from "./gen"
+import { QueryKeys } from "./queryKeys"
+
+// The backend loads these in parallel on launch, failed loads count as loaded too
+export function useStartupDataLoaded(data: StartupData): boolean {
+  const queryClient = useQueryClient()
+  const { data: loaded } = useQuery({
+    queryKey: QueryKeys.LOADED_STARTUP_DATA,
+    queryFn: () => client.getLoadedStartupData(),
+    refetchOnWindowFocus: false,
+  })
+
+  useEffect(() => {
+    let isCancelled = false
+    let unsubscribe: VoidFunction | undefined
+    client
+      .subscribe("startup_data", (event) => {
+        queryClient.setQueryData<readonly StartupData[]>(
+          QueryKeys.LOADED_STARTUP_DATA,
+          (current = []) => (current.includes(event.data) ? current : [...current, event.data])
+        )
+      })
+      .then((unsubscribeFn) => {
+        if (isCancelled) {
+          unsubscribeFn()
+        } else {
+          unsubscribe = unsubscribeFn
+          // Catches data that was loaded before we were listening
+          queryClient.invalidateQueries(QueryKeys.LOADED_STARTUP_DATA)
+        }
+      })
+
+    return () => {
+      isCancelled = true
+      unsubscribe?.()
+    }
+  }, [queryClient])
+
+  return loaded?.includes(data) ?? false
+}
@@ -0,0 +1,51 @@
+import { useQuery, useQueryClient } from "@tanstack/react-query"
+import { useEffect } from "react"
+import { client } from "./client"
+import { Subsystem } from "./gen"
+import { QueryKeys } from "./queryKeys"
+
+// The backend starts these a little after the window is up, `ensure` starts one on first use
+export function useSubsystemReady(
+  subsystem: Subsystem,
+  { ensure = false }: Readonly<{ ensure?: boolean }> = {}
+): boolean {
+  const queryClient = useQueryClient()
+  const { data } = useQuery({
+    queryKey: QueryKeys.READY_SUBSYSTEMS,
+    queryFn: () => client.getReadySubsystems(),
+    refetchOnWindowFocus: false,
+  })
+
+  useEffect(() => {
+    if (ensure) {
+      client.ensureSubsystem(subsystem)
+    }
+  }, [ensure, subsystem])
+
+  useEffect(() => {
+    let isCancelled = false
+    let unsubscribe: VoidFunction | undefined
+    client
+      .subscribe("subsystem_ready", (ready) => {
+        queryClient.setQueryData<readonly Subsystem[]>(QueryKeys.READY_SUBSYSTEMS, (current = []) =>
+          current.includes(ready) ? current : [...current, ready]
+        )
+      })
+      .then((unsubscribeFn) => {
+        if (isCancelled) {
+          unsubscribeFn()
+        } else {
+          unsubscribe = unsubscribeFn
+          // Catches subsystems that became ready before we were listening
+          queryClient.invalidateQueries(QueryKeys.READY_SUBSYSTEMS)
+        }
+      })
+
+    return () => {
+      isCancelled = true
+      unsubscribe?.()
+    }
+  }, [queryClient])
+
+  return data?.includes(subsystem) ?? false
+}
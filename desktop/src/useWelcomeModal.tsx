@@ -1,4 +1,5 @@
 import {
+  Button,
   Code,
   Heading,
   HStack,
@@ -9,6 +10,7 @@ import {
   ModalContent,
   ModalFooter,
   ModalOverlay,
+  Spinner,
   Text,
   useDisclosure,
   VStack,
@@ -17,12 +19,28 @@ import { useCallback, useEffect, useMemo, useState } from "react"
 import { useNavigate } from "react-router"
 import { client } from "./client"
 import { LoftOSSBadge, Step, Steps, useInstallCLI } from "./components"
-import { Briefcase, CommandLine, DevpodWordmark } from "./icons"
+import { OnboardingFix, OnboardingStep, OnboardingStepId } from "./gen"
+import {
+  Briefcase,
+  CheckCircle,
+  CommandLine,
+  DevpodWordmark,
+  ExclamationCircle,
+  ExclamationTriangle,
+} from "./icons"
 import { Routes } from "./routes"
 
+// Only read to carry over the first visit of installs from before the backend kept track of it
 const IS_FIRST_VISIT_KEY = "devpod-is-first-visit"
+const FIX_LABELS: Record<OnboardingFix, string> = {
+  installDocker: "Download Docker",
+  startDocker: "Start Docker",
+  addDockerProvider: "Add Docker Provider",
+  registerProtocolHandler: "Register",
+}
 
-export function useWelcomeModal() {
+type TWelcomeModalOptions = Readonly<{ showOnFirstRun?: boolean }>
+export function useWelcomeModal({ showOnFirstRun = false }: TWelcomeModalOptions = {}) {
   const navigate = useNavigate()
   const { isOpen, onClose, onOpen } = useDisclosure()
   const [isCancellable, setIsCancellable] = useState(false)
@@ -33,20 +51,28 @@ export function useWelcomeModal() {
     errorMessage: installCLIErrorMessage,
   } = useInstallCLI()
   const handleSetupFinished = useCallback(() => {
+    client.completeOnboarding()
     onClose()
     navigate(Routes.WORKSPACE_CREATE)
   }, [navigate, onClose])
 
-  // Only show the welcome modal once
+  // Only show the welcome modal on the first run, the backend remembers once it's completed
   useEffect(() => {
-    const maybeFirstVisit = localStorage.getItem(IS_FIRST_VISIT_KEY)
-    if (maybeFirstVisit === null && !isOpen) {
-      onOpen()
-      localStorage.setItem(IS_FIRST_VISIT_KEY, "false")
+    if (!showOnFirstRun) {
+      return
+    }
+    if (localStorage.getItem(IS_FIRST_VISIT_KEY) !== null) {
+      client.completeOnboarding()
 
       return
     }
-  }, [isOpen, onOpen])
+
+    client.isOnboardingCompleted().then((completed) => {
+      if (!completed) {
+        onOpen()
+      }
+    })
+  }, [onOpen, showOnFirstRun])
 
   const modal = useMemo(() => {
     return (
@@ -95,6 +121,21 @@ export function useWelcomeModal() {
                   <Text fontWeight="bold">Let&apos;s set you up!</Text>
                 </Step>
 
+                <Step>
+                  <HStack>
+                    <CheckCircle boxSize="6" />
+                    <Heading as="h1" size="lg" marginRight="2">
+                      Prerequisites
+                    </Heading>
+                  </HStack>
+
+                  <Text>
+                    DevPod needs a few things to run workspaces on this computer. Fix what&apos;s
+                    missing right here, or skip it if you&apos;re going to use a remote provider.
+                  </Text>
+                  <Prerequisites />
+                </Step>
+
                 <Step>
                   <HStack>
                     <CommandLine boxSize="6" />
@@ -171,3 +212,100 @@ export function useWelcomeModal() {
 
   return { modal, show }
 }
+
+function Prerequisites() {
+  const [steps, setSteps] = useState<readonly OnboardingStep[] | null>(null)
+  const [fixingStep, setFixingStep] = useState<OnboardingStepId | null>(null)
+  const [errorMessage, setErrorMessage] = useState<string | null>(null)
+
+  useEffect(() => {
+    client.getOnboardingSteps().then((result) => {
+      if (result.ok) {
+        setSteps(result.val)
+      } else {
+        setErrorMessage(result.val.message)
+      }
+    })
+  }, [])
+
+  const updateStep = useCallback((step: OnboardingStep) => {
+    setSteps((current) => current?.map((s) => (s.id === step.id ? step : s)) ?? null)
+  }, [])
+
+  const handleFixClicked = useCallback(
+    async (step: OnboardingStep) => {
+      setFixingStep(step.id)
+      setErrorMessage(null)
+      const result = await client.fixOnboardingStep(step.id)
+      setFixingStep(null)
+      if (result.err) {
+        setErrorMessage(result.val.message)
+
+        return
+      }
+
+      updateStep(result.val)
+    },
+    [updateStep]
+  )
+
+  const handleSkipClicked = useCallback(
+    (step: OnboardingStep) => {
+      client.skipOnboardingStep(step.id)
+      updateStep({ ...step, skipped: true })
+    },
+    [updateStep]
+  )
+
+  if (steps === null) {
+    return errorMessage !== null ? <Text color="red.400">{errorMessage}</Text> : <Spinner />
+  }
+
+  return (
+    <VStack align="start" width="full" spacing="4">
+      {steps.map((step) => (
+        <HStack key={step.id} width="full" justifyContent="space-between" alignItems="start">
+          <HStack alignItems="start">
+            {step.status === "pass" ? (
+              <CheckCircle boxSize="5" color="green.500" />
+            ) : step.status === "warn" ? (
+              <ExclamationTriangle boxSize="5" color="yellow.500" />
+            ) : (
+              <ExclamationCircle boxSize="5" color="red.400" />
+            )}
+            <VStack align="start" spacing="0">
+              <Text fontWeight="bold">{step.name}</Text>
+              <Text>{step.message}</Text>
+              {step.status !== "pass" && step.remediation !== null && (
+                <Text color="gray.500" fontSize="sm">
+                  {step.remediation}
+                </Text>
+              )}
+            </VStack>
+          </HStack>
+          {step.status !== "pass" && (
+            <HStack>
+              {step.fix !== null && (
+                <Button
+                  variant="outline"
+                  isLoading={fixingStep === step.id}
+                  isDisabled={fixingStep !== null && fixingStep !== step.id}
+                  onClick={() => handleFixClicked(step)}>
+                  {FIX_LABELS[step.fix]}
+                </Button>
+              )}
+              {step.skipped ? (
+                <Text color="gray.500">Skipped</Text>
+              ) : (
+                <Button variant="ghost" onClick={() => handleSkipClicked(step)}>
+                  Skip
+                </Button>
+              )}
+            </HStack>
+          )}
+        </HStack>
+      ))}
+      {errorMessage !== null && <Text color="red.400">{errorMessage}</Text>}
+    </VStack>
+  )
+}
@@ -1,3 +1,4 @@
+import { client } from "@/client"
 import { BottomActionBar, BottomActionBarError, Form, useStreamingTerminal } from "@/components"
 import { useProInstances, useProviders } from "@/contexts"
 import { exists, useFormErrors } from "@/lib"
@@ -295,5 +296,11 @@ export function useLoginProModal() {
     providers,
   ])
 
-  return { modal, handleOpenLogin: onOpen }
+  const handleOpenLogin = useCallback(() => {
+    // Pro instances are polled once the user starts using them
+    client.ensureSubsystem("pro")
+    onOpen()
+  }, [onOpen])
+
+  return { modal, handleOpenLogin }
 }
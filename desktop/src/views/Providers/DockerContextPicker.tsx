@@ -0,0 +1,101 @@
+import {
+  Button,
+  FormControl,
+  FormErrorMessage,
+  FormHelperText,
+  FormLabel,
+  HStack,
+  Select,
+} from "@chakra-ui/react"
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
+import { useCallback, useState } from "react"
+import { client } from "../../client"
+import { DockerContextCheck } from "../../gen"
+import { exists } from "../../lib"
+import { QueryKeys } from "../../queryKeys"
+import { TProviderID } from "../../types"
+
+// Empty value of the select, the provider talks to whatever `docker` uses by default
+const DEFAULT_CONTEXT = ""
+
+type TDockerContextPickerProps = Readonly<{ providerID: TProviderID }>
+export function DockerContextPicker({ providerID }: TDockerContextPickerProps) {
+  const queryClient = useQueryClient()
+  const { data: contexts, error: contextsError } = useQuery({
+    queryKey: QueryKeys.DOCKER_CONTEXTS,
+    queryFn: async () => (await client.providers.listDockerContexts()).unwrap(),
+  })
+  const { data: selected } = useQuery({
+    queryKey: QueryKeys.providerDockerContext(providerID),
+    queryFn: async () => (await client.providers.getDockerContext(providerID)).unwrap(),
+  })
+  const [check, setCheck] = useState<DockerContextCheck | undefined>(undefined)
+
+  const {
+    mutate: select,
+    status: selectStatus,
+    error: selectError,
+  } = useMutation<void, Error, string | null>({
+    mutationFn: async (context) =>
+      (await client.providers.setDockerContext(providerID, context)).unwrap(),
+    onSettled: () => {
+      setCheck(undefined)
+      queryClient.invalidateQueries(QueryKeys.provider(providerID))
+    },
+  })
+  const {
+    mutate: checkContext,
+    status: checkStatus,
+    error: checkError,
+  } = useMutation<DockerContextCheck | undefined, Error, string>({
+    mutationFn: async (context) => (await client.providers.checkDockerContext(context)).unwrap(),
+    onSuccess: (result) => setCheck(result),
+  })
+
+  const handleChange = useCallback(
+    (context: string) => select(context === DEFAULT_CONTEXT ? null : context),
+    [select]
+  )
+  const current = contexts?.find((context) => context.current)?.name
+  const error = contextsError ?? selectError ?? checkError
+
+  return (
+    <FormControl isInvalid={exists(error) || check?.reachable === false} marginBottom="8">
+      <FormLabel>Docker Context</FormLabel>
+      <HStack>
+        <Select
+          value={selected ?? DEFAULT_CONTEXT}
+          isDisabled={!exists(contexts) || selectStatus === "loading"}
+          onChange={(e) => handleChange(e.target.value)}>
+          <option value={DEFAULT_CONTEXT}>
+            Default{exists(current) ? ` (currently ${current})` : ""}
+          </option>
+          {contexts?.map((context) => (
+            <option key={context.name} value={context.name} disabled={exists(context.error)}>
+              {context.name} - {context.description ?? context.endpoint}
+            </option>
+          ))}
+        </Select>
+        <Button
+          flexShrink={0}
+          isDisabled={!exists(selected ?? current)}
+          isLoading={checkStatus === "loading"}
+          onClick={() => checkContext(selected ?? current ?? "")}>
+          Test Connection
+        </Button>
+      </HStack>
+      {exists(error) ? (
+        <FormErrorMessage>{(error as Error).message}</FormErrorMessage>
+      ) : check?.reachable === false ? (
+        <FormErrorMessage>Unable to reach Docker: {check.error}</FormErrorMessage>
+      ) : check?.reachable ? (
+        <FormHelperText>Connected to Docker {check.serverVersion ?? ""}</FormHelperText>
+      ) : (
+        <FormHelperText>
+          The Docker daemon this provider creates workspaces on, e.g. colima, a podman socket or a
+          remote host. Other providers keep theirs.
+        </FormHelperText>
+      )}
+    </FormControl>
+  )
+}
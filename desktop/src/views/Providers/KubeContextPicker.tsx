@@ -0,0 +1,141 @@
+import {
+  Button,
+  FormControl,
+  FormErrorMessage,
+  FormHelperText,
+  FormLabel,
+  HStack,
+  Select,
+} from "@chakra-ui/react"
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
+import { useState } from "react"
+import { client } from "../../client"
+import { KubeContextCheck, KubeProviderContext } from "../../gen"
+import { exists } from "../../lib"
+import { QueryKeys } from "../../queryKeys"
+import { TProviderID } from "../../types"
+
+// Empty value of the selects, the provider uses what the kubeconfig defaults to
+const DEFAULT_VALUE = ""
+const DEFAULT_NAMESPACE = "default"
+
+type TKubeContextPickerProps = Readonly<{ providerID: TProviderID }>
+export function KubeContextPicker({ providerID }: TKubeContextPickerProps) {
+  const queryClient = useQueryClient()
+  const { data: contexts, error: contextsError } = useQuery({
+    queryKey: QueryKeys.providerKubeContexts(providerID),
+    queryFn: async () => (await client.providers.listKubeContexts(providerID)).unwrap(),
+  })
+  const { data: selected } = useQuery({
+    queryKey: QueryKeys.providerKubeContext(providerID),
+    queryFn: async () => (await client.providers.getKubeContext(providerID)).unwrap(),
+  })
+  const context =
+    selected?.context ?? contexts?.find((kubeContext) => kubeContext.current)?.name ?? undefined
+  const contextNamespace = contexts?.find((kubeContext) => kubeContext.name === context)?.namespace
+  const namespace = selected?.namespace ?? contextNamespace ?? DEFAULT_NAMESPACE
+  // Listing namespaces needs cluster wide permissions, the current namespace is offered regardless
+  const { data: namespaces } = useQuery({
+    queryKey: QueryKeys.providerKubeNamespaces(providerID, context ?? DEFAULT_VALUE),
+    queryFn: async () =>
+      (await client.providers.listKubeNamespaces(providerID, context ?? DEFAULT_VALUE)).unwrap(),
+    enabled: exists(context),
+    retry: false,
+  })
+  const namespaceOptions = Array.from(new Set([namespace, ...(namespaces ?? [])]))
+  const [check, setCheck] = useState<KubeContextCheck | undefined>(undefined)
+
+  const {
+    mutate: select,
+    status: selectStatus,
+    error: selectError,
+  } = useMutation<void, Error, KubeProviderContext>({
+    mutationFn: async (kubeContext) =>
+      (await client.providers.setKubeContext(providerID, kubeContext)).unwrap(),
+    onSettled: () => {
+      setCheck(undefined)
+      queryClient.invalidateQueries(QueryKeys.provider(providerID))
+    },
+  })
+  const {
+    mutate: checkContext,
+    status: checkStatus,
+    error: checkError,
+  } = useMutation<KubeContextCheck | undefined, Error, void>({
+    mutationFn: async () =>
+      (
+        await client.providers.checkKubeContext(providerID, context ?? DEFAULT_VALUE, namespace)
+      ).unwrap(),
+    onSuccess: (result) => setCheck(result),
+  })
+
+  const error = contextsError ?? selectError ?? checkError
+  const isBusy = !exists(contexts) || selectStatus === "loading"
+  const checkFailed = exists(check) && (!check.canListPods || exists(check.storageError))
+
+  return (
+    <FormControl isInvalid={exists(error) || checkFailed} marginBottom="8">
+      <FormLabel>Kubernetes Context</FormLabel>
+      <HStack>
+        <Select
+          value={selected?.context ?? DEFAULT_VALUE}
+          isDisabled={isBusy}
+          onChange={(e) =>
+            // Namespaces differ between clusters, the new context starts out with its own
+            select({
+              context: e.target.value === DEFAULT_VALUE ? null : e.target.value,
+              namespace: null,
+            })
+          }>
+          <option value={DEFAULT_VALUE}>
+            Default{exists(context) && !exists(selected?.context) ? ` (currently ${context})` : ""}
+          </option>
+          {contexts?.map((kubeContext) => (
+            <option key={kubeContext.name} value={kubeContext.name}>
+              {kubeContext.name} - {kubeContext.cluster}
+            </option>
+          ))}
+        </Select>
+        <Select
+          value={namespace}
+          isDisabled={isBusy || !exists(context)}
+          onChange={(e) =>
+            select({
+              context: selected?.context ?? null,
+              namespace: e.target.value === contextNamespace ? null : e.target.value,
+            })
+          }>
+          {namespaceOptions.map((name) => (
+            <option key={name} value={name}>
+              {name}
+            </option>
+          ))}
+        </Select>
+        <Button
+          flexShrink={0}
+          isDisabled={!exists(context)}
+          isLoading={checkStatus === "loading"}
+          onClick={() => checkContext()}>
+          Test Access
+        </Button>
+      </HStack>
+      {exists(error) ? (
+        <FormErrorMessage>{(error as Error).message}</FormErrorMessage>
+      ) : exists(check) && !check.canListPods ? (
+        <FormErrorMessage>Unable to list pods in {namespace}: {check.podsError}</FormErrorMessage>
+      ) : exists(check?.storageError) ? (
+        <FormErrorMessage>Unable to find a storage class: {check?.storageError}</FormErrorMessage>
+      ) : exists(check) ? (
+        <FormHelperText>
+          Pods can be listed in {namespace}, volumes use{" "}
+          {check.defaultStorageClass ?? `one of ${check.storageClasses.join(", ")}`}
+        </FormHelperText>
+      ) : (
+        <FormHelperText>
+          The cluster and namespace this provider creates workspaces in, from your kubeconfig.
+          Other providers keep theirs.
+        </FormHelperText>
+      )}
+    </FormControl>
+  )
+}
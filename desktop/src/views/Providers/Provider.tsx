@@ -5,6 +5,12 @@ import { useProvider } from "../../contexts"
 import { exists } from "../../lib"
 import { Routes } from "../../routes"
 import { ConfigureProviderOptionsForm } from "./AddProvider"
+import { DockerContextPicker } from "./DockerContextPicker"
+import { KubeContextPicker } from "./KubeContextPicker"
+
+// Declared by the providers that talk to a docker daemon or a kubernetes cluster
+const DOCKER_HOST_OPTION = "DOCKER_HOST"
+const KUBERNETES_CONTEXT_OPTION = "KUBERNETES_CONTEXT"
 
 export function Provider() {
   const navigate = useNavigate()
@@ -22,6 +28,12 @@ export function Provider() {
 
   return (
     <Container width="full" maxWidth="container.lg" ref={containerRef}>
+      {exists(provider.config?.options?.[DOCKER_HOST_OPTION]) && (
+        <DockerContextPicker providerID={providerID} />
+      )}
+      {exists(provider.config?.options?.[KUBERNETES_CONTEXT_OPTION]) && (
+        <KubeContextPicker providerID={providerID} />
+      )}
       <ConfigureProviderOptionsForm
         containerRef={containerRef}
         providerID={providerID}
@@ -0,0 +1,172 @@
+import {
+  Button,
+  Checkbox,
+  HStack,
+  Modal,
+  ModalBody,
+  ModalCloseButton,
+  ModalContent,
+  ModalFooter,
+  ModalHeader,
+  ModalOverlay,
+  Portal,
+  Text,
+  VStack,
+  useDisclosure,
+} from "@chakra-ui/react"
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
+import { useCallback, useMemo, useState } from "react"
+import { useNavigate } from "react-router"
+import { client } from "../../client"
+import { useProviders } from "../../contexts"
+import { SshHost } from "../../gen"
+import { exists } from "../../lib"
+import { QueryKeys } from "../../queryKeys"
+import { Routes } from "../../routes"
+import { TProviderID } from "../../types"
+
+const SSH_PROVIDER = "ssh"
+
+function describe(host: SshHost): string {
+  const target = `${exists(host.user) ? `${host.user}@` : ""}${host.hostName}`
+  const port = exists(host.port) ? `:${host.port}` : ""
+
+  return `${target}${port}${exists(host.identityFile) ? ` with ${host.identityFile}` : ""}`
+}
+
+export function useImportSshHostsModal() {
+  const navigate = useNavigate()
+  const queryClient = useQueryClient()
+  const { isOpen, onOpen, onClose } = useDisclosure()
+  const [[providers]] = useProviders()
+  const { data: hosts } = useQuery({
+    queryKey: QueryKeys.SSH_HOSTS,
+    queryFn: () => client.providers.listSshHosts(),
+    enabled: isOpen,
+  })
+  const [selected, setSelected] = useState<ReadonlySet<string>>(new Set())
+
+  const isImported = useCallback(
+    (host: SshHost) => !!providers?.[host.provider]?.state?.initialized,
+    [providers]
+  )
+  const toggle = useCallback((alias: string) => {
+    setSelected((current) => {
+      const next = new Set(current)
+      if (!next.delete(alias)) {
+        next.add(alias)
+      }
+
+      return next
+    })
+  }, [])
+
+  // Every host gets its own instance of the SSH provider, prefilled from the SSH config
+  const {
+    mutate: importHosts,
+    status,
+    error,
+  } = useMutation<void, Error, readonly SshHost[]>({
+    mutationFn: async (hostsToImport) => {
+      for (const host of hostsToImport) {
+        ;(await client.providers.add(SSH_PROVIDER, { name: host.provider })).unwrap()
+        ;(
+          await client.providers.configure(host.provider, { options: host.providerOptions })
+        ).unwrap()
+      }
+    },
+    onSettled: () => {
+      setSelected(new Set())
+      queryClient.invalidateQueries(QueryKeys.PROVIDERS)
+    },
+  })
+
+  const createWorkspace = useCallback(
+    (providerID: TProviderID) => {
+      onClose()
+      navigate(
+        Routes.toWorkspaceCreate({
+          providerID,
+          workspaceID: null,
+          ide: null,
+          rawSource: null,
+          devcontainerPath: null,
+        })
+      )
+    },
+    [navigate, onClose]
+  )
+
+  const modal = useMemo(() => {
+    const selectedHosts = hosts?.filter((host) => selected.has(host.alias) && !isImported(host))
+
+    return (
+      <Portal>
+        <Modal onClose={onClose} isOpen={isOpen} size="2xl" scrollBehavior="inside" isCentered>
+          <ModalOverlay />
+          <ModalContent>
+            <ModalHeader>Import SSH Hosts</ModalHeader>
+            <ModalCloseButton />
+            <ModalBody>
+              {hosts?.length === 0 ? (
+                <Text>No hosts found in your SSH config or VS Code Remote-SSH.</Text>
+              ) : (
+                <VStack align="start">
+                  {hosts?.map((host) => (
+                    <HStack key={host.alias} width="full" justify="space-between">
+                      <Checkbox
+                        isChecked={isImported(host) || selected.has(host.alias)}
+                        isDisabled={isImported(host)}
+                        onChange={() => toggle(host.alias)}>
+                        <Text fontWeight="semibold">{host.alias}</Text>
+                        <Text fontSize="sm" color="gray.500">
+                          {describe(host)}
+                          {host.usedByVscode ? " - used by VS Code" : ""}
+                        </Text>
+                      </Checkbox>
+                      {isImported(host) && (
+                        <Button size="sm" onClick={() => createWorkspace(host.provider)}>
+                          Create Workspace
+                        </Button>
+                      )}
+                    </HStack>
+                  ))}
+                </VStack>
+              )}
+              {exists(error) && (
+                <Text color="red.500" marginTop="4">
+                  {error.message}
+                </Text>
+              )}
+            </ModalBody>
+            <ModalFooter>
+              <HStack spacing="2">
+                <Button onClick={onClose}>Close</Button>
+                <Button
+                  variant="primary"
+                  isDisabled={!selectedHosts?.length}
+                  isLoading={status === "loading"}
+                  onClick={() => importHosts(selectedHosts ?? [])}>
+                  Import
+                </Button>
+              </HStack>
+            </ModalFooter>
+          </ModalContent>
+        </Modal>
+      </Portal>
+    )
+  }, [
+    createWorkspace,
+    error,
+    hosts,
+    importHosts,
+    isImported,
+    isOpen,
+    onClose,
+    selected,
+    status,
+    toggle,
+  ])
+
+  return { modal, open: onOpen }
+}
@@ -1,10 +1,11 @@
-import { Button, IconButton } from "@chakra-ui/react"
+import { Button, HStack, IconButton } from "@chakra-ui/react"
 import { useCallback, useMemo } from "react"
 import { useMatch, useNavigate } from "react-router"
 import { TViewTitle } from "../../components"
 import { ArrowLeft, Plus } from "../../icons"
 import { exists } from "../../lib"
 import { Routes } from "../../routes"
+import { useImportSshHostsModal } from "./useImportSshHostsModal"
 import { useSetupProviderModal } from "./useSetupProviderModal"
 
 export function useProviderTitle(): TViewTitle | null {
@@ -13,6 +14,7 @@ export function useProviderTitle(): TViewTitle | null {
   const matchProviderRoot = useMatch(Routes.PROVIDERS)
   const matchProvider = useMatch(Routes.PROVIDER)
   const { modal, show: showSetupProvider } = useSetupProviderModal()
+  const { modal: importSshHostsModal, open: openImportSshHosts } = useImportSshHostsModal()
 
   const navigateToProviderRoot = useCallback(() => {
     navigate(Routes.PROVIDERS)
@@ -35,7 +37,14 @@ export function useProviderTitle(): TViewTitle | null {
         label: "Providers",
         priority: "high",
         trailingAction: (
-          <>
+          <HStack>
+            <Button
+              size="sm"
+              variant="outline"
+              aria-label="Import SSH hosts"
+              onClick={openImportSshHosts}>
+              Import SSH Hosts
+            </Button>
             <Button
               size="sm"
               variant="outline"
@@ -45,7 +54,8 @@ export function useProviderTitle(): TViewTitle | null {
               Add
             </Button>
             {modal}
-          </>
+            {importSshHostsModal}
+          </HStack>
         ),
       }
     }
@@ -61,5 +71,13 @@ export function useProviderTitle(): TViewTitle | null {
     }
 
     return null
-  }, [matchProvider, matchProviderRoot, modal, navigateBackAction, showSetupProvider])
+  }, [
+    importSshHostsModal,
+    matchProvider,
+    matchProviderRoot,
+    modal,
+    navigateBackAction,
+    openImportSshHosts,
+    showSetupProvider,
+  ])
 }
@@ -22,10 +22,12 @@ import {
   VStack,
   useColorModeValue,
 } from "@chakra-ui/react"
+import { useQuery } from "@tanstack/react-query"
+import { appWindow } from "@tauri-apps/api/window"
 import { ReactNode, useEffect, useMemo, useState } from "react"
 import { HiMagnifyingGlassPlus } from "react-icons/hi2"
 import { client } from "../../client"
-import { ToolbarTitle, useInstallCLI } from "../../components"
+import { ToolbarActions, ToolbarTitle, useInstallCLI } from "../../components"
 import { TSettings, useChangeSettings } from "../../contexts"
 import {
   getIDEDisplayName,
@@ -36,7 +38,11 @@ import {
   useUpdate,
   useVersion,
 } from "../../lib"
+import { QueryKeys } from "../../queryKeys"
 import { useWelcomeModal } from "../../useWelcomeModal"
+import { useAppLock } from "./useAppLock"
+import { useCredentialDecisions } from "./useCredentialDecisions"
+import { useGitCredentials } from "./useGitCredentials"
 import { useAgentURLOption } from "./useContextOptions"
 import { useIDESettings } from "./useIDESettings"
 
@@ -56,6 +62,13 @@ export function Settings() {
           Settings
         </Heading>
       </ToolbarTitle>
+      {appWindow.label === "main" && (
+        <ToolbarActions>
+          <Button size="sm" variant="outline" onClick={() => client.showSettings()}>
+            Open in Window
+          </Button>
+        </ToolbarActions>
+      )}
 
       <Tabs isLazy isFitted variant="muted">
         <TabList marginBottom="6">
@@ -83,6 +96,15 @@ function GeneralSettings() {
     helpText: installCLIHelpText,
     errorMessage: installCLIErrorMessage,
   } = useInstallCLI()
+  const { data: startupProfile } = useQuery({
+    queryKey: QueryKeys.STARTUP_PROFILE,
+    queryFn: () => client.getStartupProfile(),
+  })
+  const firstRenderMs = startupProfile?.firstRenderMs ?? undefined
+  const appLock = useAppLock()
+  const credentialDecisions = useCredentialDecisions()
+  const allowedCredentials = credentialDecisions.decisions.filter((decision) => decision.allowed)
+  const gitCredentials = useGitCredentials()
 
   return (
     <>
@@ -112,6 +134,73 @@ function GeneralSettings() {
         />
       </SettingSection>
 
+      <SettingSection
+        title="Fast start"
+        description={
+          <>
+            Show the window before setting up menus, port forwarding and other background services.
+            Takes effect on the next start.
+            {firstRenderMs !== undefined && <> Last start took {firstRenderMs}ms.</>}
+          </>
+        }>
+        <Switch
+          isChecked={settings.fastStart}
+          onChange={(e) => set("fastStart", e.target.checked)}
+        />
+      </SettingSection>
+
+      <SettingSection
+        title="Trusted link domains"
+        description={
+          settings.trustedLinkDomains.length > 0
+            ? `DevPod links for ${settings.trustedLinkDomains.join(", ")} run without asking`
+            : "DevPod links from web pages ask before opening a workspace"
+        }>
+        <Button
+          variant="outline"
+          isDisabled={settings.trustedLinkDomains.length === 0}
+          onClick={() => set("trustedLinkDomains", [])}>
+          Forget
+        </Button>
+      </SettingSection>
+
+      <SettingSection
+        title="App lock"
+        description={
+          appLock.errorMessage ??
+          "Ask for Touch ID, Windows Hello or your password before deleting workspaces, providers or stored secrets"
+        }>
+        <Switch
+          isChecked={appLock.isEnabled}
+          onChange={(e) => appLock.setEnabled(e.target.checked)}
+        />
+      </SettingSection>
+
+      <SettingSection
+        title="Credential access"
+        description={
+          credentialDecisions.errorMessage ??
+          (credentialDecisions.decisions.length > 0
+            ? `${allowedCredentials.length} of ${credentialDecisions.decisions.length} requests for stored secrets have been allowed`
+            : "Workspaces ask before they use a stored secret for the first time")
+        }>
+        <Button
+          variant="outline"
+          isDisabled={credentialDecisions.decisions.length === 0}
+          onClick={() => credentialDecisions.forget()}>
+          Forget
+        </Button>
+      </SettingSection>
+
+      <SettingSection
+        title="Git credentials"
+        description={
+          gitCredentials.errorMessage ??
+          "Personal access tokens for private repositories, your git credential helpers are used for other hosts"
+        }>
+        {gitCredentials.content}
+      </SettingSection>
+
       <SettingSection
         showDivider={false}
         title="Show Intro"
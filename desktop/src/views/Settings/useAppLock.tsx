@@ -0,0 +1,23 @@
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
+import { useMemo } from "react"
+import { client } from "../../client"
+import { QueryKeys } from "../../queryKeys"
+
+export function useAppLock() {
+  const queryClient = useQueryClient()
+  const { data: isEnabled } = useQuery({
+    queryKey: QueryKeys.APP_LOCK,
+    queryFn: () => client.getAppLock(),
+  })
+  const { mutate: setEnabled, error } = useMutation<boolean, Error, boolean>({
+    mutationFn: async (enabled) => (await client.setAppLock(enabled)).unwrap(),
+    onSettled: () => {
+      queryClient.invalidateQueries(QueryKeys.APP_LOCK)
+    },
+  })
+
+  return useMemo(
+    () => ({ isEnabled: isEnabled ?? false, setEnabled, errorMessage: error?.message }),
+    [error, isEnabled, setEnabled]
+  )
+}
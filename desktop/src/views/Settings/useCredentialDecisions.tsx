@@ -0,0 +1,35 @@
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
+import { useMemo } from "react"
+import { client } from "../../client"
+import { CredentialRequester } from "../../gen"
+import { QueryKeys } from "../../queryKeys"
+
+export function getCredentialRequesterLabel(requester: CredentialRequester): string {
+  switch (requester.kind) {
+    case "provider":
+      return `Workspaces of provider ${requester.name}`
+    case "tag":
+      return `Workspaces tagged ${requester.name}`
+    case "allWorkspaces":
+      return "All workspaces"
+  }
+}
+
+export function useCredentialDecisions() {
+  const queryClient = useQueryClient()
+  const { data: decisions } = useQuery({
+    queryKey: QueryKeys.CREDENTIAL_DECISIONS,
+    queryFn: () => client.getCredentialDecisions(),
+  })
+  const { mutate: forget, error } = useMutation<void, Error>({
+    mutationFn: async () => (await client.forgetCredentialDecisions()).unwrap(),
+    onSettled: () => {
+      queryClient.invalidateQueries(QueryKeys.CREDENTIAL_DECISIONS)
+    },
+  })
+
+  return useMemo(
+    () => ({ decisions: decisions ?? [], forget, errorMessage: error?.message }),
+    [decisions, error, forget]
+  )
+}
@@ -0,0 +1,91 @@
+import { Button, HStack, Input, Text, VStack } from "@chakra-ui/react"
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query"
+import { useMemo, useState } from "react"
+import { client } from "../../client"
+import { QueryKeys } from "../../queryKeys"
+
+type TNewGitCredential = Readonly<{ host: string; username: string; token: string }>
+const EMPTY_CREDENTIAL: TNewGitCredential = { host: "", username: "", token: "" }
+
+export function useGitCredentials() {
+  const queryClient = useQueryClient()
+  const { data: credentials } = useQuery({
+    queryKey: QueryKeys.GIT_CREDENTIALS,
+    queryFn: () => client.getGitCredentials(),
+  })
+  const [newCredential, setNewCredential] = useState<TNewGitCredential>(EMPTY_CREDENTIAL)
+
+  const {
+    mutate: add,
+    status: addStatus,
+    error: addError,
+  } = useMutation<void, Error, TNewGitCredential>({
+    mutationFn: async ({ host, username, token }) =>
+      (await client.setGitCredential(host, username.trim() || null, token)).unwrap(),
+    onSuccess: () => setNewCredential(EMPTY_CREDENTIAL),
+    onSettled: () => {
+      queryClient.invalidateQueries(QueryKeys.GIT_CREDENTIALS)
+    },
+  })
+  const { mutate: remove, error: removeError } = useMutation<void, Error, string>({
+    mutationFn: async (host) => (await client.deleteGitCredential(host)).unwrap(),
+    onSettled: () => {
+      queryClient.invalidateQueries(QueryKeys.GIT_CREDENTIALS)
+    },
+  })
+
+  const content = useMemo(
+    () => (
+      <VStack align="start" width="full">
+        {credentials?.map((credential) => (
+          <HStack key={credential.host} width="full" justify="space-between">
+            <Text>
+              {credential.host}
+              {credential.username !== null ? ` as ${credential.username}` : ""}
+              {credential.hasToken ? "" : " - token missing"}
+            </Text>
+            <Button size="sm" variant="outline" onClick={() => remove(credential.host)}>
+              Remove
+            </Button>
+          </HStack>
+        ))}
+        <HStack width="full">
+          <Input
+            spellCheck={false}
+            placeholder="github.com"
+            value={newCredential.host}
+            onChange={(e) => setNewCredential((current) => ({ ...current, host: e.target.value }))}
+          />
+          <Input
+            spellCheck={false}
+            placeholder="Username (optional)"
+            value={newCredential.username}
+            onChange={(e) =>
+              setNewCredential((current) => ({ ...current, username: e.target.value }))
+            }
+          />
+          <Input
+            type="password"
+            placeholder="Personal access token"
+            value={newCredential.token}
+            onChange={(e) => setNewCredential((current) => ({ ...current, token: e.target.value }))}
+          />
+          <Button
+            flexShrink={0}
+            variant="outline"
+            isDisabled={newCredential.host.trim() === "" || newCredential.token === ""}
+            isLoading={addStatus === "loading"}
+            onClick={() => add(newCredential)}>
+            Add
+          </Button>
+        </HStack>
+      </VStack>
+    ),
+    [add, addStatus, credentials, newCredential, remove]
+  )
+
+  return useMemo(
+    () => ({ content, errorMessage: addError?.message ?? removeError?.message }),
+    [addError, content, removeError]
+  )
+}
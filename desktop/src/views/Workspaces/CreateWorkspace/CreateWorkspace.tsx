@@ -2,6 +2,7 @@ import { ExampleCard, Form, IDEIcon, WarningMessageBox } from "@/components"
 import {
   Box,
   Button,
+  Code,
   FormControl,
   FormErrorMessage,
   FormHelperText,
@@ -17,6 +18,7 @@ import {
   Tooltip,
   useColorMode,
   useColorModeValue,
+  useToast,
   useToken,
   VStack,
 } from "@chakra-ui/react"
@@ -55,6 +57,7 @@ export function CreateWorkspace() {
   const workspace = useWorkspace(undefined)
   const [[providers]] = useProviders()
 
+  const toast = useToast()
   const handleCreateWorkspace = useCallback(
     ({
       workspaceID,
@@ -79,8 +82,34 @@ export function CreateWorkspace() {
       if (!isEmpty(actionID)) {
         navigate(Routes.toAction(actionID, Routes.WORKSPACES))
       }
+
+      // Settings meant for GitHub Codespaces only, the workspace is created regardless
+      client
+        .checkCodespacesCompatibility(workspaceSource, devcontainerPath, providerID)
+        .then((compatibility) => {
+          const fields = compatibility?.fields.filter(({ support }) => support !== "supported")
+          if (fields === undefined || fields.length === 0) {
+            return
+          }
+
+          toast({
+            title: "Codespaces settings need your attention",
+            description: (
+              <VStack align="start" spacing="0">
+                {fields.map(({ path, message }) => (
+                  <Text key={path}>
+                    <Code>{path}</Code> {message}
+                  </Text>
+                ))}
+              </VStack>
+            ),
+            status: "warning",
+            duration: null,
+            isClosable: true,
+          })
+        })
     },
-    [navigate, workspace]
+    [navigate, toast, workspace]
   )
 
   const {
@@ -120,13 +149,54 @@ export function CreateWorkspace() {
 
   const handleSelectFolderClicked = useCallback(async () => {
     const selected = await client.selectFromDir()
-    if (typeof selected === "string") {
-      setValue(FieldName.SOURCE, selected, {
-        shouldDirty: true,
-        shouldValidate: true,
+    if (typeof selected !== "string") {
+      return
+    }
+    setValue(FieldName.SOURCE, selected, {
+      shouldDirty: true,
+      shouldValidate: true,
+    })
+
+    // Folders that only come with a `.gitpod.yml` get a devcontainer config imported from it
+    const res = await client.importGitpodWorkspace(selected)
+    if (res.err) {
+      toast({
+        title: "Failed to import .gitpod.yml",
+        description: res.val.message,
+        status: "error",
+        duration: 5_000,
+        isClosable: true,
       })
+
+      return
+    }
+    if (res.val === null) {
+      return
     }
-  }, [setValue])
+
+    const { devcontainerPath, untranslated } = res.val
+    setValue(FieldName.DEVCONTAINER_PATH, devcontainerPath, {
+      shouldDirty: true,
+      shouldValidate: true,
+    })
+    toast({
+      title: `Imported .gitpod.yml into ${devcontainerPath}`,
+      description:
+        untranslated.length > 0 ? (
+          <VStack align="start" spacing="0">
+            <Text>These settings couldn&apos;t be translated:</Text>
+            {untranslated.map(({ path, reason }) => (
+              <Text key={path}>
+                <Code>{path}</Code> {reason}
+              </Text>
+            ))}
+          </VStack>
+        ) : undefined,
+      status: untranslated.length > 0 ? "warning" : "success",
+      duration: untranslated.length > 0 ? null : 5_000,
+      isClosable: true,
+    })
+  }, [setValue, toast])
 
   const handleExampleCardClicked = useCallback(
     (newSource: string) => {
@@ -77,6 +77,10 @@ export function useCreateWorkspaceForm(
       setValue(FieldName.SOURCE, params.rawSource, opts)
     }
 
+    if (params.devcontainerPath !== undefined) {
+      setValue(FieldName.DEVCONTAINER_PATH, params.devcontainerPath, opts)
+    }
+
     // default ide
     if (params.ide !== undefined) {
       setValue(FieldName.DEFAULT_IDE, params.ide, opts)
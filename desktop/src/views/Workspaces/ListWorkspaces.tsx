@@ -1,47 +1,121 @@
-import { Button, Text, VStack } from "@chakra-ui/react"
-import { useMemo } from "react"
+import { Button, HStack, Input, Select, Spinner, Text, VStack } from "@chakra-ui/react"
+import { useInfiniteQuery, useQueryClient } from "@tanstack/react-query"
+import { useEffect, useMemo, useState } from "react"
 import { useNavigate } from "react-router"
-import { useWorkspaces } from "../../contexts"
+import { client } from "../../client"
+import { SortDirection, WorkspaceSort } from "../../gen"
 import { exists } from "../../lib"
+import { QueryKeys } from "../../queryKeys"
 import { Routes } from "../../routes"
 import { TWorkspace } from "../../types"
+import { useStartupDataLoaded } from "../../useStartupDataLoaded"
 import { WorkspaceCard } from "./WorkspaceCard"
 
-type TWorkspacesInfo = Readonly<{
-  workspaceCards: TWorkspace[]
-}>
+const PAGE_SIZE = 50
+const SORT_OPTIONS: readonly Readonly<{
+  sort: WorkspaceSort
+  direction: SortDirection
+  label: string
+}>[] = [
+  { sort: "lastUsed", direction: "desc", label: "Last used" },
+  { sort: "created", direction: "desc", label: "Newest" },
+  { sort: "name", direction: "asc", label: "Name" },
+  { sort: "provider", direction: "asc", label: "Provider" },
+]
 
 export function ListWorkspaces() {
   const navigate = useNavigate()
-  const workspaces = useWorkspaces() // TODO: add loading state for workspaces
-  const { workspaceCards } = useMemo<TWorkspacesInfo>(() => {
-    const empty: TWorkspacesInfo = { workspaceCards: [] }
-    if (!exists(workspaces)) {
-      return empty
-    }
+  const queryClient = useQueryClient()
+  const areWorkspacesLoaded = useStartupDataLoaded("workspaces")
+  const [sort, setSort] = useState<WorkspaceSort>("lastUsed")
+  const [filter, setFilter] = useState("")
+  const direction = SORT_OPTIONS.find((option) => option.sort === sort)?.direction ?? "desc"
+  const { data, fetchNextPage, hasNextPage, isFetchingNextPage } = useInfiniteQuery({
+    queryKey: [...QueryKeys.WORKSPACE_PAGES, sort, direction, filter],
+    queryFn: async ({ pageParam }) => {
+      return (
+        await client.queryWorkspaces({
+          sort,
+          direction,
+          filter: filter === "" ? null : filter,
+          provider: null,
+          cursor: pageParam ?? null,
+          limit: PAGE_SIZE,
+        })
+      ).unwrap()
+    },
+    getNextPageParam: (page) => page?.nextCursor ?? undefined,
+    keepPreviousData: true,
+  })
 
-    return workspaces.reduce<TWorkspacesInfo>((acc, workspace) => {
-      const { id } = workspace
-      if (!exists(id)) {
-        return acc
+  // The pages are cut from the backend's copy of the workspaces, so they're stale once it changed
+  useEffect(() => {
+    const unsubscribe = client.subscribe("state_patch", (patch) => {
+      if (patch.collection === "workspaces") {
+        queryClient.invalidateQueries(QueryKeys.WORKSPACE_PAGES)
       }
+    })
+
+    return () => {
+      unsubscribe.then((u) => u())
+    }
+  }, [queryClient])
 
-      acc.workspaceCards.push(workspace)
+  const workspaceCards = useMemo(
+    () =>
+      (data?.pages ?? [])
+        .flatMap((page) => (page?.items ?? []) as TWorkspace[])
+        .filter((workspace) => exists(workspace.id)),
+    [data]
+  )
+  const total = data?.pages[0]?.total
 
-      return acc
-    }, empty)
-  }, [workspaces])
+  // Until the first list comes in the backend doesn't know about any workspace
+  if (!areWorkspacesLoaded && !total) {
+    return <Spinner />
+  }
 
-  return workspaceCards.length === 0 ? (
-    <VStack>
-      <Text>No workspaces found. Click here to create one</Text>
-      <Button onClick={() => navigate(Routes.WORKSPACE_CREATE)}>Create Workspace</Button>
-    </VStack>
-  ) : (
+  if (total === 0 && filter === "") {
+    return (
+      <VStack>
+        <Text>No workspaces found. Click here to create one</Text>
+        <Button onClick={() => navigate(Routes.WORKSPACE_CREATE)}>Create Workspace</Button>
+      </VStack>
+    )
+  }
+
+  return (
     <VStack alignItems={"flex-start"} paddingBottom="6">
+      <HStack width="full">
+        <Input
+          placeholder="Filter workspaces"
+          value={filter}
+          onChange={(e) => setFilter(e.target.value)}
+        />
+        <Select
+          maxWidth="40"
+          value={sort}
+          onChange={(e) => setSort(e.target.value as WorkspaceSort)}>
+          {SORT_OPTIONS.map((option) => (
+            <option key={option.sort} value={option.sort}>
+              {option.label}
+            </option>
+          ))}
+        </Select>
+      </HStack>
+      {total === 0 && <Text>No workspaces match the filter</Text>}
       {workspaceCards.map((workspace) => (
         <WorkspaceCard key={workspace.id} workspaceID={workspace.id} />
       ))}
+      {hasNextPage && (
+        <Button
+          variant="ghost"
+          alignSelf="center"
+          isLoading={isFetchingNextPage}
+          onClick={() => fetchNextPage()}>
+          Load more
+        </Button>
+      )}
     </VStack>
   )
 }
@@ -532,6 +532,17 @@ function WorkspaceStatusBadge({
       )
     }
 
+    if (status === "Unreachable") {
+      return (
+        <>
+          <Box {...sharedProps} backgroundColor="gray.200" borderColor="gray.400" />
+          <Text {...sharedTextProps} color="gray.400">
+            Offline
+          </Text>
+        </>
+      )
+    }
+
     return (
       <>
         <Box {...sharedProps} backgroundColor="purple.200" borderColor="purple.400" zIndex="1" />
@@ -0,0 +1,84 @@
+import { StrictMode, useEffect, useState } from "react"
+import ReactDOM from "react-dom/client"
+import { ThemeProvider } from "@/Theme"
+import { SettingsProvider } from "@/contexts"
+import { Button, HStack, Stack, Text } from "@chakra-ui/react"
+import { client } from "@/client"
+import { StatusWidgetSnapshot, WidgetWorkspace } from "@/gen"
+
+ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(<Root />)
+
+function Root() {
+  return (
+    <StrictMode>
+      <SettingsProvider>
+        <ThemeProvider>
+          <StatusWidget />
+        </ThemeProvider>
+      </SettingsProvider>
+    </StrictMode>
+  )
+}
+
+function StatusWidget() {
+  const [snapshot, setSnapshot] = useState<StatusWidgetSnapshot | null>(null)
+
+  useEffect(() => {
+    client.getStatusWidgetSnapshot().then(setSnapshot)
+
+    const unsubscribe = client.subscribe("status_widget_snapshot", setSnapshot)
+
+    return () => {
+      unsubscribe.then((u) => u())
+    }
+  }, [])
+
+  if (snapshot === null) {
+    return <Text padding="2">Waiting for backend...</Text>
+  }
+  if (snapshot.workspaces.length === 0) {
+    return (
+      <Text padding="2" fontSize="sm" color="gray.500">
+        No running workspaces
+      </Text>
+    )
+  }
+
+  return (
+    <Stack padding="2" spacing="2" width="100vw" height="100vh" overflowY="auto">
+      {snapshot.workspaces.map((workspace) => (
+        <WorkspaceRow key={workspace.id} workspace={workspace} />
+      ))}
+    </Stack>
+  )
+}
+
+function WorkspaceRow({ workspace }: Readonly<{ workspace: WidgetWorkspace }>) {
+  const { id, state, build } = workspace
+  // Stopping is disabled while building, the build would bring it back up right away
+  const isBuilding = build !== null
+
+  return (
+    <Stack spacing="0">
+      <HStack justifyContent="space-between">
+        <Text fontSize="sm" fontWeight="semibold" noOfLines={1}>
+          {id}
+        </Text>
+        <HStack spacing="1">
+          <Button size="xs" onClick={() => client.openWidgetWorkspace(id)}>
+            Open
+          </Button>
+          <Button
+            size="xs"
+            isDisabled={isBuilding || state !== "Running"}
+            onClick={() => client.stopWidgetWorkspace(id)}>
+            Stop
+          </Button>
+        </HStack>
+      </HStack>
+      <Text fontSize="xs" color="gray.500" noOfLines={1}>
+        {isBuilding ? (build.message ?? `Running ${build.kind}...`) : state}
+      </Text>
+    </Stack>
+  )
+}
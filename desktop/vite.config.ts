@@ -36,6 +36,11 @@ export default defineConfig({
       input: {
         main: resolve(__dirname, "index.html"),
         updateWindow: resolve(__dirname, "update-window/index.html"),
+        debugConsole: resolve(__dirname, "debug-console/index.html"),
+        shutdownWindow: resolve(__dirname, "shutdown-window/index.html"),
+        logWindow: resolve(__dirname, "log-window/index.html"),
+        statusWidget: resolve(__dirname, "status-widget/index.html"),
+        settingsWindow: resolve(__dirname, "settings-window/index.html"),
       },
     },
   },
@@ -169,6 +169,10 @@ module.exports = {
           type: "doc",
           id: "other-topics/telemetry",
         },
+        {
+          type: "doc",
+          id: "other-topics/certificate-pinning",
+        },
         {
           type: "category",
           label: "Advanced guides",